@@ -689,6 +689,22 @@ export default function ChatPage() {
       speculativeResults
     );
     
+    // Contexto web limpo de instruções suspeitas, delimitado e dentro da fração do contexto do modelo
+    let guardedKnowledgeBase = knowledgeBaseContext;
+    if (knowledgeBaseContext) {
+      try {
+        const { invoke } = await import('@tauri-apps/api/core');
+        const guarded = await invoke<{ context: string; flagged_sources: number }>('guard_web_context', {
+          model: selectedModel,
+          sources: [{ title: 'Base de conhecimento', url: '', content: '', markdown: knowledgeBaseContext }],
+          sessionId: currentSessionId,
+        });
+        guardedKnowledgeBase = guarded.context;
+      } catch (error) {
+        chatLog.warn(`⚠️ Failed to guard web context: ${error}`);
+      }
+    }
+    
     // Passo 5: Formular resposta
     chatLog.info('\nStep 5: Formulating Response');
     // Removido: setThinkingStep e setProcessSteps - agora usando mensagens thinking agrupadas
@@ -714,7 +730,7 @@ export default function ChatPage() {
     
     if (knowledgeBaseContext && knowledgeBaseContext.length > 100) {
        const strictPrompt = DEEP_RESEARCH_PROMPTS.STRICT_GENERATION
-         .replace('{{knowledgeBase}}', knowledgeBaseContext)
+         .replace('{{knowledgeBase}}', guardedKnowledgeBase)
          .replace('{{validationReport}}', validationReport || 'Nenhuma validação disponível.')
          .replace('{{userQuery}}', content);
          
@@ -776,7 +792,7 @@ Use esta data exata para referências temporais.`;
    - Bullets apenas para listar dados brutos (números, estatísticas).
 
 ## CONTEXTO WEB RECUPERADO
-${knowledgeBaseContext}
+${guardedKnowledgeBase}
 
 ---
 `;
@@ -806,7 +822,7 @@ Ao responder sobre fatos atuais ou notícias, inicie mencionando explicitamente
     
     let finalUserContent = content;
     if (knowledgeBaseContext && knowledgeBaseContext.length > 100) {
-      finalUserContent = `[KNOWLEDGE BASE - ÚNICA FONTE DE VERDADE]\n${knowledgeBaseContext}\n[/KNOWLEDGE BASE]\n\nResponda a pergunta usando APENAS os dados acima. Se a informação não estiver na Knowledge Base, diga "Não encontrei essa informação nas fontes consultadas."\n\nPergunta: ${content}`;
+      finalUserContent = `[KNOWLEDGE BASE - ÚNICA FONTE DE VERDADE]\n${guardedKnowledgeBase}\n[/KNOWLEDGE BASE]\n\nResponda a pergunta usando APENAS os dados acima. Se a informação não estiver na Knowledge Base, diga "Não encontrei essa informação nas fontes consultadas."\n\nPergunta: ${content}`;
     }
     
     chatLog.info(`Final User Content Length: ${finalUserContent.length} chars`);
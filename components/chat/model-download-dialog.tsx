@@ -84,7 +84,7 @@ export function ModelDownloadDialog({ open, onOpenChange, onSuccess }: ModelDown
     let unlistenFn: (() => void) | null = null;
 
     const setupListener = async () => {
-      const unlisten = await listen<string>('download-progress', (event) => {
+      const unlisten = await listen<DownloadInfo | string>('download-progress', (event) => {
         // Só processar se estiver fazendo download (usar ref para evitar stale closure)
         if (!isDownloadingRef.current) return;
 
@@ -93,12 +93,13 @@ export function ModelDownloadDialog({ open, onOpenChange, onSuccess }: ModelDown
         // Tentar parsear como JSON estruturado
         let info: DownloadInfo | null = null;
         try {
-          info = JSON.parse(payload) as DownloadInfo;
+          // v1 do evento é um objeto; versões antigas enviavam uma string JSON
+          info = (typeof payload === 'string' ? JSON.parse(payload) : payload) as DownloadInfo;
         } catch {
           // Fallback: tratar como string raw
           info = {
             status: "downloading",
-            raw: payload,
+            raw: String(payload),
           };
         }
         
@@ -291,7 +292,7 @@ export function ModelDownloadDialog({ open, onOpenChange, onSuccess }: ModelDown
         setLocalInstallProgress(30);
         
         // Instalar diretamente do caminho (sem precisar copiar para temp primeiro)
-        const installedModelName = await invoke<string>('install_gguf_model', {
+        const { model_name: installedModelName } = await invoke<{ model_name: string }>('install_gguf_model', {
           filePath: selectedPath,
           modelName: modelNameToUse || undefined,
         });
@@ -38,18 +38,19 @@ export function ModelSelector({ recommendation, onComplete }: ModelSelectorProps
   }, [selectedModelId]);
 
   useEffect(() => {
-    const unlisten = listen<string>('download-progress', (event) => {
+    const unlisten = listen<DownloadInfo | string>('download-progress', (event) => {
       const payload = event.payload;
       
       // Tentar parsear como JSON estruturado
       let info: DownloadInfo | null = null;
       try {
-        info = JSON.parse(payload) as DownloadInfo;
+        // v1 do evento é um objeto; versões antigas enviavam uma string JSON
+        info = (typeof payload === 'string' ? JSON.parse(payload) : payload) as DownloadInfo;
       } catch {
         // Fallback: tratar como string raw
         info = {
           status: "downloading",
-          raw: payload,
+          raw: String(payload),
         };
       }
       
@@ -23,15 +23,15 @@ export function useAutoLabelingModel() {
 
       setIsDownloading(true);
       // Listen for progress
-      const unlisten = await listen<string>('download-progress', (event) => {
-        const payload = typeof event.payload === 'string' ? event.payload : String(event.payload);
+      const unlisten = await listen<unknown>('download-progress', (event) => {
+        // v1 do evento é um objeto; versões antigas enviavam uma string JSON
+        const payload = typeof event.payload === 'string' ? event.payload : JSON.stringify(event.payload);
         
         // Try to parse as JSON, if fails use as plain text
         try {
             const data = JSON.parse(payload);
-            if (data.total && data.completed) {
-                const percent = Math.round((data.completed / data.total) * 100);
-                setProgress(`Baixando modelo de IA (${percent}%)`);
+            if (typeof data.percent === 'number') {
+                setProgress(`Baixando modelo de IA (${data.percent}%)`);
             } else if (data.status) {
                 setProgress(data.status);
             } else {
@@ -16,6 +16,7 @@ export interface SessionSummary {
 /** Resultado de carregamento paginado do backend */
 interface PaginatedHistoryResult {
   messages: Array<{
+    id?: number;
     role: string;
     content: string;
     metadata?: unknown;
@@ -129,12 +130,14 @@ export function useChatStorage() {
       // Se loadAll = true, usa o comando antigo que carrega tudo
       if (options?.loadAll) {
         const rawMessages = await invoke<Array<{
+          id?: number;
           role: string;
           content: string;
           metadata?: unknown;
         }>>('load_chat_history', { id });
         
         return rawMessages.map(msg => ({
+          id: msg.id,
           role: (msg.role === 'user' || msg.role === 'assistant' || msg.role === 'system') 
             ? msg.role as 'user' | 'assistant' | 'system'
             : 'user' as const,
@@ -160,6 +163,7 @@ export function useChatStorage() {
       
       // Converter para tipo Message
       return result.messages.map(msg => ({
+        id: msg.id,
         role: (msg.role === 'user' || msg.role === 'assistant' || msg.role === 'system') 
           ? msg.role as 'user' | 'assistant' | 'system'
           : 'user' as const,
@@ -172,12 +176,14 @@ export function useChatStorage() {
       // Fallback: tentar carregar tudo com o comando antigo
       try {
         const rawMessages = await invoke<Array<{
+          id?: number;
           role: string;
           content: string;
           metadata?: unknown;
         }>>('load_chat_history', { id });
         
         return rawMessages.map(msg => ({
+          id: msg.id,
           role: (msg.role === 'user' || msg.role === 'assistant' || msg.role === 'system') 
             ? msg.role as 'user' | 'assistant' | 'system'
             : 'user' as const,
@@ -224,6 +230,7 @@ export function useChatStorage() {
       
       // Converter para tipo Message
       return result.messages.map(msg => ({
+        id: msg.id,
         role: (msg.role === 'user' || msg.role === 'assistant' || msg.role === 'system') 
           ? msg.role as 'user' | 'assistant' | 'system'
           : 'user' as const,
@@ -385,18 +392,6 @@ export function useChatStorage() {
     if (existingSession && existingTitle && existingTitle !== "Nova Conversa") {
       const title = currentTitle || existingTitle;
       
-      // Extract memory context from metadata keywords
-      const memoryContext: string[] = [];
-      messages.forEach(msg => {
-        if (msg.role === 'assistant' && msg.metadata?.keywords) {
-          const keywords = Array.isArray(msg.metadata.keywords) 
-            ? msg.metadata.keywords 
-            : [];
-          memoryContext.push(...keywords.filter(k => typeof k === 'string'));
-        }
-      });
-      const uniqueMemoryContext = Array.from(new Set(memoryContext));
-
       const platform = typeof navigator !== 'undefined' 
         ? navigator.platform || navigator.userAgent.split(' ')[0] || 'Unknown'
         : 'Unknown';
@@ -406,8 +401,7 @@ export function useChatStorage() {
           id, 
           title, 
           messages,
-          platform: platform,
-          memory_context: uniqueMemoryContext
+          platform: platform
         });
         await loadSessions();
       } catch (error) {
@@ -450,19 +444,6 @@ export function useChatStorage() {
       }
     }
 
-    // Extract memory context from metadata keywords
-    const memoryContext: string[] = [];
-    messages.forEach(msg => {
-      if (msg.role === 'assistant' && msg.metadata?.keywords) {
-        const keywords = Array.isArray(msg.metadata.keywords) 
-          ? msg.metadata.keywords 
-          : [];
-        memoryContext.push(...keywords.filter(k => typeof k === 'string'));
-      }
-    });
-    // Deduplicate
-    const uniqueMemoryContext = Array.from(new Set(memoryContext));
-
     // Detect platform
     const platform = typeof navigator !== 'undefined' 
       ? navigator.platform || navigator.userAgent.split(' ')[0] || 'Unknown'
@@ -475,8 +456,7 @@ export function useChatStorage() {
           id, 
           title, 
           messages,
-          platform: platform,
-          memory_context: uniqueMemoryContext
+          platform: platform
         });
         await loadSessions();
       } catch (error) {
@@ -497,8 +477,7 @@ export function useChatStorage() {
               id, 
               title: newTitle, 
               messages,
-              platform: platform,
-              memory_context: uniqueMemoryContext
+              platform: platform
             });
             await loadSessions();
           } catch (e) {
@@ -527,8 +506,7 @@ export function useChatStorage() {
             id,
             title: fallbackTitle,
             messages,
-            platform: platform,
-            memory_context: uniqueMemoryContext
+            platform: platform
           }).then(() => loadSessions()).catch(e => console.error("Failed to save session with fallback:", e));
         });
     }
@@ -29,6 +29,8 @@ export interface ThinkingMessageMetadata {
 }
 
 export interface Message {
+  /** ID da mensagem no SQLite (ausente até a mensagem ser gravada) */
+  id?: number;
   role: 'user' | 'assistant' | 'system';
   content: string;
   metadata?: any | ThinkingMessageMetadata;
@@ -193,12 +195,14 @@ export function detectToolCalls(content: string, availableTools: any[]): ToolCal
 }
 
 // Helper to execute a tool call
-export async function executeToolCall(toolCall: ToolCall): Promise<string> {
+// (com sessionId, ferramentas de rede são barradas em sessões offline)
+export async function executeToolCall(toolCall: ToolCall, sessionId?: string): Promise<string> {
   try {
     const result = await invoke<any>('call_mcp_tool', {
       serverName: toolCall.server_name,
       toolName: toolCall.tool_name,
       arguments: toolCall.arguments,
+      sessionId: sessionId ?? null,
     });
     
     // Format result for display
@@ -5,6 +5,7 @@ export interface SourceCategory {
   name: string;
   base_sites: string[];
   enabled: boolean;
+  weight?: number;
 }
 
 export interface SourcesConfig {
@@ -210,6 +210,7 @@ class WebSearchService {
             name: cat.name,
             base_sites: cat.baseSites,
             enabled: cat.enabled,
+            weight: cat.weight,
           })),
           user_custom_sites: searchConfig.userCustomSites,
           excluded_domains: searchConfig.excludedDomains,
@@ -287,6 +288,7 @@ class WebSearchService {
           name: cat.name,
           base_sites: cat.baseSites,
           enabled: cat.enabled,
+          weight: cat.weight,
         })),
         user_custom_sites: searchConfig.userCustomSites,
         excluded_domains: searchConfig.excludedDomains,
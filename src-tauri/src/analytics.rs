@@ -0,0 +1,186 @@
+//! Estatísticas de uso locais (nada sai da máquina)
+//!
+//! Agrega do SQLite as mensagens por dia, o uso e os tokens por modelo (de `metadata.usage`,
+//! gravado pelo `chat_stream`), as tags mais usadas e a taxa de sucesso das tasks agendadas.
+
+use crate::db::Database;
+use chrono::{DateTime, Duration, NaiveDate, Utc};
+use serde::Serialize;
+use tauri::AppHandle;
+
+/// Período padrão do painel
+pub const DEFAULT_RANGE: &str = "30d";
+const TOP_TAGS: usize = 10;
+
+/// Mensagens de um dia (UTC)
+#[derive(Serialize, Clone, Debug, PartialEq)]
+pub struct DailyMessages {
+    pub date: String,
+    pub user: u64,
+    pub assistant: u64,
+}
+
+/// Respostas e tokens de um modelo
+#[derive(Serialize, Clone, Debug, Default, PartialEq)]
+pub struct ModelUsage {
+    pub model: String,
+    pub responses: u64,
+    pub prompt_tokens: u64,
+    pub completion_tokens: u64,
+    /// Tempo médio até o fim da resposta
+    pub avg_latency_ms: Option<f64>,
+    /// Tempo médio até o primeiro token
+    pub avg_first_token_ms: Option<f64>,
+}
+
+#[derive(Serialize, Clone, Debug, PartialEq)]
+pub struct TagCount {
+    pub tag: String,
+    pub sessions: u64,
+}
+
+/// Execuções das tasks agendadas no período
+#[derive(Serialize, Clone, Debug, Default, PartialEq)]
+pub struct TaskRunStats {
+    pub total: u64,
+    pub succeeded: u64,
+    pub failed: u64,
+    /// None sem execuções
+    pub success_rate: Option<f64>,
+}
+
+/// Resposta de `get_usage_analytics`
+#[derive(Serialize, Clone, Debug)]
+pub struct UsageAnalytics {
+    pub range: String,
+    /// Início do período (None em "all")
+    pub since: Option<DateTime<Utc>>,
+    pub total_messages: u64,
+    pub messages_per_day: Vec<DailyMessages>,
+    /// Ordenado do mais usado para o menos usado
+    pub models: Vec<ModelUsage>,
+    pub prompt_tokens: u64,
+    pub completion_tokens: u64,
+    pub avg_latency_ms: Option<f64>,
+    pub avg_first_token_ms: Option<f64>,
+    pub top_tags: Vec<TagCount>,
+    pub tasks: TaskRunStats,
+}
+
+/// Início do período: "24h", "7d", "30d", "12w"... ou "all" (None)
+pub fn range_start(range: &str, now: DateTime<Utc>) -> Result<Option<DateTime<Utc>>, String> {
+    let range = range.trim().to_lowercase();
+    if range == "all" {
+        return Ok(None);
+    }
+    let invalid = || format!("Período inválido: '{}' (use 24h, 7d, 30d, 90d, 365d ou all)", range);
+    let unit = range.chars().last().ok_or_else(invalid)?;
+    let amount: i64 = range[..range.len() - unit.len_utf8()].parse().map_err(|_| invalid())?;
+    if amount <= 0 {
+        return Err(invalid());
+    }
+    let span = match unit {
+        'h' => Duration::hours(amount),
+        'd' => Duration::days(amount),
+        'w' => Duration::weeks(amount),
+        _ => return Err(invalid()),
+    };
+    Ok(Some(now - span))
+}
+
+/// Completa com zero os dias sem mensagens, do início do período (ou do primeiro dia com
+/// mensagens) até hoje
+pub fn fill_days(rows: Vec<DailyMessages>, since: Option<DateTime<Utc>>, now: DateTime<Utc>) -> Vec<DailyMessages> {
+    let parse = |date: &str| NaiveDate::parse_from_str(date, "%Y-%m-%d").ok();
+    let first = since
+        .map(|s| s.date_naive())
+        .or_else(|| rows.iter().filter_map(|r| parse(&r.date)).min());
+    let Some(mut day) = first else {
+        return rows;
+    };
+
+    let mut filled = Vec::new();
+    while day <= now.date_naive() {
+        let date = day.format("%Y-%m-%d").to_string();
+        let row = rows
+            .iter()
+            .find(|r| r.date == date)
+            .cloned()
+            .unwrap_or(DailyMessages { date, user: 0, assistant: 0 });
+        filled.push(row);
+        day += Duration::days(1);
+    }
+    filled
+}
+
+/// Média de `value` ponderada pelas respostas de cada modelo
+fn weighted_average(models: &[ModelUsage], value: impl Fn(&ModelUsage) -> Option<f64>) -> Option<f64> {
+    let (sum, weight) = models
+        .iter()
+        .filter_map(|m| value(m).map(|v| (v * m.responses as f64, m.responses as f64)))
+        .fold((0.0, 0.0), |(s, w), (v, r)| (s + v, w + r));
+    (weight > 0.0).then(|| sum / weight)
+}
+
+pub fn collect(app: &AppHandle, range: &str) -> Result<UsageAnalytics, String> {
+    let now = Utc::now();
+    let since = range_start(range, now)?;
+    let since_key = since.map(|s| s.to_rfc3339()).unwrap_or_default();
+    let db = Database::new(app).map_err(|e| format!("Failed to open database: {}", e))?;
+
+    let daily = db
+        .count_messages_per_day(&since_key)
+        .map_err(|e| format!("Failed to load message counts: {}", e))?;
+    let models = db
+        .model_usage(&since_key)
+        .map_err(|e| format!("Failed to load model usage: {}", e))?;
+    let top_tags = db
+        .top_session_tags(&since_key, TOP_TAGS)
+        .map_err(|e| format!("Failed to load tags: {}", e))?;
+    let tasks = db
+        .task_run_stats(&since_key)
+        .map_err(|e| format!("Failed to load task runs: {}", e))?;
+
+    Ok(UsageAnalytics {
+        range: range.trim().to_lowercase(),
+        since,
+        total_messages: daily.iter().map(|d| d.user + d.assistant).sum(),
+        messages_per_day: fill_days(daily, since, now),
+        prompt_tokens: models.iter().map(|m| m.prompt_tokens).sum(),
+        completion_tokens: models.iter().map(|m| m.completion_tokens).sum(),
+        avg_latency_ms: weighted_average(&models, |m| m.avg_latency_ms),
+        avg_first_token_ms: weighted_average(&models, |m| m.avg_first_token_ms),
+        models,
+        top_tags,
+        tasks,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_range_and_fill_days() {
+        let now = DateTime::parse_from_rfc3339("2024-03-10T12:00:00Z").unwrap().with_timezone(&Utc);
+        assert_eq!(range_start("all", now).unwrap(), None);
+        assert_eq!(range_start("7d", now).unwrap().unwrap().to_rfc3339(), "2024-03-03T12:00:00+00:00");
+        assert_eq!(range_start("24H", now).unwrap().unwrap().to_rfc3339(), "2024-03-09T12:00:00+00:00");
+        assert!(range_start("0d", now).is_err());
+        assert!(range_start("d", now).is_err());
+        assert!(range_start("7y", now).is_err());
+
+        let rows = vec![DailyMessages { date: "2024-03-08".to_string(), user: 2, assistant: 2 }];
+        let filled = fill_days(rows.clone(), None, now);
+        assert_eq!(filled.iter().map(|d| d.date.as_str()).collect::<Vec<_>>(), ["2024-03-08", "2024-03-09", "2024-03-10"]);
+        assert_eq!((filled[0].user, filled[1].user), (2, 0));
+        assert_eq!(fill_days(rows, range_start("7d", now).unwrap(), now).len(), 8);
+
+        let models = vec![
+            ModelUsage { responses: 3, avg_latency_ms: Some(100.0), ..ModelUsage::default() },
+            ModelUsage { responses: 1, avg_latency_ms: Some(500.0), ..ModelUsage::default() },
+            ModelUsage { responses: 5, avg_latency_ms: None, ..ModelUsage::default() },
+        ];
+        assert_eq!(weighted_average(&models, |m| m.avg_latency_ms), Some(200.0));
+    }
+}
@@ -0,0 +1,112 @@
+//! Tokens de acesso com escopo para o servidor de API local.
+//!
+//! O servidor embutido (compatível com OpenAI) ainda não existe; este módulo
+//! cuida da geração e do armazenamento (apenas o hash) dos tokens.
+
+use crate::db::{ApiToken, Database};
+use chrono::Utc;
+use rand::distributions::Alphanumeric;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+const TOKEN_PREFIX: &str = "ohk_";
+const TOKEN_RANDOM_LEN: usize = 40;
+
+/// Escopo de um token de API
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ApiTokenScope {
+    /// Apenas enviar mensagens de chat
+    ChatOnly,
+    /// Apenas leitura do histórico
+    ReadHistory,
+    /// Acesso total
+    Admin,
+}
+
+impl ApiTokenScope {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ApiTokenScope::ChatOnly => "chat_only",
+            ApiTokenScope::ReadHistory => "read_history",
+            ApiTokenScope::Admin => "admin",
+        }
+    }
+}
+
+impl std::str::FromStr for ApiTokenScope {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "chat_only" => Ok(ApiTokenScope::ChatOnly),
+            "read_history" => Ok(ApiTokenScope::ReadHistory),
+            "admin" => Ok(ApiTokenScope::Admin),
+            other => Err(format!("Unknown API token scope: {}", other)),
+        }
+    }
+}
+
+/// Token recém-criado: o valor em texto puro só é retornado nesse momento
+#[derive(Debug, Clone, Serialize)]
+pub struct CreatedApiToken {
+    pub token: String,
+    pub info: ApiToken,
+}
+
+/// Gera um novo token aleatório (`ohk_...`)
+pub fn generate_token() -> String {
+    let random: String = rand::thread_rng()
+        .sample_iter(&Alphanumeric)
+        .take(TOKEN_RANDOM_LEN)
+        .map(char::from)
+        .collect();
+    format!("{}{}", TOKEN_PREFIX, random)
+}
+
+/// Hash SHA-256 (hex) do token; apenas o hash é persistido
+pub fn hash_token(token: &str) -> String {
+    let digest = Sha256::digest(token.as_bytes());
+    digest.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// Cria e persiste um token com o escopo informado
+pub fn create_token(db: &Database, name: &str, scope: ApiTokenScope) -> Result<CreatedApiToken, String> {
+    let token = generate_token();
+    let info = ApiToken {
+        id: uuid::Uuid::new_v4().to_string(),
+        name: name.to_string(),
+        scope,
+        token_prefix: token.chars().take(TOKEN_PREFIX.len() + 6).collect(),
+        created_at: Utc::now(),
+        last_used_at: None,
+        revoked_at: None,
+    };
+
+    db.insert_api_token(&info, &hash_token(&token))
+        .map_err(|e| format!("Failed to save API token: {}", e))?;
+    db.append_audit_log("api_token_created", &info.id, info.scope.as_str())
+        .map_err(|e| format!("Failed to write audit log: {}", e))?;
+
+    Ok(CreatedApiToken { token, info })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_scope_and_token_format() {
+        for scope in [ApiTokenScope::ChatOnly, ApiTokenScope::ReadHistory, ApiTokenScope::Admin] {
+            assert_eq!(scope.as_str().parse::<ApiTokenScope>(), Ok(scope));
+        }
+        assert!("root".parse::<ApiTokenScope>().is_err());
+
+        let token = generate_token();
+        assert!(token.starts_with(TOKEN_PREFIX));
+        assert_eq!(token.len(), TOKEN_PREFIX.len() + TOKEN_RANDOM_LEN);
+        assert_eq!(hash_token(&token), hash_token(&token));
+        assert_eq!(hash_token(&token).len(), 64);
+    }
+}
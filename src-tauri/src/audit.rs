@@ -0,0 +1,269 @@
+//! Auditoria opt-in de prompts, respostas, ferramentas e sites acessados (`audit` nas configurações)
+//!
+//! Com `audit.enabled`, cada chamada ao modelo, chamada de ferramenta e URL buscada entra na
+//! tabela `audit_log` (append-only: triggers bloqueiam UPDATE e DELETE) com o SHA-256 do
+//! conteúdo e um hash encadeado ao registro anterior. `export_audit_log` gera um JSONL do
+//! período e confere a cadeia, para mostrar que nada saiu da máquina além dos sites listados.
+
+use crate::db::Database;
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::io::Write;
+use std::path::PathBuf;
+use std::sync::mpsc::{self, Sender};
+use std::sync::{Mutex, OnceLock};
+use tauri::{AppHandle, Manager};
+
+static APP: OnceLock<AppHandle> = OnceLock::new();
+/// Fila do gravador em segundo plano (uma thread, na ordem de chegada)
+static WRITER: OnceLock<Mutex<Sender<PendingEntry>>> = OnceLock::new();
+
+/// Registro aguardando a thread de gravação
+struct PendingEntry {
+    kind: AuditKind,
+    actor: String,
+    detail: String,
+    content_hash: Option<String>,
+    payload: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct AuditSettings {
+    #[serde(default)]
+    pub enabled: bool,
+    /// Guarda o texto completo (sem isso, apenas o hash do conteúdo)
+    #[serde(default = "default_record_content")]
+    pub record_content: bool,
+}
+
+fn default_record_content() -> bool {
+    true
+}
+
+impl Default for AuditSettings {
+    fn default() -> Self {
+        Self { enabled: false, record_content: default_record_content() }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum AuditKind {
+    /// Mensagens enviadas ao modelo
+    ModelPrompt,
+    ModelResponse,
+    ToolCall,
+    ToolResult,
+    /// Página buscada (HTTP ou navegador headless)
+    WebFetch,
+    /// Consulta enviada a um motor de busca
+    WebSearch,
+}
+
+impl AuditKind {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            AuditKind::ModelPrompt => "model_prompt",
+            AuditKind::ModelResponse => "model_response",
+            AuditKind::ToolCall => "tool_call",
+            AuditKind::ToolResult => "tool_result",
+            AuditKind::WebFetch => "web_fetch",
+            AuditKind::WebSearch => "web_search",
+        }
+    }
+}
+
+/// Linha do `audit_log`
+#[derive(Serialize, Clone, Debug)]
+pub struct AuditEntry {
+    pub id: i64,
+    pub kind: String,
+    pub actor: String,
+    pub detail: String,
+    pub content_hash: Option<String>,
+    pub payload: Option<String>,
+    pub prev_hash: Option<String>,
+    /// None em registros anteriores ao encadeamento
+    pub entry_hash: Option<String>,
+    pub created_at: String,
+}
+
+/// Resultado de `export_audit_log`
+#[derive(Serialize, Clone, Debug)]
+pub struct AuditExport {
+    pub file: String,
+    pub entries: usize,
+    pub chain_valid: bool,
+    /// Primeiro registro cujo hash não confere
+    pub first_invalid_id: Option<i64>,
+}
+
+pub fn init(app_handle: &AppHandle) {
+    let _ = APP.set(app_handle.clone());
+    let (tx, rx) = mpsc::channel::<PendingEntry>();
+    let app = app_handle.clone();
+    let spawned = std::thread::Builder::new().name("audit-writer".to_string()).spawn(move || {
+        while let Ok(entry) = rx.recv() {
+            let result = Database::new(&app).and_then(|db| {
+                db.append_audit_entry(
+                    entry.kind.as_str(),
+                    &entry.actor,
+                    &entry.detail,
+                    entry.content_hash.as_deref(),
+                    entry.payload.as_deref(),
+                )
+            });
+            if let Err(e) = result {
+                log::warn!("[Audit] Falha ao registrar {}: {}", entry.kind.as_str(), e);
+            }
+        }
+    });
+    match spawned {
+        Ok(_) => {
+            let _ = WRITER.set(Mutex::new(tx));
+        }
+        Err(e) => log::warn!("[Audit] Falha ao iniciar o gravador: {}", e),
+    }
+}
+
+pub fn sha256_hex(content: &str) -> String {
+    Sha256::digest(content.as_bytes()).iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// Hash de um registro, encadeado ao do anterior
+pub fn entry_hash(
+    prev_hash: &str,
+    kind: &str,
+    actor: &str,
+    detail: &str,
+    content_hash: Option<&str>,
+    created_at: &str,
+) -> String {
+    sha256_hex(&[prev_hash, kind, actor, detail, content_hash.unwrap_or_default(), created_at].join("\n"))
+}
+
+/// Primeiro registro com hash adulterado ou fora da cadeia (registros antigos sem hash são ignorados).
+/// O `prev_hash` do primeiro registro encadeado é aceito como ponto de partida do período.
+pub fn verify_chain(entries: &[AuditEntry]) -> Option<i64> {
+    let mut previous: Option<&str> = None;
+    for entry in entries {
+        let Some(hash) = entry.entry_hash.as_deref() else { continue };
+        let prev = entry.prev_hash.as_deref().unwrap_or_default();
+        let expected = entry_hash(prev, &entry.kind, &entry.actor, &entry.detail, entry.content_hash.as_deref(), &entry.created_at);
+        if expected != hash || previous.is_some_and(|p| p != prev) {
+            return Some(entry.id);
+        }
+        previous = Some(hash);
+    }
+    None
+}
+
+/// Registra uma operação (não faz nada com a auditoria desligada). Não bloqueia: a gravação
+/// fica com a thread `audit-writer`, então pode ser chamada de código async.
+pub fn record(kind: AuditKind, actor: &str, detail: &str, content: Option<&str>) {
+    let Some(app_handle) = APP.get() else { return };
+    let settings = crate::settings::current(app_handle).audit;
+    if !settings.enabled {
+        return;
+    }
+    let entry = PendingEntry {
+        kind,
+        actor: actor.to_string(),
+        detail: detail.to_string(),
+        content_hash: content.map(sha256_hex),
+        payload: content.filter(|_| settings.record_content).map(str::to_string),
+    };
+    let sent = WRITER
+        .get()
+        .map(|writer| writer.lock().unwrap_or_else(|e| e.into_inner()).send(entry).is_ok());
+    if sent != Some(true) {
+        log::warn!("[Audit] Gravador indisponível, registro {} descartado", kind.as_str());
+    }
+}
+
+fn audit_dir(app_handle: &AppHandle) -> Result<PathBuf, String> {
+    let dir = app_handle
+        .path()
+        .app_data_dir()
+        .map_err(|e| format!("Failed to get app data dir: {}", e))?
+        .join("audit");
+    fs::create_dir_all(&dir).map_err(|e| format!("Failed to create audit dir: {}", e))?;
+    Ok(dir)
+}
+
+/// Exporta o período ("24h", "30d", "all"...) em JSONL, um registro por linha
+pub fn export(app_handle: &AppHandle, range: &str) -> Result<AuditExport, String> {
+    let now = Utc::now();
+    let since = crate::analytics::range_start(range, now)?;
+    let db = Database::new(app_handle).map_err(|e| format!("Failed to open database: {}", e))?;
+    let entries = db
+        .audit_log_since(&since.map(|s| s.to_rfc3339()).unwrap_or_default())
+        .map_err(|e| format!("Failed to read audit log: {}", e))?;
+    let first_invalid_id = verify_chain(&entries);
+
+    let path = audit_dir(app_handle)?.join(format!("audit-{}.jsonl", now.format("%Y%m%d-%H%M%S")));
+    let mut file = fs::File::create(&path).map_err(|e| format!("Failed to create audit export: {}", e))?;
+    for entry in &entries {
+        let line = serde_json::to_string(entry).map_err(|e| format!("Failed to serialize audit entry: {}", e))?;
+        writeln!(file, "{}", line).map_err(|e| format!("Failed to write audit export: {}", e))?;
+    }
+
+    let file_name = path.to_string_lossy().to_string();
+    if let Err(e) = db.append_audit_log("audit_export", "user", &format!("{} registros ({})", entries.len(), range)) {
+        log::warn!("[Audit] Falha ao registrar exportação: {}", e);
+    }
+    Ok(AuditExport {
+        file: file_name,
+        entries: entries.len(),
+        chain_valid: first_invalid_id.is_none(),
+        first_invalid_id,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn chain(details: &[&str]) -> Vec<AuditEntry> {
+        let mut prev = String::new();
+        details
+            .iter()
+            .enumerate()
+            .map(|(i, detail)| {
+                let created_at = format!("2024-01-01T00:00:0{}+00:00", i);
+                let hash = entry_hash(&prev, "web_fetch", "scraper", detail, None, &created_at);
+                let entry = AuditEntry {
+                    id: i as i64 + 1,
+                    kind: "web_fetch".to_string(),
+                    actor: "scraper".to_string(),
+                    detail: detail.to_string(),
+                    content_hash: None,
+                    payload: None,
+                    prev_hash: Some(prev.clone()),
+                    entry_hash: Some(hash.clone()),
+                    created_at,
+                };
+                prev = hash;
+                entry
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_verify_chain() {
+        let mut entries = chain(&["https://a.com", "https://b.com", "https://c.com"]);
+        assert_eq!(verify_chain(&entries), None);
+        // Um período pode começar no meio da cadeia
+        assert_eq!(verify_chain(&entries[1..]), None);
+
+        entries[1].detail = "https://outro.com".to_string();
+        assert_eq!(verify_chain(&entries), Some(2));
+
+        let mut entries = chain(&["https://a.com", "https://b.com", "https://c.com"]);
+        entries.remove(1);
+        assert_eq!(verify_chain(&entries), Some(3));
+        assert_eq!(sha256_hex("").len(), 64);
+    }
+}
@@ -0,0 +1,114 @@
+//! Inicialização com o sistema e modo em segundo plano
+//!
+//! `set_autostart` registra o app no login pelo `tauri-plugin-autostart` (chave Run no
+//! registro do Windows, LaunchAgent no macOS, `.desktop` em `~/.config/autostart` no Linux),
+//! sempre com o argumento `--autostart`. Iniciado assim com `start_minimized`, ou com
+//! `--background`, o app sobe só o scheduler e o tray: a janela principal (`create: false`
+//! no tauri.conf.json) só é criada quando pedida pelo tray.
+
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager, Runtime, WebviewWindow, WebviewWindowBuilder};
+use tauri_plugin_autostart::{MacosLauncher, ManagerExt};
+
+/// Argumento passado pelo registro de inicialização do sistema
+pub const AUTOSTART_ARG: &str = "--autostart";
+/// Inicia sem janela mesmo quando aberto manualmente
+pub const BACKGROUND_ARG: &str = "--background";
+const MAIN_WINDOW: &str = "main";
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct AutostartSettings {
+    #[serde(default)]
+    pub enabled: bool,
+    /// Ao iniciar com o sistema, fica só no tray (sem criar a janela)
+    #[serde(default = "default_start_minimized")]
+    pub start_minimized: bool,
+}
+
+fn default_start_minimized() -> bool {
+    true
+}
+
+impl Default for AutostartSettings {
+    fn default() -> Self {
+        Self { enabled: false, start_minimized: default_start_minimized() }
+    }
+}
+
+/// Estado devolvido por `set_autostart` (`enabled` é o que o sistema tem registrado)
+#[derive(Serialize, Clone, Debug)]
+pub struct AutostartStatus {
+    pub enabled: bool,
+    pub start_minimized: bool,
+}
+
+pub fn plugin<R: Runtime>() -> tauri::plugin::TauriPlugin<R> {
+    tauri_plugin_autostart::init(MacosLauncher::LaunchAgent, Some(vec![AUTOSTART_ARG]))
+}
+
+/// Se esta execução deve subir sem a janela principal
+pub fn starts_in_background<S: AsRef<str>>(args: &[S], settings: &AutostartSettings) -> bool {
+    let has = |flag: &str| args.iter().any(|arg| arg.as_ref() == flag);
+    has(BACKGROUND_ARG) || (has(AUTOSTART_ARG) && settings.start_minimized)
+}
+
+/// Liga ou desliga o registro no sistema conforme `settings` e devolve o estado efetivo
+pub fn apply(app_handle: &AppHandle, settings: &AutostartSettings) -> Result<AutostartStatus, String> {
+    let launcher = app_handle.autolaunch();
+    let registered = launcher
+        .is_enabled()
+        .map_err(|e| format!("Failed to read autostart state: {}", e))?;
+    if settings.enabled && !registered {
+        launcher.enable().map_err(|e| format!("Failed to enable autostart: {}", e))?;
+    } else if !settings.enabled && registered {
+        launcher.disable().map_err(|e| format!("Failed to disable autostart: {}", e))?;
+    }
+    let enabled = launcher
+        .is_enabled()
+        .map_err(|e| format!("Failed to read autostart state: {}", e))?;
+    Ok(AutostartStatus { enabled, start_minimized: settings.start_minimized })
+}
+
+/// Janela principal, criada a partir do tauri.conf.json na primeira vez que é pedida.
+/// Fechar a janela apenas a oculta; o app continua no tray.
+pub fn main_window(app_handle: &AppHandle) -> tauri::Result<WebviewWindow> {
+    if let Some(window) = app_handle.get_webview_window(MAIN_WINDOW) {
+        return Ok(window);
+    }
+    let config = app_handle
+        .config()
+        .app
+        .windows
+        .iter()
+        .find(|w| w.label == MAIN_WINDOW)
+        .cloned()
+        .unwrap_or_default();
+    let window = WebviewWindowBuilder::from_config(app_handle, &config)?.build()?;
+    log::info!("[Autostart] Janela principal criada");
+
+    let window_clone = window.clone();
+    window.on_window_event(move |event| {
+        if let tauri::WindowEvent::CloseRequested { api, .. } = event {
+            // Ocultar ao invés de fechar
+            let _ = window_clone.hide();
+            api.prevent_close();
+        }
+    });
+    Ok(window)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_starts_in_background() {
+        let minimized = AutostartSettings { enabled: true, start_minimized: true };
+        let windowed = AutostartSettings { enabled: true, start_minimized: false };
+
+        assert!(starts_in_background(&["app", AUTOSTART_ARG], &minimized));
+        assert!(!starts_in_background(&["app", AUTOSTART_ARG], &windowed));
+        assert!(!starts_in_background(&["app"], &minimized));
+        assert!(starts_in_background(&["app", BACKGROUND_ARG], &windowed));
+    }
+}
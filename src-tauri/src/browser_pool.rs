@@ -0,0 +1,137 @@
+use anyhow::Result;
+use headless_chrome::Browser;
+use serde::Serialize;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::{Duration, Instant};
+
+/// Abas abertas por instância antes de ela ser reciclada (o Chrome degrada com o uso)
+const MAX_TABS_PER_BROWSER: usize = 40;
+/// Idade máxima de uma instância antes de ser reciclada
+const MAX_BROWSER_AGE: Duration = Duration::from_secs(30 * 60);
+/// Instâncias no pool (limitadas pelo perfil de recursos)
+const MAX_POOL_SIZE: usize = 3;
+
+/// Pool de headless browsers. Instâncias são criadas sob demanda, distribuídas em
+/// rodízio e substituídas quando ficam velhas, atingem o limite de abas ou caem.
+static POOL: OnceLock<Mutex<Vec<Option<PooledBrowser>>>> = OnceLock::new();
+static CURSOR: AtomicUsize = AtomicUsize::new(0);
+
+struct PooledBrowser {
+    browser: Arc<Browser>,
+    created_at: Instant,
+    tabs_opened: usize,
+}
+
+impl PooledBrowser {
+    fn expired(&self) -> bool {
+        self.tabs_opened >= MAX_TABS_PER_BROWSER || self.created_at.elapsed() >= MAX_BROWSER_AGE
+    }
+
+    /// Chamada leve ao DevTools para confirmar que o processo ainda responde
+    fn healthy(&self) -> bool {
+        self.browser.get_version().is_ok()
+    }
+}
+
+/// Estado do pool exposto ao frontend
+#[derive(Serialize, Clone, Debug)]
+pub struct PoolStats {
+    pub size: usize,
+    pub active: usize,
+    pub tabs_opened: Vec<usize>,
+}
+
+/// Quantidade de instâncias conforme o perfil (uma por até 3 abas simultâneas)
+fn pool_size() -> usize {
+    let tabs = crate::resource_profile::current().max_concurrent_tabs.max(1);
+    tabs.div_ceil(3).clamp(1, MAX_POOL_SIZE)
+}
+
+fn slots() -> &'static Mutex<Vec<Option<PooledBrowser>>> {
+    POOL.get_or_init(|| Mutex::new(Vec::new()))
+}
+
+/// Obtém um browser para abrir uma nova aba (cria ou recicla a instância do slot)
+pub fn acquire() -> Result<Arc<Browser>> {
+    let mut slots = slots().lock().map_err(|e| anyhow::anyhow!("Browser pool poisoned: {}", e))?;
+    let size = pool_size();
+    slots.resize_with(size, || None);
+
+    let index = CURSOR.fetch_add(1, Ordering::Relaxed) % size;
+    let slot = &mut slots[index];
+
+    let replace = match slot {
+        Some(pooled) if pooled.expired() => {
+            log::info!("[BrowserPool] Reciclando instância {} ({} abas)", index, pooled.tabs_opened);
+            true
+        }
+        Some(pooled) if !pooled.healthy() => {
+            log::warn!("[BrowserPool] Instância {} não responde, substituindo", index);
+            true
+        }
+        Some(_) => false,
+        None => true,
+    };
+
+    if replace {
+        // Abas em andamento mantêm a instância antiga viva até terminarem (Arc)
+        *slot = None;
+        log::info!("[BrowserPool] Iniciando headless browser no slot {}", index);
+        *slot = Some(PooledBrowser {
+            browser: Arc::new(crate::web_scraper::create_browser()?),
+            created_at: Instant::now(),
+            tabs_opened: 0,
+        });
+    }
+
+    match slot {
+        Some(pooled) => {
+            pooled.tabs_opened += 1;
+            Ok(pooled.browser.clone())
+        }
+        None => Err(anyhow::anyhow!("Browser pool sem instância disponível")),
+    }
+}
+
+/// Remove do pool a instância (ex: conexão com o DevTools fechada); o próximo uso cria outra
+pub fn discard(browser: &Arc<Browser>) {
+    if let Ok(mut slots) = slots().lock() {
+        for slot in slots.iter_mut() {
+            if slot.as_ref().is_some_and(|p| Arc::ptr_eq(&p.browser, browser)) {
+                log::warn!("[BrowserPool] Descartando instância com falha");
+                *slot = None;
+            }
+        }
+    }
+}
+
+/// Encerra todas as instâncias (liberar memória ou aplicar nova configuração de proxy)
+pub fn clear() {
+    if let Ok(mut slots) = slots().lock() {
+        if slots.iter().any(|s| s.is_some()) {
+            log::info!("[BrowserPool] Encerrando instâncias do pool");
+        }
+        slots.clear();
+    }
+}
+
+pub fn stats() -> PoolStats {
+    let slots = slots().lock().map(|slots| {
+        slots.iter().flatten().map(|p| p.tabs_opened).collect::<Vec<_>>()
+    });
+    let tabs_opened = slots.unwrap_or_default();
+    PoolStats {
+        size: pool_size(),
+        active: tabs_opened.len(),
+        tabs_opened,
+    }
+}
+
+/// Erros que indicam que a instância morreu (vale trocar de browser e tentar de novo)
+pub fn is_connection_error(error: &anyhow::Error) -> bool {
+    let message = format!("{}", error);
+    message.contains("underlying connection is closed")
+        || message.contains("Falha ao criar aba")
+        || message.contains("connection closed")
+}
@@ -0,0 +1,413 @@
+//! Ferramentas embutidas (sem servidor MCP externo): arquivos e pesquisa na web
+//!
+//! Aparecem para o modelo como o servidor `builtin` em `get_all_mcp_tools` e são executadas
+//! por `call_mcp_tool`. Todo caminho precisa estar dentro de uma pasta aprovada nas configurações;
+//! as ferramentas de web reutilizam o web_scraper e devolvem as fontes para citação.
+
+use crate::McpTool;
+use serde::{Deserialize, Serialize};
+use std::path::{Component, Path, PathBuf};
+use tauri::AppHandle;
+
+/// Nome reservado do provedor nas listas de ferramentas
+pub const SERVER_NAME: &str = "builtin";
+/// Entradas devolvidas por `fs_list`
+const MAX_LIST_ENTRIES: usize = 500;
+
+/// Ferramentas embutidas expostas ao modelo
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct BuiltinToolsSettings {
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+    /// Pastas aprovadas pelo usuário; sem nenhuma, as ferramentas não são oferecidas
+    #[serde(default)]
+    pub roots: Vec<String>,
+    /// Libera `fs_write` (leitura e listagem ficam sempre disponíveis)
+    #[serde(default)]
+    pub allow_write: bool,
+    #[serde(default = "default_max_read_kb")]
+    pub max_read_kb: u64,
+    /// Oferece `web_search` e `fetch_page`
+    #[serde(default = "default_enabled")]
+    pub web_tools: bool,
+    /// Limite de caracteres do resultado das ferramentas de web (cabe no contexto do modelo)
+    #[serde(default = "default_max_result_chars")]
+    pub max_result_chars: usize,
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+fn default_max_read_kb() -> u64 {
+    256
+}
+
+fn default_max_result_chars() -> usize {
+    6000
+}
+
+impl Default for BuiltinToolsSettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            roots: Vec::new(),
+            allow_write: false,
+            max_read_kb: default_max_read_kb(),
+            web_tools: true,
+            max_result_chars: default_max_result_chars(),
+        }
+    }
+}
+
+impl BuiltinToolsSettings {
+    pub fn validate(&self, errors: &mut Vec<String>) {
+        for root in &self.roots {
+            if !Path::new(root).is_absolute() {
+                errors.push(format!("builtin_tools.roots deve conter caminhos absolutos: {}", root));
+            }
+        }
+        if !(1..=10_240).contains(&self.max_read_kb) {
+            errors.push("builtin_tools.max_read_kb deve estar entre 1 e 10240".to_string());
+        }
+        if !(500..=100_000).contains(&self.max_result_chars) {
+            errors.push("builtin_tools.max_result_chars deve estar entre 500 e 100000".to_string());
+        }
+    }
+
+    fn canonical_roots(&self) -> Vec<PathBuf> {
+        self.roots.iter().filter_map(|root| Path::new(root).canonicalize().ok()).collect()
+    }
+}
+
+/// Ferramentas disponíveis com as configurações atuais
+pub fn tools(settings: &BuiltinToolsSettings) -> Vec<McpTool> {
+    let mut tools = if settings.web_tools { web_tools() } else { Vec::new() };
+    tools.extend(fs_tools(settings));
+    tools
+}
+
+fn web_tools() -> Vec<McpTool> {
+    vec![
+        McpTool {
+            name: "web_search".to_string(),
+            description: "Pesquisa na web e retorna título, URL e trecho dos resultados numerados".to_string(),
+            input_schema: Some(serde_json::json!({
+                "type": "object",
+                "properties": {
+                    "query": { "type": "string" },
+                    "limit": { "type": "integer", "minimum": 1, "maximum": 10 }
+                },
+                "required": ["query"]
+            })),
+        },
+        McpTool {
+            name: "fetch_page".to_string(),
+            description: "Abre uma página e retorna o conteúdo principal em markdown".to_string(),
+            input_schema: Some(serde_json::json!({
+                "type": "object",
+                "properties": {
+                    "url": { "type": "string", "description": "URL http(s) completa" }
+                },
+                "required": ["url"]
+            })),
+        },
+    ]
+}
+
+fn fs_tools(settings: &BuiltinToolsSettings) -> Vec<McpTool> {
+    if !settings.enabled || settings.roots.is_empty() {
+        return Vec::new();
+    }
+    let roots = settings.roots.join(", ");
+    let mut tools = vec![
+        McpTool {
+            name: "fs_read".to_string(),
+            description: format!("Lê um arquivo de texto. Pastas permitidas: {}", roots),
+            input_schema: Some(serde_json::json!({
+                "type": "object",
+                "properties": {
+                    "path": { "type": "string", "description": "Caminho absoluto ou relativo à primeira pasta permitida" }
+                },
+                "required": ["path"]
+            })),
+        },
+        McpTool {
+            name: "fs_list".to_string(),
+            description: format!("Lista arquivos e pastas. Pastas permitidas: {}", roots),
+            input_schema: Some(serde_json::json!({
+                "type": "object",
+                "properties": {
+                    "path": { "type": "string", "description": "Pasta a listar (padrão: primeira pasta permitida)" },
+                    "recursive": { "type": "boolean" }
+                }
+            })),
+        },
+    ];
+    if settings.allow_write {
+        tools.push(McpTool {
+            name: "fs_write".to_string(),
+            description: format!("Cria ou sobrescreve um arquivo de texto. Pastas permitidas: {}", roots),
+            input_schema: Some(serde_json::json!({
+                "type": "object",
+                "properties": {
+                    "path": { "type": "string" },
+                    "content": { "type": "string" },
+                    "append": { "type": "boolean" }
+                },
+                "required": ["path", "content"]
+            })),
+        });
+    }
+    tools
+}
+
+/// Resolve o caminho pedido pelo modelo dentro das pastas aprovadas.
+/// Para arquivos novos (`must_exist = false`), a pasta pai precisa existir e estar dentro delas.
+pub fn resolve(roots: &[PathBuf], requested: &str, must_exist: bool) -> Result<PathBuf, String> {
+    let first_root = roots.first().ok_or("Nenhuma pasta aprovada para as ferramentas de arquivos")?;
+    let requested = Path::new(requested.trim());
+    if requested.components().any(|c| c == Component::ParentDir) {
+        return Err("Caminhos com '..' não são permitidos".to_string());
+    }
+    let path = if requested.is_absolute() { requested.to_path_buf() } else { first_root.join(requested) };
+
+    let resolved = if must_exist || path.exists() {
+        path.canonicalize().map_err(|e| format!("Failed to resolve path: {}", e))?
+    } else {
+        // `exists()` é falso para um link simbólico quebrado, que a escrita seguiria para fora
+        if std::fs::symlink_metadata(&path).is_ok() {
+            return Err(format!("Acesso negado: {} é um link simbólico quebrado", path.display()));
+        }
+        let parent = path.parent().ok_or("Caminho inválido")?
+            .canonicalize()
+            .map_err(|e| format!("Failed to resolve parent directory: {}", e))?;
+        parent.join(path.file_name().ok_or("Caminho inválido")?)
+    };
+
+    // Pastas comparadas já resolvidas (links simbólicos e `.` nas configurações)
+    let inside = roots
+        .iter()
+        .filter_map(|root| root.canonicalize().ok())
+        .any(|root| resolved.starts_with(root));
+    if inside {
+        Ok(resolved)
+    } else {
+        Err(format!("Acesso negado: {} está fora das pastas aprovadas", resolved.display()))
+    }
+}
+
+fn text_result(text: String) -> serde_json::Value {
+    serde_json::json!({ "content": [{ "type": "text", "text": text }] })
+}
+
+/// Fonte usada por uma ferramenta de web (mesmo formato lido por `citations::collect_sources`)
+#[derive(Serialize, Clone, Debug, PartialEq)]
+pub struct ToolSource {
+    pub title: String,
+    pub url: String,
+}
+
+/// Corta o texto em `max_chars` caracteres, no último fim de linha quando possível
+pub fn truncate_for_context(text: &str, max_chars: usize) -> String {
+    if text.chars().count() <= max_chars {
+        return text.to_string();
+    }
+    let cut: String = text.chars().take(max_chars).collect();
+    let cut = match cut.rfind('\n') {
+        Some(index) if index > max_chars / 2 => cut[..index].to_string(),
+        _ => cut,
+    };
+    format!("{}\n[... conteúdo truncado]", cut)
+}
+
+/// Resultado com as fontes numeradas no texto (para o modelo citar como [n]) e em `sources`
+/// (gravadas nos metadados da resposta para a exportação de citações)
+fn cited_result(body: String, sources: Vec<ToolSource>) -> serde_json::Value {
+    let references: Vec<String> = sources
+        .iter()
+        .enumerate()
+        .map(|(index, source)| format!("[{}] {} - {}", index + 1, source.title, source.url))
+        .collect();
+    let text = format!(
+        "{}\n\nFontes (cite como [n] na resposta):\n{}",
+        body,
+        references.join("\n")
+    );
+    serde_json::json!({
+        "content": [{ "type": "text", "text": text }],
+        "sources": sources,
+    })
+}
+
+async fn call_web(app_handle: &AppHandle, settings: &BuiltinToolsSettings, tool_name: &str, arguments: &serde_json::Value) -> Result<serde_json::Value, String> {
+    if !settings.web_tools {
+        return Err("Ferramentas de web desativadas".to_string());
+    }
+    match tool_name {
+        "web_search" => {
+            let query = string_arg(arguments, "query").ok_or("Parâmetro 'query' obrigatório")?.to_string();
+            let limit = arguments.get("limit").and_then(|v| v.as_u64()).unwrap_or(5).clamp(1, 10) as usize;
+            // Sem confirmação: com `redaction.require_confirmation` o modelo recebe o erro
+            let results = crate::search_web_metadata(app_handle.clone(), query.clone(), Some(limit), None, None, None, None).await?;
+            log::info!("[BuiltinTools] web_search '{}': {} resultados", query, results.len());
+
+            // Snippets também vêm da página: passam pela mesma limpeza do conteúdo extraído
+            let guard = crate::settings::current(app_handle).content_guard;
+            let body = results
+                .iter()
+                .enumerate()
+                .map(|(index, result)| {
+                    let snippet = crate::content_guard::sanitize(&result.snippet, &guard).text;
+                    format!("[{}] {}\n{}\n{}", index + 1, result.title, result.url, snippet)
+                })
+                .collect::<Vec<_>>()
+                .join("\n\n");
+            let sources = results
+                .into_iter()
+                .map(|result| ToolSource { title: result.title, url: result.url })
+                .collect();
+            Ok(cited_result(truncate_for_context(&body, settings.max_result_chars), sources))
+        }
+        "fetch_page" => {
+            let url = string_arg(arguments, "url").ok_or("Parâmetro 'url' obrigatório")?.to_string();
+            let page = crate::extract_url_content(app_handle.clone(), url, None, None).await?;
+            log::info!("[BuiltinTools] fetch_page {} ({} caracteres)", page.url, page.markdown.len());
+
+            let content = if page.markdown.trim().is_empty() { &page.content } else { &page.markdown };
+            let text = truncate_for_context(content, settings.max_result_chars);
+            let guard = crate::settings::current(app_handle).content_guard;
+            let body = if guard.enabled && guard.wrap_sources {
+                let source = crate::content_guard::SourceText { title: &page.title, url: &page.url, text: &text };
+                crate::content_guard::wrap_sources(&[source], &guard, settings.max_result_chars).0
+            } else {
+                format!("# {}\n\n{}", page.title, text)
+            };
+            Ok(cited_result(body, vec![ToolSource { title: page.title.clone(), url: page.url.clone() }]))
+        }
+        other => Err(format!("Ferramenta embutida desconhecida: {}", other)),
+    }
+}
+
+fn string_arg<'a>(arguments: &'a serde_json::Value, key: &str) -> Option<&'a str> {
+    arguments.get(key).and_then(|v| v.as_str())
+}
+
+/// Executa uma ferramenta embutida (resultado no formato do `tools/call` do MCP)
+pub async fn call(app_handle: &AppHandle, tool_name: &str, arguments: &serde_json::Value) -> Result<serde_json::Value, String> {
+    let settings = crate::settings::current(app_handle).builtin_tools;
+    match tool_name {
+        "web_search" | "fetch_page" => call_web(app_handle, &settings, tool_name, arguments).await,
+        _ => call_fs(&settings, tool_name, arguments),
+    }
+}
+
+fn call_fs(settings: &BuiltinToolsSettings, tool_name: &str, arguments: &serde_json::Value) -> Result<serde_json::Value, String> {
+    if !settings.enabled {
+        return Err("Ferramentas de arquivos desativadas".to_string());
+    }
+    let roots = settings.canonical_roots();
+
+    match tool_name {
+        "fs_read" => {
+            let path = resolve(&roots, string_arg(arguments, "path").ok_or("Parâmetro 'path' obrigatório")?, true)?;
+            let size = std::fs::metadata(&path)
+                .map_err(|e| format!("Failed to read file metadata: {}", e))?
+                .len();
+            if size > settings.max_read_kb * 1024 {
+                return Err(format!("Arquivo grande demais ({} KB, limite {} KB)", size / 1024, settings.max_read_kb));
+            }
+            let content = std::fs::read_to_string(&path)
+                .map_err(|e| format!("Failed to read file: {}", e))?;
+            log::info!("[BuiltinTools] fs_read {}", path.display());
+            Ok(text_result(content))
+        }
+        "fs_list" => {
+            let path = match string_arg(arguments, "path") {
+                Some(path) => resolve(&roots, path, true)?,
+                None => roots.first().cloned().ok_or("Nenhuma pasta aprovada para as ferramentas de arquivos")?,
+            };
+            let recursive = arguments.get("recursive").and_then(|v| v.as_bool()).unwrap_or(false);
+            let walker = walkdir::WalkDir::new(&path)
+                .min_depth(1)
+                .max_depth(if recursive { usize::MAX } else { 1 })
+                .sort_by_file_name();
+            let mut lines = Vec::new();
+            for entry in walker.into_iter().filter_map(|e| e.ok()).take(MAX_LIST_ENTRIES + 1) {
+                if lines.len() == MAX_LIST_ENTRIES {
+                    lines.push(format!("... (limite de {} entradas)", MAX_LIST_ENTRIES));
+                    break;
+                }
+                let relative = entry.path().strip_prefix(&path).unwrap_or(entry.path()).display().to_string();
+                if entry.file_type().is_dir() {
+                    lines.push(format!("{}/", relative));
+                } else {
+                    let size = entry.metadata().map(|m| m.len()).unwrap_or(0);
+                    lines.push(format!("{} ({} bytes)", relative, size));
+                }
+            }
+            log::info!("[BuiltinTools] fs_list {}", path.display());
+            Ok(text_result(lines.join("\n")))
+        }
+        "fs_write" => {
+            if !settings.allow_write {
+                return Err("Escrita de arquivos não permitida nas configurações".to_string());
+            }
+            let path = resolve(&roots, string_arg(arguments, "path").ok_or("Parâmetro 'path' obrigatório")?, false)?;
+            let content = string_arg(arguments, "content").ok_or("Parâmetro 'content' obrigatório")?;
+            let append = arguments.get("append").and_then(|v| v.as_bool()).unwrap_or(false);
+            if append {
+                use std::io::Write;
+                std::fs::OpenOptions::new()
+                    .create(true)
+                    .append(true)
+                    .open(&path)
+                    .and_then(|mut file| file.write_all(content.as_bytes()))
+                    .map_err(|e| format!("Failed to append to file: {}", e))?;
+            } else {
+                std::fs::write(&path, content)
+                    .map_err(|e| format!("Failed to write file: {}", e))?;
+            }
+            log::info!("[BuiltinTools] fs_write {} ({} bytes)", path.display(), content.len());
+            Ok(text_result(format!("{} bytes gravados em {}", content.len(), path.display())))
+        }
+        other => Err(format!("Ferramenta embutida desconhecida: {}", other)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resolve_stays_inside_roots() {
+        let root = std::env::temp_dir().join(format!("ollahub-fs-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(root.join("src")).unwrap();
+        std::fs::write(root.join("src/main.rs"), "fn main() {}").unwrap();
+        let roots = vec![root.canonicalize().unwrap()];
+
+        assert!(resolve(&roots, "src/main.rs", true).is_ok());
+        assert!(resolve(&roots, "src/novo.rs", false).is_ok());
+        assert!(resolve(&roots, "src/../../etc/passwd", true).is_err());
+        assert!(resolve(&roots, "/etc/hosts", true).is_err());
+        assert!(resolve(&roots, "inexistente/arquivo.txt", false).is_err());
+        assert!(resolve(&[], "src/main.rs", true).is_err());
+
+        // Link quebrado apontando para fora das pastas aprovadas
+        #[cfg(unix)]
+        {
+            std::os::unix::fs::symlink("/tmp/ollahub-fora-das-pastas.txt", root.join("src/link.txt")).unwrap();
+            assert!(resolve(&roots, "src/link.txt", false).is_err());
+        }
+
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn test_truncate_for_context() {
+        assert_eq!(truncate_for_context("curto", 100), "curto");
+        let text = format!("{}\n{}", "a".repeat(80), "b".repeat(80));
+        assert_eq!(truncate_for_context(&text, 100), format!("{}\n[... conteúdo truncado]", "a".repeat(80)));
+        assert!(truncate_for_context(&"c".repeat(300), 100).starts_with(&"c".repeat(100)));
+    }
+}
@@ -0,0 +1,722 @@
+use crate::api_tokens::ApiTokenScope;
+use serde::{Deserialize, Serialize};
+
+/// Permissão exigida por um comando (usada pela UI de configurações e por integrações externas)
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum Permission {
+    /// Lê conversas, mensagens ou fontes
+    ReadHistory,
+    /// Cria ou altera conversas e mensagens
+    WriteHistory,
+    /// Acessa a internet ou o servidor do Ollama
+    Network,
+    /// Lê ou grava arquivos fora do banco
+    FileSystem,
+    /// Inicia ou encerra processos externos
+    Process,
+    /// Altera configurações persistidas
+    Settings,
+    /// Apaga dados sem como desfazer
+    Destructive,
+}
+
+/// Parâmetro de um comando. `name` é a chave enviada pelo frontend (camelCase, como o Tauri espera)
+#[derive(Serialize, Clone, Copy, Debug)]
+pub struct ParamSpec {
+    pub name: &'static str,
+    /// Tipo no estilo TypeScript (`string`, `number`, `string[]`, ou o nome da struct)
+    #[serde(rename = "type")]
+    pub ty: &'static str,
+    pub optional: bool,
+}
+
+/// Recurso que precisa estar disponível ou habilitado para o comando funcionar
+#[derive(Serialize, Clone, Copy, Debug)]
+pub struct FeatureFlag {
+    pub id: &'static str,
+    pub description: &'static str,
+}
+
+#[derive(Serialize, Clone, Copy, Debug)]
+pub struct CommandSpec {
+    pub name: &'static str,
+    pub category: &'static str,
+    pub description: &'static str,
+    pub params: &'static [ParamSpec],
+    pub permissions: &'static [Permission],
+    /// Escopo mínimo de um token de API para chamar o comando
+    pub min_token_scope: ApiTokenScope,
+    pub feature_flags: &'static [&'static str],
+}
+
+/// Catálogo retornado por `list_capabilities`
+#[derive(Serialize, Clone, Debug)]
+pub struct CapabilityCatalog {
+    pub app_version: &'static str,
+    pub commands: &'static [CommandSpec],
+    pub feature_flags: &'static [FeatureFlag],
+}
+
+const fn req(name: &'static str, ty: &'static str) -> ParamSpec {
+    ParamSpec { name, ty, optional: false }
+}
+
+const fn opt(name: &'static str, ty: &'static str) -> ParamSpec {
+    ParamSpec { name, ty, optional: true }
+}
+
+const fn command(
+    name: &'static str,
+    category: &'static str,
+    description: &'static str,
+    params: &'static [ParamSpec],
+    permissions: &'static [Permission],
+    min_token_scope: ApiTokenScope,
+    feature_flags: &'static [&'static str],
+) -> CommandSpec {
+    CommandSpec { name, category, description, params, permissions, min_token_scope, feature_flags }
+}
+
+pub static FEATURE_FLAGS: &[FeatureFlag] = &[
+    FeatureFlag { id: "ollama", description: "Servidor do Ollama rodando" },
+    FeatureFlag { id: "headless_browser", description: "Chrome/Chromium disponível para o scraping" },
+    FeatureFlag { id: "embedding_model", description: "Modelo de embeddings baixado (download_embedding_model)" },
+    FeatureFlag { id: "mcp", description: "Servidor MCP configurado em mcp.json" },
+    FeatureFlag { id: "digest", description: "Resumo diário habilitado (settings.digest.enabled)" },
+];
+
+/// Registro central dos comandos Tauri. Todo comando novo em `generate_handler!` precisa
+/// de uma entrada aqui (o teste abaixo compara as duas listas).
+pub static COMMANDS: &[CommandSpec] = &[
+    command("chat_stream", "chat", "Envia mensagens ao modelo com resposta em streaming (eventos `chat-token`)",
+        &[opt("sessionId", "string"), req("messages", "Message[]"), opt("model", "string"), opt("systemPrompt", "string"), opt("enableRag", "boolean"), opt("options", "GenerationOptions"), opt("format", "json"), opt("offline", "boolean")],
+        &[Permission::WriteHistory, Permission::Network], ApiTokenScope::ChatOnly, &["ollama"]),
+    command("replay_message", "chat", "Repete a geração de uma resposta com o mesmo prompt, seed e opções para verificar reprodutibilidade",
+        &[req("messageId", "number"), opt("model", "string")],
+        &[Permission::ReadHistory, Permission::Network], ApiTokenScope::ReadHistory, &["ollama"]),
+    command("list_active_streams", "chat", "Respostas em geração no momento, uma por sessão",
+        &[],
+        &[], ApiTokenScope::ChatOnly, &[]),
+    command("check_ollama_installed", "ollama", "Verifica se o executável do Ollama está instalado",
+        &[],
+        &[Permission::Process], ApiTokenScope::Admin, &[]),
+    command("check_ollama_running", "ollama", "Verifica se o servidor do Ollama está respondendo",
+        &[],
+        &[Permission::Network], ApiTokenScope::Admin, &[]),
+    command("get_system_specs", "system", "Especificações da máquina (CPU, memória, GPUs)",
+        &[],
+        &[], ApiTokenScope::Admin, &[]),
+    command("get_operating_system", "system", "Retorna o sistema operacional atual",
+        &[],
+        &[], ApiTokenScope::Admin, &[]),
+    command("check_if_model_installed", "models", "Verifica se um modelo está instalado no Ollama",
+        &[req("name", "string")],
+        &[Permission::Process], ApiTokenScope::Admin, &[]),
+    command("pull_model", "models", "Baixa um modelo do registro do Ollama (progresso via eventos)",
+        &[req("name", "string")],
+        &[Permission::Network, Permission::Process], ApiTokenScope::Admin, &["ollama"]),
+    command("install_gguf_model", "models", "Instala um modelo GGUF a partir de um arquivo local",
+        &[req("filePath", "string"), opt("modelName", "string"), opt("importId", "string")],
+        &[Permission::FileSystem, Permission::Network], ApiTokenScope::Admin, &["ollama"]),
+    command("inspect_gguf_file", "models", "Lê os metadados de um arquivo GGUF (arquitetura, parâmetros, quantização, contexto)",
+        &[req("filePath", "string")],
+        &[Permission::FileSystem], ApiTokenScope::Admin, &[]),
+    command("export_model", "models", "Empacota um modelo do Ollama (manifest + blobs) para levar a outra máquina",
+        &[req("name", "string"), req("destPath", "string"), opt("exportId", "string")],
+        &[Permission::FileSystem], ApiTokenScope::Admin, &[]),
+    command("inspect_model_archive", "models", "Lê o índice de um pacote de modelo sem importar",
+        &[req("path", "string")],
+        &[Permission::FileSystem], ApiTokenScope::Admin, &[]),
+    command("import_model_archive", "models", "Importa um pacote de modelo para o diretório do Ollama",
+        &[req("path", "string"), opt("overwrite", "boolean"), opt("importId", "string")],
+        &[Permission::FileSystem], ApiTokenScope::Admin, &[]),
+    command("save_temp_file", "storage", "Salva um arquivo temporário e retorna o caminho",
+        &[req("data", "bytes"), req("extension", "string")],
+        &[Permission::FileSystem], ApiTokenScope::Admin, &[]),
+    command("open_gguf_file_dialog", "models", "Abre um dialog de seleção de arquivo GGUF usando dialog nativo do sistema",
+        &[],
+        &[Permission::FileSystem], ApiTokenScope::Admin, &[]),
+    command("start_ollama_server", "ollama", "Inicia o servidor do Ollama",
+        &[],
+        &[Permission::Process], ApiTokenScope::Admin, &[]),
+    command("stop_ollama_server", "ollama", "Encerra o servidor do Ollama iniciado pelo app (force: também os demais processos)",
+        &[opt("force", "boolean")],
+        &[Permission::Process], ApiTokenScope::Admin, &[]),
+    command("restart_ollama_server", "ollama", "Reinicia o servidor do Ollama com as variáveis de ambiente configuradas",
+        &[],
+        &[Permission::Process], ApiTokenScope::Admin, &[]),
+    command("get_ollama_env", "ollama", "Variáveis de ambiente do servidor do Ollama e instruções para serviços do sistema",
+        &[],
+        &[], ApiTokenScope::Admin, &[]),
+    command("configure_ollama_env", "ollama", "Salva as variáveis de ambiente do servidor do Ollama (opcionalmente reiniciando-o)",
+        &[req("vars", "Record<string, string>"), opt("restart", "boolean")],
+        &[Permission::Settings, Permission::Process], ApiTokenScope::Admin, &[]),
+    command("start_system_monitor", "system", "Liga o monitor com o intervalo padrão e o evento legado `system-stats`",
+        &[],
+        &[], ApiTokenScope::Admin, &[]),
+    command("configure_system_monitor", "system", "Liga, desliga ou ajusta o monitor que emite `system-stats-v2`",
+        &[opt("intervalMs", "number"), opt("enabledMetrics", "MonitorMetric[]")],
+        &[], ApiTokenScope::Admin, &[]),
+    command("get_gpu_stats", "system", "Obtém estatísticas detalhadas de uma GPU específica",
+        &[opt("gpuId", "string")],
+        &[Permission::Process], ApiTokenScope::Admin, &[]),
+    command("get_metrics_history", "system", "Histórico de CPU, RAM, VRAM e memória dos processos do Ollama",
+        &[opt("range", "string")],
+        &[], ApiTokenScope::Admin, &[]),
+    command("list_local_models", "models", "Lista os modelos instalados no Ollama",
+        &[],
+        &[Permission::Process], ApiTokenScope::Admin, &["ollama"]),
+    command("delete_model", "models", "Remove um modelo (modelos fixados exigem `force`)",
+        &[req("name", "string"), opt("force", "boolean")],
+        &[Permission::Process, Permission::Destructive], ApiTokenScope::Admin, &["ollama"]),
+    command("set_model_pinned", "models", "Fixa (ou desafixa) um modelo para protegê-lo de remoções acidentais",
+        &[req("name", "string"), req("pinned", "boolean")],
+        &[Permission::Settings], ApiTokenScope::Admin, &[]),
+    command("copy_model", "models", "Duplica um modelo (ollama cp) levando junto as configurações por modelo",
+        &[req("src", "string"), req("dst", "string")],
+        &[Permission::Process, Permission::Settings], ApiTokenScope::Admin, &["ollama"]),
+    command("rename_model", "models", "Renomeia um modelo (cópia + remoção do original)",
+        &[req("src", "string"), req("dst", "string")],
+        &[Permission::Process, Permission::Settings, Permission::Destructive], ApiTokenScope::Admin, &["ollama"]),
+    command("save_chat_session", "sessions", "Salva uma sessão de chat com suas mensagens",
+        &[req("id", "string"), req("title", "string"), req("messages", "Message[]"), opt("platform", "string"), opt("memoryContext", "string[]")],
+        &[Permission::WriteHistory], ApiTokenScope::Admin, &[]),
+    command("load_chat_sessions", "sessions", "Lista as sessões de chat (com filtros de tags, pasta e arquivamento)",
+        &[opt("filter", "SessionFilter")],
+        &[Permission::ReadHistory], ApiTokenScope::ReadHistory, &[]),
+    command("search_chat_sessions", "sessions", "Busca em todas as conversas",
+        &[req("query", "string"), opt("limit", "number"), opt("filter", "SessionFilter"), opt("hitsPerSession", "number")],
+        &[Permission::ReadHistory], ApiTokenScope::ReadHistory, &[]),
+    command("validate_search_query", "sessions", "Valida a sintaxe de busca avançada (AND/OR/NOT, NEAR, prefixo*, aspas)",
+        &[req("query", "string")],
+        &[], ApiTokenScope::ReadHistory, &[]),
+    command("search_in_session", "sessions", "Busca dentro de uma conversa",
+        &[req("sessionId", "string"), req("query", "string"), opt("limit", "number")],
+        &[Permission::ReadHistory], ApiTokenScope::ReadHistory, &[]),
+    command("set_session_tags", "sessions", "Define as tags de uma sessão (substitui as existentes)",
+        &[req("sessionId", "string"), req("tags", "string[]")],
+        &[Permission::WriteHistory], ApiTokenScope::Admin, &[]),
+    command("move_session_to_folder", "sessions", "Move uma sessão para uma pasta (`None` ou vazio remove da pasta)",
+        &[req("sessionId", "string"), opt("folder", "string")],
+        &[Permission::WriteHistory], ApiTokenScope::Admin, &[]),
+    command("archive_session", "sessions", "Arquiva (ou desarquiva, com `archived = false`) uma sessão",
+        &[req("sessionId", "string"), opt("archived", "boolean")],
+        &[Permission::WriteHistory], ApiTokenScope::Admin, &[]),
+    command("set_session_template", "sessions", "Marca (ou desmarca, com `template = false`) uma sessão como template",
+        &[req("sessionId", "string"), opt("template", "boolean")],
+        &[Permission::WriteHistory], ApiTokenScope::Admin, &[]),
+    command("set_session_offline", "sessions", "Liga (ou desliga, com `offline = false`) o modo offline da sessão: sem RAG, busca web nem ferramentas de rede",
+        &[req("sessionId", "string"), opt("offline", "boolean")],
+        &[Permission::WriteHistory], ApiTokenScope::Admin, &[]),
+    command("get_session_memory", "sessions", "Fatos lembrados da sessão, incluídos no system prompt das próximas mensagens",
+        &[req("sessionId", "string")],
+        &[Permission::ReadHistory], ApiTokenScope::ReadHistory, &[]),
+    command("add_memory_item", "sessions", "Acrescenta um fato à memória da sessão (ignorado se já lembrado)",
+        &[req("sessionId", "string"), req("item", "string")],
+        &[Permission::WriteHistory], ApiTokenScope::Admin, &[]),
+    command("remove_memory_item", "sessions", "Remove um fato da memória da sessão",
+        &[req("sessionId", "string"), req("item", "string")],
+        &[Permission::WriteHistory], ApiTokenScope::Admin, &[]),
+    command("duplicate_session", "sessions", "Duplica uma sessão com sua configuração; templates sempre começam sem mensagens",
+        &[req("id", "string"), opt("includeMessages", "boolean")],
+        &[Permission::WriteHistory], ApiTokenScope::Admin, &[]),
+    command("get_unread_counts", "sessions", "Respostas concluídas em background ainda não vistas, por sessão",
+        &[],
+        &[Permission::ReadHistory], ApiTokenScope::ReadHistory, &[]),
+    command("get_usage_analytics", "sessions", "Estatísticas de uso locais: mensagens por dia, tokens e latência por modelo, tags e sucesso das tasks",
+        &[opt("range", "string")],
+        &[Permission::ReadHistory], ApiTokenScope::ReadHistory, &[]),
+    command("mark_session_read", "sessions", "Marca a sessão como lida (o frontend chama ao abrir a sessão) e atualiza o badge",
+        &[req("id", "string")],
+        &[Permission::WriteHistory], ApiTokenScope::Admin, &[]),
+    command("get_pending_digest_items", "digest", "Itens aguardando o próximo resumo diário",
+        &[],
+        &[Permission::ReadHistory], ApiTokenScope::ReadHistory, &[]),
+    command("add_digest_item", "digest", "Adiciona um item ao próximo resumo (feeds e atualizações de modelos detectados pelo frontend)",
+        &[req("kind", "DigestItemKind"), req("title", "string"), req("content", "string"), opt("sources", "DigestSource[]")],
+        &[Permission::WriteHistory], ApiTokenScope::Admin, &["digest"]),
+    command("run_digest_now", "digest", "Gera o resumo agora com os itens pendentes, sem esperar o horário agendado",
+        &[],
+        &[Permission::WriteHistory], ApiTokenScope::Admin, &[]),
+    command("load_chat_history", "history", "Carrega todas as mensagens de uma sessão",
+        &[req("id", "string")],
+        &[Permission::ReadHistory], ApiTokenScope::ReadHistory, &[]),
+    command("load_chat_history_paginated", "history", "Carrega histórico de chat com paginação (lazy loading)",
+        &[req("id", "string"), opt("limit", "number"), opt("offset", "number")],
+        &[Permission::ReadHistory], ApiTokenScope::ReadHistory, &[]),
+    command("load_chat_history_page", "history", "Carrega uma página do histórico com total e indicação de mais páginas",
+        &[req("id", "string"), opt("limit", "number"), opt("offset", "number")],
+        &[Permission::ReadHistory], ApiTokenScope::ReadHistory, &[]),
+    command("get_message", "history", "Retorna uma mensagem pelo ID (com metadata já desserializado)",
+        &[req("messageId", "number")],
+        &[Permission::ReadHistory], ApiTokenScope::ReadHistory, &[]),
+    command("truncate_session_after", "history", "Volta a sessão até a mensagem informada",
+        &[req("messageId", "number")],
+        &[Permission::WriteHistory, Permission::Destructive], ApiTokenScope::Admin, &[]),
+    command("undo_session_truncation", "history", "Restaura as mensagens de um truncamento ainda dentro da janela de desfazer",
+        &[req("batchId", "string")],
+        &[Permission::WriteHistory], ApiTokenScope::Admin, &[]),
+    command("list_session_truncations", "history", "Lista os truncamentos ainda desfazíveis de uma sessão",
+        &[req("sessionId", "string")],
+        &[Permission::ReadHistory], ApiTokenScope::ReadHistory, &[]),
+    command("set_message_metadata", "history", "Atualiza o metadata de uma mensagem com semântica de JSON merge-patch",
+        &[req("messageId", "number"), req("patch", "json")],
+        &[Permission::WriteHistory], ApiTokenScope::Admin, &[]),
+    command("delete_chat_session", "sessions", "Apaga uma sessão e suas mensagens",
+        &[req("id", "string")],
+        &[Permission::WriteHistory, Permission::Destructive], ApiTokenScope::Admin, &[]),
+    command("cleanup_orphan_sessions", "storage", "Limpa sessões órfãs do SQLite que não têm arquivo JSON correspondente",
+        &[],
+        &[Permission::WriteHistory, Permission::Destructive], ApiTokenScope::Admin, &[]),
+    command("load_mcp_config", "mcp", "Carrega a configuração dos servidores MCP",
+        &[],
+        &[Permission::FileSystem], ApiTokenScope::Admin, &[]),
+    command("save_mcp_config", "mcp", "Salva a configuração dos servidores MCP",
+        &[req("config", "McpConfig")],
+        &[Permission::FileSystem, Permission::Settings], ApiTokenScope::Admin, &[]),
+    command("get_mcp_config_path_command", "mcp", "Caminho do arquivo de configuração MCP",
+        &[],
+        &[], ApiTokenScope::Admin, &[]),
+    command("start_mcp_server", "mcp", "Inicia um servidor MCP",
+        &[req("name", "string"), req("config", "McpServerConfig")],
+        &[Permission::Process], ApiTokenScope::Admin, &["mcp"]),
+    command("stop_mcp_server", "mcp", "Encerra um servidor MCP",
+        &[req("name", "string")],
+        &[Permission::Process], ApiTokenScope::Admin, &["mcp"]),
+    command("restart_mcp_server", "mcp", "Reinicia um servidor MCP",
+        &[req("name", "string")],
+        &[Permission::Process], ApiTokenScope::Admin, &["mcp"]),
+    command("list_mcp_server_status", "mcp", "Status dos servidores MCP",
+        &[],
+        &[], ApiTokenScope::Admin, &["mcp"]),
+    command("restart_all_mcp_servers", "mcp", "Reinicia todos os servidores MCP",
+        &[],
+        &[Permission::Process], ApiTokenScope::Admin, &["mcp"]),
+    command("list_mcp_tools", "mcp", "Ferramentas expostas por um servidor MCP",
+        &[req("serverName", "string")],
+        &[], ApiTokenScope::Admin, &["mcp"]),
+    command("call_mcp_tool", "mcp", "Executa uma ferramenta de um servidor MCP (ou das ferramentas embutidas de arquivos e web, servidor `builtin`)",
+        &[req("serverName", "string"), req("toolName", "string"), req("arguments", "json"), opt("sessionId", "string")],
+        &[Permission::Process, Permission::FileSystem, Permission::Network], ApiTokenScope::Admin, &["mcp"]),
+    command("get_all_mcp_tools", "mcp", "Ferramentas de todos os servidores MCP ativos",
+        &[],
+        &[], ApiTokenScope::Admin, &["mcp"]),
+    command("ensure_mcp_server_installed", "mcp", "Instala o pacote de um servidor MCP, se necessário",
+        &[req("name", "string"), req("config", "McpServerConfig")],
+        &[Permission::Network, Permission::Process], ApiTokenScope::Admin, &["mcp"]),
+    command("check_mcp_server_available", "mcp", "Verifica se o comando de um servidor MCP está disponível",
+        &[req("name", "string"), req("config", "McpServerConfig")],
+        &[Permission::Process], ApiTokenScope::Admin, &["mcp"]),
+    command("search_and_extract_content", "web", "Busca no DuckDuckGo e extrai conteúdo das URLs encontradas",
+        &[req("query", "string"), opt("limit", "number"), opt("excludedDomains", "string[]"), opt("searchConfig", "SearchConfig"), opt("translateTo", "string"), opt("translationModel", "string"), opt("maxAge", "number"), opt("jobId", "string"), opt("confirmRedaction", "boolean"), opt("sessionId", "string")],
+        &[Permission::Network], ApiTokenScope::Admin, &["headless_browser"]),
+    command("extract_url_content", "web", "Extrai conteúdo de uma URL específica",
+        &[req("url", "string"), opt("maxAge", "number"), opt("sessionId", "string")],
+        &[Permission::Network], ApiTokenScope::Admin, &["headless_browser"]),
+    command("search_web_metadata", "web", "Busca metadados leves (título/URL/snippet) sem abrir páginas",
+        &[req("query", "string"), opt("limit", "number"), opt("searchConfig", "SearchConfig"), opt("engineOrder", "string[]"), opt("confirmRedaction", "boolean"), opt("sessionId", "string")],
+        &[Permission::Network], ApiTokenScope::Admin, &[]),
+    command("test_search_engine", "web", "Busca de teste em um motor com a saúde de cada selector",
+        &[req("name", "string")],
+        &[Permission::Network], ApiTokenScope::Admin, &[]),
+    command("get_search_engine_health", "web", "Taxa de sucesso e falhas seguidas de cada motor de busca",
+        &[],
+        &[], ApiTokenScope::Admin, &[]),
+    command("search_images", "web", "Busca imagens com miniatura e página de origem (Bing/DuckDuckGo)",
+        &[req("query", "string"), opt("limit", "number"), opt("confirmRedaction", "boolean")],
+        &[Permission::Network], ApiTokenScope::Admin, &[]),
+    command("download_image", "web", "Baixa uma imagem para os dados do app (base64 para modelos com visão)",
+        &[req("url", "string")],
+        &[Permission::Network, Permission::FileSystem], ApiTokenScope::Admin, &[]),
+    command("scrape_urls", "web", "Faz scraping em lote de URLs fornecidas",
+        &[req("urls", "string[]"), opt("translateTo", "string"), opt("translationModel", "string"), opt("maxAge", "number"), opt("jobId", "string")],
+        &[Permission::Network], ApiTokenScope::Admin, &["headless_browser"]),
+    command("cancel_scrape_job", "web", "Cancela um job de `scrape_urls`/`search_and_extract_content`",
+        &[req("jobId", "string")],
+        &[], ApiTokenScope::Admin, &[]),
+    command("guard_web_context", "web", "Monta o contexto web do prompt: fontes sem instruções suspeitas, delimitadas e dentro da fração do contexto do modelo",
+        &[req("model", "string"), req("sources", "ScrapedContent[]"), opt("sessionId", "string")],
+        &[], ApiTokenScope::Admin, &[]),
+    command("translate_text", "web", "Traduz um texto para o idioma alvo usando um modelo local",
+        &[req("text", "string"), req("targetLanguage", "string"), req("model", "string")],
+        &[], ApiTokenScope::Admin, &["ollama"]),
+    command("detect_text_language", "web", "Detecta o idioma de um texto (código ISO 639-1 quando disponível)",
+        &[req("text", "string")],
+        &[], ApiTokenScope::Admin, &[]),
+    command("reset_browser", "web", "Reinicia os browsers do pool de headless browsers",
+        &[],
+        &[Permission::Process], ApiTokenScope::Admin, &["headless_browser"]),
+    command("get_browser_pool_stats", "web", "Instâncias ativas do pool de headless browsers",
+        &[],
+        &[], ApiTokenScope::Admin, &[]),
+    command("clear_scrape_cache", "web", "Limpa o cache de páginas do scraper (tudo, ou apenas páginas mais antigas que `older_than_secs`)",
+        &[opt("olderThanSecs", "number")],
+        &[Permission::Destructive], ApiTokenScope::Admin, &[]),
+    command("force_kill_browser", "web", "Força o encerramento dos processos Chrome/Chromium headless criados pelo app",
+        &[],
+        &[Permission::Process, Permission::Destructive], ApiTokenScope::Admin, &[]),
+    command("get_storage_usage", "storage", "Espaço em disco usado por modelos do Ollama (por blob), chats, banco + WAL, cache do scraper e logs",
+        &[],
+        &[Permission::FileSystem], ApiTokenScope::Admin, &[]),
+    command("export_chat_sessions", "export", "Exporta todas as sessões de chat para um arquivo ZIP",
+        &[],
+        &[Permission::ReadHistory, Permission::FileSystem], ApiTokenScope::Admin, &[]),
+    command("export_all_data", "export", "Exporta todos os dados do app (chats, tasks, sources, settings) para um arquivo ZIP",
+        &[opt("compressionLevel", "number")],
+        &[Permission::ReadHistory, Permission::FileSystem], ApiTokenScope::Admin, &[]),
+    command("export_workspace_bundle", "export", "Exporta MCP (sem segredos), fontes, presets, templates e configurações em um JSON",
+        &[],
+        &[Permission::FileSystem], ApiTokenScope::Admin, &[]),
+    command("import_workspace_bundle", "export", "Importa um bundle de workspace",
+        &[req("path", "string")],
+        &[Permission::FileSystem, Permission::Settings], ApiTokenScope::Admin, &[]),
+    command("import_external_history", "export", "Importa conversas exportadas do ChatGPT, Claude ou Open WebUI com os horários originais",
+        &[req("path", "string"), opt("format", "ExternalFormat")],
+        &[Permission::WriteHistory, Permission::FileSystem], ApiTokenScope::Admin, &[]),
+    command("export_audit_log", "export", "Exporta o log de auditoria (prompts, respostas, ferramentas e sites) em JSONL e confere a cadeia de hashes",
+        &[opt("range", "string")],
+        &[Permission::ReadHistory, Permission::FileSystem], ApiTokenScope::Admin, &[]),
+    command("archive_old_sessions", "archive", "Move sessões sem atividade para arquivos ZIP mensais (arquivo frio)",
+        &[opt("olderThanDays", "number")],
+        &[Permission::WriteHistory, Permission::FileSystem], ApiTokenScope::Admin, &[]),
+    command("preview_retention_cleanup", "archive", "Sessões que a política de retenção removeria (a informada ou a configurada)",
+        &[opt("policy", "RetentionSettings")],
+        &[Permission::ReadHistory], ApiTokenScope::ReadHistory, &[]),
+    command("list_archived_sessions", "archive", "Lista as sessões movidas para o arquivo frio",
+        &[opt("limit", "number")],
+        &[Permission::ReadHistory], ApiTokenScope::ReadHistory, &[]),
+    command("search_archived_sessions", "archive", "Busca no índice leve das sessões arquivadas (título e palavras das mensagens)",
+        &[req("query", "string"), opt("limit", "number")],
+        &[Permission::ReadHistory], ApiTokenScope::ReadHistory, &[]),
+    command("restore_from_archive", "archive", "Traz uma sessão arquivada de volta ao banco (mensagens, anexos e JSON legado)",
+        &[req("sessionId", "string")],
+        &[Permission::WriteHistory, Permission::FileSystem], ApiTokenScope::Admin, &[]),
+    command("clear_chat_history", "storage", "Apaga todo o histórico de conversas",
+        &[],
+        &[Permission::WriteHistory, Permission::Destructive], ApiTokenScope::Admin, &[]),
+    command("get_app_data_dir", "storage", "Retorna o caminho do diretório de dados do app",
+        &[],
+        &[], ApiTokenScope::Admin, &[]),
+    command("verify_storage_paths", "storage", "Diagnóstico dos diretórios usados pelo app",
+        &[],
+        &[Permission::FileSystem], ApiTokenScope::Admin, &[]),
+    command("load_sources_config_command", "sources", "Carrega a configuração de fontes de busca",
+        &[],
+        &[], ApiTokenScope::Admin, &[]),
+    command("save_sources_config_command", "sources", "Salva a configuração de fontes de busca",
+        &[req("config", "SourcesConfig")],
+        &[Permission::Settings], ApiTokenScope::Admin, &[]),
+    command("fetch_feeds", "sources", "Itens novos dos feeds RSS/Atom de uma categoria (GUIDs já entregues são ignorados)",
+        &[opt("category", "string"), opt("includeSeen", "boolean")],
+        &[Permission::Network], ApiTokenScope::Admin, &[]),
+    command("export_sources_config", "sources", "Exporta as categorias de fontes em OPML ou JSON",
+        &[req("destPath", "string"), req("format", "string")],
+        &[Permission::FileSystem], ApiTokenScope::Admin, &[]),
+    command("import_sources_config", "sources", "Importa categorias de fontes de um arquivo OPML ou JSON (mescla ou substitui)",
+        &[req("path", "string"), opt("replace", "boolean")],
+        &[Permission::FileSystem, Permission::Settings], ApiTokenScope::Admin, &[]),
+    command("get_recent_logs", "logs", "Obtém as últimas N linhas dos logs do sistema (incluindo arquivos rotacionados)",
+        &[req("lines", "number")],
+        &[Permission::FileSystem], ApiTokenScope::Admin, &[]),
+    command("set_log_level", "logs", "Altera o nível de log em tempo de execução (off, error, warn, info, debug, trace)",
+        &[req("level", "string")],
+        &[Permission::Settings], ApiTokenScope::Admin, &[]),
+    command("stream_logs", "logs", "Liga/desliga o evento `log-entry` do visualizador de logs ao vivo",
+        &[req("enabled", "boolean")],
+        &[], ApiTokenScope::Admin, &[]),
+    command("export_logs", "logs", "Exporta os logs recentes e as specs do sistema em um ZIP para relatórios de bug",
+        &[req("zipPath", "string")],
+        &[Permission::FileSystem], ApiTokenScope::Admin, &[]),
+    command("log_to_terminal", "logs", "Recebe logs do frontend e os imprime no terminal",
+        &[req("level", "string"), req("message", "string")],
+        &[], ApiTokenScope::Admin, &[]),
+    command("get_system_stats", "system", "Obtém estatísticas do sistema em tempo real",
+        &[],
+        &[], ApiTokenScope::Admin, &[]),
+    command("create_task", "scheduler", "Cria uma task agendada",
+        &[req("label", "string"), req("cronSchedule", "string"), req("action", "TaskAction"), opt("postActions", "TaskAction[]")],
+        &[Permission::Settings], ApiTokenScope::Admin, &[]),
+    command("list_tasks", "scheduler", "Lista as tasks agendadas",
+        &[],
+        &[], ApiTokenScope::Admin, &[]),
+    command("update_task", "scheduler", "Atualiza uma task agendada",
+        &[req("task", "SentinelTask")],
+        &[Permission::Settings], ApiTokenScope::Admin, &[]),
+    command("delete_task", "scheduler", "Remove uma task agendada",
+        &[req("id", "string")],
+        &[Permission::Settings, Permission::Destructive], ApiTokenScope::Admin, &[]),
+    command("toggle_task", "scheduler", "Habilita ou desabilita uma task agendada",
+        &[req("id", "string"), req("enabled", "boolean")],
+        &[Permission::Settings], ApiTokenScope::Admin, &[]),
+    command("pause_scheduler", "scheduler", "Pausa ou retoma a execução das tasks agendadas",
+        &[req("paused", "boolean")],
+        &[], ApiTokenScope::Admin, &[]),
+    command("run_palette_command", "app", "Busca ações da paleta de comandos (fuzzy) e, com run, executa a mais relevante",
+        &[req("query", "string"), opt("run", "boolean")],
+        &[Permission::Process, Permission::Network], ApiTokenScope::Admin, &[]),
+    command("register_palette_action", "app", "Registra uma ação de extensão na paleta de comandos",
+        &[req("action", "PluginAction")],
+        &[], ApiTokenScope::Admin, &[]),
+    command("install_plugin", "plugins", "Instala um plugin (sidecar MCP ou módulo WASM) a partir de uma pasta ou ZIP com plugin.json",
+        &[req("path", "string")],
+        &[Permission::FileSystem], ApiTokenScope::Admin, &[]),
+    command("list_plugins", "plugins", "Plugins instalados com manifesto, permissões declaradas e estado",
+        &[],
+        &[], ApiTokenScope::Admin, &[]),
+    command("enable_plugin", "plugins", "Ativa ou desativa um plugin (sidecars são iniciados ou encerrados)",
+        &[req("name", "string"), req("enabled", "boolean")],
+        &[Permission::Process], ApiTokenScope::Admin, &[]),
+    command("subscribe_events", "app", "Define os eventos tipados emitidos pelo backend e retorna o contrato (tipos e versões)",
+        &[opt("kinds", "EventKind[]")],
+        &[], ApiTokenScope::Admin, &[]),
+    command("quit_app", "app", "Encerra o app de verdade (fechar a janela apenas a oculta no tray)",
+        &[],
+        &[Permission::Process], ApiTokenScope::Admin, &[]),
+    command("check_download_url", "installer", "Verifica se uma URL de download está disponível",
+        &[req("url", "string")],
+        &[Permission::Network], ApiTokenScope::Admin, &[]),
+    command("get_local_installer_path", "installer", "Obtém o caminho do instalador local se existir",
+        &[req("filename", "string")],
+        &[Permission::FileSystem], ApiTokenScope::Admin, &[]),
+    command("download_installer", "installer", "Faz download do instalador da URL oficial ou usa fallback local",
+        &[req("url", "string"), req("filename", "string")],
+        &[Permission::Network, Permission::FileSystem], ApiTokenScope::Admin, &[]),
+    command("run_installer", "installer", "Executa o instalador baixado",
+        &[req("filePath", "string")],
+        &[Permission::Process], ApiTokenScope::Admin, &[]),
+    command("get_downloaded_installer_path", "installer", "Verifica se o instalador já foi baixado",
+        &[req("filename", "string")],
+        &[Permission::FileSystem], ApiTokenScope::Admin, &[]),
+    command("check_ollama_full", "ollama", "Verifica instalação e execução do Ollama em uma única chamada",
+        &[],
+        &[Permission::Process, Permission::Network], ApiTokenScope::Admin, &[]),
+    command("get_onboarding_recommendations", "onboarding", "Modelos iniciais recomendados para a RAM/VRAM/GPU detectadas, com motivos e tamanhos",
+        &[],
+        &[Permission::Process, Permission::Network], ApiTokenScope::Admin, &[]),
+    command("run_diagnostics", "system", "Diagnóstico para a tela de Suporte (Ollama, modelos, SQLite, pasta de chats, Chrome, MCP, disco) com correções sugeridas",
+        &[],
+        &[Permission::Network, Permission::Process, Permission::FileSystem], ApiTokenScope::Admin, &[]),
+    command("run_onboarding_step", "onboarding", "Executa um passo do primeiro uso (instalar Ollama → iniciar → baixar modelo → primeiro chat)",
+        &[req("step", "OnboardingStep")],
+        &[Permission::Process, Permission::Network, Permission::FileSystem, Permission::WriteHistory, Permission::Settings], ApiTokenScope::Admin, &["ollama"]),
+    command("get_ollama_version", "ollama", "Versão do Ollama instalado (ou do servidor em execução)",
+        &[],
+        &[Permission::Process, Permission::Network], ApiTokenScope::Admin, &[]),
+    command("check_ollama_update", "ollama", "Compara a versão instalada do Ollama com a última release no GitHub",
+        &[],
+        &[Permission::Process, Permission::Network], ApiTokenScope::Admin, &[]),
+    command("upgrade_ollama", "ollama", "Baixa e executa o instalador da última versão do Ollama e verifica o servidor",
+        &[],
+        &[Permission::Network, Permission::FileSystem, Permission::Process], ApiTokenScope::Admin, &[]),
+    command("auto_start_ollama", "ollama", "Inicia o Ollama automaticamente se estiver instalado mas não estiver rodando",
+        &[],
+        &[Permission::Process], ApiTokenScope::Admin, &[]),
+    command("classify_intent", "chat", "Classifica a intenção de uma pergunta (factual, técnica, conversa...)",
+        &[req("query", "string")],
+        &[], ApiTokenScope::ChatOnly, &[]),
+    command("download_embedding_model", "embeddings", "Baixa o modelo de embeddings se não existir",
+        &[],
+        &[Permission::Network, Permission::FileSystem], ApiTokenScope::Admin, &[]),
+    command("is_embedding_model_available", "embeddings", "Verifica se o modelo de embeddings está disponível",
+        &[],
+        &[], ApiTokenScope::Admin, &[]),
+    command("calculate_relevance_scores", "embeddings", "Calcula scores de relevância para textos em relação a uma query",
+        &[req("query", "string"), req("texts", "string[]")],
+        &[], ApiTokenScope::Admin, &["embedding_model"]),
+    command("generate_embedding", "embeddings", "Gera embedding para um texto",
+        &[req("text", "string")],
+        &[], ApiTokenScope::Admin, &["embedding_model"]),
+    command("prune_context", "embeddings", "Poda o contexto mantendo apenas os parágrafos mais relevantes",
+        &[req("query", "string"), req("context", "string"), opt("maxTokens", "number"), opt("minScore", "number")],
+        &[], ApiTokenScope::Admin, &["embedding_model"]),
+    command("start_embedding_job", "embeddings", "Gera em background os embeddings dos documentos RAG que ainda não têm",
+        &[opt("sessionId", "string")],
+        &[Permission::WriteHistory], ApiTokenScope::Admin, &["embedding_model"]),
+    command("list_jobs", "jobs", "Lista os jobs de background em andamento e os finalizados recentemente",
+        &[],
+        &[], ApiTokenScope::Admin, &[]),
+    command("cancel_job", "jobs", "Solicita o cancelamento de um job em andamento",
+        &[req("jobId", "string")],
+        &[], ApiTokenScope::Admin, &[]),
+    command("get_resource_profile", "settings", "Retorna o perfil de recursos efetivo (modo de baixo consumo)",
+        &[],
+        &[], ApiTokenScope::Admin, &[]),
+    command("set_low_resource_mode", "settings", "Define o modo de baixo consumo (auto/on/off), persistindo em settings.json",
+        &[req("mode", "LowResourceMode")],
+        &[Permission::Settings], ApiTokenScope::Admin, &[]),
+    command("set_autostart", "settings", "Liga ou desliga a inicialização com o sistema (só no tray com `start_minimized`)",
+        &[req("enabled", "boolean"), opt("startMinimized", "boolean")],
+        &[Permission::Settings, Permission::Process], ApiTokenScope::Admin, &[]),
+    command("get_user_profile", "settings", "Perfil do usuário compartilhado entre as sessões (preferências, nível, idioma e tom)",
+        &[],
+        &[], ApiTokenScope::Admin, &[]),
+    command("set_user_profile_consent", "settings", "Dá o consentimento para guardar o perfil do usuário ou o revoga, apagando o perfil",
+        &[req("consent", "boolean")],
+        &[Permission::Settings], ApiTokenScope::Admin, &[]),
+    command("update_user_profile", "settings", "Altera o perfil do usuário (exige consentimento); com `enabled`, entra no system prompt dos chats",
+        &[req("patch", "json")],
+        &[Permission::Settings], ApiTokenScope::Admin, &[]),
+    command("get_chat_templates", "settings", "Templates de mensagens por padrão de nome de modelo",
+        &[],
+        &[], ApiTokenScope::Admin, &[]),
+    command("set_chat_template", "settings", "Cria, substitui ou remove o template de mensagens de um padrão de modelo",
+        &[req("pattern", "string"), opt("template", "json")],
+        &[Permission::Settings], ApiTokenScope::Admin, &[]),
+    command("get_settings", "settings", "Retorna as configurações do app",
+        &[],
+        &[], ApiTokenScope::Admin, &[]),
+    command("update_settings", "settings", "Atualiza as configurações com semântica de JSON merge-patch (apenas os campos enviados)",
+        &[req("patch", "json")],
+        &[Permission::Settings], ApiTokenScope::Admin, &[]),
+    command("get_ollama_server_tuning", "ollama", "Retorna os ajustes do servidor Ollama salvos nas configurações",
+        &[],
+        &[], ApiTokenScope::Admin, &[]),
+    command("apply_ollama_server_tuning", "ollama", "Salva e aplica os ajustes do servidor Ollama (opcionalmente com benchmark)",
+        &[req("tuning", "OllamaServerTuning"), opt("benchmarkModel", "string")],
+        &[Permission::Settings, Permission::Process], ApiTokenScope::Admin, &["ollama"]),
+    command("get_model_context_info", "models", "Detecta o contexto máximo do modelo (via /api/show) e retorna o override configurado",
+        &[req("model", "string")],
+        &[Permission::Network], ApiTokenScope::Admin, &["ollama"]),
+    command("set_model_context_override", "models", "Define (ou remove, com `num_ctx = null`) o `num_ctx` usado para um modelo",
+        &[req("model", "string"), opt("numCtx", "number")],
+        &[Permission::Settings], ApiTokenScope::Admin, &[]),
+    command("handle_dropped_files", "chat", "Decide a ação para arquivos soltos na área de chat (imagem, anexo ou modelo GGUF)",
+        &[opt("sessionId", "string"), req("paths", "string[]")],
+        &[Permission::FileSystem], ApiTokenScope::Admin, &[]),
+    command("save_prompt_preset", "prompts", "Cria ou atualiza um preset de prompt (id ausente = novo preset)",
+        &[opt("id", "string"), req("name", "string"), req("content", "string"), opt("variables", "Record<string, string>")],
+        &[Permission::Settings], ApiTokenScope::Admin, &[]),
+    command("list_prompt_presets", "prompts", "Lista os presets de prompt",
+        &[],
+        &[], ApiTokenScope::Admin, &[]),
+    command("delete_prompt_preset", "prompts", "Remove um preset de prompt",
+        &[req("id", "string")],
+        &[Permission::Settings, Permission::Destructive], ApiTokenScope::Admin, &[]),
+    command("apply_prompt_preset", "prompts", "Aplica um preset a uma sessão (None remove)",
+        &[req("sessionId", "string"), opt("presetId", "string"), opt("variables", "Record<string, string>")],
+        &[Permission::WriteHistory], ApiTokenScope::Admin, &[]),
+    command("run_db_maintenance", "storage", "Checkpoint do WAL, VACUUM, REINDEX e verificação de integridade do banco",
+        &[opt("mode", "MaintenanceMode")],
+        &[Permission::WriteHistory], ApiTokenScope::Admin, &[]),
+    command("sync_now", "storage", "Sincroniza conversas e configurações com a pasta compartilhada (Syncthing, Dropbox...)",
+        &[],
+        &[Permission::WriteHistory, Permission::FileSystem, Permission::Settings], ApiTokenScope::Admin, &[]),
+    command("smart_paste", "chat", "Trata um texto colado (textos longos viram anexo referenciado no prompt)",
+        &[opt("sessionId", "string"), req("text", "string"), opt("summarize", "boolean"), opt("embed", "boolean"), opt("model", "string")],
+        &[Permission::WriteHistory], ApiTokenScope::ChatOnly, &[]),
+    command("get_paste_attachment", "chat", "Retorna um anexo de texto colado (para pré-visualização na UI)",
+        &[req("id", "string")],
+        &[Permission::ReadHistory], ApiTokenScope::ReadHistory, &[]),
+    command("save_conversation_template", "templates", "Cria ou atualiza um template de conversa (id ausente = novo template)",
+        &[req("template", "ConversationTemplateInput")],
+        &[Permission::Settings], ApiTokenScope::Admin, &[]),
+    command("list_conversation_templates", "templates", "Lista os templates de conversa",
+        &[],
+        &[], ApiTokenScope::Admin, &[]),
+    command("delete_conversation_template", "templates", "Remove um template de conversa",
+        &[req("id", "string")],
+        &[Permission::Settings, Permission::Destructive], ApiTokenScope::Admin, &[]),
+    command("create_session_from_template", "templates", "Cria uma nova sessão a partir de um template",
+        &[req("id", "string")],
+        &[Permission::WriteHistory], ApiTokenScope::Admin, &[]),
+    command("get_session_config", "templates", "Retorna a configuração própria de uma sessão (modelo, system prompt, escopo RAG)",
+        &[req("sessionId", "string")],
+        &[Permission::ReadHistory], ApiTokenScope::ReadHistory, &[]),
+    command("set_session_generation_options", "templates", "Define as opções de geração padrão de uma sessão (temperature, stop, num_predict...)",
+        &[req("sessionId", "string"), opt("options", "GenerationOptions")],
+        &[Permission::WriteHistory], ApiTokenScope::Admin, &[]),
+    command("share_session", "export", "Exporta uma conversa como um único arquivo HTML (prompts de sistema omitidos por padrão)",
+        &[req("sessionId", "string"), opt("path", "string"), opt("redactSystemPrompts", "boolean")],
+        &[Permission::ReadHistory, Permission::FileSystem], ApiTokenScope::Admin, &[]),
+    command("preview_shared_session", "export", "Serve o HTML da conversa na rede local por tempo limitado, com QR code da URL",
+        &[req("sessionId", "string"), opt("redactSystemPrompts", "boolean")],
+        &[Permission::ReadHistory, Permission::Network], ApiTokenScope::Admin, &[]),
+    command("stop_share_preview", "export", "Encerra o preview local da conversa compartilhada",
+        &[],
+        &[Permission::Network], ApiTokenScope::Admin, &[]),
+    command("transcribe_audio", "stt", "Transcreve áudio com whisper.cpp, emitindo trechos parciais em stt-partial",
+        &[opt("path", "string"), opt("bytes", "bytes"), opt("language", "string"), opt("requestId", "string")],
+        &[Permission::FileSystem, Permission::Process], ApiTokenScope::ChatOnly, &[]),
+    command("get_stt_status", "stt", "Disponibilidade do whisper.cpp, do modelo de voz e do ffmpeg",
+        &[],
+        &[], ApiTokenScope::ChatOnly, &[]),
+    command("download_stt_model", "stt", "Baixa o modelo ggml do whisper",
+        &[opt("model", "string")],
+        &[Permission::Network, Permission::FileSystem], ApiTokenScope::Admin, &[]),
+    command("extract_code_blocks", "code", "Lista os blocos de código cercados de uma mensagem",
+        &[req("messageId", "number")],
+        &[Permission::ReadHistory], ApiTokenScope::ReadHistory, &[]),
+    command("run_code_snippet", "code", "Executa um snippet Python/Node/Shell em subprocesso isolado (timeout, diretório temporário, sem rede por padrão)",
+        &[req("language", "string"), req("code", "string"), opt("timeoutSecs", "number"), opt("allowNetwork", "boolean")],
+        &[Permission::Process], ApiTokenScope::Admin, &[]),
+    command("attach_workspace", "workspace", "Anexa uma pasta local à sessão, indexando os arquivos (respeita .gitignore) e observando alterações",
+        &[req("sessionId", "string"), req("path", "string")],
+        &[Permission::FileSystem, Permission::WriteHistory], ApiTokenScope::Admin, &[]),
+    command("detach_workspace", "workspace", "Desanexa a pasta da sessão e remove o índice",
+        &[req("sessionId", "string")],
+        &[Permission::WriteHistory], ApiTokenScope::Admin, &[]),
+    command("list_workspace_files", "workspace", "Lista os arquivos indexados do workspace da sessão",
+        &[req("sessionId", "string")],
+        &[Permission::ReadHistory], ApiTokenScope::ReadHistory, &[]),
+    command("search_workspace", "workspace", "Busca os trechos do workspace mais relevantes para uma consulta",
+        &[req("sessionId", "string"), req("query", "string"), opt("limit", "number")],
+        &[Permission::ReadHistory], ApiTokenScope::ReadHistory, &[]),
+    command("read_workspace_file", "workspace", "Lê um arquivo do workspace anexado à sessão",
+        &[req("sessionId", "string"), req("path", "string")],
+        &[Permission::FileSystem], ApiTokenScope::ReadHistory, &[]),
+    command("export_citations", "citations", "Exporta as fontes citadas em uma sessão como BibTeX ou CSL-JSON",
+        &[req("sessionId", "string"), req("format", "CitationFormat")],
+        &[Permission::ReadHistory], ApiTokenScope::ReadHistory, &[]),
+    command("save_message_sources", "citations", "Guarda o conteúdo completo das fontes usadas em uma resposta",
+        &[opt("sessionId", "string"), req("messageId", "number"), req("sources", "ScrapedContent[]")],
+        &[Permission::WriteHistory], ApiTokenScope::Admin, &[]),
+    command("get_message_sources", "citations", "Cópias das fontes que embasaram uma mensagem",
+        &[req("messageId", "number")],
+        &[Permission::ReadHistory], ApiTokenScope::ReadHistory, &[]),
+    command("create_api_token", "api_tokens", "Cria um token de API com escopo (chat_only, read_history, admin)",
+        &[req("name", "string"), req("scope", "ApiTokenScope")],
+        &[Permission::Settings], ApiTokenScope::Admin, &[]),
+    command("list_api_tokens", "api_tokens", "Lista tokens de API (sem os valores)",
+        &[],
+        &[], ApiTokenScope::Admin, &[]),
+    command("revoke_api_token", "api_tokens", "Revoga um token de API",
+        &[req("id", "string")],
+        &[Permission::Settings, Permission::Destructive], ApiTokenScope::Admin, &[]),
+    command("list_capabilities", "app", "Catálogo dos comandos registrados, parâmetros e permissões",
+        &[],
+        &[], ApiTokenScope::Admin, &[]),
+];
+
+pub fn catalog() -> CapabilityCatalog {
+    CapabilityCatalog {
+        app_version: env!("CARGO_PKG_VERSION"),
+        commands: COMMANDS,
+        feature_flags: FEATURE_FLAGS,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+
+    #[test]
+    fn test_registry_matches_handler() {
+        let source = include_str!("lib.rs");
+        let start = source.find("generate_handler![").unwrap() + "generate_handler![".len();
+        let end = start + source[start..].find(']').unwrap();
+        let registered: HashSet<&str> = source[start..end]
+            .lines()
+            .map(|line| line.split("//").next().unwrap_or("").trim().trim_end_matches(','))
+            .filter(|name| !name.is_empty())
+            .collect();
+        let cataloged: HashSet<&str> = COMMANDS.iter().map(|c| c.name).collect();
+
+        assert_eq!(cataloged.len(), COMMANDS.len(), "comando duplicado no catálogo");
+        assert_eq!(registered, cataloged);
+
+        let flags: HashSet<&str> = FEATURE_FLAGS.iter().map(|f| f.id).collect();
+        assert!(COMMANDS.iter().flat_map(|c| c.feature_flags.iter()).all(|f| flags.contains(f)));
+    }
+}
@@ -0,0 +1,155 @@
+//! Ajustes de formato das mensagens por família de modelo (`chat_templates` nas configurações)
+//!
+//! Alguns modelos ignoram ou rejeitam mensagens `system`, ou esperam marcadores em volta
+//! do prompt. O template é escolhido pelo padrão do nome do modelo e aplicado às mensagens
+//! logo antes do envio ao Ollama.
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct ChatTemplate {
+    /// Junta as mensagens de sistema no início da primeira mensagem do usuário
+    #[serde(default)]
+    pub merge_system_into_user: bool,
+    #[serde(default)]
+    pub system_prefix: Option<String>,
+    #[serde(default)]
+    pub system_suffix: Option<String>,
+    #[serde(default)]
+    pub user_prefix: Option<String>,
+    #[serde(default)]
+    pub user_suffix: Option<String>,
+}
+
+pub fn validate(templates: &HashMap<String, ChatTemplate>, errors: &mut Vec<String>) {
+    for pattern in templates.keys() {
+        if pattern.trim().is_empty() || pattern.chars().all(|c| c == '*') {
+            errors.push(format!("chat_templates: padrão inválido: \"{}\"", pattern));
+        }
+    }
+}
+
+/// Casa o nome do modelo com um padrão com curinga `*` (sem diferenciar maiúsculas)
+pub fn matches(pattern: &str, model: &str) -> bool {
+    let pattern = pattern.to_lowercase();
+    let model = model.to_lowercase();
+    let parts: Vec<&str> = pattern.split('*').collect();
+    if parts.len() == 1 {
+        return pattern == model || crate::settings::normalize_model_name(&pattern) == model;
+    }
+
+    let (first, last) = (parts[0], parts[parts.len() - 1]);
+    if !model.starts_with(first) || model.len() < first.len() + last.len() || !model.ends_with(last) {
+        return false;
+    }
+    let mut rest = &model[first.len()..model.len() - last.len()];
+    for part in &parts[1..parts.len() - 1] {
+        match rest.find(part) {
+            Some(index) => rest = &rest[index + part.len()..],
+            None => return false,
+        }
+    }
+    true
+}
+
+fn specificity(pattern: &str) -> usize {
+    pattern.chars().filter(|c| *c != '*').count()
+}
+
+/// Template do padrão mais específico (mais caracteres fora dos curingas) que casa com o modelo
+pub fn resolve<'a>(templates: &'a HashMap<String, ChatTemplate>, model: &str) -> Option<(&'a str, &'a ChatTemplate)> {
+    templates
+        .iter()
+        .filter(|(pattern, _)| matches(pattern, model))
+        .max_by(|(a, _), (b, _)| specificity(a).cmp(&specificity(b)).then_with(|| b.cmp(a)))
+        .map(|(pattern, template)| (pattern.as_str(), template))
+}
+
+fn wrap(content: &str, prefix: &Option<String>, suffix: &Option<String>) -> String {
+    format!(
+        "{}{}{}",
+        prefix.as_deref().unwrap_or_default(),
+        content,
+        suffix.as_deref().unwrap_or_default()
+    )
+}
+
+fn content_of(message: &serde_json::Value) -> String {
+    message["content"].as_str().unwrap_or_default().to_string()
+}
+
+/// Reescreve as mensagens (formato do /api/chat) segundo o template
+pub fn apply(template: &ChatTemplate, messages: &mut Vec<serde_json::Value>) {
+    for message in messages.iter_mut() {
+        let wrapped = match message["role"].as_str() {
+            Some("system") => wrap(&content_of(message), &template.system_prefix, &template.system_suffix),
+            Some("user") => wrap(&content_of(message), &template.user_prefix, &template.user_suffix),
+            _ => continue,
+        };
+        message["content"] = serde_json::Value::String(wrapped);
+    }
+
+    if !template.merge_system_into_user {
+        return;
+    }
+    let system: Vec<String> = messages
+        .iter()
+        .filter(|m| m["role"] == "system")
+        .map(content_of)
+        .filter(|content| !content.trim().is_empty())
+        .collect();
+    if system.is_empty() {
+        return;
+    }
+    // Sem mensagem do usuário para receber o prompt, o sistema é mantido como está
+    let Some(first_user) = messages.iter().position(|m| m["role"] == "user") else {
+        return;
+    };
+    let merged = format!("{}\n\n{}", system.join("\n\n"), content_of(&messages[first_user]));
+    messages[first_user]["content"] = serde_json::Value::String(merged);
+    messages.retain(|m| m["role"] != "system");
+}
+
+/// Aplica o template configurado para `model`, se houver
+pub fn apply_for_model(templates: &HashMap<String, ChatTemplate>, model: &str, messages: &mut Vec<serde_json::Value>) {
+    if let Some((pattern, template)) = resolve(templates, model) {
+        log::debug!("[ChatTemplates] Template \"{}\" aplicado a {}", pattern, model);
+        apply(template, messages);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resolve_and_apply_template() {
+        assert!(matches("gemma*", "gemma2:9b"));
+        assert!(matches("*:instruct", "mistral:instruct"));
+        assert!(matches("llama3", "llama3:latest"));
+        assert!(!matches("gemma*", "codegemma:7b"));
+
+        let templates = HashMap::from([
+            ("gemma*".to_string(), ChatTemplate { merge_system_into_user: true, ..ChatTemplate::default() }),
+            ("gemma2*".to_string(), ChatTemplate {
+                merge_system_into_user: true,
+                user_prefix: Some("[U] ".to_string()),
+                ..ChatTemplate::default()
+            }),
+        ]);
+        let (pattern, template) = resolve(&templates, "gemma2:9b").unwrap();
+        assert_eq!(pattern, "gemma2*");
+
+        let mut messages = vec![
+            serde_json::json!({ "role": "system", "content": "Seja breve." }),
+            serde_json::json!({ "role": "user", "content": "Oi" }),
+            serde_json::json!({ "role": "assistant", "content": "Olá!" }),
+            serde_json::json!({ "role": "user", "content": "Tudo bem?" }),
+        ];
+        apply(template, &mut messages);
+        assert_eq!(messages.len(), 3);
+        assert_eq!(messages[0]["content"], "Seja breve.\n\n[U] Oi");
+        assert_eq!(messages[2]["content"], "[U] Tudo bem?");
+    }
+}
@@ -0,0 +1,269 @@
+use crate::db::ChatMessage;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// Formato de exportação das citações
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum CitationFormat {
+    Bibtex,
+    CslJson,
+}
+
+/// Fonte citada em uma sessão (a partir dos metadados das mensagens)
+#[derive(Debug, Clone, PartialEq)]
+pub struct CitationSource {
+    pub title: String,
+    pub url: String,
+    /// Data de acesso (YYYY-MM-DD)
+    pub accessed: String,
+    /// Data de publicação (YYYY-MM-DD), quando extraída da página
+    pub published: Option<String>,
+}
+
+/// Coleta as fontes de todas as mensagens, sem duplicatas de URL e na ordem em que aparecem
+pub fn collect_sources(messages: &[ChatMessage]) -> Vec<CitationSource> {
+    let mut sources: Vec<CitationSource> = Vec::new();
+
+    for message in messages {
+        let metadata = match message.metadata.as_deref().map(serde_json::from_str::<Value>) {
+            Some(Ok(value)) => value,
+            _ => continue,
+        };
+        let accessed = message.created_at.format("%Y-%m-%d").to_string();
+
+        let mut found = Vec::new();
+        find_source_arrays(&metadata, &mut found);
+
+        for entry in found {
+            let source = match source_from_value(entry, &accessed) {
+                Some(source) => source,
+                None => continue,
+            };
+            match sources.iter_mut().find(|s| s.url == source.url) {
+                // Mantém o primeiro acesso, mas aproveita a data de publicação se só veio depois
+                Some(existing) => {
+                    if existing.published.is_none() {
+                        existing.published = source.published;
+                    }
+                }
+                None => sources.push(source),
+            }
+        }
+    }
+
+    sources
+}
+
+/// URLs das fontes em um valor de metadados, na ordem em que aparecem (numeração `[n]`)
+pub fn source_urls(metadata: &Value) -> Vec<String> {
+    let mut found = Vec::new();
+    find_source_arrays(metadata, &mut found);
+    found
+        .into_iter()
+        .filter_map(|entry| source_from_value(entry, ""))
+        .map(|source| source.url)
+        .collect()
+}
+
+/// Procura arrays `sources` em qualquer nível dos metadados (etapas de pesquisa também guardam fontes)
+fn find_source_arrays<'a>(value: &'a Value, found: &mut Vec<&'a Value>) {
+    match value {
+        Value::Object(map) => {
+            for (key, child) in map {
+                if key == "sources" {
+                    if let Value::Array(items) = child {
+                        found.extend(items.iter());
+                        continue;
+                    }
+                }
+                find_source_arrays(child, found);
+            }
+        }
+        Value::Array(items) => {
+            for item in items {
+                find_source_arrays(item, found);
+            }
+        }
+        _ => {}
+    }
+}
+
+fn source_from_value(value: &Value, accessed: &str) -> Option<CitationSource> {
+    let (title, url, published, accessed_at) = match value {
+        Value::String(url) => (None, url.as_str(), None, None),
+        Value::Object(map) => (
+            map.get("title").and_then(|v| v.as_str()),
+            map.get("url").and_then(|v| v.as_str())?,
+            map.get("published_at").and_then(|v| v.as_str()),
+            map.get("accessed_at").and_then(|v| v.as_str()),
+        ),
+        _ => return None,
+    };
+
+    let url = url.trim();
+    if !url.starts_with("http://") && !url.starts_with("https://") {
+        return None;
+    }
+
+    let title = title
+        .map(|t| t.trim())
+        .filter(|t| !t.is_empty())
+        .unwrap_or(url)
+        .to_string();
+
+    Some(CitationSource {
+        title,
+        url: url.to_string(),
+        accessed: accessed_at
+            .and_then(normalize_date)
+            .unwrap_or_else(|| accessed.to_string()),
+        published: published.and_then(normalize_date),
+    })
+}
+
+/// Aceita RFC 3339 ou `YYYY-MM-DD...` e devolve `YYYY-MM-DD`
+pub(crate) fn normalize_date(value: &str) -> Option<String> {
+    if let Ok(dt) = chrono::DateTime::parse_from_rfc3339(value.trim()) {
+        return Some(dt.format("%Y-%m-%d").to_string());
+    }
+    let prefix: String = value.trim().chars().take(10).collect();
+    chrono::NaiveDate::parse_from_str(&prefix, "%Y-%m-%d")
+        .ok()
+        .map(|d| d.format("%Y-%m-%d").to_string())
+}
+
+fn host_of(url: &str) -> Option<String> {
+    url::Url::parse(url)
+        .ok()
+        .and_then(|u| u.host_str().map(|h| h.trim_start_matches("www.").to_string()))
+}
+
+/// Chave BibTeX estável: domínio + ano (publicação ou acesso) + posição
+fn citation_key(source: &CitationSource, index: usize) -> String {
+    let host: String = host_of(&source.url)
+        .unwrap_or_else(|| "fonte".to_string())
+        .chars()
+        .filter(|c| c.is_ascii_alphanumeric())
+        .collect();
+    let year = source
+        .published
+        .as_deref()
+        .unwrap_or(&source.accessed)
+        .chars()
+        .take(4)
+        .collect::<String>();
+    format!("{}{}_{}", host, year, index + 1)
+}
+
+fn escape_bibtex(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '\\' => escaped.push_str("\\textbackslash{}"),
+            '{' | '}' | '&' | '%' | '$' | '#' | '_' => {
+                escaped.push('\\');
+                escaped.push(c);
+            }
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Entradas `@misc` (compatível com BibTeX clássico e biblatex via `url`/`urldate`)
+pub fn to_bibtex(sources: &[CitationSource]) -> String {
+    let mut out = String::new();
+
+    for (index, source) in sources.iter().enumerate() {
+        out.push_str(&format!("@misc{{{},\n", citation_key(source, index)));
+        out.push_str(&format!("  title = {{{}}},\n", escape_bibtex(&source.title)));
+        if let Some(host) = host_of(&source.url) {
+            out.push_str(&format!("  publisher = {{{}}},\n", escape_bibtex(&host)));
+        }
+        if let Some(published) = &source.published {
+            out.push_str(&format!("  date = {{{}}},\n", published));
+            out.push_str(&format!("  year = {{{}}},\n", &published[..4]));
+        }
+        out.push_str(&format!("  url = {{{}}},\n", source.url));
+        out.push_str(&format!("  howpublished = {{\\url{{{}}}}},\n", source.url));
+        out.push_str(&format!("  urldate = {{{}}},\n", source.accessed));
+        out.push_str(&format!("  note = {{Acessado em {}}}\n", source.accessed));
+        out.push_str("}\n\n");
+    }
+
+    out.trim_end().to_string()
+}
+
+fn date_parts(date: &str) -> Value {
+    let parts: Vec<u32> = date.split('-').filter_map(|p| p.parse().ok()).collect();
+    serde_json::json!({ "date-parts": [parts] })
+}
+
+/// Lista CSL-JSON (tipo `webpage`), aceita por Zotero, Pandoc e afins
+pub fn to_csl_json(sources: &[CitationSource]) -> Result<String, String> {
+    let items: Vec<Value> = sources
+        .iter()
+        .enumerate()
+        .map(|(index, source)| {
+            let mut item = serde_json::json!({
+                "id": citation_key(source, index),
+                "type": "webpage",
+                "title": source.title,
+                "URL": source.url,
+                "accessed": date_parts(&source.accessed),
+            });
+            if let Some(host) = host_of(&source.url) {
+                item["container-title"] = Value::String(host);
+            }
+            if let Some(published) = &source.published {
+                item["issued"] = date_parts(published);
+            }
+            item
+        })
+        .collect();
+
+    serde_json::to_string_pretty(&items)
+        .map_err(|e| format!("Failed to serialize citations: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{TimeZone, Utc};
+
+    fn message(metadata: &str) -> ChatMessage {
+        ChatMessage {
+            id: None,
+            session_id: "s1".to_string(),
+            role: "assistant".to_string(),
+            content: String::new(),
+            metadata: Some(metadata.to_string()),
+            created_at: Utc.with_ymd_and_hms(2024, 3, 5, 12, 0, 0).unwrap(),
+        }
+    }
+
+    #[test]
+    fn test_collect_and_format() {
+        let messages = vec![
+            message(r#"{"sources":[{"title":"Rust {Book}","url":"https://www.rust-lang.org/learn"}]}"#),
+            message(r#"{"steps":[{"sources":[{"title":"Dup","url":"https://www.rust-lang.org/learn","published_at":"2023-11-02T10:00:00Z"},"https://example.com/a"]}]}"#),
+        ];
+
+        let sources = collect_sources(&messages);
+        assert_eq!(sources.len(), 2);
+        assert_eq!(sources[0].title, "Rust {Book}");
+        assert_eq!(sources[0].published.as_deref(), Some("2023-11-02"));
+        assert_eq!(sources[1].title, "https://example.com/a");
+        assert_eq!(sources[1].accessed, "2024-03-05");
+
+        let bib = to_bibtex(&sources);
+        assert!(bib.starts_with("@misc{rustlangorg2023_1,"));
+        assert!(bib.contains("title = {Rust \\{Book\\}}"));
+        assert!(bib.contains("urldate = {2024-03-05}"));
+
+        let csl: Value = serde_json::from_str(&to_csl_json(&sources).unwrap()).unwrap();
+        assert_eq!(csl[0]["issued"]["date-parts"][0][0], 2023);
+        assert_eq!(csl[1]["accessed"]["date-parts"][0], serde_json::json!([2024, 3, 5]));
+    }
+}
@@ -0,0 +1,401 @@
+//! Extração de blocos de código das respostas e execução de snippets
+//!
+//! Os snippets rodam em um subprocesso com diretório temporário próprio, ambiente mínimo,
+//! timeout e (por padrão) sem acesso à rede.
+
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use std::time::{Duration, Instant};
+use tokio::io::{AsyncRead, AsyncReadExt};
+
+pub const DEFAULT_TIMEOUT_SECS: u64 = 10;
+pub const MAX_TIMEOUT_SECS: u64 = 120;
+/// Limite de saída devolvida por stream (stdout/stderr)
+const MAX_OUTPUT_BYTES: usize = 64 * 1024;
+/// Prazo para esvaziar os pipes depois que o interpretador termina
+const PIPE_DRAIN_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// Bloco de código cercado (```lang ... ```) em uma mensagem
+#[derive(Serialize, Clone, Debug, PartialEq)]
+pub struct CodeBlock {
+    pub index: usize,
+    pub language: Option<String>,
+    pub code: String,
+    /// Se `run_code_snippet` sabe executar a linguagem
+    pub runnable: bool,
+}
+
+#[derive(Serialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum SnippetLanguage {
+    Python,
+    Node,
+    /// sh/bash (não existe no Windows)
+    Shell,
+    /// `pwsh` ou, no Windows, `powershell`
+    #[serde(rename = "powershell")]
+    PowerShell,
+}
+
+impl SnippetLanguage {
+    pub fn parse(language: &str) -> Option<Self> {
+        match language.trim().to_lowercase().as_str() {
+            "python" | "python3" | "py" => Some(Self::Python),
+            "javascript" | "js" | "node" | "nodejs" | "mjs" => Some(Self::Node),
+            "sh" | "bash" | "shell" | "zsh" | "console" => Some(Self::Shell),
+            "powershell" | "ps1" | "pwsh" => Some(Self::PowerShell),
+            _ => None,
+        }
+    }
+
+    /// Se a linguagem pode rodar nesta plataforma (scripts sh não rodam no Windows)
+    pub fn supported_on_platform(&self) -> bool {
+        !(matches!(self, Self::Shell) && cfg!(target_os = "windows"))
+    }
+
+    fn script_name(&self) -> &'static str {
+        match self {
+            Self::Python => "main.py",
+            Self::Node => "main.js",
+            Self::Shell => "main.sh",
+            Self::PowerShell => "main.ps1",
+        }
+    }
+
+    /// Interpretador encontrado no PATH e os argumentos antes do script
+    fn interpreter(&self) -> Option<(PathBuf, Vec<&'static str>)> {
+        let find = |names: &[&str]| names.iter().find_map(|name| crate::stt::find_in_path(name));
+        match self {
+            Self::Python => find(&["python3", "python"]).map(|p| (p, vec!["-I"])),
+            Self::Node => find(&["node"]).map(|p| (p, vec![])),
+            Self::Shell if cfg!(target_os = "windows") => None,
+            Self::Shell => find(&["bash", "sh"]).map(|p| (p, vec![])),
+            Self::PowerShell => find(&["pwsh", "powershell"])
+                .map(|p| (p, vec!["-NoProfile", "-NonInteractive", "-ExecutionPolicy", "Bypass", "-File"])),
+        }
+    }
+}
+
+/// Resultado da execução de um snippet
+#[derive(Serialize, Clone, Debug)]
+pub struct RunResult {
+    pub language: SnippetLanguage,
+    /// None quando o processo foi encerrado por timeout ou sinal
+    pub exit_code: Option<i32>,
+    pub stdout: String,
+    pub stderr: String,
+    pub timed_out: bool,
+    /// A saída passou do limite e foi cortada
+    pub truncated: bool,
+    pub network_isolated: bool,
+    pub duration_ms: u64,
+}
+
+/// Blocos de código cercados por ``` ou ~~~ (um bloco sem fechamento vai até o fim do texto)
+pub fn extract_code_blocks(markdown: &str) -> Vec<CodeBlock> {
+    let mut blocks = Vec::new();
+    let mut current: Option<(&str, Option<String>, Vec<&str>)> = None;
+
+    for line in markdown.lines() {
+        let trimmed = line.trim_start();
+        match current.take() {
+            None => {
+                if let Some(fence) = ["```", "~~~"].into_iter().find(|f| trimmed.starts_with(f)) {
+                    let info = trimmed.trim_start_matches(fence.chars().next().unwrap_or('`')).trim();
+                    let language = info.split_whitespace().next().map(|l| l.to_lowercase());
+                    current = Some((fence, language, Vec::new()));
+                }
+            }
+            Some((fence, language, lines)) if trimmed.starts_with(fence) && trimmed.trim_start_matches(&fence[..1]).trim().is_empty() => {
+                blocks.push(code_block(blocks.len(), language, &lines));
+            }
+            Some((fence, language, mut lines)) => {
+                lines.push(line);
+                current = Some((fence, language, lines));
+            }
+        }
+    }
+    if let Some((_, language, lines)) = current {
+        blocks.push(code_block(blocks.len(), language, &lines));
+    }
+    blocks
+}
+
+fn code_block(index: usize, language: Option<String>, lines: &[&str]) -> CodeBlock {
+    CodeBlock {
+        index,
+        runnable: language.as_deref().and_then(SnippetLanguage::parse).is_some_and(|l| l.supported_on_platform()),
+        language,
+        code: lines.join("\n"),
+    }
+}
+
+/// Prefixo que executa o comando sem rede (namespace de rede vazio no Linux,
+/// perfil do sandbox-exec no macOS). None se a plataforma não oferece isolamento.
+fn network_isolation_prefix() -> Option<Vec<String>> {
+    if cfg!(target_os = "linux") {
+        crate::stt::find_in_path("unshare").map(|unshare| {
+            vec![
+                unshare.to_string_lossy().to_string(),
+                "--user".to_string(),
+                "--map-root-user".to_string(),
+                "--net".to_string(),
+                "--".to_string(),
+            ]
+        })
+    } else if cfg!(target_os = "macos") {
+        crate::stt::find_in_path("sandbox-exec").map(|sandbox| {
+            vec![
+                sandbox.to_string_lossy().to_string(),
+                "-p".to_string(),
+                "(version 1)(allow default)(deny network*)".to_string(),
+            ]
+        })
+    } else {
+        None
+    }
+}
+
+/// Lê o próximo pedaço do pipe; sem pipe (fechado ou descartado) nunca completa
+async fn read_chunk<R: AsyncRead + Unpin>(pipe: &mut Option<R>, buf: &mut [u8]) -> std::io::Result<usize> {
+    match pipe {
+        Some(pipe) => pipe.read(buf).await,
+        None => std::future::pending().await,
+    }
+}
+
+/// Acumula o pedaço até MAX_OUTPUT_BYTES; devolve false quando o limite estourou e o pipe deve ser descartado
+fn append_capped(output: &mut Vec<u8>, chunk: &[u8], truncated: &mut bool) -> bool {
+    let room = MAX_OUTPUT_BYTES - output.len();
+    if chunk.len() > room {
+        output.extend_from_slice(&chunk[..room]);
+        *truncated = true;
+        return false;
+    }
+    output.extend_from_slice(chunk);
+    true
+}
+
+/// Encerra o grupo de processos do snippet, incluindo filhos deixados em segundo plano
+#[cfg(not(target_os = "windows"))]
+fn kill_process_group(pid: u32) {
+    // O grupo foi criado com o próprio PID do interpretador (process_group(0))
+    if let Err(e) = std::process::Command::new("kill")
+        .args(["-9", "--", &format!("-{}", pid)])
+        .output()
+    {
+        log::warn!("[CodeRunner] Erro ao encerrar grupo {}: {}", pid, e);
+    }
+}
+
+/// Encerra a árvore de processos do snippet (`taskkill /T` percorre os descendentes)
+#[cfg(target_os = "windows")]
+fn kill_process_group(pid: u32) {
+    use std::os::windows::process::CommandExt;
+    const CREATE_NO_WINDOW: u32 = 0x08000000;
+    if let Err(e) = std::process::Command::new("taskkill")
+        .args(["/F", "/T", "/PID", &pid.to_string()])
+        .creation_flags(CREATE_NO_WINDOW)
+        .output()
+    {
+        log::warn!("[CodeRunner] Erro ao encerrar processo {}: {}", pid, e);
+    }
+}
+
+/// Executa o snippet em um diretório temporário, com ambiente mínimo e timeout
+pub async fn run(language: &str, code: &str, timeout_secs: Option<u64>, allow_network: bool) -> Result<RunResult, String> {
+    let language = SnippetLanguage::parse(language)
+        .ok_or_else(|| format!("Unsupported language: {}", language))?;
+    if !language.supported_on_platform() {
+        return Err(format!("{:?} snippets cannot run on this platform", language));
+    }
+    let (interpreter, interpreter_args) = language.interpreter()
+        .ok_or_else(|| format!("No {:?} interpreter found in PATH", language))?;
+    let isolation = if allow_network {
+        None
+    } else {
+        Some(network_isolation_prefix()
+            .ok_or("Network isolation is not available on this platform; run with network access allowed")?)
+    };
+    let timeout = Duration::from_secs(timeout_secs.unwrap_or(DEFAULT_TIMEOUT_SECS).clamp(1, MAX_TIMEOUT_SECS));
+
+    let workdir = std::env::temp_dir().join(format!("ollahub-run-{}", uuid::Uuid::new_v4()));
+    std::fs::create_dir_all(&workdir)
+        .map_err(|e| format!("Failed to create work dir: {}", e))?;
+    let result = run_in(&workdir, language, &interpreter, &interpreter_args, isolation, code, timeout).await;
+    let _ = std::fs::remove_dir_all(&workdir);
+    result
+}
+
+async fn run_in(
+    workdir: &Path,
+    language: SnippetLanguage,
+    interpreter: &Path,
+    interpreter_args: &[&str],
+    isolation: Option<Vec<String>>,
+    code: &str,
+    timeout: Duration,
+) -> Result<RunResult, String> {
+    let script = workdir.join(language.script_name());
+    std::fs::write(&script, code)
+        .map_err(|e| format!("Failed to write snippet: {}", e))?;
+
+    let network_isolated = isolation.is_some();
+    let mut cmd = match &isolation {
+        Some(prefix) => {
+            let mut cmd = tokio::process::Command::new(&prefix[0]);
+            cmd.args(&prefix[1..]).arg(interpreter);
+            cmd
+        }
+        None => tokio::process::Command::new(interpreter),
+    };
+    cmd.args(interpreter_args)
+        .arg(&script)
+        .current_dir(workdir)
+        .env_clear()
+        .env("HOME", workdir)
+        .env("TMPDIR", workdir)
+        .env("TEMP", workdir)
+        .env("TMP", workdir)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true);
+    // PATH e (no Windows) SystemRoot são necessários para os interpretadores funcionarem
+    for key in ["PATH", "SystemRoot", "LANG"] {
+        if let Some(value) = std::env::var_os(key) {
+            cmd.env(key, value);
+        }
+    }
+    #[cfg(target_os = "windows")]
+    {
+        const CREATE_NO_WINDOW: u32 = 0x08000000;
+        cmd.creation_flags(CREATE_NO_WINDOW);
+    }
+    // Grupo próprio para que o timeout encerre também os processos que o snippet criar
+    #[cfg(unix)]
+    cmd.process_group(0);
+
+    let started = Instant::now();
+    let mut child = cmd.spawn()
+        .map_err(|e| format!("Failed to start interpreter: {}", e))?;
+    let pid = child.id();
+    let mut stdout_pipe = child.stdout.take();
+    let mut stderr_pipe = child.stderr.take();
+
+    // Os pipes são lidos enquanto o snippet roda, então a memória fica limitada a MAX_OUTPUT_BYTES
+    // por stream; estourado o limite, o pipe é descartado e o snippet passa a receber EPIPE
+    let mut stdout = Vec::new();
+    let mut stderr = Vec::new();
+    let mut stdout_buf = [0u8; 8192];
+    let mut stderr_buf = [0u8; 8192];
+    let mut truncated = false;
+    let mut status = None;
+    let mut timed_out = false;
+    let deadline = tokio::time::sleep(timeout);
+    tokio::pin!(deadline);
+    while status.is_none() || stdout_pipe.is_some() || stderr_pipe.is_some() {
+        tokio::select! {
+            read = read_chunk(&mut stdout_pipe, &mut stdout_buf) => match read {
+                Ok(n) if n > 0 && append_capped(&mut stdout, &stdout_buf[..n], &mut truncated) => {}
+                _ => stdout_pipe = None,
+            },
+            read = read_chunk(&mut stderr_pipe, &mut stderr_buf) => match read {
+                Ok(n) if n > 0 && append_capped(&mut stderr, &stderr_buf[..n], &mut truncated) => {}
+                _ => stderr_pipe = None,
+            },
+            exit = child.wait(), if status.is_none() => {
+                status = Some(exit);
+                // Descendentes em segundo plano herdam os pipes; encerra o grupo e esvazia o que sobrou
+                if let Some(pid) = pid {
+                    kill_process_group(pid);
+                }
+                deadline.as_mut().reset(tokio::time::Instant::now() + PIPE_DRAIN_TIMEOUT);
+            }
+            _ = &mut deadline => {
+                if status.is_none() {
+                    timed_out = true;
+                    if let Some(pid) = pid {
+                        kill_process_group(pid);
+                    }
+                    let _ = child.kill().await;
+                }
+                break;
+            }
+        }
+    }
+
+    let exit_code = match status {
+        Some(status) => status.map_err(|e| format!("Failed to wait for snippet: {}", e))?.code(),
+        None => None,
+    };
+    if timed_out {
+        if !stderr.is_empty() {
+            stderr.push(b'\n');
+        }
+        stderr.extend_from_slice(format!("Timed out after {}s", timeout.as_secs()).as_bytes());
+    }
+    let stdout = String::from_utf8_lossy(&stdout).to_string();
+    let stderr = String::from_utf8_lossy(&stderr).to_string();
+    log::info!(
+        "[CodeRunner] Snippet {:?} executado em {:?} (exit {:?}, timeout {})",
+        language,
+        started.elapsed(),
+        exit_code,
+        timed_out
+    );
+
+    Ok(RunResult {
+        language,
+        exit_code,
+        stdout,
+        stderr,
+        timed_out,
+        truncated,
+        network_isolated,
+        duration_ms: started.elapsed().as_millis() as u64,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_extract_code_blocks() {
+        let markdown = "Exemplo:\n```python\nprint('oi')\n\nprint(1)\n```\nTexto\n~~~\nsem linguagem\n~~~\n```js\nconsole.log(1)";
+        let blocks = extract_code_blocks(markdown);
+        assert_eq!(blocks.len(), 3);
+        assert_eq!(blocks[0].language.as_deref(), Some("python"));
+        assert_eq!(blocks[0].code, "print('oi')\n\nprint(1)");
+        assert!(blocks[0].runnable);
+        assert_eq!(blocks[1].language, None);
+        assert!(!blocks[1].runnable);
+        assert_eq!(blocks[2].code, "console.log(1)");
+        assert_eq!(SnippetLanguage::parse("Bash"), Some(SnippetLanguage::Shell));
+        assert_eq!(SnippetLanguage::parse("ps1"), Some(SnippetLanguage::PowerShell));
+        assert_eq!(SnippetLanguage::Shell.supported_on_platform(), !cfg!(target_os = "windows"));
+        assert_eq!(SnippetLanguage::parse("rust"), None);
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_run_limits() {
+        let result = run("sh", "echo oi; exit 3", Some(1), true).await.unwrap();
+        assert_eq!(result.exit_code, Some(3));
+        assert_eq!(result.stdout, "oi\n");
+        assert!(!result.timed_out && !result.truncated);
+
+        let result = run("sh", "yes | head -c 200000", Some(1), true).await.unwrap();
+        assert!(result.truncated);
+        assert_eq!(result.stdout.len(), MAX_OUTPUT_BYTES);
+
+        let result = run("sh", "sleep 30", Some(1), true).await.unwrap();
+        assert!(result.timed_out);
+        assert_eq!(result.exit_code, None);
+        assert!(result.stderr.contains("Timed out after 1s"));
+
+        assert!(run("cobol", "", None, true).await.is_err());
+    }
+}
@@ -0,0 +1,174 @@
+use crate::db::SessionSnapshot;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use tauri::{AppHandle, Manager};
+use zip::write::{FileOptions, ZipWriter};
+use zip::{CompressionMethod, ZipArchive};
+
+/// Idade mínima padrão (dias sem atividade) para mover uma sessão ao arquivo frio
+pub const DEFAULT_ARCHIVE_AFTER_DAYS: u32 = 180;
+/// Limite de palavras-chave guardadas no índice leve por sessão
+const MAX_KEYWORDS: usize = 3000;
+const MIN_KEYWORD_CHARS: usize = 3;
+
+/// Conteúdo de uma sessão dentro do arquivo mensal
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ArchivedPayload {
+    pub archived_at: DateTime<Utc>,
+    pub snapshot: SessionSnapshot,
+    /// Conteúdo do arquivo JSON legado (`chats/{id}.json`), quando existia
+    #[serde(default)]
+    pub legacy_json: Option<String>,
+}
+
+/// Resultado de uma rodada de arquivamento
+#[derive(Serialize, Clone, Debug, Default)]
+pub struct ArchiveReport {
+    pub archived: usize,
+    pub archive_files: Vec<String>,
+    pub bytes_written: u64,
+    pub errors: Vec<String>,
+}
+
+pub fn archive_dir(app_handle: &AppHandle) -> Result<PathBuf, String> {
+    let dir = app_handle
+        .path()
+        .app_data_dir()
+        .map_err(|e| format!("Failed to get app data dir: {}", e))?
+        .join("archive");
+    fs::create_dir_all(&dir).map_err(|e| format!("Failed to create archive dir: {}", e))?;
+    Ok(dir)
+}
+
+/// Arquivo mensal de uma sessão, pelo mês da última atividade
+pub fn archive_file_name(updated_at: &DateTime<Utc>) -> String {
+    format!("sessions-{}.zip", updated_at.format("%Y-%m"))
+}
+
+pub fn entry_name(session_id: &str) -> String {
+    format!("{}.json", session_id)
+}
+
+/// Palavras distintas (minúsculas) usadas pela busca no índice leve. Acima de `MAX_KEYWORDS`
+/// ficam as mais frequentes, para o corte não depender da ordem alfabética.
+pub fn extract_keywords<'a>(texts: impl IntoIterator<Item = &'a str>) -> String {
+    let mut counts: HashMap<String, usize> = HashMap::new();
+    for text in texts {
+        for word in text.split(|c: char| !c.is_alphanumeric()) {
+            if word.chars().count() >= MIN_KEYWORD_CHARS {
+                *counts.entry(word.to_lowercase()).or_insert(0) += 1;
+            }
+        }
+    }
+    let mut words: Vec<(String, usize)> = counts.into_iter().collect();
+    words.sort_by(|(a, a_count), (b, b_count)| b_count.cmp(a_count).then_with(|| a.cmp(b)));
+    words.truncate(MAX_KEYWORDS);
+    let mut words: Vec<String> = words.into_iter().map(|(word, _)| word).collect();
+    words.sort();
+    words.join(" ")
+}
+
+/// Textos da sessão indexáveis (título e conteúdo das mensagens)
+pub fn snapshot_texts(snapshot: &SessionSnapshot) -> Vec<&str> {
+    let mut texts: Vec<&str> = snapshot
+        .session
+        .get("title")
+        .and_then(|t| t.as_str())
+        .into_iter()
+        .collect();
+    if let Some(messages) = snapshot.children.get("messages") {
+        texts.extend(messages.iter().filter_map(|m| m.get("content").and_then(|c| c.as_str())));
+    }
+    texts
+}
+
+/// Reescreve um arquivo mensal removendo e adicionando entradas. A escrita vai para um
+/// arquivo temporário e só substitui o original depois de concluída.
+pub fn update_archive(path: &Path, remove: &HashSet<String>, add: &[(String, Vec<u8>)]) -> Result<u64, String> {
+    let tmp_path = path.with_extension("zip.tmp");
+    let tmp = fs::File::create(&tmp_path).map_err(|e| format!("Failed to create archive file: {}", e))?;
+    let mut writer = ZipWriter::new(tmp);
+    let options = FileOptions::default().compression_method(CompressionMethod::Deflated);
+    let replaced: HashSet<&str> = add.iter().map(|(name, _)| name.as_str()).collect();
+
+    if path.exists() {
+        let file = fs::File::open(path).map_err(|e| format!("Failed to open archive: {}", e))?;
+        let mut archive = ZipArchive::new(file).map_err(|e| format!("Failed to read archive: {}", e))?;
+        for i in 0..archive.len() {
+            let entry = archive.by_index_raw(i).map_err(|e| format!("Failed to read archive entry: {}", e))?;
+            if remove.contains(entry.name()) || replaced.contains(entry.name()) {
+                continue;
+            }
+            writer
+                .raw_copy_file(entry)
+                .map_err(|e| format!("Failed to copy archive entry: {}", e))?;
+        }
+    }
+
+    for (name, bytes) in add {
+        writer
+            .start_file(name.as_str(), options)
+            .map_err(|e| format!("Failed to add file to archive: {}", e))?;
+        writer
+            .write_all(bytes)
+            .map_err(|e| format!("Failed to write file to archive: {}", e))?;
+    }
+
+    let file = writer.finish().map_err(|e| format!("Failed to finish archive: {}", e))?;
+    let size = file.metadata().map(|m| m.len()).unwrap_or(0);
+    drop(file);
+
+    let empty = size <= 22; // só o registro final do ZIP
+    if empty {
+        let _ = fs::remove_file(&tmp_path);
+        if path.exists() {
+            fs::remove_file(path).map_err(|e| format!("Failed to remove empty archive: {}", e))?;
+        }
+        return Ok(0);
+    }
+    fs::rename(&tmp_path, path).map_err(|e| format!("Failed to replace archive: {}", e))?;
+    Ok(size)
+}
+
+pub fn read_payload(path: &Path, session_id: &str) -> Result<ArchivedPayload, String> {
+    let file = fs::File::open(path).map_err(|e| format!("Failed to open archive: {}", e))?;
+    let mut archive = ZipArchive::new(file).map_err(|e| format!("Failed to read archive: {}", e))?;
+    let mut entry = archive
+        .by_name(&entry_name(session_id))
+        .map_err(|e| format!("Session {} not found in archive: {}", session_id, e))?;
+    let mut content = String::new();
+    entry
+        .read_to_string(&mut content)
+        .map_err(|e| format!("Failed to read archived session: {}", e))?;
+    serde_json::from_str(&content).map_err(|e| format!("Failed to parse archived session: {}", e))
+}
+
+/// Agrupa entradas por arquivo mensal para reescrever cada arquivo uma única vez
+pub fn group_by_file<T>(items: Vec<(String, T)>) -> HashMap<String, Vec<T>> {
+    let mut grouped: HashMap<String, Vec<T>> = HashMap::new();
+    for (file, item) in items {
+        grouped.entry(file).or_default().push(item);
+    }
+    grouped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn test_keywords_and_file_names() {
+        assert_eq!(extract_keywords(["Olá, mundo! O MUNDO é de Rust", "rust ok"]), "mundo olá rust");
+        let long: String = (0..MAX_KEYWORDS + 10).map(|i| format!("aaa{} ", i)).collect();
+        let keywords = extract_keywords([long.as_str(), "zebra zebra"]);
+        assert!(keywords.split(' ').any(|w| w == "zebra"));
+        assert_eq!(keywords.split(' ').count(), MAX_KEYWORDS);
+        let date = Utc.with_ymd_and_hms(2024, 3, 31, 23, 59, 0).unwrap();
+        assert_eq!(archive_file_name(&date), "sessions-2024-03.zip");
+    }
+}
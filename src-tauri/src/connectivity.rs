@@ -0,0 +1,66 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+use tauri::{AppHandle, Emitter};
+
+/// Endereços usados para testar a conexão (DNS públicos, sem depender de resolução de nomes)
+const PROBE_ADDRS: [&str; 2] = ["1.1.1.1:443", "8.8.8.8:53"];
+/// Tempo máximo de cada tentativa de conexão
+const PROBE_TIMEOUT: Duration = Duration::from_secs(3);
+/// Intervalo entre verificações do watcher
+const WATCH_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Último estado observado (assume online até a primeira verificação)
+static ONLINE: AtomicBool = AtomicBool::new(true);
+static WATCHER_STARTED: AtomicBool = AtomicBool::new(false);
+
+/// Testa a conexão abrindo um socket TCP para algum dos endereços conhecidos
+pub async fn check_online() -> bool {
+    for addr in PROBE_ADDRS {
+        if let Ok(Ok(_)) = tokio::time::timeout(PROBE_TIMEOUT, tokio::net::TcpStream::connect(addr)).await {
+            return true;
+        }
+    }
+    false
+}
+
+/// Último estado conhecido da rede, sem fazer nova verificação
+pub fn is_online() -> bool {
+    ONLINE.load(Ordering::Relaxed)
+}
+
+/// Inicia (uma única vez) o watcher que emite `connectivity-changed` e chama
+/// `on_restored` sempre que a rede volta depois de uma queda
+pub fn start_watcher<F, Fut>(app_handle: AppHandle, on_restored: F)
+where
+    F: Fn(AppHandle) -> Fut + Send + 'static,
+    Fut: std::future::Future<Output = ()> + Send + 'static,
+{
+    if WATCHER_STARTED.swap(true, Ordering::SeqCst) {
+        return;
+    }
+
+    tauri::async_runtime::spawn(async move {
+        let mut interval = tokio::time::interval(WATCH_INTERVAL);
+        loop {
+            interval.tick().await;
+
+            let online = check_online().await;
+            let was_online = ONLINE.swap(online, Ordering::Relaxed);
+            if online == was_online {
+                continue;
+            }
+
+            log::info!("[Connectivity] Rede {}", if online { "restabelecida" } else { "indisponível" });
+            let _ = app_handle.emit("connectivity-changed", online);
+
+            if online {
+                on_restored(app_handle.clone()).await;
+            }
+        }
+    });
+}
+
+/// Registra uma falha de rede observada fora do watcher (ex: task que não conseguiu conectar)
+pub fn mark_offline() {
+    ONLINE.store(false, Ordering::Relaxed);
+}
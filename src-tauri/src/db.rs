@@ -1,7 +1,24 @@
-use rusqlite::{Connection, Result as SqliteResult, params};
+use rusqlite::{Result as SqliteResult, params};
+use r2d2_sqlite::SqliteConnectionManager;
 use serde::{Serialize, Deserialize};
 use chrono::{DateTime, Utc};
 use tauri::{AppHandle, Manager};
+use crate::api_tokens::ApiTokenScope;
+use crate::system_monitor::MetricSample;
+use crate::engine_health::EngineHealth;
+use crate::analytics::{DailyMessages, ModelUsage, TagCount, TaskRunStats};
+use crate::audit::AuditEntry;
+
+/// Converte uma coluna TEXT rfc3339 opcional em DateTime<Utc>
+fn parse_optional_datetime(value: Option<String>, idx: usize) -> SqliteResult<Option<DateTime<Utc>>> {
+    value
+        .map(|v| {
+            DateTime::parse_from_rfc3339(&v)
+                .map(|d| d.with_timezone(&Utc))
+                .map_err(|_| rusqlite::Error::InvalidColumnType(idx, "TEXT".to_string(), rusqlite::types::Type::Text))
+        })
+        .transpose()
+}
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct ChatSession {
@@ -22,6 +39,631 @@ pub struct ChatMessage {
     pub created_at: DateTime<Utc>,
 }
 
+/// Token de API (sem o valor em texto puro, apenas metadados)
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ApiToken {
+    pub id: String,
+    pub name: String,
+    pub scope: ApiTokenScope,
+    /// Primeiros caracteres do token, para identificação na UI
+    pub token_prefix: String,
+    pub created_at: DateTime<Utc>,
+    pub last_used_at: Option<DateTime<Utc>>,
+    pub revoked_at: Option<DateTime<Utc>>,
+}
+
+/// Preset de prompt reutilizável (persona/instruções com variáveis `{{nome}}`)
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct PromptPreset {
+    pub id: String,
+    pub name: String,
+    pub content: String,
+    /// Valores padrão das variáveis do preset
+    #[serde(default)]
+    pub variables: std::collections::HashMap<String, String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Página extraída guardada no cache do scraper (chave = hash SHA-256 da URL)
+#[derive(Debug, Clone)]
+pub struct CachedPage {
+    pub url: String,
+    pub title: String,
+    pub content: String,
+    pub markdown: String,
+    pub published_at: Option<String>,
+    pub etag: Option<String>,
+    pub fetched_at: DateTime<Utc>,
+}
+
+/// Texto grande colado no chat, guardado como documento e referenciado no prompt
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct PasteAttachment {
+    pub id: String,
+    pub session_id: Option<String>,
+    pub title: String,
+    pub content: String,
+    pub summary: Option<String>,
+    pub has_embedding: bool,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Cópia do conteúdo de uma fonte no momento em que ela embasou uma resposta
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct MessageSource {
+    pub id: String,
+    pub message_id: i64,
+    pub session_id: Option<String>,
+    pub url: String,
+    pub title: String,
+    /// Markdown completo lido pelo modelo
+    pub content: String,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Saída de uma task aguardando o próximo resumo diário
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct DigestItem {
+    pub id: i64,
+    /// "research_brief", "feed_item", "model_update" ou "task_output"
+    pub kind: String,
+    pub task_id: Option<String>,
+    pub title: String,
+    pub content: String,
+    pub sources: Vec<crate::digests::DigestSource>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Resposta em streaming ainda não finalizada (checkpoint para recuperação após crash)
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct PendingMessage {
+    pub session_id: String,
+    pub model: String,
+    /// Mensagens enviadas na rodada (JSON), ainda não persistidas
+    pub request_messages: String,
+    pub content: String,
+    pub tokens: i64,
+    pub started_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Pasta local anexada a uma sessão
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Workspace {
+    pub session_id: String,
+    pub root_path: String,
+    pub attached_at: DateTime<Utc>,
+    pub indexed_at: Option<DateTime<Utc>>,
+}
+
+/// Trecho de arquivo indexado de um workspace
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct WorkspaceChunk {
+    pub id: String,
+    /// Caminho relativo à raiz do workspace
+    pub path: String,
+    /// `caminho:linha_inicial-linha_final`
+    pub title: String,
+    pub content: String,
+    #[serde(skip)]
+    pub embedding: Option<Vec<u8>>,
+}
+
+/// Mensagem pré-definida de um template de conversa
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct TemplateMessage {
+    pub role: String,
+    pub content: String,
+}
+
+/// Escopo de RAG/busca web de uma conversa
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct RagScope {
+    #[serde(default)]
+    pub enabled: bool,
+    /// Domínios de fontes a considerar, incluindo subdomínios (vazio = todas)
+    #[serde(default)]
+    pub sources: Vec<String>,
+}
+
+impl RagScope {
+    /// Indica se a URL pertence a um dos domínios do escopo
+    pub fn allows(&self, url: &str) -> bool {
+        if self.sources.is_empty() {
+            return true;
+        }
+        let Some(host) = url::Url::parse(url).ok().and_then(|u| u.host_str().map(|h| h.to_lowercase())) else {
+            return false;
+        };
+        let host = host.trim_start_matches("www.");
+        self.sources.iter().any(|source| {
+            let source = source.trim().trim_start_matches("www.").to_lowercase();
+            !source.is_empty() && (host == source || host.ends_with(&format!(".{}", source)))
+        })
+    }
+}
+
+/// Template de conversa: system prompt, mensagens iniciais, modelo e escopo de RAG
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ConversationTemplate {
+    pub id: String,
+    pub name: String,
+    pub description: Option<String>,
+    pub system_prompt: Option<String>,
+    #[serde(default)]
+    pub messages: Vec<TemplateMessage>,
+    pub model: Option<String>,
+    pub rag_scope: Option<RagScope>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Dados editáveis de um template de conversa (id ausente = novo template)
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ConversationTemplateInput {
+    #[serde(default)]
+    pub id: Option<String>,
+    pub name: String,
+    #[serde(default)]
+    pub description: Option<String>,
+    #[serde(default)]
+    pub system_prompt: Option<String>,
+    #[serde(default)]
+    pub messages: Vec<TemplateMessage>,
+    #[serde(default)]
+    pub model: Option<String>,
+    #[serde(default)]
+    pub rag_scope: Option<RagScope>,
+}
+
+/// Configuração própria de uma sessão (definida ao criar a partir de um template)
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct SessionConfig {
+    pub template_id: Option<String>,
+    pub model: Option<String>,
+    pub system_prompt: Option<String>,
+    pub rag_scope: Option<RagScope>,
+    /// Opções de geração da sessão (sobrescrevem `settings.generation_defaults`)
+    #[serde(default)]
+    pub generation_options: Option<crate::ollama_client::GenerationOptions>,
+}
+
+/// Tipo de manutenção do banco
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum MaintenanceMode {
+    /// Checkpoint do WAL + quick_check
+    Quick,
+    /// Checkpoint, VACUUM, REINDEX e integrity_check completo
+    #[default]
+    Full,
+}
+
+/// Resultado de `run_maintenance` (tamanhos em bytes)
+#[derive(Debug, Serialize, Clone)]
+pub struct MaintenanceReport {
+    pub mode: MaintenanceMode,
+    pub size_before: u64,
+    pub size_after: u64,
+    pub wal_size_before: u64,
+    pub wal_size_after: u64,
+    /// O checkpoint não foi completo porque havia leitores/escritores ativos
+    pub checkpoint_busy: bool,
+    pub integrity_ok: bool,
+    /// Problemas reportados pelo integrity_check (vazio se ok)
+    pub findings: Vec<String>,
+    pub duration_ms: u64,
+}
+
+/// Lote de mensagens removidas por `truncate_session_after` (recuperável até expirar)
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct TruncationBatch {
+    pub batch_id: String,
+    pub session_id: String,
+    pub message_count: usize,
+    pub truncated_at: DateTime<Utc>,
+}
+
+/// Organização de uma sessão (tags, pasta, arquivamento e template)
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq)]
+pub struct SessionOrganization {
+    pub tags: Vec<String>,
+    pub folder: Option<String>,
+    pub archived: bool,
+    /// Sessão modelo: duplicá-la copia a configuração, mas começa sem mensagens
+    #[serde(default)]
+    pub template: bool,
+    /// Sessão restrita ao que roda localmente (sem RAG, busca web ou ferramentas de rede)
+    #[serde(default)]
+    pub offline: bool,
+}
+
+/// Filtros de organização para listagem e busca de sessões
+#[derive(Debug, Deserialize, Clone, Default)]
+pub struct SessionFilter {
+    /// A sessão precisa ter todas as tags informadas
+    #[serde(default)]
+    pub tags: Vec<String>,
+    #[serde(default)]
+    pub folder: Option<String>,
+    /// Sessões arquivadas ficam ocultas por padrão
+    #[serde(default)]
+    pub include_archived: bool,
+    /// Retorna apenas sessões arquivadas
+    #[serde(default)]
+    pub archived_only: bool,
+    /// Retorna apenas sessões marcadas como template
+    #[serde(default)]
+    pub templates_only: bool,
+}
+
+impl SessionFilter {
+    pub fn matches(&self, org: &SessionOrganization) -> bool {
+        if org.archived && !self.include_archived && !self.archived_only {
+            return false;
+        }
+        if self.archived_only && !org.archived {
+            return false;
+        }
+        if self.templates_only && !org.template {
+            return false;
+        }
+        if let Some(folder) = &self.folder {
+            if org.folder.as_deref() != Some(folder.as_str()) {
+                return false;
+            }
+        }
+        self.tags.iter().all(|tag| org.tags.iter().any(|t| t.eq_ignore_ascii_case(tag)))
+    }
+}
+
+/// Trecho de uma mensagem que casou com a busca (offsets em unidades UTF-16,
+/// compatíveis com `String.prototype.slice` no frontend)
+#[derive(Debug, Serialize, Clone, PartialEq)]
+pub struct MatchRange {
+    pub start: usize,
+    pub end: usize,
+}
+
+/// Mensagem de uma sessão que casou com a busca
+#[derive(Debug, Serialize, Clone)]
+pub struct MessageSearchMatch {
+    pub message_id: i64,
+    pub role: String,
+    pub created_at: DateTime<Utc>,
+    /// Trecho com os termos envolvidos em `<mark>...</mark>`
+    pub snippet: String,
+    pub ranges: Vec<MatchRange>,
+}
+
+/// Mensagem que casou com a busca global (alvo de navegação na UI)
+#[derive(Debug, Serialize, Clone)]
+pub struct MessageHit {
+    pub message_id: i64,
+    pub ranges: Vec<MatchRange>,
+}
+
+/// Mensagens de uma sessão que casaram com a busca global
+#[derive(Debug, Serialize, Clone, Default)]
+pub struct SessionHits {
+    /// Total de mensagens que casaram (pode ser maior que `hits.len()`)
+    pub total: usize,
+    pub hits: Vec<MessageHit>,
+}
+
+/// Marcadores usados no highlight() para calcular offsets (não aparecem em texto normal)
+const HIGHLIGHT_OPEN: char = '\u{1}';
+const HIGHLIGHT_CLOSE: char = '\u{2}';
+
+/// Converte o texto retornado por highlight() em offsets sobre o conteúdo original
+fn parse_highlight_ranges(highlighted: &str) -> Vec<MatchRange> {
+    let mut ranges = Vec::new();
+    let mut offset = 0usize;
+    let mut start = None;
+    
+    for c in highlighted.chars() {
+        match c {
+            HIGHLIGHT_OPEN => start = Some(offset),
+            HIGHLIGHT_CLOSE => {
+                if let Some(s) = start.take() {
+                    ranges.push(MatchRange { start: s, end: offset });
+                }
+            }
+            _ => offset += c.len_utf16(),
+        }
+    }
+    ranges
+}
+
+/// Monta uma query FTS5 em que cada termo é tratado literalmente (todos obrigatórios)
+fn build_fts_terms_query(query: &str) -> String {
+    query
+        .split_whitespace()
+        .map(|term| format!("\"{}\"", term.replace('"', "\"\"")))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Erro de sintaxe em uma busca avançada (posição em caracteres da query original)
+#[derive(Debug, Serialize, Clone, PartialEq)]
+pub struct FtsSyntaxError {
+    pub message: String,
+    pub position: usize,
+}
+
+impl std::fmt::Display for FtsSyntaxError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} (posição {})", self.message, self.position)
+    }
+}
+
+fn fts_error(message: &str, position: usize) -> FtsSyntaxError {
+    FtsSyntaxError { message: message.to_string(), position }
+}
+
+/// Lê uma frase entre aspas a partir de `start` (que aponta para a aspa de abertura)
+fn read_fts_phrase(chars: &[char], start: usize) -> Result<(String, usize), FtsSyntaxError> {
+    let mut end = start + 1;
+    while end < chars.len() && chars[end] != '"' {
+        end += 1;
+    }
+    if end >= chars.len() {
+        return Err(fts_error("Aspas sem fechamento", start));
+    }
+    let phrase: String = chars[start + 1..end].iter().collect();
+    if phrase.trim().is_empty() {
+        return Err(fts_error("Frase vazia entre aspas", start));
+    }
+    Ok((phrase, end + 1))
+}
+
+/// Lê um termo simples (até espaço, aspas ou parênteses; dentro de NEAR também até vírgula)
+fn read_fts_term(chars: &[char], start: usize, in_near: bool) -> (String, usize) {
+    let mut end = start;
+    while end < chars.len()
+        && !chars[end].is_whitespace()
+        && !matches!(chars[end], '"' | '(' | ')')
+        && !(in_near && chars[end] == ',')
+    {
+        end += 1;
+    }
+    (chars[start..end].iter().collect(), end)
+}
+
+/// Termo ou frase literal em FTS5 (aspas escapadas), com `*` para prefixo
+fn quote_fts_literal(text: &str, prefix: bool) -> String {
+    format!("\"{}\"{}", text.replace('"', "\"\""), if prefix { "*" } else { "" })
+}
+
+/// Lê um grupo `NEAR(a b "c d", N)` a partir do `(`
+fn read_fts_near(chars: &[char], open: usize) -> Result<(String, usize), FtsSyntaxError> {
+    let mut i = open + 1;
+    let mut items = Vec::new();
+    let mut distance = None;
+
+    loop {
+        while i < chars.len() && chars[i].is_whitespace() {
+            i += 1;
+        }
+        match chars.get(i) {
+            None => return Err(fts_error("NEAR sem parêntese de fechamento", open)),
+            Some(')') => {
+                i += 1;
+                break;
+            }
+            Some('"') => {
+                let (phrase, next) = read_fts_phrase(chars, i)?;
+                let prefix = chars.get(next) == Some(&'*');
+                items.push(quote_fts_literal(&phrase, prefix));
+                i = if prefix { next + 1 } else { next };
+            }
+            Some(',') => {
+                let mut start = i + 1;
+                while start < chars.len() && chars[start].is_whitespace() {
+                    start += 1;
+                }
+                let (value, next) = read_fts_term(chars, start, true);
+                match value.parse::<u32>() {
+                    Ok(n) if distance.is_none() => distance = Some(n),
+                    _ => return Err(fts_error("Distância do NEAR deve ser um número (ex: NEAR(a b, 10))", i)),
+                }
+                i = next;
+            }
+            Some('(') => return Err(fts_error("Parênteses não são permitidos dentro de NEAR", i)),
+            Some(_) => {
+                if distance.is_some() {
+                    return Err(fts_error("A distância deve ser o último item do NEAR", i));
+                }
+                let (term, next) = read_fts_term(chars, i, true);
+                let prefix = term.len() > 1 && term.ends_with('*');
+                let term = if prefix { &term[..term.len() - 1] } else { term.as_str() };
+                if term == "*" {
+                    return Err(fts_error("Prefixo '*' sem termo", i));
+                }
+                items.push(quote_fts_literal(term, prefix));
+                i = next;
+            }
+        }
+    }
+
+    if items.len() < 2 {
+        return Err(fts_error("NEAR precisa de pelo menos dois termos", open));
+    }
+    let group = match distance {
+        Some(n) => format!("NEAR({}, {})", items.join(" "), n),
+        None => format!("NEAR({})", items.join(" ")),
+    };
+    Ok((group, i))
+}
+
+/// Converte a busca do usuário em uma query FTS5 segura, validando a sintaxe.
+/// Suporta frases entre aspas, `AND`/`OR`/`NOT` (maiúsculos), `NEAR(...)`, `prefixo*`
+/// e parênteses; os demais termos são tratados literalmente.
+pub fn build_fts_advanced_query(query: &str) -> Result<String, FtsSyntaxError> {
+    let chars: Vec<char> = query.chars().collect();
+    let mut out: Vec<String> = Vec::new();
+    let mut i = 0;
+    let mut depth: Vec<usize> = Vec::new();
+    // Verdadeiro quando o último item foi um termo/grupo (pode vir operador ou `)`)
+    let mut after_operand = false;
+    let mut pending_operator: Option<(String, usize)> = None;
+    let mut has_operand = false;
+
+    while i < chars.len() {
+        let c = chars[i];
+        if c.is_whitespace() {
+            i += 1;
+            continue;
+        }
+
+        match c {
+            '(' => {
+                depth.push(i);
+                out.push("(".to_string());
+                after_operand = false;
+                pending_operator = None;
+                i += 1;
+            }
+            ')' => {
+                if depth.pop().is_none() {
+                    return Err(fts_error("Parêntese ')' sem abertura", i));
+                }
+                if !after_operand {
+                    return Err(fts_error("Parênteses vazios ou operador sem termo antes de ')'", i));
+                }
+                out.push(")".to_string());
+                i += 1;
+            }
+            '"' => {
+                let (phrase, next) = read_fts_phrase(&chars, i)?;
+                let prefix = chars.get(next) == Some(&'*');
+                out.push(quote_fts_literal(&phrase, prefix));
+                i = if prefix { next + 1 } else { next };
+                after_operand = true;
+                has_operand = true;
+                pending_operator = None;
+            }
+            _ => {
+                let (term, next) = read_fts_term(&chars, i, false);
+                match term.as_str() {
+                    "AND" | "OR" | "NOT" => {
+                        if !after_operand {
+                            return Err(fts_error(&format!("Operador {} precisa de um termo antes", term), i));
+                        }
+                        out.push(term.clone());
+                        after_operand = false;
+                        pending_operator = Some((term, i));
+                        i = next;
+                    }
+                    "NEAR" if chars.get(next) == Some(&'(') => {
+                        let (group, end) = read_fts_near(&chars, next)?;
+                        out.push(group);
+                        i = end;
+                        after_operand = true;
+                        has_operand = true;
+                        pending_operator = None;
+                    }
+                    _ => {
+                        let prefix = term.chars().count() > 1 && term.ends_with('*');
+                        let literal = if prefix { &term[..term.len() - 1] } else { term.as_str() };
+                        if literal == "*" {
+                            return Err(fts_error("Prefixo '*' sem termo", i));
+                        }
+                        out.push(quote_fts_literal(literal, prefix));
+                        i = next;
+                        after_operand = true;
+                        has_operand = true;
+                        pending_operator = None;
+                    }
+                }
+            }
+        }
+    }
+
+    if let Some((operator, position)) = pending_operator {
+        return Err(fts_error(&format!("Operador {} precisa de um termo depois", operator), position));
+    }
+    if let Some(open) = depth.pop() {
+        return Err(fts_error("Parêntese '(' sem fechamento", open));
+    }
+    if !has_operand {
+        return Err(fts_error("Busca sem termos", 0));
+    }
+    Ok(out.join(" "))
+}
+
+/// Texto da busca sem operadores nem sintaxe FTS (usado no fallback com LIKE)
+fn fts_plain_text(query: &str) -> String {
+    query
+        .split(|c: char| c.is_whitespace() || matches!(c, '"' | '(' | ')'))
+        .map(|t| t.trim_end_matches('*'))
+        .filter(|t| !t.is_empty() && !matches!(*t, "AND" | "OR" | "NOT" | "NEAR"))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Tabelas com linhas vinculadas a uma sessão (copiadas junto com ela para o arquivo frio)
+const SESSION_CHILD_TABLES: &[&str] = &["messages", "rag_documents", "truncated_messages"];
+
+/// Linha de tabela serializada coluna a coluna (BLOBs como `{"$blob": "<base64>"}`)
+pub type JsonRow = serde_json::Map<String, serde_json::Value>;
+
+/// Cópia completa de uma sessão e das linhas vinculadas a ela
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct SessionSnapshot {
+    pub session: JsonRow,
+    /// Linhas por tabela (`messages`, `rag_documents`, ...)
+    pub children: std::collections::HashMap<String, Vec<JsonRow>>,
+}
+
+/// Entrada do índice de sessões arquivadas
+#[derive(Debug, Serialize, Clone)]
+pub struct ArchivedSession {
+    pub session_id: String,
+    pub title: String,
+    pub emoji: String,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+    pub archived_at: DateTime<Utc>,
+    pub archive_file: String,
+    pub message_count: i64,
+}
+
+fn sql_to_json(value: rusqlite::types::ValueRef<'_>) -> serde_json::Value {
+    use base64::Engine;
+    use rusqlite::types::ValueRef;
+    match value {
+        ValueRef::Null => serde_json::Value::Null,
+        ValueRef::Integer(i) => serde_json::Value::from(i),
+        ValueRef::Real(f) => serde_json::Value::from(f),
+        ValueRef::Text(t) => serde_json::Value::String(String::from_utf8_lossy(t).into_owned()),
+        ValueRef::Blob(b) => serde_json::json!({ "$blob": base64::engine::general_purpose::STANDARD.encode(b) }),
+    }
+}
+
+fn json_to_sql(value: &serde_json::Value) -> rusqlite::types::Value {
+    use base64::Engine;
+    use rusqlite::types::Value;
+    match value {
+        serde_json::Value::Null => Value::Null,
+        serde_json::Value::Bool(b) => Value::Integer(*b as i64),
+        serde_json::Value::Number(n) => match n.as_i64() {
+            Some(i) => Value::Integer(i),
+            None => Value::Real(n.as_f64().unwrap_or_default()),
+        },
+        serde_json::Value::String(s) => Value::Text(s.clone()),
+        serde_json::Value::Object(map) => match map.get("$blob").and_then(|b| b.as_str()) {
+            Some(encoded) => base64::engine::general_purpose::STANDARD
+                .decode(encoded)
+                .map(Value::Blob)
+                .unwrap_or(Value::Null),
+            None => Value::Text(value.to_string()),
+        },
+        serde_json::Value::Array(_) => Value::Text(value.to_string()),
+    }
+}
+
 /// Resultado de busca de sessões com contagem de matches
 #[derive(Debug, Clone)]
 pub struct SearchSessionResult {
@@ -29,50 +671,111 @@ pub struct SearchSessionResult {
     pub match_count: i64,
 }
 
-pub struct Database {
-    conn: Connection,
+/// Pool de conexões compartilhado pelo app (gerenciado pelo Tauri)
+pub type DbPool = r2d2::Pool<SqliteConnectionManager>;
+
+/// Conexões simultâneas no pool (WAL permite leituras em paralelo com uma escrita)
+const POOL_MAX_SIZE: u32 = 4;
+
+fn pool_error(e: r2d2::Error) -> rusqlite::Error {
+    rusqlite::Error::SqliteFailure(
+        rusqlite::ffi::Error::new(rusqlite::ffi::SQLITE_BUSY),
+        Some(format!("Failed to get database connection from pool: {}", e))
+    )
 }
 
-impl Database {
-    /// Cria ou abre conexão com o banco de dados
-    pub fn new(app_handle: &AppHandle) -> SqliteResult<Self> {
-        let app_data_dir = app_handle.path()
-            .app_data_dir()
-            .map_err(|e| {
-                rusqlite::Error::SqliteFailure(
-                    rusqlite::ffi::Error::new(rusqlite::ffi::SQLITE_IOERR),
-                    Some(format!("Failed to get app data dir: {}", e))
-                )
-            })?;
-        
-        std::fs::create_dir_all(&app_data_dir)
-            .map_err(|e| {
-                rusqlite::Error::SqliteFailure(
-                    rusqlite::ffi::Error::new(rusqlite::ffi::SQLITE_IOERR),
-                    Some(format!("Failed to create app data dir: {}", e))
-                )
-            })?;
-        
-        let db_path = app_data_dir.join("ollahub.db");
-        let conn = Connection::open(&db_path)?;
-        
-        // Otimizações de performance do SQLite
-        // WAL mode permite leituras e escritas simultâneas (evita bloqueio da UI)
-        // synchronous=NORMAL reduz fsync sem perder segurança
-        // cache_size maior acelera operações frequentes
-        // temp_store=MEMORY usa RAM para tabelas temporárias
+/// Cria o pool de conexões e inicializa o schema (uma única vez por execução do app)
+pub fn create_pool(app_handle: &AppHandle) -> SqliteResult<DbPool> {
+    let app_data_dir = app_handle.path()
+        .app_data_dir()
+        .map_err(|e| {
+            rusqlite::Error::SqliteFailure(
+                rusqlite::ffi::Error::new(rusqlite::ffi::SQLITE_IOERR),
+                Some(format!("Failed to get app data dir: {}", e))
+            )
+        })?;
+    
+    std::fs::create_dir_all(&app_data_dir)
+        .map_err(|e| {
+            rusqlite::Error::SqliteFailure(
+                rusqlite::ffi::Error::new(rusqlite::ffi::SQLITE_IOERR),
+                Some(format!("Failed to create app data dir: {}", e))
+            )
+        })?;
+    
+    let db_path = app_data_dir.join("ollahub.db");
+    
+    // Otimizações de performance do SQLite (aplicadas a cada conexão do pool)
+    // WAL mode permite leituras e escritas simultâneas (evita bloqueio da UI)
+    // synchronous=NORMAL reduz fsync sem perder segurança
+    // cache_size maior acelera operações frequentes
+    // temp_store=MEMORY usa RAM para tabelas temporárias
+    // busy_timeout evita falhas imediatas quando outra conexão está escrevendo
+    let manager = SqliteConnectionManager::file(&db_path).with_init(|conn| {
         conn.execute_batch(
             "PRAGMA journal_mode=WAL;
              PRAGMA synchronous=NORMAL;
              PRAGMA cache_size=10000;
              PRAGMA temp_store=MEMORY;
-             PRAGMA foreign_keys=ON;"
-        )?;
+             PRAGMA foreign_keys=ON;
+             PRAGMA busy_timeout=5000;"
+        )
+    });
+    
+    let pool = r2d2::Pool::builder()
+        .max_size(POOL_MAX_SIZE)
+        .connection_timeout(std::time::Duration::from_secs(10))
+        .build(manager)
+        .map_err(pool_error)?;
+    
+    // Schema e população do FTS rodam só na criação do pool
+    let db = Database { conn: pool.get().map_err(pool_error)? };
+    db.init_schema()?;
+    
+    Ok(pool)
+}
+
+pub struct Database {
+    conn: r2d2::PooledConnection<SqliteConnectionManager>,
+}
+
+impl Database {
+    /// Obtém uma conexão do pool compartilhado (criando o pool se ainda não existir)
+    pub fn new(app_handle: &AppHandle) -> SqliteResult<Self> {
+        let pool = match app_handle.try_state::<DbPool>() {
+            Some(pool) => pool.inner().clone(),
+            None => {
+                let pool = create_pool(app_handle)?;
+                app_handle.manage(pool.clone());
+                pool
+            }
+        };
         
-        let db = Self { conn };
-        db.init_schema()?;
+        Ok(Self { conn: pool.get().map_err(pool_error)? })
+    }
+    
+    /// Adiciona uma coluna a uma tabela existente (migração idempotente)
+    fn add_column_if_missing(&self, table: &str, column: &str, definition: &str) -> SqliteResult<()> {
+        let exists = {
+            let mut stmt = self.conn.prepare(&format!("PRAGMA table_info({})", table))?;
+            let columns = stmt.query_map([], |row| row.get::<_, String>(1))?;
+            let mut found = false;
+            for name in columns {
+                if name? == column {
+                    found = true;
+                    break;
+                }
+            }
+            found
+        };
         
-        Ok(db)
+        if !exists {
+            self.conn.execute(
+                &format!("ALTER TABLE {} ADD COLUMN {} {}", table, column, definition),
+                [],
+            )?;
+        }
+        Ok(())
     }
     
     /// Inicializa o schema do banco de dados
@@ -117,43 +820,316 @@ impl Database {
             [],
         )?;
         
-        // Índices para performance
-        self.conn.execute(
-            "CREATE INDEX IF NOT EXISTS idx_messages_session_id ON messages(session_id)",
-            [],
-        )?;
+        // Anexos de texto colado reutilizam rag_documents
+        self.add_column_if_missing("rag_documents", "kind", "TEXT")?;
+        self.add_column_if_missing("rag_documents", "title", "TEXT")?;
+        self.add_column_if_missing("rag_documents", "summary", "TEXT")?;
+        // Cópias das páginas usadas em uma resposta (kind = 'snapshot') apontam para a mensagem
+        self.add_column_if_missing("rag_documents", "message_id", "INTEGER")?;
         
+        // Tabela de tokens do servidor de API local (apenas o hash é salvo)
         self.conn.execute(
-            "CREATE INDEX IF NOT EXISTS idx_rag_session_id ON rag_documents(session_id)",
+            "CREATE TABLE IF NOT EXISTS api_tokens (
+                id TEXT PRIMARY KEY,
+                name TEXT NOT NULL,
+                scope TEXT NOT NULL,
+                token_hash TEXT NOT NULL UNIQUE,
+                token_prefix TEXT NOT NULL,
+                created_at TEXT NOT NULL,
+                last_used_at TEXT,
+                revoked_at TEXT
+            )",
             [],
         )?;
         
-        // Índice para ordenação por updated_at
+        // Biblioteca de prompts (presets de system prompt)
         self.conn.execute(
-            "CREATE INDEX IF NOT EXISTS idx_sessions_updated_at ON sessions(updated_at DESC)",
+            "CREATE TABLE IF NOT EXISTS prompts (
+                id TEXT PRIMARY KEY,
+                name TEXT NOT NULL,
+                content TEXT NOT NULL,
+                variables TEXT,
+                created_at TEXT NOT NULL,
+                updated_at TEXT NOT NULL
+            )",
             [],
         )?;
         
-        // Inicializar FTS (Full-Text Search)
-        self.init_fts_schema()?;
+        // Preset aplicado a cada sessão (colunas adicionadas depois da criação da tabela)
+        self.add_column_if_missing("sessions", "prompt_preset_id", "TEXT")?;
+        self.add_column_if_missing("sessions", "prompt_variables", "TEXT")?;
         
-        Ok(())
-    }
-    
-    /// Inicializa tabelas FTS5 para busca de texto completo
-    fn init_fts_schema(&self) -> SqliteResult<()> {
-        // Tabela FTS para títulos de sessões
+        // Cache de páginas do scraper
         self.conn.execute(
-            "CREATE VIRTUAL TABLE IF NOT EXISTS sessions_fts USING fts5(
-                id UNINDEXED,
-                title,
-                content='sessions',
-                content_rowid='rowid'
+            "CREATE TABLE IF NOT EXISTS scrape_cache (
+                url_hash TEXT PRIMARY KEY,
+                url TEXT NOT NULL,
+                title TEXT NOT NULL,
+                content TEXT NOT NULL,
+                markdown TEXT NOT NULL,
+                published_at TEXT,
+                etag TEXT,
+                fetched_at TEXT NOT NULL
             )",
             [],
         )?;
         
-        // Tabela FTS para conteúdo de mensagens
+        // Templates de conversa
+        self.conn.execute(
+            "CREATE TABLE IF NOT EXISTS conversation_templates (
+                id TEXT PRIMARY KEY,
+                name TEXT NOT NULL,
+                description TEXT,
+                system_prompt TEXT,
+                messages TEXT,
+                model TEXT,
+                rag_scope TEXT,
+                created_at TEXT NOT NULL,
+                updated_at TEXT NOT NULL
+            )",
+            [],
+        )?;
+        
+        // Configuração da sessão herdada do template
+        self.add_column_if_missing("sessions", "template_id", "TEXT")?;
+        self.add_column_if_missing("sessions", "model", "TEXT")?;
+        self.add_column_if_missing("sessions", "system_prompt", "TEXT")?;
+        self.add_column_if_missing("sessions", "rag_scope", "TEXT")?;
+        self.add_column_if_missing("sessions", "generation_options", "TEXT")?;
+        
+        // Organização das sessões (tags em JSON, pasta e arquivamento)
+        self.add_column_if_missing("sessions", "tags", "TEXT")?;
+        self.add_column_if_missing("sessions", "folder", "TEXT")?;
+        self.add_column_if_missing("sessions", "archived", "INTEGER NOT NULL DEFAULT 0")?;
+        self.add_column_if_missing("sessions", "is_template", "INTEGER NOT NULL DEFAULT 0")?;
+        // Modo offline: nada da sessão sai da máquina (ver `offline`)
+        self.add_column_if_missing("sessions", "offline", "INTEGER NOT NULL DEFAULT 0")?;
+        
+        // Mensagens removidas por truncamento (shadow table para desfazer)
+        self.conn.execute(
+            "CREATE TABLE IF NOT EXISTS truncated_messages (
+                id INTEGER PRIMARY KEY,
+                batch_id TEXT NOT NULL,
+                session_id TEXT NOT NULL,
+                role TEXT NOT NULL,
+                content TEXT NOT NULL,
+                metadata TEXT,
+                created_at TEXT NOT NULL,
+                truncated_at TEXT NOT NULL,
+                FOREIGN KEY (session_id) REFERENCES sessions(id) ON DELETE CASCADE
+            )",
+            [],
+        )?;
+        
+        // Log de auditoria (append-only)
+        self.conn.execute(
+            "CREATE TABLE IF NOT EXISTS audit_log (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                kind TEXT NOT NULL,
+                actor TEXT,
+                detail TEXT,
+                created_at TEXT NOT NULL
+            )",
+            [],
+        )?;
+        // Hash do conteúdo auditado (o conteúdo só é guardado se `audit.record_content`) e
+        // encadeamento de hashes: alterar ou remover um registro quebra a cadeia
+        self.add_column_if_missing("audit_log", "content_hash", "TEXT")?;
+        self.add_column_if_missing("audit_log", "payload", "TEXT")?;
+        self.add_column_if_missing("audit_log", "prev_hash", "TEXT")?;
+        self.add_column_if_missing("audit_log", "entry_hash", "TEXT")?;
+        self.conn.execute_batch(
+            "CREATE TRIGGER IF NOT EXISTS audit_log_no_update BEFORE UPDATE ON audit_log BEGIN
+                SELECT RAISE(ABORT, 'audit_log is append-only');
+             END;
+             CREATE TRIGGER IF NOT EXISTS audit_log_no_delete BEFORE DELETE ON audit_log BEGIN
+                SELECT RAISE(ABORT, 'audit_log is append-only');
+             END;
+             CREATE INDEX IF NOT EXISTS idx_audit_log_created ON audit_log(created_at);",
+        )?;
+        
+        // Índice leve das sessões movidas para o arquivo frio (arquivos .zip mensais)
+        self.conn.execute(
+            "CREATE TABLE IF NOT EXISTS archived_sessions (
+                session_id TEXT PRIMARY KEY,
+                title TEXT NOT NULL,
+                emoji TEXT,
+                created_at TEXT NOT NULL,
+                updated_at TEXT NOT NULL,
+                archived_at TEXT NOT NULL,
+                archive_file TEXT NOT NULL,
+                message_count INTEGER NOT NULL,
+                keywords TEXT NOT NULL
+            )",
+            [],
+        )?;
+        
+        // Saídas de tasks agrupadas no resumo diário (digest_session_id preenchido ao compor)
+        self.conn.execute(
+            "CREATE TABLE IF NOT EXISTS digest_items (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                kind TEXT NOT NULL,
+                task_id TEXT,
+                title TEXT NOT NULL,
+                content TEXT NOT NULL,
+                sources TEXT,
+                created_at TEXT NOT NULL,
+                digest_session_id TEXT
+            )",
+            [],
+        )?;
+        self.conn.execute(
+            "CREATE INDEX IF NOT EXISTS idx_digest_items_pending ON digest_items(digest_session_id, created_at)",
+            [],
+        )?;
+        
+        // Histórico de métricas de recursos (opcional, ver settings.metrics_history)
+        self.conn.execute(
+            "CREATE TABLE IF NOT EXISTS metric_samples (
+                timestamp TEXT PRIMARY KEY,
+                cpu_percent REAL NOT NULL,
+                ram_used_mb INTEGER NOT NULL,
+                ram_total_mb INTEGER NOT NULL,
+                vram_used_mb INTEGER,
+                vram_total_mb INTEGER,
+                ollama_rss_mb INTEGER NOT NULL,
+                ollama_cpu_percent REAL NOT NULL,
+                ollama_processes INTEGER NOT NULL
+            )",
+            [],
+        )?;
+        
+        // Checkpoints das respostas em streaming (removidos ao finalizar; sobras indicam crash)
+        self.conn.execute(
+            "CREATE TABLE IF NOT EXISTS pending_messages (
+                session_id TEXT PRIMARY KEY,
+                model TEXT NOT NULL,
+                request_messages TEXT NOT NULL,
+                content TEXT NOT NULL,
+                tokens INTEGER NOT NULL DEFAULT 0,
+                started_at TEXT NOT NULL,
+                updated_at TEXT NOT NULL
+            )",
+            [],
+        )?;
+        
+        // Pasta local anexada a uma sessão (arquivos indexados em rag_documents, kind = 'workspace')
+        self.conn.execute(
+            "CREATE TABLE IF NOT EXISTS workspaces (
+                session_id TEXT PRIMARY KEY,
+                root_path TEXT NOT NULL,
+                attached_at TEXT NOT NULL,
+                indexed_at TEXT
+            )",
+            [],
+        )?;
+        
+        // Respostas concluídas em background que o usuário ainda não viu (badge do tray)
+        self.conn.execute(
+            "CREATE TABLE IF NOT EXISTS unread_sessions (
+                session_id TEXT PRIMARY KEY,
+                unread_count INTEGER NOT NULL DEFAULT 0,
+                last_unread_at TEXT NOT NULL
+            )",
+            [],
+        )?;
+        
+        // GUIDs de itens de feeds RSS/Atom já entregues (dedupe entre execuções)
+        self.conn.execute(
+            "CREATE TABLE IF NOT EXISTS feed_seen_items (
+                feed_url TEXT NOT NULL,
+                guid TEXT NOT NULL,
+                seen_at TEXT NOT NULL,
+                PRIMARY KEY (feed_url, guid)
+            )",
+            [],
+        )?;
+        
+        // Saúde dos motores de busca (sucessos, buscas vazias e falhas seguidas)
+        self.conn.execute(
+            "CREATE TABLE IF NOT EXISTS search_engine_health (
+                engine TEXT PRIMARY KEY,
+                successes INTEGER NOT NULL DEFAULT 0,
+                failures INTEGER NOT NULL DEFAULT 0,
+                zero_results INTEGER NOT NULL DEFAULT 0,
+                consecutive_failures INTEGER NOT NULL DEFAULT 0,
+                last_success_at TEXT,
+                last_failure_at TEXT,
+                last_error TEXT
+            )",
+            [],
+        )?;
+        
+        // Execuções das tasks agendadas (taxa de sucesso nas estatísticas de uso)
+        self.conn.execute(
+            "CREATE TABLE IF NOT EXISTS task_runs (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                task_id TEXT NOT NULL,
+                success INTEGER NOT NULL,
+                error TEXT,
+                duration_ms INTEGER NOT NULL,
+                started_at TEXT NOT NULL
+            )",
+            [],
+        )?;
+        
+        // Índices para performance
+        self.conn.execute(
+            "CREATE INDEX IF NOT EXISTS idx_messages_session_id ON messages(session_id)",
+            [],
+        )?;
+        
+        self.conn.execute(
+            "CREATE INDEX IF NOT EXISTS idx_messages_created_at ON messages(created_at)",
+            [],
+        )?;
+        
+        self.conn.execute(
+            "CREATE INDEX IF NOT EXISTS idx_task_runs_started_at ON task_runs(started_at)",
+            [],
+        )?;
+        
+        self.conn.execute(
+            "CREATE INDEX IF NOT EXISTS idx_rag_session_id ON rag_documents(session_id)",
+            [],
+        )?;
+        
+        self.conn.execute(
+            "CREATE INDEX IF NOT EXISTS idx_rag_message_id ON rag_documents(message_id)",
+            [],
+        )?;
+        
+        // Índice para ordenação por updated_at
+        self.conn.execute(
+            "CREATE INDEX IF NOT EXISTS idx_sessions_updated_at ON sessions(updated_at DESC)",
+            [],
+        )?;
+        
+        self.conn.execute(
+            "CREATE INDEX IF NOT EXISTS idx_sessions_folder ON sessions(folder)",
+            [],
+        )?;
+        
+        // Inicializar FTS (Full-Text Search)
+        self.init_fts_schema()?;
+        
+        Ok(())
+    }
+    
+    /// Inicializa tabelas FTS5 para busca de texto completo
+    fn init_fts_schema(&self) -> SqliteResult<()> {
+        // Tabela FTS para títulos de sessões
+        self.conn.execute(
+            "CREATE VIRTUAL TABLE IF NOT EXISTS sessions_fts USING fts5(
+                id UNINDEXED,
+                title,
+                content='sessions',
+                content_rowid='rowid'
+            )",
+            [],
+        )?;
+        
+        // Tabela FTS para conteúdo de mensagens
         self.conn.execute(
             "CREATE VIRTUAL TABLE IF NOT EXISTS messages_fts USING fts5(
                 session_id UNINDEXED,
@@ -285,6 +1261,29 @@ impl Database {
         Ok(())
     }
     
+    /// Cria a sessão já com as mensagens em uma única transação (importação: ou entra tudo ou
+    /// nada). As mensagens devem vir em ordem de `created_at`.
+    pub fn create_session_with_messages(&self, session: &ChatSession, messages: &[ChatMessage]) -> SqliteResult<()> {
+        let tx = self.conn.unchecked_transaction()?;
+        self.create_session(session)?;
+        {
+            let mut stmt = tx.prepare(
+                "INSERT INTO messages (session_id, role, content, metadata, created_at)
+                 VALUES (?1, ?2, ?3, ?4, ?5)",
+            )?;
+            for message in messages {
+                stmt.execute(params![
+                    session.id,
+                    message.role,
+                    message.content,
+                    message.metadata,
+                    message.created_at.to_rfc3339()
+                ])?;
+            }
+        }
+        tx.commit()
+    }
+    
     /// Atualiza uma sessão existente
     pub fn update_session(&self, session: &ChatSession) -> SqliteResult<()> {
         self.conn.execute(
@@ -299,6 +1298,15 @@ impl Database {
         Ok(())
     }
     
+    /// Define título e emoji sem mexer em `updated_at` (título gerado em background)
+    pub fn set_session_title(&self, session_id: &str, title: &str, emoji: &str) -> SqliteResult<bool> {
+        let updated = self.conn.execute(
+            "UPDATE sessions SET title = ?1, emoji = ?2 WHERE id = ?3",
+            params![title, emoji, session_id],
+        )?;
+        Ok(updated > 0)
+    }
+    
     /// Busca uma sessão por ID
     pub fn get_session(&self, session_id: &str) -> SqliteResult<Option<ChatSession>> {
         let mut stmt = self.conn.prepare(
@@ -361,9 +1369,91 @@ impl Database {
     /// Deleta uma sessão e todas as suas mensagens
     pub fn delete_session(&self, session_id: &str) -> SqliteResult<()> {
         self.conn.execute("DELETE FROM sessions WHERE id = ?1", params![session_id])?;
+        self.conn.execute("DELETE FROM unread_sessions WHERE session_id = ?1", params![session_id])?;
+        self.conn.execute("DELETE FROM pending_messages WHERE session_id = ?1", params![session_id])?;
+        self.conn.execute("DELETE FROM workspaces WHERE session_id = ?1", params![session_id])?;
+        Ok(())
+    }
+    
+    /// Grava (ou atualiza) o checkpoint da resposta em streaming da sessão
+    pub fn save_pending_message(&self, pending: &PendingMessage) -> SqliteResult<()> {
+        self.conn.execute(
+            "INSERT OR REPLACE INTO pending_messages
+             (session_id, model, request_messages, content, tokens, started_at, updated_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+            params![
+                pending.session_id,
+                pending.model,
+                pending.request_messages,
+                pending.content,
+                pending.tokens,
+                pending.started_at.to_rfc3339(),
+                pending.updated_at.to_rfc3339(),
+            ],
+        )?;
+        Ok(())
+    }
+    
+    pub fn delete_pending_message(&self, session_id: &str) -> SqliteResult<()> {
+        self.conn.execute("DELETE FROM pending_messages WHERE session_id = ?1", params![session_id])?;
+        Ok(())
+    }
+    
+    /// Checkpoints restantes (respostas interrompidas), mais antigos primeiro
+    pub fn get_pending_messages(&self) -> SqliteResult<Vec<PendingMessage>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT session_id, model, request_messages, content, tokens, started_at, updated_at
+             FROM pending_messages
+             ORDER BY started_at ASC"
+        )?;
+        
+        let rows = stmt.query_map([], |row| {
+            Ok(PendingMessage {
+                session_id: row.get(0)?,
+                model: row.get(1)?,
+                request_messages: row.get(2)?,
+                content: row.get(3)?,
+                tokens: row.get(4)?,
+                started_at: DateTime::parse_from_rfc3339(&row.get::<_, String>(5)?)
+                    .map_err(|_| rusqlite::Error::InvalidColumnType(5, "TEXT".to_string(), rusqlite::types::Type::Text))?
+                    .with_timezone(&Utc),
+                updated_at: DateTime::parse_from_rfc3339(&row.get::<_, String>(6)?)
+                    .map_err(|_| rusqlite::Error::InvalidColumnType(6, "TEXT".to_string(), rusqlite::types::Type::Text))?
+                    .with_timezone(&Utc),
+            })
+        })?;
+        rows.collect()
+    }
+    
+    /// Marca uma nova resposta não lida na sessão (concluída por task ou com a janela oculta)
+    pub fn add_unread(&self, session_id: &str) -> SqliteResult<()> {
+        self.conn.execute(
+            "INSERT INTO unread_sessions (session_id, unread_count, last_unread_at) VALUES (?1, 1, ?2)
+             ON CONFLICT(session_id) DO UPDATE SET
+                unread_count = unread_count + 1,
+                last_unread_at = excluded.last_unread_at",
+            params![session_id, Utc::now().to_rfc3339()],
+        )?;
+        Ok(())
+    }
+    
+    /// Zera o contador de não lidas da sessão
+    pub fn mark_session_read(&self, session_id: &str) -> SqliteResult<()> {
+        self.conn.execute("DELETE FROM unread_sessions WHERE session_id = ?1", params![session_id])?;
         Ok(())
     }
     
+    /// Contadores de não lidas por sessão (mais recentes primeiro)
+    pub fn get_unread_counts(&self) -> SqliteResult<Vec<(String, i64)>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT session_id, unread_count FROM unread_sessions
+             WHERE unread_count > 0
+             ORDER BY last_unread_at DESC"
+        )?;
+        let rows = stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?;
+        rows.collect()
+    }
+    
     /// Adiciona uma mensagem a uma sessão
     pub fn add_message(&self, message: &ChatMessage) -> SqliteResult<i64> {
         self.conn.execute(
@@ -390,43 +1480,79 @@ impl Database {
         Ok(self.conn.last_insert_rowid())
     }
     
-    /// Salva múltiplas mensagens de uma sessão em uma transação
+    /// Salva as mensagens de uma sessão em uma transação, preservando os IDs das já gravadas
     /// 
-    /// Remove mensagens existentes da sessão antes de inserir as novas
-    /// para garantir que não haja duplicatas.
+    /// Cada mensagem é associada a uma gravada (`align_messages`): as associadas são atualizadas
+    /// no lugar (metadados mesclados por `merge_metadata`), as demais inseridas. Gravadas que não
+    /// vieram são removidas a partir da primeira associada (edição, regeneração); as anteriores
+    /// ficam, pois o frontend pode enviar só as páginas carregadas do histórico.
+    /// Retorna as mensagens como ficaram gravadas, na ordem recebida.
     pub fn save_messages_batch(
         &self,
         session_id: &str,
         messages: &[ChatMessage],
-    ) -> SqliteResult<()> {
-        // Usar execute_batch para executar múltiplas operações atomicamente
-        // WAL mode permite isso de forma segura mesmo sem transação explícita
-        
-        // Remover mensagens existentes da sessão (para evitar duplicatas)
-        self.conn.execute(
-            "DELETE FROM messages WHERE session_id = ?1",
-            params![session_id],
-        )?;
+    ) -> SqliteResult<Vec<ChatMessage>> {
+        let stored = self.get_messages(session_id)?;
+        let matches = align_messages(&stored, messages);
+        let window_start = matches.iter().flatten().min().copied().unwrap_or(0);
         
-        // Inserir todas as mensagens
-        let mut stmt = self.conn.prepare(
-            "INSERT INTO messages (session_id, role, content, metadata, created_at) 
-             VALUES (?1, ?2, ?3, ?4, ?5)"
-        )?;
+        let tx = self.conn.unchecked_transaction()?;
+        for (index, message) in stored.iter().enumerate().skip(window_start) {
+            if matches.contains(&Some(index)) {
+                continue;
+            }
+            if let Some(id) = message.id {
+                tx.execute("DELETE FROM messages WHERE id = ?1", params![id])?;
+            }
+        }
         
-        for message in messages {
-            stmt.execute(params![
-                message.session_id,
-                message.role,
-                message.content,
-                message.metadata,
-                message.created_at.to_rfc3339()
-            ])?;
+        // A ordem vem de created_at: cada mensagem fica depois da anterior
+        let mut previous = window_start.checked_sub(1).map(|index| stored[index].created_at);
+        let mut saved = Vec::with_capacity(messages.len());
+        for (message, matched) in messages.iter().zip(&matches) {
+            let existing = matched.map(|index| &stored[index]);
+            let mut created_at = existing.map_or(message.created_at, |s| s.created_at);
+            if let Some(previous) = previous {
+                if created_at <= previous {
+                    created_at = previous + chrono::Duration::seconds(1);
+                }
+            }
+            previous = Some(created_at);
+            
+            let metadata = match existing {
+                Some(existing) => merge_metadata(existing.metadata.as_deref(), message.metadata.as_deref()),
+                None => message.metadata.clone(),
+            };
+            let id = match existing.and_then(|s| s.id) {
+                Some(id) => {
+                    tx.execute(
+                        "UPDATE messages SET role = ?1, content = ?2, metadata = ?3, created_at = ?4 WHERE id = ?5",
+                        params![message.role, message.content, metadata, created_at.to_rfc3339(), id],
+                    )?;
+                    id
+                }
+                None => {
+                    tx.execute(
+                        "INSERT INTO messages (session_id, role, content, metadata, created_at) 
+                         VALUES (?1, ?2, ?3, ?4, ?5)",
+                        params![session_id, message.role, message.content, metadata, created_at.to_rfc3339()],
+                    )?;
+                    tx.last_insert_rowid()
+                }
+            };
+            saved.push(ChatMessage {
+                id: Some(id),
+                session_id: session_id.to_string(),
+                role: message.role.clone(),
+                content: message.content.clone(),
+                metadata,
+                created_at,
+            });
         }
         
         // Atualizar updated_at da sessão com a data da última mensagem
-        if let Some(last_message) = messages.last() {
-            self.conn.execute(
+        if let Some(last_message) = saved.last() {
+            tx.execute(
                 "UPDATE sessions SET updated_at = ?1 WHERE id = ?2",
                 params![
                     last_message.created_at.to_rfc3339(),
@@ -435,7 +1561,8 @@ impl Database {
             )?;
         }
         
-        Ok(())
+        tx.commit()?;
+        Ok(saved)
     }
     
     /// Busca todas as mensagens de uma sessão
@@ -467,6 +1594,43 @@ impl Database {
         Ok(messages)
     }
     
+    /// Busca uma mensagem pelo ID
+    pub fn get_message(&self, message_id: i64) -> SqliteResult<Option<ChatMessage>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT id, session_id, role, content, metadata, created_at 
+             FROM messages 
+             WHERE id = ?1"
+        )?;
+        
+        let mut rows = stmt.query_map(params![message_id], |row| {
+            Ok(ChatMessage {
+                id: Some(row.get(0)?),
+                session_id: row.get(1)?,
+                role: row.get(2)?,
+                content: row.get(3)?,
+                metadata: row.get(4)?,
+                created_at: DateTime::parse_from_rfc3339(&row.get::<_, String>(5)?)
+                    .map_err(|_| rusqlite::Error::InvalidColumnType(5, "TEXT".to_string(), rusqlite::types::Type::Text))?
+                    .with_timezone(&Utc),
+            })
+        })?;
+        
+        if let Some(row) = rows.next() {
+            row.map(Some)
+        } else {
+            Ok(None)
+        }
+    }
+    
+    /// Substitui o metadata (JSON serializado) de uma mensagem
+    pub fn update_message_metadata(&self, message_id: i64, metadata: Option<&str>) -> SqliteResult<()> {
+        self.conn.execute(
+            "UPDATE messages SET metadata = ?1 WHERE id = ?2",
+            params![metadata, message_id],
+        )?;
+        Ok(())
+    }
+    
     /// Busca mensagens de uma sessão com paginação (lazy loading)
     /// 
     /// Retorna as últimas `limit` mensagens a partir do `offset`.
@@ -594,32 +1758,182 @@ impl Database {
         Ok(docs)
     }
     
-    /// Busca sessões por query (título ou conteúdo de mensagens)
-    /// Retorna resultados ordenados por relevância (match no título > match no conteúdo)
-    /// Inclui contagem de matches para navegação
-    pub fn search_sessions(&self, query: &str, limit: usize) -> SqliteResult<Vec<SearchSessionResult>> {
-        if query.trim().is_empty() {
-            // Se query vazia, retornar todas as sessões ordenadas por updated_at com match_count = 0
-            let sessions = self.list_sessions()?;
-            return Ok(sessions.into_iter().map(|session| SearchSessionResult {
-                session,
-                match_count: 0,
-            }).collect());
-        }
-        
-        // Escapar caracteres especiais para FTS5
-        let escaped_query = query.replace('"', "\"\"");
-        let fts_query = format!("\"{}\"", escaped_query);
+    /// Documentos RAG ainda sem embedding (id, conteúdo), opcionalmente de uma sessão
+    pub fn get_documents_without_embedding(&self, session_id: Option<&str>) -> SqliteResult<Vec<(String, String)>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT id, content FROM rag_documents
+             WHERE embedding IS NULL AND (?1 IS NULL OR session_id = ?1)
+             ORDER BY created_at"
+        )?;
         
-        // Busca simplificada: primeiro buscar por título, depois por conteúdo
-        // Usando abordagem em duas etapas para evitar problemas com bm25 em CTEs
+        let rows = stmt.query_map(params![session_id], |row| Ok((row.get(0)?, row.get(1)?)))?;
         
-        // Etapa 1: Buscar sessões por título (FTS5)
-        let mut title_sessions: Vec<SearchSessionResult> = Vec::new();
-        {
-            let mut stmt = self.conn.prepare(
-                "SELECT s.id, s.title, s.emoji, s.created_at, s.updated_at
-                 FROM sessions s
+        let mut docs = Vec::new();
+        for row in rows {
+            docs.push(row?);
+        }
+        Ok(docs)
+    }
+    
+    /// Grava os embeddings de um lote de documentos RAG em uma única transação
+    pub fn set_document_embeddings(&self, embeddings: &[(String, Vec<u8>)]) -> SqliteResult<()> {
+        let tx = self.conn.unchecked_transaction()?;
+        {
+            let mut stmt = tx.prepare("UPDATE rag_documents SET embedding = ?1 WHERE id = ?2")?;
+            for (id, embedding) in embeddings {
+                stmt.execute(params![embedding, id])?;
+            }
+        }
+        tx.commit()
+    }
+    
+    /// Anexa (ou troca) a pasta local da sessão
+    pub fn set_workspace(&self, session_id: &str, root_path: &str) -> SqliteResult<()> {
+        self.conn.execute(
+            "INSERT OR REPLACE INTO workspaces (session_id, root_path, attached_at, indexed_at)
+             VALUES (?1, ?2, ?3, NULL)",
+            params![session_id, root_path, Utc::now().to_rfc3339()],
+        )?;
+        Ok(())
+    }
+    
+    pub fn mark_workspace_indexed(&self, session_id: &str) -> SqliteResult<()> {
+        self.conn.execute(
+            "UPDATE workspaces SET indexed_at = ?1 WHERE session_id = ?2",
+            params![Utc::now().to_rfc3339(), session_id],
+        )?;
+        Ok(())
+    }
+    
+    pub fn get_workspace(&self, session_id: &str) -> SqliteResult<Option<Workspace>> {
+        Ok(self.list_workspaces()?.into_iter().find(|w| w.session_id == session_id))
+    }
+    
+    pub fn list_workspaces(&self) -> SqliteResult<Vec<Workspace>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT session_id, root_path, attached_at, indexed_at FROM workspaces"
+        )?;
+        let parse_date = |value: String, index: usize| {
+            DateTime::parse_from_rfc3339(&value)
+                .map(|d| d.with_timezone(&Utc))
+                .map_err(|_| rusqlite::Error::InvalidColumnType(index, "TEXT".to_string(), rusqlite::types::Type::Text))
+        };
+        let rows = stmt.query_map([], |row| {
+            Ok(Workspace {
+                session_id: row.get(0)?,
+                root_path: row.get(1)?,
+                attached_at: parse_date(row.get(2)?, 2)?,
+                indexed_at: row.get::<_, Option<String>>(3)?.map(|d| parse_date(d, 3)).transpose()?,
+            })
+        })?;
+        rows.collect()
+    }
+    
+    /// Remove o workspace da sessão e todos os trechos indexados
+    pub fn delete_workspace(&self, session_id: &str) -> SqliteResult<()> {
+        let tx = self.conn.unchecked_transaction()?;
+        tx.execute(
+            "DELETE FROM rag_documents WHERE kind = 'workspace' AND session_id = ?1",
+            params![session_id],
+        )?;
+        tx.execute("DELETE FROM workspaces WHERE session_id = ?1", params![session_id])?;
+        tx.commit()
+    }
+    
+    /// Substitui os trechos indexados de um arquivo (lista vazia remove o arquivo do índice)
+    pub fn replace_workspace_file(&self, session_id: &str, path: &str, chunks: &[(String, String)]) -> SqliteResult<()> {
+        let tx = self.conn.unchecked_transaction()?;
+        tx.execute(
+            "DELETE FROM rag_documents WHERE kind = 'workspace' AND session_id = ?1 AND source_url = ?2",
+            params![session_id, path],
+        )?;
+        let now = Utc::now().to_rfc3339();
+        for (title, content) in chunks {
+            tx.execute(
+                "INSERT INTO rag_documents (id, session_id, source_url, content, embedding, created_at, kind, title)
+                 VALUES (?1, ?2, ?3, ?4, NULL, ?5, 'workspace', ?6)",
+                params![uuid::Uuid::new_v4().to_string(), session_id, path, content, now, title],
+            )?;
+        }
+        tx.commit()
+    }
+    
+    /// Arquivos indexados do workspace (caminho relativo, número de trechos)
+    pub fn list_workspace_files(&self, session_id: &str) -> SqliteResult<Vec<(String, i64)>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT source_url, COUNT(*) FROM rag_documents
+             WHERE kind = 'workspace' AND session_id = ?1
+             GROUP BY source_url ORDER BY source_url"
+        )?;
+        let rows = stmt.query_map(params![session_id], |row| Ok((row.get(0)?, row.get(1)?)))?;
+        rows.collect()
+    }
+    
+    pub fn get_workspace_chunks(&self, session_id: &str) -> SqliteResult<Vec<WorkspaceChunk>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT id, source_url, title, content, embedding FROM rag_documents
+             WHERE kind = 'workspace' AND session_id = ?1
+             ORDER BY source_url, rowid"
+        )?;
+        let rows = stmt.query_map(params![session_id], |row| {
+            let path: String = row.get(1)?;
+            Ok(WorkspaceChunk {
+                id: row.get(0)?,
+                title: row.get::<_, Option<String>>(2)?.unwrap_or_else(|| path.clone()),
+                path,
+                content: row.get(3)?,
+                embedding: row.get(4)?,
+            })
+        })?;
+        rows.collect()
+    }
+    
+    /// Busca sessões por query (título ou conteúdo de mensagens)
+    /// Retorna resultados ordenados por relevância (match no título > match no conteúdo)
+    /// Inclui contagem de matches para navegação
+    pub fn search_sessions(&self, query: &str, limit: usize) -> SqliteResult<Vec<SearchSessionResult>> {
+        if query.trim().is_empty() {
+            // Se query vazia, retornar todas as sessões ordenadas por updated_at com match_count = 0
+            let sessions = self.list_sessions()?;
+            return Ok(sessions.into_iter().map(|session| SearchSessionResult {
+                session,
+                match_count: 0,
+            }).collect());
+        }
+        
+        // Sintaxe inválida ou rejeitada pelo FTS5 cai direto no fallback com LIKE
+        let mut sessions = match build_fts_advanced_query(query) {
+            Ok(fts_query) => self.search_sessions_fts(&fts_query, limit).unwrap_or_else(|e| {
+                log::warn!("[Search] Query FTS rejeitada ({}), usando LIKE: {}", e, fts_query);
+                Vec::new()
+            }),
+            Err(e) => {
+                log::debug!("[Search] Sintaxe de busca inválida ({}), usando LIKE", e);
+                Vec::new()
+            }
+        };
+        
+        // Se não encontrou resultados com FTS, tentar busca simples com LIKE (fallback)
+        if sessions.is_empty() {
+            let plain = fts_plain_text(query);
+            let like_text = if plain.is_empty() { query.trim() } else { plain.as_str() };
+            sessions = self.search_sessions_like(like_text, limit)?;
+        }
+        
+        Ok(sessions)
+    }
+    
+    /// Busca por título e conteúdo com uma query FTS5 já validada
+    fn search_sessions_fts(&self, fts_query: &str, limit: usize) -> SqliteResult<Vec<SearchSessionResult>> {
+        // Busca simplificada: primeiro buscar por título, depois por conteúdo
+        // Usando abordagem em duas etapas para evitar problemas com bm25 em CTEs
+        
+        // Etapa 1: Buscar sessões por título (FTS5)
+        let mut title_sessions: Vec<SearchSessionResult> = Vec::new();
+        {
+            let mut stmt = self.conn.prepare(
+                "SELECT s.id, s.title, s.emoji, s.created_at, s.updated_at
+                 FROM sessions s
                  JOIN sessions_fts ON s.rowid = sessions_fts.rowid
                  WHERE sessions_fts MATCH ?1
                  ORDER BY s.updated_at DESC
@@ -698,8 +2012,13 @@ impl Database {
         let mut sessions: Vec<SearchSessionResult> = session_map.into_values().collect();
         sessions.sort_by(|a, b| b.session.updated_at.cmp(&a.session.updated_at));
         
-        // Se não encontrou resultados com FTS, tentar busca simples com LIKE (fallback)
-        if sessions.is_empty() {
+        Ok(sessions)
+    }
+    
+    /// Busca simples por substring no título e no conteúdo das mensagens
+    fn search_sessions_like(&self, text: &str, limit: usize) -> SqliteResult<Vec<SearchSessionResult>> {
+        let mut sessions = Vec::new();
+        {
             let mut stmt = self.conn.prepare(
                 "SELECT s.id, s.title, s.emoji, s.created_at, s.updated_at,
                         COUNT(CASE WHEN m.content LIKE ?1 THEN 1 END) as match_count
@@ -711,7 +2030,7 @@ impl Database {
                  LIMIT ?2"
             )?;
             
-            let like_query = format!("%{}%", query);
+            let like_query = format!("%{}%", text);
             let rows = stmt.query_map(params![like_query, limit], |row| {
                 Ok(SearchSessionResult {
                     session: ChatSession {
@@ -736,5 +2055,1486 @@ impl Database {
         
         Ok(sessions)
     }
-}
-
+    
+    /// Salva um novo token de API
+    pub fn insert_api_token(&self, token: &ApiToken, token_hash: &str) -> SqliteResult<()> {
+        self.conn.execute(
+            "INSERT INTO api_tokens (id, name, scope, token_hash, token_prefix, created_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            params![
+                token.id,
+                token.name,
+                token.scope.as_str(),
+                token_hash,
+                token.token_prefix,
+                token.created_at.to_rfc3339()
+            ],
+        )?;
+        Ok(())
+    }
+    
+    fn row_to_api_token(row: &rusqlite::Row) -> SqliteResult<ApiToken> {
+        let scope: String = row.get(2)?;
+        Ok(ApiToken {
+            id: row.get(0)?,
+            name: row.get(1)?,
+            scope: scope.parse::<ApiTokenScope>()
+                .map_err(|_| rusqlite::Error::InvalidColumnType(2, "TEXT".to_string(), rusqlite::types::Type::Text))?,
+            token_prefix: row.get(3)?,
+            created_at: DateTime::parse_from_rfc3339(&row.get::<_, String>(4)?)
+                .map_err(|_| rusqlite::Error::InvalidColumnType(4, "TEXT".to_string(), rusqlite::types::Type::Text))?
+                .with_timezone(&Utc),
+            last_used_at: parse_optional_datetime(row.get(5)?, 5)?,
+            revoked_at: parse_optional_datetime(row.get(6)?, 6)?,
+        })
+    }
+    
+    /// Lista tokens de API (inclui revogados)
+    pub fn list_api_tokens(&self) -> SqliteResult<Vec<ApiToken>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT id, name, scope, token_prefix, created_at, last_used_at, revoked_at
+             FROM api_tokens ORDER BY created_at DESC"
+        )?;
+        
+        let rows = stmt.query_map([], Self::row_to_api_token)?;
+        
+        let mut tokens = Vec::new();
+        for row in rows {
+            tokens.push(row?);
+        }
+        Ok(tokens)
+    }
+    
+    /// Revoga um token (mantém o registro para auditoria). Retorna false se não existir.
+    pub fn revoke_api_token(&self, id: &str) -> SqliteResult<bool> {
+        let changed = self.conn.execute(
+            "UPDATE api_tokens SET revoked_at = ?1 WHERE id = ?2 AND revoked_at IS NULL",
+            params![Utc::now().to_rfc3339(), id],
+        )?;
+        Ok(changed > 0)
+    }
+    
+    /// Adiciona uma entrada ao log de auditoria
+    pub fn append_audit_log(&self, kind: &str, actor: &str, detail: &str) -> SqliteResult<()> {
+        self.append_audit_entry(kind, actor, detail, None, None)
+    }
+    
+    /// Adiciona uma entrada encadeada ao hash da anterior (ver `audit::entry_hash`)
+    pub fn append_audit_entry(
+        &self,
+        kind: &str,
+        actor: &str,
+        detail: &str,
+        content_hash: Option<&str>,
+        payload: Option<&str>,
+    ) -> SqliteResult<()> {
+        // IMMEDIATE: outra conexão do pool não lê o mesmo `prev_hash` antes deste INSERT
+        let tx = rusqlite::Transaction::new_unchecked(&self.conn, rusqlite::TransactionBehavior::Immediate)?;
+        let prev_hash: String = tx
+            .query_row(
+                "SELECT entry_hash FROM audit_log WHERE entry_hash IS NOT NULL ORDER BY id DESC LIMIT 1",
+                [],
+                |row| row.get(0),
+            )
+            .or_else(|e| if e == rusqlite::Error::QueryReturnedNoRows { Ok(String::new()) } else { Err(e) })?;
+        let created_at = Utc::now().to_rfc3339();
+        let entry_hash = crate::audit::entry_hash(&prev_hash, kind, actor, detail, content_hash, &created_at);
+        tx.execute(
+            "INSERT INTO audit_log (kind, actor, detail, content_hash, payload, prev_hash, entry_hash, created_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+            params![kind, actor, detail, content_hash, payload, prev_hash, entry_hash, created_at],
+        )?;
+        tx.commit()
+    }
+    
+    /// Entradas do log de auditoria a partir de `since` (RFC 3339; vazio = todas), em ordem
+    pub fn audit_log_since(&self, since: &str) -> SqliteResult<Vec<AuditEntry>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT id, kind, actor, detail, content_hash, payload, prev_hash, entry_hash, created_at
+             FROM audit_log WHERE created_at >= ?1 ORDER BY id",
+        )?;
+        let rows = stmt.query_map(params![since], |row| {
+            Ok(AuditEntry {
+                id: row.get(0)?,
+                kind: row.get(1)?,
+                actor: row.get::<_, Option<String>>(2)?.unwrap_or_default(),
+                detail: row.get::<_, Option<String>>(3)?.unwrap_or_default(),
+                content_hash: row.get(4)?,
+                payload: row.get(5)?,
+                prev_hash: row.get(6)?,
+                entry_hash: row.get(7)?,
+                created_at: row.get(8)?,
+            })
+        })?;
+        rows.collect()
+    }
+    
+    fn row_to_prompt_preset(row: &rusqlite::Row) -> SqliteResult<PromptPreset> {
+        let variables: Option<String> = row.get(3)?;
+        Ok(PromptPreset {
+            id: row.get(0)?,
+            name: row.get(1)?,
+            content: row.get(2)?,
+            variables: variables
+                .and_then(|v| serde_json::from_str(&v).ok())
+                .unwrap_or_default(),
+            created_at: DateTime::parse_from_rfc3339(&row.get::<_, String>(4)?)
+                .map_err(|_| rusqlite::Error::InvalidColumnType(4, "TEXT".to_string(), rusqlite::types::Type::Text))?
+                .with_timezone(&Utc),
+            updated_at: DateTime::parse_from_rfc3339(&row.get::<_, String>(5)?)
+                .map_err(|_| rusqlite::Error::InvalidColumnType(5, "TEXT".to_string(), rusqlite::types::Type::Text))?
+                .with_timezone(&Utc),
+        })
+    }
+    
+    /// Cria ou atualiza um preset de prompt
+    pub fn save_prompt_preset(&self, preset: &PromptPreset) -> SqliteResult<()> {
+        let variables = serde_json::to_string(&preset.variables).unwrap_or_else(|_| "{}".to_string());
+        self.conn.execute(
+            "INSERT INTO prompts (id, name, content, variables, created_at, updated_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6)
+             ON CONFLICT(id) DO UPDATE SET
+                name = ?2,
+                content = ?3,
+                variables = ?4,
+                updated_at = ?6",
+            params![
+                preset.id,
+                preset.name,
+                preset.content,
+                variables,
+                preset.created_at.to_rfc3339(),
+                preset.updated_at.to_rfc3339()
+            ],
+        )?;
+        Ok(())
+    }
+    
+    /// Lista presets de prompt ordenados por nome
+    pub fn list_prompt_presets(&self) -> SqliteResult<Vec<PromptPreset>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT id, name, content, variables, created_at, updated_at
+             FROM prompts ORDER BY name COLLATE NOCASE ASC"
+        )?;
+        
+        let rows = stmt.query_map([], Self::row_to_prompt_preset)?;
+        
+        let mut presets = Vec::new();
+        for row in rows {
+            presets.push(row?);
+        }
+        Ok(presets)
+    }
+    
+    /// Busca um preset por ID
+    pub fn get_prompt_preset(&self, id: &str) -> SqliteResult<Option<PromptPreset>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT id, name, content, variables, created_at, updated_at
+             FROM prompts WHERE id = ?1"
+        )?;
+        
+        let mut rows = stmt.query_map(params![id], Self::row_to_prompt_preset)?;
+        
+        if let Some(row) = rows.next() {
+            row.map(Some)
+        } else {
+            Ok(None)
+        }
+    }
+    
+    /// Remove um preset e desvincula as sessões que o usavam
+    pub fn delete_prompt_preset(&self, id: &str) -> SqliteResult<()> {
+        self.conn.execute(
+            "UPDATE sessions SET prompt_preset_id = NULL, prompt_variables = NULL WHERE prompt_preset_id = ?1",
+            params![id],
+        )?;
+        self.conn.execute("DELETE FROM prompts WHERE id = ?1", params![id])?;
+        Ok(())
+    }
+    
+    /// Vincula (ou desvincula, com None) um preset a uma sessão
+    pub fn set_session_prompt_preset(
+        &self,
+        session_id: &str,
+        preset_id: Option<&str>,
+        variables: Option<&str>,
+    ) -> SqliteResult<()> {
+        self.conn.execute(
+            "UPDATE sessions SET prompt_preset_id = ?1, prompt_variables = ?2 WHERE id = ?3",
+            params![preset_id, variables, session_id],
+        )?;
+        Ok(())
+    }
+    
+    /// Retorna o preset vinculado à sessão e as variáveis sobrescritas
+    pub fn get_session_prompt_preset(
+        &self,
+        session_id: &str,
+    ) -> SqliteResult<Option<(String, std::collections::HashMap<String, String>)>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT prompt_preset_id, prompt_variables FROM sessions WHERE id = ?1"
+        )?;
+        
+        let mut rows = stmt.query_map(params![session_id], |row| {
+            Ok((row.get::<_, Option<String>>(0)?, row.get::<_, Option<String>>(1)?))
+        })?;
+        
+        match rows.next() {
+            Some(row) => {
+                let (preset_id, variables) = row?;
+                Ok(preset_id.map(|id| {
+                    let vars = variables
+                        .and_then(|v| serde_json::from_str(&v).ok())
+                        .unwrap_or_default();
+                    (id, vars)
+                }))
+            }
+            None => Ok(None),
+        }
+    }
+    
+    /// Move para a shadow table todas as mensagens posteriores a `message_id` na mesma sessão.
+    /// Retorna None se a mensagem não existir.
+    pub fn truncate_session_after(&self, message_id: i64) -> SqliteResult<Option<TruncationBatch>> {
+        let message = match self.get_message(message_id)? {
+            Some(m) => m,
+            None => return Ok(None),
+        };
+        
+        let batch_id = uuid::Uuid::new_v4().to_string();
+        let now = Utc::now();
+        let pivot_created_at = message.created_at.to_rfc3339();
+        
+        let tx = self.conn.unchecked_transaction()?;
+        
+        tx.execute(
+            "INSERT INTO truncated_messages (id, batch_id, session_id, role, content, metadata, created_at, truncated_at)
+             SELECT id, ?1, session_id, role, content, metadata, created_at, ?2
+             FROM messages
+             WHERE session_id = ?3 AND (created_at > ?4 OR (created_at = ?4 AND id > ?5))",
+            params![batch_id, now.to_rfc3339(), message.session_id, pivot_created_at, message_id],
+        )?;
+        
+        let moved = tx.execute(
+            "DELETE FROM messages
+             WHERE session_id = ?1 AND (created_at > ?2 OR (created_at = ?2 AND id > ?3))",
+            params![message.session_id, pivot_created_at, message_id],
+        )?;
+        
+        tx.execute(
+            "UPDATE sessions SET updated_at = ?1 WHERE id = ?2",
+            params![now.to_rfc3339(), message.session_id],
+        )?;
+        
+        tx.commit()?;
+        
+        Ok(Some(TruncationBatch {
+            batch_id,
+            session_id: message.session_id,
+            message_count: moved,
+            truncated_at: now,
+        }))
+    }
+    
+    /// Restaura as mensagens de um lote truncado (mantendo os IDs originais).
+    /// Retorna a sessão afetada e quantas mensagens voltaram.
+    pub fn undo_truncation(&self, batch_id: &str) -> SqliteResult<Option<(String, usize)>> {
+        let session_id: String = {
+            let mut stmt = self.conn.prepare(
+                "SELECT session_id FROM truncated_messages WHERE batch_id = ?1 LIMIT 1"
+            )?;
+            let mut rows = stmt.query_map(params![batch_id], |row| row.get(0))?;
+            match rows.next() {
+                Some(row) => row?,
+                None => return Ok(None),
+            }
+        };
+        
+        let tx = self.conn.unchecked_transaction()?;
+        
+        let restored = tx.execute(
+            "INSERT OR IGNORE INTO messages (id, session_id, role, content, metadata, created_at)
+             SELECT id, session_id, role, content, metadata, created_at
+             FROM truncated_messages WHERE batch_id = ?1",
+            params![batch_id],
+        )?;
+        
+        tx.execute("DELETE FROM truncated_messages WHERE batch_id = ?1", params![batch_id])?;
+        
+        tx.execute(
+            "UPDATE sessions SET updated_at = ?1 WHERE id = ?2",
+            params![Utc::now().to_rfc3339(), session_id],
+        )?;
+        
+        tx.commit()?;
+        
+        Ok(Some((session_id, restored)))
+    }
+    
+    /// Lista os lotes truncados de uma sessão (mais recentes primeiro)
+    pub fn list_truncations(&self, session_id: &str) -> SqliteResult<Vec<TruncationBatch>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT batch_id, session_id, COUNT(*), MAX(truncated_at)
+             FROM truncated_messages
+             WHERE session_id = ?1
+             GROUP BY batch_id, session_id
+             ORDER BY MAX(truncated_at) DESC"
+        )?;
+        
+        let rows = stmt.query_map(params![session_id], |row| {
+            Ok(TruncationBatch {
+                batch_id: row.get(0)?,
+                session_id: row.get(1)?,
+                message_count: row.get(2)?,
+                truncated_at: DateTime::parse_from_rfc3339(&row.get::<_, String>(3)?)
+                    .map_err(|_| rusqlite::Error::InvalidColumnType(3, "TEXT".to_string(), rusqlite::types::Type::Text))?
+                    .with_timezone(&Utc),
+            })
+        })?;
+        
+        let mut batches = Vec::new();
+        for row in rows {
+            batches.push(row?);
+        }
+        Ok(batches)
+    }
+    
+    /// Remove definitivamente lotes truncados antes de `cutoff`
+    pub fn purge_truncations_before(&self, cutoff: DateTime<Utc>) -> SqliteResult<usize> {
+        self.conn.execute(
+            "DELETE FROM truncated_messages WHERE truncated_at < ?1",
+            params![cutoff.to_rfc3339()],
+        )
+    }
+    
+    /// Página em cache pelo hash da URL (independente da idade)
+    pub fn get_cached_page(&self, url_hash: &str) -> SqliteResult<Option<CachedPage>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT url, title, content, markdown, published_at, etag, fetched_at
+             FROM scrape_cache WHERE url_hash = ?1"
+        )?;
+        
+        let mut rows = stmt.query_map(params![url_hash], |row| {
+            let fetched_at_str: String = row.get(6)?;
+            let fetched_at = DateTime::parse_from_rfc3339(&fetched_at_str)
+                .map_err(|_| rusqlite::Error::InvalidColumnType(6, "TEXT".to_string(), rusqlite::types::Type::Text))?
+                .with_timezone(&Utc);
+            
+            Ok(CachedPage {
+                url: row.get(0)?,
+                title: row.get(1)?,
+                content: row.get(2)?,
+                markdown: row.get(3)?,
+                published_at: row.get(4)?,
+                etag: row.get(5)?,
+                fetched_at,
+            })
+        })?;
+        
+        match rows.next() {
+            Some(row) => Ok(Some(row?)),
+            None => Ok(None),
+        }
+    }
+    
+    /// Grava (ou substitui) uma página no cache
+    pub fn save_cached_page(&self, url_hash: &str, page: &CachedPage) -> SqliteResult<()> {
+        self.conn.execute(
+            "INSERT OR REPLACE INTO scrape_cache (url_hash, url, title, content, markdown, published_at, etag, fetched_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+            params![
+                url_hash,
+                page.url,
+                page.title,
+                page.content,
+                page.markdown,
+                page.published_at,
+                page.etag,
+                page.fetched_at.to_rfc3339(),
+            ],
+        )?;
+        Ok(())
+    }
+    
+    /// Renova a data de uma página revalidada (HTTP 304)
+    pub fn touch_cached_page(&self, url_hash: &str) -> SqliteResult<()> {
+        self.conn.execute(
+            "UPDATE scrape_cache SET fetched_at = ?1 WHERE url_hash = ?2",
+            params![Utc::now().to_rfc3339(), url_hash],
+        )?;
+        Ok(())
+    }
+    
+    /// Remove páginas do cache (todas, ou apenas as obtidas antes de `older_than`)
+    pub fn clear_scrape_cache(&self, older_than: Option<DateTime<Utc>>) -> SqliteResult<usize> {
+        match older_than {
+            Some(cutoff) => self.conn.execute(
+                "DELETE FROM scrape_cache WHERE fetched_at < ?1",
+                params![cutoff.to_rfc3339()],
+            ),
+            None => self.conn.execute("DELETE FROM scrape_cache", []),
+        }
+    }
+    
+    /// Quantidade de páginas em cache e bytes aproximados do conteúdo guardado
+    pub fn scrape_cache_usage(&self) -> SqliteResult<(u64, u64)> {
+        self.conn.query_row(
+            "SELECT COUNT(*), COALESCE(SUM(LENGTH(CAST(content AS BLOB)) + LENGTH(CAST(markdown AS BLOB))), 0) FROM scrape_cache",
+            [],
+            |row| Ok((row.get::<_, i64>(0)? as u64, row.get::<_, i64>(1)? as u64)),
+        )
+    }
+    
+    /// Define as tags de uma sessão. Retorna false se a sessão não existir.
+    pub fn set_session_tags(&self, session_id: &str, tags: &[String]) -> SqliteResult<bool> {
+        let tags_json = if tags.is_empty() {
+            None
+        } else {
+            Some(serde_json::to_string(tags)
+                .map_err(|e| rusqlite::Error::ToSqlConversionFailure(Box::new(e)))?)
+        };
+        let updated = self.conn.execute(
+            "UPDATE sessions SET tags = ?1 WHERE id = ?2",
+            params![tags_json, session_id],
+        )?;
+        Ok(updated > 0)
+    }
+    
+    /// Move uma sessão para uma pasta (None = raiz). Retorna false se a sessão não existir.
+    pub fn set_session_folder(&self, session_id: &str, folder: Option<&str>) -> SqliteResult<bool> {
+        let updated = self.conn.execute(
+            "UPDATE sessions SET folder = ?1 WHERE id = ?2",
+            params![folder, session_id],
+        )?;
+        Ok(updated > 0)
+    }
+    
+    /// Arquiva ou desarquiva uma sessão. Retorna false se a sessão não existir.
+    pub fn set_session_archived(&self, session_id: &str, archived: bool) -> SqliteResult<bool> {
+        let updated = self.conn.execute(
+            "UPDATE sessions SET archived = ?1 WHERE id = ?2",
+            params![archived, session_id],
+        )?;
+        Ok(updated > 0)
+    }
+    
+    /// Marca ou desmarca uma sessão como template. Retorna false se a sessão não existir.
+    pub fn set_session_template(&self, session_id: &str, template: bool) -> SqliteResult<bool> {
+        let updated = self.conn.execute(
+            "UPDATE sessions SET is_template = ?1 WHERE id = ?2",
+            params![template, session_id],
+        )?;
+        Ok(updated > 0)
+    }
+    
+    /// Liga ou desliga o modo offline de uma sessão. Retorna false se a sessão não existir.
+    pub fn set_session_offline(&self, session_id: &str, offline: bool) -> SqliteResult<bool> {
+        let updated = self.conn.execute(
+            "UPDATE sessions SET offline = ?1 WHERE id = ?2",
+            params![offline, session_id],
+        )?;
+        Ok(updated > 0)
+    }
+    
+    /// Modo offline da sessão (false se ela não existir)
+    pub fn is_session_offline(&self, session_id: &str) -> SqliteResult<bool> {
+        self.conn
+            .query_row("SELECT offline FROM sessions WHERE id = ?1", params![session_id], |row| row.get(0))
+            .or_else(|e| if e == rusqlite::Error::QueryReturnedNoRows { Ok(false) } else { Err(e) })
+    }
+    
+    /// Retorna a organização de todas as sessões (por ID)
+    pub fn get_sessions_organization(&self) -> SqliteResult<std::collections::HashMap<String, SessionOrganization>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT id, tags, folder, archived, is_template, offline FROM sessions"
+        )?;
+        
+        let rows = stmt.query_map([], |row| {
+            let tags: Option<String> = row.get(1)?;
+            Ok((row.get::<_, String>(0)?, SessionOrganization {
+                tags: tags
+                    .and_then(|t| serde_json::from_str(&t).ok())
+                    .unwrap_or_default(),
+                folder: row.get(2)?,
+                archived: row.get(3)?,
+                template: row.get(4)?,
+                offline: row.get(5)?,
+            }))
+        })?;
+        
+        let mut organization = std::collections::HashMap::new();
+        for row in rows {
+            let (id, org) = row?;
+            organization.insert(id, org);
+        }
+        Ok(organization)
+    }
+    
+    /// Busca dentro de uma sessão, retornando as mensagens que casaram com offsets e snippet
+    pub fn search_in_session(&self, session_id: &str, query: &str, limit: usize) -> SqliteResult<Vec<MessageSearchMatch>> {
+        let fts_query = build_fts_terms_query(query);
+        if fts_query.is_empty() {
+            return Ok(Vec::new());
+        }
+        
+        let mut stmt = self.conn.prepare(
+            "SELECT m.id, m.role, m.created_at,
+                    highlight(messages_fts, 1, char(1), char(2)),
+                    snippet(messages_fts, 1, '<mark>', '</mark>', '…', 24)
+             FROM messages_fts
+             JOIN messages m ON m.rowid = messages_fts.rowid
+             WHERE messages_fts MATCH ?1 AND m.session_id = ?2
+             ORDER BY m.created_at ASC, m.id ASC
+             LIMIT ?3"
+        )?;
+        
+        let rows = stmt.query_map(params![fts_query, session_id, limit], |row| {
+            Ok(MessageSearchMatch {
+                message_id: row.get(0)?,
+                role: row.get(1)?,
+                created_at: DateTime::parse_from_rfc3339(&row.get::<_, String>(2)?)
+                    .map_err(|_| rusqlite::Error::InvalidColumnType(2, "TEXT".to_string(), rusqlite::types::Type::Text))?
+                    .with_timezone(&Utc),
+                ranges: parse_highlight_ranges(&row.get::<_, String>(3)?),
+                snippet: row.get(4)?,
+            })
+        })?;
+        
+        let mut matches = Vec::new();
+        for row in rows {
+            matches.push(row?);
+        }
+        Ok(matches)
+    }
+    
+    fn table_columns(&self, table: &str) -> SqliteResult<Vec<String>> {
+        let mut stmt = self.conn.prepare(&format!("PRAGMA table_info({})", table))?;
+        let columns = stmt.query_map([], |row| row.get::<_, String>(1))?;
+        columns.collect()
+    }
+    
+    fn rows_as_json(&self, table: &str, key_column: &str, key: &str) -> SqliteResult<Vec<JsonRow>> {
+        let mut stmt = self.conn.prepare(&format!("SELECT * FROM {} WHERE {} = ?1", table, key_column))?;
+        let names: Vec<String> = stmt.column_names().iter().map(|n| n.to_string()).collect();
+        let rows = stmt.query_map(params![key], |row| {
+            let mut map = JsonRow::new();
+            for (index, name) in names.iter().enumerate() {
+                map.insert(name.clone(), sql_to_json(row.get_ref(index)?));
+            }
+            Ok(map)
+        })?;
+        rows.collect()
+    }
+    
+    /// Insere linhas serializadas, ignorando colunas que não existem mais na tabela
+    fn insert_json_rows(&self, table: &str, rows: &[JsonRow]) -> SqliteResult<()> {
+        let known = self.table_columns(table)?;
+        for row in rows {
+            let columns: Vec<&String> = row.keys().filter(|c| known.contains(c)).collect();
+            if columns.is_empty() {
+                continue;
+            }
+            let placeholders = (1..=columns.len()).map(|i| format!("?{}", i)).collect::<Vec<_>>().join(", ");
+            let sql = format!(
+                "INSERT OR REPLACE INTO {} ({}) VALUES ({})",
+                table,
+                columns.iter().map(|c| c.as_str()).collect::<Vec<_>>().join(", "),
+                placeholders
+            );
+            let values: Vec<rusqlite::types::Value> = columns.iter().map(|c| json_to_sql(&row[c.as_str()])).collect();
+            self.conn.execute(&sql, rusqlite::params_from_iter(values))?;
+        }
+        Ok(())
+    }
+    
+    /// Copia a sessão e as linhas vinculadas (mensagens, anexos, truncamentos)
+    pub fn snapshot_session(&self, session_id: &str) -> SqliteResult<Option<SessionSnapshot>> {
+        let session = match self.rows_as_json("sessions", "id", session_id)?.into_iter().next() {
+            Some(session) => session,
+            None => return Ok(None),
+        };
+        let mut children = std::collections::HashMap::new();
+        for table in SESSION_CHILD_TABLES {
+            children.insert(table.to_string(), self.rows_as_json(table, "session_id", session_id)?);
+        }
+        Ok(Some(SessionSnapshot { session, children }))
+    }
+    
+    /// Recria uma sessão a partir de uma cópia (mantém ids de mensagens para referências estáveis)
+    pub fn restore_session_snapshot(&self, snapshot: &SessionSnapshot) -> SqliteResult<()> {
+        let tx = self.conn.unchecked_transaction()?;
+        self.insert_json_rows("sessions", std::slice::from_ref(&snapshot.session))?;
+        for table in SESSION_CHILD_TABLES {
+            if let Some(rows) = snapshot.children.get(*table) {
+                self.insert_json_rows(table, rows)?;
+            }
+        }
+        tx.commit()
+    }
+    
+    /// Sessões sem atividade desde `cutoff` (candidatas ao arquivo frio)
+    pub fn list_sessions_updated_before(&self, cutoff: DateTime<Utc>) -> SqliteResult<Vec<ChatSession>> {
+        Ok(self
+            .list_sessions()?
+            .into_iter()
+            .filter(|s| s.updated_at < cutoff)
+            .collect())
+    }
+    
+    /// Registra no índice uma sessão movida para o arquivo frio e a remove do banco
+    pub fn mark_session_archived(&self, entry: &ArchivedSession, keywords: &str) -> SqliteResult<()> {
+        let tx = self.conn.unchecked_transaction()?;
+        self.conn.execute(
+            "INSERT OR REPLACE INTO archived_sessions
+                (session_id, title, emoji, created_at, updated_at, archived_at, archive_file, message_count, keywords)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+            params![
+                entry.session_id,
+                entry.title,
+                entry.emoji,
+                entry.created_at.to_rfc3339(),
+                entry.updated_at.to_rfc3339(),
+                entry.archived_at.to_rfc3339(),
+                entry.archive_file,
+                entry.message_count,
+                keywords
+            ],
+        )?;
+        self.conn.execute("DELETE FROM sessions WHERE id = ?1", params![entry.session_id])?;
+        tx.commit()
+    }
+    
+    pub fn remove_archived_session(&self, session_id: &str) -> SqliteResult<()> {
+        self.conn.execute("DELETE FROM archived_sessions WHERE session_id = ?1", params![session_id])?;
+        Ok(())
+    }
+    
+    fn row_to_archived_session(row: &rusqlite::Row) -> SqliteResult<ArchivedSession> {
+        let parse = |index: usize| -> SqliteResult<DateTime<Utc>> {
+            DateTime::parse_from_rfc3339(&row.get::<_, String>(index)?)
+                .map(|d| d.with_timezone(&Utc))
+                .map_err(|_| rusqlite::Error::InvalidColumnType(index, "TEXT".to_string(), rusqlite::types::Type::Text))
+        };
+        Ok(ArchivedSession {
+            session_id: row.get(0)?,
+            title: row.get(1)?,
+            emoji: row.get(2)?,
+            created_at: parse(3)?,
+            updated_at: parse(4)?,
+            archived_at: parse(5)?,
+            archive_file: row.get(6)?,
+            message_count: row.get(7)?,
+        })
+    }
+    
+    pub fn get_archived_session(&self, session_id: &str) -> SqliteResult<Option<ArchivedSession>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT session_id, title, emoji, created_at, updated_at, archived_at, archive_file, message_count
+             FROM archived_sessions WHERE session_id = ?1"
+        )?;
+        let mut rows = stmt.query_map(params![session_id], Self::row_to_archived_session)?;
+        if let Some(row) = rows.next() {
+            row.map(Some)
+        } else {
+            Ok(None)
+        }
+    }
+    
+    /// Busca no índice leve: todas as palavras precisam aparecer no título ou nas palavras-chave
+    pub fn search_archived_sessions(&self, query: &str, limit: usize) -> SqliteResult<Vec<ArchivedSession>> {
+        let terms: Vec<String> = query
+            .split_whitespace()
+            .map(|t| format!("%{}%", t.to_lowercase()))
+            .collect();
+        
+        let conditions = if terms.is_empty() {
+            "1 = 1".to_string()
+        } else {
+            (0..terms.len())
+                .map(|i| format!("(lower(title) LIKE ?{0} OR keywords LIKE ?{0})", i + 2))
+                .collect::<Vec<_>>()
+                .join(" AND ")
+        };
+        let sql = format!(
+            "SELECT session_id, title, emoji, created_at, updated_at, archived_at, archive_file, message_count
+             FROM archived_sessions WHERE {} ORDER BY updated_at DESC LIMIT ?1",
+            conditions
+        );
+        
+        let mut stmt = self.conn.prepare(&sql)?;
+        let mut values: Vec<rusqlite::types::Value> = vec![rusqlite::types::Value::Integer(limit as i64)];
+        values.extend(terms.into_iter().map(rusqlite::types::Value::Text));
+        let rows = stmt.query_map(rusqlite::params_from_iter(values), Self::row_to_archived_session)?;
+        rows.collect()
+    }
+    
+    /// Mensagens que casaram com a busca, agrupadas por sessão (até `per_session_limit` por sessão,
+    /// em ordem cronológica). Sintaxe inválida retorna um mapa vazio (a busca usou o fallback LIKE).
+    pub fn search_message_hits(
+        &self,
+        session_ids: &[String],
+        query: &str,
+        per_session_limit: usize,
+    ) -> SqliteResult<std::collections::HashMap<String, SessionHits>> {
+        let mut grouped: std::collections::HashMap<String, SessionHits> = std::collections::HashMap::new();
+        let fts_query = match build_fts_advanced_query(query) {
+            Ok(fts_query) => fts_query,
+            Err(_) => return Ok(grouped),
+        };
+        if session_ids.is_empty() {
+            return Ok(grouped);
+        }
+        
+        let placeholders = (0..session_ids.len()).map(|i| format!("?{}", i + 2)).collect::<Vec<_>>().join(", ");
+        let sql = format!(
+            "SELECT m.session_id, m.id, highlight(messages_fts, 1, char(1), char(2))
+             FROM messages_fts
+             JOIN messages m ON m.rowid = messages_fts.rowid
+             WHERE messages_fts MATCH ?1 AND m.session_id IN ({})
+             ORDER BY m.session_id, m.created_at ASC, m.id ASC",
+            placeholders
+        );
+        let mut stmt = self.conn.prepare(&sql)?;
+        
+        let mut values: Vec<&dyn rusqlite::ToSql> = vec![&fts_query];
+        values.extend(session_ids.iter().map(|id| id as &dyn rusqlite::ToSql));
+        
+        let rows = stmt.query_map(values.as_slice(), |row| {
+            Ok((row.get::<_, String>(0)?, row.get::<_, i64>(1)?, row.get::<_, String>(2)?))
+        })?;
+        
+        for row in rows {
+            let (session_id, message_id, highlighted) = row?;
+            let entry = grouped.entry(session_id).or_default();
+            entry.total += 1;
+            if entry.hits.len() < per_session_limit {
+                entry.hits.push(MessageHit {
+                    message_id,
+                    ranges: parse_highlight_ranges(&highlighted),
+                });
+            }
+        }
+        Ok(grouped)
+    }
+    
+    /// Salva um anexo de texto colado (kind = 'paste')
+    pub fn save_paste_attachment(&self, attachment: &PasteAttachment, embedding: Option<&[u8]>) -> SqliteResult<()> {
+        self.conn.execute(
+            "INSERT OR REPLACE INTO rag_documents (id, session_id, source_url, content, embedding, created_at, kind, title, summary)
+             VALUES (?1, ?2, NULL, ?3, ?4, ?5, 'paste', ?6, ?7)",
+            params![
+                attachment.id,
+                attachment.session_id,
+                attachment.content,
+                embedding,
+                attachment.created_at.to_rfc3339(),
+                attachment.title,
+                attachment.summary
+            ],
+        )?;
+        Ok(())
+    }
+    
+    /// Salva as fontes de uma resposta; uma nova cópia da mesma URL substitui a anterior
+    pub fn save_message_sources(&self, sources: &[MessageSource]) -> SqliteResult<()> {
+        let tx = self.conn.unchecked_transaction()?;
+        for source in sources {
+            self.conn.execute(
+                "DELETE FROM rag_documents WHERE kind = 'snapshot' AND message_id = ?1 AND source_url = ?2",
+                params![source.message_id, source.url],
+            )?;
+            self.conn.execute(
+                "INSERT INTO rag_documents (id, session_id, source_url, content, embedding, created_at, kind, title, message_id)
+                 VALUES (?1, ?2, ?3, ?4, NULL, ?5, 'snapshot', ?6, ?7)",
+                params![
+                    source.id,
+                    source.session_id,
+                    source.url,
+                    source.content,
+                    source.created_at.to_rfc3339(),
+                    source.title,
+                    source.message_id
+                ],
+            )?;
+        }
+        tx.commit()
+    }
+    
+    /// Fontes guardadas para uma mensagem, na ordem em que foram salvas
+    pub fn get_message_sources(&self, message_id: i64) -> SqliteResult<Vec<MessageSource>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT id, message_id, session_id, source_url, title, content, created_at
+             FROM rag_documents
+             WHERE kind = 'snapshot' AND message_id = ?1
+             ORDER BY created_at ASC, rowid ASC"
+        )?;
+        
+        let rows = stmt.query_map(params![message_id], |row| {
+            let url: String = row.get::<_, Option<String>>(3)?.unwrap_or_default();
+            Ok(MessageSource {
+                id: row.get(0)?,
+                message_id: row.get(1)?,
+                session_id: row.get(2)?,
+                title: row.get::<_, Option<String>>(4)?.unwrap_or_else(|| url.clone()),
+                url,
+                content: row.get(5)?,
+                created_at: DateTime::parse_from_rfc3339(&row.get::<_, String>(6)?)
+                    .map_err(|_| rusqlite::Error::InvalidColumnType(6, "TEXT".to_string(), rusqlite::types::Type::Text))?
+                    .with_timezone(&Utc),
+            })
+        })?;
+        rows.collect()
+    }
+    
+    /// Registra o item do feed como visto; `false` se ele já tinha sido entregue antes
+    pub fn mark_feed_item_seen(&self, feed_url: &str, guid: &str) -> SqliteResult<bool> {
+        let inserted = self.conn.execute(
+            "INSERT OR IGNORE INTO feed_seen_items (feed_url, guid, seen_at) VALUES (?1, ?2, ?3)",
+            params![feed_url, guid, Utc::now().to_rfc3339()],
+        )?;
+        Ok(inserted > 0)
+    }
+    
+    /// Grava as estatísticas de um motor de busca
+    pub fn save_engine_health(&self, health: &EngineHealth) -> SqliteResult<()> {
+        self.conn.execute(
+            "INSERT OR REPLACE INTO search_engine_health
+             (engine, successes, failures, zero_results, consecutive_failures,
+              last_success_at, last_failure_at, last_error)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+            params![
+                health.engine,
+                health.successes as i64,
+                health.failures as i64,
+                health.zero_results as i64,
+                health.consecutive_failures as i64,
+                health.last_success_at.map(|d| d.to_rfc3339()),
+                health.last_failure_at.map(|d| d.to_rfc3339()),
+                health.last_error,
+            ],
+        )?;
+        Ok(())
+    }
+    
+    /// Estatísticas salvas de todos os motores de busca
+    pub fn list_engine_health(&self) -> SqliteResult<Vec<EngineHealth>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT engine, successes, failures, zero_results, consecutive_failures,
+                    last_success_at, last_failure_at, last_error
+             FROM search_engine_health"
+        )?;
+        let parse = |value: Option<String>| {
+            value
+                .and_then(|v| DateTime::parse_from_rfc3339(&v).ok())
+                .map(|d| d.with_timezone(&Utc))
+        };
+        let rows = stmt.query_map([], |row| {
+            Ok(EngineHealth {
+                engine: row.get(0)?,
+                successes: row.get::<_, i64>(1)? as u64,
+                failures: row.get::<_, i64>(2)? as u64,
+                zero_results: row.get::<_, i64>(3)? as u64,
+                consecutive_failures: row.get::<_, i64>(4)? as u32,
+                last_success_at: parse(row.get(5)?),
+                last_failure_at: parse(row.get(6)?),
+                last_error: row.get(7)?,
+            })
+        })?;
+        rows.collect()
+    }
+    
+    /// Registra uma execução de task agendada
+    pub fn record_task_run(&self, task_id: &str, error: Option<&str>, duration_ms: u64, started_at: DateTime<Utc>) -> SqliteResult<()> {
+        self.conn.execute(
+            "INSERT INTO task_runs (task_id, success, error, duration_ms, started_at) VALUES (?1, ?2, ?3, ?4, ?5)",
+            params![task_id, error.is_none(), error, duration_ms as i64, started_at.to_rfc3339()],
+        )?;
+        Ok(())
+    }
+    
+    /// Início da execução mais recente de cada task, com sucesso ou falha
+    pub fn last_task_attempts(&self) -> SqliteResult<std::collections::HashMap<String, DateTime<Utc>>> {
+        let mut stmt = self.conn.prepare("SELECT task_id, MAX(started_at) FROM task_runs GROUP BY task_id")?;
+        let rows = stmt.query_map([], |row| Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?)))?;
+        let mut attempts = std::collections::HashMap::new();
+        for row in rows {
+            let (task_id, started_at) = row?;
+            if let Ok(started_at) = DateTime::parse_from_rfc3339(&started_at) {
+                attempts.insert(task_id, started_at.with_timezone(&Utc));
+            }
+        }
+        Ok(attempts)
+    }
+    
+    /// Mensagens de usuário e assistente por dia (UTC) criadas a partir de `since` (RFC 3339; vazio = todas)
+    pub fn count_messages_per_day(&self, since: &str) -> SqliteResult<Vec<DailyMessages>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT substr(created_at, 1, 10) AS day,
+                    SUM(role = 'user'), SUM(role = 'assistant')
+             FROM messages
+             WHERE created_at >= ?1 AND role IN ('user', 'assistant')
+             GROUP BY day
+             ORDER BY day"
+        )?;
+        let rows = stmt.query_map(params![since], |row| {
+            Ok(DailyMessages {
+                date: row.get(0)?,
+                user: row.get::<_, i64>(1)? as u64,
+                assistant: row.get::<_, i64>(2)? as u64,
+            })
+        })?;
+        rows.collect()
+    }
+    
+    /// Respostas, tokens e tempos por modelo (de `metadata.generation` e `metadata.usage`),
+    /// do mais usado para o menos usado
+    pub fn model_usage(&self, since: &str) -> SqliteResult<Vec<ModelUsage>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT COALESCE(json_extract(metadata, '$.usage.model'), json_extract(metadata, '$.generation.model')) AS model,
+                    COUNT(*),
+                    COALESCE(SUM(json_extract(metadata, '$.usage.prompt_tokens')), 0),
+                    COALESCE(SUM(json_extract(metadata, '$.usage.completion_tokens')), 0),
+                    AVG(json_extract(metadata, '$.usage.total_duration_ms')),
+                    AVG(json_extract(metadata, '$.usage.first_token_ms'))
+             FROM messages
+             WHERE role = 'assistant' AND created_at >= ?1 AND json_valid(metadata)
+             GROUP BY model
+             HAVING model IS NOT NULL
+             ORDER BY COUNT(*) DESC, model"
+        )?;
+        let rows = stmt.query_map(params![since], |row| {
+            Ok(ModelUsage {
+                model: row.get(0)?,
+                responses: row.get::<_, i64>(1)? as u64,
+                prompt_tokens: row.get::<_, i64>(2)? as u64,
+                completion_tokens: row.get::<_, i64>(3)? as u64,
+                avg_latency_ms: row.get(4)?,
+                avg_first_token_ms: row.get(5)?,
+            })
+        })?;
+        rows.collect()
+    }
+    
+    /// Tags mais frequentes entre as sessões atualizadas a partir de `since`
+    pub fn top_session_tags(&self, since: &str, limit: usize) -> SqliteResult<Vec<TagCount>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT tag.value, COUNT(*) AS total
+             FROM sessions, json_each(sessions.tags) AS tag
+             WHERE sessions.tags IS NOT NULL AND json_valid(sessions.tags) AND sessions.updated_at >= ?1
+             GROUP BY tag.value
+             ORDER BY total DESC, tag.value
+             LIMIT ?2"
+        )?;
+        let rows = stmt.query_map(params![since, limit as i64], |row| {
+            Ok(TagCount {
+                tag: row.get(0)?,
+                sessions: row.get::<_, i64>(1)? as u64,
+            })
+        })?;
+        rows.collect()
+    }
+    
+    /// Execuções de tasks agendadas iniciadas a partir de `since`
+    pub fn task_run_stats(&self, since: &str) -> SqliteResult<TaskRunStats> {
+        let (total, succeeded): (i64, i64) = self.conn.query_row(
+            "SELECT COUNT(*), COALESCE(SUM(success), 0) FROM task_runs WHERE started_at >= ?1",
+            params![since],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )?;
+        Ok(TaskRunStats {
+            total: total as u64,
+            succeeded: succeeded as u64,
+            failed: (total - succeeded) as u64,
+            success_rate: (total > 0).then(|| succeeded as f64 / total as f64),
+        })
+    }
+    
+    /// Guarda uma saída para o próximo resumo diário
+    pub fn add_digest_item(
+        &self,
+        kind: &str,
+        task_id: Option<&str>,
+        title: &str,
+        content: &str,
+        sources: &[crate::digests::DigestSource],
+    ) -> SqliteResult<i64> {
+        let sources_json = serde_json::to_string(sources).unwrap_or_else(|_| "[]".to_string());
+        self.conn.execute(
+            "INSERT INTO digest_items (kind, task_id, title, content, sources, created_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            params![kind, task_id, title, content, sources_json, Utc::now().to_rfc3339()],
+        )?;
+        Ok(self.conn.last_insert_rowid())
+    }
+    
+    /// Itens ainda não incluídos em um resumo (mais recentes primeiro)
+    pub fn get_pending_digest_items(&self) -> SqliteResult<Vec<DigestItem>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT id, kind, task_id, title, content, sources, created_at
+             FROM digest_items
+             WHERE digest_session_id IS NULL
+             ORDER BY created_at DESC, id DESC"
+        )?;
+        
+        let rows = stmt.query_map([], |row| {
+            let sources: Option<String> = row.get(5)?;
+            Ok(DigestItem {
+                id: row.get(0)?,
+                kind: row.get(1)?,
+                task_id: row.get(2)?,
+                title: row.get(3)?,
+                content: row.get(4)?,
+                sources: sources
+                    .and_then(|s| serde_json::from_str(&s).ok())
+                    .unwrap_or_default(),
+                created_at: DateTime::parse_from_rfc3339(&row.get::<_, String>(6)?)
+                    .map_err(|_| rusqlite::Error::InvalidColumnType(6, "TEXT".to_string(), rusqlite::types::Type::Text))?
+                    .with_timezone(&Utc),
+            })
+        })?;
+        rows.collect()
+    }
+    
+    /// Marca os itens como incluídos na sessão de resumo
+    pub fn mark_digest_items(&self, ids: &[i64], session_id: &str) -> SqliteResult<()> {
+        let tx = self.conn.unchecked_transaction()?;
+        {
+            let mut stmt = tx.prepare("UPDATE digest_items SET digest_session_id = ?1 WHERE id = ?2")?;
+            for id in ids {
+                stmt.execute(params![session_id, id])?;
+            }
+        }
+        tx.commit()
+    }
+    
+    /// Grava amostras do histórico de métricas
+    pub fn insert_metric_samples(&self, samples: &[MetricSample]) -> SqliteResult<()> {
+        let tx = self.conn.unchecked_transaction()?;
+        {
+            let mut stmt = tx.prepare(
+                "INSERT OR REPLACE INTO metric_samples
+                 (timestamp, cpu_percent, ram_used_mb, ram_total_mb, vram_used_mb, vram_total_mb,
+                  ollama_rss_mb, ollama_cpu_percent, ollama_processes)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)"
+            )?;
+            for s in samples {
+                stmt.execute(params![
+                    s.timestamp.to_rfc3339(),
+                    s.cpu_percent,
+                    s.ram_used_mb as i64,
+                    s.ram_total_mb as i64,
+                    s.vram_used_mb.map(|v| v as i64),
+                    s.vram_total_mb.map(|v| v as i64),
+                    s.ollama_rss_mb as i64,
+                    s.ollama_cpu_percent,
+                    s.ollama_processes as i64,
+                ])?;
+            }
+        }
+        tx.commit()
+    }
+    
+    /// Amostras gravadas a partir de `since` (ordem cronológica)
+    pub fn get_metric_samples(&self, since: &DateTime<Utc>) -> SqliteResult<Vec<MetricSample>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT timestamp, cpu_percent, ram_used_mb, ram_total_mb, vram_used_mb, vram_total_mb,
+                    ollama_rss_mb, ollama_cpu_percent, ollama_processes
+             FROM metric_samples
+             WHERE timestamp >= ?1
+             ORDER BY timestamp ASC"
+        )?;
+        
+        let rows = stmt.query_map(params![since.to_rfc3339()], |row| {
+            Ok(MetricSample {
+                timestamp: DateTime::parse_from_rfc3339(&row.get::<_, String>(0)?)
+                    .map_err(|_| rusqlite::Error::InvalidColumnType(0, "TEXT".to_string(), rusqlite::types::Type::Text))?
+                    .with_timezone(&Utc),
+                cpu_percent: row.get(1)?,
+                ram_used_mb: row.get::<_, i64>(2)? as u64,
+                ram_total_mb: row.get::<_, i64>(3)? as u64,
+                vram_used_mb: row.get::<_, Option<i64>>(4)?.map(|v| v as u64),
+                vram_total_mb: row.get::<_, Option<i64>>(5)?.map(|v| v as u64),
+                ollama_rss_mb: row.get::<_, i64>(6)? as u64,
+                ollama_cpu_percent: row.get(7)?,
+                ollama_processes: row.get::<_, i64>(8)? as usize,
+            })
+        })?;
+        rows.collect()
+    }
+    
+    /// Remove amostras anteriores a `before` (retenção configurada)
+    pub fn prune_metric_samples(&self, before: &DateTime<Utc>) -> SqliteResult<()> {
+        self.conn.execute(
+            "DELETE FROM metric_samples WHERE timestamp < ?1",
+            params![before.to_rfc3339()],
+        )?;
+        Ok(())
+    }
+    
+    /// Busca um anexo de texto colado por ID
+    pub fn get_paste_attachment(&self, id: &str) -> SqliteResult<Option<PasteAttachment>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT id, session_id, title, content, summary, embedding IS NOT NULL, created_at
+             FROM rag_documents
+             WHERE id = ?1 AND kind = 'paste'"
+        )?;
+        
+        let mut rows = stmt.query_map(params![id], |row| {
+            Ok(PasteAttachment {
+                id: row.get(0)?,
+                session_id: row.get(1)?,
+                title: row.get::<_, Option<String>>(2)?.unwrap_or_default(),
+                content: row.get(3)?,
+                summary: row.get(4)?,
+                has_embedding: row.get(5)?,
+                created_at: DateTime::parse_from_rfc3339(&row.get::<_, String>(6)?)
+                    .map_err(|_| rusqlite::Error::InvalidColumnType(6, "TEXT".to_string(), rusqlite::types::Type::Text))?
+                    .with_timezone(&Utc),
+            })
+        })?;
+        
+        if let Some(row) = rows.next() {
+            row.map(Some)
+        } else {
+            Ok(None)
+        }
+    }
+    
+    fn row_to_conversation_template(row: &rusqlite::Row) -> SqliteResult<ConversationTemplate> {
+        let messages: Option<String> = row.get(4)?;
+        let rag_scope: Option<String> = row.get(6)?;
+        Ok(ConversationTemplate {
+            id: row.get(0)?,
+            name: row.get(1)?,
+            description: row.get(2)?,
+            system_prompt: row.get(3)?,
+            messages: messages
+                .and_then(|m| serde_json::from_str(&m).ok())
+                .unwrap_or_default(),
+            model: row.get(5)?,
+            rag_scope: rag_scope.and_then(|r| serde_json::from_str(&r).ok()),
+            created_at: DateTime::parse_from_rfc3339(&row.get::<_, String>(7)?)
+                .map_err(|_| rusqlite::Error::InvalidColumnType(7, "TEXT".to_string(), rusqlite::types::Type::Text))?
+                .with_timezone(&Utc),
+            updated_at: DateTime::parse_from_rfc3339(&row.get::<_, String>(8)?)
+                .map_err(|_| rusqlite::Error::InvalidColumnType(8, "TEXT".to_string(), rusqlite::types::Type::Text))?
+                .with_timezone(&Utc),
+        })
+    }
+    
+    /// Cria ou atualiza um template de conversa
+    pub fn save_conversation_template(&self, template: &ConversationTemplate) -> SqliteResult<()> {
+        let messages = serde_json::to_string(&template.messages).unwrap_or_else(|_| "[]".to_string());
+        let rag_scope = template.rag_scope.as_ref().and_then(|r| serde_json::to_string(r).ok());
+        self.conn.execute(
+            "INSERT INTO conversation_templates (id, name, description, system_prompt, messages, model, rag_scope, created_at, updated_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)
+             ON CONFLICT(id) DO UPDATE SET
+                name = ?2,
+                description = ?3,
+                system_prompt = ?4,
+                messages = ?5,
+                model = ?6,
+                rag_scope = ?7,
+                updated_at = ?9",
+            params![
+                template.id,
+                template.name,
+                template.description,
+                template.system_prompt,
+                messages,
+                template.model,
+                rag_scope,
+                template.created_at.to_rfc3339(),
+                template.updated_at.to_rfc3339()
+            ],
+        )?;
+        Ok(())
+    }
+    
+    /// Lista templates de conversa ordenados por nome
+    pub fn list_conversation_templates(&self) -> SqliteResult<Vec<ConversationTemplate>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT id, name, description, system_prompt, messages, model, rag_scope, created_at, updated_at
+             FROM conversation_templates ORDER BY name COLLATE NOCASE ASC"
+        )?;
+        
+        let rows = stmt.query_map([], Self::row_to_conversation_template)?;
+        
+        let mut templates = Vec::new();
+        for row in rows {
+            templates.push(row?);
+        }
+        Ok(templates)
+    }
+    
+    /// Busca um template de conversa por ID
+    pub fn get_conversation_template(&self, id: &str) -> SqliteResult<Option<ConversationTemplate>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT id, name, description, system_prompt, messages, model, rag_scope, created_at, updated_at
+             FROM conversation_templates WHERE id = ?1"
+        )?;
+        
+        let mut rows = stmt.query_map(params![id], Self::row_to_conversation_template)?;
+        
+        if let Some(row) = rows.next() {
+            row.map(Some)
+        } else {
+            Ok(None)
+        }
+    }
+    
+    /// Remove um template de conversa (sessões já criadas mantêm sua configuração)
+    pub fn delete_conversation_template(&self, id: &str) -> SqliteResult<()> {
+        self.conn.execute("DELETE FROM conversation_templates WHERE id = ?1", params![id])?;
+        Ok(())
+    }
+    
+    /// Grava a configuração (modelo, system prompt, escopo RAG) de uma sessão
+    pub fn set_session_config(&self, session_id: &str, config: &SessionConfig) -> SqliteResult<()> {
+        let rag_scope = config.rag_scope.as_ref().and_then(|r| serde_json::to_string(r).ok());
+        let generation_options = config.generation_options.as_ref().and_then(|o| serde_json::to_string(o).ok());
+        self.conn.execute(
+            "UPDATE sessions SET template_id = ?1, model = ?2, system_prompt = ?3, rag_scope = ?4, generation_options = ?5
+             WHERE id = ?6",
+            params![config.template_id, config.model, config.system_prompt, rag_scope, generation_options, session_id],
+        )?;
+        Ok(())
+    }
+    
+    /// Configuração gravada na sessão (None se a sessão não existir)
+    pub fn get_session_config(&self, session_id: &str) -> SqliteResult<Option<SessionConfig>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT template_id, model, system_prompt, rag_scope, generation_options FROM sessions WHERE id = ?1"
+        )?;
+        
+        let mut rows = stmt.query_map(params![session_id], |row| {
+            let rag_scope: Option<String> = row.get(3)?;
+            let generation_options: Option<String> = row.get(4)?;
+            Ok(SessionConfig {
+                template_id: row.get(0)?,
+                model: row.get(1)?,
+                system_prompt: row.get(2)?,
+                rag_scope: rag_scope.and_then(|r| serde_json::from_str(&r).ok()),
+                generation_options: generation_options.and_then(|o| serde_json::from_str(&o).ok()),
+            })
+        })?;
+        
+        if let Some(row) = rows.next() {
+            row.map(Some)
+        } else {
+            Ok(None)
+        }
+    }
+    
+    /// Data do registro mais recente de um tipo no audit log
+    pub fn last_audit_log_time(&self, kind: &str) -> SqliteResult<Option<DateTime<Utc>>> {
+        let value: Option<String> = self.conn.query_row(
+            "SELECT MAX(created_at) FROM audit_log WHERE kind = ?1",
+            params![kind],
+            |row| row.get(0),
+        )?;
+        parse_optional_datetime(value, 0)
+    }
+    
+    /// Tamanho em bytes do arquivo do banco somado ao WAL
+    fn database_size(&self) -> (u64, u64) {
+        let path = match self.conn.path() {
+            Some(p) => std::path::PathBuf::from(p),
+            None => return (0, 0),
+        };
+        let db_size = std::fs::metadata(&path).map(|m| m.len()).unwrap_or(0);
+        let wal_size = std::fs::metadata(path.with_extension("db-wal")).map(|m| m.len()).unwrap_or(0);
+        (db_size, wal_size)
+    }
+    
+    /// Problemas reportados por `PRAGMA quick_check`/`integrity_check` (vazio se ok)
+    fn integrity_findings(&self, pragma: &str) -> SqliteResult<Vec<String>> {
+        let mut stmt = self.conn.prepare(pragma)?;
+        let rows = stmt.query_map([], |row| row.get::<_, String>(0))?;
+        let mut findings = Vec::new();
+        for row in rows {
+            let line = row?;
+            if line != "ok" {
+                findings.push(line);
+            }
+        }
+        Ok(findings)
+    }
+    
+    /// Verificação rápida de integridade, sem checkpoint nem VACUUM (usada pelo diagnóstico)
+    pub fn quick_check(&self) -> SqliteResult<Vec<String>> {
+        self.integrity_findings("PRAGMA quick_check")
+    }
+    
+    /// Executa a manutenção do banco: checkpoint do WAL e verificação de integridade;
+    /// no modo completo também VACUUM e REINDEX
+    pub fn run_maintenance(&self, mode: MaintenanceMode) -> SqliteResult<MaintenanceReport> {
+        let started = std::time::Instant::now();
+        let (size_before, wal_size_before) = self.database_size();
+        
+        // Checkpoint truncando o WAL (retorna busy, páginas no log, páginas copiadas)
+        let busy: i64 = self.conn.query_row("PRAGMA wal_checkpoint(TRUNCATE)", [], |row| row.get(0))?;
+        
+        if mode == MaintenanceMode::Full {
+            self.conn.execute_batch("VACUUM; REINDEX;")?;
+            // VACUUM escreve no WAL; truncar de novo para liberar espaço
+            self.conn.query_row("PRAGMA wal_checkpoint(TRUNCATE)", [], |row| row.get::<_, i64>(0))?;
+        }
+        
+        let check_pragma = match mode {
+            MaintenanceMode::Quick => "PRAGMA quick_check",
+            MaintenanceMode::Full => "PRAGMA integrity_check",
+        };
+        let findings = self.integrity_findings(check_pragma)?;
+        
+        let (size_after, wal_size_after) = self.database_size();
+        
+        Ok(MaintenanceReport {
+            mode,
+            size_before,
+            size_after,
+            wal_size_before,
+            wal_size_after,
+            checkpoint_busy: busy != 0,
+            integrity_ok: findings.is_empty(),
+            findings,
+            duration_ms: started.elapsed().as_millis() as u64,
+        })
+    }
+}
+
+/// Associa cada mensagem recebida a uma gravada da sessão, seguindo a ordem: pelo `id`, pela
+/// próxima com o mesmo papel e conteúdo ou, não havendo, pela gravada seguinte se tiver o mesmo
+/// papel (conteúdo editado ou pós-processado). None: mensagem nova.
+fn align_messages(stored: &[ChatMessage], incoming: &[ChatMessage]) -> Vec<Option<usize>> {
+    let mut used = vec![false; stored.len()];
+    let mut cursor = 0;
+    let mut matches = Vec::with_capacity(incoming.len());
+    for message in incoming {
+        let by_id = message
+            .id
+            .and_then(|id| stored.iter().position(|s| s.id == Some(id)))
+            .filter(|&index| !used[index]);
+        let found = by_id
+            .or_else(|| {
+                (cursor..stored.len()).find(|&index| {
+                    !used[index] && stored[index].role == message.role && stored[index].content == message.content
+                })
+            })
+            .or_else(|| {
+                stored
+                    .get(cursor)
+                    .filter(|s| !used[cursor] && s.role == message.role)
+                    .map(|_| cursor)
+            });
+        if let Some(index) = found {
+            used[index] = true;
+            cursor = index + 1;
+        }
+        matches.push(found);
+    }
+    matches
+}
+
+/// Metadados de uma mensagem já gravada: os gravados prevalecem (`set_message_metadata`,
+/// fontes, seed) e os recebidos só acrescentam as chaves que faltam
+fn merge_metadata(stored: Option<&str>, incoming: Option<&str>) -> Option<String> {
+    let parse = |metadata: Option<&str>| metadata.and_then(|m| serde_json::from_str::<serde_json::Value>(m).ok());
+    match (parse(stored), parse(incoming)) {
+        (Some(serde_json::Value::Object(mut stored_map)), Some(serde_json::Value::Object(incoming_map))) => {
+            for (key, value) in incoming_map {
+                stored_map.entry(key).or_insert(value);
+            }
+            serde_json::to_string(&stored_map).ok()
+        }
+        (Some(_), _) => stored.map(str::to_string),
+        (None, _) => incoming.map(str::to_string),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn message(id: Option<i64>, role: &str, content: &str) -> ChatMessage {
+        ChatMessage {
+            id,
+            session_id: "s".to_string(),
+            role: role.to_string(),
+            content: content.to_string(),
+            metadata: None,
+            created_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_align_messages() {
+        let stored = vec![
+            message(Some(1), "user", "oi"),
+            message(Some(2), "assistant", "olá!"),
+            message(Some(3), "user", "e agora?"),
+            message(Some(4), "assistant", "resposta antiga"),
+            message(Some(5), "assistant", "resposta nova"),
+        ];
+        // Página final com IDs, resposta regenerada e mensagem nova
+        let incoming = vec![
+            message(Some(3), "user", "e agora?"),
+            message(None, "assistant", "resposta nova"),
+            message(None, "user", "obrigado"),
+        ];
+        assert_eq!(align_messages(&stored, &incoming), vec![Some(2), Some(4), None]);
+
+        // Sem IDs: mesmo conteúdo ou, na mesma posição, mesmo papel com texto pós-processado
+        let incoming = vec![message(None, "user", "oi"), message(None, "assistant", "olá")];
+        assert_eq!(align_messages(&stored, &incoming), vec![Some(0), Some(1)]);
+    }
+
+    #[test]
+    fn test_merge_metadata() {
+        let merged = merge_metadata(Some(r#"{"seed":7,"pinned":true}"#), Some(r#"{"pinned":false,"title":"T"}"#)).unwrap();
+        let merged: serde_json::Value = serde_json::from_str(&merged).unwrap();
+        assert_eq!(merged, serde_json::json!({"seed": 7, "pinned": true, "title": "T"}));
+        assert_eq!(merge_metadata(None, Some("{}")).as_deref(), Some("{}"));
+        assert_eq!(merge_metadata(Some(r#"{"a":1}"#), None).as_deref(), Some(r#"{"a":1}"#));
+    }
+
+    #[test]
+    fn test_parse_highlight_ranges() {
+        let highlighted = format!("olá {}mundo{} e {}mundo{}", HIGHLIGHT_OPEN, HIGHLIGHT_CLOSE, HIGHLIGHT_OPEN, HIGHLIGHT_CLOSE);
+        assert_eq!(
+            parse_highlight_ranges(&highlighted),
+            vec![MatchRange { start: 4, end: 9 }, MatchRange { start: 12, end: 17 }]
+        );
+        assert_eq!(build_fts_terms_query(" rust  \"fts\" "), "\"rust\" \"\"\"fts\"\"\"");
+    }
+
+    #[test]
+    fn test_rag_scope_allows() {
+        let urls = ["https://docs.rs/serde", "https://www.rust-lang.org/learn", "https://blog.example.com/post"];
+        let all = RagScope { enabled: true, sources: Vec::new() };
+        assert_eq!(urls.iter().filter(|u| all.allows(u)).count(), 3);
+
+        let scoped = RagScope { enabled: true, sources: vec!["rust-lang.org".to_string(), "Example.com".to_string()] };
+        let kept: Vec<_> = urls.iter().filter(|u| scoped.allows(u)).collect();
+        assert_eq!(kept, vec![&"https://www.rust-lang.org/learn", &"https://blog.example.com/post"]);
+        assert!(!scoped.allows("https://notexample.com/"));
+        assert!(!scoped.allows("não é url"));
+    }
+
+    #[test]
+    fn test_build_fts_advanced_query() {
+        assert_eq!(build_fts_advanced_query("rust OR go").unwrap(), "\"rust\" OR \"go\"");
+        assert_eq!(build_fts_advanced_query("tok* \"full text\"").unwrap(), "\"tok\"* \"full text\"");
+        assert_eq!(build_fts_advanced_query("(a AND b) NOT c-d").unwrap(), "( \"a\" AND \"b\" ) NOT \"c-d\"");
+        assert_eq!(build_fts_advanced_query("NEAR(sqlite fts5, 5)").unwrap(), "NEAR(\"sqlite\" \"fts5\", 5)");
+        assert_eq!(build_fts_advanced_query("rust and go").unwrap(), "\"rust\" \"and\" \"go\"");
+
+        assert_eq!(build_fts_advanced_query("rust OR").unwrap_err().position, 5);
+        assert_eq!(build_fts_advanced_query("AND rust").unwrap_err().position, 0);
+        assert_eq!(build_fts_advanced_query("(rust").unwrap_err().position, 0);
+        assert_eq!(build_fts_advanced_query("say \"hi").unwrap_err().position, 4);
+        assert!(build_fts_advanced_query("NEAR(a, x)").is_err());
+        assert_eq!(fts_plain_text("(rust OR go*) NEAR(\"a\" b, 3)"), "rust go a b, 3");
+    }
+}
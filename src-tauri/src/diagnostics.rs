@@ -0,0 +1,285 @@
+//! Diagnóstico para a tela de Suporte (`run_diagnostics`)
+//!
+//! Cada verificação vira um item pass/warn/fail com o detalhe encontrado e, quando algo
+//! está errado, a correção sugerida. Nenhuma verificação altera estado (além de um arquivo
+//! temporário criado e apagado na pasta de chats).
+
+use crate::db::Database;
+use crate::McpProcessMap;
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use tauri::{AppHandle, Manager};
+
+/// Abaixo disso o app pode falhar ao gravar (banco, chats, downloads)
+const DISK_FAIL_BYTES: u64 = 1024 * 1024 * 1024;
+/// Abaixo disso um modelo médio já não cabe
+const DISK_WARN_BYTES: u64 = 10 * 1024 * 1024 * 1024;
+const OLLAMA_TIMEOUT: Duration = Duration::from_secs(5);
+
+#[derive(Serialize, Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "snake_case")]
+pub enum CheckStatus {
+    Pass,
+    Warn,
+    Fail,
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct DiagnosticItem {
+    pub id: String,
+    pub label: String,
+    pub status: CheckStatus,
+    pub detail: String,
+    /// Correção sugerida (None quando passou)
+    pub fix: Option<String>,
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct DiagnosticsReport {
+    /// Pior status entre os itens
+    pub status: CheckStatus,
+    pub items: Vec<DiagnosticItem>,
+    pub generated_at: DateTime<Utc>,
+    pub duration_ms: u64,
+}
+
+fn item(id: &str, label: &str, status: CheckStatus, detail: String, fix: Option<&str>) -> DiagnosticItem {
+    DiagnosticItem {
+        id: id.to_string(),
+        label: label.to_string(),
+        status,
+        detail,
+        fix: fix.filter(|_| status != CheckStatus::Pass).map(str::to_string),
+    }
+}
+
+pub fn overall(items: &[DiagnosticItem]) -> CheckStatus {
+    items.iter().map(|i| i.status).max().unwrap_or(CheckStatus::Pass)
+}
+
+pub fn disk_status(available: u64) -> CheckStatus {
+    if available < DISK_FAIL_BYTES {
+        CheckStatus::Fail
+    } else if available < DISK_WARN_BYTES {
+        CheckStatus::Warn
+    } else {
+        CheckStatus::Pass
+    }
+}
+
+/// Ponto de montagem que contém `path` (o prefixo mais longo)
+pub fn mount_for<'a>(path: &Path, mounts: &'a [PathBuf]) -> Option<&'a PathBuf> {
+    mounts
+        .iter()
+        .filter(|mount| path.starts_with(mount))
+        .max_by_key(|mount| mount.components().count())
+}
+
+fn gib(bytes: u64) -> String {
+    format!("{:.1} GB", bytes as f64 / 1024.0 / 1024.0 / 1024.0)
+}
+
+async fn check_ollama(app_handle: &AppHandle, ollama_url: &str) -> Vec<DiagnosticItem> {
+    let running = crate::check_ollama_running(app_handle.clone()).await;
+    if !running {
+        let (detail, fix) = if crate::check_ollama_installed() {
+            ("Ollama instalado, mas o servidor não responde", "Inicie o Ollama pelo tray ou em Configurações")
+        } else {
+            ("Ollama não está instalado", "Instale o Ollama pelo assistente de primeiro uso")
+        };
+        return vec![
+            item("ollama", "Conexão com o Ollama", CheckStatus::Fail, detail.to_string(), Some(fix)),
+            item("models", "Lista de modelos", CheckStatus::Warn, "Não verificada (Ollama parado)".to_string(), Some("Inicie o Ollama e rode o diagnóstico de novo")),
+        ];
+    }
+
+    let version = crate::ollama_update::server_version(ollama_url).await.unwrap_or_else(|| "versão desconhecida".to_string());
+    let ollama = item("ollama", "Conexão com o Ollama", CheckStatus::Pass, format!("Servidor respondendo ({})", version), None);
+
+    let models = match reqwest::Client::new()
+        .get(format!("{}/api/tags", ollama_url.trim_end_matches('/')))
+        .timeout(OLLAMA_TIMEOUT)
+        .send()
+        .await
+    {
+        Ok(response) if response.status().is_success() => match response.json::<serde_json::Value>().await {
+            Ok(body) => {
+                let count = body.get("models").and_then(|m| m.as_array()).map_or(0, |m| m.len());
+                if count == 0 {
+                    item("models", "Lista de modelos", CheckStatus::Warn, "Nenhum modelo instalado".to_string(), Some("Baixe um modelo recomendado para o seu hardware"))
+                } else {
+                    item("models", "Lista de modelos", CheckStatus::Pass, format!("{} modelo(s) instalados", count), None)
+                }
+            }
+            Err(e) => item("models", "Lista de modelos", CheckStatus::Fail, format!("Resposta inválida do Ollama: {}", e), Some("Atualize o Ollama")),
+        },
+        Ok(response) => item("models", "Lista de modelos", CheckStatus::Fail, format!("Ollama respondeu {}", response.status()), Some("Reinicie o Ollama")),
+        Err(e) => item("models", "Lista de modelos", CheckStatus::Fail, format!("Falha ao listar modelos: {}", e), Some("Confira a URL do Ollama nas configurações")),
+    };
+    vec![ollama, models]
+}
+
+fn check_database(app_handle: &AppHandle) -> DiagnosticItem {
+    const LABEL: &str = "Integridade do banco SQLite";
+    match Database::new(app_handle).and_then(|db| db.quick_check()) {
+        Ok(findings) if findings.is_empty() => item("sqlite", LABEL, CheckStatus::Pass, "quick_check ok".to_string(), None),
+        Ok(findings) => item(
+            "sqlite",
+            LABEL,
+            CheckStatus::Fail,
+            format!("{} problema(s): {}", findings.len(), findings.iter().take(3).cloned().collect::<Vec<_>>().join("; ")),
+            Some("Rode a manutenção completa do banco e restaure um backup se persistir"),
+        ),
+        Err(e) => item("sqlite", LABEL, CheckStatus::Fail, format!("Falha ao abrir o banco: {}", e), Some("Feche outras instâncias do app e confira as permissões da pasta de dados")),
+    }
+}
+
+fn check_chats_dir(app_handle: &AppHandle) -> DiagnosticItem {
+    const LABEL: &str = "Gravação na pasta de chats";
+    const FIX: &str = "Confira as permissões da pasta de dados do app";
+    let dir = match crate::get_chats_dir(app_handle) {
+        Ok(dir) => dir,
+        Err(e) => return item("chats_dir", LABEL, CheckStatus::Fail, e, Some(FIX)),
+    };
+    let probe = dir.join(format!(".diagnostics-{}", uuid::Uuid::new_v4()));
+    let result = std::fs::write(&probe, b"ok");
+    let _ = std::fs::remove_file(&probe);
+    match result {
+        Ok(()) => item("chats_dir", LABEL, CheckStatus::Pass, dir.to_string_lossy().to_string(), None),
+        Err(e) => item("chats_dir", LABEL, CheckStatus::Fail, format!("{}: {}", dir.display(), e), Some(FIX)),
+    }
+}
+
+fn check_chrome() -> DiagnosticItem {
+    const LABEL: &str = "Chrome headless";
+    if !crate::resource_profile::current().headless_scraping_enabled {
+        return item("chrome", LABEL, CheckStatus::Pass, "Desativado pelo modo de baixo consumo (busca só via HTTP)".to_string(), None);
+    }
+    match headless_chrome::browser::default_executable() {
+        Ok(path) => item("chrome", LABEL, CheckStatus::Pass, path.to_string_lossy().to_string(), None),
+        Err(e) => item(
+            "chrome",
+            LABEL,
+            CheckStatus::Warn,
+            format!("Chrome/Chromium não encontrado: {}", e),
+            Some("Instale o Chrome ou Chromium; sem ele a busca web usa apenas HTTP"),
+        ),
+    }
+}
+
+fn check_mcp(app_handle: &AppHandle, processes: &McpProcessMap) -> DiagnosticItem {
+    const LABEL: &str = "Servidores MCP";
+    let config = match crate::load_mcp_config(app_handle.clone()) {
+        Ok(config) => config,
+        Err(e) => return item("mcp", LABEL, CheckStatus::Fail, format!("mcp.json inválido: {}", e), Some("Corrija o mcp.json nas configurações de MCP")),
+    };
+    if config.mcp_servers.is_empty() {
+        return item("mcp", LABEL, CheckStatus::Pass, "Nenhum servidor configurado".to_string(), None);
+    }
+
+    let mut stopped: Vec<String> = match processes.lock() {
+        Ok(mut map) => config
+            .mcp_servers
+            .keys()
+            .filter(|name| !map.get_mut(*name).is_some_and(|handle| matches!(handle.child.try_wait(), Ok(None))))
+            .cloned()
+            .collect(),
+        Err(_) => config.mcp_servers.keys().cloned().collect(),
+    };
+    stopped.sort();
+    let total = config.mcp_servers.len();
+    if stopped.is_empty() {
+        item("mcp", LABEL, CheckStatus::Pass, format!("{} servidor(es) rodando", total), None)
+    } else {
+        item(
+            "mcp",
+            LABEL,
+            CheckStatus::Warn,
+            format!("{} de {} parado(s): {}", stopped.len(), total, stopped.join(", ")),
+            Some("Reinicie os servidores MCP e confira se o comando de cada um está instalado"),
+        )
+    }
+}
+
+fn check_disks(app_handle: &AppHandle) -> Vec<DiagnosticItem> {
+    let disks = sysinfo::Disks::new_with_refreshed_list();
+    let mounts: Vec<PathBuf> = disks.list().iter().map(|d| d.mount_point().to_path_buf()).collect();
+
+    let targets = [
+        ("disk_app_data", "Espaço livre (dados do app)", app_handle.path().app_data_dir().ok()),
+        ("disk_models", "Espaço livre (modelos do Ollama)", crate::storage_usage::ollama_models_dir(&crate::settings::current(app_handle))),
+    ];
+    let mut items: Vec<DiagnosticItem> = Vec::new();
+    let mut seen: Vec<&PathBuf> = Vec::new();
+    for (id, label, path) in targets {
+        let Some(path) = path else { continue };
+        let Some(mount) = mount_for(&path, &mounts) else {
+            items.push(item(id, label, CheckStatus::Warn, format!("Disco de {} não identificado", path.display()), None));
+            continue;
+        };
+        // Os dois no mesmo disco: um item só
+        if seen.contains(&mount) {
+            continue;
+        }
+        seen.push(mount);
+        let Some(disk) = disks.list().iter().find(|d| d.mount_point() == mount.as_path()) else { continue };
+        let available = disk.available_space();
+        items.push(item(
+            id,
+            label,
+            disk_status(available),
+            format!("{} livres de {} em {}", gib(available), gib(disk.total_space()), mount.display()),
+            Some("Libere espaço: remova modelos sem uso ou arquive conversas antigas"),
+        ));
+    }
+    items
+}
+
+/// Executa todas as verificações
+pub async fn run(app_handle: &AppHandle, processes: &McpProcessMap) -> DiagnosticsReport {
+    let started = std::time::Instant::now();
+    let ollama_url = crate::settings::current(app_handle).ollama_url;
+
+    let mut items = check_ollama(app_handle, &ollama_url).await;
+    items.push(check_database(app_handle));
+    items.push(check_chats_dir(app_handle));
+    items.push(check_chrome());
+    items.push(check_mcp(app_handle, processes));
+    items.extend(check_disks(app_handle));
+
+    for failed in items.iter().filter(|i| i.status != CheckStatus::Pass) {
+        log::warn!("[Diagnostics] {} ({:?}): {}", failed.id, failed.status, failed.detail);
+    }
+    DiagnosticsReport {
+        status: overall(&items),
+        items,
+        generated_at: Utc::now(),
+        duration_ms: started.elapsed().as_millis() as u64,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_disk_and_overall_status() {
+        assert_eq!(disk_status(512 * 1024 * 1024), CheckStatus::Fail);
+        assert_eq!(disk_status(5 * 1024 * 1024 * 1024), CheckStatus::Warn);
+        assert_eq!(disk_status(50 * 1024 * 1024 * 1024), CheckStatus::Pass);
+
+        let mounts = vec![PathBuf::from("/"), PathBuf::from("/home"), PathBuf::from("/home/user/data")];
+        assert_eq!(mount_for(Path::new("/home/user/.ollama"), &mounts), Some(&mounts[1]));
+        assert_eq!(mount_for(Path::new("/var/lib"), &mounts), Some(&mounts[0]));
+
+        let items = vec![
+            item("a", "A", CheckStatus::Pass, String::new(), Some("nada")),
+            item("b", "B", CheckStatus::Warn, String::new(), Some("corrigir")),
+        ];
+        assert_eq!(overall(&items), CheckStatus::Warn);
+        assert!(items[0].fix.is_none());
+        assert_eq!(overall(&[]), CheckStatus::Pass);
+    }
+}
@@ -0,0 +1,389 @@
+use crate::db::{Database, DigestItem};
+use crate::Message;
+use chrono::{DateTime, Local, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::str::FromStr;
+use tauri::AppHandle;
+use tauri_plugin_notification::NotificationExt;
+
+/// Tipo registrado no audit_log a cada resumo gerado (marca a última execução)
+const DIGEST_AUDIT_KIND: &str = "digest";
+
+/// Origem de um item do resumo
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum DigestItemKind {
+    /// Resultado de `SearchAndSummarize`
+    ResearchBrief,
+    /// Novidade de um feed acompanhado
+    FeedItem,
+    /// Nova versão de um modelo instalado
+    ModelUpdate,
+    /// Saída de um prompt customizado
+    TaskOutput,
+}
+
+impl DigestItemKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DigestItemKind::ResearchBrief => "research_brief",
+            DigestItemKind::FeedItem => "feed_item",
+            DigestItemKind::ModelUpdate => "model_update",
+            DigestItemKind::TaskOutput => "task_output",
+        }
+    }
+}
+
+/// Fonte citada por um item
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct DigestSource {
+    pub title: String,
+    pub url: String,
+}
+
+/// Seção do resumo: quais itens entram e com qual título
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct DigestSectionTemplate {
+    pub kind: DigestItemKind,
+    pub heading: String,
+}
+
+/// Modelo do resumo diário
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct DigestTemplate {
+    /// Título da sessão; aceita `{date}`
+    #[serde(default = "default_title")]
+    pub title: String,
+    /// Texto antes das seções (opcional); aceita `{date}` e `{count}`
+    #[serde(default)]
+    pub intro: String,
+    /// Seções na ordem de exibição (tipos ausentes ficam fora do resumo)
+    #[serde(default = "default_sections")]
+    pub sections: Vec<DigestSectionTemplate>,
+    #[serde(default = "default_max_items_per_section")]
+    pub max_items_per_section: usize,
+    /// Lista consolidada de fontes no fim do resumo
+    #[serde(default = "default_true")]
+    pub include_sources: bool,
+}
+
+impl Default for DigestTemplate {
+    fn default() -> Self {
+        Self {
+            title: default_title(),
+            intro: String::new(),
+            sections: default_sections(),
+            max_items_per_section: default_max_items_per_section(),
+            include_sources: true,
+        }
+    }
+}
+
+/// Agenda e modelo do resumo diário (settings.json)
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct DigestSettings {
+    #[serde(default)]
+    pub enabled: bool,
+    /// Expressão cron com segundos, no horário local (padrão: todo dia às 8h)
+    #[serde(default = "default_cron_schedule")]
+    pub cron_schedule: String,
+    #[serde(default)]
+    pub template: DigestTemplate,
+}
+
+impl Default for DigestSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            cron_schedule: default_cron_schedule(),
+            template: DigestTemplate::default(),
+        }
+    }
+}
+
+impl DigestSettings {
+    pub fn validate(&self, errors: &mut Vec<String>) {
+        if cron::Schedule::from_str(&self.cron_schedule).is_err() {
+            errors.push(format!("digest.cron_schedule inválido: {}", self.cron_schedule));
+        }
+        if self.template.sections.is_empty() {
+            errors.push("digest.template.sections deve ter pelo menos uma seção".to_string());
+        }
+        if !(1..=50).contains(&self.template.max_items_per_section) {
+            errors.push("digest.template.max_items_per_section deve estar entre 1 e 50".to_string());
+        }
+    }
+}
+
+fn default_title() -> String {
+    "Resumo do dia — {date}".to_string()
+}
+
+fn default_sections() -> Vec<DigestSectionTemplate> {
+    [
+        (DigestItemKind::ResearchBrief, "Pesquisas"),
+        (DigestItemKind::FeedItem, "Feeds"),
+        (DigestItemKind::ModelUpdate, "Atualizações de modelos"),
+        (DigestItemKind::TaskOutput, "Outras tarefas"),
+    ]
+    .into_iter()
+    .map(|(kind, heading)| DigestSectionTemplate { kind, heading: heading.to_string() })
+    .collect()
+}
+
+fn default_max_items_per_section() -> usize {
+    10
+}
+
+fn default_cron_schedule() -> String {
+    "0 0 8 * * *".to_string()
+}
+
+fn default_true() -> bool {
+    true
+}
+
+/// Seção montada (exposta no metadata da mensagem para o frontend)
+#[derive(Serialize, Clone, Debug)]
+pub struct DigestSection {
+    pub kind: DigestItemKind,
+    pub heading: String,
+    pub item_ids: Vec<i64>,
+}
+
+/// Resumo pronto para virar sessão
+#[derive(Serialize, Clone, Debug)]
+pub struct ComposedDigest {
+    pub title: String,
+    pub content: String,
+    pub sections: Vec<DigestSection>,
+    pub sources: Vec<DigestSource>,
+}
+
+impl ComposedDigest {
+    pub fn item_ids(&self) -> Vec<i64> {
+        self.sections.iter().flat_map(|s| s.item_ids.iter().copied()).collect()
+    }
+}
+
+/// Monta o resumo a partir dos itens pendentes (mais recentes primeiro). Itens além do
+/// limite da seção ou de tipos fora do modelo ficam para o próximo resumo.
+pub fn compose(items: &[DigestItem], template: &DigestTemplate, date: &DateTime<Local>) -> Option<ComposedDigest> {
+    let date_label = date.format("%d/%m/%Y").to_string();
+    let mut body = String::new();
+    let mut sections = Vec::new();
+    let mut sources: Vec<DigestSource> = Vec::new();
+    let mut seen_urls = HashSet::new();
+
+    for section in &template.sections {
+        let section_items: Vec<&DigestItem> = items
+            .iter()
+            .filter(|item| item.kind == section.kind.as_str())
+            .take(template.max_items_per_section)
+            .collect();
+        if section_items.is_empty() {
+            continue;
+        }
+
+        body.push_str(&format!("## {}\n\n", section.heading));
+        for item in &section_items {
+            body.push_str(&format!("### {}\n\n{}\n\n", item.title, item.content.trim()));
+            if !item.sources.is_empty() {
+                let links: Vec<String> = item
+                    .sources
+                    .iter()
+                    .map(|s| format!("[{}]({})", s.title, s.url))
+                    .collect();
+                body.push_str(&format!("_Fontes: {}_\n\n", links.join(", ")));
+            }
+            for source in &item.sources {
+                if seen_urls.insert(source.url.clone()) {
+                    sources.push(source.clone());
+                }
+            }
+        }
+
+        sections.push(DigestSection {
+            kind: section.kind,
+            heading: section.heading.clone(),
+            item_ids: section_items.iter().map(|item| item.id).collect(),
+        });
+    }
+
+    if sections.is_empty() {
+        return None;
+    }
+
+    let count: usize = sections.iter().map(|s| s.item_ids.len()).sum();
+    let mut content = String::new();
+    let intro = template.intro.trim();
+    if !intro.is_empty() {
+        content.push_str(&intro.replace("{date}", &date_label).replace("{count}", &count.to_string()));
+        content.push_str("\n\n");
+    }
+    content.push_str(&body);
+    if template.include_sources && !sources.is_empty() {
+        content.push_str("## Fontes\n\n");
+        for source in &sources {
+            content.push_str(&format!("- [{}]({})\n", source.title, source.url));
+        }
+    }
+
+    Some(ComposedDigest {
+        title: template.title.replace("{date}", &date_label),
+        content: content.trim_end().to_string(),
+        sections,
+        sources,
+    })
+}
+
+/// Guarda uma saída para o próximo resumo (ignorado se o resumo estiver desativado)
+pub fn record(
+    app_handle: &AppHandle,
+    kind: DigestItemKind,
+    task_id: Option<&str>,
+    title: &str,
+    content: &str,
+    sources: &[DigestSource],
+) {
+    if !crate::settings::current(app_handle).digest.enabled {
+        return;
+    }
+    if let Err(e) = Database::new(app_handle)
+        .and_then(|db| db.add_digest_item(kind.as_str(), task_id, title, content, sources))
+    {
+        log::warn!("[Digest] Falha ao guardar item '{}': {}", title, e);
+    }
+}
+
+/// Indica se o horário do resumo passou desde a última execução
+pub fn is_due(cron_schedule: &str, last_run: Option<DateTime<Utc>>, now: DateTime<Local>) -> bool {
+    let Ok(schedule) = cron::Schedule::from_str(cron_schedule) else {
+        return false;
+    };
+    // Sem execução anterior, considera apenas o horário que acabou de passar
+    let reference = last_run
+        .map(|t| t.with_timezone(&Local))
+        .unwrap_or(now - chrono::Duration::minutes(2));
+    schedule.after(&reference).next().is_some_and(|next| next <= now)
+}
+
+/// Gera o resumo se estiver habilitado e no horário (chamado periodicamente pelo scheduler)
+pub fn maybe_run(app_handle: &AppHandle) {
+    let settings = crate::settings::current(app_handle).digest;
+    if !settings.enabled {
+        return;
+    }
+
+    let last_run = match Database::new(app_handle).and_then(|db| db.last_audit_log_time(DIGEST_AUDIT_KIND)) {
+        Ok(last_run) => last_run,
+        Err(e) => {
+            log::warn!("[Digest] Falha ao ler última execução: {}", e);
+            return;
+        }
+    };
+    if !is_due(&settings.cron_schedule, last_run, Local::now()) {
+        return;
+    }
+
+    match run(app_handle, "scheduler") {
+        Ok(Some(session_id)) => log::info!("[Digest] Resumo diário salvo na sessão {}", session_id),
+        Ok(None) => log::info!("[Digest] Nenhum item pendente para o resumo"),
+        Err(e) => log::error!("[Digest] Falha ao gerar resumo: {}", e),
+    }
+}
+
+/// Compõe o resumo com os itens pendentes e salva como sessão de chat.
+/// Retorna o ID da sessão (None quando não há itens).
+pub fn run(app_handle: &AppHandle, actor: &str) -> Result<Option<String>, String> {
+    let template = crate::settings::current(app_handle).digest.template;
+    let db = Database::new(app_handle).map_err(|e| format!("Failed to open database: {}", e))?;
+    let items = db
+        .get_pending_digest_items()
+        .map_err(|e| format!("Failed to load digest items: {}", e))?;
+
+    // Registrar a execução mesmo sem itens para não reavaliar o mesmo horário
+    let composed = compose(&items, &template, &Local::now());
+    let detail = composed
+        .as_ref()
+        .map(|d| format!("{} item(s)", d.item_ids().len()))
+        .unwrap_or_else(|| "sem itens".to_string());
+    db.append_audit_log(DIGEST_AUDIT_KIND, actor, &detail)
+        .map_err(|e| format!("Failed to write audit log: {}", e))?;
+
+    let Some(digest) = composed else {
+        return Ok(None);
+    };
+
+    let session_id = uuid::Uuid::new_v4().to_string();
+    let messages = vec![Message {
+        id: None,
+        role: "assistant".to_string(),
+        content: digest.content.clone(),
+        metadata: Some(serde_json::json!({
+            "digest": {
+                "sections": digest.sections,
+            },
+            "sources": digest.sources,
+        })),
+    }];
+    crate::task_executor::save_task_session_internal(app_handle, &session_id, &digest.title, messages)?;
+    db.mark_digest_items(&digest.item_ids(), &session_id)
+        .map_err(|e| format!("Failed to mark digest items: {}", e))?;
+    crate::unread::record(app_handle, &session_id);
+
+    if let Err(e) = app_handle
+        .notification()
+        .builder()
+        .title(&digest.title)
+        .body(&format!("{} novidade(s) reunidas no resumo do dia.", digest.item_ids().len()))
+        .show()
+    {
+        log::warn!("[Digest] Falha ao enviar notificação: {}", e);
+    }
+
+    Ok(Some(session_id))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn item(id: i64, kind: DigestItemKind, title: &str, url: Option<&str>) -> DigestItem {
+        DigestItem {
+            id,
+            kind: kind.as_str().to_string(),
+            task_id: None,
+            title: title.to_string(),
+            content: format!("conteúdo {}", id),
+            sources: url
+                .map(|u| vec![DigestSource { title: "Fonte".to_string(), url: u.to_string() }])
+                .unwrap_or_default(),
+            created_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_compose_and_schedule() {
+        let date = Local.with_ymd_and_hms(2024, 3, 9, 8, 0, 0).unwrap();
+        let template = DigestTemplate { max_items_per_section: 1, ..DigestTemplate::default() };
+        let items = vec![
+            item(3, DigestItemKind::ModelUpdate, "llama3 atualizado", None),
+            item(2, DigestItemKind::ResearchBrief, "IA hoje", Some("https://a.com")),
+            item(1, DigestItemKind::ResearchBrief, "IA ontem", Some("https://a.com")),
+        ];
+
+        let digest = compose(&items, &template, &date).unwrap();
+        assert_eq!(digest.title, "Resumo do dia — 09/03/2024");
+        assert_eq!(digest.item_ids(), vec![2, 3]);
+        assert!(digest.content.find("## Pesquisas").unwrap() < digest.content.find("## Atualizações").unwrap());
+        assert_eq!(digest.sources.len(), 1);
+        assert!(compose(&[], &template, &date).is_none());
+
+        let last = Local.with_ymd_and_hms(2024, 3, 8, 8, 0, 0).unwrap().with_timezone(&Utc);
+        assert!(is_due("0 0 8 * * *", Some(last), date));
+        assert!(!is_due("0 0 8 * * *", Some(date.with_timezone(&Utc)), date));
+    }
+}
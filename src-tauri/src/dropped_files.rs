@@ -0,0 +1,123 @@
+use std::io::Read;
+use std::path::Path;
+
+/// Bytes lidos do início do arquivo para identificar o tipo
+const SNIFF_BYTES: usize = 8192;
+
+/// Extensões tratadas como texto mesmo quando a amostra é ambígua
+const TEXT_EXTENSIONS: &[&str] = &[
+    "txt", "md", "markdown", "csv", "tsv", "json", "jsonl", "yaml", "yml", "toml", "xml", "html", "htm",
+    "log", "ini", "cfg", "rs", "py", "js", "ts", "tsx", "jsx", "java", "c", "h", "cpp", "hpp", "go",
+    "rb", "php", "sh", "sql", "css", "scss", "kt", "swift",
+];
+
+/// Tipo de um arquivo solto na área de chat
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DroppedKind {
+    /// Imagem para modelos com visão (mime type)
+    Image(&'static str),
+    Pdf,
+    Text,
+    /// Modelo GGUF para importar no Ollama
+    Gguf,
+    Unsupported(String),
+}
+
+/// Lê o início do arquivo para detecção por assinatura
+pub fn read_header(path: &Path) -> std::io::Result<Vec<u8>> {
+    let mut file = std::fs::File::open(path)?;
+    let mut buffer = Vec::with_capacity(SNIFF_BYTES);
+    file.by_ref().take(SNIFF_BYTES as u64).read_to_end(&mut buffer)?;
+    Ok(buffer)
+}
+
+/// Classifica pelo conteúdo (assinatura) e, na falta dela, pela extensão
+pub fn classify(path: &Path, header: &[u8]) -> DroppedKind {
+    if header.starts_with(b"GGUF") {
+        return DroppedKind::Gguf;
+    }
+    if header.starts_with(b"%PDF-") {
+        return DroppedKind::Pdf;
+    }
+    if let Some(mime) = image_mime(header) {
+        return DroppedKind::Image(mime);
+    }
+
+    let extension = path
+        .extension()
+        .and_then(|e| e.to_str())
+        .map(|e| e.to_lowercase())
+        .unwrap_or_default();
+
+    if extension == "gguf" {
+        return DroppedKind::Unsupported("Arquivo .gguf sem cabeçalho GGUF válido".to_string());
+    }
+    if looks_like_text(header) || (TEXT_EXTENSIONS.contains(&extension.as_str()) && !header.contains(&0)) {
+        return DroppedKind::Text;
+    }
+
+    DroppedKind::Unsupported(if extension.is_empty() {
+        "Tipo de arquivo não suportado".to_string()
+    } else {
+        format!("Tipo de arquivo não suportado: .{}", extension)
+    })
+}
+
+pub(crate) fn image_mime(header: &[u8]) -> Option<&'static str> {
+    if header.starts_with(&[0x89, b'P', b'N', b'G']) {
+        Some("image/png")
+    } else if header.starts_with(&[0xFF, 0xD8, 0xFF]) {
+        Some("image/jpeg")
+    } else if header.starts_with(b"GIF87a") || header.starts_with(b"GIF89a") {
+        Some("image/gif")
+    } else if header.len() >= 12 && &header[..4] == b"RIFF" && &header[8..12] == b"WEBP" {
+        Some("image/webp")
+    } else if header.starts_with(b"BM") && header.len() >= 14 {
+        Some("image/bmp")
+    } else {
+        None
+    }
+}
+
+/// UTF-8 válido (tolerando um caractere cortado no fim da amostra) e sem bytes nulos
+fn looks_like_text(header: &[u8]) -> bool {
+    if header.is_empty() || header.contains(&0) {
+        return false;
+    }
+    match std::str::from_utf8(header) {
+        Ok(_) => true,
+        Err(e) => e.error_len().is_none() && e.valid_up_to() + 4 > header.len(),
+    }
+}
+
+/// Nome sugerido para um modelo importado (nome do arquivo, minúsculo e sem caracteres inválidos)
+pub fn suggested_model_name(path: &Path) -> String {
+    let stem = path.file_stem().and_then(|s| s.to_str()).unwrap_or("model");
+    let name: String = stem
+        .to_lowercase()
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() || c == '.' || c == '-' || c == '_' { c } else { '-' })
+        .collect();
+    let name = name.trim_matches('-').to_string();
+    if name.is_empty() {
+        "model".to_string()
+    } else {
+        name
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_classify_by_signature_and_extension() {
+        assert_eq!(classify(Path::new("a.bin"), b"GGUF\x03\x00\x00\x00"), DroppedKind::Gguf);
+        assert_eq!(classify(Path::new("doc"), b"%PDF-1.7\n"), DroppedKind::Pdf);
+        assert_eq!(classify(Path::new("x.jpg"), &[0xFF, 0xD8, 0xFF, 0xE0]), DroppedKind::Image("image/jpeg"));
+        assert_eq!(classify(Path::new("notes.md"), "# Título\nção".as_bytes()), DroppedKind::Text);
+        assert!(matches!(classify(Path::new("a.exe"), b"MZ\x90\x00\x03\x00"), DroppedKind::Unsupported(_)));
+        assert!(matches!(classify(Path::new("fake.gguf"), b"hello"), DroppedKind::Unsupported(_)));
+        assert_eq!(suggested_model_name(Path::new("/m/Llama 3.1 8B-Q4_K_M.gguf")), "llama-3.1-8b-q4_k_m");
+    }
+}
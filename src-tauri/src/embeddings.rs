@@ -546,6 +546,111 @@ pub fn prune_context_bm25(
     pruned.join("\n\n")
 }
 
+/// Pausa entre lotes enquanto um chat interativo está em streaming
+const THROTTLE_PAUSE: std::time::Duration = std::time::Duration::from_millis(300);
+
+/// Parâmetros de um job de embeddings em lote
+#[derive(Debug, Clone, Copy)]
+pub struct PoolOptions {
+    pub concurrency: usize,
+    pub batch_size: usize,
+}
+
+/// Carrega uma instância própria do modelo (fora do singleton global)
+fn load_model(app_data_dir: &Path) -> Result<EmbeddingModel> {
+    init_ort_runtime(app_data_dir)?;
+    let model_path = app_data_dir.join("models").join("all-MiniLM-L6-v2.onnx");
+    let tokenizer_path = app_data_dir.join("models").join("tokenizer.json");
+    EmbeddingModel::new(
+        model_path.to_str().unwrap_or(""),
+        tokenizer_path.to_str().unwrap_or("")
+    )
+}
+
+/// Gera embeddings para `items` (id, texto) em um pool de threads dedicado.
+///
+/// Cada worker carrega sua própria sessão ONNX, então o modelo global usado pelo chat
+/// continua livre. Os resultados chegam em lotes de `batch_size` para `on_batch`, que roda
+/// na thread chamadora. Enquanto houver chat em streaming, apenas um worker continua e
+/// com pausa entre lotes. Retorna quantos itens foram processados.
+pub fn embed_in_pool<F>(
+    app_data_dir: &Path,
+    items: Vec<(String, String)>,
+    options: PoolOptions,
+    job: &crate::jobs::JobHandle,
+    mut on_batch: F,
+) -> Result<usize>
+where
+    F: FnMut(Vec<(String, Vec<f32>)>) -> Result<()>,
+{
+    use std::collections::VecDeque;
+    use std::sync::mpsc;
+    
+    let batch_size = options.batch_size.max(1);
+    let mut batches: VecDeque<Vec<(String, String)>> = VecDeque::new();
+    let mut items = items.into_iter().peekable();
+    while items.peek().is_some() {
+        batches.push_back(items.by_ref().take(batch_size).collect());
+    }
+    let workers = options.concurrency.clamp(1, batches.len().max(1));
+    let queue = Mutex::new(batches);
+    let (tx, rx) = mpsc::channel::<Result<Vec<(String, Vec<f32>)>>>();
+    
+    std::thread::scope(|scope| {
+        for index in 0..workers {
+            let worker_tx = tx.clone();
+            let queue = &queue;
+            let spawned = std::thread::Builder::new()
+                .name(format!("embeddings-{}", index))
+                .spawn_scoped(scope, move || {
+                    let mut model = match load_model(app_data_dir) {
+                        Ok(model) => model,
+                        Err(e) => {
+                            let _ = worker_tx.send(Err(e));
+                            return;
+                        }
+                    };
+                    
+                    while !job.is_cancelled() {
+                        if crate::jobs::interactive_active() {
+                            std::thread::sleep(THROTTLE_PAUSE);
+                            if index > 0 {
+                                continue;
+                            }
+                        }
+                        
+                        let batch = match queue.lock().ok().and_then(|mut q| q.pop_front()) {
+                            Some(batch) => batch,
+                            None => break,
+                        };
+                        let texts: Vec<&str> = batch.iter().map(|(_, text)| text.as_str()).collect();
+                        let result = model.embed_batch(&texts).map(|vectors| {
+                            batch.iter().map(|(id, _)| id.clone()).zip(vectors).collect()
+                        });
+                        if worker_tx.send(result).is_err() {
+                            break;
+                        }
+                    }
+                });
+            if let Err(e) = spawned {
+                let _ = tx.send(Err(anyhow!("Failed to spawn embedding worker: {}", e)));
+            }
+        }
+        drop(tx);
+        
+        // Ao retornar com erro, `rx` é descartado e os workers param no próximo envio
+        let mut processed = 0;
+        for result in rx {
+            let batch = result?;
+            let count = batch.len();
+            on_batch(batch)?;
+            processed += count;
+            job.advance(count);
+        }
+        Ok(processed)
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
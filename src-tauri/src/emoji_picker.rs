@@ -0,0 +1,122 @@
+use crate::embeddings;
+use std::path::Path;
+use std::sync::OnceLock;
+
+/// Emoji padrão quando nenhum candidato é parecido o suficiente com o título
+pub const DEFAULT_EMOJI: &str = "💬";
+/// Similaridade mínima (cosseno) para aceitar um candidato
+const MIN_SIMILARITY: f32 = 0.3;
+
+/// Candidatos com descrições em inglês e português (o modelo de embeddings é multilíngue só em parte)
+const CATALOG: &[(&str, &str)] = &[
+    ("💻", "programming code software bug function programação código"),
+    ("🐍", "python script"),
+    ("🗄️", "database sql query banco de dados"),
+    ("🌐", "web website html css javascript site"),
+    ("🤖", "artificial intelligence machine learning llm model inteligência artificial"),
+    ("📊", "data analysis statistics chart spreadsheet análise de dados planilha"),
+    ("🧮", "math equation calculation matemática cálculo"),
+    ("🔬", "science research experiment ciência pesquisa"),
+    ("📚", "explanation study learning concept explicação estudo aprender"),
+    ("✍️", "writing text essay article redação texto artigo"),
+    ("🌍", "translation language tradução idioma"),
+    ("📧", "email message letter e-mail mensagem carta"),
+    ("💼", "business work career job negócios trabalho carreira emprego"),
+    ("💰", "money finance investment budget dinheiro finanças investimento"),
+    ("⚖️", "law legal contract direito jurídico contrato"),
+    ("🩺", "health medicine symptoms doctor saúde medicina sintomas"),
+    ("🏋️", "exercise fitness workout exercício treino academia"),
+    ("🍳", "cooking recipe food culinária receita comida"),
+    ("✈️", "travel trip vacation viagem férias"),
+    ("🎵", "music song musician música canção"),
+    ("🎮", "games gaming video game jogos"),
+    ("🎨", "design art drawing arte desenho"),
+    ("📷", "photo image picture foto imagem"),
+    ("🎬", "movie film series cinema filme série"),
+    ("📰", "news current events notícias atualidades"),
+    ("🏛️", "history historical história"),
+    ("🧠", "psychology mind emotions psicologia mente emoções"),
+    ("❤️", "relationship love family relacionamento amor família"),
+    ("🐧", "linux terminal command line shell"),
+    ("🔧", "fix repair troubleshooting configuration conserto configuração"),
+    ("🔒", "security password privacy segurança senha privacidade"),
+    ("📅", "planning schedule calendar organization planejamento agenda"),
+    ("💡", "ideas brainstorming creativity ideias criatividade"),
+    ("❓", "question doubt how to pergunta dúvida como"),
+];
+
+static CATALOG_EMBEDDINGS: OnceLock<Vec<Vec<f32>>> = OnceLock::new();
+
+/// Emoji para o título da conversa: similaridade de embeddings com o catálogo quando o
+/// modelo local está disponível, palavras-chave caso contrário. Bloqueante (inferência ONNX).
+pub fn pick(app_data_dir: &Path, title: &str) -> String {
+    if !embeddings::is_model_available(app_data_dir) {
+        return pick_by_keywords(title);
+    }
+    match pick_by_embeddings(app_data_dir, title) {
+        Ok(emoji) => emoji,
+        Err(e) => {
+            log::debug!("[Emoji] Embeddings indisponíveis ({}), usando palavras-chave", e);
+            pick_by_keywords(title)
+        }
+    }
+}
+
+fn pick_by_embeddings(app_data_dir: &Path, title: &str) -> Result<String, String> {
+    let model = embeddings::get_or_init_model(app_data_dir).map_err(|e| e.to_string())?;
+    let mut model = model.lock().map_err(|e| format!("Failed to lock embedding model: {}", e))?;
+
+    if CATALOG_EMBEDDINGS.get().is_none() {
+        let descriptions: Vec<&str> = CATALOG.iter().map(|(_, description)| *description).collect();
+        let vectors = model.embed_batch(&descriptions).map_err(|e| e.to_string())?;
+        let _ = CATALOG_EMBEDDINGS.set(vectors);
+    }
+    let catalog = CATALOG_EMBEDDINGS.get().ok_or("Catálogo de emojis não inicializado")?;
+
+    let title_embedding = model.embed(title).map_err(|e| e.to_string())?;
+    Ok(best_match(&title_embedding, catalog)
+        .map(|index| CATALOG[index].0.to_string())
+        .unwrap_or_else(|| pick_by_keywords(title)))
+}
+
+/// Índice do candidato mais parecido, se passar do limiar
+fn best_match(query: &[f32], candidates: &[Vec<f32>]) -> Option<usize> {
+    candidates
+        .iter()
+        .enumerate()
+        .map(|(index, candidate)| (index, embeddings::cosine_similarity(query, candidate)))
+        .filter(|(_, score)| *score >= MIN_SIMILARITY)
+        .max_by(|a, b| a.1.partial_cmp(&b.1).unwrap_or(std::cmp::Ordering::Equal))
+        .map(|(index, _)| index)
+}
+
+/// Fallback sem modelo: primeira palavra do catálogo encontrada no título
+pub fn pick_by_keywords(title: &str) -> String {
+    let words: Vec<String> = title
+        .to_lowercase()
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|w| w.len() > 2)
+        .map(|w| w.to_string())
+        .collect();
+    CATALOG
+        .iter()
+        .find(|(_, description)| description.split_whitespace().any(|k| words.iter().any(|w| w == k)))
+        .map(|(emoji, _)| emoji.to_string())
+        .unwrap_or_else(|| DEFAULT_EMOJI.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_keyword_and_similarity_pick() {
+        assert_eq!(pick_by_keywords("Receita de bolo de cenoura"), "🍳");
+        assert_eq!(pick_by_keywords("Fix Python script error"), "🐍");
+        assert_eq!(pick_by_keywords("Olá"), DEFAULT_EMOJI);
+
+        let candidates = vec![vec![1.0, 0.0], vec![0.6, 0.8]];
+        assert_eq!(best_match(&[0.5, 0.9], &candidates), Some(1));
+        assert_eq!(best_match(&[0.0, -1.0], &candidates), None);
+    }
+}
@@ -0,0 +1,207 @@
+//! Saúde dos motores de busca (`search_engine_health` no SQLite)
+//!
+//! Cada tentativa de busca conta como sucesso, resultado vazio ou erro. Um motor com
+//! `DEMOTE_AFTER_FAILURES` falhas seguidas (vazio conta como falha: selectors quebrados
+//! costumam aparecer assim) sai das buscas até que uma sondagem volte a dar resultados,
+//! evitando esperar pelo timeout dele em toda consulta.
+
+use crate::web_scraper::SearchEngine;
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant};
+use tauri::AppHandle;
+
+/// Falhas consecutivas até o motor ser rebaixado
+pub const DEMOTE_AFTER_FAILURES: u32 = 3;
+/// Intervalo entre sondagens automáticas de um motor rebaixado
+const PROBE_INTERVAL: Duration = Duration::from_secs(30 * 60);
+
+static APP: OnceLock<AppHandle> = OnceLock::new();
+static STATS: Mutex<Option<HashMap<String, EngineHealth>>> = Mutex::new(None);
+/// Última sondagem automática por motor (só em memória)
+static PROBED: Mutex<Option<HashMap<String, Instant>>> = Mutex::new(None);
+
+/// Resultado de uma tentativa de busca
+#[derive(Debug, Clone, PartialEq)]
+pub enum Outcome {
+    Success,
+    Empty,
+    Error(String),
+}
+
+/// Estatísticas acumuladas de um motor (chave de `search_apis.engines`)
+#[derive(Serialize, Clone, Debug, Default, PartialEq)]
+pub struct EngineHealth {
+    pub engine: String,
+    pub successes: u64,
+    pub failures: u64,
+    pub zero_results: u64,
+    /// Falhas ou buscas vazias seguidas
+    pub consecutive_failures: u32,
+    pub last_success_at: Option<DateTime<Utc>>,
+    pub last_failure_at: Option<DateTime<Utc>>,
+    pub last_error: Option<String>,
+}
+
+impl EngineHealth {
+    pub fn new(engine: &str) -> Self {
+        Self { engine: engine.to_string(), ..Self::default() }
+    }
+
+    /// Contabiliza uma tentativa
+    pub fn record(&mut self, outcome: &Outcome, now: DateTime<Utc>) {
+        match outcome {
+            Outcome::Success => {
+                self.successes += 1;
+                self.consecutive_failures = 0;
+                self.last_success_at = Some(now);
+                return;
+            }
+            Outcome::Empty => {
+                self.zero_results += 1;
+                self.last_error = Some("Nenhum resultado".to_string());
+            }
+            Outcome::Error(e) => {
+                self.failures += 1;
+                self.last_error = Some(e.clone());
+            }
+        }
+        self.consecutive_failures += 1;
+        self.last_failure_at = Some(now);
+    }
+
+    pub fn attempts(&self) -> u64 {
+        self.successes + self.failures + self.zero_results
+    }
+
+    pub fn is_demoted(&self) -> bool {
+        self.consecutive_failures >= DEMOTE_AFTER_FAILURES
+    }
+}
+
+/// Linha de `get_search_engine_health`
+#[derive(Serialize, Clone, Debug)]
+pub struct EngineHealthReport {
+    #[serde(flatten)]
+    pub health: EngineHealth,
+    /// Fração de tentativas com resultados (None sem tentativas)
+    pub success_rate: Option<f64>,
+    pub demoted: bool,
+}
+
+/// Carrega as estatísticas salvas; sem isso o registro fica só em memória
+pub fn init(app_handle: &AppHandle) {
+    let _ = APP.set(app_handle.clone());
+    let loaded = crate::db::Database::new(app_handle).and_then(|db| db.list_engine_health());
+    match loaded {
+        Ok(rows) => {
+            let map = rows.into_iter().map(|h| (h.engine.clone(), h)).collect();
+            *STATS.lock().unwrap_or_else(|e| e.into_inner()) = Some(map);
+        }
+        Err(e) => log::warn!("[EngineHealth] Falha ao carregar estatísticas: {}", e),
+    }
+}
+
+/// Registra o resultado de uma tentativa e persiste a linha do motor
+pub fn record(engine: &str, outcome: Outcome) {
+    let health = {
+        let mut guard = STATS.lock().unwrap_or_else(|e| e.into_inner());
+        let entry = guard
+            .get_or_insert_with(HashMap::new)
+            .entry(engine.to_string())
+            .or_insert_with(|| EngineHealth::new(engine));
+        let was_demoted = entry.is_demoted();
+        entry.record(&outcome, Utc::now());
+        if entry.is_demoted() && !was_demoted {
+            log::warn!("[EngineHealth] {} rebaixado após {} falhas seguidas", engine, entry.consecutive_failures);
+        } else if was_demoted && !entry.is_demoted() {
+            log::info!("[EngineHealth] {} voltou a responder", engine);
+        }
+        entry.clone()
+    };
+
+    if let Some(app_handle) = APP.get() {
+        let saved = crate::db::Database::new(app_handle).and_then(|db| db.save_engine_health(&health));
+        if let Err(e) = saved {
+            log::warn!("[EngineHealth] Falha ao salvar estatísticas de {}: {}", engine, e);
+        }
+    }
+}
+
+pub fn is_demoted(engine: &str) -> bool {
+    STATS
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .as_ref()
+        .and_then(|map| map.get(engine))
+        .is_some_and(EngineHealth::is_demoted)
+}
+
+/// Tira os motores rebaixados da lista (se todos estiverem, mantém a lista inteira)
+/// e devolve os rebaixados cuja sondagem automática está vencida
+pub fn partition(engines: Vec<SearchEngine>) -> (Vec<SearchEngine>, Vec<SearchEngine>) {
+    let (demoted, active): (Vec<SearchEngine>, Vec<SearchEngine>) =
+        engines.into_iter().partition(|engine| is_demoted(engine.key()));
+    if active.is_empty() {
+        return (demoted, Vec::new());
+    }
+
+    let mut guard = PROBED.lock().unwrap_or_else(|e| e.into_inner());
+    let probed = guard.get_or_insert_with(HashMap::new);
+    let due = demoted
+        .into_iter()
+        .filter(|engine| {
+            let due = probed.get(engine.key()).map_or(true, |at| at.elapsed() >= PROBE_INTERVAL);
+            if due {
+                probed.insert(engine.key().to_string(), Instant::now());
+            }
+            due
+        })
+        .collect();
+    (active, due)
+}
+
+/// Estatísticas de todos os motores já usados
+pub fn report() -> Vec<EngineHealthReport> {
+    let mut rows: Vec<EngineHealthReport> = STATS
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .as_ref()
+        .map(|map| map.values().cloned().collect::<Vec<_>>())
+        .unwrap_or_default()
+        .into_iter()
+        .map(|health| EngineHealthReport {
+            success_rate: (health.attempts() > 0).then(|| health.successes as f64 / health.attempts() as f64),
+            demoted: health.is_demoted(),
+            health,
+        })
+        .collect();
+    rows.sort_by(|a, b| a.health.engine.cmp(&b.health.engine));
+    rows
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_demotion_and_recovery() {
+        let now = Utc::now();
+        let mut health = EngineHealth::new("bing");
+        health.record(&Outcome::Success, now);
+        health.record(&Outcome::Empty, now);
+        health.record(&Outcome::Error("timeout".to_string()), now);
+        assert!(!health.is_demoted());
+        health.record(&Outcome::Empty, now);
+        assert!(health.is_demoted());
+        assert_eq!((health.successes, health.failures, health.zero_results), (1, 1, 2));
+        assert_eq!(health.last_error.as_deref(), Some("Nenhum resultado"));
+
+        health.record(&Outcome::Success, now);
+        assert!(!health.is_demoted());
+        assert_eq!(health.consecutive_failures, 0);
+        assert_eq!(health.attempts(), 5);
+    }
+}
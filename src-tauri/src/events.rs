@@ -0,0 +1,371 @@
+//! Eventos backend → frontend com payload tipado e versionado
+//!
+//! Cada payload implementa `EventPayload` (nome do evento e versão) e é enviado por `emit`,
+//! que acrescenta o campo `v` com a versão. Mudanças incompatíveis num payload sobem a
+//! versão; campos novos opcionais não. O contrato está em `docs/events.md`.
+//!
+//! Sem chamada a `subscribe_events`, todos os eventos são emitidos (compatível com a UI
+//! antiga). Depois dela, só os tipos assinados; os demais nem são serializados.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::sync::{OnceLock, RwLock};
+use tauri::{Emitter, Runtime};
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[serde(rename_all = "kebab-case")]
+pub enum EventKind {
+    ChatCreated,
+    ChatToken,
+    ChatThinking,
+    ChatError,
+    ChatWarning,
+    ChatRetry,
+    ChatPostprocessed,
+    SessionRecovered,
+    DownloadProgress,
+    #[serde(rename = "system-stats-v2")]
+    SystemStats,
+    SearchQueryRedacted,
+    PolicyViolation,
+    SessionMemoryUpdated,
+    RagContentFlagged,
+    JobProgress,
+    ScrapeProgress,
+    SttPartial,
+    GgufImportProgress,
+    ModelArchiveProgress,
+    ActiveStreamsChanged,
+}
+
+/// Descrição de um evento (resposta de `subscribe_events`)
+#[derive(Serialize, Clone, Debug)]
+pub struct EventDescriptor {
+    pub kind: EventKind,
+    pub version: u32,
+    pub description: &'static str,
+    pub subscribed: bool,
+}
+
+impl EventKind {
+    pub const ALL: [EventKind; 20] = [
+        EventKind::ChatCreated,
+        EventKind::ChatToken,
+        EventKind::ChatThinking,
+        EventKind::ChatError,
+        EventKind::ChatWarning,
+        EventKind::ChatRetry,
+        EventKind::ChatPostprocessed,
+        EventKind::SessionRecovered,
+        EventKind::DownloadProgress,
+        EventKind::SystemStats,
+        EventKind::SearchQueryRedacted,
+        EventKind::PolicyViolation,
+        EventKind::SessionMemoryUpdated,
+        EventKind::RagContentFlagged,
+        EventKind::JobProgress,
+        EventKind::ScrapeProgress,
+        EventKind::SttPartial,
+        EventKind::GgufImportProgress,
+        EventKind::ModelArchiveProgress,
+        EventKind::ActiveStreamsChanged,
+    ];
+
+    /// Nome do evento no Tauri
+    pub fn name(self) -> &'static str {
+        match self {
+            EventKind::ChatCreated => "chat-created",
+            EventKind::ChatToken => "chat-token",
+            EventKind::ChatThinking => "chat-thinking",
+            EventKind::ChatError => "chat-error",
+            EventKind::ChatWarning => "chat-warning",
+            EventKind::ChatRetry => "chat-retry",
+            EventKind::ChatPostprocessed => "chat-postprocessed",
+            EventKind::SessionRecovered => "session-recovered",
+            EventKind::DownloadProgress => "download-progress",
+            EventKind::SystemStats => "system-stats-v2",
+            EventKind::SearchQueryRedacted => "search-query-redacted",
+            EventKind::PolicyViolation => "policy-violation",
+            EventKind::SessionMemoryUpdated => "session-memory-updated",
+            EventKind::RagContentFlagged => "rag-content-flagged",
+            EventKind::JobProgress => "job-progress",
+            EventKind::ScrapeProgress => "scrape-progress",
+            EventKind::SttPartial => "stt-partial",
+            EventKind::GgufImportProgress => "gguf-import-progress",
+            EventKind::ModelArchiveProgress => "model-archive-progress",
+            EventKind::ActiveStreamsChanged => "active-streams-changed",
+        }
+    }
+
+    fn description(self) -> &'static str {
+        match self {
+            EventKind::ChatCreated => "Sessão criada no início de um chat (título e emoji)",
+            EventKind::ChatToken => "Trecho da resposta em streaming; done=true encerra",
+            EventKind::ChatThinking => "Trecho do raciocínio do modelo, à parte da resposta",
+            EventKind::ChatError => "Falha definitiva do streaming",
+            EventKind::ChatWarning => "Aviso sem interromper a resposta (contexto, saída estruturada)",
+            EventKind::ChatRetry => "Nova tentativa após queda de conexão",
+            EventKind::ChatPostprocessed => "Conteúdo final quando o pós-processamento o altera",
+            EventKind::SessionRecovered => "Resposta interrompida recuperada na inicialização",
+            EventKind::DownloadProgress => "Progresso do download de um modelo",
+            EventKind::SystemStats => "Amostra do monitor de sistema",
+            EventKind::SearchQueryRedacted => "Consulta com dados pessoais removidos antes da busca externa",
+            EventKind::PolicyViolation => "Acesso à rede barrado numa sessão em modo offline",
+            EventKind::SessionMemoryUpdated => "Memória da sessão alterada (item manual ou fato extraído)",
+            EventKind::RagContentFlagged => "Página da web com possível prompt injection (limpa antes do prompt)",
+            EventKind::JobProgress => "Estado de um job em segundo plano (embeddings, exportações, scraping)",
+            EventKind::ScrapeProgress => "Andamento de cada URL de um scraping em lote",
+            EventKind::SttPartial => "Trecho transcrito durante o ditado",
+            EventKind::GgufImportProgress => "Progresso da importação de um GGUF (hash, envio, criação)",
+            EventKind::ModelArchiveProgress => "Progresso da exportação ou importação de um pacote de modelo",
+            EventKind::ActiveStreamsChanged => "Respostas em geração no momento (uma por sessão)",
+        }
+    }
+}
+
+/// Payload de um evento tipado
+pub trait EventPayload: Serialize + Clone {
+    const KIND: EventKind;
+    const VERSION: u32 = 1;
+}
+
+/// Payload como enviado: os campos do evento mais `v`
+#[derive(Serialize, Clone)]
+struct Versioned<'a, P> {
+    v: u32,
+    #[serde(flatten)]
+    payload: &'a P,
+}
+
+/// None: todos os eventos (ninguém chamou `subscribe_events` ainda)
+fn subscriptions() -> &'static RwLock<Option<HashSet<EventKind>>> {
+    static SUBSCRIPTIONS: OnceLock<RwLock<Option<HashSet<EventKind>>>> = OnceLock::new();
+    SUBSCRIPTIONS.get_or_init(|| RwLock::new(None))
+}
+
+pub fn is_subscribed(kind: EventKind) -> bool {
+    subscriptions()
+        .read()
+        .map(|subs| subs.as_ref().map_or(true, |set| set.contains(&kind)))
+        .unwrap_or(true)
+}
+
+/// Define os eventos emitidos (lista vazia: nenhum; None: todos)
+pub fn subscribe(kinds: Option<Vec<EventKind>>) -> Vec<EventDescriptor> {
+    if let Ok(mut subs) = subscriptions().write() {
+        *subs = kinds.map(|kinds| kinds.into_iter().collect());
+    }
+    describe()
+}
+
+pub fn describe() -> Vec<EventDescriptor> {
+    EventKind::ALL
+        .iter()
+        .map(|&kind| EventDescriptor {
+            kind,
+            version: version_of(kind),
+            description: kind.description(),
+            subscribed: is_subscribed(kind),
+        })
+        .collect()
+}
+
+fn version_of(kind: EventKind) -> u32 {
+    match kind {
+        EventKind::ChatCreated => ChatCreatedEvent::VERSION,
+        EventKind::ChatToken => ChatTokenEvent::VERSION,
+        EventKind::ChatThinking => ChatThinkingEvent::VERSION,
+        EventKind::ChatError => ChatErrorEvent::VERSION,
+        EventKind::ChatWarning => ChatWarningEvent::VERSION,
+        EventKind::ChatRetry => crate::stream_manager::ChatRetryEvent::VERSION,
+        EventKind::ChatPostprocessed => ChatPostProcessedEvent::VERSION,
+        EventKind::SessionRecovered => SessionRecoveredEvent::VERSION,
+        EventKind::DownloadProgress => DownloadProgress::VERSION,
+        EventKind::SystemStats => crate::system_monitor::SystemStatsV2::VERSION,
+        EventKind::SearchQueryRedacted => crate::redaction::SearchQueryRedactedEvent::VERSION,
+        EventKind::PolicyViolation => crate::offline::PolicyViolationEvent::VERSION,
+        EventKind::SessionMemoryUpdated => crate::memory::SessionMemoryUpdatedEvent::VERSION,
+        EventKind::RagContentFlagged => crate::content_guard::RagContentFlaggedEvent::VERSION,
+        EventKind::JobProgress => crate::jobs::JobInfo::VERSION,
+        EventKind::ScrapeProgress => crate::web_scraper::ScrapeProgressEvent::VERSION,
+        EventKind::SttPartial => crate::stt::SttPartialEvent::VERSION,
+        EventKind::GgufImportProgress => crate::gguf_import::ImportProgress::VERSION,
+        EventKind::ModelArchiveProgress => crate::model_archive::ArchiveProgress::VERSION,
+        EventKind::ActiveStreamsChanged => crate::stream_manager::ActiveStreamsChangedEvent::VERSION,
+    }
+}
+
+/// Emite o evento se ele estiver assinado
+pub fn emit<R: Runtime, E: Emitter<R>, P: EventPayload>(emitter: &E, payload: &P) -> tauri::Result<()> {
+    if !is_subscribed(P::KIND) {
+        return Ok(());
+    }
+    emitter.emit(P::KIND.name(), Versioned { v: P::VERSION, payload })
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct ChatCreatedEvent {
+    pub session_id: String,
+    pub title: String,
+    pub emoji: String,
+}
+
+impl EventPayload for ChatCreatedEvent {
+    const KIND: EventKind = EventKind::ChatCreated;
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct ChatTokenEvent {
+    pub session_id: String,
+    pub content: String,
+    pub done: bool,
+}
+
+impl EventPayload for ChatTokenEvent {
+    const KIND: EventKind = EventKind::ChatToken;
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct ChatErrorEvent {
+    pub session_id: String,
+    pub error: String,
+}
+
+impl EventPayload for ChatErrorEvent {
+    const KIND: EventKind = EventKind::ChatError;
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct ChatWarningEvent {
+    pub session_id: String,
+    pub warning: String,
+}
+
+impl EventPayload for ChatWarningEvent {
+    const KIND: EventKind = EventKind::ChatWarning;
+}
+
+/// Trecho do raciocínio de modelos como o deepseek-r1, transmitido à parte da resposta
+#[derive(Serialize, Clone, Debug)]
+pub struct ChatThinkingEvent {
+    pub session_id: String,
+    pub content: String,
+}
+
+impl EventPayload for ChatThinkingEvent {
+    const KIND: EventKind = EventKind::ChatThinking;
+}
+
+/// Conteúdo final após o pós-processamento, quando difere do que foi transmitido
+#[derive(Serialize, Clone, Debug)]
+pub struct ChatPostProcessedEvent {
+    pub session_id: String,
+    pub content: String,
+    pub reasoning: Option<String>,
+}
+
+impl EventPayload for ChatPostProcessedEvent {
+    const KIND: EventKind = EventKind::ChatPostprocessed;
+}
+
+/// Resposta interrompida (crash ou encerramento durante o streaming) recuperada na inicialização
+#[derive(Serialize, Clone, Debug)]
+pub struct SessionRecoveredEvent {
+    pub session_id: String,
+    pub model: String,
+    pub partial_chars: usize,
+    pub tokens: i64,
+    pub interrupted_at: DateTime<Utc>,
+}
+
+impl EventPayload for SessionRecoveredEvent {
+    const KIND: EventKind = EventKind::SessionRecovered;
+}
+
+/// Progresso de `pull_model`. A v1 é um objeto (antes era uma string JSON)
+#[derive(Serialize, Clone, Debug)]
+pub struct DownloadProgress {
+    /// "pulling", "verifying", "success"
+    pub status: String,
+    /// 0-100
+    pub percent: Option<u8>,
+    /// "552 MB"
+    pub downloaded: Option<String>,
+    /// "1.2 GB"
+    pub total: Option<String>,
+    /// "25 MB/s"
+    pub speed: Option<String>,
+    /// Linha original do Ollama (fallback)
+    pub raw: String,
+}
+
+impl EventPayload for DownloadProgress {
+    const KIND: EventKind = EventKind::DownloadProgress;
+}
+
+impl EventPayload for crate::stream_manager::ChatRetryEvent {
+    const KIND: EventKind = EventKind::ChatRetry;
+}
+
+impl EventPayload for crate::system_monitor::SystemStatsV2 {
+    const KIND: EventKind = EventKind::SystemStats;
+}
+
+impl EventPayload for crate::redaction::SearchQueryRedactedEvent {
+    const KIND: EventKind = EventKind::SearchQueryRedacted;
+}
+
+impl EventPayload for crate::offline::PolicyViolationEvent {
+    const KIND: EventKind = EventKind::PolicyViolation;
+}
+
+impl EventPayload for crate::memory::SessionMemoryUpdatedEvent {
+    const KIND: EventKind = EventKind::SessionMemoryUpdated;
+}
+
+impl EventPayload for crate::content_guard::RagContentFlaggedEvent {
+    const KIND: EventKind = EventKind::RagContentFlagged;
+}
+
+impl EventPayload for crate::jobs::JobInfo {
+    const KIND: EventKind = EventKind::JobProgress;
+}
+
+impl EventPayload for crate::web_scraper::ScrapeProgressEvent {
+    const KIND: EventKind = EventKind::ScrapeProgress;
+}
+
+impl EventPayload for crate::stt::SttPartialEvent {
+    const KIND: EventKind = EventKind::SttPartial;
+}
+
+impl EventPayload for crate::gguf_import::ImportProgress {
+    const KIND: EventKind = EventKind::GgufImportProgress;
+}
+
+impl EventPayload for crate::model_archive::ArchiveProgress {
+    const KIND: EventKind = EventKind::ModelArchiveProgress;
+}
+
+/// A v1 é um objeto com `streams` (antes era a lista direto)
+impl EventPayload for crate::stream_manager::ActiveStreamsChangedEvent {
+    const KIND: EventKind = EventKind::ActiveStreamsChanged;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_versioned_payload_and_names() {
+        let event = ChatTokenEvent { session_id: "s1".to_string(), content: "oi".to_string(), done: false };
+        let value = serde_json::to_value(Versioned { v: ChatTokenEvent::VERSION, payload: &event }).unwrap();
+        assert_eq!(value, serde_json::json!({ "v": 1, "session_id": "s1", "content": "oi", "done": false }));
+
+        // O nome serializado do tipo é o nome do evento no Tauri
+        for kind in EventKind::ALL {
+            assert_eq!(serde_json::to_value(kind).unwrap(), kind.name());
+        }
+    }
+}
@@ -0,0 +1,492 @@
+//! Importação do histórico de outros apps (ChatGPT, Claude e Open WebUI)
+//!
+//! Cada conversa vira uma sessão com os horários originais. O ID da sessão é derivado do
+//! ID da conversa no app de origem, então importar o mesmo export de novo pula as
+//! conversas já importadas.
+
+use crate::db::{self, Database};
+use chrono::{DateTime, Duration, TimeZone, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use std::collections::HashSet;
+use std::fs;
+use std::io::Read;
+use std::path::Path;
+use tauri::AppHandle;
+
+/// Arquivo procurado dentro de exports em ZIP (ChatGPT e Claude)
+const CONVERSATIONS_FILE: &str = "conversations.json";
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ExternalFormat {
+    /// `conversations.json` do ChatGPT (árvore `mapping`)
+    Chatgpt,
+    /// `conversations.json` do Claude (`chat_messages`)
+    Claude,
+    /// JSON exportado pelo Open WebUI (antigo Ollama WebUI)
+    OpenWebui,
+}
+
+impl ExternalFormat {
+    fn key(self) -> &'static str {
+        match self {
+            ExternalFormat::Chatgpt => "chatgpt",
+            ExternalFormat::Claude => "claude",
+            ExternalFormat::OpenWebui => "open_webui",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ImportedMessage {
+    pub role: String,
+    pub content: String,
+    pub created_at: Option<DateTime<Utc>>,
+    pub model: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ImportedConversation {
+    pub external_id: String,
+    pub title: String,
+    pub created_at: Option<DateTime<Utc>>,
+    pub updated_at: Option<DateTime<Utc>>,
+    pub messages: Vec<ImportedMessage>,
+}
+
+/// Resultado de `import_external_history`
+#[derive(Serialize, Clone, Debug)]
+pub struct ExternalImportReport {
+    pub format: ExternalFormat,
+    pub sessions_imported: usize,
+    pub messages_imported: usize,
+    /// Conversas já importadas antes
+    pub skipped_existing: usize,
+    /// Conversas sem mensagens de usuário/assistente
+    pub skipped_empty: usize,
+    pub errors: Vec<String>,
+}
+
+fn from_unix(value: &Value) -> Option<DateTime<Utc>> {
+    let secs = value.as_f64()?;
+    // Open WebUI grava alguns horários em milissegundos ou nanossegundos
+    let secs = match secs {
+        s if s > 1e17 => s / 1e9,
+        s if s > 1e11 => s / 1e3,
+        s => s,
+    };
+    Utc.timestamp_millis_opt((secs * 1000.0) as i64).single()
+}
+
+fn from_iso(value: &Value) -> Option<DateTime<Utc>> {
+    DateTime::parse_from_rfc3339(value.as_str()?).ok().map(|d| d.with_timezone(&Utc))
+}
+
+fn normalize_role(role: &str) -> Option<&'static str> {
+    match role {
+        "user" | "human" => Some("user"),
+        "assistant" => Some("assistant"),
+        _ => None,
+    }
+}
+
+/// Formato pelo primeiro item do export
+pub fn detect_format(root: &Value) -> Option<ExternalFormat> {
+    let first = match root {
+        Value::Array(items) => items.first()?,
+        other => other,
+    };
+    if first.get("mapping").is_some() {
+        Some(ExternalFormat::Chatgpt)
+    } else if first.get("chat_messages").is_some() {
+        Some(ExternalFormat::Claude)
+    } else if first.get("chat").is_some() || first.get("history").is_some() {
+        Some(ExternalFormat::OpenWebui)
+    } else {
+        None
+    }
+}
+
+fn items(root: &Value) -> Vec<&Value> {
+    match root {
+        Value::Array(items) => items.iter().collect(),
+        other => vec![other],
+    }
+}
+
+/// Ramo visível da árvore do ChatGPT: de `current_node` até a raiz, pelos `parent`
+fn parse_chatgpt(conversation: &Value) -> Option<ImportedConversation> {
+    let mapping = conversation.get("mapping")?.as_object()?;
+    let mut node_id = conversation["current_node"].as_str().map(str::to_string);
+    let mut visited = HashSet::new();
+    let mut messages = Vec::new();
+    while let Some(id) = node_id.take() {
+        if !visited.insert(id.clone()) {
+            break;
+        }
+        let Some(node) = mapping.get(&id) else { break };
+        node_id = node["parent"].as_str().map(str::to_string);
+
+        let message = &node["message"];
+        let Some(role) = message["author"]["role"].as_str().and_then(normalize_role) else {
+            continue;
+        };
+        if message["metadata"]["is_visually_hidden_from_conversation"].as_bool() == Some(true) {
+            continue;
+        }
+        let content = message["content"]["parts"]
+            .as_array()
+            .map(|parts| {
+                parts
+                    .iter()
+                    .map(|part| match part {
+                        Value::String(text) => text.clone(),
+                        _ => "[anexo]".to_string(),
+                    })
+                    .collect::<Vec<_>>()
+                    .join("\n")
+            })
+            .or_else(|| message["content"]["text"].as_str().map(str::to_string))
+            .unwrap_or_default();
+        if content.trim().is_empty() {
+            continue;
+        }
+        messages.push(ImportedMessage {
+            role: role.to_string(),
+            content,
+            created_at: from_unix(&message["create_time"]),
+            model: message["metadata"]["model_slug"].as_str().map(str::to_string),
+        });
+    }
+    messages.reverse();
+
+    Some(ImportedConversation {
+        external_id: conversation["conversation_id"]
+            .as_str()
+            .or_else(|| conversation["id"].as_str())
+            .map(str::to_string)
+            .unwrap_or_else(|| conversation["create_time"].to_string()),
+        title: conversation["title"].as_str().unwrap_or_default().to_string(),
+        created_at: from_unix(&conversation["create_time"]),
+        updated_at: from_unix(&conversation["update_time"]),
+        messages,
+    })
+}
+
+fn parse_claude(conversation: &Value) -> Option<ImportedConversation> {
+    let messages = conversation["chat_messages"]
+        .as_array()?
+        .iter()
+        .filter_map(|message| {
+            let role = normalize_role(message["sender"].as_str()?)?;
+            let mut content = message["text"].as_str().unwrap_or_default().to_string();
+            if content.trim().is_empty() {
+                content = message["content"]
+                    .as_array()
+                    .map(|blocks| {
+                        blocks
+                            .iter()
+                            .filter_map(|block| block["text"].as_str())
+                            .collect::<Vec<_>>()
+                            .join("\n")
+                    })
+                    .unwrap_or_default();
+            }
+            (!content.trim().is_empty()).then(|| ImportedMessage {
+                role: role.to_string(),
+                content,
+                created_at: from_iso(&message["created_at"]),
+                model: None,
+            })
+        })
+        .collect();
+
+    Some(ImportedConversation {
+        external_id: conversation["uuid"].as_str()?.to_string(),
+        title: conversation["name"].as_str().unwrap_or_default().to_string(),
+        created_at: from_iso(&conversation["created_at"]),
+        updated_at: from_iso(&conversation["updated_at"]),
+        messages,
+    })
+}
+
+/// Open WebUI: ramo atual de `chat.history` (pelos `parentId`), ou `chat.messages`
+fn parse_open_webui(item: &Value) -> Option<ImportedConversation> {
+    let chat = item.get("chat").unwrap_or(item);
+    let history = &chat["history"];
+    let mut raw: Vec<&Value> = Vec::new();
+    if let (Some(nodes), Some(current)) = (history["messages"].as_object(), history["currentId"].as_str()) {
+        let mut node_id = Some(current.to_string());
+        let mut visited = HashSet::new();
+        while let Some(id) = node_id.take() {
+            if !visited.insert(id.clone()) {
+                break;
+            }
+            let Some(node) = nodes.get(&id) else { break };
+            raw.push(node);
+            node_id = node["parentId"].as_str().map(str::to_string);
+        }
+        raw.reverse();
+    } else if let Some(messages) = chat["messages"].as_array() {
+        raw = messages.iter().collect();
+    }
+
+    let messages = raw
+        .into_iter()
+        .filter_map(|message| {
+            let role = normalize_role(message["role"].as_str()?)?;
+            let content = message["content"].as_str().unwrap_or_default().to_string();
+            (!content.trim().is_empty()).then(|| ImportedMessage {
+                role: role.to_string(),
+                content,
+                created_at: from_unix(&message["timestamp"]),
+                model: message["model"]
+                    .as_str()
+                    .or_else(|| message["models"][0].as_str())
+                    .map(str::to_string),
+            })
+        })
+        .collect();
+
+    Some(ImportedConversation {
+        external_id: item["id"]
+            .as_str()
+            .or_else(|| chat["id"].as_str())
+            .map(str::to_string)
+            .unwrap_or_else(|| item["created_at"].to_string()),
+        title: item["title"]
+            .as_str()
+            .or_else(|| chat["title"].as_str())
+            .unwrap_or_default()
+            .to_string(),
+        created_at: from_unix(&item["created_at"]).or_else(|| from_unix(&chat["timestamp"])),
+        updated_at: from_unix(&item["updated_at"]),
+        messages,
+    })
+}
+
+pub fn parse(root: &Value, format: ExternalFormat) -> Vec<ImportedConversation> {
+    let parser: fn(&Value) -> Option<ImportedConversation> = match format {
+        ExternalFormat::Chatgpt => parse_chatgpt,
+        ExternalFormat::Claude => parse_claude,
+        ExternalFormat::OpenWebui => parse_open_webui,
+    };
+    items(root).into_iter().filter_map(parser).collect()
+}
+
+/// ID da sessão para uma conversa importada (o mesmo em importações repetidas)
+pub fn session_id_for(format: ExternalFormat, external_id: &str) -> String {
+    let digest = Sha256::digest(format!("{}:{}", format.key(), external_id).as_bytes());
+    let mut bytes = [0u8; 16];
+    bytes.copy_from_slice(&digest[..16]);
+    uuid::Uuid::from_bytes(bytes).to_string()
+}
+
+/// Conteúdo do export: o JSON direto ou o `conversations.json` de dentro do ZIP
+fn read_export(path: &Path) -> Result<String, String> {
+    if path.extension().is_some_and(|ext| ext.eq_ignore_ascii_case("zip")) {
+        let file = fs::File::open(path).map_err(|e| format!("Failed to open export: {}", e))?;
+        let mut archive = zip::ZipArchive::new(file).map_err(|e| format!("Failed to read export archive: {}", e))?;
+        let name = archive
+            .file_names()
+            .find(|name| Path::new(name).file_name().is_some_and(|f| f == CONVERSATIONS_FILE))
+            .map(str::to_string)
+            .ok_or_else(|| format!("{} não encontrado no arquivo", CONVERSATIONS_FILE))?;
+        let mut content = String::new();
+        archive
+            .by_name(&name)
+            .map_err(|e| format!("Failed to read {}: {}", name, e))?
+            .read_to_string(&mut content)
+            .map_err(|e| format!("Failed to read {}: {}", name, e))?;
+        return Ok(content);
+    }
+    fs::read_to_string(path).map_err(|e| format!("Failed to read export: {}", e))
+}
+
+/// Grava a conversa como sessão (SQLite e JSON legado) preservando os horários originais.
+/// O JSON é gravado por último; se falhar, a sessão sai do banco para a próxima importação
+/// tentar de novo em vez de contá-la como existente.
+fn save_conversation(
+    app_handle: &AppHandle,
+    db: &Database,
+    format: ExternalFormat,
+    session_id: &str,
+    conversation: ImportedConversation,
+) -> Result<usize, String> {
+    let fallback = conversation.created_at.or(conversation.updated_at).unwrap_or_else(Utc::now);
+    // Mensagens sem horário (ou fora de ordem) ficam um segundo depois da anterior
+    let mut last = fallback - Duration::seconds(1);
+    let messages: Vec<(ImportedMessage, DateTime<Utc>)> = conversation
+        .messages
+        .into_iter()
+        .map(|message| {
+            let at = message.created_at.filter(|at| *at > last).unwrap_or(last + Duration::seconds(1));
+            last = at;
+            (message, at)
+        })
+        .collect();
+    let created_at = conversation.created_at.unwrap_or(fallback);
+    let updated_at = conversation.updated_at.unwrap_or(last).max(last);
+    let title = match conversation.title.trim() {
+        "" => messages
+            .iter()
+            .find(|(m, _)| m.role == "user")
+            .map(|(m, _)| m.content.chars().take(60).collect::<String>())
+            .unwrap_or_else(|| "Conversa importada".to_string()),
+        title => title.to_string(),
+    };
+
+    let metadata = |message: &ImportedMessage| {
+        serde_json::json!({ "imported": { "source": format.key(), "model": message.model } })
+    };
+    let legacy = crate::ChatSession {
+        id: session_id.to_string(),
+        title: title.clone(),
+        messages: messages
+            .iter()
+            .map(|(message, _)| crate::Message {
+                id: None,
+                role: message.role.clone(),
+                content: message.content.clone(),
+                metadata: Some(metadata(message)),
+            })
+            .collect(),
+        created_at,
+        updated_at,
+        platform: format.key().to_string(),
+        memory_context: Vec::new(),
+    };
+    let json = serde_json::to_string_pretty(&legacy).map_err(|e| format!("Failed to serialize session: {}", e))?;
+    let path = crate::get_chats_dir(app_handle)?.join(format!("{}.json", session_id));
+
+    let chat_messages: Vec<db::ChatMessage> = messages
+        .iter()
+        .map(|(message, at)| db::ChatMessage {
+            id: None,
+            session_id: session_id.to_string(),
+            role: message.role.clone(),
+            content: message.content.clone(),
+            metadata: Some(metadata(message).to_string()),
+            created_at: *at,
+        })
+        .collect();
+    let session = db::ChatSession { id: session_id.to_string(), title, emoji: "💬".to_string(), created_at, updated_at };
+    db.create_session_with_messages(&session, &chat_messages)
+        .map_err(|e| format!("Failed to save session: {}", e))?;
+    if let Err(e) = fs::write(&path, json) {
+        let _ = fs::remove_file(&path);
+        if let Err(db_error) = db.delete_session(session_id) {
+            log::warn!("[Import] Falha ao desfazer a sessão {}: {}", session_id, db_error);
+        }
+        return Err(format!("Failed to write session file: {}", e));
+    }
+    Ok(chat_messages.len())
+}
+
+/// Importa o export (formato detectado quando `format` é None)
+pub fn import(app_handle: &AppHandle, path: &Path, format: Option<ExternalFormat>) -> Result<ExternalImportReport, String> {
+    let content = read_export(path)?;
+    let root: Value = serde_json::from_str(&content).map_err(|e| format!("Failed to parse export: {}", e))?;
+    let format = format
+        .or_else(|| detect_format(&root))
+        .ok_or_else(|| "Formato do export não reconhecido (use chatgpt, claude ou open_webui)".to_string())?;
+    let db = Database::new(app_handle).map_err(|e| format!("Failed to open database: {}", e))?;
+
+    let mut report = ExternalImportReport {
+        format,
+        sessions_imported: 0,
+        messages_imported: 0,
+        skipped_existing: 0,
+        skipped_empty: 0,
+        errors: Vec::new(),
+    };
+    for conversation in parse(&root, format) {
+        if conversation.messages.is_empty() {
+            report.skipped_empty += 1;
+            continue;
+        }
+        let session_id = session_id_for(format, &conversation.external_id);
+        match db.get_session(&session_id) {
+            Ok(Some(_)) => {
+                report.skipped_existing += 1;
+                continue;
+            }
+            Ok(None) => {}
+            Err(e) => {
+                report.errors.push(format!("{}: {}", conversation.external_id, e));
+                continue;
+            }
+        }
+        let external_id = conversation.external_id.clone();
+        match save_conversation(app_handle, &db, format, &session_id, conversation) {
+            Ok(count) => {
+                report.sessions_imported += 1;
+                report.messages_imported += count;
+            }
+            Err(e) => report.errors.push(format!("{}: {}", external_id, e)),
+        }
+    }
+
+    log::info!(
+        "[Import] {} conversas ({} mensagens) importadas de {} ({} já existiam)",
+        report.sessions_imported,
+        report.messages_imported,
+        format.key(),
+        report.skipped_existing
+    );
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_parse_exports() {
+        let chatgpt = json!([{
+            "title": "Receita", "create_time": 1700000000.5, "update_time": 1700000100.0,
+            "conversation_id": "c1", "current_node": "n3",
+            "mapping": {
+                "root": { "message": null, "parent": null },
+                "n1": { "parent": "root", "message": { "author": { "role": "system" }, "content": { "parts": [""] } } },
+                "n2": { "parent": "n1", "message": { "author": { "role": "user" }, "content": { "parts": ["Oi"] }, "create_time": 1700000001.0 } },
+                "n2b": { "parent": "n1", "message": { "author": { "role": "user" }, "content": { "parts": ["ramo antigo"] } } },
+                "n3": { "parent": "n2", "message": { "author": { "role": "assistant" }, "content": { "parts": ["Olá!"] }, "metadata": { "model_slug": "gpt-4o" } } }
+            }
+        }]);
+        assert_eq!(detect_format(&chatgpt), Some(ExternalFormat::Chatgpt));
+        let parsed = parse(&chatgpt, ExternalFormat::Chatgpt);
+        assert_eq!(parsed[0].messages.iter().map(|m| m.content.as_str()).collect::<Vec<_>>(), ["Oi", "Olá!"]);
+        assert_eq!(parsed[0].messages[1].model.as_deref(), Some("gpt-4o"));
+        assert_eq!(parsed[0].created_at.unwrap().timestamp_millis(), 1700000000500);
+
+        let claude = json!([{
+            "uuid": "u1", "name": "Plano", "created_at": "2024-05-01T10:00:00Z", "updated_at": "2024-05-01T10:05:00Z",
+            "chat_messages": [
+                { "sender": "human", "text": "Olá", "created_at": "2024-05-01T10:00:00Z" },
+                { "sender": "assistant", "text": "", "content": [{ "type": "text", "text": "Oi!" }], "created_at": "2024-05-01T10:00:05Z" }
+            ]
+        }]);
+        assert_eq!(detect_format(&claude), Some(ExternalFormat::Claude));
+        let parsed = parse(&claude, ExternalFormat::Claude);
+        assert_eq!((parsed[0].messages[0].role.as_str(), parsed[0].messages[1].content.as_str()), ("user", "Oi!"));
+
+        let webui = json!([{
+            "id": "w1", "title": "Teste", "created_at": 1700000000,
+            "chat": { "history": { "currentId": "b", "messages": {
+                "a": { "id": "a", "parentId": null, "role": "user", "content": "pergunta", "timestamp": 1700000000 },
+                "b": { "id": "b", "parentId": "a", "role": "assistant", "content": "resposta", "model": "llama3", "timestamp": 1700000005 }
+            }}}
+        }]);
+        assert_eq!(detect_format(&webui), Some(ExternalFormat::OpenWebui));
+        let parsed = parse(&webui, ExternalFormat::OpenWebui);
+        assert_eq!(parsed[0].messages.len(), 2);
+        assert_eq!(parsed[0].messages[1].model.as_deref(), Some("llama3"));
+
+        assert_eq!(session_id_for(ExternalFormat::Claude, "u1"), session_id_for(ExternalFormat::Claude, "u1"));
+        assert_ne!(session_id_for(ExternalFormat::Claude, "u1"), session_id_for(ExternalFormat::Chatgpt, "u1"));
+    }
+}
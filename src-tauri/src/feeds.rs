@@ -0,0 +1,185 @@
+//! Feeds RSS/Atom como fonte de conteúdo (`feeds` em sources.json)
+//!
+//! Mais barato e estável que raspar buscadores: tasks agendadas e o RAG leem os itens
+//! recentes direto dos feeds. Os GUIDs já entregues ficam no SQLite (`feed_seen_items`),
+//! então cada item aparece uma única vez.
+
+use crate::sources_config::{load_sources_config, FeedSource};
+use serde::Serialize;
+use std::time::Duration;
+use tauri::AppHandle;
+
+const FETCH_TIMEOUT: Duration = Duration::from_secs(20);
+/// Caracteres do resumo de cada item
+const SUMMARY_CHARS: usize = 1200;
+
+/// Item de um feed
+#[derive(Serialize, Clone, Debug, PartialEq)]
+pub struct FeedItem {
+    pub feed_url: String,
+    pub feed_title: Option<String>,
+    pub category: String,
+    pub guid: String,
+    pub title: String,
+    pub url: Option<String>,
+    /// Texto simples (HTML do feed convertido)
+    pub summary: String,
+    pub published_at: Option<String>,
+}
+
+impl FeedItem {
+    /// Mesmo formato das páginas extraídas, para entrar no contexto junto com elas
+    pub fn into_scraped(self) -> crate::web_scraper::ScrapedContent {
+        let markdown = format!("# {}\n\n{}", self.title, self.summary);
+        crate::web_scraper::ScrapedContent {
+            title: self.title,
+            url: self.url.unwrap_or(self.feed_url),
+            content: self.summary,
+            markdown,
+            original_language: None,
+            published_at: self.published_at,
+        }
+    }
+}
+
+fn plain_text(html: &str) -> String {
+    let text = html2text::from_read(html.as_bytes(), 120);
+    let text = text.trim();
+    if text.chars().count() > SUMMARY_CHARS {
+        format!("{}…", text.chars().take(SUMMARY_CHARS).collect::<String>())
+    } else {
+        text.to_string()
+    }
+}
+
+/// Interpreta o conteúdo de um feed RSS ou Atom
+pub fn parse(feed: &FeedSource, bytes: &[u8]) -> Result<Vec<FeedItem>, String> {
+    let parsed = feed_rs::parser::parse(bytes)
+        .map_err(|e| format!("Failed to parse feed {}: {}", feed.url, e))?;
+    let feed_title = feed.title.clone().or_else(|| parsed.title.map(|t| t.content));
+
+    Ok(parsed
+        .entries
+        .into_iter()
+        .map(|entry| {
+            let url = entry
+                .links
+                .iter()
+                .find(|link| link.rel.as_deref().map_or(true, |rel| rel == "alternate"))
+                .or_else(|| entry.links.first())
+                .map(|link| link.href.clone());
+            let summary = entry
+                .summary
+                .map(|s| s.content)
+                .or_else(|| entry.content.and_then(|c| c.body))
+                .map(|html| plain_text(&html))
+                .unwrap_or_default();
+            FeedItem {
+                feed_url: feed.url.clone(),
+                feed_title: feed_title.clone(),
+                category: feed.category.clone(),
+                guid: entry.id,
+                title: entry.title.map(|t| t.content.trim().to_string()).unwrap_or_default(),
+                url,
+                summary,
+                published_at: entry.published.or(entry.updated).map(|d| d.to_rfc3339()),
+            }
+        })
+        .collect())
+}
+
+async fn fetch(feed: &FeedSource) -> Result<Vec<FeedItem>, String> {
+    let client = crate::web_scraper::client_builder(None)
+        .timeout(FETCH_TIMEOUT)
+        .user_agent(concat!("OllaHub/", env!("CARGO_PKG_VERSION")))
+        .build()
+        .map_err(|e| format!("Failed to create HTTP client: {}", e))?;
+    let response = client
+        .get(feed.url.trim())
+        .send()
+        .await
+        .map_err(|e| format!("Failed to fetch feed {}: {}", feed.url, e))?;
+    if !response.status().is_success() {
+        return Err(format!("Failed to fetch feed {}: HTTP {}", feed.url, response.status()));
+    }
+    let bytes = response
+        .bytes()
+        .await
+        .map_err(|e| format!("Failed to read feed {}: {}", feed.url, e))?;
+    parse(feed, &bytes)
+}
+
+/// Itens dos feeds ativos das categorias (vazio: todas), mais recentes primeiro.
+/// Sem `include_seen`, só os itens ainda não entregues; os retornados passam a contar como vistos.
+pub async fn fetch_feeds(
+    app_handle: &AppHandle,
+    categories: &[String],
+    include_seen: bool,
+) -> Result<Vec<FeedItem>, String> {
+    let config = load_sources_config(app_handle)?;
+    let feeds: Vec<&FeedSource> = config
+        .feeds
+        .iter()
+        .filter(|feed| feed.enabled && (categories.is_empty() || categories.contains(&feed.category)))
+        .collect();
+    if feeds.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let results = futures_util::future::join_all(feeds.iter().map(|feed| fetch(feed))).await;
+    let db = crate::db::Database::new(app_handle)
+        .map_err(|e| format!("Failed to open database: {}", e))?;
+    let mut items = Vec::new();
+    for result in results {
+        match result {
+            Ok(feed_items) => {
+                for item in feed_items {
+                    let new = db.mark_feed_item_seen(&item.feed_url, &item.guid)
+                        .map_err(|e| format!("Failed to record feed item: {}", e))?;
+                    if new || include_seen {
+                        items.push(item);
+                    }
+                }
+            }
+            // Um feed fora do ar não impede os demais
+            Err(e) => log::warn!("[Feeds] {}", e),
+        }
+    }
+
+    items.sort_by(|a, b| b.published_at.cmp(&a.published_at));
+    log::info!("[Feeds] {} itens de {} feeds", items.len(), feeds.len());
+    Ok(items)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_rss_and_atom() {
+        let feed = FeedSource {
+            url: "https://example.org/feed".to_string(),
+            title: None,
+            category: "tech".to_string(),
+            enabled: true,
+        };
+        let rss = r#"<?xml version="1.0"?><rss version="2.0"><channel><title>Exemplo</title>
+            <item><guid>item-1</guid><title>Novo modelo</title><link>https://example.org/1</link>
+            <description>&lt;p&gt;Lançado &lt;b&gt;hoje&lt;/b&gt;&lt;/p&gt;</description>
+            <pubDate>Sat, 09 Mar 2024 10:00:00 GMT</pubDate></item></channel></rss>"#.as_bytes();
+        let items = parse(&feed, rss).unwrap();
+        assert_eq!(items.len(), 1);
+        assert_eq!((items[0].guid.as_str(), items[0].title.as_str()), ("item-1", "Novo modelo"));
+        assert_eq!(items[0].url.as_deref(), Some("https://example.org/1"));
+        assert_eq!(items[0].feed_title.as_deref(), Some("Exemplo"));
+        assert!(items[0].summary.contains("Lançado"));
+        assert_eq!(items[0].published_at.as_deref(), Some("2024-03-09T10:00:00+00:00"));
+
+        let atom = r#"<?xml version="1.0"?><feed xmlns="http://www.w3.org/2005/Atom"><title>Atom</title>
+            <entry><id>urn:x:2</id><title>Artigo</title><link rel="alternate" href="https://example.org/2"/>
+            <updated>2024-03-10T08:00:00Z</updated></entry></feed>"#.as_bytes();
+        let items = parse(&feed, atom).unwrap();
+        assert_eq!(items[0].guid, "urn:x:2");
+        assert_eq!(items[0].url.as_deref(), Some("https://example.org/2"));
+    }
+}
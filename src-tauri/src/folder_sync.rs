@@ -0,0 +1,654 @@
+//! Sincronização entre dispositivos por uma pasta do usuário (Syncthing, Dropbox...)
+//!
+//! Cada dispositivo só acrescenta linhas aos próprios arquivos em
+//! `<pasta>/OllaHub/devices/<device_id>/` (um JSONL por sessão e um para as configurações),
+//! então o serviço de sincronização nunca vê dois dispositivos escrevendo no mesmo arquivo.
+//! Mensagens são identificadas por um `uid` derivado do conteúdo e a importação é uma
+//! união: nada é removido, e remoções locais não se propagam. Uma sessão removida aqui
+//! ganha um registro `Deleted` e só volta se outro dispositivo a usar depois disso; sessões
+//! no arquivo frio nunca são recriadas. Nas configurações vale a gravação mais recente,
+//! só com as chaves de `SYNCED_SETTINGS`.
+
+use crate::db::{self, Database};
+use crate::FileLockMap;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use tauri::{AppHandle, Manager};
+
+/// Pasta criada dentro da pasta escolhida pelo usuário
+const SYNC_ROOT: &str = "OllaHub";
+const SETTINGS_FILE: &str = "settings.jsonl";
+/// Configurações aplicadas a partir de outros dispositivos. Ficam de fora as que dependem
+/// da máquina (URL e ambiente do Ollama, baixo consumo, autostart) e as que executam
+/// programas, liberam arquivos ao modelo ou desligam proteções (stt, builtin_tools, audit,
+/// redaction, content_guard): quem escreve na pasta compartilhada não decide sobre elas.
+const SYNCED_SETTINGS: &[&str] = &[
+    "theme",
+    "language",
+    "default_model",
+    "scraping",
+    "telemetry_opt_in",
+    "truncate_undo_days",
+    "smart_paste_threshold",
+    "model_rate_limits",
+    "auto_db_maintenance",
+    "embeddings",
+    "model_context_overrides",
+    "chat_templates",
+    "pinned_models",
+    "digest",
+    "metrics_history",
+    "logging",
+    "stream_retry",
+    "max_concurrent_streams",
+    "generation_defaults",
+    "post_processing",
+    "retention",
+    "memory",
+    "scheduler",
+];
+
+/// Impede duas sincronizações ao mesmo tempo (agendada e `sync_now`)
+static RUNNING: Mutex<()> = Mutex::new(());
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Default)]
+pub struct SyncSettings {
+    #[serde(default)]
+    pub enabled: bool,
+    /// Pasta compartilhada entre os dispositivos (caminho absoluto)
+    #[serde(default)]
+    pub folder: Option<String>,
+    #[serde(default = "default_include_settings")]
+    pub include_settings: bool,
+}
+
+fn default_include_settings() -> bool {
+    true
+}
+
+impl SyncSettings {
+    pub fn validate(&self, errors: &mut Vec<String>) {
+        match self.folder.as_deref().map(str::trim) {
+            Some(folder) if !folder.is_empty() => {
+                if !Path::new(folder).is_absolute() {
+                    errors.push("sync.folder deve ser um caminho absoluto".to_string());
+                }
+            }
+            _ if self.enabled => errors.push("sync.folder é obrigatório com a sincronização ativa".to_string()),
+            _ => {}
+        }
+    }
+}
+
+/// Linha dos arquivos JSONL de sincronização
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum SyncRecord {
+    /// Título, emoji e contexto fixado da sessão (vale o mais recente)
+    Session {
+        device: String,
+        at: DateTime<Utc>,
+        session_id: String,
+        title: String,
+        emoji: String,
+        created_at: DateTime<Utc>,
+        #[serde(default)]
+        memory_context: Vec<String>,
+    },
+    Message {
+        device: String,
+        at: DateTime<Utc>,
+        session_id: String,
+        uid: String,
+        role: String,
+        content: String,
+        #[serde(default)]
+        metadata: Option<Value>,
+        created_at: DateTime<Utc>,
+    },
+    /// Sessão removida neste dispositivo (não é recriada por registros mais antigos)
+    Deleted {
+        device: String,
+        at: DateTime<Utc>,
+        session_id: String,
+    },
+    /// Configurações com as chaves de `SYNCED_SETTINGS`
+    Settings {
+        device: String,
+        at: DateTime<Utc>,
+        settings: Value,
+    },
+}
+
+/// Resultado de uma sincronização
+#[derive(Serialize, Clone, Debug, Default)]
+pub struct SyncReport {
+    pub device_id: String,
+    /// Outros dispositivos encontrados na pasta
+    pub devices: Vec<String>,
+    pub imported_sessions: usize,
+    pub imported_messages: usize,
+    pub exported_sessions: usize,
+    pub exported_messages: usize,
+    pub settings_exported: bool,
+    pub settings_applied: bool,
+    pub errors: Vec<String>,
+}
+
+/// Identificador estável de uma mensagem: a mesma mensagem tem o mesmo `uid` em todos os
+/// dispositivos (`occurrence` separa mensagens repetidas na mesma sessão)
+pub fn message_uid(session_id: &str, role: &str, content: &str, occurrence: usize) -> String {
+    let mut hasher = Sha256::new();
+    for part in [session_id, role, content, &occurrence.to_string()] {
+        hasher.update(part.as_bytes());
+        hasher.update([0u8]);
+    }
+    hasher.finalize().iter().take(16).map(|b| format!("{:02x}", b)).collect()
+}
+
+/// `uid` de cada mensagem, na ordem recebida
+pub fn assign_uids<'a>(session_id: &str, messages: impl IntoIterator<Item = (&'a str, &'a str)>) -> Vec<String> {
+    let mut seen: HashMap<(&str, &str), usize> = HashMap::new();
+    messages
+        .into_iter()
+        .map(|(role, content)| {
+            let occurrence = seen.entry((role, content)).or_insert(0);
+            let uid = message_uid(session_id, role, content, *occurrence);
+            *occurrence += 1;
+            uid
+        })
+        .collect()
+}
+
+/// Registros de um JSONL; linhas inválidas (ex.: arquivo ainda sendo copiado) são ignoradas
+pub fn parse_records(text: &str) -> Vec<SyncRecord> {
+    text.lines()
+        .filter(|line| !line.trim().is_empty())
+        .filter_map(|line| serde_json::from_str(line).ok())
+        .collect()
+}
+
+/// Só as configurações de `SYNCED_SETTINGS`
+pub fn shared_settings(mut settings: Value) -> Value {
+    if let Some(map) = settings.as_object_mut() {
+        map.retain(|key, _| SYNCED_SETTINGS.contains(&key.as_str()));
+    }
+    settings
+}
+
+/// ID deste dispositivo (gerado uma vez, fora do settings.json para não ser sincronizado)
+pub fn device_id(app_handle: &AppHandle) -> Result<String, String> {
+    let path = app_handle
+        .path()
+        .app_data_dir()
+        .map_err(|e| format!("Failed to get app data dir: {}", e))?
+        .join("device_id");
+    if let Ok(id) = fs::read_to_string(&path) {
+        if !id.trim().is_empty() {
+            return Ok(id.trim().to_string());
+        }
+    }
+    let id = uuid::Uuid::new_v4().to_string();
+    fs::write(&path, &id).map_err(|e| format!("Failed to save device id: {}", e))?;
+    Ok(id)
+}
+
+fn read_records(path: &Path) -> Vec<SyncRecord> {
+    fs::read_to_string(path).map(|text| parse_records(&text)).unwrap_or_default()
+}
+
+fn append_records(path: &Path, records: &[SyncRecord]) -> Result<(), String> {
+    if records.is_empty() {
+        return Ok(());
+    }
+    // Uma gravação interrompida deixa a última linha incompleta: começa numa linha nova
+    let mut lines = String::new();
+    let ends_mid_line = fs::File::open(path)
+        .and_then(|mut file| {
+            file.seek(SeekFrom::End(-1))?;
+            let mut last = [0u8; 1];
+            file.read_exact(&mut last)?;
+            Ok(last[0] != b'\n')
+        })
+        .unwrap_or(false);
+    if ends_mid_line {
+        lines.push('\n');
+    }
+    for record in records {
+        lines.push_str(&serde_json::to_string(record).map_err(|e| format!("Failed to serialize sync record: {}", e))?);
+        lines.push('\n');
+    }
+    let mut file = fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .map_err(|e| format!("Failed to open sync file: {}", e))?;
+    file.write_all(lines.as_bytes()).map_err(|e| format!("Failed to write sync file: {}", e))
+}
+
+/// Registros de todos os dispositivos (o próprio incluído), por dispositivo
+fn read_devices(devices_dir: &Path) -> HashMap<String, (Vec<SyncRecord>, Vec<SyncRecord>)> {
+    let mut devices = HashMap::new();
+    let Ok(entries) = fs::read_dir(devices_dir) else {
+        return devices;
+    };
+    for entry in entries.flatten().filter(|e| e.path().is_dir()) {
+        let dir = entry.path();
+        let settings = read_records(&dir.join(SETTINGS_FILE));
+        let sessions = fs::read_dir(dir.join("sessions"))
+            .map(|files| {
+                files
+                    .flatten()
+                    .filter(|f| f.path().extension().is_some_and(|ext| ext == "jsonl"))
+                    .flat_map(|f| read_records(&f.path()))
+                    .collect()
+            })
+            .unwrap_or_default();
+        devices.insert(entry.file_name().to_string_lossy().to_string(), (sessions, settings));
+    }
+    devices
+}
+
+fn record_at(record: &SyncRecord) -> DateTime<Utc> {
+    match record {
+        SyncRecord::Session { at, .. }
+        | SyncRecord::Message { at, .. }
+        | SyncRecord::Deleted { at, .. }
+        | SyncRecord::Settings { at, .. } => *at,
+    }
+}
+
+/// Contexto fixado da sessão (só existe no JSON legado)
+fn legacy_session(app_handle: &AppHandle, session_id: &str) -> Option<crate::ChatSession> {
+    let path = crate::get_chats_dir(app_handle).ok()?.join(format!("{}.json", session_id));
+    serde_json::from_str(&fs::read_to_string(path).ok()?).ok()
+}
+
+/// Cria ou atualiza o JSON legado de uma sessão recebida e reescreve suas mensagens a partir
+/// do SQLite (sem o JSON, `cleanup_orphan_sessions` removeria a sessão)
+fn write_legacy_session(
+    app_handle: &AppHandle,
+    db: &Database,
+    session: &db::ChatSession,
+    memory_context: Option<Vec<String>>,
+) -> Result<(), String> {
+    let file_locks = app_handle.state::<FileLockMap>();
+    let path = crate::get_chats_dir(app_handle)?.join(format!("{}.json", session.id));
+    {
+        let lock = {
+            let mut locks_map = file_locks.lock().map_err(|e| format!("Failed to lock file locks map: {}", e))?;
+            locks_map.entry(session.id.clone()).or_default().clone()
+        };
+        let _guard = lock.lock().map_err(|e| format!("Failed to acquire file lock for session {}: {}", session.id, e))?;
+
+        let mut legacy = legacy_session(app_handle, &session.id).unwrap_or_else(|| crate::ChatSession {
+            id: session.id.clone(),
+            title: session.title.clone(),
+            messages: Vec::new(),
+            created_at: session.created_at,
+            updated_at: session.updated_at,
+            platform: "sync".to_string(),
+            memory_context: Vec::new(),
+        });
+        legacy.title = session.title.clone();
+        if let Some(memory_context) = memory_context {
+            legacy.memory_context = memory_context;
+        }
+        let json = serde_json::to_string_pretty(&legacy).map_err(|e| format!("Failed to serialize session: {}", e))?;
+        fs::write(&path, json).map_err(|e| format!("Failed to write session file: {}", e))?;
+    }
+    crate::sync_legacy_session_messages(app_handle, &file_locks, db, &session.id)
+}
+
+/// Aplica as configurações mais recentes dos outros dispositivos, se forem mais novas que
+/// a última gravação deste
+fn import_settings(
+    app_handle: &AppHandle,
+    devices: &HashMap<String, (Vec<SyncRecord>, Vec<SyncRecord>)>,
+    own_id: &str,
+) -> Result<bool, String> {
+    let own_latest = devices.get(own_id).and_then(|(_, settings)| settings.iter().map(record_at).max());
+    let newest = devices
+        .iter()
+        .filter(|(id, _)| id.as_str() != own_id)
+        .flat_map(|(_, (_, settings))| settings.iter())
+        .filter(|record| own_latest.map_or(true, |own| record_at(record) > own))
+        .max_by_key(|record| record_at(record));
+    let Some(SyncRecord::Settings { settings, device, .. }) = newest else {
+        return Ok(false);
+    };
+
+    let state = app_handle.state::<crate::settings::SettingsState>();
+    let before = state.lock().map(|s| s.clone()).map_err(|e| format!("Failed to lock settings: {}", e))?;
+    let updated = crate::apply_settings_patch(app_handle, &state, &shared_settings(settings.clone()))?;
+    if updated != before {
+        log::info!("[Sync] Configurações recebidas do dispositivo {}", device);
+    }
+    Ok(updated != before)
+}
+
+/// Grava as configurações atuais se mudaram desde a última gravação deste dispositivo
+fn export_settings(app_handle: &AppHandle, own_dir: &Path, own_id: &str, own_records: &[SyncRecord]) -> Result<bool, String> {
+    let current = serde_json::to_value(crate::settings::current(app_handle))
+        .map_err(|e| format!("Failed to serialize settings: {}", e))?;
+    let current = shared_settings(current);
+    let last = own_records.iter().max_by_key(|record| record_at(record));
+    if matches!(last, Some(SyncRecord::Settings { settings, .. }) if *settings == current) {
+        return Ok(false);
+    }
+    append_records(
+        &own_dir.join(SETTINGS_FILE),
+        &[SyncRecord::Settings { device: own_id.to_string(), at: Utc::now(), settings: current }],
+    )?;
+    Ok(true)
+}
+
+/// Exporta, importa e exporta de novo: configurações locais ainda não gravadas vencem as
+/// remotas mais antigas, e mensagens recebidas não são regravadas por este dispositivo
+pub fn sync_now(app_handle: &AppHandle) -> Result<SyncReport, String> {
+    let sync = crate::settings::current(app_handle).sync;
+    let folder = sync
+        .folder
+        .as_deref()
+        .map(str::trim)
+        .filter(|f| !f.is_empty())
+        .ok_or_else(|| "Pasta de sincronização não configurada".to_string())?;
+    let _running = RUNNING.try_lock().map_err(|_| "Sincronização já em andamento".to_string())?;
+
+    let own_id = device_id(app_handle)?;
+    let devices_dir = PathBuf::from(folder).join(SYNC_ROOT).join("devices");
+    let own_dir = devices_dir.join(&own_id);
+    fs::create_dir_all(own_dir.join("sessions")).map_err(|e| format!("Failed to create sync folder: {}", e))?;
+
+    let mut report = SyncReport { device_id: own_id.clone(), ..SyncReport::default() };
+    let db = Database::new(app_handle).map_err(|e| format!("Failed to open database: {}", e))?;
+
+    if sync.include_settings {
+        let own_settings = read_records(&own_dir.join(SETTINGS_FILE));
+        report.settings_exported = export_settings(app_handle, &own_dir, &own_id, &own_settings)?;
+    }
+
+    let devices = read_devices(&devices_dir);
+    report.devices = devices.keys().filter(|id| **id != own_id).cloned().collect();
+    report.devices.sort();
+
+    if sync.include_settings {
+        match import_settings(app_handle, &devices, &own_id) {
+            Ok(applied) => report.settings_applied = applied,
+            Err(e) => report.errors.push(format!("settings: {}", e)),
+        }
+    }
+
+    // Tudo que já está na pasta, de qualquer dispositivo
+    let mut known_uids: HashSet<String> = HashSet::new();
+    let mut headers: HashMap<String, SyncRecord> = HashMap::new();
+    let mut remote_messages: HashMap<String, Vec<SyncRecord>> = HashMap::new();
+    // Último registro deste dispositivo por sessão (`true` se for `Deleted`) e a atividade
+    // mais recente dos outros dispositivos (remoções deles não recriam nada aqui)
+    let mut own_latest: HashMap<String, (DateTime<Utc>, bool)> = HashMap::new();
+    let mut remote_latest: HashMap<String, DateTime<Utc>> = HashMap::new();
+    for (device, (records, _)) in &devices {
+        for record in records {
+            let at = record_at(record);
+            if let SyncRecord::Session { session_id, .. } | SyncRecord::Message { session_id, .. } | SyncRecord::Deleted { session_id, .. } =
+                record
+            {
+                if device == &own_id {
+                    let deleted = matches!(record, SyncRecord::Deleted { .. });
+                    let latest = own_latest.entry(session_id.clone()).or_insert((at, deleted));
+                    if at >= latest.0 {
+                        *latest = (at, deleted);
+                    }
+                } else if !matches!(record, SyncRecord::Deleted { .. }) {
+                    let latest = remote_latest.entry(session_id.clone()).or_insert(at);
+                    *latest = (*latest).max(at);
+                }
+            }
+            match record {
+                SyncRecord::Session { session_id, at, .. } => {
+                    if headers.get(session_id).map_or(true, |current| record_at(current) < *at) {
+                        headers.insert(session_id.clone(), record.clone());
+                    }
+                }
+                SyncRecord::Message { session_id, uid, .. } => {
+                    if known_uids.insert(uid.clone()) && device != &own_id {
+                        remote_messages.entry(session_id.clone()).or_default().push(record.clone());
+                    }
+                }
+                SyncRecord::Deleted { .. } | SyncRecord::Settings { .. } => {}
+            }
+        }
+    }
+
+    // Importação: sessões novas, títulos mais recentes e mensagens que faltam
+    let session_ids: HashSet<&String> = own_latest.keys().chain(remote_latest.keys()).collect();
+    for session_id in session_ids {
+        let result = (|| -> Result<(usize, bool), String> {
+            let local = db.get_session(session_id).map_err(|e| format!("Failed to load session: {}", e))?;
+            if local.is_none() {
+                if db.get_archived_session(session_id).map_err(|e| format!("Failed to load archived session: {}", e))?.is_some() {
+                    return Ok((0, false));
+                }
+                match own_latest.get(session_id) {
+                    // Removida aqui desde a última sincronização
+                    Some((_, false)) => {
+                        append_records(
+                            &own_dir.join("sessions").join(format!("{}.jsonl", session_id)),
+                            &[SyncRecord::Deleted { device: own_id.clone(), at: Utc::now(), session_id: session_id.clone() }],
+                        )?;
+                        return Ok((0, false));
+                    }
+                    Some((deleted_at, true)) if remote_latest.get(session_id).map_or(true, |at| at <= deleted_at) => {
+                        return Ok((0, false));
+                    }
+                    _ if !remote_latest.contains_key(session_id) => return Ok((0, false)),
+                    _ => {}
+                }
+            }
+            let header = headers.get(session_id);
+            let mut changed = false;
+            let mut created = false;
+            let remote_header = matches!(header, Some(SyncRecord::Session { device, .. }) if device != &own_id);
+
+            let mut session = match local {
+                Some(session) => session,
+                None => {
+                    let (title, emoji, created_at) = match header {
+                        Some(SyncRecord::Session { title, emoji, created_at, .. }) => (title.clone(), emoji.clone(), *created_at),
+                        _ => ("Sessão sincronizada".to_string(), "💬".to_string(), Utc::now()),
+                    };
+                    let session = db::ChatSession { id: session_id.clone(), title, emoji, created_at, updated_at: created_at };
+                    db.create_session(&session).map_err(|e| format!("Failed to create session: {}", e))?;
+                    created = true;
+                    changed = true;
+                    session
+                }
+            };
+            if let Some(SyncRecord::Session { title, emoji, at, device, .. }) = header {
+                if device != &own_id && *at > session.updated_at && (title != &session.title || emoji != &session.emoji) {
+                    db.set_session_title(session_id, title, emoji).map_err(|e| format!("Failed to update session title: {}", e))?;
+                    session.title = title.clone();
+                    session.emoji = emoji.clone();
+                    changed = true;
+                }
+            }
+
+            let local_messages = db.get_messages(session_id).map_err(|e| format!("Failed to load messages: {}", e))?;
+            let local_uids: HashSet<String> = assign_uids(session_id, local_messages.iter().map(|m| (m.role.as_str(), m.content.as_str())))
+                .into_iter()
+                .collect();
+            let mut incoming: Vec<&SyncRecord> = remote_messages
+                .get(session_id)
+                .map(|records| records.iter().filter(|r| matches!(r, SyncRecord::Message { uid, .. } if !local_uids.contains(uid))).collect())
+                .unwrap_or_default();
+            incoming.sort_by_key(|record| match record {
+                SyncRecord::Message { created_at, .. } => *created_at,
+                other => record_at(other),
+            });
+            for record in &incoming {
+                if let SyncRecord::Message { role, content, metadata, created_at, .. } = record {
+                    db.add_message(&db::ChatMessage {
+                        id: None,
+                        session_id: session_id.clone(),
+                        role: role.clone(),
+                        content: content.clone(),
+                        metadata: metadata.as_ref().map(|m| m.to_string()),
+                        created_at: *created_at,
+                    })
+                    .map_err(|e| format!("Failed to save message: {}", e))?;
+                }
+            }
+            changed |= !incoming.is_empty();
+
+            if changed {
+                let memory_context = match header {
+                    Some(SyncRecord::Session { memory_context, .. }) if remote_header => Some(memory_context.clone()),
+                    _ => None,
+                };
+                write_legacy_session(app_handle, &db, &session, memory_context)?;
+            }
+            Ok((incoming.len(), created))
+        })();
+        match result {
+            Ok((messages, created)) => {
+                report.imported_messages += messages;
+                report.imported_sessions += created as usize;
+            }
+            Err(e) => report.errors.push(format!("{}: {}", session_id, e)),
+        }
+    }
+
+    // Exportação: cabeçalhos alterados e mensagens que nenhum dispositivo gravou ainda
+    let sessions = db.list_sessions().map_err(|e| format!("Failed to list sessions: {}", e))?;
+    let now = Utc::now();
+    for session in sessions {
+        let mut records = Vec::new();
+        let memory_context = legacy_session(app_handle, &session.id).map(|s| s.memory_context).unwrap_or_default();
+        // Recriada depois de um `Deleted` deste dispositivo: grava o cabeçalho de novo para
+        // que uma nova remoção seja detectada
+        let recreated = own_latest.get(&session.id).is_some_and(|(_, deleted)| *deleted);
+        let header_changed = recreated
+            || match headers.get(&session.id) {
+                Some(SyncRecord::Session { title, emoji, memory_context: written, .. }) => {
+                    title != &session.title || emoji != &session.emoji || written != &memory_context
+                }
+                _ => true,
+            };
+        let messages = match db.get_messages(&session.id) {
+            Ok(messages) => messages,
+            Err(e) => {
+                report.errors.push(format!("{}: {}", session.id, e));
+                continue;
+            }
+        };
+        let uids = assign_uids(&session.id, messages.iter().map(|m| (m.role.as_str(), m.content.as_str())));
+        for (message, uid) in messages.into_iter().zip(uids) {
+            if known_uids.contains(&uid) {
+                continue;
+            }
+            records.push(SyncRecord::Message {
+                device: own_id.clone(),
+                at: now,
+                session_id: session.id.clone(),
+                uid,
+                role: message.role,
+                content: message.content,
+                metadata: message.metadata.and_then(|m| serde_json::from_str(&m).ok()),
+                created_at: message.created_at,
+            });
+        }
+        let message_count = records.len();
+        if header_changed {
+            records.insert(0, SyncRecord::Session {
+                device: own_id.clone(),
+                at: now,
+                session_id: session.id.clone(),
+                title: session.title.clone(),
+                emoji: session.emoji.clone(),
+                created_at: session.created_at,
+                memory_context,
+            });
+        }
+        if records.is_empty() {
+            continue;
+        }
+        match append_records(&own_dir.join("sessions").join(format!("{}.jsonl", session.id)), &records) {
+            Ok(()) => {
+                report.exported_messages += message_count;
+                report.exported_sessions += 1;
+            }
+            Err(e) => report.errors.push(format!("{}: {}", session.id, e)),
+        }
+    }
+
+    log::info!(
+        "[Sync] {} mensagens importadas ({} sessões novas), {} exportadas, {} dispositivos",
+        report.imported_messages,
+        report.imported_sessions,
+        report.exported_messages,
+        report.devices.len()
+    );
+    Ok(report)
+}
+
+/// Verificação periódica do scheduler (só com a sincronização ativa)
+pub fn maybe_run(app_handle: &AppHandle) {
+    if !crate::settings::current(app_handle).sync.enabled {
+        return;
+    }
+    match sync_now(app_handle) {
+        Ok(report) => {
+            for error in &report.errors {
+                log::warn!("[Sync] {}", error);
+            }
+        }
+        Err(e) => log::warn!("[Sync] Sincronização falhou: {}", e),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_uids_records_and_settings() {
+        let uids = assign_uids("s1", [("user", "oi"), ("assistant", "olá"), ("user", "oi")]);
+        assert_eq!(uids.len(), 3);
+        assert_ne!(uids[0], uids[2]);
+        assert_eq!(uids[0], message_uid("s1", "user", "oi", 0));
+        assert_ne!(message_uid("s1", "user", "oi", 0), message_uid("s2", "user", "oi", 0));
+
+        let record = SyncRecord::Message {
+            device: "a".to_string(),
+            at: Utc::now(),
+            session_id: "s1".to_string(),
+            uid: uids[0].clone(),
+            role: "user".to_string(),
+            content: "oi".to_string(),
+            metadata: None,
+            created_at: Utc::now(),
+        };
+        let text = format!("{}\n{{\"kind\":\"message\",\"trunc", serde_json::to_string(&record).unwrap());
+        assert_eq!(parse_records(&text), vec![record]);
+
+        let shared = shared_settings(serde_json::json!({
+            "theme": "dark",
+            "sync": { "enabled": true },
+            "ollama_url": "http://x",
+            "stt": { "binary_path": "/tmp/evil" },
+            "builtin_tools": { "allow_write": true },
+            "autostart": { "enabled": true },
+        }));
+        assert_eq!(shared, serde_json::json!({ "theme": "dark" }));
+
+        let deleted = SyncRecord::Deleted { device: "a".to_string(), at: Utc::now(), session_id: "s1".to_string() };
+        assert_eq!(parse_records(&serde_json::to_string(&deleted).unwrap()), vec![deleted]);
+    }
+}
@@ -0,0 +1,333 @@
+//! Leitura do cabeçalho de arquivos GGUF (formato de modelos do llama.cpp/Ollama)
+//!
+//! Lê apenas o cabeçalho, os metadados e a tabela de tensores; os pesos não são carregados.
+
+use serde::Serialize;
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{self, BufReader, Read};
+use std::path::Path;
+
+const MAGIC: &[u8; 4] = b"GGUF";
+const DEFAULT_ALIGNMENT: u64 = 32;
+
+// Limites contra arquivos corrompidos (evitam alocações gigantes)
+const MAX_KV_COUNT: u64 = 1_000_000;
+const MAX_TENSOR_COUNT: u64 = 1_000_000;
+const MAX_STRING_LEN: u64 = 64 * 1024 * 1024;
+const MAX_DIMS: u32 = 8;
+const MAX_ARRAY_DEPTH: u32 = 4;
+
+/// Metadados exibidos antes de importar um modelo
+#[derive(Serialize, Clone, Debug, PartialEq)]
+pub struct GgufMetadata {
+    pub version: u32,
+    pub architecture: Option<String>,
+    pub name: Option<String>,
+    /// `general.parameter_count` ou a soma dos elementos dos tensores
+    pub parameter_count: u64,
+    /// Tipo de quantização (`general.file_type`), ex.: "Q4_K_M"
+    pub quantization: Option<String>,
+    pub context_length: Option<u64>,
+    pub tensor_count: u64,
+    pub file_size: u64,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Value {
+    Int(i128),
+    Str(String),
+    /// Float, bool ou array (não usados nos metadados exibidos)
+    Other,
+}
+
+fn invalid(message: impl Into<String>) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message.into())
+}
+
+struct Reader<R> {
+    inner: R,
+    version: u32,
+    position: u64,
+}
+
+impl<R: Read> Reader<R> {
+    fn bytes<const N: usize>(&mut self) -> io::Result<[u8; N]> {
+        let mut buffer = [0u8; N];
+        self.inner.read_exact(&mut buffer)?;
+        self.position += N as u64;
+        Ok(buffer)
+    }
+
+    fn u32(&mut self) -> io::Result<u32> {
+        Ok(u32::from_le_bytes(self.bytes()?))
+    }
+
+    fn u64(&mut self) -> io::Result<u64> {
+        Ok(u64::from_le_bytes(self.bytes()?))
+    }
+
+    /// Contagens e tamanhos: u32 na versão 1, u64 a partir da versão 2
+    fn count(&mut self) -> io::Result<u64> {
+        if self.version == 1 {
+            self.u32().map(u64::from)
+        } else {
+            self.u64()
+        }
+    }
+
+    fn skip(&mut self, len: u64) -> io::Result<()> {
+        let skipped = io::copy(&mut self.inner.by_ref().take(len), &mut io::sink())?;
+        self.position += skipped;
+        if skipped < len {
+            return Err(io::ErrorKind::UnexpectedEof.into());
+        }
+        Ok(())
+    }
+
+    fn string(&mut self) -> io::Result<String> {
+        let len = self.count()?;
+        if len > MAX_STRING_LEN {
+            return Err(invalid(format!("string de {} bytes", len)));
+        }
+        let mut buffer = vec![0u8; len as usize];
+        self.inner.read_exact(&mut buffer)?;
+        self.position += len;
+        Ok(String::from_utf8_lossy(&buffer).into_owned())
+    }
+
+    fn value(&mut self, value_type: u32, depth: u32) -> io::Result<Value> {
+        Ok(match value_type {
+            0 => Value::Int(self.bytes::<1>()?[0] as i128),
+            1 => Value::Int(self.bytes::<1>()?[0] as i8 as i128),
+            2 => Value::Int(u16::from_le_bytes(self.bytes()?) as i128),
+            3 => Value::Int(i16::from_le_bytes(self.bytes()?) as i128),
+            4 => Value::Int(self.u32()? as i128),
+            5 => Value::Int(i32::from_le_bytes(self.bytes()?) as i128),
+            10 => Value::Int(self.u64()? as i128),
+            11 => Value::Int(i64::from_le_bytes(self.bytes()?) as i128),
+            6 => { self.skip(4)?; Value::Other }
+            7 => { self.skip(1)?; Value::Other }
+            12 => { self.skip(8)?; Value::Other }
+            8 => Value::Str(self.string()?),
+            9 => {
+                if depth >= MAX_ARRAY_DEPTH {
+                    return Err(invalid("arrays aninhados demais"));
+                }
+                let item_type = self.u32()?;
+                let len = self.count()?;
+                // Arrays grandes (vocabulário do tokenizer) são pulados sem alocar
+                match fixed_size(item_type) {
+                    Some(size) => self.skip(len.checked_mul(size).ok_or_else(|| invalid("array grande demais"))?)?,
+                    None => {
+                        for _ in 0..len {
+                            self.value(item_type, depth + 1)?;
+                        }
+                    }
+                }
+                Value::Other
+            }
+            other => return Err(invalid(format!("tipo de valor desconhecido: {}", other))),
+        })
+    }
+}
+
+fn fixed_size(value_type: u32) -> Option<u64> {
+    match value_type {
+        0 | 1 | 7 => Some(1),
+        2 | 3 => Some(2),
+        4 | 5 | 6 => Some(4),
+        10 | 11 | 12 => Some(8),
+        _ => None,
+    }
+}
+
+/// Nome do tipo de quantização (`llama_ftype` do llama.cpp)
+fn quantization_name(file_type: i128) -> Option<&'static str> {
+    Some(match file_type {
+        0 => "F32",
+        1 => "F16",
+        2 => "Q4_0",
+        3 => "Q4_1",
+        7 => "Q8_0",
+        8 => "Q5_0",
+        9 => "Q5_1",
+        10 => "Q2_K",
+        11 => "Q3_K_S",
+        12 => "Q3_K_M",
+        13 => "Q3_K_L",
+        14 => "Q4_K_S",
+        15 => "Q4_K_M",
+        16 => "Q5_K_S",
+        17 => "Q5_K_M",
+        18 => "Q6_K",
+        19 => "IQ2_XXS",
+        20 => "IQ2_XS",
+        21 => "Q2_K_S",
+        22 => "IQ3_XS",
+        23 => "IQ3_XXS",
+        24 => "IQ1_S",
+        25 => "IQ4_NL",
+        26 => "IQ3_S",
+        27 => "IQ3_M",
+        28 => "IQ2_S",
+        29 => "IQ2_M",
+        30 => "IQ4_XS",
+        31 => "IQ1_M",
+        32 => "BF16",
+        36 => "TQ1_0",
+        37 => "TQ2_0",
+        _ => return None,
+    })
+}
+
+/// Lê o cabeçalho de um GGUF; `file_size` é usado para detectar arquivos truncados
+pub fn parse<R: Read>(input: R, file_size: u64) -> io::Result<GgufMetadata> {
+    let mut reader = Reader { inner: input, version: 0, position: 0 };
+
+    if &reader.bytes::<4>()? != MAGIC {
+        return Err(invalid("assinatura GGUF ausente"));
+    }
+    reader.version = reader.u32()?;
+    if !(1..=3).contains(&reader.version) {
+        return Err(invalid(format!("versão GGUF não suportada: {}", reader.version)));
+    }
+
+    let tensor_count = reader.count()?;
+    let kv_count = reader.count()?;
+    if tensor_count == 0 || tensor_count > MAX_TENSOR_COUNT {
+        return Err(invalid(format!("número de tensores inválido: {}", tensor_count)));
+    }
+    if kv_count > MAX_KV_COUNT {
+        return Err(invalid(format!("número de metadados inválido: {}", kv_count)));
+    }
+
+    let mut metadata: HashMap<String, Value> = HashMap::new();
+    for _ in 0..kv_count {
+        let key = reader.string()?;
+        let value_type = reader.u32()?;
+        let value = reader.value(value_type, 0)?;
+        if value != Value::Other {
+            metadata.insert(key, value);
+        }
+    }
+
+    let mut element_count: u64 = 0;
+    let mut max_offset: u64 = 0;
+    for _ in 0..tensor_count {
+        reader.string()?;
+        let dims = reader.u32()?;
+        if dims == 0 || dims > MAX_DIMS {
+            return Err(invalid(format!("tensor com {} dimensões", dims)));
+        }
+        let mut elements: u64 = 1;
+        for _ in 0..dims {
+            elements = elements.saturating_mul(reader.count()?);
+        }
+        element_count = element_count.saturating_add(elements);
+        reader.u32()?; // tipo do tensor
+        max_offset = max_offset.max(reader.u64()?);
+    }
+
+    let int = |key: &str| match metadata.get(key) {
+        Some(Value::Int(v)) => Some(*v),
+        _ => None,
+    };
+    let string = |key: &str| match metadata.get(key) {
+        Some(Value::Str(s)) if !s.trim().is_empty() => Some(s.trim().to_string()),
+        _ => None,
+    };
+
+    // Os dados dos tensores começam alinhados após a tabela
+    let alignment = int("general.alignment")
+        .filter(|a| *a > 0)
+        .map(|a| a as u64)
+        .unwrap_or(DEFAULT_ALIGNMENT);
+    let data_start = reader.position.div_ceil(alignment) * alignment;
+    if data_start.saturating_add(max_offset) >= file_size {
+        return Err(invalid("arquivo truncado (dados dos tensores incompletos)"));
+    }
+
+    let architecture = string("general.architecture");
+    let context_length = architecture
+        .as_ref()
+        .and_then(|arch| int(&format!("{}.context_length", arch)))
+        .and_then(|v| u64::try_from(v).ok());
+
+    Ok(GgufMetadata {
+        version: reader.version,
+        name: string("general.name"),
+        parameter_count: int("general.parameter_count")
+            .and_then(|v| u64::try_from(v).ok())
+            .unwrap_or(element_count),
+        quantization: int("general.file_type").map(|t| {
+            quantization_name(t).map(str::to_string).unwrap_or_else(|| format!("tipo {}", t))
+        }),
+        architecture,
+        context_length,
+        tensor_count,
+        file_size,
+    })
+}
+
+/// Lê e valida os metadados de um arquivo GGUF
+pub fn read_metadata(path: &Path) -> Result<GgufMetadata, String> {
+    let file = File::open(path)
+        .map_err(|e| format!("Failed to open GGUF file: {}", e))?;
+    let file_size = file.metadata()
+        .map_err(|e| format!("Erro ao ler metadados do arquivo: {}", e))?
+        .len();
+    parse(BufReader::new(file), file_size).map_err(|e| match e.kind() {
+        io::ErrorKind::UnexpectedEof => "Arquivo GGUF inválido: cabeçalho incompleto".to_string(),
+        _ => format!("Arquivo GGUF inválido: {}", e),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn string(out: &mut Vec<u8>, s: &str) {
+        out.extend((s.len() as u64).to_le_bytes());
+        out.extend(s.as_bytes());
+    }
+
+    #[test]
+    fn test_parse_header_and_metadata() {
+        let mut file = b"GGUF".to_vec();
+        file.extend(3u32.to_le_bytes());
+        file.extend(1u64.to_le_bytes()); // tensores
+        file.extend(4u64.to_le_bytes()); // metadados
+        string(&mut file, "general.architecture");
+        file.extend(8u32.to_le_bytes());
+        string(&mut file, "llama");
+        string(&mut file, "general.file_type");
+        file.extend(4u32.to_le_bytes());
+        file.extend(15u32.to_le_bytes());
+        string(&mut file, "llama.context_length");
+        file.extend(4u32.to_le_bytes());
+        file.extend(8192u32.to_le_bytes());
+        string(&mut file, "tokenizer.ggml.tokens");
+        file.extend(9u32.to_le_bytes());
+        file.extend(8u32.to_le_bytes());
+        file.extend(2u64.to_le_bytes());
+        string(&mut file, "<s>");
+        string(&mut file, "</s>");
+        string(&mut file, "token_embd.weight");
+        file.extend(2u32.to_le_bytes());
+        file.extend(4096u64.to_le_bytes());
+        file.extend(32000u64.to_le_bytes());
+        file.extend(12u32.to_le_bytes());
+        file.extend(0u64.to_le_bytes());
+        let size = file.len() as u64 + 1024;
+
+        let metadata = parse(file.as_slice(), size).unwrap();
+        assert_eq!(metadata.architecture.as_deref(), Some("llama"));
+        assert_eq!(metadata.quantization.as_deref(), Some("Q4_K_M"));
+        assert_eq!(metadata.context_length, Some(8192));
+        assert_eq!(metadata.parameter_count, 4096 * 32000);
+
+        assert!(parse(file.as_slice(), file.len() as u64).is_err()); // truncado
+        assert!(parse(&b"GGML\x03\x00\x00\x00"[..], 1024).is_err());
+    }
+}
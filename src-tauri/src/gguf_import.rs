@@ -0,0 +1,224 @@
+//! Importação de arquivos GGUF para o Ollama pela API de blobs
+//!
+//! O arquivo é lido em blocos (sha256 e envio), com progresso em `gguf-import-progress` e
+//! cancelamento via `cancel_job`. O digest fica em cache por caminho/tamanho/data de
+//! modificação e o envio é pulado quando o servidor já tem o blob, então repetir uma
+//! importação interrompida só refaz a etapa que falhou.
+
+use crate::jobs::JobHandle;
+use crate::ollama_client::OllamaClient;
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::fs;
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, UNIX_EPOCH};
+use tauri::{AppHandle, Manager};
+use tokio::io::AsyncReadExt;
+
+const CHUNK_SIZE: usize = 8 * 1024 * 1024;
+/// Intervalo mínimo de bytes entre eventos de progresso
+const PROGRESS_STEP_BYTES: u64 = 64 * 1024 * 1024;
+const DIGEST_CACHE_FILE: &str = "gguf_digests.json";
+const UPLOAD_TIMEOUT: Duration = Duration::from_secs(4 * 60 * 60);
+const CANCELLED: &str = "Importação cancelada";
+
+/// Payload do evento `gguf-import-progress`
+#[derive(Serialize, Clone, Debug)]
+pub struct ImportProgress {
+    pub import_id: String,
+    /// "hashing" | "uploading" | "creating" | "done"
+    pub stage: &'static str,
+    pub bytes_done: u64,
+    pub bytes_total: u64,
+}
+
+/// Emite o progresso de uma etapa respeitando `PROGRESS_STEP_BYTES`
+struct Tracker {
+    app_handle: AppHandle,
+    import_id: String,
+    bytes_total: u64,
+    last_emitted: Option<u64>,
+}
+
+impl Tracker {
+    fn update(&mut self, stage: &'static str, bytes_done: u64) {
+        let due = match self.last_emitted {
+            Some(last) => bytes_done >= self.bytes_total || bytes_done - last >= PROGRESS_STEP_BYTES,
+            None => true,
+        };
+        if !due {
+            return;
+        }
+        self.last_emitted = Some(bytes_done);
+        let _ = crate::events::emit(&self.app_handle, &ImportProgress {
+            import_id: self.import_id.clone(),
+            stage,
+            bytes_done,
+            bytes_total: self.bytes_total,
+        });
+    }
+
+    fn stage(&mut self, stage: &'static str, bytes_done: u64) {
+        self.last_emitted = None;
+        self.update(stage, bytes_done);
+    }
+}
+
+fn cache_path(app_handle: &AppHandle) -> Result<PathBuf, String> {
+    app_handle.path().app_data_dir()
+        .map(|dir| dir.join(DIGEST_CACHE_FILE))
+        .map_err(|e| format!("Failed to get app data dir: {}", e))
+}
+
+/// Chave do cache: o digest só é reaproveitado se o arquivo não mudou
+fn cache_key(path: &Path, metadata: &fs::Metadata) -> String {
+    let modified = metadata.modified()
+        .ok()
+        .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    format!("{}|{}|{}", path.display(), metadata.len(), modified)
+}
+
+fn load_cache(app_handle: &AppHandle) -> HashMap<String, String> {
+    cache_path(app_handle)
+        .ok()
+        .and_then(|path| fs::read_to_string(path).ok())
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+fn store_digest(app_handle: &AppHandle, key: String, digest: &str) -> Result<(), String> {
+    let mut cache = load_cache(app_handle);
+    cache.insert(key, digest.to_string());
+    let content = serde_json::to_string_pretty(&cache)
+        .map_err(|e| format!("Failed to serialize digest cache: {}", e))?;
+    fs::write(cache_path(app_handle)?, content)
+        .map_err(|e| format!("Failed to write digest cache: {}", e))
+}
+
+/// sha256 do arquivo no formato de digest do Ollama ("sha256:<hex>")
+fn hash_file(path: &Path, job: &JobHandle, tracker: &mut Tracker) -> Result<String, String> {
+    let mut file = fs::File::open(path)
+        .map_err(|e| format!("Failed to open GGUF file: {}", e))?;
+    let mut hasher = Sha256::new();
+    let mut buffer = vec![0u8; CHUNK_SIZE];
+    let mut done: u64 = 0;
+    tracker.stage("hashing", 0);
+    loop {
+        if job.is_cancelled() {
+            return Err(CANCELLED.to_string());
+        }
+        let read = file.read(&mut buffer)
+            .map_err(|e| format!("Failed to read GGUF file: {}", e))?;
+        if read == 0 {
+            break;
+        }
+        hasher.update(&buffer[..read]);
+        done += read as u64;
+        tracker.update("hashing", done);
+    }
+    let hex: String = hasher.finalize().iter().map(|b| format!("{:02x}", b)).collect();
+    Ok(format!("sha256:{}", hex))
+}
+
+/// Corpo do envio lido em blocos; cancelar interrompe o stream e aborta a requisição
+async fn upload_body(path: &Path, job: JobHandle, mut tracker: Tracker) -> Result<reqwest::Body, String> {
+    let file = tokio::fs::File::open(path)
+        .await
+        .map_err(|e| format!("Failed to open GGUF file: {}", e))?;
+    tracker.stage("uploading", 0);
+    let stream = futures_util::stream::try_unfold((file, 0u64, job, tracker), |(mut file, done, job, mut tracker)| async move {
+        if job.is_cancelled() {
+            return Err(io::Error::new(io::ErrorKind::Interrupted, CANCELLED));
+        }
+        let mut buffer = vec![0u8; CHUNK_SIZE];
+        let read = file.read(&mut buffer).await?;
+        if read == 0 {
+            return Ok(None);
+        }
+        buffer.truncate(read);
+        let done = done + read as u64;
+        tracker.update("uploading", done);
+        Ok(Some((buffer, (file, done, job, tracker))))
+    });
+    Ok(reqwest::Body::wrap_stream(stream))
+}
+
+/// Nome do arquivo informado ao /api/create (o Ollama identifica o formato pela extensão)
+fn blob_file_name(path: &Path) -> String {
+    path.file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .filter(|name| name.to_lowercase().ends_with(".gguf"))
+        .unwrap_or_else(|| "model.gguf".to_string())
+}
+
+/// Importa o arquivo como `model_name` e retorna o digest do blob
+pub async fn import(
+    app_handle: &AppHandle,
+    job: &JobHandle,
+    client: &OllamaClient,
+    path: &Path,
+    model_name: &str,
+) -> Result<String, String> {
+    let metadata = fs::metadata(path)
+        .map_err(|e| format!("Erro ao ler metadados do arquivo: {}", e))?;
+    let bytes_total = metadata.len();
+    let tracker = || Tracker {
+        app_handle: app_handle.clone(),
+        import_id: job.id().to_string(),
+        bytes_total,
+        last_emitted: None,
+    };
+
+    let key = cache_key(path, &metadata);
+    let digest = match load_cache(app_handle).remove(&key) {
+        Some(digest) => {
+            log::info!("[GgufImport] Digest em cache para {}: {}", path.display(), digest);
+            digest
+        }
+        None => {
+            let (owned_path, owned_job, mut hash_tracker) = (path.to_path_buf(), job.clone(), tracker());
+            let digest = tokio::task::spawn_blocking(move || hash_file(&owned_path, &owned_job, &mut hash_tracker))
+                .await
+                .map_err(|e| format!("Hash task failed: {}", e))??;
+            if let Err(e) = store_digest(app_handle, key, &digest) {
+                log::warn!("[GgufImport] {}", e);
+            }
+            digest
+        }
+    };
+    job.advance(1);
+
+    if client.blob_exists(&digest).await? {
+        log::info!("[GgufImport] Blob {} já existe no Ollama, envio ignorado", digest);
+        tracker().stage("uploading", bytes_total);
+    } else {
+        let body = upload_body(path, job.clone(), tracker()).await?;
+        client.push_blob(&digest, body, UPLOAD_TIMEOUT).await.map_err(|e| {
+            if job.is_cancelled() { CANCELLED.to_string() } else { e }
+        })?;
+    }
+    job.advance(1);
+
+    tracker().stage("creating", bytes_total);
+    let files = HashMap::from([(blob_file_name(path), digest.clone())]);
+    client.create_from_files(model_name, &files).await?;
+    job.advance(1);
+
+    tracker().stage("done", bytes_total);
+    Ok(digest)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_blob_file_name() {
+        assert_eq!(blob_file_name(Path::new("/m/Llama-3.1-8B.Q4_K_M.GGUF")), "Llama-3.1-8B.Q4_K_M.GGUF");
+        assert_eq!(blob_file_name(Path::new("/m/modelo-sem-extensao")), "model.gguf");
+    }
+}
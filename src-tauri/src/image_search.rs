@@ -0,0 +1,284 @@
+//! Busca de imagens (Bing e DuckDuckGo Images) e download para entrada de visão
+//!
+//! As imagens baixadas ficam em `<app_data>/images`, nomeadas pelo hash da URL, e voltam
+//! no mesmo formato de um arquivo solto no chat (base64 + mime) para anexar a modelos como o llava.
+
+use regex::Regex;
+use reqwest::header::{REFERER, USER_AGENT};
+use scraper::{Html, Selector};
+use serde::Serialize;
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use std::time::Duration;
+use tauri::{AppHandle, Manager};
+
+const SEARCH_TIMEOUT: Duration = Duration::from_secs(10);
+const DOWNLOAD_TIMEOUT: Duration = Duration::from_secs(30);
+/// Mesmo limite das imagens soltas no chat
+const MAX_IMAGE_BYTES: usize = 20 * 1024 * 1024;
+/// Extensões dos arquivos salvos (pelo mime detectado; jpeg vira jpg)
+const IMAGE_EXTENSIONS: &[&str] = &["png", "jpg", "gif", "webp", "bmp"];
+const BROWSER_USER_AGENT: &str =
+    "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/124.0 Safari/537.36";
+
+/// Imagem encontrada
+#[derive(Serialize, Clone, Debug, PartialEq)]
+pub struct ImageResult {
+    pub title: String,
+    pub image_url: String,
+    pub thumbnail_url: Option<String>,
+    /// Página onde a imagem aparece
+    pub source_url: Option<String>,
+    pub width: Option<u32>,
+    pub height: Option<u32>,
+    pub engine: &'static str,
+}
+
+/// Imagem salva em disco, pronta para o campo `images` do Ollama
+#[derive(Serialize, Clone, Debug)]
+pub struct DownloadedImage {
+    pub path: String,
+    pub url: String,
+    pub mime: String,
+    pub size_bytes: u64,
+    pub base64: String,
+}
+
+fn http_client(timeout: Duration) -> Result<reqwest::Client, String> {
+    crate::web_scraper::client_builder(None)
+        .timeout(timeout)
+        .build()
+        .map_err(|e| format!("Failed to create HTTP client: {}", e))
+}
+
+/// Resultados da página `images/async` do Bing (metadados no atributo `m` de `a.iusc`)
+fn parse_bing_images(html: &str, limit: usize) -> Vec<ImageResult> {
+    let document = Html::parse_document(html);
+    let Ok(selector) = Selector::parse("a.iusc") else {
+        return Vec::new();
+    };
+    document
+        .select(&selector)
+        .filter_map(|node| serde_json::from_str::<Value>(node.value().attr("m")?).ok())
+        .filter_map(|meta| {
+            let image_url = meta["murl"].as_str()?.to_string();
+            Some(ImageResult {
+                title: meta["t"].as_str().unwrap_or_default().trim().to_string(),
+                image_url,
+                thumbnail_url: meta["turl"].as_str().map(str::to_string),
+                source_url: meta["purl"].as_str().map(str::to_string),
+                width: None,
+                height: None,
+                engine: "bing",
+            })
+        })
+        .take(limit)
+        .collect()
+}
+
+/// Token `vqd` exigido pelo endpoint de imagens do DuckDuckGo
+fn extract_vqd(html: &str) -> Option<String> {
+    let pattern = Regex::new(r#"vqd=["']?([\d-]+)"#).expect("valid vqd regex");
+    pattern.captures(html).map(|caps| caps[1].to_string())
+}
+
+/// Resultados do `i.js` do DuckDuckGo
+fn parse_duckduckgo_images(body: &Value, limit: usize) -> Vec<ImageResult> {
+    body["results"]
+        .as_array()
+        .map(|results| {
+            results
+                .iter()
+                .filter_map(|item| {
+                    Some(ImageResult {
+                        title: item["title"].as_str().unwrap_or_default().trim().to_string(),
+                        image_url: item["image"].as_str()?.to_string(),
+                        thumbnail_url: item["thumbnail"].as_str().map(str::to_string),
+                        source_url: item["url"].as_str().map(str::to_string),
+                        width: item["width"].as_u64().map(|w| w as u32),
+                        height: item["height"].as_u64().map(|h| h as u32),
+                        engine: "duckduckgo",
+                    })
+                })
+                .take(limit)
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+async fn search_bing_images(query: &str, limit: usize) -> Result<Vec<ImageResult>, String> {
+    let url = format!(
+        "https://www.bing.com/images/async?q={}&first=0&count={}&mmasync=1",
+        urlencoding::encode(query),
+        limit.max(10)
+    );
+    let html = http_client(SEARCH_TIMEOUT)?
+        .get(&url)
+        .header(USER_AGENT, BROWSER_USER_AGENT)
+        .send()
+        .await
+        .map_err(|e| format!("Failed to search Bing images: {}", e))?
+        .text()
+        .await
+        .map_err(|e| format!("Failed to read Bing images response: {}", e))?;
+    Ok(parse_bing_images(&html, limit))
+}
+
+async fn search_duckduckgo_images(query: &str, limit: usize) -> Result<Vec<ImageResult>, String> {
+    let client = http_client(SEARCH_TIMEOUT)?;
+    let encoded = urlencoding::encode(query);
+    let page = client
+        .get(format!("https://duckduckgo.com/?q={}&iax=images&ia=images", encoded))
+        .header(USER_AGENT, BROWSER_USER_AGENT)
+        .send()
+        .await
+        .map_err(|e| format!("Failed to open DuckDuckGo images: {}", e))?
+        .text()
+        .await
+        .map_err(|e| format!("Failed to read DuckDuckGo images page: {}", e))?;
+    let vqd = extract_vqd(&page).ok_or_else(|| "Token vqd do DuckDuckGo não encontrado".to_string())?;
+
+    let body: Value = client
+        .get(format!("https://duckduckgo.com/i.js?l=wt-wt&o=json&q={}&vqd={}&f=,,,&p=1", encoded, vqd))
+        .header(USER_AGENT, BROWSER_USER_AGENT)
+        .header(REFERER, "https://duckduckgo.com/")
+        .send()
+        .await
+        .map_err(|e| format!("Failed to search DuckDuckGo images: {}", e))?
+        .json()
+        .await
+        .map_err(|e| format!("Failed to parse DuckDuckGo images response: {}", e))?;
+    Ok(parse_duckduckgo_images(&body, limit))
+}
+
+/// Imagens para a consulta: Bing primeiro, completando com o DuckDuckGo (motores
+/// desativados em `search_apis.engines` ficam de fora)
+pub async fn search_images(query: &str, limit: usize) -> Result<Vec<ImageResult>, String> {
+    let apis = crate::web_scraper::current_search_apis();
+    let mut results: Vec<ImageResult> = Vec::new();
+    let mut errors = Vec::new();
+
+    if apis.is_engine_enabled("bing") {
+        match search_bing_images(query, limit).await {
+            Ok(found) => results.extend(found),
+            Err(e) => errors.push(e),
+        }
+    }
+    if results.len() < limit && apis.is_engine_enabled("duckduckgo") {
+        match search_duckduckgo_images(query, limit).await {
+            Ok(found) => {
+                for image in found {
+                    if !results.iter().any(|r| r.image_url == image.image_url) {
+                        results.push(image);
+                    }
+                }
+            }
+            Err(e) => errors.push(e),
+        }
+    }
+
+    if results.is_empty() && !errors.is_empty() {
+        return Err(errors.join("; "));
+    }
+    results.truncate(limit);
+    log::info!("[ImageSearch] {} imagens para '{}'", results.len(), query);
+    Ok(results)
+}
+
+/// Baixa a imagem para `<app_data>/images` (reaproveitando um download anterior da mesma URL)
+pub async fn download_image(app_handle: &AppHandle, url: &str) -> Result<DownloadedImage, String> {
+    use base64::Engine;
+
+    let parsed = url::Url::parse(url).map_err(|e| format!("URL inválida: {}", e))?;
+    if parsed.scheme() != "http" && parsed.scheme() != "https" {
+        return Err(format!("URL inválida: {}", url));
+    }
+
+    let images_dir = app_handle
+        .path()
+        .app_data_dir()
+        .map_err(|e| format!("Failed to get app data dir: {}", e))?
+        .join("images");
+    std::fs::create_dir_all(&images_dir).map_err(|e| format!("Failed to create images dir: {}", e))?;
+    let stem: String = Sha256::digest(url.as_bytes()).iter().take(12).map(|b| format!("{:02x}", b)).collect();
+
+    for extension in IMAGE_EXTENSIONS {
+        let cached = images_dir.join(format!("{}.{}", stem, extension));
+        if let Ok(bytes) = std::fs::read(&cached) {
+            if let Some(mime) = crate::dropped_files::image_mime(&bytes) {
+                return Ok(DownloadedImage {
+                    path: cached.to_string_lossy().to_string(),
+                    url: url.to_string(),
+                    mime: mime.to_string(),
+                    size_bytes: bytes.len() as u64,
+                    base64: base64::engine::general_purpose::STANDARD.encode(&bytes),
+                });
+            }
+        }
+    }
+
+    let response = http_client(DOWNLOAD_TIMEOUT)?
+        .get(url)
+        .header(USER_AGENT, BROWSER_USER_AGENT)
+        .send()
+        .await
+        .map_err(|e| format!("Failed to download image: {}", e))?;
+    if !response.status().is_success() {
+        return Err(format!("Failed to download image: HTTP {}", response.status()));
+    }
+    if response.content_length().is_some_and(|len| len as usize > MAX_IMAGE_BYTES) {
+        return Err(format!("Imagem maior que {} MB", MAX_IMAGE_BYTES / (1024 * 1024)));
+    }
+    let bytes = response
+        .bytes()
+        .await
+        .map_err(|e| format!("Failed to read image: {}", e))?;
+    if bytes.len() > MAX_IMAGE_BYTES {
+        return Err(format!("Imagem maior que {} MB", MAX_IMAGE_BYTES / (1024 * 1024)));
+    }
+    // Pela assinatura: servidores costumam mandar HTML de erro com status 200
+    let mime = crate::dropped_files::image_mime(&bytes)
+        .ok_or_else(|| "O conteúdo baixado não é uma imagem suportada".to_string())?;
+    let extension = IMAGE_EXTENSIONS
+        .iter()
+        .find(|ext| mime.ends_with(*ext))
+        .copied()
+        .unwrap_or("jpg");
+
+    let path = images_dir.join(format!("{}.{}", stem, extension));
+    std::fs::write(&path, &bytes).map_err(|e| format!("Failed to save image: {}", e))?;
+
+    Ok(DownloadedImage {
+        path: path.to_string_lossy().to_string(),
+        url: url.to_string(),
+        mime: mime.to_string(),
+        size_bytes: bytes.len() as u64,
+        base64: base64::engine::general_purpose::STANDARD.encode(&bytes),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_image_results() {
+        let bing = r#"<div><a class="iusc" m='{"murl":"https://img.example/cat.jpg","turl":"https://tse.example/th?id=1","purl":"https://example.org/cats","t":" Gato "}'></a>
+            <a class="iusc" m='not json'></a></div>"#;
+        let images = parse_bing_images(bing, 5);
+        assert_eq!(images.len(), 1);
+        assert_eq!(images[0].image_url, "https://img.example/cat.jpg");
+        assert_eq!(images[0].source_url.as_deref(), Some("https://example.org/cats"));
+        assert_eq!(images[0].title, "Gato");
+
+        assert_eq!(extract_vqd("DDG.deep.vqd='4-123456789';").as_deref(), Some("4-123456789"));
+
+        let ddg = serde_json::json!({ "results": [
+            { "title": "Dog", "image": "https://img.example/dog.png", "thumbnail": "https://t.example/d", "url": "https://example.org/dogs", "width": 800, "height": 600 },
+            { "title": "sem imagem" }
+        ]});
+        let images = parse_duckduckgo_images(&ddg, 5);
+        assert_eq!(images.len(), 1);
+        assert_eq!((images[0].width, images[0].height), (Some(800), Some(600)));
+    }
+}
@@ -13,9 +13,26 @@ pub enum QueryIntent {
 
 pub struct IntentClassifier;
 
+/// Padrões extras de um idioma, somados aos padrões base (português/inglês)
+#[derive(Default)]
+struct LanguagePatterns {
+    factual: &'static [&'static str],
+    conversational: &'static [&'static str],
+    technical: &'static [&'static str],
+    opinion: &'static [&'static str],
+    calculation: &'static [&'static str],
+}
+
 impl IntentClassifier {
-    /// Classifica a intenção de uma query usando heurísticas baseadas em palavras-chave
+    /// Classifica a intenção de uma query usando heurísticas baseadas em palavras-chave.
+    /// O idioma é detectado automaticamente para incluir os padrões específicos dele.
     pub fn classify(query: &str) -> QueryIntent {
+        let language = crate::translation::detect_language(query);
+        Self::classify_with_language(query, language.as_deref())
+    }
+
+    /// Classifica usando os padrões base (pt/en) mais os do idioma informado
+    pub fn classify_with_language(query: &str, language: Option<&str>) -> QueryIntent {
         if query.is_empty() {
             return QueryIntent::Unknown;
         }
@@ -83,6 +100,14 @@ impl IntentClassifier {
             r"\b(porcentagem|percentage|percent|por cento)\b",
         ];
 
+        // Padrões adicionais do idioma detectado
+        let extra = Self::language_patterns(language.unwrap_or(""));
+        let factual_patterns: Vec<&str> = factual_patterns.into_iter().chain(extra.factual.iter().copied()).collect();
+        let conversational_patterns: Vec<&str> = conversational_patterns.into_iter().chain(extra.conversational.iter().copied()).collect();
+        let technical_patterns: Vec<&str> = technical_patterns.into_iter().chain(extra.technical.iter().copied()).collect();
+        let opinion_patterns: Vec<&str> = opinion_patterns.into_iter().chain(extra.opinion.iter().copied()).collect();
+        let calculation_patterns: Vec<&str> = calculation_patterns.into_iter().chain(extra.calculation.iter().copied()).collect();
+
         // Calcular scores
         Self::score_patterns(&query_normalized, &factual_patterns, &mut scores, QueryIntent::Factual);
         Self::score_patterns(&query_normalized, &conversational_patterns, &mut scores, QueryIntent::Conversational);
@@ -109,6 +134,73 @@ impl IntentClassifier {
         }
     }
 
+    /// Padrões específicos por idioma (já sem acentos, como a query normalizada)
+    fn language_patterns(language: &str) -> LanguagePatterns {
+        match language {
+            "es" => LanguagePatterns {
+                factual: &[
+                    r"\b(que es|cual|cuales|quien|quienes|donde|cuando)\b",
+                    r"\b(precio|costo|noticias?|hoy|ahora|actual|ultimo|ultima|historia)\b",
+                ],
+                conversational: &[
+                    r"^(hola|buenas)\s*[!.]*$",
+                    r"\b(como estas|que tal|gracias|adios|hasta luego|buenos dias|buenas tardes|buenas noches)\b",
+                ],
+                technical: &[
+                    r"\b(como hacer|como se hace|tutorial|documentacion|ejemplos?|codigo|implementacion)\b",
+                    r"\b(error|fallo|problema|guia|instalacion|libreria)\b",
+                ],
+                opinion: &[
+                    r"\b(crees|piensas|opinion|prefieres|mejor|peor|recomiendas|de acuerdo)\b",
+                ],
+                calculation: &[
+                    r"\b(calcular|calcula|cuanto es|cuantos|cuantas|suma|resta|porcentaje|por ciento)\b",
+                ],
+            },
+            "fr" => LanguagePatterns {
+                factual: &[
+                    r"\b(qu'est-ce que|quel|quelle|quels|quelles|qui|ou|quand)\b",
+                    r"\b(prix|cout|actualites?|aujourd'hui|maintenant|dernier|derniere|histoire)\b",
+                ],
+                conversational: &[
+                    r"^(bonjour|salut|coucou|bonsoir)\s*[!.]*$",
+                    r"\b(comment ca va|comment vas-tu|merci|au revoir|a bientot)\b",
+                ],
+                technical: &[
+                    r"\b(comment faire|tutoriel|documentation|exemples?|code|implementation)\b",
+                    r"\b(erreur|bogue|probleme|guide|installation|bibliotheque)\b",
+                ],
+                opinion: &[
+                    r"\b(penses-tu|pensez-vous|avis|opinion|preferes|meilleur|pire|recommandes)\b",
+                ],
+                calculation: &[
+                    r"\b(calculer|calcule|combien|somme|pourcentage|pour cent)\b",
+                ],
+            },
+            "de" => LanguagePatterns {
+                factual: &[
+                    r"\b(was ist|welche|welcher|wer|wo|wann)\b",
+                    r"\b(preis|kosten|nachrichten|heute|jetzt|aktuell|neueste|geschichte)\b",
+                ],
+                conversational: &[
+                    r"^(hallo|servus|moin)\s*[!.]*$",
+                    r"\b(wie geht's|wie geht es dir|danke|tschuss|auf wiedersehen|guten morgen|guten abend)\b",
+                ],
+                technical: &[
+                    r"\b(wie macht man|anleitung|dokumentation|beispiele?|implementierung)\b",
+                    r"\b(fehler|problem|installation|bibliothek)\b",
+                ],
+                opinion: &[
+                    r"\b(meinst du|denkst du|meinung|bevorzugst|beste|schlechteste|empfiehlst)\b",
+                ],
+                calculation: &[
+                    r"\b(berechne|berechnen|wie viel|wieviel|summe|prozent)\b",
+                ],
+            },
+            _ => LanguagePatterns::default(),
+        }
+    }
+
     /// Normaliza a query removendo acentos e caracteres especiais (simplificado)
     fn normalize_query(query: &str) -> String {
         query
@@ -204,5 +296,17 @@ mod tests {
             QueryIntent::Calculation
         );
     }
+
+    #[test]
+    fn test_language_specific_patterns() {
+        assert_eq!(
+            IntentClassifier::classify_with_language("¿Cuánto es el porcentaje de descuento?", Some("es")),
+            QueryIntent::Calculation
+        );
+        assert_eq!(
+            IntentClassifier::classify_with_language("Wie macht man eine Installation unter Linux?", Some("de")),
+            QueryIntent::Technical
+        );
+    }
 }
 
@@ -0,0 +1,199 @@
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, OnceLock};
+use tauri::AppHandle;
+
+/// Quantos jobs finalizados continuam visíveis em `list_jobs`
+const MAX_FINISHED_JOBS: usize = 20;
+
+/// Jobs de background conhecidos (em andamento e os últimos finalizados)
+static JOBS: OnceLock<Mutex<HashMap<String, JobEntry>>> = OnceLock::new();
+/// Streams de chat interativo em andamento (jobs pesados reduzem o ritmo enquanto > 0)
+static INTERACTIVE_STREAMS: AtomicUsize = AtomicUsize::new(0);
+
+#[derive(Debug, Clone, Copy, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum JobStatus {
+    Running,
+    Completed,
+    Cancelled,
+    Failed,
+}
+
+/// Estado de um job exposto ao frontend (também enviado no evento `job-progress`)
+#[derive(Debug, Clone, Serialize)]
+pub struct JobInfo {
+    pub id: String,
+    pub kind: String,
+    pub status: JobStatus,
+    pub total: usize,
+    pub completed: usize,
+    pub error: Option<String>,
+    pub started_at: DateTime<Utc>,
+    pub finished_at: Option<DateTime<Utc>>,
+}
+
+struct JobEntry {
+    info: JobInfo,
+    cancel: Arc<AtomicBool>,
+}
+
+fn jobs() -> &'static Mutex<HashMap<String, JobEntry>> {
+    JOBS.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+/// Referência a um job em andamento, usada pela thread que faz o trabalho
+#[derive(Clone)]
+pub struct JobHandle {
+    id: String,
+    cancel: Arc<AtomicBool>,
+    app_handle: AppHandle,
+}
+
+impl JobHandle {
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancel.load(Ordering::Relaxed)
+    }
+
+    /// Atualiza o job e emite `job-progress` com o novo estado
+    fn update(&self, apply: impl FnOnce(&mut JobInfo)) {
+        let info = match jobs().lock() {
+            Ok(mut jobs) => match jobs.get_mut(&self.id) {
+                Some(entry) => {
+                    apply(&mut entry.info);
+                    entry.info.clone()
+                }
+                None => return,
+            },
+            Err(_) => return,
+        };
+        let _ = crate::events::emit(&self.app_handle, &info);
+    }
+
+    pub fn set_total(&self, total: usize) {
+        self.update(|info| info.total = total);
+    }
+
+    /// Soma ao total (jobs que descobrem o trabalho em etapas)
+    pub fn add_total(&self, count: usize) {
+        self.update(|info| info.total += count);
+    }
+
+    pub fn advance(&self, count: usize) {
+        self.update(|info| info.completed += count);
+    }
+
+    /// Marca o job como finalizado (cancelado tem precedência sobre o resultado)
+    pub fn finish(&self, result: Result<(), String>) {
+        let cancelled = self.is_cancelled();
+        self.update(|info| {
+            info.finished_at = Some(Utc::now());
+            match result {
+                _ if cancelled => info.status = JobStatus::Cancelled,
+                Ok(()) => info.status = JobStatus::Completed,
+                Err(e) => {
+                    info.status = JobStatus::Failed;
+                    info.error = Some(e);
+                }
+            }
+        });
+        prune_finished();
+    }
+}
+
+/// Registra um novo job em andamento
+pub fn start(app_handle: &AppHandle, kind: &str) -> JobHandle {
+    start_with_id(app_handle, kind, None)
+}
+
+/// Como `start`, mas com o id escolhido pelo chamador (o frontend passa a ouvir os
+/// eventos do job antes de o comando retornar)
+pub fn start_with_id(app_handle: &AppHandle, kind: &str, id: Option<String>) -> JobHandle {
+    let id = id
+        .filter(|id| !id.trim().is_empty())
+        .unwrap_or_else(|| uuid::Uuid::new_v4().to_string());
+    let cancel = Arc::new(AtomicBool::new(false));
+    let info = JobInfo {
+        id: id.clone(),
+        kind: kind.to_string(),
+        status: JobStatus::Running,
+        total: 0,
+        completed: 0,
+        error: None,
+        started_at: Utc::now(),
+        finished_at: None,
+    };
+
+    if let Ok(mut jobs) = jobs().lock() {
+        jobs.insert(id.clone(), JobEntry { info, cancel: cancel.clone() });
+    }
+
+    JobHandle { id, cancel, app_handle: app_handle.clone() }
+}
+
+/// Remove os jobs finalizados mais antigos além de `MAX_FINISHED_JOBS`
+fn prune_finished() {
+    if let Ok(mut jobs) = jobs().lock() {
+        let mut finished: Vec<(String, DateTime<Utc>)> = jobs
+            .values()
+            .filter_map(|e| e.info.finished_at.map(|t| (e.info.id.clone(), t)))
+            .collect();
+        if finished.len() <= MAX_FINISHED_JOBS {
+            return;
+        }
+        finished.sort_by_key(|(_, t)| *t);
+        let excess = finished.len() - MAX_FINISHED_JOBS;
+        for (id, _) in finished.into_iter().take(excess) {
+            jobs.remove(&id);
+        }
+    }
+}
+
+/// Jobs em andamento e finalizados recentemente (mais novos primeiro)
+pub fn list() -> Vec<JobInfo> {
+    let mut list: Vec<JobInfo> = jobs()
+        .lock()
+        .map(|jobs| jobs.values().map(|e| e.info.clone()).collect())
+        .unwrap_or_default();
+    list.sort_by(|a, b| b.started_at.cmp(&a.started_at));
+    list
+}
+
+/// Solicita o cancelamento; o job para no próximo lote. Retorna false se não estiver rodando.
+pub fn cancel(id: &str) -> bool {
+    match jobs().lock() {
+        Ok(jobs) => match jobs.get(id) {
+            Some(entry) if entry.info.status == JobStatus::Running => {
+                entry.cancel.store(true, Ordering::Relaxed);
+                true
+            }
+            _ => false,
+        },
+        Err(_) => false,
+    }
+}
+
+/// Mantido enquanto um chat interativo está em streaming
+pub struct InteractiveGuard;
+
+impl Drop for InteractiveGuard {
+    fn drop(&mut self) {
+        INTERACTIVE_STREAMS.fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
+/// Sinaliza que um chat interativo começou (jobs pesados desaceleram até o guard ser liberado)
+pub fn begin_interactive() -> InteractiveGuard {
+    INTERACTIVE_STREAMS.fetch_add(1, Ordering::Relaxed);
+    InteractiveGuard
+}
+
+pub fn interactive_active() -> bool {
+    INTERACTIVE_STREAMS.load(Ordering::Relaxed) > 0
+}
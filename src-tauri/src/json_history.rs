@@ -0,0 +1,138 @@
+use serde::de::{self, DeserializeOwned, DeserializeSeed, IgnoredAny, MapAccess, SeqAccess, Visitor};
+use std::collections::VecDeque;
+use std::fmt;
+use std::fs::File;
+use std::io::BufReader;
+use std::marker::PhantomData;
+use std::path::Path;
+
+/// Janela com as últimas `capacity` mensagens de um arquivo de sessão legado
+pub struct MessageWindow<T> {
+    pub messages: VecDeque<T>,
+    pub total: usize,
+}
+
+/// Lê o array `messages` do JSON em streaming, mantendo em memória apenas as
+/// últimas `capacity` mensagens (o resto do arquivo é descartado durante a leitura)
+pub fn read_last_messages<T: DeserializeOwned>(path: &Path, capacity: usize) -> Result<MessageWindow<T>, String> {
+    let file = File::open(path)
+        .map_err(|e| format!("Failed to open session file: {}", e))?;
+    let mut deserializer = serde_json::Deserializer::from_reader(BufReader::new(file));
+
+    SessionSeed { capacity, marker: PhantomData }
+        .deserialize(&mut deserializer)
+        .map_err(|e| format!("Failed to parse session: {}", e))
+}
+
+/// Seleciona uma página contando a partir do fim: `offset = 0` são as últimas `limit` mensagens.
+/// Retorna (mensagens, total, has_more).
+pub fn read_page<T: DeserializeOwned>(path: &Path, limit: usize, offset: usize) -> Result<(Vec<T>, usize, bool), String> {
+    let window = read_last_messages::<T>(path, offset.saturating_add(limit))?;
+    let total = window.total;
+
+    // A janela cobre [total - offset - limit, total); a página exclui as `offset` últimas
+    let keep = window.messages.len().saturating_sub(offset);
+    let page: Vec<T> = window.messages.into_iter().take(keep).collect();
+    let has_more = offset + page.len() < total;
+
+    Ok((page, total, has_more))
+}
+
+struct SessionSeed<T> {
+    capacity: usize,
+    marker: PhantomData<T>,
+}
+
+impl<'de, T: DeserializeOwned> DeserializeSeed<'de> for SessionSeed<T> {
+    type Value = MessageWindow<T>;
+
+    fn deserialize<D: de::Deserializer<'de>>(self, deserializer: D) -> Result<Self::Value, D::Error> {
+        deserializer.deserialize_map(self)
+    }
+}
+
+impl<'de, T: DeserializeOwned> Visitor<'de> for SessionSeed<T> {
+    type Value = MessageWindow<T>;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("a chat session object")
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+        let mut window = None;
+
+        while let Some(key) = map.next_key::<String>()? {
+            if key == "messages" {
+                window = Some(map.next_value_seed(WindowSeed::<T> {
+                    capacity: self.capacity,
+                    marker: PhantomData,
+                })?);
+            } else {
+                map.next_value::<IgnoredAny>()?;
+            }
+        }
+
+        Ok(window.unwrap_or(MessageWindow { messages: VecDeque::new(), total: 0 }))
+    }
+}
+
+struct WindowSeed<T> {
+    capacity: usize,
+    marker: PhantomData<T>,
+}
+
+impl<'de, T: DeserializeOwned> DeserializeSeed<'de> for WindowSeed<T> {
+    type Value = MessageWindow<T>;
+
+    fn deserialize<D: de::Deserializer<'de>>(self, deserializer: D) -> Result<Self::Value, D::Error> {
+        deserializer.deserialize_seq(self)
+    }
+}
+
+impl<'de, T: DeserializeOwned> Visitor<'de> for WindowSeed<T> {
+    type Value = MessageWindow<T>;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("an array of messages")
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+        let mut messages = VecDeque::with_capacity(self.capacity.min(256));
+        let mut total = 0;
+
+        while let Some(message) = seq.next_element::<T>()? {
+            total += 1;
+            if self.capacity == 0 {
+                continue;
+            }
+            if messages.len() == self.capacity {
+                messages.pop_front();
+            }
+            messages.push_back(message);
+        }
+
+        Ok(MessageWindow { messages, total })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_read_page_from_end() {
+        let path = std::env::temp_dir().join(format!("ollahub_json_history_{}.json", std::process::id()));
+        std::fs::write(
+            &path,
+            r#"{"id":"s1","title":"t","messages":[1,2,3,4,5,6,7],"platform":"linux"}"#,
+        )
+        .unwrap();
+
+        assert_eq!(read_page::<u32>(&path, 3, 0).unwrap(), (vec![5, 6, 7], 7, true));
+        assert_eq!(read_page::<u32>(&path, 3, 3).unwrap(), (vec![2, 3, 4], 7, true));
+        assert_eq!(read_page::<u32>(&path, 3, 6).unwrap(), (vec![1], 7, false));
+        assert_eq!(read_page::<u32>(&path, 3, 10).unwrap(), (vec![], 7, false));
+
+        let _ = std::fs::remove_file(&path);
+    }
+}
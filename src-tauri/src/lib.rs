@@ -4,9 +4,9 @@ use std::time::{Duration, Instant};
 use futures_util::StreamExt;
 use std::fs;
 use std::path::PathBuf;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::sync::{Arc, Mutex};
-use tauri::{command, Window, Emitter, Manager, AppHandle, State};
+use tauri::{command, Window, Emitter, Listener, Manager, AppHandle, State};
 use sysinfo::System;
 use chrono::{DateTime, Utc};
 use zip::write::{FileOptions, ZipWriter};
@@ -22,11 +22,71 @@ mod system_monitor;
 mod intent_classifier;
 mod db;
 mod embeddings;
+mod resource_profile;
+mod translation;
+mod api_tokens;
+mod tray;
+mod settings;
+mod prompts;
+mod paste;
+mod json_history;
+mod rate_limit;
+mod citations;
+mod connectivity;
+mod jobs;
+mod model_context;
+mod dropped_files;
+mod workspace_bundle;
+mod cold_storage;
+mod browser_pool;
+mod paths;
+mod unread;
+mod digests;
+mod capabilities;
+mod storage_usage;
+mod logging;
+mod stream_manager;
+mod replay;
+mod emoji_picker;
+mod share;
+mod stt;
+mod code_runner;
+mod workspace;
+mod builtin_tools;
+mod ollama_server;
+mod ollama_update;
+mod gguf;
+mod gguf_import;
+mod model_archive;
+mod chat_templates;
+mod post_process;
+mod structured_output;
+mod feeds;
+mod engine_health;
+mod query_expansion;
+mod image_search;
+mod youtube;
+mod resolvers;
+mod analytics;
+mod retention;
+mod folder_sync;
+mod external_import;
+mod palette;
+mod plugins;
+mod events;
+mod audit;
+mod redaction;
+mod offline;
+mod autostart;
+mod onboarding;
+mod diagnostics;
+mod memory;
+mod user_profile;
+mod content_guard;
 
 use web_scraper::{
     ScrapedContent,
     SearchResultMetadata,
-    create_browser,
     search_and_scrape,
     search_and_scrape_with_config,
     scrape_url,
@@ -36,52 +96,34 @@ use web_scraper::{
     SearchEngine,
     smart_search,
     scrape_urls_bulk,
+    scrape_url_static,
+    scrape_urls_static,
+    search_and_scrape_static,
 };
-use headless_chrome::Browser;
 use scheduler::{SentinelTask, SchedulerService, SchedulerState, TaskAction};
 use sources_config::{SourcesConfig, load_sources_config, save_sources_config};
 use system_monitor::{SystemStats, SystemMonitorState, GpuInfo, GpuStats};
+use resource_profile::{ResourceProfile, LowResourceMode};
+use settings::{AppSettings, SettingsState};
 
 // CommandExt é importado localmente onde necessário
 
 #[derive(serde::Serialize, serde::Deserialize, Clone)]
 struct Message {
+    /// ID da mensagem no SQLite (ausente em mensagens ainda não persistidas)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    id: Option<i64>,
     role: String,
     content: String,
     #[serde(skip_serializing_if = "Option::is_none")]
     metadata: Option<serde_json::Value>,
 }
 
-// Eventos para comunicação Frontend <-> Rust
-#[derive(serde::Serialize, Clone)]
-struct ChatCreatedEvent {
-    session_id: String,
-    title: String,
-    emoji: String,
-}
-
-#[derive(serde::Serialize, Clone)]
-struct ChatTokenEvent {
-    session_id: String,
-    content: String,
-    done: bool,
-}
-
-#[derive(serde::Serialize, Clone)]
-struct ChatErrorEvent {
-    session_id: String,
-    error: String,
-}
-
-#[derive(serde::Serialize)]
-struct DownloadProgress {
-    status: String,          // "pulling", "verifying", "success"
-    percent: Option<u8>,     // 0-100
-    downloaded: Option<String>, // "552 MB"
-    total: Option<String>,      // "1.2 GB"
-    speed: Option<String>,      // "25 MB/s"
-    raw: String,             // linha original para fallback
-}
+// Eventos para comunicação Frontend <-> Rust (payloads em events.rs)
+use events::{
+    ChatCreatedEvent, ChatErrorEvent, ChatPostProcessedEvent, ChatThinkingEvent, ChatTokenEvent,
+    ChatWarningEvent, DownloadProgress, SessionRecoveredEvent,
+};
 
 #[derive(serde::Deserialize)]
 struct PullProgress {
@@ -117,6 +159,12 @@ struct SessionSummary {
     platform: String,
     #[serde(skip_serializing_if = "Option::is_none")]
     match_count: Option<usize>,
+    /// Mensagens que casaram com a busca (ids e offsets para navegação)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    hits: Option<db::SessionHits>,
+    tags: Vec<String>,
+    folder: Option<String>,
+    archived: bool,
 }
 
 #[derive(serde::Serialize)]
@@ -127,15 +175,6 @@ struct SystemSpecs {
     gpus: Vec<GpuInfo>,
 }
 
-// SystemStats movido para system_monitor.rs
-// Mantendo apenas para compatibilidade com start_system_monitor
-#[derive(serde::Serialize, Clone)]
-struct LegacySystemStats {
-    cpu_usage: f32,
-    memory_used: u64,
-    memory_total: u64,
-}
-
 #[derive(serde::Serialize)]
 struct LocalModel {
     name: String,
@@ -175,9 +214,6 @@ struct McpProcessHandle {
 // MCP Process Manager State
 type McpProcessMap = Arc<Mutex<HashMap<String, McpProcessHandle>>>;
 
-// Web Scraper Browser State (singleton para reutilização)
-type BrowserState = Arc<Mutex<Option<Arc<Browser>>>>;
-
 // File Lock Manager - previne corrupção de dados em escritas concorrentes
 type FileLockMap = Arc<Mutex<HashMap<String, Arc<Mutex<()>>>>>;
 
@@ -358,25 +394,19 @@ fn save_chat_session(
     
     let now = Utc::now();
     
-    // Try to load existing to keep created_at, or use now
-    let created_at = if file_path.exists() {
-        if let Ok(content) = fs::read_to_string(&file_path) {
-            if let Ok(session) = serde_json::from_str::<ChatSession>(&content) {
-                session.created_at
-            } else {
-                now
-            }
-        } else {
-            now
-        }
-    } else {
-        now
-    };
+    // Try to load existing to keep created_at (and the memory when not sent), or use now
+    let existing = fs::read_to_string(&file_path)
+        .ok()
+        .and_then(|content| serde_json::from_str::<ChatSession>(&content).ok());
+    let created_at = existing.as_ref().map_or(now, |session| session.created_at);
 
     let platform = platform.unwrap_or_else(|| System::name().unwrap_or("Unknown".to_string()));
-    let memory_context = memory_context.unwrap_or_default();
+    // Sem memory_context, mantém a memória gravada (add_memory_item e a extração automática)
+    let memory_context = memory_context
+        .or_else(|| existing.map(|session| session.memory_context))
+        .unwrap_or_default();
 
-    let session = ChatSession {
+    let mut session = ChatSession {
         id: id.clone(),
         title,
         messages,
@@ -386,20 +416,8 @@ fn save_chat_session(
         memory_context,
     };
 
-    let json = serde_json::to_string_pretty(&session)
-        .map_err(|e| format!("Failed to serialize session: {}", e))?;
-    
-    // Escrever em arquivo temporário primeiro, depois renomear (atomic write)
-    let temp_path = file_path.with_extension("json.tmp");
-    fs::write(&temp_path, json)
-        .map_err(|e| format!("Failed to write temp session file: {}", e))?;
-    
-    // Renomear atomicamente (operação atômica na maioria dos sistemas)
-    fs::rename(&temp_path, &file_path)
-        .map_err(|e| format!("Failed to rename temp file to session file: {}", e))?;
-    
-    // Também salvar no SQLite (sistema novo) para melhor performance e paginação
-    // Se falhar, apenas logar erro mas não falhar a operação (compatibilidade)
+    // Salvar primeiro no SQLite (sistema novo, melhor performance e paginação) para gravar no
+    // JSON os IDs e metadados resultantes. Se falhar, apenas logar erro (compatibilidade)
     use db::Database;
     match Database::new(&app_handle) {
         Ok(db) => {
@@ -416,19 +434,17 @@ fn save_chat_session(
                 log::warn!("Failed to save session to SQLite (continuing with JSON only): {}", e);
             } else {
                 // Converter Message para ChatMessage e salvar no SQLite
-                // Preservar ordem usando timestamps incrementais baseados no índice
+                // Mensagens novas recebem timestamps incrementais a partir da criação da sessão;
+                // `save_messages_batch` mantém os das já gravadas e garante a ordem
                 let chat_messages: Vec<db::ChatMessage> = session.messages.iter().enumerate().map(|(idx, msg)| {
                     let metadata_str = msg.metadata.as_ref()
                         .and_then(|m| serde_json::to_string(m).ok());
                     
-                    // Criar timestamp incremental para preservar ordem das mensagens
-                    // Usar created_at da sessão como base e adicionar segundos baseados no índice
-                    // Isso garante que a ordem seja mantida quando ordenado por created_at ASC
                     let base_time = session.created_at;
                     let msg_created_at = base_time + chrono::Duration::seconds(idx as i64);
                     
                     db::ChatMessage {
-                        id: None,
+                        id: msg.id,
                         session_id: session.id.clone(),
                         role: msg.role.clone(),
                         content: msg.content.clone(),
@@ -437,24 +453,65 @@ fn save_chat_session(
                     }
                 }).collect();
                 
-                if let Err(e) = db.save_messages_batch(&session.id, &chat_messages) {
-                    log::warn!("Failed to save messages to SQLite (continuing with JSON only): {}", e);
-                } else {
-                    log::debug!("Successfully saved {} messages to SQLite for session {}", chat_messages.len(), session.id);
+                match db.save_messages_batch(&session.id, &chat_messages) {
+                    Ok(saved) => {
+                        // IDs estáveis e metadados mesclados (patches de set_message_metadata)
+                        for (msg, stored) in session.messages.iter_mut().zip(saved) {
+                            msg.id = stored.id;
+                            msg.metadata = stored.metadata
+                                .and_then(|m| serde_json::from_str::<serde_json::Value>(&m).ok());
+                        }
+                        log::debug!("Successfully saved {} messages to SQLite for session {}", chat_messages.len(), session.id);
+                    }
+                    Err(e) => {
+                        log::warn!("Failed to save messages to SQLite (continuing with JSON only): {}", e);
+                    }
                 }
             }
         }
         Err(e) => {
-            log::debug!("Failed to open database for saving (JSON saved successfully): {}", e);
+            log::debug!("Failed to open database for saving (saving JSON only): {}", e);
         }
     }
+
+    let json = serde_json::to_string_pretty(&session)
+        .map_err(|e| format!("Failed to serialize session: {}", e))?;
+    
+    // Escrever em arquivo temporário primeiro, depois renomear (atomic write)
+    let temp_path = file_path.with_extension("json.tmp");
+    fs::write(&temp_path, json)
+        .map_err(|e| format!("Failed to write temp session file: {}", e))?;
+    
+    // Renomear atomicamente (operação atômica na maioria dos sistemas)
+    fs::rename(&temp_path, &file_path)
+        .map_err(|e| format!("Failed to rename temp file to session file: {}", e))?;
     
     // Lock é liberado automaticamente quando _guard sai de escopo
     Ok(())
 }
 
+/// Mensagens por sessão retornadas em `hits` na busca global (o total vem em `hits.total`)
+const SEARCH_HITS_PER_SESSION: usize = 20;
+
+/// Valida a sintaxe de busca avançada (AND/OR/NOT, NEAR, prefixo*, aspas e parênteses)
+/// para a UI mostrar o erro enquanto o usuário digita. `None` quando a busca é válida;
+/// buscas inválidas continuam funcionando em `search_chat_sessions` via LIKE.
+#[command]
+fn validate_search_query(query: String) -> Option<db::FtsSyntaxError> {
+    if query.trim().is_empty() {
+        return None;
+    }
+    db::build_fts_advanced_query(&query).err()
+}
+
 #[command]
-fn search_chat_sessions(app_handle: AppHandle, query: String, limit: Option<usize>) -> Result<Vec<SessionSummary>, String> {
+fn search_chat_sessions(
+    app_handle: AppHandle,
+    query: String,
+    limit: Option<usize>,
+    filter: Option<db::SessionFilter>,
+    hits_per_session: Option<usize>,
+) -> Result<Vec<SessionSummary>, String> {
     use db::Database;
     
     let db = Database::new(&app_handle)
@@ -464,6 +521,22 @@ fn search_chat_sessions(app_handle: AppHandle, query: String, limit: Option<usiz
     let search_results = db.search_sessions(&query, search_limit)
         .map_err(|e| format!("Search failed: {}", e))?;
     
+    // Mensagens que casaram em cada sessão (alvos de navegação)
+    let mut hits = if query.trim().is_empty() {
+        HashMap::new()
+    } else {
+        let session_ids: Vec<String> = search_results.iter().map(|r| r.session.id.clone()).collect();
+        db.search_message_hits(&session_ids, &query, hits_per_session.unwrap_or(SEARCH_HITS_PER_SESSION))
+            .unwrap_or_else(|e| {
+                log::warn!("Failed to load message hits: {}", e);
+                HashMap::new()
+            })
+    };
+    
+    let filter = filter.unwrap_or_default();
+    let organization = db.get_sessions_organization()
+        .map_err(|e| format!("Failed to load session organization: {}", e))?;
+    
     // Validar existência de cada sessão antes de retornar
     let chats_dir = get_chats_dir(&app_handle)?;
     let mut summaries = Vec::new();
@@ -472,6 +545,11 @@ fn search_chat_sessions(app_handle: AppHandle, query: String, limit: Option<usiz
     for search_result in search_results {
         let session = search_result.session;
         let match_count = search_result.match_count;
+        
+        let org = organization.get(&session.id).cloned().unwrap_or_default();
+        if !filter.matches(&org) {
+            continue;
+        }
         // Verificar se sessão existe no SQLite (já temos)
         let exists_in_sqlite = db.get_session(&session.id)
             .ok()
@@ -513,6 +591,7 @@ fn search_chat_sessions(app_handle: AppHandle, query: String, limit: Option<usiz
                 None
             })
             .unwrap_or_default();
+        let session_hits = hits.remove(&session.id);
         
         summaries.push(SessionSummary {
             id: session.id,
@@ -522,6 +601,10 @@ fn search_chat_sessions(app_handle: AppHandle, query: String, limit: Option<usiz
             preview,
             platform: String::new(), // Platform não está no SQLite ainda
             match_count: Some(match_count as usize),
+            hits: session_hits,
+            tags: org.tags,
+            folder: org.folder,
+            archived: org.archived,
         });
     }
     
@@ -533,10 +616,23 @@ fn search_chat_sessions(app_handle: AppHandle, query: String, limit: Option<usiz
 }
 
 #[command]
-fn load_chat_sessions(app_handle: AppHandle) -> Result<Vec<SessionSummary>, String> {
+fn load_chat_sessions(app_handle: AppHandle, filter: Option<db::SessionFilter>) -> Result<Vec<SessionSummary>, String> {
     let chats_dir = get_chats_dir(&app_handle)?;
     let mut summaries = Vec::new();
     
+    // Organização (tags/pasta/arquivada) fica no SQLite; sessões só no JSON usam o padrão
+    let filter = filter.unwrap_or_default();
+    let organization = match db::Database::new(&app_handle) {
+        Ok(db) => db.get_sessions_organization().unwrap_or_else(|e| {
+            log::warn!("Failed to load session organization: {}", e);
+            HashMap::new()
+        }),
+        Err(e) => {
+            log::debug!("Failed to open database for session organization: {}", e);
+            HashMap::new()
+        }
+    };
+    
     let entries = fs::read_dir(chats_dir)
         .map_err(|e| format!("Failed to read chats dir: {}", e))?;
         
@@ -546,6 +642,11 @@ fn load_chat_sessions(app_handle: AppHandle) -> Result<Vec<SessionSummary>, Stri
             if path.extension().and_then(|s| s.to_str()) == Some("json") {
                 if let Ok(content) = fs::read_to_string(&path) {
                     if let Ok(session) = serde_json::from_str::<ChatSession>(&content) {
+                        let org = organization.get(&session.id).cloned().unwrap_or_default();
+                        if !filter.matches(&org) {
+                            continue;
+                        }
+                        
                         let last_msg = session.messages.last()
                             .map(|m| m.content.chars().take(50).collect::<String>())
                             .unwrap_or_default();
@@ -572,6 +673,10 @@ fn load_chat_sessions(app_handle: AppHandle) -> Result<Vec<SessionSummary>, Stri
                             preview: last_msg,
                             platform: session.platform,
                             match_count: None,
+                            hits: None,
+                            tags: org.tags,
+                            folder: org.folder,
+                            archived: org.archived,
                         });
                     }
                 }
@@ -618,6 +723,7 @@ fn load_chat_history(app_handle: AppHandle, id: String) -> Result<Vec<Message>,
                             });
                         
                         Message {
+                            id: msg.id,
                             role: role.to_string(),
                             content: msg.content,
                             metadata: metadata_value,
@@ -658,1248 +764,1690 @@ fn load_chat_history(app_handle: AppHandle, id: String) -> Result<Vec<Message>,
     Ok(session.messages)
 }
 
-/// Resultado de carregamento paginado de histórico
-#[derive(serde::Serialize)]
-struct PaginatedHistory {
-    messages: Vec<Message>,
-    total_count: usize,
-    has_more: bool,
-}
-
-/// Carrega histórico de chat com paginação (lazy loading)
-/// 
-/// Parâmetros:
-/// - id: ID da sessão
-/// - limit: número máximo de mensagens a retornar (default: 20)
-/// - offset: número de mensagens a pular do final (default: 0)
+/// Busca dentro de uma conversa: IDs das mensagens, offsets e snippets destacados
 #[command]
-fn load_chat_history_paginated(
+fn search_in_session(
     app_handle: AppHandle,
-    id: String,
+    session_id: String,
+    query: String,
     limit: Option<usize>,
-    offset: Option<usize>,
-) -> Result<PaginatedHistory, String> {
-    use db::Database;
-    
-    let limit = limit.unwrap_or(20);
-    let offset = offset.unwrap_or(0);
+) -> Result<Vec<db::MessageSearchMatch>, String> {
+    let db = db::Database::new(&app_handle)
+        .map_err(|e| format!("Failed to open database: {}", e))?;
     
-    match Database::new(&app_handle) {
-        Ok(db) => {
-            match db.get_messages_paginated(&id, limit, offset) {
-                Ok((messages, total_count, has_more)) => {
-                    // Se SQLite retornou 0 mensagens, tentar fallback para JSON (sistema legado)
-                    if total_count == 0 {
-                        log::debug!("No messages in SQLite for session {}, trying JSON fallback", id);
-                        
-                        // Tentar carregar do JSON
-                        let chats_dir = match get_chats_dir(&app_handle) {
-                            Ok(dir) => dir,
-                            Err(e) => {
-                                log::debug!("Failed to get chats dir for JSON fallback: {}", e);
-                                // Retornar vazio se não conseguir acessar diretório
-                                return Ok(PaginatedHistory {
-                                    messages: Vec::new(),
-                                    total_count: 0,
-                                    has_more: false,
-                                });
-                            }
-                        };
-                        
-                        let file_path = chats_dir.join(format!("{}.json", id));
-                        if file_path.exists() {
-                            match fs::read_to_string(&file_path) {
-                                Ok(content) => {
-                                    match serde_json::from_str::<ChatSession>(&content) {
-                                        Ok(session) => {
-                                            let all_messages = session.messages;
-                                            let total = all_messages.len();
-                                            
-                                            if total == 0 {
-                                                return Ok(PaginatedHistory {
-                                                    messages: Vec::new(),
-                                                    total_count: 0,
-                                                    has_more: false,
-                                                });
-                                            }
-                                            
-                                            // Aplicar paginação em memória (simular comportamento do SQLite)
-                                            // offset=0 significa últimas N mensagens
-                                            // offset>0 significa mensagens antes das últimas N
-                                            let start_idx = if offset + limit <= total {
-                                                total - offset - limit
-                                            } else {
-                                                0
-                                            };
-                                            
-                                            let end_idx = std::cmp::min(start_idx + limit, total);
-                                            let paginated_messages: Vec<Message> = all_messages
-                                                .into_iter()
-                                                .skip(start_idx)
-                                                .take(end_idx - start_idx)
-                                                .collect();
-                                            
-                                            let has_more = offset + paginated_messages.len() < total;
-                                            
-                                            log::info!(
-                                                "Loaded {} messages (offset: {}, total: {}, has_more: {}) from JSON fallback for session {}",
-                                                paginated_messages.len(), offset, total, has_more, id
-                                            );
-                                            
-                                            return Ok(PaginatedHistory {
-                                                messages: paginated_messages,
-                                                total_count: total,
-                                                has_more,
-                                            });
-                                        }
-                                        Err(e) => {
-                                            log::debug!("Failed to parse JSON session: {}", e);
-                                        }
-                                    }
-                                }
-                                Err(e) => {
-                                    log::debug!("Failed to read JSON file: {}", e);
-                                }
-                            }
-                        }
-                        
-                        // Se chegou aqui, não encontrou mensagens nem no SQLite nem no JSON
-                        return Ok(PaginatedHistory {
-                            messages: Vec::new(),
-                            total_count: 0,
-                            has_more: false,
-                        });
-                    }
-                    
-                    // Converter ChatMessage para Message
-                    let result: Vec<Message> = messages.into_iter().map(|msg| {
-                        let role = if msg.role == "user" {
-                            "user"
-                        } else if msg.role == "assistant" {
-                            "assistant"
-                        } else {
-                            "system"
-                        };
-                        
-                        let metadata = msg.metadata.and_then(|m| {
-                            serde_json::from_str::<serde_json::Value>(&m).ok()
-                        });
-                        
-                        let metadata_value = metadata
-                            .and_then(|m| {
-                                if m.is_object() && !m.as_object().unwrap().is_empty() {
-                                    Some(m)
-                                } else {
-                                    None
-                                }
-                            });
-                        
-                        Message {
-                            role: role.to_string(),
-                            content: msg.content,
-                            metadata: metadata_value,
-                        }
-                    }).collect();
-                    
-                    log::info!(
-                        "Loaded {} messages (offset: {}, total: {}, has_more: {}) from SQLite for session {}",
-                        result.len(), offset, total_count, has_more, id
-                    );
-                    
-                    Ok(PaginatedHistory {
-                        messages: result,
-                        total_count,
-                        has_more,
-                    })
-                }
-                Err(e) => {
-                    log::debug!("SQLite query failed for session {}: {}, trying JSON fallback", id, e);
-                    
-                    // Fallback para JSON em caso de erro também
-                    let chats_dir = match get_chats_dir(&app_handle) {
-                        Ok(dir) => dir,
-                        Err(_) => {
-                            return Err(format!("Failed to load paginated history: {}", e));
-                        }
-                    };
-                    
-                    let file_path = chats_dir.join(format!("{}.json", id));
-                    if file_path.exists() {
-                        match fs::read_to_string(&file_path) {
-                            Ok(content) => {
-                                match serde_json::from_str::<ChatSession>(&content) {
-                                    Ok(session) => {
-                                        let all_messages = session.messages;
-                                        let total = all_messages.len();
-                                        
-                                        if total == 0 {
-                                            return Ok(PaginatedHistory {
-                                                messages: Vec::new(),
-                                                total_count: 0,
-                                                has_more: false,
-                                            });
-                                        }
-                                        
-                                        let start_idx = if offset + limit <= total {
-                                            total - offset - limit
-                                        } else {
-                                            0
-                                        };
-                                        
-                                        let end_idx = std::cmp::min(start_idx + limit, total);
-                                        let paginated_messages: Vec<Message> = all_messages
-                                            .into_iter()
-                                            .skip(start_idx)
-                                            .take(end_idx - start_idx)
-                                            .collect();
-                                        
-                                        let has_more = offset + paginated_messages.len() < total;
-                                        
-                                        log::info!(
-                                            "Loaded {} messages (offset: {}, total: {}, has_more: {}) from JSON fallback (error case) for session {}",
-                                            paginated_messages.len(), offset, total, has_more, id
-                                        );
-                                        
-                                        return Ok(PaginatedHistory {
-                                            messages: paginated_messages,
-                                            total_count: total,
-                                            has_more,
-                                        });
-                                    }
-                                    Err(e2) => {
-                                        return Err(format!("Failed to load paginated history: {} (JSON parse error: {})", e, e2));
-                                    }
-                                }
-                            }
-                            Err(e2) => {
-                                return Err(format!("Failed to load paginated history: {} (JSON read error: {})", e, e2));
-                            }
-                        }
-                    }
-                    
-                    Err(format!("Failed to load paginated history: {}", e))
-                }
-            }
-        }
-        Err(e) => {
-            log::debug!("Failed to open database: {}, trying JSON fallback", e);
-            
-            // Fallback para JSON se não conseguir abrir banco
-            let chats_dir = match get_chats_dir(&app_handle) {
-                Ok(dir) => dir,
-                Err(e2) => {
-                    return Err(format!("Failed to open database: {} (chats dir error: {})", e, e2));
-                }
-            };
-            
-            let file_path = chats_dir.join(format!("{}.json", id));
-            if file_path.exists() {
-                match fs::read_to_string(&file_path) {
-                    Ok(content) => {
-                        match serde_json::from_str::<ChatSession>(&content) {
-                            Ok(session) => {
-                                let all_messages = session.messages;
-                                let total = all_messages.len();
-                                
-                                if total == 0 {
-                                    return Ok(PaginatedHistory {
-                                        messages: Vec::new(),
-                                        total_count: 0,
-                                        has_more: false,
-                                    });
-                                }
-                                
-                                let start_idx = if offset + limit <= total {
-                                    total - offset - limit
-                                } else {
-                                    0
-                                };
-                                
-                                let end_idx = std::cmp::min(start_idx + limit, total);
-                                let paginated_messages: Vec<Message> = all_messages
-                                    .into_iter()
-                                    .skip(start_idx)
-                                    .take(end_idx - start_idx)
-                                    .collect();
-                                
-                                let has_more = offset + paginated_messages.len() < total;
-                                
-                                log::info!(
-                                    "Loaded {} messages (offset: {}, total: {}, has_more: {}) from JSON fallback (db open error) for session {}",
-                                    paginated_messages.len(), offset, total, has_more, id
-                                );
-                                
-                                return Ok(PaginatedHistory {
-                                    messages: paginated_messages,
-                                    total_count: total,
-                                    has_more,
-                                });
-                            }
-                            Err(e2) => {
-                                return Err(format!("Failed to open database: {} (JSON parse error: {})", e, e2));
-                            }
-                        }
-                    }
-                    Err(e2) => {
-                        return Err(format!("Failed to open database: {} (JSON read error: {})", e, e2));
-                    }
-                }
-            }
-            
-            Err(format!("Failed to open database: {}", e))
+    db.search_in_session(&session_id, &query, limit.unwrap_or(200))
+        .map_err(|e| format!("Search failed: {}", e))
+}
+
+/// Normaliza tags: remove espaços, vazias e duplicadas (sem diferenciar maiúsculas)
+fn normalize_tags(tags: Vec<String>) -> Vec<String> {
+    let mut normalized: Vec<String> = Vec::new();
+    for tag in tags {
+        let tag = tag.trim().to_string();
+        if !tag.is_empty() && !normalized.iter().any(|t| t.eq_ignore_ascii_case(&tag)) {
+            normalized.push(tag);
         }
     }
+    normalized
 }
 
+/// Define as tags de uma sessão (substitui as existentes)
 #[command]
-fn delete_chat_session(app_handle: AppHandle, id: String) -> Result<(), String> {
-    use db::Database;
-    
-    let mut errors = Vec::new();
-    
-    // 1. Deletar do sistema legado (arquivos JSON)
-    let chats_dir = get_chats_dir(&app_handle)?;
-    let file_path = chats_dir.join(format!("{}.json", id));
+fn set_session_tags(app_handle: AppHandle, session_id: String, tags: Vec<String>) -> Result<Vec<String>, String> {
+    let db = db::Database::new(&app_handle)
+        .map_err(|e| format!("Failed to open database: {}", e))?;
     
-    if file_path.exists() {
-        if let Err(e) = fs::remove_file(&file_path) {
-            errors.push(format!("Failed to delete JSON file: {}", e));
-        } else {
-            log::info!("Deleted session JSON file: {}", id);
-        }
+    let tags = normalize_tags(tags);
+    if !db.set_session_tags(&session_id, &tags)
+        .map_err(|e| format!("Failed to set session tags: {}", e))? {
+        return Err("Session not found".to_string());
     }
+    Ok(tags)
+}
+
+/// Move uma sessão para uma pasta (`None` ou vazio remove da pasta)
+#[command]
+fn move_session_to_folder(app_handle: AppHandle, session_id: String, folder: Option<String>) -> Result<(), String> {
+    let db = db::Database::new(&app_handle)
+        .map_err(|e| format!("Failed to open database: {}", e))?;
     
-    // 2. Deletar do SQLite (sistema novo)
-    match Database::new(&app_handle) {
-        Ok(db) => {
-            if let Err(e) = db.delete_session(&id) {
-                errors.push(format!("Failed to delete from SQLite: {}", e));
-            } else {
-                log::info!("Deleted session from SQLite: {}", id);
-            }
-        }
-        Err(e) => {
-            errors.push(format!("Failed to open database: {}", e));
-        }
+    let folder = folder
+        .map(|f| f.trim().to_string())
+        .filter(|f| !f.is_empty());
+    if !db.set_session_folder(&session_id, folder.as_deref())
+        .map_err(|e| format!("Failed to move session: {}", e))? {
+        return Err("Session not found".to_string());
     }
+    Ok(())
+}
+
+/// Arquiva (ou desarquiva, com `archived = false`) uma sessão
+#[command]
+fn archive_session(app_handle: AppHandle, session_id: String, archived: Option<bool>) -> Result<(), String> {
+    let db = db::Database::new(&app_handle)
+        .map_err(|e| format!("Failed to open database: {}", e))?;
     
-    // Se ambos falharam, retornar erro
-    if !errors.is_empty() && !file_path.exists() {
-        // Se arquivo JSON não existe, verificar se pelo menos deletou do SQLite
-        match Database::new(&app_handle) {
-            Ok(db) => {
-                if db.get_session(&id).ok().flatten().is_none() {
-                    // Sessão não existe em nenhum lugar, considerar sucesso
-                    return Ok(());
-                }
-            }
-            _ => {}
-        }
+    if !db.set_session_archived(&session_id, archived.unwrap_or(true))
+        .map_err(|e| format!("Failed to archive session: {}", e))? {
+        return Err("Session not found".to_string());
     }
+    Ok(())
+}
+
+/// Marca (ou desmarca, com `template = false`) uma sessão como template
+#[command]
+fn set_session_template(app_handle: AppHandle, session_id: String, template: Option<bool>) -> Result<(), String> {
+    let db = db::Database::new(&app_handle)
+        .map_err(|e| format!("Failed to open database: {}", e))?;
     
-    // Se houve erros mas pelo menos um sistema foi atualizado, logar mas não falhar
-    if !errors.is_empty() {
-        log::warn!("Some errors during deletion of session {}: {:?}", id, errors);
+    if !db.set_session_template(&session_id, template.unwrap_or(true))
+        .map_err(|e| format!("Failed to update session template flag: {}", e))? {
+        return Err("Session not found".to_string());
     }
+    Ok(())
+}
+
+/// Liga (ou desliga, com `offline = false`) o modo offline: a sessão não usa RAG, busca web
+/// nem ferramentas de rede, e cada tentativa emite `policy-violation`
+#[command]
+fn set_session_offline(app_handle: AppHandle, session_id: String, offline: Option<bool>) -> Result<(), String> {
+    let db = db::Database::new(&app_handle)
+        .map_err(|e| format!("Failed to open database: {}", e))?;
     
+    if !db.set_session_offline(&session_id, offline.unwrap_or(true))
+        .map_err(|e| format!("Failed to update session offline flag: {}", e))? {
+        return Err("Session not found".to_string());
+    }
     Ok(())
 }
 
+/// Fatos lembrados da sessão (`memory_context`), incluídos no system prompt de `chat_stream`
 #[command]
-fn get_system_specs() -> SystemSpecs {
-    let mut sys = System::new_all();
-    sys.refresh_all();
+fn get_session_memory(app_handle: AppHandle, session_id: String) -> Result<Vec<String>, String> {
+    memory::get(&app_handle, &session_id)
+}
 
-    // Detectar todas as GPUs
-    let gpus = system_monitor::detect_all_gpus();
+/// Acrescenta um fato à memória da sessão e devolve a memória atualizada
+#[command]
+fn add_memory_item(app_handle: AppHandle, session_id: String, item: String) -> Result<Vec<String>, String> {
+    memory::add(&app_handle, &session_id, &item)
+}
 
-    SystemSpecs {
-        total_memory: sys.total_memory(),
-        cpu_count: sys.cpus().len(),
-        os_name: System::name().unwrap_or("Unknown".to_string()),
-        gpus,
-    }
+/// Remove um fato da memória da sessão (sem diferenciar maiúsculas) e devolve a memória atualizada
+#[command]
+fn remove_memory_item(app_handle: AppHandle, session_id: String, item: String) -> Result<Vec<String>, String> {
+    memory::remove(&app_handle, &session_id, &item)
 }
 
-/// Retorna o sistema operacional atual: 'windows', 'mac', ou 'linux'
+/// Duplica uma sessão com sua configuração (modelo, system prompt, opções de geração, escopo
+/// RAG, preset de prompt e contexto fixado), tags e pasta. As mensagens só são copiadas com
+/// `include_messages` (padrão) e nunca de um template, que sempre gera uma conversa vazia.
 #[command]
-fn get_operating_system() -> String {
-    #[cfg(target_os = "windows")]
-    {
-        return "windows".to_string();
-    }
+fn duplicate_session(
+    app_handle: AppHandle,
+    file_locks: State<'_, FileLockMap>,
+    id: String,
+    include_messages: Option<bool>,
+) -> Result<TemplateSession, String> {
+    let db = db::Database::new(&app_handle)
+        .map_err(|e| format!("Failed to open database: {}", e))?;
+    let source = db.get_session(&id)
+        .map_err(|e| format!("Failed to load session: {}", e))?
+        .ok_or_else(|| "Session not found".to_string())?;
+    let organization = db.get_sessions_organization()
+        .map_err(|e| format!("Failed to load session organization: {}", e))?
+        .remove(&id)
+        .unwrap_or_default();
+    let copy_messages = include_messages.unwrap_or(true) && !organization.template;
+    
+    // Contexto fixado só existe no JSON legado
+    let legacy: Option<ChatSession> = fs::read_to_string(get_chats_dir(&app_handle)?.join(format!("{}.json", id)))
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok());
+    let messages: Vec<Message> = if copy_messages {
+        db.get_messages(&id)
+            .map_err(|e| format!("Failed to load messages: {}", e))?
+            .into_iter()
+            .map(|msg| Message {
+                id: None,
+                role: msg.role,
+                content: msg.content,
+                metadata: msg.metadata.and_then(|m| serde_json::from_str(&m).ok()),
+            })
+            .collect()
+    } else {
+        Vec::new()
+    };
+    let title = if organization.template {
+        source.title.clone()
+    } else {
+        format!("{} (cópia)", source.title)
+    };
     
-    #[cfg(target_os = "macos")]
+    let session_id = uuid::Uuid::new_v4().to_string();
+    save_chat_session(
+        app_handle.clone(),
+        file_locks,
+        session_id.clone(),
+        title.clone(),
+        messages,
+        legacy.as_ref().map(|s| s.platform.clone()).filter(|p| !p.is_empty()),
+        legacy.map(|s| s.memory_context),
+    )?;
+    
+    // save_chat_session não cria a sessão no SQLite sem mensagens
+    if db.get_session(&session_id)
+        .map_err(|e| format!("Failed to load session: {}", e))?
+        .is_none()
     {
-        return "mac".to_string();
+        let now = Utc::now();
+        db.create_session(&db::ChatSession {
+            id: session_id.clone(),
+            title: title.clone(),
+            emoji: source.emoji.clone(),
+            created_at: now,
+            updated_at: now,
+        }).map_err(|e| format!("Failed to create session: {}", e))?;
     }
     
-    #[cfg(target_os = "linux")]
+    let config = db.get_session_config(&id)
+        .map_err(|e| format!("Failed to load session config: {}", e))?
+        .unwrap_or_default();
+    db.set_session_config(&session_id, &config)
+        .map_err(|e| format!("Failed to save session config: {}", e))?;
+    if let Some((preset_id, variables)) = db.get_session_prompt_preset(&id)
+        .map_err(|e| format!("Failed to load prompt preset: {}", e))?
     {
-        return "linux".to_string();
+        let variables = serde_json::to_string(&variables).ok();
+        db.set_session_prompt_preset(&session_id, Some(&preset_id), variables.as_deref())
+            .map_err(|e| format!("Failed to save prompt preset: {}", e))?;
     }
+    db.set_session_tags(&session_id, &organization.tags)
+        .map_err(|e| format!("Failed to set session tags: {}", e))?;
+    db.set_session_folder(&session_id, organization.folder.as_deref())
+        .map_err(|e| format!("Failed to move session to folder: {}", e))?;
+    
+    // Recarregar para devolver as mensagens com seus IDs
+    let messages = db.get_messages(&session_id)
+        .map_err(|e| format!("Failed to load messages: {}", e))?
+        .into_iter()
+        .map(|msg| Message {
+            id: msg.id,
+            role: msg.role,
+            content: msg.content,
+            metadata: None,
+        })
+        .collect();
     
-    #[cfg(not(any(target_os = "windows", target_os = "macos", target_os = "linux")))]
-    return "unknown".to_string();
+    log::info!("Sessão {} duplicada como {} ({} mensagens)", id, session_id, if copy_messages { "com" } else { "sem" });
+    
+    Ok(TemplateSession {
+        session_id,
+        title,
+        config,
+        messages,
+    })
 }
 
+/// Respostas concluídas em background ainda não vistas, por sessão
 #[command]
-fn start_system_monitor(window: Window) {
-    std::thread::spawn(move || {
-        let mut sys = System::new_all();
-        loop {
-            sys.refresh_cpu_all();
-            sys.refresh_memory();
-
-            let cpu_usage = sys.global_cpu_usage();
-            let memory_used = sys.used_memory();
-            let memory_total = sys.total_memory();
-
-            let stats = LegacySystemStats {
-                cpu_usage,
-                memory_used,
-                memory_total,
-            };
-
-            if window.emit("system-stats", stats).is_err() {
-                break; // Stop if window is closed
-            }
+fn get_unread_counts(app_handle: AppHandle) -> Result<unread::UnreadCounts, String> {
+    unread::counts(&app_handle)
+}
 
-            std::thread::sleep(Duration::from_secs(2));
-        }
-    });
+/// Estatísticas de uso locais no período (`range`: "24h", "7d", "30d", "90d", "365d" ou "all"):
+/// mensagens por dia, tokens e latência por modelo, tags mais usadas e sucesso das tasks
+#[command]
+fn get_usage_analytics(app_handle: AppHandle, range: Option<String>) -> Result<analytics::UsageAnalytics, String> {
+    analytics::collect(&app_handle, range.as_deref().unwrap_or(analytics::DEFAULT_RANGE))
 }
 
+/// Marca a sessão como lida (o frontend chama ao abrir a sessão) e atualiza o badge
 #[command]
-fn list_local_models() -> Vec<LocalModel> {
-    let output = Command::new("ollama")
-        .arg("list")
-        .output();
+fn mark_session_read(app_handle: AppHandle, id: String) -> Result<unread::UnreadCounts, String> {
+    unread::mark_read(&app_handle, &id)
+}
 
-    match output {
-        Ok(output) => {
-            let stdout = String::from_utf8_lossy(&output.stdout);
-            let mut models = Vec::new();
-            
-            // Skip header line
-            for line in stdout.lines().skip(1) {
-                let parts: Vec<&str> = line.split_whitespace().collect();
-                if parts.len() >= 4 {
-                    // NAME ID SIZE MODIFIED
-                    // Note: Modified can be "2 days ago" (multiple parts)
-                    // We'll take the first part as name, second as ID, third as size
-                    // and the rest as modified
-                    let name = parts[0].to_string();
-                    let id = parts[1].to_string();
-                    let size = parts[2].to_string();
-                    let modified_at = parts[3..].join(" ");
+/// Mensagem persistida com seus identificadores
+#[derive(serde::Serialize)]
+struct MessageDetails {
+    id: i64,
+    session_id: String,
+    role: String,
+    content: String,
+    metadata: Option<serde_json::Value>,
+    created_at: DateTime<Utc>,
+}
 
-                    models.push(LocalModel {
-                        name,
-                        id,
-                        size,
-                        modified_at,
-                    });
-                }
-            }
-            models
+impl MessageDetails {
+    fn from_db(msg: db::ChatMessage) -> Self {
+        Self {
+            id: msg.id.unwrap_or_default(),
+            session_id: msg.session_id,
+            role: msg.role,
+            content: msg.content,
+            metadata: msg.metadata.and_then(|m| serde_json::from_str(&m).ok()),
+            created_at: msg.created_at,
         }
-        Err(_) => Vec::new(),
     }
 }
 
-#[command]
-async fn delete_model(name: String) -> Result<(), String> {
-    let output = Command::new("ollama")
-        .arg("rm")
-        .arg(&name)
-        .output()
-        .map_err(|e| e.to_string())?;
-
-    if output.status.success() {
-        Ok(())
+/// Aplica um JSON merge-patch (RFC 7386): objetos são mesclados recursivamente,
+/// `null` remove a chave e qualquer outro valor substitui o existente
+fn json_merge_patch(target: &mut serde_json::Value, patch: &serde_json::Value) {
+    if let serde_json::Value::Object(patch_map) = patch {
+        if !target.is_object() {
+            *target = serde_json::Value::Object(serde_json::Map::new());
+        }
+        if let serde_json::Value::Object(target_map) = target {
+            for (key, value) in patch_map {
+                if value.is_null() {
+                    target_map.remove(key);
+                } else {
+                    json_merge_patch(
+                        target_map.entry(key.clone()).or_insert(serde_json::Value::Null),
+                        value,
+                    );
+                }
+            }
+        }
     } else {
-        Err(String::from_utf8_lossy(&output.stderr).to_string())
+        *target = patch.clone();
     }
 }
 
+/// Retorna uma mensagem pelo ID (com metadata já desserializado)
 #[command]
-fn check_if_model_installed(name: String) -> bool {
-    let output = Command::new("ollama")
-        .arg("list")
-        .output();
-
-    match output {
-        Ok(output) => {
-            let stdout = String::from_utf8_lossy(&output.stdout);
-            stdout.contains(&name)
-        }
-        Err(_) => false,
-    }
+fn get_message(app_handle: AppHandle, message_id: i64) -> Result<MessageDetails, String> {
+    let db = db::Database::new(&app_handle)
+        .map_err(|e| format!("Failed to open database: {}", e))?;
+    
+    db.get_message(message_id)
+        .map_err(|e| format!("Failed to load message: {}", e))?
+        .map(MessageDetails::from_db)
+        .ok_or_else(|| "Message not found".to_string())
 }
 
-/// Instala um modelo GGUF a partir de um arquivo local
+/// Atualiza o metadata de uma mensagem com semântica de JSON merge-patch
 #[command]
-async fn install_gguf_model(
+fn set_message_metadata(
     app_handle: AppHandle,
-    file_path: String,
-    model_name: Option<String>,
-) -> Result<String, String> {
-    use std::path::Path;
+    message_id: i64,
+    patch: serde_json::Value,
+) -> Result<MessageDetails, String> {
+    let db = db::Database::new(&app_handle)
+        .map_err(|e| format!("Failed to open database: {}", e))?;
     
-    let source_path = Path::new(&file_path);
+    let mut message = db.get_message(message_id)
+        .map_err(|e| format!("Failed to load message: {}", e))?
+        .ok_or_else(|| "Message not found".to_string())?;
     
-    // Validar que o arquivo existe
-    if !source_path.exists() {
-        return Err("Arquivo não encontrado".to_string());
-    }
+    let mut metadata = message.metadata
+        .as_deref()
+        .and_then(|m| serde_json::from_str::<serde_json::Value>(m).ok())
+        .unwrap_or(serde_json::Value::Object(serde_json::Map::new()));
     
-    // Validar extensão (mas aceitar arquivos sem extensão também)
-    let is_gguf = if let Some(ext) = source_path.extension() {
-        ext.to_string_lossy().to_lowercase() == "gguf"
-    } else {
-        // Arquivo sem extensão - verificar pelo tamanho (modelos GGUF são grandes)
-        let metadata = fs::metadata(source_path)
-            .map_err(|e| format!("Erro ao ler metadados do arquivo: {}", e))?;
-        metadata.len() >= 50 * 1024 * 1024 // Pelo menos 50MB
+    json_merge_patch(&mut metadata, &patch);
+    
+    // Metadata vazio é salvo como NULL
+    let serialized = match &metadata {
+        serde_json::Value::Null => None,
+        serde_json::Value::Object(map) if map.is_empty() => None,
+        other => Some(serde_json::to_string(other)
+            .map_err(|e| format!("Failed to serialize metadata: {}", e))?),
     };
     
-    if !is_gguf {
-        // Verificar se é um arquivo grande sem extensão (pode ser GGUF)
-        let metadata = fs::metadata(source_path)
-            .map_err(|e| format!("Erro ao ler metadados do arquivo: {}", e))?;
-        if metadata.len() < 50 * 1024 * 1024 {
-            return Err("Arquivo muito pequeno ou não é um modelo GGUF válido".to_string());
+    db.update_message_metadata(message_id, serialized.as_deref())
+        .map_err(|e| format!("Failed to update message metadata: {}", e))?;
+    
+    message.metadata = serialized;
+    Ok(MessageDetails::from_db(message))
+}
+
+/// Resultado de um truncamento de sessão (desfazível até `undo_expires_at`)
+#[derive(serde::Serialize)]
+struct SessionTruncation {
+    batch_id: String,
+    session_id: String,
+    removed_count: usize,
+    truncated_at: DateTime<Utc>,
+    undo_expires_at: DateTime<Utc>,
+}
+
+impl SessionTruncation {
+    fn from_batch(batch: db::TruncationBatch, undo_days: u32) -> Self {
+        Self {
+            undo_expires_at: batch.truncated_at + chrono::Duration::days(undo_days as i64),
+            batch_id: batch.batch_id,
+            session_id: batch.session_id,
+            removed_count: batch.message_count,
+            truncated_at: batch.truncated_at,
         }
-        // Se for grande o suficiente, aceitar mesmo sem extensão
     }
-    
-    // Validar tamanho mínimo (100MB)
-    let metadata = fs::metadata(source_path)
-        .map_err(|e| format!("Erro ao ler metadados do arquivo: {}", e))?;
-    let min_size = 100 * 1024 * 1024; // 100MB
-    if metadata.len() < min_size {
-        return Err("Arquivo muito pequeno. Modelos GGUF geralmente têm pelo menos 100MB".to_string());
+}
+
+/// Remove definitivamente truncamentos cuja janela de desfazer expirou
+fn purge_expired_truncations(db: &db::Database, undo_days: u32) {
+    let cutoff = Utc::now() - chrono::Duration::days(undo_days as i64);
+    match db.purge_truncations_before(cutoff) {
+        Ok(0) => {}
+        Ok(n) => log::info!("{} mensagem(ns) truncada(s) expirada(s) removida(s)", n),
+        Err(e) => log::warn!("Failed to purge expired truncations: {}", e),
     }
-    
-    // Determinar nome do modelo
-    let final_model_name = if let Some(name) = model_name {
-        name.trim().to_string()
-    } else {
-        // Extrair nome do arquivo sem extensão
-        source_path
-            .file_stem()
-            .and_then(|s| s.to_str())
-            .unwrap_or("model")
-            .to_string()
+}
+
+/// Reescreve as mensagens do JSON legado (se existir) a partir do SQLite,
+/// para que o fallback de leitura não ressuscite mensagens truncadas
+fn sync_legacy_session_messages(
+    app_handle: &AppHandle,
+    file_locks: &FileLockMap,
+    db: &db::Database,
+    session_id: &str,
+) -> Result<(), String> {
+    let lock = {
+        let mut locks_map = file_locks.lock()
+            .map_err(|e| format!("Failed to lock file locks map: {}", e))?;
+        locks_map.entry(session_id.to_string())
+            .or_insert_with(|| Arc::new(Mutex::new(())))
+            .clone()
     };
+    let _guard = lock.lock()
+        .map_err(|e| format!("Failed to acquire file lock for session {}: {}", session_id, e))?;
     
-    if final_model_name.is_empty() {
-        return Err("Nome do modelo não pode estar vazio".to_string());
+    let file_path = get_chats_dir(app_handle)?.join(format!("{}.json", session_id));
+    if !file_path.exists() {
+        return Ok(());
     }
     
-    // Obter diretório de modelos do Ollama
-    // Ollama armazena modelos em ~/.ollama/models (Linux/Mac) ou %USERPROFILE%\.ollama\models (Windows)
-    let models_dir = dirs::home_dir()
-        .ok_or_else(|| "Não foi possível determinar diretório home".to_string())?
-        .join(".ollama")
-        .join("models");
+    let content = fs::read_to_string(&file_path)
+        .map_err(|e| format!("Failed to read session file: {}", e))?;
+    let mut session: ChatSession = serde_json::from_str(&content)
+        .map_err(|e| format!("Failed to parse session: {}", e))?;
     
-    // Criar diretório se não existir
-    fs::create_dir_all(&models_dir)
-        .map_err(|e| format!("Erro ao criar diretório de modelos: {}", e))?;
+    session.messages = db.get_messages(session_id)
+        .map_err(|e| format!("Failed to load messages: {}", e))?
+        .into_iter()
+        .map(|msg| Message {
+            id: msg.id,
+            role: msg.role,
+            content: msg.content,
+            metadata: msg.metadata.and_then(|m| serde_json::from_str(&m).ok()),
+        })
+        .collect();
+    session.updated_at = Utc::now();
     
-    // Criar diretório para o modelo específico
-    let model_dir = models_dir.join(&final_model_name);
-    fs::create_dir_all(&model_dir)
-        .map_err(|e| format!("Erro ao criar diretório do modelo: {}", e))?;
+    let json = serde_json::to_string_pretty(&session)
+        .map_err(|e| format!("Failed to serialize session: {}", e))?;
+    let temp_path = file_path.with_extension("json.tmp");
+    fs::write(&temp_path, json)
+        .map_err(|e| format!("Failed to write temp session file: {}", e))?;
+    fs::rename(&temp_path, &file_path)
+        .map_err(|e| format!("Failed to rename temp file to session file: {}", e))?;
     
-    // Nome do arquivo de destino (usar nome do modelo + .gguf)
-    let dest_file = model_dir.join(format!("{}.gguf", final_model_name));
+    Ok(())
+}
+
+/// Volta a sessão até a mensagem informada: as mensagens seguintes vão para a
+/// shadow table e podem ser restauradas com `undo_session_truncation`
+#[command]
+fn truncate_session_after(
+    app_handle: AppHandle,
+    file_locks: State<'_, FileLockMap>,
+    message_id: i64,
+) -> Result<SessionTruncation, String> {
+    let db = db::Database::new(&app_handle)
+        .map_err(|e| format!("Failed to open database: {}", e))?;
+    let undo_days = settings::current(&app_handle).truncate_undo_days;
     
-    // Copiar arquivo
-    log::info!("Copiando arquivo GGUF de {} para {}", file_path, dest_file.display());
-    fs::copy(source_path, &dest_file)
-        .map_err(|e| format!("Erro ao copiar arquivo: {}", e))?;
+    purge_expired_truncations(&db, undo_days);
     
-    log::info!("Arquivo copiado com sucesso. Tentando registrar no Ollama...");
+    let batch = db.truncate_session_after(message_id)
+        .map_err(|e| format!("Failed to truncate session: {}", e))?
+        .ok_or_else(|| "Message not found".to_string())?;
     
-    // Tentar criar Modelfile e importar modelo no Ollama
-    // Ollama pode importar modelos GGUF usando: ollama create <name> -f <modelfile>
-    // Mas para GGUF direto, podemos usar: ollama create <name> --file <path>
-    // Ou simplesmente copiar para o diretório e o Ollama detecta automaticamente
+    if let Err(e) = sync_legacy_session_messages(&app_handle, &file_locks, &db, &batch.session_id) {
+        log::warn!("Failed to sync legacy session file after truncation: {}", e);
+    }
     
-    // Tentar criar Modelfile e registrar modelo no Ollama
-    // Ollama requer um Modelfile para criar modelos GGUF
-    let modelfile_path = model_dir.join("Modelfile");
-    let modelfile_content = format!("FROM {}\n", dest_file.display());
+    log::info!("Sessão {} truncada após mensagem {} ({} mensagens movidas)", batch.session_id, message_id, batch.message_count);
+    Ok(SessionTruncation::from_batch(batch, undo_days))
+}
+
+/// Restaura as mensagens de um truncamento ainda dentro da janela de desfazer
+#[command]
+fn undo_session_truncation(
+    app_handle: AppHandle,
+    file_locks: State<'_, FileLockMap>,
+    batch_id: String,
+) -> Result<usize, String> {
+    let db = db::Database::new(&app_handle)
+        .map_err(|e| format!("Failed to open database: {}", e))?;
     
-    // Escrever Modelfile
-    if let Err(e) = fs::write(&modelfile_path, &modelfile_content) {
-        log::warn!("Erro ao criar Modelfile: {}. Tentando método alternativo...", e);
-    }
+    purge_expired_truncations(&db, settings::current(&app_handle).truncate_undo_days);
     
-    // Tentar usar ollama create com Modelfile
-    let create_output = Command::new("ollama")
-        .arg("create")
-        .arg(&final_model_name)
-        .arg("-f")
-        .arg(&modelfile_path)
-        .output();
+    let (session_id, restored) = db.undo_truncation(&batch_id)
+        .map_err(|e| format!("Failed to undo truncation: {}", e))?
+        .ok_or_else(|| "Truncamento não encontrado ou expirado".to_string())?;
     
-    match create_output {
-        Ok(output) => {
-            if output.status.success() {
-                log::info!("Modelo {} registrado com sucesso no Ollama", final_model_name);
-                Ok(final_model_name)
-            } else {
-                let stderr = String::from_utf8_lossy(&output.stderr);
-                // Se o modelo já existe, ainda consideramos sucesso
-                if stderr.contains("already exists") || stderr.contains("model already exists") {
-                    log::info!("Modelo {} já existe no Ollama", final_model_name);
-                    Ok(final_model_name)
-                } else {
-                    // Tentar método alternativo: usar FROM diretamente
-                    log::warn!("Primeira tentativa falhou: {}. Tentando método alternativo...", stderr);
-                    
-                    // Método alternativo: criar modelo usando FROM diretamente
-                    let alt_output = Command::new("ollama")
-                        .arg("create")
-                        .arg(&final_model_name)
-                        .arg("--file")
-                        .arg(&dest_file)
-                        .output();
-                    
-                    match alt_output {
-                        Ok(alt_out) => {
-                            if alt_out.status.success() {
-                                log::info!("Modelo {} registrado com sucesso (método alternativo)", final_model_name);
-                                Ok(final_model_name)
-                            } else {
-                                let alt_stderr = String::from_utf8_lossy(&alt_out.stderr);
-                                // Se falhar, ainda retornamos sucesso pois o arquivo foi copiado
-                                log::warn!("Não foi possível registrar modelo automaticamente: {}. Arquivo copiado para: {}. Você pode registrar manualmente usando: ollama create {} -f {}", alt_stderr, dest_file.display(), final_model_name, modelfile_path.display());
-                                Ok(final_model_name)
-                            }
-                        }
-                        Err(_) => {
-                            // Se ambos falharem, ainda retornamos sucesso pois o arquivo foi copiado
-                            log::warn!("Não foi possível registrar modelo automaticamente. Arquivo copiado para: {}. Você pode registrar manualmente usando: ollama create {} -f {}", dest_file.display(), final_model_name, modelfile_path.display());
-                            Ok(final_model_name)
-                        }
-                    }
-                }
-            }
-        }
-        Err(e) => {
-            // Se ollama create falhar, ainda retornamos sucesso pois o arquivo foi copiado
-            // O usuário pode registrar manualmente depois
-            log::warn!("Não foi possível registrar modelo automaticamente: {}. Arquivo copiado para: {}. Você pode registrar manualmente usando: ollama create {} -f {}", e, dest_file.display(), final_model_name, modelfile_path.display());
-            Ok(final_model_name)
-        }
+    if let Err(e) = sync_legacy_session_messages(&app_handle, &file_locks, &db, &session_id) {
+        log::warn!("Failed to sync legacy session file after undo: {}", e);
     }
+    
+    Ok(restored)
 }
 
-// Função auxiliar para ler linha até encontrar \r ou \n (mantida para fallback)
-#[allow(dead_code)]
-fn read_line_until_delimiter<R: Read>(reader: &mut BufReader<R>, buffer: &mut Vec<u8>) -> Result<usize, std::io::Error> {
-    buffer.clear();
-    let mut byte = [0u8; 1];
-    let mut count = 0;
+/// Lista os truncamentos ainda desfazíveis de uma sessão
+#[command]
+fn list_session_truncations(app_handle: AppHandle, session_id: String) -> Result<Vec<SessionTruncation>, String> {
+    let db = db::Database::new(&app_handle)
+        .map_err(|e| format!("Failed to open database: {}", e))?;
+    let undo_days = settings::current(&app_handle).truncate_undo_days;
     
-    loop {
-        match reader.read(&mut byte)? {
-            0 => break, // EOF
-            _ => {
-                if byte[0] == b'\r' {
-                    // Se for \r, verificar se o próximo é \n e pular ambos
-                    let mut peek = [0u8; 1];
-                    if reader.read(&mut peek).unwrap_or(0) > 0 && peek[0] == b'\n' {
-                        // É \r\n, já consumimos ambos
-                    } else {
-                        // É apenas \r, já consumimos
-                    }
-                    break;
-                } else if byte[0] == b'\n' {
-                    break;
-                }
-                buffer.push(byte[0]);
-                count += 1;
+    purge_expired_truncations(&db, undo_days);
+    
+    Ok(db.list_truncations(&session_id)
+        .map_err(|e| format!("Failed to list truncations: {}", e))?
+        .into_iter()
+        .map(|batch| SessionTruncation::from_batch(batch, undo_days))
+        .collect())
+}
+
+/// Resultado de carregamento paginado de histórico
+#[derive(serde::Serialize)]
+struct PaginatedHistory {
+    messages: Vec<Message>,
+    total_count: usize,
+    has_more: bool,
+}
+
+/// Carrega histórico de chat com paginação (lazy loading)
+/// 
+/// Parâmetros:
+/// - id: ID da sessão
+/// - limit: número máximo de mensagens a retornar (default: 20)
+/// - offset: número de mensagens a pular do final (default: 0)
+#[command]
+fn load_chat_history_paginated(
+    app_handle: AppHandle,
+    id: String,
+    limit: Option<usize>,
+    offset: Option<usize>,
+) -> Result<PaginatedHistory, String> {
+    load_history_page(&app_handle, &id, limit.unwrap_or(20), offset.unwrap_or(0))
+}
+
+/// Carrega uma página do histórico e retorna `(messages, total, has_more)`.
+/// `offset` conta a partir das mensagens mais recentes.
+#[command]
+fn load_chat_history_page(
+    app_handle: AppHandle,
+    id: String,
+    limit: Option<usize>,
+    offset: Option<usize>,
+) -> Result<(Vec<Message>, usize, bool), String> {
+    let page = load_history_page(&app_handle, &id, limit.unwrap_or(20), offset.unwrap_or(0))?;
+    Ok((page.messages, page.total_count, page.has_more))
+}
+
+/// Página do histórico: SQLite primeiro, com fallback para o JSON legado lido em streaming
+fn load_history_page(app_handle: &AppHandle, id: &str, limit: usize, offset: usize) -> Result<PaginatedHistory, String> {
+    use db::Database;
+    
+    let sqlite_error = match Database::new(app_handle) {
+        Ok(db) => match db.get_messages_paginated(id, limit, offset) {
+            Ok((messages, total_count, has_more)) if total_count > 0 => {
+                // Converter ChatMessage para Message
+                let result: Vec<Message> = messages.into_iter().map(|msg| {
+                    let role = if msg.role == "user" {
+                        "user"
+                    } else if msg.role == "assistant" {
+                        "assistant"
+                    } else {
+                        "system"
+                    };
+                    
+                    let metadata_value = msg.metadata
+                        .and_then(|m| serde_json::from_str::<serde_json::Value>(&m).ok())
+                        .filter(|m| m.as_object().map(|o| !o.is_empty()).unwrap_or(false));
+                    
+                    Message {
+                        id: msg.id,
+                        role: role.to_string(),
+                        content: msg.content,
+                        metadata: metadata_value,
+                    }
+                }).collect();
+                
+                log::info!(
+                    "Loaded {} messages (offset: {}, total: {}, has_more: {}) from SQLite for session {}",
+                    result.len(), offset, total_count, has_more, id
+                );
+                
+                return Ok(PaginatedHistory {
+                    messages: result,
+                    total_count,
+                    has_more,
+                });
+            }
+            Ok(_) => {
+                log::debug!("No messages in SQLite for session {}, trying JSON fallback", id);
+                None
+            }
+            Err(e) => {
+                log::debug!("SQLite query failed for session {}: {}, trying JSON fallback", id, e);
+                Some(format!("Failed to load paginated history: {}", e))
             }
+        },
+        Err(e) => {
+            log::debug!("Failed to open database: {}, trying JSON fallback", e);
+            Some(format!("Failed to open database: {}", e))
+        }
+    };
+    
+    // Fallback: sistema legado (JSON), sem carregar o arquivo inteiro em memória
+    let empty = PaginatedHistory {
+        messages: Vec::new(),
+        total_count: 0,
+        has_more: false,
+    };
+    
+    let file_path = match get_chats_dir(app_handle) {
+        Ok(dir) => dir.join(format!("{}.json", id)),
+        Err(e) => {
+            return match sqlite_error {
+                Some(sqlite_error) => Err(format!("{} (chats dir error: {})", sqlite_error, e)),
+                None => Ok(empty),
+            };
         }
+    };
+    
+    if !file_path.exists() {
+        return match sqlite_error {
+            Some(sqlite_error) => Err(sqlite_error),
+            None => Ok(empty),
+        };
     }
     
-    Ok(count)
+    match json_history::read_page::<Message>(&file_path, limit, offset) {
+        Ok((messages, total_count, has_more)) => {
+            log::info!(
+                "Loaded {} messages (offset: {}, total: {}, has_more: {}) from JSON fallback for session {}",
+                messages.len(), offset, total_count, has_more, id
+            );
+            Ok(PaginatedHistory {
+                messages,
+                total_count,
+                has_more,
+            })
+        }
+        Err(e) => match sqlite_error {
+            Some(sqlite_error) => Err(format!("{} (JSON error: {})", sqlite_error, e)),
+            None => {
+                log::debug!("Failed to read JSON session {}: {}", id, e);
+                Ok(empty)
+            }
+        },
+    }
 }
 
-// Função auxiliar para formatar bytes em formato legível
-fn format_bytes(bytes: u64) -> Option<String> {
-    if bytes == 0 {
-        return None;
-    }
+#[command]
+fn delete_chat_session(app_handle: AppHandle, id: String) -> Result<(), String> {
+    use db::Database;
     
-    const KB: u64 = 1024;
-    const MB: u64 = KB * 1024;
-    const GB: u64 = MB * 1024;
+    let mut errors = Vec::new();
     
-    Some(if bytes >= GB {
-        format!("{:.1} GB", bytes as f64 / GB as f64)
-    } else if bytes >= MB {
-        format!("{:.1} MB", bytes as f64 / MB as f64)
-    } else if bytes >= KB {
-        format!("{:.1} KB", bytes as f64 / KB as f64)
-    } else {
-        format!("{} B", bytes)
-    })
-}
-
-// Função para parsear linha do Ollama e extrair informações (mantida para fallback)
-#[allow(dead_code)]
-fn parse_ollama_progress(line: &str) -> DownloadProgress {
-    let line_lower = line.to_lowercase();
-    let mut status = "downloading".to_string();
-    let mut percent: Option<u8> = None;
-    let mut downloaded: Option<String> = None;
-    let mut total: Option<String> = None;
-    let mut speed: Option<String> = None;
+    // 1. Deletar do sistema legado (arquivos JSON)
+    let chats_dir = get_chats_dir(&app_handle)?;
+    let file_path = chats_dir.join(format!("{}.json", id));
     
-    // Determinar status
-    if line_lower.contains("pulling manifest") || line_lower.contains("pulling") {
-        status = "pulling".to_string();
-    } else if line_lower.contains("verifying") {
-        status = "verifying".to_string();
-    } else if line_lower.contains("writing manifest") {
-        status = "writing".to_string();
-    } else if line_lower.contains("success") || line_lower.contains("complete") || line_lower.contains("pulled") {
-        status = "success".to_string();
+    if file_path.exists() {
+        if let Err(e) = fs::remove_file(&file_path) {
+            errors.push(format!("Failed to delete JSON file: {}", e));
+        } else {
+            log::info!("Deleted session JSON file: {}", id);
+        }
     }
     
-    // Extrair porcentagem: "45%" ou "45 %"
-    if let Some(caps) = regex::Regex::new(r"(\d+)\s*%").unwrap().captures(line) {
-        if let Ok(p) = caps[1].parse::<u8>() {
-            percent = Some(p);
+    // 2. Deletar do SQLite (sistema novo)
+    match Database::new(&app_handle) {
+        Ok(db) => {
+            if let Err(e) = db.delete_session(&id) {
+                errors.push(format!("Failed to delete from SQLite: {}", e));
+            } else {
+                log::info!("Deleted session from SQLite: {}", id);
+            }
+        }
+        Err(e) => {
+            errors.push(format!("Failed to open database: {}", e));
         }
     }
     
-    // Extrair tamanho baixado/total: "552 MB/1.2 GB" ou "552MB / 1.2GB"
-    if let Some(caps) = regex::Regex::new(r"(\d+(?:\.\d+)?)\s*([KMGT]?B)\s*/\s*(\d+(?:\.\d+)?)\s*([KMGT]?B)").unwrap().captures(line) {
-        downloaded = Some(format!("{} {}", &caps[1], &caps[2]));
-        total = Some(format!("{} {}", &caps[3], &caps[4]));
+    // Se ambos falharam, retornar erro
+    if !errors.is_empty() && !file_path.exists() {
+        // Se arquivo JSON não existe, verificar se pelo menos deletou do SQLite
+        match Database::new(&app_handle) {
+            Ok(db) => {
+                if db.get_session(&id).ok().flatten().is_none() {
+                    // Sessão não existe em nenhum lugar, considerar sucesso
+                    return Ok(());
+                }
+            }
+            _ => {}
+        }
     }
     
-    // Extrair velocidade: "25 MB/s" ou "25MB/s"
-    if let Some(caps) = regex::Regex::new(r"(\d+(?:\.\d+)?)\s*([KMGT]?B/s)").unwrap().captures(line) {
-        speed = Some(format!("{} {}", &caps[1], &caps[2]));
+    // Se houve erros mas pelo menos um sistema foi atualizado, logar mas não falhar
+    if !errors.is_empty() {
+        log::warn!("Some errors during deletion of session {}: {:?}", id, errors);
     }
     
-    DownloadProgress {
-        status,
-        percent,
-        downloaded,
-        total,
-        speed,
-        raw: line.to_string(),
+    Ok(())
+}
+
+#[command]
+fn get_system_specs() -> SystemSpecs {
+    let mut sys = System::new_all();
+    sys.refresh_all();
+
+    // Detectar todas as GPUs
+    let gpus = system_monitor::detect_all_gpus();
+
+    SystemSpecs {
+        total_memory: sys.total_memory(),
+        cpu_count: sys.cpus().len(),
+        os_name: System::name().unwrap_or("Unknown".to_string()),
+        gpus,
     }
 }
 
+/// Retorna o sistema operacional atual: 'windows', 'mac', ou 'linux'
 #[command]
-async fn pull_model(window: Window, name: String) -> Result<(), String> {
-    let client = reqwest::Client::new();
-    
-    // Fazer requisição POST para API do Ollama com streaming
-    let response = client
-        .post("http://localhost:11434/api/pull")
-        .json(&serde_json::json!({ "name": name, "stream": true }))
-        .send()
-        .await
-        .map_err(|e| format!("Failed to connect to Ollama API: {}", e))?;
+fn get_operating_system() -> String {
+    #[cfg(target_os = "windows")]
+    {
+        return "windows".to_string();
+    }
     
-    if !response.status().is_success() {
-        return Err(format!("Ollama API returned error: {}", response.status()));
+    #[cfg(target_os = "macos")]
+    {
+        return "mac".to_string();
     }
     
-    let mut stream = response.bytes_stream();
-    let mut buffer = String::new();
-    let mut last_completed: u64 = 0;
-    let mut last_time = Instant::now();
+    #[cfg(target_os = "linux")]
+    {
+        return "linux".to_string();
+    }
     
-    // Processar stream NDJSON (Newline Delimited JSON)
-    while let Some(chunk_result) = stream.next().await {
-        let chunk = chunk_result.map_err(|e| format!("Stream error: {}", e))?;
-        let chunk_str = String::from_utf8_lossy(&chunk);
-        buffer.push_str(&chunk_str);
-        
-        // Processar linhas completas (separadas por \n)
-        while let Some(pos) = buffer.find('\n') {
-            let line = buffer[..pos].trim().to_string();
-            buffer = buffer[pos + 1..].to_string();
+    #[cfg(not(any(target_os = "windows", target_os = "macos", target_os = "linux")))]
+    return "unknown".to_string();
+}
+
+/// Compatibilidade com o hook antigo: liga o monitor com o intervalo padrão e
+/// também emite `system-stats`. Prefira `configure_system_monitor`.
+#[command]
+fn start_system_monitor(app_handle: AppHandle) {
+    // Já ativo: preserva as métricas atuais e apenas liga o evento legado
+    let metrics = (!system_monitor::monitor_config().enabled)
+        .then(|| system_monitor::MonitorConfig::default().enabled_metrics);
+    system_monitor::configure_monitor(&app_handle, None, metrics, true);
+}
+
+#[command]
+fn list_local_models() -> Vec<LocalModel> {
+    let output = Command::new("ollama")
+        .arg("list")
+        .output();
+
+    match output {
+        Ok(output) => {
+            let stdout = String::from_utf8_lossy(&output.stdout);
+            let mut models = Vec::new();
             
-            if line.is_empty() {
-                continue;
-            }
-            
-            // Tentar deserializar como PullProgress
-            match serde_json::from_str::<PullProgress>(&line) {
-                Ok(json_progress) => {
-                    // Calcular porcentagem se tiver total/completed
-                    let percent = if json_progress.total > 0 {
-                        Some(((json_progress.completed as f64 / json_progress.total as f64) * 100.0) as u8)
-                    } else {
-                        None
-                    };
-                    
-                    // Calcular velocidade
-                    let now = Instant::now();
-                    let delta_time = now.duration_since(last_time).as_secs_f64();
-                    let speed = if delta_time > 0.0 && json_progress.completed > last_completed {
-                        let delta_bytes = json_progress.completed - last_completed;
-                        let bytes_per_sec = delta_bytes as f64 / delta_time;
-                        Some(format_speed(bytes_per_sec))
-                    } else {
-                        None
-                    };
-                    
-                    last_completed = json_progress.completed;
-                    last_time = now;
-                    
-                    // Criar DownloadProgress estruturado
-                    let progress = DownloadProgress {
-                        status: json_progress.status.clone(),
-                        percent,
-                        downloaded: format_bytes(json_progress.completed),
-                        total: format_bytes(json_progress.total),
-                        speed,
-                        raw: line.clone(),
-                    };
-                    
-                    // Emitir evento para frontend
-                    if let Ok(json) = serde_json::to_string(&progress) {
-                        window.emit("download-progress", json).unwrap_or(());
-                    }
-                    
-                    // Se status for "success", finalizar
-                    if json_progress.status == "success" {
-                        let success_progress = DownloadProgress {
-                            status: "success".to_string(),
-                            percent: Some(100),
-                            downloaded: format_bytes(json_progress.completed),
-                            total: format_bytes(json_progress.total),
-                            speed: None,
-                            raw: "success".to_string(),
-                        };
-                        if let Ok(json) = serde_json::to_string(&success_progress) {
-                            window.emit("download-progress", json).unwrap_or(());
-                        }
-                        return Ok(());
-                    }
-                }
-                Err(_) => {
-                    // Se não conseguir parsear como JSON, tratar como linha raw (fallback)
-                    let progress = DownloadProgress {
-                        status: "downloading".to_string(),
-                        percent: None,
-                        downloaded: None,
-                        total: None,
-                        speed: None,
-                        raw: line,
-                    };
-                    if let Ok(json) = serde_json::to_string(&progress) {
-                        window.emit("download-progress", json).unwrap_or(());
-                    }
-                }
+            // Skip header line
+            for line in stdout.lines().skip(1) {
+                let parts: Vec<&str> = line.split_whitespace().collect();
+                if parts.len() >= 4 {
+                    // NAME ID SIZE MODIFIED
+                    // Note: Modified can be "2 days ago" (multiple parts)
+                    // We'll take the first part as name, second as ID, third as size
+                    // and the rest as modified
+                    let name = parts[0].to_string();
+                    let id = parts[1].to_string();
+                    let size = parts[2].to_string();
+                    let modified_at = parts[3..].join(" ");
+
+                    models.push(LocalModel {
+                        name,
+                        id,
+                        size,
+                        modified_at,
+                    });
+                }
             }
+            models
         }
+        Err(_) => Vec::new(),
     }
-    
-    // Se chegou aqui, o stream terminou sem "success" explícito
-    // Emitir sucesso final
-    let success_progress = DownloadProgress {
-        status: "success".to_string(),
-        percent: Some(100),
-        downloaded: format_bytes(last_completed),
-        total: None,
-        speed: None,
-        raw: "success".to_string(),
-    };
-    if let Ok(json) = serde_json::to_string(&success_progress) {
-        window.emit("download-progress", json).unwrap_or(());
-    }
-    
-    Ok(())
 }
 
-// Função auxiliar para formatar velocidade
-fn format_speed(bytes_per_sec: f64) -> String {
-    const KB: f64 = 1024.0;
-    const MB: f64 = KB * 1024.0;
-    const GB: f64 = MB * 1024.0;
+#[command]
+async fn delete_model(app_handle: AppHandle, name: String, force: Option<bool>) -> Result<(), String> {
+    if settings::current(&app_handle).is_model_pinned(&name) && !force.unwrap_or(false) {
+        return Err(format!("O modelo {} está fixado. Desafixe-o ou confirme a remoção forçada.", name));
+    }
     
-    if bytes_per_sec >= GB {
-        format!("{:.1} GB/s", bytes_per_sec / GB)
-    } else if bytes_per_sec >= MB {
-        format!("{:.1} MB/s", bytes_per_sec / MB)
-    } else if bytes_per_sec >= KB {
-        format!("{:.1} KB/s", bytes_per_sec / KB)
+    let output = Command::new("ollama")
+        .arg("rm")
+        .arg(&name)
+        .output()
+        .map_err(|e| e.to_string())?;
+
+    if output.status.success() {
+        Ok(())
     } else {
-        format!("{:.0} B/s", bytes_per_sec)
+        Err(String::from_utf8_lossy(&output.stderr).to_string())
     }
 }
 
-#[command]
-fn check_ollama_installed() -> bool {
-    match Command::new("ollama").arg("--version").output() {
-        Ok(output) => output.status.success(),
-        Err(_) => false,
+/// Valida o nome de destino de um modelo (mesmas regras do diálogo de download)
+fn validate_model_name(name: &str) -> Result<String, String> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err("Nome do modelo não pode estar vazio".to_string());
     }
-}
-
-#[command]
-async fn check_ollama_running() -> bool {
-    match reqwest::get("http://localhost:11434").await {
-        Ok(resp) => resp.status().is_success(),
-        Err(_) => false,
+    if !name.chars().all(|c| c.is_ascii_alphanumeric() || "._:/-".contains(c)) {
+        return Err(format!("Nome do modelo contém caracteres inválidos: {}", name));
+    }
+    if name.starts_with(&['/', ':', '-'][..]) || name.ends_with(&['/', ':', '-'][..]) {
+        return Err("Nome do modelo não pode começar ou terminar com /, : ou -".to_string());
     }
+    if storage_usage::manifest_relative_path(name).is_none() || name.matches(':').count() > 1 {
+        return Err(format!("Nome de modelo inválido: {}", name));
+    }
+    Ok(settings::normalize_model_name(name))
 }
 
-/// Verificação completa do Ollama: instalação e execução
-#[derive(serde::Serialize)]
-struct OllamaCheckResult {
-    installed: bool,
-    running: bool,
-    status: String, // "not_installed" | "installed_stopped" | "running"
+fn emit_model_copy_progress(app_handle: &AppHandle, src: &str, dst: &str, stage: &str) {
+    let _ = app_handle.emit("model-copy-progress", serde_json::json!({
+        "src": src,
+        "dst": dst,
+        "stage": stage,
+    }));
 }
 
-/// Inicia o Ollama automaticamente se estiver instalado mas não estiver rodando
-#[command]
-async fn auto_start_ollama() -> Result<bool, String> {
-    // Verificar se está instalado
-    let installed = check_ollama_installed();
-    if !installed {
-        log::info!("Ollama não está instalado, pulando inicialização automática");
-        return Ok(false);
+/// `ollama cp` com validação do destino e checagem de colisão; retorna o nome normalizado do destino
+fn copy_model_internal(app_handle: &AppHandle, src: &str, dst: &str) -> Result<String, String> {
+    let src = settings::normalize_model_name(src);
+    let dst = validate_model_name(dst)?;
+    if src.eq_ignore_ascii_case(&dst) {
+        return Err("Origem e destino são o mesmo modelo".to_string());
     }
     
-    // Verificar se já está rodando
-    let running = check_ollama_running().await;
-    if running {
-        log::info!("Ollama já está rodando");
-        return Ok(true);
+    let installed: Vec<String> = list_local_models()
+        .into_iter()
+        .map(|model| settings::normalize_model_name(&model.name))
+        .collect();
+    if !installed.iter().any(|name| name.eq_ignore_ascii_case(&src)) {
+        return Err(format!("Modelo não encontrado: {}", src));
+    }
+    if installed.iter().any(|name| name.eq_ignore_ascii_case(&dst)) {
+        return Err(format!("Já existe um modelo chamado {}", dst));
     }
     
-    // Tentar iniciar
-    log::info!("Iniciando Ollama automaticamente...");
-    match start_ollama_server() {
-        Ok(_) => {
-            // Aguardar um pouco para o servidor iniciar
-            tokio::time::sleep(tokio::time::Duration::from_secs(2)).await;
-            
-            // Verificar se iniciou com sucesso
-            let is_running = check_ollama_running().await;
-            if is_running {
-                log::info!("Ollama iniciado com sucesso");
-                Ok(true)
-            } else {
-                log::warn!("Ollama foi iniciado mas ainda não está respondendo");
-                Ok(false)
-            }
-        }
-        Err(e) => {
-            log::error!("Falha ao iniciar Ollama automaticamente: {}", e);
-            Err(e)
-        }
+    emit_model_copy_progress(app_handle, &src, &dst, "copying");
+    let output = Command::new("ollama")
+        .arg("cp")
+        .arg(&src)
+        .arg(&dst)
+        .output()
+        .map_err(|e| format!("Failed to run ollama cp: {}", e))?;
+    if !output.status.success() {
+        emit_model_copy_progress(app_handle, &src, &dst, "failed");
+        return Err(String::from_utf8_lossy(&output.stderr).trim().to_string());
     }
+    Ok(dst)
 }
 
-#[command]
-async fn check_ollama_full() -> Result<OllamaCheckResult, String> {
-    let installed = check_ollama_installed();
-    
-    if !installed {
-        return Ok(OllamaCheckResult {
-            installed: false,
-            running: false,
-            status: "not_installed".to_string(),
-        });
+/// Replica (ou move, ao renomear) as configurações por modelo: contexto, limite de taxa e fixação
+fn carry_model_settings(
+    app_handle: &AppHandle,
+    state: &SettingsState,
+    src: &str,
+    dst: &str,
+    remove_src: bool,
+) -> Result<(), String> {
+    let current = settings::current(app_handle);
+    let mut patch = serde_json::Map::new();
+    
+    let src_key = current.model_context_overrides.keys()
+        .find(|key| settings::normalize_model_name(key) == src)
+        .cloned();
+    if let Some(key) = src_key {
+        let mut overrides = serde_json::Map::new();
+        overrides.insert(dst.to_string(), serde_json::json!(current.model_context_overrides[&key]));
+        if remove_src {
+            overrides.insert(key, serde_json::Value::Null);
+        }
+        patch.insert("model_context_overrides".to_string(), serde_json::Value::Object(overrides));
     }
     
-    let running = check_ollama_running().await;
+    let src_key = current.model_rate_limits.keys()
+        .find(|key| settings::normalize_model_name(key) == src)
+        .cloned();
+    if let Some(key) = src_key {
+        let mut limits = serde_json::Map::new();
+        let limit = serde_json::to_value(&current.model_rate_limits[&key])
+            .map_err(|e| format!("Failed to serialize rate limit: {}", e))?;
+        limits.insert(dst.to_string(), limit);
+        if remove_src {
+            limits.insert(key, serde_json::Value::Null);
+        }
+        patch.insert("model_rate_limits".to_string(), serde_json::Value::Object(limits));
+    }
     
-    if !running {
-        return Ok(OllamaCheckResult {
-            installed: true,
-            running: false,
-            status: "installed_stopped".to_string(),
-        });
+    // A fixação acompanha só a renomeação (a cópia é um snapshot descartável)
+    if remove_src && current.is_model_pinned(src) {
+        let mut pinned: Vec<String> = current.pinned_models
+            .into_iter()
+            .filter(|model| settings::normalize_model_name(model) != src)
+            .collect();
+        pinned.push(dst.to_string());
+        pinned.sort();
+        patch.insert("pinned_models".to_string(), serde_json::json!(pinned));
     }
     
-    Ok(OllamaCheckResult {
-        installed: true,
-        running: true,
-        status: "running".to_string(),
-    })
+    if patch.is_empty() {
+        return Ok(());
+    }
+    apply_settings_patch(app_handle, state, &serde_json::Value::Object(patch)).map(|_| ())
 }
 
+/// Duplica um modelo (`ollama cp`), por exemplo antes de editar os parâmetros do Modelfile.
+/// Emite `model-copy-progress` e retorna o nome do novo modelo.
 #[command]
-fn start_ollama_server() -> Result<(), String> {
-    let mut cmd = Command::new("ollama");
-    cmd.arg("serve");
-
-    #[cfg(target_os = "windows")]
-    {
-        use std::os::windows::process::CommandExt;
-        const CREATE_NO_WINDOW: u32 = 0x08000000;
-        cmd.creation_flags(CREATE_NO_WINDOW);
-    }
-
-    // Spawn detached
-    cmd.spawn()
-        .map_err(|e| format!("Failed to start ollama: {}", e))?;
-        
-    Ok(())
+async fn copy_model(
+    app_handle: AppHandle,
+    state: State<'_, SettingsState>,
+    src: String,
+    dst: String,
+) -> Result<String, String> {
+    let src = settings::normalize_model_name(&src);
+    let dst = copy_model_internal(&app_handle, &src, &dst)?;
+    carry_model_settings(&app_handle, &state, &src, &dst, false)?;
+    emit_model_copy_progress(&app_handle, &src, &dst, "done");
+    Ok(dst)
 }
 
-// MCP Configuration Commands
+/// Renomeia um modelo: copia para o novo nome e remove o original.
+/// Modelos fixados são renomeados normalmente (a fixação passa para o novo nome).
 #[command]
-fn load_mcp_config(app_handle: AppHandle) -> Result<McpConfig, String> {
-    let config_path = get_mcp_config_path(&app_handle)?;
+async fn rename_model(
+    app_handle: AppHandle,
+    state: State<'_, SettingsState>,
+    src: String,
+    dst: String,
+) -> Result<String, String> {
+    let src = settings::normalize_model_name(&src);
+    let dst = copy_model_internal(&app_handle, &src, &dst)?;
     
-    // If file doesn't exist, return empty config
-    if !config_path.exists() {
-        return Ok(McpConfig {
-            mcp_servers: HashMap::new(),
-        });
+    emit_model_copy_progress(&app_handle, &src, &dst, "removing");
+    let output = Command::new("ollama")
+        .arg("rm")
+        .arg(&src)
+        .output()
+        .map_err(|e| format!("Failed to run ollama rm: {}", e))?;
+    if !output.status.success() {
+        // A cópia fica: o usuário não perde o modelo, só acaba com dois nomes
+        emit_model_copy_progress(&app_handle, &src, &dst, "failed");
+        return Err(format!(
+            "Modelo copiado para {}, mas não foi possível remover {}: {}",
+            dst, src, String::from_utf8_lossy(&output.stderr).trim()
+        ));
     }
     
-    let content = fs::read_to_string(&config_path)
-        .map_err(|e| format!("Failed to read MCP config: {}", e))?;
-    
-    let config: McpConfig = serde_json::from_str(&content)
-        .map_err(|e| format!("Failed to parse MCP config: {}", e))?;
-    
-    Ok(config)
+    carry_model_settings(&app_handle, &state, &src, &dst, true)?;
+    emit_model_copy_progress(&app_handle, &src, &dst, "done");
+    Ok(dst)
 }
 
+/// Fixa (ou desafixa) um modelo para protegê-lo de remoções acidentais
 #[command]
-fn save_mcp_config(app_handle: AppHandle, config: McpConfig) -> Result<(), String> {
-    let config_path = get_mcp_config_path(&app_handle)?;
-    
-    // Ensure parent directory exists
-    if let Some(parent) = config_path.parent() {
-        if !parent.exists() {
-            fs::create_dir_all(parent)
-                .map_err(|e| format!("Failed to create config directory: {}", e))?;
-        }
+fn set_model_pinned(
+    app_handle: AppHandle,
+    state: State<'_, SettingsState>,
+    name: String,
+    pinned: bool,
+) -> Result<AppSettings, String> {
+    if name.trim().is_empty() {
+        return Err("Nome do modelo não pode estar vazio".to_string());
     }
     
-    let json = serde_json::to_string_pretty(&config)
-        .map_err(|e| format!("Failed to serialize MCP config: {}", e))?;
-    
-    fs::write(&config_path, json)
-        .map_err(|e| format!("Failed to write MCP config: {}", e))?;
+    let mut pinned_models = settings::current(&app_handle).pinned_models;
+    let normalized = settings::normalize_model_name(&name);
+    pinned_models.retain(|model| settings::normalize_model_name(model) != normalized);
+    if pinned {
+        pinned_models.push(normalized);
+        pinned_models.sort();
+    }
     
-    Ok(())
+    apply_settings_patch(&app_handle, &state, &serde_json::json!({ "pinned_models": pinned_models }))
 }
 
 #[command]
-fn get_mcp_config_path_command(app_handle: AppHandle) -> Result<String, String> {
-    let path = get_mcp_config_path(&app_handle)?;
-    Ok(path.to_string_lossy().to_string())
+fn check_if_model_installed(name: String) -> bool {
+    let output = Command::new("ollama")
+        .arg("list")
+        .output();
+
+    match output {
+        Ok(output) => {
+            let stdout = String::from_utf8_lossy(&output.stdout);
+            stdout.contains(&name)
+        }
+        Err(_) => false,
+    }
 }
 
-// MCP Process Management Commands
-#[command]
-fn start_mcp_server(
-    processes: State<'_, McpProcessMap>,
-    name: String,
-    config: McpServerConfig,
-) -> Result<u32, String> {
-    let mut processes_map = processes.lock()
-        .map_err(|e| format!("Failed to lock processes map: {}", e))?;
+/// Resultado da importação de um GGUF
+#[derive(serde::Serialize)]
+struct GgufImportResult {
+    model_name: String,
+    metadata: gguf::GgufMetadata,
+    /// Digest sha256 do blob no Ollama
+    digest: String,
+}
+
+/// Lê os metadados de um arquivo GGUF (arquitetura, parâmetros, quantização, contexto)
+#[command]
+async fn inspect_gguf_file(file_path: String) -> Result<gguf::GgufMetadata, String> {
+    let path = paths::from_user_input(&file_path);
+    tokio::task::spawn_blocking(move || gguf::read_metadata(&path))
+        .await
+        .map_err(|e| format!("Failed to read GGUF metadata: {}", e))?
+}
+
+/// Instala um modelo GGUF a partir de um arquivo local.
+/// `import_id` permite ouvir `gguf-import-progress` e cancelar com `cancel_job` antes do retorno.
+#[command]
+async fn install_gguf_model(
+    app_handle: AppHandle,
+    file_path: String,
+    model_name: Option<String>,
+    import_id: Option<String>,
+) -> Result<GgufImportResult, String> {
+    let source_path = paths::from_user_input(&file_path);
+    let source_path = source_path.as_path();
     
-    // Kill existing process if running
-    if let Some(mut handle) = processes_map.remove(&name) {
-        let _ = handle.child.kill();
-        let _ = handle.child.wait();
+    // Validar que o arquivo existe
+    if !source_path.exists() {
+        return Err("Arquivo não encontrado".to_string());
     }
     
-    // Check if command exists before attempting to spawn
-    // On Windows, we need to check both with and without .exe extension
-    let mut command_exists = {
-        let check = Command::new(&config.command)
-            .arg("--version")
-            .stdout(Stdio::null())
-            .stderr(Stdio::null())
-            .output();
-        
-        #[cfg(target_os = "windows")]
-        {
-            if check.is_err() {
-                // Try with .exe extension on Windows
-                Command::new(format!("{}.exe", config.command))
-                    .arg("--version")
-                    .stdout(Stdio::null())
-                    .stderr(Stdio::null())
-                    .output()
-                    .is_ok()
-            } else {
-                true
-            }
-        }
-        
-        #[cfg(not(target_os = "windows"))]
-        {
-            check.is_ok()
-        }
-    };
-    
-    // Build command - try to use full path if found, otherwise use command as-is
-    // On Windows, we may need to check common Node.js installation paths
-    let mut command_path = config.command.clone();
+    // Validar o cabeçalho GGUF antes de importar (aceita arquivos sem extensão)
+    let metadata = inspect_gguf_file(file_path.clone()).await?;
+    log::info!(
+        "GGUF v{}: arquitetura {:?}, {} parâmetros, quantização {:?}, contexto {:?}",
+        metadata.version, metadata.architecture, metadata.parameter_count, metadata.quantization, metadata.context_length
+    );
     
-    #[cfg(target_os = "windows")]
-    {
-        // If command is npx and not found in PATH, try common Node.js locations
-        if config.command == "npx" && !command_exists {
-            let program_files = std::env::var("ProgramFiles").unwrap_or_else(|_| "C:\\Program Files".to_string());
-            let program_files_x86 = std::env::var("ProgramFiles(x86)").unwrap_or_else(|_| "C:\\Program Files (x86)".to_string());
-            
-            let common_paths: Vec<String> = vec![
-                format!("{}\\nodejs\\npx.cmd", program_files),
-                format!("{}\\nodejs\\npx.cmd", program_files_x86),
-                r"C:\Program Files\nodejs\npx.cmd".to_string(),
-                r"C:\Program Files (x86)\nodejs\npx.cmd".to_string(),
-            ];
-            
-            for path in common_paths {
-                if std::path::Path::new(&path).exists() {
-                    command_path = path;
-                    command_exists = true; // Mark as found
-                    break;
-                }
-            }
-        }
-    }
+    // Determinar nome do modelo
+    let final_model_name = if let Some(name) = model_name {
+        name.trim().to_string()
+    } else {
+        // Nome do arquivo sem extensão, sem acentos/espaços (o Ollama rejeita esses nomes)
+        dropped_files::suggested_model_name(source_path)
+    };
     
-    if !command_exists {
-        // Command not found - provide helpful error message
-        return Err(format!(
-            "Comando '{}' não encontrado no PATH. Verifique se está instalado e acessível. {}",
-            config.command,
-            if config.command == "npx" {
-                "O Node.js e npm precisam estar instalados. Instale de https://nodejs.org/ e reinicie o aplicativo após a instalação."
-            } else if config.command == "uvx" {
-                "O uv (Python package manager) precisa estar instalado. Instale com: pip install uv"
-            } else {
-                "Certifique-se de que o comando está disponível no PATH do sistema."
-            }
-        ));
+    if final_model_name.is_empty() {
+        return Err("Nome do modelo não pode estar vazio".to_string());
     }
     
-    let mut cmd = Command::new(&command_path);
-    cmd.args(&config.args);
-    cmd.stdin(Stdio::piped());
-    cmd.stdout(Stdio::piped());
-    cmd.stderr(Stdio::piped());
+    // Envio pela API de blobs do Ollama (progresso em `gguf-import-progress`, cancelável via `cancel_job`)
+    let client = ollama_client::OllamaClient::new(Some(settings::current(&app_handle).ollama_url));
+    client.check_connection().await?;
     
-    // Set environment variables if provided
-    if let Some(env_vars) = &config.env {
-        for (key, value) in env_vars {
-            cmd.env(key, value);
-        }
-    }
+    let job = jobs::start_with_id(&app_handle, "gguf_import", import_id);
+    job.set_total(3);
+    let result = gguf_import::import(&app_handle, &job, &client, source_path, &final_model_name).await;
+    job.finish(result.as_ref().map(|_| ()).map_err(|e| e.clone()));
+    let digest = result?;
     
-    #[cfg(target_os = "windows")]
-    {
-        use std::os::windows::process::CommandExt;
-        const CREATE_NO_WINDOW: u32 = 0x08000000;
-        cmd.creation_flags(CREATE_NO_WINDOW);
-    }
+    log::info!("Modelo {} importado no Ollama ({})", final_model_name, digest);
+    Ok(GgufImportResult { model_name: final_model_name, metadata, digest })
+}
+
+// Função auxiliar para ler linha até encontrar \r ou \n (mantida para fallback)
+#[allow(dead_code)]
+fn read_line_until_delimiter<R: Read>(reader: &mut BufReader<R>, buffer: &mut Vec<u8>) -> Result<usize, std::io::Error> {
+    buffer.clear();
+    let mut byte = [0u8; 1];
+    let mut count = 0;
     
-    // Spawn process
-    let child = cmd.spawn()
-        .map_err(|e| {
-            let error_msg = e.to_string();
-            // Provide more context for common errors
-            if error_msg.contains("program not found") || 
-               error_msg.contains("No such file") || 
-               error_msg.contains("The system cannot find the file") ||
-               error_msg.contains("not found") {
-                format!(
-                    "Comando '{}' não encontrado. Verifique se está instalado e no PATH do sistema. {}",
-                    config.command,
-                    if config.command == "npx" {
-                        "Instale Node.js de https://nodejs.org/ e reinicie o aplicativo após a instalação."
-                    } else if config.command == "uvx" {
-                        "Instale uv com: pip install uv"
+    loop {
+        match reader.read(&mut byte)? {
+            0 => break, // EOF
+            _ => {
+                if byte[0] == b'\r' {
+                    // Se for \r, verificar se o próximo é \n e pular ambos
+                    let mut peek = [0u8; 1];
+                    if reader.read(&mut peek).unwrap_or(0) > 0 && peek[0] == b'\n' {
+                        // É \r\n, já consumimos ambos
                     } else {
-                        "Certifique-se de que o comando está disponível no PATH."
+                        // É apenas \r, já consumimos
                     }
-                )
-            } else {
-                format!("Erro ao iniciar servidor '{}': {}", name, error_msg)
+                    break;
+                } else if byte[0] == b'\n' {
+                    break;
+                }
+                buffer.push(byte[0]);
+                count += 1;
             }
-        })?;
-    
-    let pid = child.id();
-    
-    // Create process handle with request ID counter
-    let handle = McpProcessHandle {
-        child,
-        request_id: Arc::new(Mutex::new(0)),
-    };
-    
-    // Store in map
-    processes_map.insert(name, handle);
+        }
+    }
     
-    Ok(pid)
+    Ok(count)
 }
 
-#[command]
-fn stop_mcp_server(
-    processes: State<'_, McpProcessMap>,
-    name: String,
-) -> Result<(), String> {
-    let mut processes_map = processes.lock()
-        .map_err(|e| format!("Failed to lock processes map: {}", e))?;
+// Função auxiliar para formatar bytes em formato legível
+fn format_bytes(bytes: u64) -> Option<String> {
+    if bytes == 0 {
+        return None;
+    }
     
-    if let Some(mut handle) = processes_map.remove(&name) {
-        handle.child.kill()
-            .map_err(|e| format!("Failed to kill process '{}': {}", name, e))?;
-        let _ = handle.child.wait();
-        Ok(())
+    const KB: u64 = 1024;
+    const MB: u64 = KB * 1024;
+    const GB: u64 = MB * 1024;
+    
+    Some(if bytes >= GB {
+        format!("{:.1} GB", bytes as f64 / GB as f64)
+    } else if bytes >= MB {
+        format!("{:.1} MB", bytes as f64 / MB as f64)
+    } else if bytes >= KB {
+        format!("{:.1} KB", bytes as f64 / KB as f64)
     } else {
-        Err(format!("MCP server '{}' not found", name))
-    }
+        format!("{} B", bytes)
+    })
 }
 
-#[command]
-fn restart_mcp_server(
-    processes: State<'_, McpProcessMap>,
-    app_handle: AppHandle,
-    name: String,
-) -> Result<u32, String> {
-    // Load config
-    let config = load_mcp_config(app_handle)?;
+// Função para parsear linha do Ollama e extrair informações (mantida para fallback)
+#[allow(dead_code)]
+fn parse_ollama_progress(line: &str) -> DownloadProgress {
+    let line_lower = line.to_lowercase();
+    let mut status = "downloading".to_string();
+    let mut percent: Option<u8> = None;
+    let mut downloaded: Option<String> = None;
+    let mut total: Option<String> = None;
+    let mut speed: Option<String> = None;
     
-    // Find server config
-    let server_config = config.mcp_servers.get(&name)
-        .ok_or_else(|| format!("MCP server '{}' not found in config", name))?
-        .clone();
+    // Determinar status
+    if line_lower.contains("pulling manifest") || line_lower.contains("pulling") {
+        status = "pulling".to_string();
+    } else if line_lower.contains("verifying") {
+        status = "verifying".to_string();
+    } else if line_lower.contains("writing manifest") {
+        status = "writing".to_string();
+    } else if line_lower.contains("success") || line_lower.contains("complete") || line_lower.contains("pulled") {
+        status = "success".to_string();
+    }
     
-    // Stop if running
-    {
-        let mut processes_map = processes.lock()
-            .map_err(|e| format!("Failed to lock processes map: {}", e))?;
-        if let Some(mut handle) = processes_map.remove(&name) {
-            let _ = handle.child.kill();
-            let _ = handle.child.wait();
+    // Extrair porcentagem: "45%" ou "45 %"
+    if let Some(caps) = regex::Regex::new(r"(\d+)\s*%").unwrap().captures(line) {
+        if let Ok(p) = caps[1].parse::<u8>() {
+            percent = Some(p);
         }
     }
     
-    // Start again
-    let mut processes_map = processes.lock()
-        .map_err(|e| format!("Failed to lock processes map: {}", e))?;
-    
+    // Extrair tamanho baixado/total: "552 MB/1.2 GB" ou "552MB / 1.2GB"
+    if let Some(caps) = regex::Regex::new(r"(\d+(?:\.\d+)?)\s*([KMGT]?B)\s*/\s*(\d+(?:\.\d+)?)\s*([KMGT]?B)").unwrap().captures(line) {
+        downloaded = Some(format!("{} {}", &caps[1], &caps[2]));
+        total = Some(format!("{} {}", &caps[3], &caps[4]));
+    }
+    
+    // Extrair velocidade: "25 MB/s" ou "25MB/s"
+    if let Some(caps) = regex::Regex::new(r"(\d+(?:\.\d+)?)\s*([KMGT]?B/s)").unwrap().captures(line) {
+        speed = Some(format!("{} {}", &caps[1], &caps[2]));
+    }
+    
+    DownloadProgress {
+        status,
+        percent,
+        downloaded,
+        total,
+        speed,
+        raw: line.to_string(),
+    }
+}
+
+#[command]
+async fn pull_model(window: Window, name: String) -> Result<(), String> {
+    let client = reqwest::Client::new();
+    let base_url = settings::current(window.app_handle()).ollama_url;
+    
+    // Fazer requisição POST para API do Ollama com streaming
+    let response = client
+        .post(format!("{}/api/pull", base_url.trim_end_matches('/')))
+        .json(&serde_json::json!({ "name": name, "stream": true }))
+        .send()
+        .await
+        .map_err(|e| format!("Failed to connect to Ollama API: {}", e))?;
+    
+    if !response.status().is_success() {
+        return Err(format!("Ollama API returned error: {}", response.status()));
+    }
+    
+    let mut stream = response.bytes_stream();
+    let mut buffer = String::new();
+    let mut last_completed: u64 = 0;
+    let mut last_time = Instant::now();
+    
+    // Processar stream NDJSON (Newline Delimited JSON)
+    while let Some(chunk_result) = stream.next().await {
+        let chunk = chunk_result.map_err(|e| format!("Stream error: {}", e))?;
+        let chunk_str = String::from_utf8_lossy(&chunk);
+        buffer.push_str(&chunk_str);
+        
+        // Processar linhas completas (separadas por \n)
+        while let Some(pos) = buffer.find('\n') {
+            let line = buffer[..pos].trim().to_string();
+            buffer = buffer[pos + 1..].to_string();
+            
+            if line.is_empty() {
+                continue;
+            }
+            
+            // Tentar deserializar como PullProgress
+            match serde_json::from_str::<PullProgress>(&line) {
+                Ok(json_progress) => {
+                    // Calcular porcentagem se tiver total/completed
+                    let percent = if json_progress.total > 0 {
+                        Some(((json_progress.completed as f64 / json_progress.total as f64) * 100.0) as u8)
+                    } else {
+                        None
+                    };
+                    
+                    // Calcular velocidade
+                    let now = Instant::now();
+                    let delta_time = now.duration_since(last_time).as_secs_f64();
+                    let speed = if delta_time > 0.0 && json_progress.completed > last_completed {
+                        let delta_bytes = json_progress.completed - last_completed;
+                        let bytes_per_sec = delta_bytes as f64 / delta_time;
+                        Some(format_speed(bytes_per_sec))
+                    } else {
+                        None
+                    };
+                    
+                    last_completed = json_progress.completed;
+                    last_time = now;
+                    
+                    // Criar DownloadProgress estruturado
+                    let progress = DownloadProgress {
+                        status: json_progress.status.clone(),
+                        percent,
+                        downloaded: format_bytes(json_progress.completed),
+                        total: format_bytes(json_progress.total),
+                        speed,
+                        raw: line.clone(),
+                    };
+                    
+                    // Emitir evento para frontend
+                    let _ = events::emit(&window, &progress);
+                    
+                    // Se status for "success", finalizar
+                    if json_progress.status == "success" {
+                        let success_progress = DownloadProgress {
+                            status: "success".to_string(),
+                            percent: Some(100),
+                            downloaded: format_bytes(json_progress.completed),
+                            total: format_bytes(json_progress.total),
+                            speed: None,
+                            raw: "success".to_string(),
+                        };
+                        let _ = events::emit(&window, &success_progress);
+                        return Ok(());
+                    }
+                }
+                Err(_) => {
+                    // Se não conseguir parsear como JSON, tratar como linha raw (fallback)
+                    let progress = DownloadProgress {
+                        status: "downloading".to_string(),
+                        percent: None,
+                        downloaded: None,
+                        total: None,
+                        speed: None,
+                        raw: line,
+                    };
+                    let _ = events::emit(&window, &progress);
+                }
+            }
+        }
+    }
+    
+    // Se chegou aqui, o stream terminou sem "success" explícito
+    // Emitir sucesso final
+    let success_progress = DownloadProgress {
+        status: "success".to_string(),
+        percent: Some(100),
+        downloaded: format_bytes(last_completed),
+        total: None,
+        speed: None,
+        raw: "success".to_string(),
+    };
+    let _ = events::emit(&window, &success_progress);
+    
+    Ok(())
+}
+
+// Função auxiliar para formatar velocidade
+fn format_speed(bytes_per_sec: f64) -> String {
+    const KB: f64 = 1024.0;
+    const MB: f64 = KB * 1024.0;
+    const GB: f64 = MB * 1024.0;
+    
+    if bytes_per_sec >= GB {
+        format!("{:.1} GB/s", bytes_per_sec / GB)
+    } else if bytes_per_sec >= MB {
+        format!("{:.1} MB/s", bytes_per_sec / MB)
+    } else if bytes_per_sec >= KB {
+        format!("{:.1} KB/s", bytes_per_sec / KB)
+    } else {
+        format!("{:.0} B/s", bytes_per_sec)
+    }
+}
+
+#[command]
+fn check_ollama_installed() -> bool {
+    match Command::new("ollama").arg("--version").output() {
+        Ok(output) => output.status.success(),
+        Err(_) => false,
+    }
+}
+
+#[command]
+async fn check_ollama_running(app_handle: AppHandle) -> bool {
+    match reqwest::get(settings::current(&app_handle).ollama_url).await {
+        Ok(resp) => resp.status().is_success(),
+        Err(_) => false,
+    }
+}
+
+/// Verificação completa do Ollama: instalação e execução
+#[derive(serde::Serialize)]
+struct OllamaCheckResult {
+    installed: bool,
+    running: bool,
+    status: String, // "not_installed" | "installed_stopped" | "running"
+}
+
+/// Inicia o Ollama automaticamente se estiver instalado mas não estiver rodando
+#[command]
+async fn auto_start_ollama(app_handle: AppHandle) -> Result<bool, String> {
+    // Verificar se está instalado
+    let installed = check_ollama_installed();
+    if !installed {
+        log::info!("Ollama não está instalado, pulando inicialização automática");
+        return Ok(false);
+    }
+    
+    // Verificar se já está rodando
+    let running = check_ollama_running(app_handle.clone()).await;
+    if running {
+        log::info!("Ollama já está rodando");
+        return Ok(true);
+    }
+    
+    // Tentar iniciar
+    log::info!("Iniciando Ollama automaticamente...");
+    match start_ollama_server(app_handle.clone()) {
+        Ok(_) => {
+            // Aguardar um pouco para o servidor iniciar
+            tokio::time::sleep(tokio::time::Duration::from_secs(2)).await;
+            
+            // Verificar se iniciou com sucesso
+            let is_running = check_ollama_running(app_handle).await;
+            if is_running {
+                log::info!("Ollama iniciado com sucesso");
+                Ok(true)
+            } else {
+                log::warn!("Ollama foi iniciado mas ainda não está respondendo");
+                Ok(false)
+            }
+        }
+        Err(e) => {
+            log::error!("Falha ao iniciar Ollama automaticamente: {}", e);
+            Err(e)
+        }
+    }
+}
+
+#[command]
+async fn check_ollama_full(app_handle: AppHandle) -> Result<OllamaCheckResult, String> {
+    let installed = check_ollama_installed();
+    
+    if !installed {
+        return Ok(OllamaCheckResult {
+            installed: false,
+            running: false,
+            status: "not_installed".to_string(),
+        });
+    }
+    
+    let running = check_ollama_running(app_handle).await;
+    
+    if !running {
+        return Ok(OllamaCheckResult {
+            installed: true,
+            running: false,
+            status: "installed_stopped".to_string(),
+        });
+    }
+    
+    Ok(OllamaCheckResult {
+        installed: true,
+        running: true,
+        status: "running".to_string(),
+    })
+}
+
+#[command]
+fn start_ollama_server(app_handle: AppHandle) -> Result<(), String> {
+    if let Some(pid) = ollama_server::spawned_pid() {
+        log::info!("Servidor Ollama já iniciado pelo app (PID {})", pid);
+        return Ok(());
+    }
+    
+    let mut cmd = Command::new("ollama");
+    cmd.arg("serve");
+    
+    // Ajustes de paralelismo/carga e variáveis configuradas no app
+    for (key, value) in ollama_server::env_vars(&settings::current(&app_handle)) {
+        cmd.env(key, value);
+    }
+
+    #[cfg(target_os = "windows")]
+    {
+        use std::os::windows::process::CommandExt;
+        const CREATE_NO_WINDOW: u32 = 0x08000000;
+        cmd.creation_flags(CREATE_NO_WINDOW);
+    }
+
+    // Processo guardado para `stop_ollama_server`/`restart_ollama_server`
+    let child = cmd.spawn()
+        .map_err(|e| format!("Failed to start ollama: {}", e))?;
+    ollama_server::track(child);
+        
+    Ok(())
+}
+
+/// Encerra processos do servidor Ollama. Retorna quantos foram encerrados.
+fn stop_ollama_processes() -> u32 {
+    let mut system = System::new_all();
+    system.refresh_all();
+    
+    let mut stopped = 0;
+    for (pid, process) in system.processes() {
+        let name = process.name().to_string_lossy().to_lowercase();
+        if name == "ollama" || name == "ollama.exe" {
+            if process.kill() {
+                stopped += 1;
+                log::info!("Processo Ollama encerrado: PID {}", pid);
+            } else {
+                log::warn!("Falha ao encerrar processo Ollama: PID {}", pid);
+            }
+        }
+    }
+    
+    stopped
+}
+
+// MCP Configuration Commands
+#[command]
+fn load_mcp_config(app_handle: AppHandle) -> Result<McpConfig, String> {
+    let config_path = get_mcp_config_path(&app_handle)?;
+    
+    // If file doesn't exist, return empty config
+    if !config_path.exists() {
+        return Ok(McpConfig {
+            mcp_servers: HashMap::new(),
+        });
+    }
+    
+    let content = fs::read_to_string(&config_path)
+        .map_err(|e| format!("Failed to read MCP config: {}", e))?;
+    
+    let config: McpConfig = serde_json::from_str(&content)
+        .map_err(|e| format!("Failed to parse MCP config: {}", e))?;
+    
+    Ok(config)
+}
+
+#[command]
+fn save_mcp_config(app_handle: AppHandle, config: McpConfig) -> Result<(), String> {
+    if config.mcp_servers.contains_key(builtin_tools::SERVER_NAME) {
+        return Err(format!("O nome '{}' é reservado para as ferramentas embutidas", builtin_tools::SERVER_NAME));
+    }
+    if config.mcp_servers.keys().any(|name| name.starts_with(plugins::SERVER_PREFIX)) {
+        return Err(format!("Nomes iniciados por '{}' são reservados para plugins", plugins::SERVER_PREFIX));
+    }
+    
+    let config_path = get_mcp_config_path(&app_handle)?;
+    
+    // Ensure parent directory exists
+    if let Some(parent) = config_path.parent() {
+        if !parent.exists() {
+            fs::create_dir_all(parent)
+                .map_err(|e| format!("Failed to create config directory: {}", e))?;
+        }
+    }
+    
+    let json = serde_json::to_string_pretty(&config)
+        .map_err(|e| format!("Failed to serialize MCP config: {}", e))?;
+    
+    fs::write(&config_path, json)
+        .map_err(|e| format!("Failed to write MCP config: {}", e))?;
+    
+    Ok(())
+}
+
+#[command]
+fn get_mcp_config_path_command(app_handle: AppHandle) -> Result<String, String> {
+    let path = get_mcp_config_path(&app_handle)?;
+    Ok(path.to_string_lossy().to_string())
+}
+
+// MCP Process Management Commands
+#[command]
+fn start_mcp_server(
+    processes: State<'_, McpProcessMap>,
+    name: String,
+    config: McpServerConfig,
+) -> Result<u32, String> {
+    let mut processes_map = processes.lock()
+        .map_err(|e| format!("Failed to lock processes map: {}", e))?;
+    
+    // Kill existing process if running
+    if let Some(mut handle) = processes_map.remove(&name) {
+        let _ = handle.child.kill();
+        let _ = handle.child.wait();
+    }
+    
+    // Check if command exists before attempting to spawn
+    // On Windows, we need to check both with and without .exe extension
+    let mut command_exists = {
+        let check = Command::new(&config.command)
+            .arg("--version")
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .output();
+        
+        #[cfg(target_os = "windows")]
+        {
+            if check.is_err() {
+                // Try with .exe extension on Windows
+                Command::new(format!("{}.exe", config.command))
+                    .arg("--version")
+                    .stdout(Stdio::null())
+                    .stderr(Stdio::null())
+                    .output()
+                    .is_ok()
+            } else {
+                true
+            }
+        }
+        
+        #[cfg(not(target_os = "windows"))]
+        {
+            check.is_ok()
+        }
+    };
+    
+    // Build command - try to use full path if found, otherwise use command as-is
+    // On Windows, we may need to check common Node.js installation paths
+    let mut command_path = config.command.clone();
+    
+    #[cfg(target_os = "windows")]
+    {
+        // If command is npx and not found in PATH, try common Node.js locations
+        if config.command == "npx" && !command_exists {
+            let program_files = std::env::var("ProgramFiles").unwrap_or_else(|_| "C:\\Program Files".to_string());
+            let program_files_x86 = std::env::var("ProgramFiles(x86)").unwrap_or_else(|_| "C:\\Program Files (x86)".to_string());
+            
+            let common_paths: Vec<String> = vec![
+                format!("{}\\nodejs\\npx.cmd", program_files),
+                format!("{}\\nodejs\\npx.cmd", program_files_x86),
+                r"C:\Program Files\nodejs\npx.cmd".to_string(),
+                r"C:\Program Files (x86)\nodejs\npx.cmd".to_string(),
+            ];
+            
+            for path in common_paths {
+                if std::path::Path::new(&path).exists() {
+                    command_path = path;
+                    command_exists = true; // Mark as found
+                    break;
+                }
+            }
+        }
+    }
+    
+    if !command_exists {
+        // Command not found - provide helpful error message
+        return Err(format!(
+            "Comando '{}' não encontrado no PATH. Verifique se está instalado e acessível. {}",
+            config.command,
+            if config.command == "npx" {
+                "O Node.js e npm precisam estar instalados. Instale de https://nodejs.org/ e reinicie o aplicativo após a instalação."
+            } else if config.command == "uvx" {
+                "O uv (Python package manager) precisa estar instalado. Instale com: pip install uv"
+            } else {
+                "Certifique-se de que o comando está disponível no PATH do sistema."
+            }
+        ));
+    }
+    
+    let mut cmd = Command::new(&command_path);
+    cmd.args(&config.args);
+    cmd.stdin(Stdio::piped());
+    cmd.stdout(Stdio::piped());
+    cmd.stderr(Stdio::piped());
+    
+    // Set environment variables if provided
+    if let Some(env_vars) = &config.env {
+        for (key, value) in env_vars {
+            cmd.env(key, value);
+        }
+    }
+    
+    #[cfg(target_os = "windows")]
+    {
+        use std::os::windows::process::CommandExt;
+        const CREATE_NO_WINDOW: u32 = 0x08000000;
+        cmd.creation_flags(CREATE_NO_WINDOW);
+    }
+    
+    // Spawn process
+    let child = cmd.spawn()
+        .map_err(|e| {
+            let error_msg = e.to_string();
+            // Provide more context for common errors
+            if error_msg.contains("program not found") || 
+               error_msg.contains("No such file") || 
+               error_msg.contains("The system cannot find the file") ||
+               error_msg.contains("not found") {
+                format!(
+                    "Comando '{}' não encontrado. Verifique se está instalado e no PATH do sistema. {}",
+                    config.command,
+                    if config.command == "npx" {
+                        "Instale Node.js de https://nodejs.org/ e reinicie o aplicativo após a instalação."
+                    } else if config.command == "uvx" {
+                        "Instale uv com: pip install uv"
+                    } else {
+                        "Certifique-se de que o comando está disponível no PATH."
+                    }
+                )
+            } else {
+                format!("Erro ao iniciar servidor '{}': {}", name, error_msg)
+            }
+        })?;
+    
+    let pid = child.id();
+    
+    // Create process handle with request ID counter
+    let handle = McpProcessHandle {
+        child,
+        request_id: Arc::new(Mutex::new(0)),
+    };
+    
+    // Store in map
+    processes_map.insert(name, handle);
+    
+    Ok(pid)
+}
+
+#[command]
+fn stop_mcp_server(
+    processes: State<'_, McpProcessMap>,
+    name: String,
+) -> Result<(), String> {
+    let mut processes_map = processes.lock()
+        .map_err(|e| format!("Failed to lock processes map: {}", e))?;
+    
+    if let Some(mut handle) = processes_map.remove(&name) {
+        handle.child.kill()
+            .map_err(|e| format!("Failed to kill process '{}': {}", name, e))?;
+        let _ = handle.child.wait();
+        Ok(())
+    } else {
+        Err(format!("MCP server '{}' not found", name))
+    }
+}
+
+#[command]
+fn restart_mcp_server(
+    processes: State<'_, McpProcessMap>,
+    app_handle: AppHandle,
+    name: String,
+) -> Result<u32, String> {
+    // Load config
+    let config = load_mcp_config(app_handle)?;
+    
+    // Find server config
+    let server_config = config.mcp_servers.get(&name)
+        .ok_or_else(|| format!("MCP server '{}' not found in config", name))?
+        .clone();
+    
+    // Stop if running
+    {
+        let mut processes_map = processes.lock()
+            .map_err(|e| format!("Failed to lock processes map: {}", e))?;
+        if let Some(mut handle) = processes_map.remove(&name) {
+            let _ = handle.child.kill();
+            let _ = handle.child.wait();
+        }
+    }
+    
+    // Start again
+    let mut processes_map = processes.lock()
+        .map_err(|e| format!("Failed to lock processes map: {}", e))?;
+    
     // Build command
     let mut cmd = Command::new(&server_config.command);
     cmd.args(&server_config.args);
@@ -1907,1727 +2455,5102 @@ fn restart_mcp_server(
     cmd.stdout(Stdio::piped());
     cmd.stderr(Stdio::piped());
     
-    // Set environment variables if provided
-    if let Some(env_vars) = &server_config.env {
-        for (key, value) in env_vars {
-            cmd.env(key, value);
-        }
+    // Set environment variables if provided
+    if let Some(env_vars) = &server_config.env {
+        for (key, value) in env_vars {
+            cmd.env(key, value);
+        }
+    }
+    
+    #[cfg(target_os = "windows")]
+    {
+        use std::os::windows::process::CommandExt;
+        const CREATE_NO_WINDOW: u32 = 0x08000000;
+        cmd.creation_flags(CREATE_NO_WINDOW);
+    }
+    
+    // Spawn process
+    let child = cmd.spawn()
+        .map_err(|e| format!("Failed to spawn MCP server '{}': {}", name, e))?;
+    
+    let pid = child.id();
+    
+    // Create process handle with request ID counter
+    let handle = McpProcessHandle {
+        child,
+        request_id: Arc::new(Mutex::new(0)),
+    };
+    
+    // Store in map
+    processes_map.insert(name, handle);
+    
+    Ok(pid)
+}
+
+#[command]
+fn list_mcp_server_status(
+    processes: State<'_, McpProcessMap>,
+    app_handle: AppHandle,
+) -> Result<Vec<McpServerStatus>, String> {
+    let mut processes_map = processes.lock()
+        .map_err(|e| format!("Failed to lock processes map: {}", e))?;
+    
+    let config = load_mcp_config(app_handle)?;
+    let mut statuses = Vec::new();
+    
+    for (name, _) in config.mcp_servers {
+        let status = if let Some(handle) = processes_map.get_mut(&name) {
+            // Check if process is still alive by trying to get its status
+            match handle.child.try_wait() {
+                Ok(Some(_)) => {
+                    // Process finished, remove from map
+                    processes_map.remove(&name);
+                    McpServerStatus {
+                        name: name.clone(),
+                        status: "stopped".to_string(),
+                        pid: None,
+                    }
+                },
+                Ok(None) => McpServerStatus {
+                    name: name.clone(),
+                    status: "running".to_string(),
+                    pid: Some(handle.child.id()),
+                },
+                Err(_) => McpServerStatus {
+                    name: name.clone(),
+                    status: "error".to_string(),
+                    pid: None,
+                },
+            }
+        } else {
+            McpServerStatus {
+                name: name.clone(),
+                status: "stopped".to_string(),
+                pid: None,
+            }
+        };
+        
+        statuses.push(status);
+    }
+    
+    Ok(statuses)
+}
+
+#[command]
+fn restart_all_mcp_servers(
+    processes: State<'_, McpProcessMap>,
+    app_handle: AppHandle,
+) -> Result<Vec<String>, String> {
+    let config = load_mcp_config(app_handle)?;
+    let mut started = Vec::new();
+    
+    let mut processes_map = processes.lock()
+        .map_err(|e| format!("Failed to lock processes map: {}", e))?;
+    
+    // First, kill all existing processes (plugin sidecars are managed by enable_plugin)
+    let names: Vec<String> = processes_map
+        .keys()
+        .filter(|name| !name.starts_with(plugins::SERVER_PREFIX))
+        .cloned()
+        .collect();
+    for name in names {
+        if let Some(mut handle) = processes_map.remove(&name) {
+            let _ = handle.child.kill();
+            let _ = handle.child.wait();
+        }
+    }
+    
+    // Now start all servers from config
+    for (name, server_config) in config.mcp_servers {
+        // Build command
+        let mut cmd = Command::new(&server_config.command);
+        cmd.args(&server_config.args);
+        cmd.stdin(Stdio::piped());
+        cmd.stdout(Stdio::piped());
+        cmd.stderr(Stdio::piped());
+        
+        // Set environment variables if provided
+        if let Some(env_vars) = &server_config.env {
+            for (key, value) in env_vars {
+                cmd.env(key, value);
+            }
+        }
+        
+        #[cfg(target_os = "windows")]
+        {
+            use std::os::windows::process::CommandExt;
+            const CREATE_NO_WINDOW: u32 = 0x08000000;
+            cmd.creation_flags(CREATE_NO_WINDOW);
+        }
+        
+        // Spawn process
+        match cmd.spawn() {
+            Ok(child) => {
+                let handle = McpProcessHandle {
+                    child,
+                    request_id: Arc::new(Mutex::new(0)),
+                };
+                processes_map.insert(name.clone(), handle);
+                started.push(name);
+            }
+            Err(e) => {
+                eprintln!("Failed to start MCP server '{}': {}", name, e);
+            }
+        }
+    }
+    
+    Ok(started)
+}
+
+// MCP JSON-RPC Communication Commands
+#[command]
+fn list_mcp_tools(
+    processes: State<'_, McpProcessMap>,
+    app_handle: AppHandle,
+    server_name: String,
+) -> Result<Vec<McpTool>, String> {
+    if server_name == builtin_tools::SERVER_NAME {
+        return Ok(builtin_tools::tools(&settings::current(&app_handle).builtin_tools));
+    }
+    if let Some((_, manifest)) = plugins::wasm_plugin(&app_handle, &server_name) {
+        return Ok(manifest.tools);
+    }
+    
+    let mut processes_map = processes.lock()
+        .map_err(|e| format!("Failed to lock processes map: {}", e))?;
+    
+    let handle = processes_map.get_mut(&server_name)
+        .ok_or_else(|| format!("MCP server '{}' not found or not running", server_name))?;
+    
+    list_mcp_tools_internal(handle)
+}
+
+#[command]
+async fn call_mcp_tool(
+    processes: State<'_, McpProcessMap>,
+    app_handle: AppHandle,
+    server_name: String,
+    tool_name: String,
+    arguments: serde_json::Value,
+    session_id: Option<String>,
+) -> Result<serde_json::Value, String> {
+    offline::guard_tool(&app_handle, session_id.as_deref(), &server_name, &tool_name)?;
+    let arguments_json = arguments.to_string();
+    audit::record(audit::AuditKind::ToolCall, &server_name, &tool_name, Some(&arguments_json));
+    let result = dispatch_tool_call(processes, &app_handle, server_name.clone(), tool_name.clone(), arguments).await;
+    let outcome = match &result {
+        Ok(value) => value.to_string(),
+        Err(e) => format!("error: {}", e),
+    };
+    audit::record(audit::AuditKind::ToolResult, &server_name, &tool_name, Some(&outcome));
+    result
+}
+
+/// Encaminha a chamada ao provedor da ferramenta (embutidas, plugin WASM ou processo MCP)
+async fn dispatch_tool_call(
+    processes: State<'_, McpProcessMap>,
+    app_handle: &AppHandle,
+    server_name: String,
+    tool_name: String,
+    arguments: serde_json::Value,
+) -> Result<serde_json::Value, String> {
+    // Ferramentas embutidas (arquivos e web) não passam por um processo MCP
+    if server_name == builtin_tools::SERVER_NAME {
+        return builtin_tools::call(app_handle, &tool_name, &arguments).await;
+    }
+    // Plugins WASM rodam no wasmtime, também fora do mapa de processos
+    if let Some((plugin_dir, manifest)) = plugins::wasm_plugin(app_handle, &server_name) {
+        return tauri::async_runtime::spawn_blocking(move || plugins::call_wasm(&plugin_dir, &manifest, &tool_name, arguments))
+            .await
+            .map_err(|e| format!("Failed to join plugin tool task: {}", e))?;
+    }
+    
+    // A comunicação com o servidor MCP é bloqueante (stdio)
+    let processes = processes.inner().clone();
+    tauri::async_runtime::spawn_blocking(move || call_mcp_tool_internal(&processes, &server_name, &tool_name, arguments))
+        .await
+        .map_err(|e| format!("Failed to join MCP tool task: {}", e))?
+}
+
+// Helper function to call a tool on a server (not a Tauri command, used internally)
+fn call_mcp_tool_internal(
+    processes: &McpProcessMap,
+    server_name: &str,
+    tool_name: &str,
+    arguments: serde_json::Value,
+) -> Result<serde_json::Value, String> {
+    let mut processes_map = processes.lock()
+        .map_err(|e| format!("Failed to lock processes map: {}", e))?;
+    
+    let handle = processes_map.get_mut(server_name)
+        .ok_or_else(|| format!("MCP server '{}' not found or not running", server_name))?;
+    
+    // Increment request ID
+    let request_id = {
+        let mut id = handle.request_id.lock()
+            .map_err(|e| format!("Failed to lock request ID: {}", e))?;
+        *id += 1;
+        *id
+    };
+    
+    // Build params for tools/call
+    let params = serde_json::json!({
+        "name": tool_name,
+        "arguments": arguments
+    });
+    
+    // Send tools/call request
+    send_jsonrpc_request(
+        &mut handle.child,
+        "tools/call",
+        Some(params),
+        request_id,
+    )?;
+    
+    // Read response (wait a moment for server to process)
+    std::thread::sleep(Duration::from_millis(200));
+    let response = read_jsonrpc_response(&mut handle.child, request_id, 30)?;
+    
+    // Parse result from response
+    if let Some(error) = response.error {
+        return Err(format!("MCP server error: {} ({})", error.message, error.code));
+    }
+    
+    response.result
+        .ok_or_else(|| "No result in response".to_string())
+}
+
+// Helper function to list tools from a server (not a Tauri command, used internally)
+fn list_mcp_tools_internal(
+    handle: &mut McpProcessHandle,
+) -> Result<Vec<McpTool>, String> {
+    // Increment request ID
+    let request_id = {
+        let mut id = handle.request_id.lock()
+            .map_err(|e| format!("Failed to lock request ID: {}", e))?;
+        *id += 1;
+        *id
+    };
+    
+    // Send tools/list request
+    send_jsonrpc_request(
+        &mut handle.child,
+        "tools/list",
+        None,
+        request_id,
+    )?;
+    
+    // Read response (wait a moment for server to process)
+    std::thread::sleep(Duration::from_millis(200));
+    let response = read_jsonrpc_response(&mut handle.child, request_id, 10)?;
+    
+    // Parse tools from response
+    if let Some(error) = response.error {
+        return Err(format!("MCP server error: {} ({})", error.message, error.code));
+    }
+    
+    let result = response.result
+        .ok_or_else(|| "No result in response".to_string())?;
+    
+    let tools_obj = result.get("tools")
+        .ok_or_else(|| "No 'tools' field in response".to_string())?
+        .as_array()
+        .ok_or_else(|| "Tools field is not an array".to_string())?;
+    
+    let mut tools = Vec::new();
+    for tool_json in tools_obj {
+        let tool: McpTool = serde_json::from_value(tool_json.clone())
+            .map_err(|e| format!("Failed to parse tool: {}", e))?;
+        tools.push(tool);
+    }
+    
+    Ok(tools)
+}
+
+#[command]
+fn get_all_mcp_tools(
+    processes: State<'_, McpProcessMap>,
+    app_handle: AppHandle,
+) -> Result<Vec<McpToolInfo>, String> {
+    let mut processes_map = processes.lock()
+        .map_err(|e| format!("Failed to lock processes map: {}", e))?;
+    
+    let builtin = builtin_tools::tools(&settings::current(&app_handle).builtin_tools);
+    let config = load_mcp_config(app_handle.clone())?;
+    let mut all_tools: Vec<McpToolInfo> = builtin
+        .into_iter()
+        .map(|tool| McpToolInfo {
+            server_name: builtin_tools::SERVER_NAME.to_string(),
+            tool,
+        })
+        .collect();
+    
+    // Get tools from each running server
+    for (server_name, _) in config.mcp_servers {
+        if let Some(handle) = processes_map.get_mut(&server_name) {
+            match list_mcp_tools_internal(handle) {
+                Ok(tools) => {
+                    for tool in tools {
+                        all_tools.push(McpToolInfo {
+                            server_name: server_name.clone(),
+                            tool,
+                        });
+                    }
+                }
+                Err(e) => {
+                    eprintln!("Failed to list tools from '{}': {}", server_name, e);
+                }
+            }
+        }
+    }
+    
+    // Plugins: sidecars ativos no mapa de processos e ferramentas declaradas pelos WASM
+    for (server_name, handle) in processes_map.iter_mut() {
+        if !server_name.starts_with(plugins::SERVER_PREFIX) {
+            continue;
+        }
+        match list_mcp_tools_internal(handle) {
+            Ok(tools) => all_tools.extend(tools.into_iter().map(|tool| McpToolInfo {
+                server_name: server_name.clone(),
+                tool,
+            })),
+            Err(e) => log::warn!("[Plugins] Falha ao listar ferramentas de '{}': {}", server_name, e),
+        }
+    }
+    all_tools.extend(
+        plugins::wasm_tools(&app_handle)
+            .into_iter()
+            .map(|(server_name, tool)| McpToolInfo { server_name, tool }),
+    );
+    
+    Ok(all_tools)
+}
+
+#[command]
+fn ensure_mcp_server_installed(
+    _name: String,
+    config: McpServerConfig,
+) -> Result<bool, String> {
+    // Check if command exists
+    let command_exists = Command::new(&config.command)
+        .arg("--version")
+        .output()
+        .is_ok();
+    
+    if !command_exists {
+        return Err(format!("Command '{}' not found in PATH", config.command));
+    }
+    
+    // For npx commands with -y flag, check if package exists
+    // Note: This is a simplified check - in production, you might want to verify
+    // the package actually exists before trying to run it
+    if config.command == "npx" && config.args.contains(&"-y".to_string()) {
+        // npx -y will auto-install if needed, so we consider it available
+        return Ok(true);
+    }
+    
+    // For other commands, assume they're installed if command exists
+    Ok(true)
+}
+
+#[command]
+fn check_mcp_server_available(
+    name: String,
+    config: McpServerConfig,
+) -> Result<bool, String> {
+    ensure_mcp_server_installed(name, config)
+}
+
+// ========== Web Scraper Commands ==========
+
+/// Busca no DuckDuckGo e extrai conteúdo das URLs encontradas.
+/// Progresso por URL via `scrape-progress` (com o `job_id` informado), cancelável com `cancel_scrape_job`.
+/// A consulta passa antes pela remoção de dados pessoais (`search-query-redacted`).
+#[command]
+async fn search_and_extract_content(
+    app_handle: AppHandle,
+    query: String,
+    limit: Option<usize>,
+    excluded_domains: Option<Vec<String>>,
+    search_config: Option<SearchConfig>,
+    translate_to: Option<String>,
+    translation_model: Option<String>,
+    max_age: Option<u64>,
+    job_id: Option<String>,
+    confirm_redaction: Option<bool>,
+    session_id: Option<String>,
+) -> Result<Vec<ScrapedContent>, String> {
+    if query.trim().is_empty() {
+        return Err("Query não pode estar vazia".to_string());
+    }
+    offline::guard(&app_handle, session_id.as_deref(), offline::Egress::WebSearch, &query)?;
+    let query = redaction::sanitize_query(&app_handle, &query, "search", confirm_redaction.unwrap_or(false))?;
+    
+    let job = jobs::start_with_id(&app_handle, "scrape", job_id);
+    let progress = web_scraper::ScrapeProgress::new(&app_handle, job.clone());
+    let results = search_and_extract_tracked(
+        &app_handle, query, limit, excluded_domains, search_config, max_age, progress,
+    ).await;
+    job.finish(results.as_ref().map(|_| ()).map_err(|e| e.clone()));
+    
+    let mut results = maybe_translate_scraped(&app_handle, results?, translate_to, translation_model).await?;
+    if let Some(scope) = session_rag_scope(&app_handle, session_id.as_deref()) {
+        results.retain(|page| scope.allows(&page.url));
+    }
+    Ok(content_guard::guard_scraped(&app_handle, session_id.as_deref(), "search", results))
+}
+
+async fn search_and_extract_tracked(
+    app_handle: &AppHandle,
+    query: String,
+    limit: Option<usize>,
+    excluded_domains: Option<Vec<String>>,
+    search_config: Option<SearchConfig>,
+    max_age: Option<u64>,
+    progress: web_scraper::ScrapeProgress,
+) -> Result<Vec<ScrapedContent>, String> {
+    let progress = Some(progress);
+    let results = if !resource_profile::current().headless_scraping_enabled {
+        // Modo de baixo consumo: apenas HTTP, sem abrir o Chrome
+        let config = search_config.unwrap_or_else(|| SearchConfig {
+            max_concurrent_tabs: 1,
+            total_sources_limit: limit.unwrap_or(3),
+            categories: Vec::new(),
+            user_custom_sites: Vec::new(),
+            excluded_domains: excluded_domains.unwrap_or_default(),
+        });
+        search_and_scrape_static(&query, &config, progress)
+            .await
+            .map_err(|e| format!("Erro ao buscar e extrair conteúdo: {}", e))?
+    } else {
+        let cache = web_scraper::PageCache::new(app_handle, max_age);
+        
+        // Se SearchConfig foi fornecido, usar a nova função
+        if let Some(config) = search_config {
+            search_and_scrape_with_config(&query, &config, Some(cache), progress)
+                .await
+                .map_err(|e| format!("Erro ao buscar e extrair conteúdo: {}", e))?
+        } else {
+            // Backward compatibility: usar configuração padrão
+            let limit = limit.unwrap_or(3);
+            let excluded_domains = excluded_domains.unwrap_or_default();
+            search_and_scrape(&query, limit, excluded_domains, Some(cache), progress)
+                .await
+                .map_err(|e| format!("Erro ao buscar e extrair conteúdo: {}", e))?
+        }
+    };
+    
+    Ok(results)
+}
+
+/// Traduz as fontes para o idioma do usuário antes de irem para o contexto, se solicitado
+async fn maybe_translate_scraped(
+    app_handle: &AppHandle,
+    results: Vec<ScrapedContent>,
+    translate_to: Option<String>,
+    translation_model: Option<String>,
+) -> Result<Vec<ScrapedContent>, String> {
+    let target = match translate_to {
+        Some(t) if !t.trim().is_empty() => t,
+        _ => return Ok(results),
+    };
+    
+    let model = translation_model
+        .filter(|m| !m.trim().is_empty())
+        .ok_or_else(|| "translation_model é obrigatório quando translate_to é informado".to_string())?;
+    
+    let client = ollama_client::OllamaClient::new(Some(settings::current(app_handle).ollama_url));
+    Ok(translation::translate_scraped_contents(&client, &model, results, &target).await)
+}
+
+/// Traduz um texto para o idioma alvo usando um modelo local
+#[command]
+async fn translate_text(
+    app_handle: AppHandle,
+    text: String,
+    target_language: String,
+    model: String,
+) -> Result<String, String> {
+    if text.trim().is_empty() {
+        return Ok(String::new());
+    }
+    
+    let client = ollama_client::OllamaClient::new(Some(settings::current(&app_handle).ollama_url));
+    translation::translate_text(&client, &model, &text, &target_language).await
+}
+
+/// Detecta o idioma de um texto (código ISO 639-1 quando disponível)
+#[command]
+fn detect_text_language(text: String) -> Option<String> {
+    translation::detect_language(&text)
+}
+
+/// Extrai conteúdo de uma URL específica
+#[command]
+async fn extract_url_content(
+    app_handle: AppHandle,
+    url: String,
+    max_age: Option<u64>,
+    session_id: Option<String>,
+) -> Result<ScrapedContent, String> {
+    if url.trim().is_empty() {
+        return Err("URL não pode estar vazia".to_string());
+    }
+    offline::guard(&app_handle, session_id.as_deref(), offline::Egress::WebFetch, &url)?;
+    
+    // Validar formato de URL
+    if !url.starts_with("http://") && !url.starts_with("https://") {
+        return Err("URL deve começar com http:// ou https://".to_string());
+    }
+    
+    let page = if !resource_profile::current().headless_scraping_enabled {
+        scrape_url_static(&url)
+            .await
+            .map_err(|e| format!("Erro ao extrair conteúdo da URL: {}", e))?
+            .ok_or_else(|| "Conteúdo insuficiente via HTTP (headless desativado no modo de baixo consumo)".to_string())?
+    } else {
+        let cache = web_scraper::PageCache::new(&app_handle, max_age);
+        scrape_url(&url, Some(&cache))
+            .await
+            .map_err(|e| format!("Erro ao extrair conteúdo da URL: {}", e))?
+    };
+    
+    content_guard::guard_scraped(&app_handle, session_id.as_deref(), "fetch", vec![page])
+        .pop()
+        .ok_or_else(|| "Erro ao extrair conteúdo da URL".to_string())
+}
+
+/// Busca metadados leves (título/URL/snippet) sem abrir páginas
+#[command]
+async fn search_web_metadata(
+    app_handle: AppHandle,
+    query: String,
+    limit: Option<usize>,
+    search_config: Option<SearchConfig>,
+    engine_order: Option<Vec<String>>,
+    confirm_redaction: Option<bool>,
+    session_id: Option<String>,
+) -> Result<Vec<SearchResultMetadata>, String> {
+    if query.trim().is_empty() {
+        return Err("Query não pode estar vazia".to_string());
+    }
+    offline::guard(&app_handle, session_id.as_deref(), offline::Egress::WebSearch, &query)?;
+    let query = redaction::sanitize_query(&app_handle, &query, "metadata", confirm_redaction.unwrap_or(false))?;
+
+    let mut results = search_metadata(&app_handle, &query, limit.unwrap_or(5), search_config, engine_order).await?;
+    if let Some(scope) = session_rag_scope(&app_handle, session_id.as_deref()) {
+        results.retain(|result| scope.allows(&result.url));
+    }
+    Ok(results)
+}
+
+/// Escopo de fontes da sessão (definido pelo template), se restringir domínios
+fn session_rag_scope(app_handle: &AppHandle, session_id: Option<&str>) -> Option<db::RagScope> {
+    let session_id = session_id?;
+    db::Database::new(app_handle).ok()
+        .and_then(|db| db.get_session_config(session_id).ok().flatten())
+        .and_then(|config| config.rag_scope)
+        .filter(|scope| !scope.sources.is_empty())
+}
+
+async fn search_metadata(
+    app_handle: &AppHandle,
+    query: &str,
+    lim: usize,
+    search_config: Option<SearchConfig>,
+    engine_order: Option<Vec<String>>,
+) -> Result<Vec<SearchResultMetadata>, String> {
+    // SearxNG e Brave API só entram quando configurados em sources.json
+    let search_apis = load_sources_config(app_handle)
+        .map(|config| config.search_apis)
+        .unwrap_or_default();
+    
+    // Converter engine_order (strings) para Vec<SearchEngine>; motores desativados ficam de fora
+    let engines: Vec<SearchEngine> = if let Some(order) = engine_order {
+        order.iter()
+            .filter_map(|s| SearchEngine::from_config(s, &search_apis))
+            .filter(|engine| search_apis.is_engine_enabled(engine.key()))
+            .collect()
+    } else {
+        // Ordem de sources.json ou a padrão: SearxNG (se houver), Google e demais, Brave API por último
+        SearchEngine::configured_order(&search_apis)
+    };
+
+    // Se não há engines configuradas, usar DuckDuckGo como fallback
+    if engines.is_empty() {
+        log::warn!("No valid engines in order, using DuckDuckGo as fallback");
+        return search_duckduckgo_metadata(query, lim)
+            .await
+            .map_err(|e| format!("Erro ao buscar metadados: {}", e));
+    }
+
+    // Usar multi-engine search
+    let min_results = 1; // Mínimo de 1 resultado para considerar sucesso
+    match search_multi_engine_metadata(query, lim, &engines, min_results).await {
+        Ok(results) => {
+            if results.is_empty() && search_config.is_some() {
+                // Fallback para smart_search se multi-engine retornou vazio
+                log::info!("Multi-engine returned empty, trying smart_search fallback");
+                if let Some(config) = search_config {
+                    match smart_search(query, &config).await {
+                        Ok(found) => {
+                            let mut metas = found.resolved
+                                .into_iter()
+                                .map(|r| SearchResultMetadata { title: r.title, url: r.url, snippet: r.snippet })
+                                .chain(found.urls.into_iter().map(|u| SearchResultMetadata { title: u.clone(), url: u, snippet: String::new() }))
+                                .collect::<Vec<_>>();
+                            metas.truncate(lim);
+                            Ok(metas)
+                        }
+                        Err(e) => Err(format!("Erro ao executar smart_search: {}", e)),
+                    }
+                } else {
+                    Ok(results)
+                }
+            } else {
+                Ok(results)
+            }
+        }
+        Err(e) => {
+            // Se multi-engine falhou completamente, tentar DuckDuckGo como último recurso
+            log::warn!("Multi-engine search failed: {}, trying DuckDuckGo fallback", e);
+            search_duckduckgo_metadata(query, lim)
+                .await
+                .map_err(|e| format!("Erro ao buscar metadados: {}", e))
+        }
+    }
+}
+
+/// Busca de teste em um motor: resultados, tempo e quantos elementos cada selector encontrou
+#[command]
+async fn test_search_engine(app_handle: AppHandle, name: String) -> Result<web_scraper::EngineProbe, String> {
+    let search_apis = load_sources_config(&app_handle)
+        .map(|config| config.search_apis)
+        .unwrap_or_default();
+    let engine = SearchEngine::from_config(&name, &search_apis)
+        .ok_or_else(|| format!("Motor de busca desconhecido ou sem chave/URL configurada: {}", name))?;
+    let probe = web_scraper::probe_engine(engine).await;
+    log::info!(
+        "[SearchEngine:{}] Teste: {} resultados em {}ms{}",
+        probe.engine,
+        probe.results,
+        probe.duration_ms,
+        probe.error.as_deref().map(|e| format!(" ({})", e)).unwrap_or_default()
+    );
+    Ok(probe)
+}
+
+/// Taxa de sucesso, buscas vazias e falhas seguidas de cada motor; os rebaixados ficam
+/// fora das buscas até uma sondagem (ou `test_search_engine`) voltar a dar resultados
+#[command]
+fn get_search_engine_health() -> Vec<engine_health::EngineHealthReport> {
+    engine_health::report()
+}
+
+/// Busca imagens (URL, miniatura e página de origem) no Bing e no DuckDuckGo Images
+#[command]
+async fn search_images(
+    app_handle: AppHandle,
+    query: String,
+    limit: Option<usize>,
+    confirm_redaction: Option<bool>,
+) -> Result<Vec<image_search::ImageResult>, String> {
+    if query.trim().is_empty() {
+        return Err("Query não pode estar vazia".to_string());
+    }
+    let query = redaction::sanitize_query(&app_handle, &query, "images", confirm_redaction.unwrap_or(false))?;
+    image_search::search_images(&query, limit.unwrap_or(10).clamp(1, 50)).await
+}
+
+/// Baixa uma imagem para os dados do app e a devolve em base64 para anexar ao chat
+#[command]
+async fn download_image(app_handle: AppHandle, url: String) -> Result<image_search::DownloadedImage, String> {
+    image_search::download_image(&app_handle, url.trim()).await
+}
+
+/// Faz scraping em lote de URLs fornecidas.
+/// Progresso por URL via `scrape-progress` (com o `job_id` informado), cancelável com `cancel_scrape_job`.
+#[command]
+async fn scrape_urls(
+    app_handle: AppHandle,
+    urls: Vec<String>,
+    translate_to: Option<String>,
+    translation_model: Option<String>,
+    max_age: Option<u64>,
+    job_id: Option<String>,
+) -> Result<Vec<ScrapedContent>, String> {
+    if urls.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let job = jobs::start_with_id(&app_handle, "scrape", job_id);
+    let progress = Some(web_scraper::ScrapeProgress::new(&app_handle, job.clone()));
+    let results = if !resource_profile::current().headless_scraping_enabled {
+        scrape_urls_static(urls, progress)
+            .await
+            .map_err(|e| format!("Erro ao extrair conteúdo das URLs: {}", e))
+    } else {
+        let cache = web_scraper::PageCache::new(&app_handle, max_age);
+        scrape_urls_bulk(urls, Some(cache), progress)
+            .await
+            .map_err(|e| format!("Erro ao extrair conteúdo das URLs: {}", e))
+    };
+    job.finish(results.as_ref().map(|_| ()).map_err(|e| e.clone()));
+
+    let results = maybe_translate_scraped(&app_handle, results?, translate_to, translation_model).await?;
+    Ok(content_guard::guard_scraped(&app_handle, None, "scrape", results))
+}
+
+/// Monta o contexto web para o prompt de `model`: fontes limpas de instruções, cada uma num
+/// bloco delimitado e todas dentro da fração do contexto em `content_guard`
+#[command]
+fn guard_web_context(
+    app_handle: AppHandle,
+    model: String,
+    sources: Vec<ScrapedContent>,
+    session_id: Option<String>,
+) -> content_guard::GuardedContext {
+    content_guard::build_context(&app_handle, session_id.as_deref(), "context", &model, sources)
+}
+
+/// Cancela um job de `scrape_urls`/`search_and_extract_content`; as URLs já extraídas são retornadas
+#[command]
+fn cancel_scrape_job(job_id: String) -> Result<bool, String> {
+    let cancelled = jobs::cancel(&job_id);
+    if cancelled {
+        log::info!("[Scrape] Cancelamento solicitado para o job {}", job_id);
+    }
+    Ok(cancelled)
+}
+
+/// Limpa o cache de páginas do scraper (tudo, ou apenas páginas mais antigas que `older_than_secs`)
+#[command]
+fn clear_scrape_cache(app_handle: AppHandle, older_than_secs: Option<u64>) -> Result<usize, String> {
+    let cutoff = older_than_secs
+        .map(|secs| chrono::Utc::now() - chrono::Duration::seconds(secs.min(u32::MAX as u64) as i64));
+    let db = db::Database::new(&app_handle)
+        .map_err(|e| format!("Failed to open database: {}", e))?;
+    let removed = db.clear_scrape_cache(cutoff)
+        .map_err(|e| format!("Failed to clear scrape cache: {}", e))?;
+    log::info!("[PageCache] {} página(s) removida(s) do cache", removed);
+    Ok(removed)
+}
+
+/// Reinicia os browsers do pool (normalmente desnecessário: instâncias são recicladas sozinhas)
+#[command]
+fn reset_browser() -> Result<(), String> {
+    browser_pool::clear();
+    log::info!("Browser pool resetado - processos serão encerrados quando não houver mais referências");
+    Ok(())
+}
+
+/// Instâncias ativas do pool de headless browsers
+#[command]
+fn get_browser_pool_stats() -> browser_pool::PoolStats {
+    browser_pool::stats()
+}
+
+/// Força o encerramento apenas de processos Chrome/Chromium headless criados pelo app
+/// Seguro: não mata o navegador pessoal do usuário
+#[command]
+fn force_kill_browser() -> Result<u32, String> {
+    let mut system = System::new_all();
+    system.refresh_all();
+    
+    let mut killed_count = 0;
+    let process_names = vec!["chrome", "chromium", "chromedriver", "headless_shell"];
+    
+    for (pid, process) in system.processes() {
+        let name = process.name().to_string_lossy().to_lowercase();
+        
+        // Verifica se o nome do processo corresponde
+        if !process_names.iter().any(|&pn| name.contains(pn)) {
+            continue;
+        }
+        
+        // SAFE KILL: Estratégia conservadora para identificar processos headless
+        // No Windows, tentamos usar wmic para obter a linha de comando completa
+        #[cfg(target_os = "windows")]
+        let is_headless = {
+            use std::process::Command;
+            // Tenta obter a linha de comando do processo via wmic
+            let cmd_output = Command::new("wmic")
+                .args(&["process", "where", &format!("ProcessId={}", pid), "get", "CommandLine", "/format:list"])
+                .output();
+            
+            if let Ok(output) = cmd_output {
+                if let Ok(cmd_str) = String::from_utf8(output.stdout) {
+                    let cmd_lower = cmd_str.to_lowercase();
+                    // Só mata se tiver flags muito específicas de headless
+                    cmd_lower.contains("--headless") 
+                        || cmd_lower.contains("--remote-debugging-port")
+                        || (cmd_lower.contains("--disable-gpu") && cmd_lower.contains("--no-sandbox"))
+                } else {
+                    false // Se não conseguir ler, não mata (seguro)
+                }
+            } else {
+                // Se wmic falhar, usa heurística conservadora: só mata se o nome for muito específico
+                name.contains("headless_shell") || name.contains("chromedriver")
+            }
+        };
+        
+        #[cfg(not(target_os = "windows"))]
+        let is_headless = {
+            // No Linux/Mac, tenta ler /proc/PID/cmdline
+            use std::fs;
+            if let Ok(cmdline) = fs::read_to_string(format!("/proc/{}/cmdline", pid)) {
+                let cmd_lower = cmdline.to_lowercase();
+                cmd_lower.contains("--headless") 
+                    || cmd_lower.contains("--remote-debugging-port")
+                    || (cmd_lower.contains("--disable-gpu") && cmd_lower.contains("--no-sandbox"))
+            } else {
+                // Se não conseguir ler, usa heurística conservadora
+                name.contains("headless_shell") || name.contains("chromedriver")
+            }
+        };
+        
+        if !is_headless {
+            log::debug!("Ignorando processo Chrome não-headless: PID {} ({})", pid, name);
+            continue;
+        }
+        
+        // Processo identificado como headless - pode matar com segurança
+            #[cfg(target_os = "windows")]
+            {
+                use std::process::Command;
+                match Command::new("taskkill")
+                    .args(&["/F", "/PID", &pid.to_string()])
+                    .output()
+                {
+                    Ok(output) => {
+                        if output.status.success() {
+                            killed_count += 1;
+                        log::info!("Processo Chrome headless encerrado: PID {} ({})", pid, name);
+                        }
+                    }
+                    Err(e) => {
+                        log::warn!("Erro ao encerrar processo {}: {}", pid, e);
+                    }
+                }
+            }
+            
+            #[cfg(not(target_os = "windows"))]
+            {
+                use std::process::Command;
+                match Command::new("kill")
+                    .args(&["-9", &pid.to_string()])
+                    .output()
+                {
+                    Ok(output) => {
+                        if output.status.success() {
+                            killed_count += 1;
+                        log::info!("Processo Chrome headless encerrado: PID {} ({})", pid, name);
+                        }
+                    }
+                    Err(e) => {
+                        log::warn!("Erro ao encerrar processo {}: {}", pid, e);
+                }
+            }
+        }
+    }
+    
+    if killed_count > 0 {
+        log::info!("Total de {} processos Chrome headless encerrados (seguro)", killed_count);
+    } else {
+        log::info!("Nenhum processo Chrome headless encontrado para encerrar");
+    }
+    
+    Ok(killed_count)
+}
+
+// ========== Storage Management Commands ==========
+
+/// Espaço em disco por área (modelos por blob, chats, banco + WAL, cache, logs) para a página de armazenamento
+#[command]
+async fn get_storage_usage(app_handle: AppHandle) -> Result<storage_usage::StorageUsage, String> {
+    tauri::async_runtime::spawn_blocking(move || storage_usage::collect(&app_handle))
+        .await
+        .map_err(|e| format!("Failed to join storage usage task: {}", e))?
+}
+
+/// Exporta todas as sessões de chat para um arquivo ZIP
+#[command]
+async fn export_chat_sessions(app_handle: AppHandle) -> Result<String, String> {
+    let chats_dir = get_chats_dir(&app_handle)?;
+    
+    // Criar nome do arquivo com timestamp
+    let timestamp = Utc::now().format("%Y%m%d_%H%M%S");
+    let export_dir = app_handle.path().app_data_dir()
+        .map_err(|e| format!("Failed to get app data dir: {}", e))?;
+    let zip_path = export_dir.join(format!("ollahub_export_{}.zip", timestamp));
+    
+    // Criar arquivo ZIP
+    let file = fs::File::create(&zip_path)
+        .map_err(|e| format!("Failed to create ZIP file: {}", e))?;
+    
+    let mut zip = ZipWriter::new(file);
+    let options = FileOptions::default()
+        .compression_method(CompressionMethod::Deflated)
+        .unix_permissions(0o755);
+    
+    // Ler todos os arquivos JSON do diretório chats
+    let entries = fs::read_dir(&chats_dir)
+        .map_err(|e| format!("Failed to read chats dir: {}", e))?;
+    
+    for entry in entries {
+        let entry = entry.map_err(|e| format!("Failed to read entry: {}", e))?;
+        let path = entry.path();
+        
+        if path.extension().and_then(|s| s.to_str()) == Some("json") {
+            let file_name = path.file_name()
+                .and_then(|n| n.to_str())
+                .ok_or_else(|| "Invalid file name".to_string())?;
+            
+            let file_content = fs::read_to_string(&path)
+                .map_err(|e| format!("Failed to read file {}: {}", file_name, e))?;
+            
+            zip.start_file(format!("chats/{}", file_name), options)
+                .map_err(|e| format!("Failed to add file to ZIP: {}", e))?;
+            zip.write_all(file_content.as_bytes())
+                .map_err(|e| format!("Failed to write file to ZIP: {}", e))?;
+        }
+    }
+    
+    zip.finish()
+        .map_err(|e| format!("Failed to finalize ZIP: {}", e))?;
+    
+    Ok(format!("{}", zip_path.display()))
+}
+
+/// Apaga todo o histórico de conversas
+#[command]
+fn clear_chat_history(app_handle: AppHandle) -> Result<(), String> {
+    use db::Database;
+    
+    let chats_dir = get_chats_dir(&app_handle)?;
+    
+    // 1. Deletar todos os arquivos JSON
+    let entries = fs::read_dir(&chats_dir)
+        .map_err(|e| format!("Failed to read chats dir: {}", e))?;
+    
+    let mut deleted_count = 0;
+    for entry in entries {
+        let entry = entry.map_err(|e| format!("Failed to read entry: {}", e))?;
+        let path = entry.path();
+        
+        if path.extension().and_then(|s| s.to_str()) == Some("json") {
+            fs::remove_file(&path)
+                .map_err(|e| format!("Failed to delete file {:?}: {}", path, e))?;
+            deleted_count += 1;
+        }
+    }
+    
+    // 2. Deletar todas as sessões do SQLite
+    match Database::new(&app_handle) {
+        Ok(db) => {
+            match db.list_sessions() {
+                Ok(sessions) => {
+                    let mut sqlite_deleted = 0;
+                    for session in sessions {
+                        if let Err(e) = db.delete_session(&session.id) {
+                            log::warn!("Failed to delete session {} from SQLite: {}", session.id, e);
+                        } else {
+                            sqlite_deleted += 1;
+                        }
+                    }
+                    log::info!("Deleted {} sessions from SQLite", sqlite_deleted);
+                }
+                Err(e) => {
+                    log::warn!("Failed to list sessions from SQLite: {}", e);
+                }
+            }
+        }
+        Err(e) => {
+            log::warn!("Failed to open database: {}", e);
+        }
+    }
+    
+    log::info!("Deleted {} chat session files", deleted_count);
+    Ok(())
+}
+
+/// Limpa sessões órfãs do SQLite que não têm arquivo JSON correspondente
+#[command]
+fn cleanup_orphan_sessions(app_handle: AppHandle) -> Result<u32, String> {
+    use db::Database;
+    
+    let db = Database::new(&app_handle)
+        .map_err(|e| format!("Failed to open database: {}", e))?;
+    
+    let chats_dir = get_chats_dir(&app_handle)?;
+    let mut orphan_count = 0;
+    
+    // Listar todas as sessões do SQLite
+    let sessions = db.list_sessions()
+        .map_err(|e| format!("Failed to list sessions: {}", e))?;
+    
+    for session in sessions {
+        let json_path = chats_dir.join(format!("{}.json", session.id));
+        
+        // Se não existe arquivo JSON correspondente, é uma sessão órfã
+        if !json_path.exists() {
+            log::info!("Found orphan session: {} (title: {}), removing from SQLite", session.id, session.title);
+            
+            if let Err(e) = db.delete_session(&session.id) {
+                log::warn!("Failed to delete orphan session {}: {}", session.id, e);
+            } else {
+                orphan_count += 1;
+            }
+        }
+    }
+    
+    log::info!("Cleaned up {} orphan sessions from SQLite", orphan_count);
+    Ok(orphan_count)
+}
+
+/// Retorna o caminho do diretório de dados do app
+#[command]
+fn get_app_data_dir(app_handle: AppHandle) -> Result<String, String> {
+    let app_data_dir = app_handle.path().app_data_dir()
+        .map_err(|e| format!("Failed to get app data dir: {}", e))?;
+    Ok(format!("{}", app_data_dir.display()))
+}
+
+/// Diagnóstico dos diretórios usados pelo app (caminhos longos no Windows, acentos no
+/// nome do usuário, falta de permissão). Caminhos sem problemas vêm com `issues` vazio.
+#[command]
+fn verify_storage_paths(app_handle: AppHandle) -> Result<Vec<paths::PathCheck>, String> {
+    let app_data_dir = app_handle.path().app_data_dir()
+        .map_err(|e| format!("Failed to get app data dir: {}", e))?;
+    
+    let mut checks = vec![
+        paths::check_path("app_data", &app_data_dir),
+        paths::check_path("chats", &get_chats_dir(&app_handle)?),
+        paths::check_path("archive", &app_data_dir.join("archive")),
+        paths::check_path("logs", &logging::logs_dir(&app_handle)?),
+        paths::check_path("installers", &app_data_dir.join("installers")),
+        paths::check_path("temp", &std::env::temp_dir()),
+    ];
+    if let Some(home) = dirs::home_dir() {
+        checks.push(paths::check_path("ollama_models", &home.join(".ollama").join("models")));
+    }
+    
+    for check in checks.iter().filter(|c| !c.issues.is_empty()) {
+        log::warn!("[Storage] {} ({}): {}", check.label, check.path, check.issues.join("; "));
+    }
+    Ok(checks)
+}
+
+/// Salva um arquivo temporário e retorna o caminho
+#[command]
+fn save_temp_file(app_handle: AppHandle, data: Vec<u8>, extension: String) -> Result<String, String> {
+    use std::time::{SystemTime, UNIX_EPOCH};
+    
+    // Obter diretório temporário
+    let temp_dir = std::env::temp_dir();
+    
+    // Criar nome de arquivo único
+    let timestamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_millis();
+    let filename = format!("ollama_model_{}.{}", timestamp, extension);
+    let temp_path = temp_dir.join(&filename);
+    
+    // Escrever arquivo
+    fs::write(&temp_path, data)
+        .map_err(|e| format!("Erro ao salvar arquivo temporário: {}", e))?;
+    
+    Ok(temp_path.to_string_lossy().to_string())
+}
+
+/// Abre um dialog de seleção de arquivo GGUF usando dialog nativo do sistema
+#[command]
+async fn open_gguf_file_dialog() -> Result<Option<String>, String> {
+    use rfd::FileDialog;
+    
+    // No rfd, o filtro "*" não funciona corretamente no Windows.
+    // Para garantir que todos os arquivos sejam mostrados, vamos criar
+    // um dialog sem filtro algum. O dialog nativo do Windows mostrará
+    // todos os arquivos por padrão quando não há filtro.
+    let file = FileDialog::new()
+        .set_title("Selecionar modelo GGUF")
+        .pick_file();
+    
+    Ok(file.map(|p| p.to_string_lossy().to_string()))
+}
+
+// ========== Model Archive Commands ==========
+
+/// Empacota um modelo do Ollama (manifest + blobs) em `dest_path` para levar a outra máquina.
+/// Progresso em `model-archive-progress`; `export_id` permite cancelar com `cancel_job`.
+#[command]
+async fn export_model(
+    app_handle: AppHandle,
+    name: String,
+    dest_path: String,
+    export_id: Option<String>,
+) -> Result<model_archive::ArchiveReport, String> {
+    let dest = paths::from_user_input(&dest_path);
+    let job = jobs::start_with_id(&app_handle, "model_export", export_id);
+    tauri::async_runtime::spawn_blocking(move || {
+        let result = model_archive::export(&app_handle, &job, &name, &dest);
+        job.finish(result.as_ref().map(|_| ()).map_err(|e| e.clone()));
+        result
+    })
+    .await
+    .map_err(|e| format!("Failed to join model export task: {}", e))?
+}
+
+/// Lê o índice de um pacote de modelo (nome, blobs e tamanhos) sem importar
+#[command]
+fn inspect_model_archive(path: String) -> Result<model_archive::ArchiveIndex, String> {
+    model_archive::read_index(&paths::from_user_input(&path))
+}
+
+/// Importa um pacote gerado por `export_model` para o diretório de modelos do Ollama
+#[command]
+async fn import_model_archive(
+    app_handle: AppHandle,
+    path: String,
+    overwrite: Option<bool>,
+    import_id: Option<String>,
+) -> Result<model_archive::ArchiveReport, String> {
+    let path = paths::from_user_input(&path);
+    let job = jobs::start_with_id(&app_handle, "model_import", import_id);
+    tauri::async_runtime::spawn_blocking(move || {
+        let result = model_archive::import(&app_handle, &job, &path, overwrite.unwrap_or(false));
+        job.finish(result.as_ref().map(|_| ()).map_err(|e| e.clone()));
+        result
+    })
+    .await
+    .map_err(|e| format!("Failed to join model import task: {}", e))?
+}
+
+// ========== Sources Config Commands ==========
+
+/// Carrega a configuração de fontes de busca
+#[command]
+fn load_sources_config_command(app_handle: AppHandle) -> Result<SourcesConfig, String> {
+    load_sources_config(&app_handle)
+}
+
+/// Salva a configuração de fontes de busca
+#[command]
+fn save_sources_config_command(app_handle: AppHandle, config: SourcesConfig) -> Result<(), String> {
+    // Mudanças de proxy reiniciam o pool de browsers (o proxy do Chrome é fixo por processo)
+    save_sources_config(&app_handle, config)
+}
+
+/// Itens novos dos feeds RSS/Atom da categoria (ou de todas), sem repetir os já entregues
+#[command]
+async fn fetch_feeds(
+    app_handle: AppHandle,
+    category: Option<String>,
+    include_seen: Option<bool>,
+) -> Result<Vec<feeds::FeedItem>, String> {
+    let categories: Vec<String> = category.into_iter().collect();
+    feeds::fetch_feeds(&app_handle, &categories, include_seen.unwrap_or(false)).await
+}
+
+/// Exporta as categorias de fontes em OPML ou JSON
+#[command]
+fn export_sources_config(
+    app_handle: AppHandle,
+    dest_path: String,
+    format: sources_config::SourcesFileFormat,
+) -> Result<String, String> {
+    let config = load_sources_config(&app_handle)?;
+    let content = sources_config::export_categories(&config.categories, format)?;
+    fs::write(&dest_path, content)
+        .map_err(|e| format!("Failed to write sources export: {}", e))?;
+    log::info!("{} categorias de fontes exportadas para {}", config.categories.len(), dest_path);
+    Ok(dest_path)
+}
+
+/// Importa categorias de um arquivo OPML ou JSON, mesclando com as atuais (ou substituindo com `replace`)
+#[command]
+fn import_sources_config(
+    app_handle: AppHandle,
+    path: String,
+    replace: Option<bool>,
+) -> Result<sources_config::SourcesImportSummary, String> {
+    let content = fs::read_to_string(&path)
+        .map_err(|e| format!("Failed to read sources file: {}", e))?;
+    let imported = sources_config::import_categories(&content)?;
+    let mut config = load_sources_config(&app_handle)?;
+    let summary = sources_config::merge_categories(&mut config.categories, imported, replace.unwrap_or(false));
+    save_sources_config(&app_handle, config)?;
+    log::info!(
+        "Fontes importadas de {}: {} categorias novas, {} atualizadas",
+        path,
+        summary.added,
+        summary.updated
+    );
+    Ok(summary)
+}
+
+// ========== Ollama Installer Download Commands ==========
+
+/// Verifica se uma URL de download está disponível
+#[command]
+async fn check_download_url(url: String) -> Result<bool, String> {
+    let client = reqwest::Client::builder()
+        .timeout(Duration::from_secs(5))
+        .build()
+        .map_err(|e| format!("Failed to create HTTP client: {}", e))?;
+    
+    match client.head(&url).send().await {
+        Ok(response) => Ok(response.status().is_success()),
+        Err(_) => Ok(false),
+    }
+}
+
+/// Obtém o caminho do instalador local se existir
+#[command]
+fn get_local_installer_path(filename: String, app_handle: AppHandle) -> Result<Option<String>, String> {
+    // Tentar no diretório do executável (dev e produção)
+    // Em desenvolvimento, os arquivos estão em public/ relativo ao projeto
+    // Em produção, tentamos encontrar o arquivo em vários locais possíveis
+    if let Ok(exe_dir) = app_handle.path().executable_dir() {
+        // Tentar vários caminhos possíveis
+        let possible_paths = vec![
+            // Caminho relativo ao executável (dev) - subir até a raiz do projeto
+            exe_dir.parent()
+                .and_then(|p| p.parent())
+                .map(|p| p.join("public").join("installers").join(&filename)),
+            // Caminho direto do executável (dev)
+            exe_dir.parent()
+                .map(|p| p.join("public").join("installers").join(&filename)),
+            // Caminho absoluto do workspace (dev) - tentar encontrar a raiz
+            exe_dir.parent()
+                .and_then(|p| p.parent())
+                .and_then(|p| p.parent())
+                .map(|p| p.join("public").join("installers").join(&filename)),
+            // Em produção, arquivos podem estar no diretório do executável
+            Some(exe_dir.join("installers").join(&filename)),
+            // Ou no diretório pai do executável
+            exe_dir.parent()
+                .map(|p| p.join("installers").join(&filename)),
+        ];
+        
+        for path_opt in possible_paths {
+            if let Some(path) = path_opt {
+                if path.exists() {
+                    return Ok(Some(path.to_string_lossy().to_string()));
+                }
+            }
+        }
+    }
+    
+    Ok(None)
+}
+
+/// Faz download do instalador da URL oficial ou usa fallback local
+#[command]
+async fn download_installer(
+    url: String,
+    filename: String,
+    window: Window,
+    app_handle: AppHandle,
+) -> Result<String, String> {
+    use std::io::Write;
+    use futures_util::StreamExt;
+    
+    // Primeiro, tentar usar instalador local como fallback
+    if let Some(local_path) = get_local_installer_path(filename.clone(), app_handle.clone())? {
+        let local_path_buf = PathBuf::from(&local_path);
+        if local_path_buf.exists() {
+            // Copiar para app_data_dir/installers
+            let app_data_dir = app_handle.path().app_data_dir()
+                .map_err(|e| format!("Failed to get app data dir: {}", e))?;
+            let installers_dir = app_data_dir.join("installers");
+            
+            if !installers_dir.exists() {
+                fs::create_dir_all(&installers_dir)
+                    .map_err(|e| format!("Failed to create installers directory: {}", e))?;
+            }
+            
+            let dest_path = installers_dir.join(&filename);
+            fs::copy(&local_path_buf, &dest_path)
+                .map_err(|e| format!("Failed to copy local installer: {}", e))?;
+            
+            window.emit("installer-download-progress", serde_json::json!({
+                "progress": 100,
+                "status": "Concluído (versão local)"
+            })).ok();
+            
+            return Ok(dest_path.to_string_lossy().to_string());
+        }
+    }
+    
+    // Fazer download da URL
+    let client = reqwest::Client::builder()
+        .timeout(Duration::from_secs(300)) // 5 minutos de timeout
+        .build()
+        .map_err(|e| format!("Failed to create HTTP client: {}", e))?;
+    
+    let response = client
+        .get(&url)
+        .send()
+        .await
+        .map_err(|e| format!("Failed to download installer: {}", e))?;
+    
+    if !response.status().is_success() {
+        return Err(format!("Download failed with status: {}", response.status()));
+    }
+    
+    // Obter tamanho total do arquivo
+    let total_size = response.content_length().unwrap_or(0);
+    
+    // Criar diretório de instaladores
+    let app_data_dir = app_handle.path().app_data_dir()
+        .map_err(|e| format!("Failed to get app data dir: {}", e))?;
+    let installers_dir = app_data_dir.join("installers");
+    
+    if !installers_dir.exists() {
+        fs::create_dir_all(&installers_dir)
+            .map_err(|e| format!("Failed to create installers directory: {}", e))?;
+    }
+    
+    let dest_path = installers_dir.join(&filename);
+    let mut file = fs::File::create(&dest_path)
+        .map_err(|e| format!("Failed to create file: {}", e))?;
+    
+    let mut downloaded: u64 = 0;
+    let mut stream = response.bytes_stream();
+    
+    while let Some(item) = stream.next().await {
+        let chunk = item.map_err(|e| format!("Failed to read chunk: {}", e))?;
+        file.write_all(&chunk)
+            .map_err(|e| format!("Failed to write chunk: {}", e))?;
+        
+        downloaded += chunk.len() as u64;
+        
+        // Emitir progresso
+        let progress = if total_size > 0 {
+            (downloaded * 100) / total_size
+        } else {
+            0
+        };
+        
+        window.emit("installer-download-progress", serde_json::json!({
+            "progress": progress,
+            "downloaded": downloaded,
+            "total": total_size,
+            "status": format!("Baixando... {}%", progress)
+        })).ok();
+    }
+    
+    window.emit("installer-download-progress", serde_json::json!({
+        "progress": 100,
+        "status": "Download concluído"
+    })).ok();
+    
+    log::info!("Instalador baixado para: {:?}", dest_path);
+    Ok(dest_path.to_string_lossy().to_string())
+}
+
+/// Executa o instalador baixado
+#[command]
+fn run_installer(file_path: String) -> Result<(), String> {
+    let path = paths::from_user_input(&file_path);
+    
+    if !path.exists() {
+        return Err(format!("Instalador não encontrado: {}", file_path));
     }
     
     #[cfg(target_os = "windows")]
     {
-        use std::os::windows::process::CommandExt;
-        const CREATE_NO_WINDOW: u32 = 0x08000000;
-        cmd.creation_flags(CREATE_NO_WINDOW);
+        // No Windows, executar o .exe diretamente
+        Command::new(&path)
+            .spawn()
+            .map_err(|e| format!("Failed to run installer: {}", e))?;
     }
     
-    // Spawn process
-    let child = cmd.spawn()
-        .map_err(|e| format!("Failed to spawn MCP server '{}': {}", name, e))?;
+    #[cfg(target_os = "linux")]
+    {
+        // No Linux, dar permissão de execução e executar
+        use std::os::unix::fs::PermissionsExt;
+        let mut perms = fs::metadata(&path)
+            .map_err(|e| format!("Failed to get file metadata: {}", e))?
+            .permissions();
+        perms.set_mode(0o755);
+        fs::set_permissions(&path, perms)
+            .map_err(|e| format!("Failed to set executable permissions: {}", e))?;
+        
+        Command::new(&path)
+            .spawn()
+            .map_err(|e| format!("Failed to run installer: {}", e))?;
+    }
     
-    let pid = child.id();
+    #[cfg(target_os = "macos")]
+    {
+        // No macOS, executar o .zip (precisa ser extraído primeiro)
+        // Por enquanto, apenas abrir o arquivo
+        Command::new("open")
+            .arg(&path)
+            .spawn()
+            .map_err(|e| format!("Failed to open installer: {}", e))?;
+    }
     
-    // Create process handle with request ID counter
-    let handle = McpProcessHandle {
-        child,
-        request_id: Arc::new(Mutex::new(0)),
+    log::info!("Instalador executado: {:?}", path);
+    Ok(())
+}
+
+/// Verifica se o instalador já foi baixado
+#[command]
+fn get_downloaded_installer_path(filename: String, app_handle: AppHandle) -> Result<Option<String>, String> {
+    let app_data_dir = app_handle.path().app_data_dir()
+        .map_err(|e| format!("Failed to get app data dir: {}", e))?;
+    let installer_path = app_data_dir.join("installers").join(&filename);
+    
+    if installer_path.exists() {
+        Ok(Some(installer_path.to_string_lossy().to_string()))
+    } else {
+        Ok(None)
+    }
+}
+
+// ========== Ollama Update Commands ==========
+
+/// Tempo máximo esperando o instalador concluir a atualização
+const OLLAMA_UPGRADE_TIMEOUT_SECS: u64 = 600;
+
+/// Versão do Ollama instalado (CLI) ou, na falta dele, do servidor em execução
+#[command]
+async fn get_ollama_version(app_handle: AppHandle) -> Result<Option<String>, String> {
+    if let Some(version) = ollama_update::installed_version() {
+        return Ok(Some(version));
+    }
+    Ok(ollama_update::server_version(&settings::current(&app_handle).ollama_url).await)
+}
+
+/// Compara a versão instalada com a última release do Ollama no GitHub
+#[command]
+async fn check_ollama_update() -> Result<ollama_update::OllamaUpdateInfo, String> {
+    let installed = get_ollama_version().await?;
+    ollama_update::check_update(installed).await
+}
+
+#[derive(serde::Serialize)]
+struct OllamaUpgradeResult {
+    previous_version: Option<String>,
+    installed_version: Option<String>,
+    /// A versão instalada alcançou a última release
+    upgraded: bool,
+    /// O servidor respondeu após a atualização
+    running: bool,
+}
+
+fn emit_upgrade_progress(window: &Window, stage: &str, message: &str) {
+    window.emit("ollama-upgrade-progress", serde_json::json!({
+        "stage": stage,
+        "message": message,
+    })).ok();
+}
+
+/// Baixa e executa o instalador da última versão do Ollama e verifica o servidor em seguida.
+/// Emite `ollama-upgrade-progress` por etapa e `installer-download-progress` durante o download.
+#[command]
+async fn upgrade_ollama(window: Window, app_handle: AppHandle) -> Result<OllamaUpgradeResult, String> {
+    emit_upgrade_progress(&window, "checking", "Verificando a última versão");
+    let info = check_ollama_update().await?;
+    let previous_version = info.installed_version.clone();
+    if previous_version.is_none() {
+        return Err("Ollama não está instalado".to_string());
+    }
+    if !info.update_available {
+        return Err(format!("Ollama já está na versão mais recente ({})", info.latest_version));
+    }
+    let (Some(url), Some(filename)) = (info.installer_url, info.installer_filename) else {
+        return Err("Atualização automática não suportada nesta plataforma".to_string());
     };
     
-    // Store in map
-    processes_map.insert(name, handle);
+    emit_upgrade_progress(&window, "downloading", &format!("Baixando Ollama {}", info.latest_version));
+    let installer_path = download_installer(url, filename, window.clone(), app_handle.clone()).await?;
+    
+    // Os instaladores não substituem binários em uso
+    emit_upgrade_progress(&window, "stopping", "Encerrando o servidor do Ollama");
+    ollama_server::stop_spawned();
+    stop_ollama_processes();
+    wait_for_ollama(&app_handle, false).await;
+    
+    emit_upgrade_progress(&window, "installing", "Executando o instalador");
+    run_installer(installer_path)?;
+    
+    emit_upgrade_progress(&window, "verifying", "Aguardando a conclusão da instalação");
+    let deadline = tokio::time::Instant::now() + Duration::from_secs(OLLAMA_UPGRADE_TIMEOUT_SECS);
+    let mut installed_version = previous_version.clone();
+    let upgraded = loop {
+        tokio::time::sleep(Duration::from_secs(2)).await;
+        installed_version = ollama_update::installed_version().or(installed_version);
+        if let Some(version) = &installed_version {
+            if !ollama_update::is_newer(&info.latest_version, version) {
+                break true;
+            }
+        }
+        if tokio::time::Instant::now() >= deadline {
+            break false;
+        }
+    };
     
-    Ok(pid)
+    // Alguns instaladores já sobem o servidor (serviço/app da bandeja)
+    let running = if check_ollama_running(app_handle.clone()).await {
+        true
+    } else {
+        start_ollama_server(app_handle.clone())?;
+        wait_for_ollama(&app_handle, true).await
+    };
+    
+    let message = if upgraded {
+        format!("Ollama atualizado para {}", info.latest_version)
+    } else {
+        "A instalação não foi concluída a tempo".to_string()
+    };
+    emit_upgrade_progress(&window, if upgraded { "done" } else { "failed" }, &message);
+    log::info!("[OllamaUpdate] {} (servidor respondendo: {})", message, running);
+    
+    Ok(OllamaUpgradeResult { previous_version, installed_version, upgraded, running })
 }
 
-#[command]
-fn list_mcp_server_status(
-    processes: State<'_, McpProcessMap>,
-    app_handle: AppHandle,
-) -> Result<Vec<McpServerStatus>, String> {
-    let mut processes_map = processes.lock()
-        .map_err(|e| format!("Failed to lock processes map: {}", e))?;
+// ========== Export & Backup Commands ==========
+
+/// Nível de compressão padrão do backup (Deflate: 0 = sem compressão, 9 = máxima)
+const EXPORT_DEFAULT_COMPRESSION_LEVEL: i32 = 6;
+/// Intervalo mínimo de bytes entre eventos `export-progress`
+const EXPORT_PROGRESS_STEP_BYTES: u64 = 4 * 1024 * 1024;
+
+/// Payload do evento `export-progress`
+#[derive(serde::Serialize, Clone, Debug)]
+struct ExportProgress {
+    bytes_done: u64,
+    bytes_total: u64,
+    files_done: usize,
+    files_total: usize,
+    current_file: Option<String>,
+}
+
+/// Conteúdo de uma entrada do backup: arquivo lido em streaming ou texto gerado na hora
+enum ExportSource {
+    File(PathBuf),
+    Generated(String),
+}
+
+/// Arquivos que entram no backup (nome no ZIP, origem e tamanho)
+fn collect_export_entries(app_handle: &AppHandle, app_data_dir: &std::path::Path) -> Result<Vec<(String, ExportSource, u64)>, String> {
+    use walkdir::WalkDir;
     
-    let config = load_mcp_config(app_handle)?;
-    let mut statuses = Vec::new();
+    let mut entries = Vec::new();
     
-    for (name, _) in config.mcp_servers {
-        let status = if let Some(handle) = processes_map.get_mut(&name) {
-            // Check if process is still alive by trying to get its status
-            match handle.child.try_wait() {
-                Ok(Some(_)) => {
-                    // Process finished, remove from map
-                    processes_map.remove(&name);
-                    McpServerStatus {
-                        name: name.clone(),
-                        status: "stopped".to_string(),
-                        pid: None,
-                    }
-                },
-                Ok(None) => McpServerStatus {
-                    name: name.clone(),
-                    status: "running".to_string(),
-                    pid: Some(handle.child.id()),
-                },
-                Err(_) => McpServerStatus {
-                    name: name.clone(),
-                    status: "error".to_string(),
-                    pid: None,
-                },
+    // 1. Pasta chats/ inteira
+    let chats_dir = get_chats_dir(app_handle)?;
+    for entry in WalkDir::new(&chats_dir) {
+        let entry = entry.map_err(|e| format!("Failed to read directory entry: {}", e))?;
+        let path = entry.path();
+        if !path.is_file() {
+            continue;
+        }
+        // Obter caminho relativo a partir de chats_dir
+        let relative_path = path.strip_prefix(&chats_dir)
+            .map_err(|e| format!("Failed to get relative path: {}", e))?;
+        let size = entry.metadata().map(|m| m.len()).unwrap_or(0);
+        entries.push((paths::zip_entry_name("chats", relative_path), ExportSource::File(path.to_path_buf()), size));
+    }
+    
+    // 2-4. tasks.json, sources.json (padrão se não existir) e settings.json
+    for name in ["tasks.json", "sources.json", "settings.json"] {
+        let path = app_data_dir.join(name);
+        if let Ok(metadata) = fs::metadata(&path) {
+            entries.push((name.to_string(), ExportSource::File(path), metadata.len()));
+        } else if name == "sources.json" {
+            let default_json = serde_json::to_string_pretty(&SourcesConfig::default())
+                .map_err(|e| format!("Failed to serialize default sources config: {}", e))?;
+            let size = default_json.len() as u64;
+            entries.push((name.to_string(), ExportSource::Generated(default_json), size));
+        }
+    }
+    
+    Ok(entries)
+}
+
+/// Escreve o backup copiando cada arquivo em blocos (sem carregar arquivos inteiros na memória)
+fn write_export_zip(
+    app_handle: &AppHandle,
+    zip_path: &std::path::Path,
+    entries: Vec<(String, ExportSource, u64)>,
+    compression_level: i32,
+) -> Result<(), String> {
+    let file = fs::File::create(zip_path)
+        .map_err(|e| format!("Failed to create ZIP file: {}", e))?;
+    let mut zip = ZipWriter::new(std::io::BufWriter::new(file));
+    let options = if compression_level == 0 {
+        FileOptions::default().compression_method(CompressionMethod::Stored)
+    } else {
+        FileOptions::default()
+            .compression_method(CompressionMethod::Deflated)
+            .compression_level(Some(compression_level))
+    }
+    .unix_permissions(0o755)
+    .large_file(true);
+    
+    let mut progress = ExportProgress {
+        bytes_done: 0,
+        bytes_total: entries.iter().map(|(_, _, size)| size).sum(),
+        files_done: 0,
+        files_total: entries.len(),
+        current_file: None,
+    };
+    let mut last_emitted = 0u64;
+    let mut buffer = vec![0u8; 64 * 1024];
+    
+    for (name, source, _) in entries {
+        zip.start_file(name.as_str(), options)
+            .map_err(|e| format!("Failed to add file to ZIP: {}", e))?;
+        progress.current_file = Some(name.clone());
+        
+        match source {
+            ExportSource::Generated(content) => {
+                zip.write_all(content.as_bytes())
+                    .map_err(|e| format!("Failed to write {} to ZIP: {}", name, e))?;
+                progress.bytes_done += content.len() as u64;
             }
-        } else {
-            McpServerStatus {
-                name: name.clone(),
-                status: "stopped".to_string(),
-                pid: None,
+            ExportSource::File(path) => {
+                let mut input = fs::File::open(&path)
+                    .map_err(|e| format!("Failed to read file {:?}: {}", path, e))?;
+                loop {
+                    let read = input.read(&mut buffer)
+                        .map_err(|e| format!("Failed to read file {:?}: {}", path, e))?;
+                    if read == 0 {
+                        break;
+                    }
+                    zip.write_all(&buffer[..read])
+                        .map_err(|e| format!("Failed to write {} to ZIP: {}", name, e))?;
+                    progress.bytes_done += read as u64;
+                    if progress.bytes_done - last_emitted >= EXPORT_PROGRESS_STEP_BYTES {
+                        last_emitted = progress.bytes_done;
+                        let _ = app_handle.emit("export-progress", &progress);
+                    }
+                }
             }
-        };
+        }
         
-        statuses.push(status);
+        progress.files_done += 1;
+        let _ = app_handle.emit("export-progress", &progress);
     }
     
-    Ok(statuses)
+    // Finalizar ZIP
+    zip.finish()
+        .map_err(|e| format!("Failed to finalize ZIP: {}", e))?
+        .flush()
+        .map_err(|e| format!("Failed to finalize ZIP: {}", e))?;
+    Ok(())
 }
 
+/// Exporta todos os dados do app (chats, tasks, sources, settings) para um arquivo ZIP.
+/// `compression_level` vai de 0 (sem compressão, mais rápido) a 9; progresso via `export-progress`.
 #[command]
-fn restart_all_mcp_servers(
-    processes: State<'_, McpProcessMap>,
-    app_handle: AppHandle,
-) -> Result<Vec<String>, String> {
-    let config = load_mcp_config(app_handle)?;
-    let mut started = Vec::new();
+async fn export_all_data(app_handle: AppHandle, compression_level: Option<i32>) -> Result<String, String> {
+    let level = compression_level.unwrap_or(EXPORT_DEFAULT_COMPRESSION_LEVEL);
+    if !(0..=9).contains(&level) {
+        return Err(format!("Nível de compressão inválido: {} (use 0 a 9)", level));
+    }
     
-    let mut processes_map = processes.lock()
-        .map_err(|e| format!("Failed to lock processes map: {}", e))?;
+    let app_data_dir = app_handle.path().app_data_dir()
+        .map_err(|e| format!("Failed to get app data dir: {}", e))?;
     
-    // First, kill all existing processes
-    for (_name, mut handle) in processes_map.drain() {
-        let _ = handle.child.kill();
-        let _ = handle.child.wait();
+    // Criar nome do arquivo com timestamp
+    let timestamp = Utc::now().format("%Y%m%d_%H%M%S");
+    let zip_path = app_data_dir.join(format!("ollahub_backup_{}.zip", timestamp));
+    
+    let entries = collect_export_entries(&app_handle, &app_data_dir)?;
+    
+    // Compressão roda fora do runtime async (exports grandes levam minutos)
+    let worker_app = app_handle.clone();
+    let worker_path = zip_path.clone();
+    let result = tokio::task::spawn_blocking(move || {
+        write_export_zip(&worker_app, &worker_path, entries, level)
+    })
+    .await
+    .map_err(|e| format!("Export task failed: {}", e))?;
+    
+    if let Err(e) = result {
+        let _ = fs::remove_file(&zip_path);
+        return Err(e);
     }
     
-    // Now start all servers from config
-    for (name, server_config) in config.mcp_servers {
-        // Build command
-        let mut cmd = Command::new(&server_config.command);
-        cmd.args(&server_config.args);
-        cmd.stdin(Stdio::piped());
-        cmd.stdout(Stdio::piped());
-        cmd.stderr(Stdio::piped());
+    log::info!("Backup completo exportado para: {}", zip_path.display());
+    Ok(format!("{}", zip_path.display()))
+}
+
+// ========== Cold Storage Commands ==========
+
+/// Move sessões sem atividade há `older_than_days` dias para arquivos ZIP mensais em
+/// `archive/`, mantendo no banco apenas um índice leve (título e palavras-chave)
+#[command]
+async fn archive_old_sessions(app_handle: AppHandle, older_than_days: Option<u32>) -> Result<cold_storage::ArchiveReport, String> {
+    let days = older_than_days.unwrap_or(cold_storage::DEFAULT_ARCHIVE_AFTER_DAYS).max(1);
+    let cutoff = Utc::now() - chrono::Duration::days(days as i64);
+    let db = db::Database::new(&app_handle)
+        .map_err(|e| format!("Failed to open database: {}", e))?;
+    let chats_dir = get_chats_dir(&app_handle)?;
+    let archive_dir = cold_storage::archive_dir(&app_handle)?;
+    
+    let sessions = db.list_sessions_updated_before(cutoff)
+        .map_err(|e| format!("Failed to list sessions: {}", e))?;
+    
+    let mut report = cold_storage::ArchiveReport::default();
+    let mut pending = Vec::new();
+    let archived_at = Utc::now();
+    
+    for session in sessions {
+        let snapshot = match db.snapshot_session(&session.id) {
+            Ok(Some(snapshot)) => snapshot,
+            Ok(None) => continue,
+            Err(e) => {
+                report.errors.push(format!("{}: {}", session.id, e));
+                continue;
+            }
+        };
+        let keywords = cold_storage::extract_keywords(cold_storage::snapshot_texts(&snapshot));
+        let message_count = snapshot.children.get("messages").map(|m| m.len()).unwrap_or(0) as i64;
+        let payload = cold_storage::ArchivedPayload {
+            archived_at,
+            snapshot,
+            legacy_json: fs::read_to_string(chats_dir.join(format!("{}.json", session.id))).ok(),
+        };
+        let bytes = match serde_json::to_vec(&payload) {
+            Ok(bytes) => bytes,
+            Err(e) => {
+                report.errors.push(format!("{}: {}", session.id, e));
+                continue;
+            }
+        };
+        let entry = db::ArchivedSession {
+            archive_file: cold_storage::archive_file_name(&session.updated_at),
+            session_id: session.id,
+            title: session.title,
+            emoji: session.emoji,
+            created_at: session.created_at,
+            updated_at: session.updated_at,
+            archived_at,
+            message_count,
+        };
+        pending.push((entry.archive_file.clone(), (entry, keywords, bytes)));
+    }
+    
+    for (file_name, items) in cold_storage::group_by_file(pending) {
+        let additions: Vec<(String, Vec<u8>)> = items
+            .iter()
+            .map(|(entry, _, bytes)| (cold_storage::entry_name(&entry.session_id), bytes.clone()))
+            .collect();
         
-        // Set environment variables if provided
-        if let Some(env_vars) = &server_config.env {
-            for (key, value) in env_vars {
-                cmd.env(key, value);
+        // O arquivo precisa estar gravado antes de qualquer remoção do banco
+        match cold_storage::update_archive(&archive_dir.join(&file_name), &HashSet::new(), &additions) {
+            Ok(size) => report.bytes_written += size,
+            Err(e) => {
+                report.errors.push(format!("{}: {}", file_name, e));
+                continue;
             }
         }
         
-        #[cfg(target_os = "windows")]
-        {
-            use std::os::windows::process::CommandExt;
-            const CREATE_NO_WINDOW: u32 = 0x08000000;
-            cmd.creation_flags(CREATE_NO_WINDOW);
-        }
-        
-        // Spawn process
-        match cmd.spawn() {
-            Ok(child) => {
-                let handle = McpProcessHandle {
-                    child,
-                    request_id: Arc::new(Mutex::new(0)),
-                };
-                processes_map.insert(name.clone(), handle);
-                started.push(name);
+        for (entry, keywords, _) in &items {
+            if let Err(e) = db.mark_session_archived(entry, keywords) {
+                report.errors.push(format!("{}: {}", entry.session_id, e));
+                continue;
             }
-            Err(e) => {
-                eprintln!("Failed to start MCP server '{}': {}", name, e);
+            let json_path = chats_dir.join(format!("{}.json", entry.session_id));
+            if json_path.exists() {
+                if let Err(e) = fs::remove_file(&json_path) {
+                    log::warn!("Failed to remove archived session JSON {}: {}", entry.session_id, e);
+                }
             }
+            report.archived += 1;
         }
+        report.archive_files.push(file_name);
     }
     
-    Ok(started)
+    report.archive_files.sort();
+    log::info!("Cold storage: {} sessões arquivadas em {} arquivo(s)", report.archived, report.archive_files.len());
+    Ok(report)
 }
 
-// MCP JSON-RPC Communication Commands
+/// Sessões que a política de retenção removeria agora (a informada ou, sem ela, a configurada),
+/// para conferir antes de ativá-la
 #[command]
-fn list_mcp_tools(
-    processes: State<'_, McpProcessMap>,
-    server_name: String,
-) -> Result<Vec<McpTool>, String> {
-    let mut processes_map = processes.lock()
-        .map_err(|e| format!("Failed to lock processes map: {}", e))?;
+fn preview_retention_cleanup(
+    app_handle: AppHandle,
+    policy: Option<retention::RetentionSettings>,
+) -> Result<retention::RetentionPreview, String> {
+    let policy = policy.unwrap_or_else(|| settings::current(&app_handle).retention);
+    let mut errors = Vec::new();
+    policy.validate(&mut errors);
+    if !errors.is_empty() {
+        return Err(errors.join("; "));
+    }
+    retention::preview(&app_handle, policy)
+}
+
+#[command]
+fn list_archived_sessions(app_handle: AppHandle, limit: Option<usize>) -> Result<Vec<db::ArchivedSession>, String> {
+    let db = db::Database::new(&app_handle)
+        .map_err(|e| format!("Failed to open database: {}", e))?;
+    db.search_archived_sessions("", limit.unwrap_or(200))
+        .map_err(|e| format!("Failed to list archived sessions: {}", e))
+}
+
+/// Busca no índice leve das sessões arquivadas (título e palavras das mensagens)
+#[command]
+fn search_archived_sessions(app_handle: AppHandle, query: String, limit: Option<usize>) -> Result<Vec<db::ArchivedSession>, String> {
+    let db = db::Database::new(&app_handle)
+        .map_err(|e| format!("Failed to open database: {}", e))?;
+    db.search_archived_sessions(&query, limit.unwrap_or(50))
+        .map_err(|e| format!("Failed to search archived sessions: {}", e))
+}
+
+/// Traz uma sessão arquivada de volta ao banco (mensagens, anexos e JSON legado)
+#[command]
+async fn restore_from_archive(app_handle: AppHandle, session_id: String) -> Result<(), String> {
+    let db = db::Database::new(&app_handle)
+        .map_err(|e| format!("Failed to open database: {}", e))?;
+    let entry = db.get_archived_session(&session_id)
+        .map_err(|e| format!("Failed to read archive index: {}", e))?
+        .ok_or_else(|| format!("Session {} is not archived", session_id))?;
+    
+    let archive_path = cold_storage::archive_dir(&app_handle)?.join(&entry.archive_file);
+    let payload = cold_storage::read_payload(&archive_path, &session_id)?;
+    
+    db.restore_session_snapshot(&payload.snapshot)
+        .map_err(|e| format!("Failed to restore session: {}", e))?;
+    if let Some(json) = payload.legacy_json {
+        let json_path = get_chats_dir(&app_handle)?.join(format!("{}.json", session_id));
+        fs::write(&json_path, json)
+            .map_err(|e| format!("Failed to restore session JSON: {}", e))?;
+    }
     
-    let handle = processes_map.get_mut(&server_name)
-        .ok_or_else(|| format!("MCP server '{}' not found or not running", server_name))?;
+    db.remove_archived_session(&session_id)
+        .map_err(|e| format!("Failed to update archive index: {}", e))?;
+    let remove = HashSet::from([cold_storage::entry_name(&session_id)]);
+    if let Err(e) = cold_storage::update_archive(&archive_path, &remove, &[]) {
+        // A sessão já está no banco; a cópia antiga no arquivo é inofensiva
+        log::warn!("Failed to remove restored session from archive: {}", e);
+    }
     
-    list_mcp_tools_internal(handle)
+    log::info!("Restored session {} from {}", session_id, entry.archive_file);
+    Ok(())
 }
 
+// ========== Workspace Bundle Commands ==========
+
+/// Exporta MCP (sem segredos), fontes, presets, templates e configurações em um único JSON
 #[command]
-fn call_mcp_tool(
-    processes: State<'_, McpProcessMap>,
-    server_name: String,
-    tool_name: String,
-    arguments: serde_json::Value,
-) -> Result<serde_json::Value, String> {
-    let mut processes_map = processes.lock()
-        .map_err(|e| format!("Failed to lock processes map: {}", e))?;
+fn export_workspace_bundle(
+    app_handle: AppHandle,
+    state: State<'_, SettingsState>,
+) -> Result<String, String> {
+    let mut mcp_servers = load_mcp_config(app_handle.clone())?.mcp_servers;
+    let mut redacted_secrets = workspace_bundle::strip_secrets(&mut mcp_servers);
+    let mut sources = sources_config::load_sources_config(&app_handle)?;
+    redacted_secrets.extend(workspace_bundle::strip_source_secrets(&mut sources));
     
-    let handle = processes_map.get_mut(&server_name)
-        .ok_or_else(|| format!("MCP server '{}' not found or not running", server_name))?;
+    let db = db::Database::new(&app_handle)
+        .map_err(|e| format!("Failed to open database: {}", e))?;
     
-    // Increment request ID
-    let request_id = {
-        let mut id = handle.request_id.lock()
-            .map_err(|e| format!("Failed to lock request ID: {}", e))?;
-        *id += 1;
-        *id
+    let bundle = workspace_bundle::WorkspaceBundle {
+        format_version: workspace_bundle::BUNDLE_FORMAT_VERSION,
+        exported_at: Utc::now(),
+        mcp_servers,
+        sources: Some(sources),
+        prompt_presets: db.list_prompt_presets()
+            .map_err(|e| format!("Failed to list prompt presets: {}", e))?,
+        conversation_templates: db.list_conversation_templates()
+            .map_err(|e| format!("Failed to list conversation templates: {}", e))?,
+        settings: Some(state.lock()
+            .map(|s| s.clone())
+            .map_err(|e| format!("Failed to lock settings: {}", e))?),
+        redacted_secrets,
     };
     
-    // Build params for tools/call
-    let params = serde_json::json!({
-        "name": tool_name,
-        "arguments": arguments
-    });
+    let app_data_dir = app_handle.path().app_data_dir()
+        .map_err(|e| format!("Failed to get app data dir: {}", e))?;
+    let timestamp = Utc::now().format("%Y%m%d_%H%M%S");
+    let bundle_path = app_data_dir.join(format!("ollahub_workspace_{}.json", timestamp));
     
-    // Send tools/call request
-    send_jsonrpc_request(
-        &mut handle.child,
-        "tools/call",
-        Some(params),
-        request_id,
-    )?;
+    let json = serde_json::to_string_pretty(&bundle)
+        .map_err(|e| format!("Failed to serialize workspace bundle: {}", e))?;
+    fs::write(&bundle_path, json)
+        .map_err(|e| format!("Failed to write workspace bundle: {}", e))?;
     
-    // Read response (wait a moment for server to process)
-    std::thread::sleep(Duration::from_millis(200));
-    let response = read_jsonrpc_response(&mut handle.child, request_id, 30)?;
+    log::info!(
+        "Workspace exportado para {} ({} segredos removidos)",
+        bundle_path.display(),
+        bundle.redacted_secrets.len()
+    );
+    Ok(format!("{}", bundle_path.display()))
+}
+
+/// Importa um bundle de workspace: servidores MCP e presets/templates são mesclados
+/// (mesmo nome/id substitui), fontes e configurações são substituídas
+#[command]
+fn import_workspace_bundle(
+    app_handle: AppHandle,
+    state: State<'_, SettingsState>,
+    path: String,
+) -> Result<workspace_bundle::WorkspaceImportSummary, String> {
+    let content = fs::read_to_string(&path)
+        .map_err(|e| format!("Failed to read workspace bundle: {}", e))?;
+    let bundle: workspace_bundle::WorkspaceBundle = serde_json::from_str(&content)
+        .map_err(|e| format!("Failed to parse workspace bundle: {}", e))?;
+    
+    if bundle.format_version > workspace_bundle::BUNDLE_FORMAT_VERSION {
+        return Err(format!(
+            "Bundle criado por uma versão mais nova do OllaHub (formato {})",
+            bundle.format_version
+        ));
+    }
     
-    // Parse result from response
-    if let Some(error) = response.error {
-        return Err(format!("MCP server error: {} ({})", error.message, error.code));
+    // Validar as configurações antes de alterar qualquer coisa
+    if let Some(settings) = &bundle.settings {
+        settings.validate()?;
+    }
+    
+    let mut summary = workspace_bundle::WorkspaceImportSummary::default();
+    
+    if !bundle.mcp_servers.is_empty() {
+        let mut config = load_mcp_config(app_handle.clone())?;
+        summary.mcp_servers = bundle.mcp_servers.len();
+        summary.missing_secrets = workspace_bundle::merge_mcp_servers(&mut config.mcp_servers, bundle.mcp_servers);
+        save_mcp_config(app_handle.clone(), config)?;
+    }
+    
+    if let Some(mut sources) = bundle.sources {
+        let local = sources_config::load_sources_config(&app_handle)?;
+        let missing = workspace_bundle::restore_source_secrets(&mut sources, &local);
+        summary.missing_secrets.extend(missing);
+        sources_config::save_sources_config(&app_handle, sources)?;
+        summary.sources_imported = true;
+    }
+    
+    let db = db::Database::new(&app_handle)
+        .map_err(|e| format!("Failed to open database: {}", e))?;
+    for preset in &bundle.prompt_presets {
+        db.save_prompt_preset(preset)
+            .map_err(|e| format!("Failed to save prompt preset: {}", e))?;
+    }
+    summary.prompt_presets = bundle.prompt_presets.len();
+    for template in &bundle.conversation_templates {
+        db.save_conversation_template(template)
+            .map_err(|e| format!("Failed to save conversation template: {}", e))?;
+    }
+    summary.conversation_templates = bundle.conversation_templates.len();
+    
+    if let Some(settings) = bundle.settings {
+        let patch = serde_json::to_value(&settings)
+            .map_err(|e| format!("Failed to serialize settings: {}", e))?;
+        apply_settings_patch(&app_handle, &state, &patch)?;
+        summary.settings_applied = true;
+    }
+    
+    log::info!(
+        "Workspace importado de {} ({} segredos pendentes)",
+        path,
+        summary.missing_secrets.len()
+    );
+    Ok(summary)
+}
+
+// ========== Logs Commands ==========
+
+/// Obtém as últimas N linhas dos logs do sistema (incluindo arquivos rotacionados)
+#[command]
+fn get_recent_logs(app_handle: AppHandle, lines: usize) -> Result<Vec<String>, String> {
+    logging::recent_lines(&app_handle, lines)
+}
+
+/// Altera o nível de log em tempo de execução e o persiste nas configurações
+#[command]
+fn set_log_level(
+    app_handle: AppHandle,
+    state: State<'_, SettingsState>,
+    level: String,
+) -> Result<String, String> {
+    let filter = logging::parse_level(&level)
+        .ok_or_else(|| format!("Nível de log inválido: {}", level))?;
+    let patch = serde_json::json!({ "logging": { "level": filter.to_string().to_lowercase() } });
+    apply_settings_patch(&app_handle, &state, &patch)?;
+    logging::set_level(&level)?;
+    Ok(filter.to_string().to_lowercase())
+}
+
+/// Liga/desliga o evento `log-entry` do visualizador ao vivo; retorna as entradas recentes
+#[command]
+fn stream_logs(enabled: bool) -> Vec<logging::LogEntry> {
+    logging::set_streaming(enabled)
+}
+
+/// Gera um ZIP com os logs recentes e as specs do sistema para relatórios de bug
+#[command]
+async fn export_logs(app_handle: AppHandle, zip_path: String) -> Result<logging::LogExport, String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        let system_info = serde_json::json!({
+            "specs": get_system_specs(),
+            "os_version": System::long_os_version(),
+            "kernel_version": System::kernel_version(),
+            "resource_profile": resource_profile::current(),
+        });
+        logging::export(&app_handle, std::path::Path::new(&zip_path), &system_info)
+    })
+    .await
+    .map_err(|e| format!("Failed to join log export task: {}", e))?
+}
+
+/// Recebe logs do frontend e os imprime no terminal
+#[command]
+fn log_to_terminal(level: String, message: String) -> Result<(), String> {
+    match level.as_str() {
+        "info" => log::info!("{}", message),
+        "warn" => log::warn!("{}", message),
+        "error" => log::error!("{}", message),
+        "debug" => log::debug!("{}", message),
+        _ => log::info!("{}", message),
+    }
+    Ok(())
+}
+
+// ========== System Monitor Commands ==========
+
+/// Liga/desliga o monitor que emite `system-stats-v2` e ajusta intervalo e métricas.
+/// `enabled_metrics` vazio desliga o monitor (ex: painel oculto).
+#[command]
+fn configure_system_monitor(
+    app_handle: AppHandle,
+    interval_ms: Option<u64>,
+    enabled_metrics: Option<Vec<system_monitor::MonitorMetric>>,
+) -> system_monitor::MonitorConfig {
+    system_monitor::configure_monitor(&app_handle, interval_ms, enabled_metrics, false)
+}
+
+/// Obtém estatísticas do sistema em tempo real
+#[command]
+fn get_system_stats(
+    monitor_state: State<'_, Arc<Mutex<SystemMonitorState>>>,
+) -> Result<SystemStats, String> {
+    let mut monitor = monitor_state.lock()
+        .map_err(|e| format!("Failed to lock monitor state: {}", e))?;
+    
+    Ok(monitor.get_stats())
+}
+
+/// Obtém estatísticas detalhadas de uma GPU específica
+#[command]
+fn get_gpu_stats(gpu_id: Option<String>) -> Result<Option<GpuStats>, String> {
+    Ok(system_monitor::get_gpu_stats(gpu_id.as_deref()))
+}
+
+/// Série temporal de CPU, RAM, VRAM e memória dos processos do Ollama no intervalo
+/// pedido ("30m", "1h", "24h"; padrão: 30 minutos)
+#[command]
+fn get_metrics_history(
+    app_handle: AppHandle,
+    range: Option<String>,
+) -> Result<Vec<system_monitor::MetricSample>, String> {
+    let range = system_monitor::parse_range(range.as_deref().unwrap_or("30m"))?;
+    system_monitor::metrics_history(&app_handle, range)
+}
+
+// ========== Task Scheduler Commands ==========
+
+#[command]
+async fn create_task(
+    scheduler: State<'_, SchedulerState>,
+    label: String,
+    cron_schedule: String,
+    action: TaskAction,
+    post_actions: Option<Vec<TaskAction>>,
+) -> Result<String, String> {
+    use uuid::Uuid;
+    
+    if action.is_output_action() {
+        return Err("A ação principal precisa produzir conteúdo (WriteFile/CopyToClipboard só podem ser usadas em post_actions)".to_string());
+    }
+    
+    let task = SentinelTask {
+        id: Uuid::new_v4().to_string(),
+        label,
+        cron_schedule,
+        action,
+        post_actions: post_actions.unwrap_or_default(),
+        enabled: true,
+        last_run: None,
+        last_result: None,
+        missed_run_policy: None,
+        created_at: Utc::now(),
+        updated_at: Utc::now(),
+    };
+    
+    let mut sched = scheduler.lock().await;
+    sched.upsert_task(task.clone())?;
+    Ok(task.id)
+}
+
+#[command]
+async fn list_tasks(
+    scheduler: State<'_, SchedulerState>,
+) -> Result<Vec<SentinelTask>, String> {
+    let sched = scheduler.lock().await;
+    Ok(sched.list_tasks())
+}
+
+#[command]
+async fn update_task(
+    scheduler: State<'_, SchedulerState>,
+    task: SentinelTask,
+) -> Result<(), String> {
+    let mut sched = scheduler.lock().await;
+    let mut updated = task;
+    updated.updated_at = Utc::now();
+    sched.upsert_task(updated)
+}
+
+#[command]
+async fn delete_task(
+    scheduler: State<'_, SchedulerState>,
+    id: String,
+) -> Result<(), String> {
+    let mut sched = scheduler.lock().await;
+    sched.remove_task(&id)
+}
+
+#[command]
+async fn toggle_task(
+    scheduler: State<'_, SchedulerState>,
+    id: String,
+    enabled: bool,
+) -> Result<(), String> {
+    let mut sched = scheduler.lock().await;
+    if let Some(mut task) = sched.get_task(&id).cloned() {
+        task.enabled = enabled;
+        task.updated_at = Utc::now();
+        sched.upsert_task(task)
+    } else {
+        Err("Task not found".to_string())
+    }
+}
+
+/// Pausa ou retoma a execução das tasks agendadas
+#[command]
+async fn pause_scheduler(
+    app_handle: AppHandle,
+    scheduler: State<'_, SchedulerState>,
+    paused: bool,
+) -> Result<bool, String> {
+    let mut sched = scheduler.lock().await;
+    sched.set_paused(paused);
+    let _ = app_handle.emit("scheduler-paused-changed", paused);
+    Ok(paused)
+}
+
+/// Encerra o app de verdade (fechar a janela apenas a oculta no tray)
+#[command]
+fn quit_app(app_handle: AppHandle) {
+    log::info!("Encerrando aplicação a pedido do usuário");
+    app_handle.exit(0);
+}
+
+#[command]
+fn classify_intent(query: String) -> String {
+    use intent_classifier::{IntentClassifier, QueryIntent};
+    let intent = IntentClassifier::classify(&query);
+    match intent {
+        QueryIntent::Factual => "factual".to_string(),
+        QueryIntent::Conversational => "conversational".to_string(),
+        QueryIntent::Technical => "technical".to_string(),
+        QueryIntent::Opinion => "opinion".to_string(),
+        QueryIntent::Calculation => "calculation".to_string(),
+        QueryIntent::Unknown => "unknown".to_string(),
     }
-    
-    response.result
-        .ok_or_else(|| "No result in response".to_string())
 }
 
-// Helper function to list tools from a server (not a Tauri command, used internally)
-fn list_mcp_tools_internal(
-    handle: &mut McpProcessHandle,
-) -> Result<Vec<McpTool>, String> {
-    // Increment request ID
-    let request_id = {
-        let mut id = handle.request_id.lock()
-            .map_err(|e| format!("Failed to lock request ID: {}", e))?;
-        *id += 1;
-        *id
-    };
+/// Persiste a rodada no SQLite: sessão (criada ou atualizada), mensagens enviadas e a resposta
+/// do assistente. Retorna true se a resposta foi salva.
+fn persist_chat_turn(
+    db: &db::Database,
+    session_id: &str,
+    messages: &[Message],
+    assistant_content: &str,
+    assistant_metadata: Option<String>,
+    sent_at: DateTime<Utc>,
+) -> bool {
+    use db::{ChatSession, ChatMessage};
     
-    // Send tools/list request
-    send_jsonrpc_request(
-        &mut handle.child,
-        "tools/list",
-        None,
-        request_id,
-    )?;
+    let now = Utc::now();
     
-    // Read response (wait a moment for server to process)
-    std::thread::sleep(Duration::from_millis(200));
-    let response = read_jsonrpc_response(&mut handle.child, request_id, 10)?;
+    // Buscar sessão existente (novas sessões são criadas no início do chat_stream) ou criar nova
+    let session = match db.get_session(session_id) {
+        Ok(Some(mut existing)) => {
+            existing.updated_at = now;
+            existing
+        }
+        _ => ChatSession {
+            id: session_id.to_string(),
+            title: "Nova Conversa".to_string(),
+            emoji: emoji_picker::DEFAULT_EMOJI.to_string(),
+            created_at: sent_at,
+            updated_at: now,
+        }
+    };
     
-    // Parse tools from response
-    if let Some(error) = response.error {
-        return Err(format!("MCP server error: {} ({})", error.message, error.code));
+    if let Err(e) = db.create_session(&session) {
+        log::warn!("Erro ao salvar sessão: {}", e);
     }
     
-    let result = response.result
-        .ok_or_else(|| "No result in response".to_string())?;
-    
-    let tools_obj = result.get("tools")
-        .ok_or_else(|| "No 'tools' field in response".to_string())?
-        .as_array()
-        .ok_or_else(|| "Tools field is not an array".to_string())?;
-    
-    let mut tools = Vec::new();
-    for tool_json in tools_obj {
-        let tool: McpTool = serde_json::from_value(tool_json.clone())
-            .map_err(|e| format!("Failed to parse tool: {}", e))?;
-        tools.push(tool);
+    // Salvar mensagens do usuário (o histórico enviado junto já está gravado: pula as mensagens
+    // com ID e as que têm o mesmo papel e conteúdo de uma gravada)
+    let mut stored: Vec<(String, String)> = db.get_messages(session_id)
+        .map(|stored| stored.into_iter().map(|m| (m.role, m.content)).collect())
+        .unwrap_or_default();
+    for msg in messages {
+        if msg.id.is_some() {
+            continue;
+        }
+        if let Some(index) = stored.iter().position(|(role, content)| *role == msg.role && *content == msg.content) {
+            stored.remove(index);
+            continue;
+        }
+        let chat_msg = ChatMessage {
+            id: None,
+            session_id: session_id.to_string(),
+            role: msg.role.clone(),
+            content: msg.content.clone(),
+            metadata: msg.metadata.as_ref().and_then(|m| serde_json::to_string(m).ok()),
+            created_at: sent_at,
+        };
+        
+        if let Err(e) = db.add_message(&chat_msg) {
+            log::warn!("Erro ao salvar mensagem: {}", e);
+        }
     }
     
-    Ok(tools)
+    // Salvar mensagem final do assistente
+    if assistant_content.is_empty() {
+        return false;
+    }
+    let assistant_msg = ChatMessage {
+        id: None,
+        session_id: session_id.to_string(),
+        role: "assistant".to_string(),
+        content: assistant_content.to_string(),
+        metadata: assistant_metadata,
+        created_at: now,
+    };
+    match db.add_message(&assistant_msg) {
+        Ok(_) => true,
+        Err(e) => {
+            log::warn!("Erro ao salvar mensagem do assistente: {}", e);
+            false
+        }
+    }
 }
 
-#[command]
-fn get_all_mcp_tools(
-    processes: State<'_, McpProcessMap>,
-    app_handle: AppHandle,
-) -> Result<Vec<McpToolInfo>, String> {
-    let mut processes_map = processes.lock()
-        .map_err(|e| format!("Failed to lock processes map: {}", e))?;
-    
-    let config = load_mcp_config(app_handle)?;
-    let mut all_tools = Vec::new();
+/// Grava o checkpoint da resposta em andamento (falhas só são logadas: o streaming continua)
+fn checkpoint_pending_message(app_handle: &AppHandle, pending: &db::PendingMessage) {
+    if let Err(e) = db::Database::new(app_handle).and_then(|db| db.save_pending_message(pending)) {
+        log::warn!("Erro ao gravar checkpoint da resposta: {}", e);
+    }
+}
+
+/// Finaliza as respostas interrompidas na execução anterior, salvando o conteúdo parcial.
+/// Deve rodar antes de qualquer `chat_stream` (os checkpoints restantes são todos órfãos).
+fn recover_pending_messages(app_handle: &AppHandle) -> Vec<SessionRecoveredEvent> {
+    let db = match db::Database::new(app_handle) {
+        Ok(db) => db,
+        Err(e) => {
+            log::warn!("Erro ao abrir banco para recuperar respostas: {}", e);
+            return Vec::new();
+        }
+    };
+    let pending = match db.get_pending_messages() {
+        Ok(pending) => pending,
+        Err(e) => {
+            log::warn!("Erro ao buscar respostas interrompidas: {}", e);
+            return Vec::new();
+        }
+    };
     
-    // Get tools from each running server
-    for (server_name, _) in config.mcp_servers {
-        if let Some(handle) = processes_map.get_mut(&server_name) {
-            match list_mcp_tools_internal(handle) {
-                Ok(tools) => {
-                    for tool in tools {
-                        all_tools.push(McpToolInfo {
-                            server_name: server_name.clone(),
-                            tool,
-                        });
-                    }
-                }
-                Err(e) => {
-                    eprintln!("Failed to list tools from '{}': {}", server_name, e);
-                }
-            }
+    let mut recovered = Vec::new();
+    for item in pending {
+        let messages: Vec<Message> = serde_json::from_str(&item.request_messages).unwrap_or_default();
+        let metadata = serde_json::json!({
+            "recovered": true,
+            "model": item.model,
+            "interrupted_at": item.updated_at,
+        });
+        persist_chat_turn(
+            &db,
+            &item.session_id,
+            &messages,
+            &item.content,
+            Some(metadata.to_string()),
+            item.started_at,
+        );
+        if let Err(e) = db.delete_pending_message(&item.session_id) {
+            log::warn!("Erro ao remover checkpoint recuperado: {}", e);
+            continue;
         }
+        log::info!(
+            "Resposta interrompida recuperada na sessão {} ({} caracteres)",
+            item.session_id,
+            item.content.len()
+        );
+        recovered.push(SessionRecoveredEvent {
+            partial_chars: item.content.chars().count(),
+            session_id: item.session_id,
+            model: item.model,
+            tokens: item.tokens,
+            interrupted_at: item.updated_at,
+        });
     }
+    recovered
+}
+
+/// Tempo máximo da geração do título (roda em paralelo ao streaming, então pode esperar mais)
+const SESSION_TITLE_TIMEOUT_SECS: u64 = 30;
+
+/// Título provisório: primeiras palavras da pergunta
+fn fallback_session_title(user_input: &str) -> String {
+    let title = user_input.split_whitespace().take(5).collect::<Vec<_>>().join(" ");
+    if title.is_empty() { "Nova Conversa".to_string() } else { title }
+}
+
+/// Gera título e emoji da nova sessão em background: atualiza o SQLite e emite `chat-created`
+fn spawn_session_title(window: Window, app_handle: AppHandle, session_id: String, model: String, user_input: String) {
+    use ollama_client::OllamaClient;
     
-    Ok(all_tools)
+    tauri::async_runtime::spawn(async move {
+        // Título no idioma da pergunta (ou no idioma configurado, se não detectado)
+        let title_language = translation::detect_language(&user_input)
+            .unwrap_or_else(|| settings::current(&app_handle).language);
+        
+        let ollama_client = OllamaClient::new(Some(settings::current(&app_handle).ollama_url));
+        let title = match tokio::time::timeout(
+            tokio::time::Duration::from_secs(SESSION_TITLE_TIMEOUT_SECS),
+            ollama_client.generate_title(&model, &user_input, &title_language)
+        ).await {
+            Ok(Ok(t)) => t,
+            Ok(Err(e)) => {
+                log::warn!("Erro ao gerar título: {}. Usando fallback.", e);
+                fallback_session_title(&user_input)
+            },
+            Err(_) => {
+                log::warn!("Timeout ao gerar título. Usando fallback.");
+                fallback_session_title(&user_input)
+            }
+        };
+        
+        // Emoji por similaridade de embeddings (inferência ONNX fora do runtime async)
+        let emoji = match app_handle.path().app_data_dir() {
+            Ok(app_data_dir) => {
+                let title_for_emoji = title.clone();
+                tauri::async_runtime::spawn_blocking(move || emoji_picker::pick(&app_data_dir, &title_for_emoji))
+                    .await
+                    .unwrap_or_else(|_| emoji_picker::DEFAULT_EMOJI.to_string())
+            }
+            Err(_) => emoji_picker::pick_by_keywords(&title),
+        };
+        
+        if let Err(e) = db::Database::new(&app_handle).and_then(|db| db.set_session_title(&session_id, &title, &emoji)) {
+            log::warn!("Erro ao salvar título da sessão: {}", e);
+        }
+        
+        let created_event = ChatCreatedEvent {
+            session_id,
+            title,
+            emoji,
+        };
+        if let Err(e) = events::emit(&window, &created_event) {
+            log::warn!("Erro ao emitir evento chat-created: {}", e);
+        }
+    });
 }
 
+/// Comando principal para streaming de chat via Rust
 #[command]
-fn ensure_mcp_server_installed(
-    _name: String,
-    config: McpServerConfig,
-) -> Result<bool, String> {
-    // Check if command exists
-    let command_exists = Command::new(&config.command)
-        .arg("--version")
-        .output()
-        .is_ok();
+async fn chat_stream(
+    window: Window,
+    app_handle: AppHandle,
+    session_id: Option<String>,
+    messages: Vec<Message>,
+    model: Option<String>,
+    system_prompt: Option<String>,
+    enable_rag: Option<bool>,
+    options: Option<ollama_client::GenerationOptions>,
+    format: Option<serde_json::Value>,
+    offline: Option<bool>,
+) -> Result<String, String> {
+    use uuid::Uuid;
+    use ollama_client::OllamaClient;
+    use futures_util::StreamExt;
+    use db::{Database, PendingMessage};
     
-    if !command_exists {
-        return Err(format!("Command '{}' not found in PATH", config.command));
-    }
+    // Jobs pesados em background desaceleram enquanto a resposta é gerada
+    let _interactive = jobs::begin_interactive();
     
-    // For npx commands with -y flag, check if package exists
-    // Note: This is a simplified check - in production, you might want to verify
-    // the package actually exists before trying to run it
-    if config.command == "npx" && config.args.contains(&"-y".to_string()) {
-        // npx -y will auto-install if needed, so we consider it available
-        return Ok(true);
+    // Gerar ou usar session_id existente
+    let session_id = session_id.unwrap_or_else(|| Uuid::new_v4().to_string());
+    
+    // Configuração própria da sessão (ex: criada a partir de um template)
+    let session_config = Database::new(&app_handle).ok()
+        .and_then(|db| db.get_session_config(&session_id).ok().flatten())
+        .unwrap_or_default();
+    // Modelo da chamada ou, na falta dele, o definido para a sessão
+    let model = model
+        .filter(|m| !m.trim().is_empty())
+        .or_else(|| session_config.model.clone())
+        .ok_or_else(|| "No model specified for this session".to_string())?;
+    
+    // Uma resposta por sessão; sessões diferentes geram em paralelo até o limite configurado
+    let stream_guard = stream_manager::register(
+        &app_handle,
+        &session_id,
+        &model,
+        settings::current(&app_handle).max_concurrent_streams,
+    )?;
+    let enable_rag = enable_rag
+        .or_else(|| session_config.rag_scope.as_ref().map(|scope| scope.enabled))
+        .unwrap_or(false);
+    let system_prompt = system_prompt.or(session_config.system_prompt);
+    
+    // Opções de geração: chamada > sessão > padrões das configurações (num_ctx cai no ajuste por modelo)
+    let app_settings = settings::current(&app_handle);
+    let mut generation = options.unwrap_or_default()
+        .or(&session_config.generation_options.unwrap_or_default())
+        .or(&app_settings.generation_defaults);
+    if generation.num_ctx.is_none() {
+        generation.num_ctx = app_settings.model_context_overrides.get(&model).copied();
+    }
+    let mut option_errors = Vec::new();
+    generation.validate("options", &mut option_errors);
+    if !option_errors.is_empty() {
+        return Err(option_errors.join("; "));
+    }
+    // Saída estruturada: `"json"` ou JSON Schema, validado ao fim da resposta
+    if let Some(format) = &format {
+        structured_output::validate_format(format)?;
+    }
+    // Seed sempre definido e gravado na resposta para permitir `replay_message`
+    if generation.seed.is_none() {
+        generation.seed = Some(replay::random_seed());
     }
     
-    // For other commands, assume they're installed if command exists
-    Ok(true)
-}
-
-#[command]
-fn check_mcp_server_available(
-    name: String,
-    config: McpServerConfig,
-) -> Result<bool, String> {
-    ensure_mcp_server_installed(name, config)
-}
-
-// ========== Web Scraper Commands ==========
-
-/// Obtém ou cria uma instância do Browser (singleton)
-pub fn get_or_create_browser(state: State<BrowserState>) -> Result<Arc<Browser>, String> {
-    let mut browser_opt = state.lock().map_err(|e| format!("Erro ao acessar estado do browser: {}", e))?;
+    // Verificar se é nova sessão (apenas 1 mensagem do usuário)
+    let is_new_session = messages.len() == 1 && messages[0].role == "user";
     
-    if let Some(ref browser) = *browser_opt {
-        let alive = browser.new_tab().is_ok();
-        if alive {
-            return Ok(browser.clone());
-        } else {
-            *browser_opt = None;
+    // Nova sessão criada já com título provisório; título e emoji definitivos são gerados
+    // em background após o primeiro token, sem atrasar o streaming (ver `spawn_session_title`)
+    if is_new_session {
+        let now = Utc::now();
+        let provisional = db::ChatSession {
+            id: session_id.clone(),
+            title: fallback_session_title(&messages[0].content),
+            emoji: emoji_picker::DEFAULT_EMOJI.to_string(),
+            created_at: now,
+            updated_at: now,
+        };
+        if let Err(e) = Database::new(&app_handle).and_then(|db| db.create_session(&provisional)) {
+            log::warn!("Erro ao criar sessão: {}", e);
         }
     }
+    let mut title_pending = is_new_session;
     
-    // Criar nova instância
-    let browser = Arc::new(
-        create_browser()
-            .map_err(|e| format!("Falha ao criar browser: {}", e))?
-    );
+    // Modo offline: o informado na chamada fica gravado na sessão (vale desde a primeira mensagem)
+    let offline_mode = match offline {
+        Some(flag) => {
+            if let Err(e) = Database::new(&app_handle).and_then(|db| db.set_session_offline(&session_id, flag)) {
+                log::warn!("Erro ao gravar modo offline da sessão: {}", e);
+            }
+            flag
+        }
+        None => offline::is_offline(&app_handle, &session_id),
+    };
+    let enable_rag = if enable_rag && offline_mode {
+        offline::violation(&app_handle, &session_id, offline::Egress::Rag, "enable_rag");
+        false
+    } else {
+        enable_rag
+    };
     
-    *browser_opt = Some(browser.clone());
-    Ok(browser)
-}
-
-/// Busca no DuckDuckGo e extrai conteúdo das URLs encontradas
-#[command]
-async fn search_and_extract_content(
-    query: String,
-    limit: Option<usize>,
-    excluded_domains: Option<Vec<String>>,
-    search_config: Option<SearchConfig>,
-    state: State<'_, BrowserState>,
-) -> Result<Vec<ScrapedContent>, String> {
-    if query.trim().is_empty() {
-        return Err("Query não pode estar vazia".to_string());
+    // 2. Preparar mensagens para Ollama
+    let mut ollama_messages = Vec::new();
+    
+    // Preset de prompt aplicado à sessão (renderizado no momento do envio)
+    let preset_prompt = Database::new(&app_handle).ok().and_then(|db| {
+        let language = settings::current(&app_handle).language;
+        prompts::resolve_session_prompt(&db, &session_id, &language)
+    });
+    let system_prompt = match (preset_prompt, system_prompt) {
+        (Some(preset), Some(sys)) => Some(format!("{}\n\n{}", preset, sys)),
+        (Some(preset), None) => Some(preset),
+        (None, sys) => sys,
+    };
+    // Fatos lembrados da sessão vão no fim do system prompt
+    let memory_block = if app_settings.memory.enabled {
+        memory::get(&app_handle, &session_id)
+            .map_err(|e| log::warn!("Erro ao carregar memória da sessão: {}", e))
+            .ok()
+            .and_then(|items| memory::prompt_block(&items))
+    } else {
+        None
+    };
+    let system_prompt = match (system_prompt, memory_block) {
+        (Some(sys), Some(memory)) => Some(format!("{}\n\n{}", sys, memory)),
+        (sys, memory) => sys.or(memory),
+    };
+    // Perfil do usuário (com consentimento) abre o system prompt
+    let system_prompt = match (user_profile::prompt_prefix(&app_handle), system_prompt) {
+        (Some(profile), Some(sys)) => Some(format!("{}\n\n{}", profile, sys)),
+        (profile, sys) => profile.or(sys),
+    };
+    
+    // Adicionar system prompt se fornecido
+    let effective_system_prompt = system_prompt.clone();
+    if let Some(sys_prompt) = system_prompt {
+        ollama_messages.push(serde_json::json!({
+            "role": "system",
+            "content": sys_prompt
+        }));
+    }
+    
+    // Converter mensagens para formato Ollama (expandindo anexos de texto colado)
+    {
+        let paste_db = Database::new(&app_handle).ok();
+        for msg in &messages {
+            let content = match &paste_db {
+                Some(db) => expand_paste_references(&app_handle, db, &msg.content),
+                None => msg.content.clone(),
+            };
+            // Raciocínio de respostas anteriores não volta ao contexto (economiza tokens)
+            let content = if msg.role == "assistant" { post_process::split_think(&content).0 } else { content };
+            ollama_messages.push(serde_json::json!({
+                "role": msg.role,
+                "content": content
+            }));
+        }
     }
     
-    let browser = get_or_create_browser(state)?;
+    // Parâmetros da geração gravados na resposta (replay determinístico)
+    let generation_record = replay::GenerationRecord {
+        model: model.clone(),
+        seed: generation.seed.unwrap_or_default(),
+        options: generation.clone(),
+        system_prompt: effective_system_prompt,
+        prompt_hash: replay::prompt_hash(&ollama_messages),
+    };
     
-    // Se SearchConfig foi fornecido, usar a nova função
-    if let Some(config) = search_config {
-        search_and_scrape_with_config(&query, &config, browser)
-            .await
-            .map_err(|e| format!("Erro ao buscar e extrair conteúdo: {}", e))
-    } else {
-        // Backward compatibility: usar configuração padrão
-        let limit = limit.unwrap_or(3);
-        let excluded_domains = excluded_domains.unwrap_or_default();
-        search_and_scrape(&query, limit, browser, excluded_domains)
-            .await
-            .map_err(|e| format!("Erro ao buscar e extrair conteúdo: {}", e))
+    // 3. TODO: Classificar intent e aplicar RAG se necessário
+    // if enable_rag {
+    //     let intent = classify_intent(messages.last().unwrap().content.clone());
+    //     // Buscar contexto via RAG
+    //     // Injetar no system prompt
+    // }
+    
+    // 4. Fazer requisição streaming para Ollama
+    let ollama_client = OllamaClient::new(Some(app_settings.ollama_url.clone()));
+    ollama_client.check_connection().await?;
+    
+    // Formato específico da família do modelo (depois do hash: o replay reaplica o template)
+    chat_templates::apply_for_model(&app_settings.chat_templates, &model, &mut ollama_messages);
+    
+    let mut request = serde_json::json!({
+        "model": model,
+        "messages": ollama_messages,
+        "stream": true
+    });
+    
+    if !generation.is_empty() {
+        request["options"] = serde_json::to_value(&generation)
+            .map_err(|e| format!("Failed to serialize generation options: {}", e))?;
     }
-}
-
-/// Extrai conteúdo de uma URL específica
-#[command]
-async fn extract_url_content(
-    url: String,
-    state: State<'_, BrowserState>,
-) -> Result<ScrapedContent, String> {
-    if url.trim().is_empty() {
-        return Err("URL não pode estar vazia".to_string());
+    if let Some(format) = &format {
+        request["format"] = format.clone();
     }
     
-    // Validar formato de URL
-    if !url.starts_with("http://") && !url.starts_with("https://") {
-        return Err("URL deve começar com http:// ou https://".to_string());
+    // Contexto configurado para o modelo (avisa antes de enviar se não couber na VRAM)
+    if let Some(num_ctx) = generation.num_ctx {
+        if let Ok(spec) = model_context::spec_for(&ollama_client, &model).await {
+            let vram_total = primary_vram_total_mb().await;
+            if let Some(estimate) = model_context::estimate_vram(&spec, num_ctx, vram_total).filter(|e| e.exceeds) {
+                let warning = format!(
+                    "Contexto de {} tokens para {} precisa de ~{} MB, acima dos {} MB de VRAM disponíveis; o modelo pode rodar parcialmente na CPU",
+                    num_ctx,
+                    model,
+                    estimate.required_mb,
+                    estimate.available_mb.unwrap_or(0)
+                );
+                log::warn!("[ModelContext] {}", warning);
+                let _ = events::emit(&window, &ChatWarningEvent {
+                    session_id: session_id.clone(),
+                    warning,
+                });
+            }
+        }
     }
     
-    let browser = get_or_create_browser(state)?;
+    // Usar reqwest diretamente para streaming
+    let client = reqwest::Client::builder()
+        .timeout(std::time::Duration::from_secs(300))
+        .build()
+        .map_err(|e| format!("Failed to create HTTP client: {}", e))?;
     
-    scrape_url(&url, browser)
-        .await
-        .map_err(|e| format!("Erro ao extrair conteúdo da URL: {}", e))
-}
-
-/// Busca metadados leves (título/URL/snippet) sem abrir páginas
-#[command]
-async fn search_web_metadata(
-    query: String,
-    limit: Option<usize>,
-    search_config: Option<SearchConfig>,
-    engine_order: Option<Vec<String>>,
-) -> Result<Vec<SearchResultMetadata>, String> {
-    if query.trim().is_empty() {
-        return Err("Query não pode estar vazia".to_string());
-    }
-
-    let lim = limit.unwrap_or(5);
-
-    // Converter engine_order (strings) para Vec<SearchEngine>
-    let engines: Vec<SearchEngine> = if let Some(order) = engine_order {
-        order.iter()
-            .filter_map(|s| SearchEngine::from_str(s))
-            .collect()
-    } else {
-        // Ordem padrão: Google primeiro, depois outros
-        vec![
-            SearchEngine::Google,
-            SearchEngine::Bing,
-            SearchEngine::Yahoo,
-            SearchEngine::DuckDuckGo,
-            SearchEngine::Startpage,
-        ]
+    let url = format!("{}/api/chat", app_settings.ollama_url.trim_end_matches('/'));
+    
+    // Quedas do Ollama no meio da resposta são retomadas (ver `stream_manager`)
+    let retry = settings::current(&app_handle).stream_retry;
+    let mut attempt: u32 = 0;
+    let mut attempt_request = request.clone();
+    
+    // 5. Processar stream e emitir tokens COM BUFFERING
+    // OTIMIZAÇÃO: Acumular tokens e emitir em batches para reduzir overhead da bridge
+    let mut full_content = String::new();
+    // Raciocínio separado da resposta: campo `thinking` do Ollama ou blocos `<think>` no conteúdo
+    let mut full_thinking = String::new();
+    let mut think_splitter = post_process::ThinkSplitter::default();
+    
+    // Checkpoint periódico da resposta: se o app cair no meio da geração, a próxima
+    // inicialização salva o conteúdo parcial (ver `recover_pending_messages`)
+    const CHECKPOINT_EVERY_TOKENS: i64 = 32;
+    let sent_at = Utc::now();
+    // Uso da rodada (tokens e tempos do chunk final do Ollama) para `get_usage_analytics`
+    let request_started = std::time::Instant::now();
+    let mut first_token_ms: Option<u64> = None;
+    let mut usage: Option<serde_json::Value> = None;
+    let mut pending = PendingMessage {
+        session_id: session_id.clone(),
+        model: model.clone(),
+        request_messages: serde_json::to_string(&messages).unwrap_or_else(|_| "[]".to_string()),
+        content: String::new(),
+        tokens: 0,
+        started_at: sent_at,
+        updated_at: sent_at,
     };
-
-    // Se não há engines configuradas, usar DuckDuckGo como fallback
-    if engines.is_empty() {
-        log::warn!("No valid engines in order, using DuckDuckGo as fallback");
-        return search_duckduckgo_metadata(&query, lim)
-            .await
-            .map_err(|e| format!("Erro ao buscar metadados: {}", e));
-    }
-
-    // Usar multi-engine search
-    let min_results = 1; // Mínimo de 1 resultado para considerar sucesso
-    match search_multi_engine_metadata(&query, lim, &engines, min_results).await {
-        Ok(results) => {
-            if results.is_empty() && search_config.is_some() {
-                // Fallback para smart_search se multi-engine retornou vazio
-                log::info!("Multi-engine returned empty, trying smart_search fallback");
-                if let Some(config) = search_config {
-                    match smart_search(&query, &config).await {
-                        Ok(mut urls) => {
-                            urls.truncate(lim);
-                            let metas = urls
-                                .into_iter()
-                                .map(|u| SearchResultMetadata { title: u.clone(), url: u, snippet: String::new() })
-                                .collect::<Vec<_>>();
-                            Ok(metas)
+    checkpoint_pending_message(&app_handle, &pending);
+    audit::record(audit::AuditKind::ModelPrompt, &session_id, &model, Some(&pending.request_messages));
+    let mut tokens_since_checkpoint: i64 = 0;
+    
+    // Buffer de tokens para reduzir eventos na bridge
+    let mut token_buffer = String::new();
+    let mut thinking_buffer = String::new();
+    let mut last_emit = std::time::Instant::now();
+    const EMIT_INTERVAL_MS: u64 = 16; // ~60fps para sincronizar com RAF do frontend
+    const MAX_BUFFER_CHARS: usize = 50; // Emitir quando buffer tiver ~50 chars
+    
+    loop {
+        let interruption = match stream_manager::send(&client, &url, &attempt_request).await {
+            Err(e) => e,
+            Ok(response) => {
+                let mut stream = response.bytes_stream();
+                let mut buffer = String::new();
+                let mut finished = false;
+                let mut stream_error = None;
+                
+                while let Some(chunk_result) = stream.next().await {
+                    let chunk = match chunk_result {
+                        Ok(chunk) => chunk,
+                        Err(e) => {
+                            stream_error = Some(stream_manager::StreamError::from_reqwest("Stream error", &e));
+                            break;
+                        }
+                    };
+                    let chunk_str = String::from_utf8_lossy(&chunk);
+                    buffer.push_str(&chunk_str);
+                    
+                    // Processar linhas completas (separadas por \n)
+                    while let Some(pos) = buffer.find('\n') {
+                        let line = buffer[..pos].trim().to_string();
+                        buffer = buffer[pos + 1..].to_string();
+                        
+                        if line.is_empty() {
+                            continue;
+                        }
+                        
+                        // Tentar deserializar como JSON do Ollama
+                        match serde_json::from_str::<serde_json::Value>(&line) {
+                            Ok(json) => {
+                                // Verificar se stream terminou primeiro
+                                let is_done = json.get("done").and_then(|d| d.as_bool()) == Some(true);
+                                
+                                // Extrair conteúdo do chunk (Ollama envia tokens incrementais)
+                                if let Some(message) = json.get("message") {
+                                    let raw_content = message.get("content").and_then(|c| c.as_str()).unwrap_or_default();
+                                    let (content, inline_thinking) = think_splitter.push(raw_content);
+                                    let thinking = message.get("thinking").and_then(|t| t.as_str()).unwrap_or_default();
+                                    if first_token_ms.is_none() && !(raw_content.is_empty() && thinking.is_empty()) {
+                                        first_token_ms = Some(request_started.elapsed().as_millis() as u64);
+                                    }
+                                    if !thinking.is_empty() || !inline_thinking.is_empty() {
+                                        full_thinking.push_str(thinking);
+                                        full_thinking.push_str(&inline_thinking);
+                                        thinking_buffer.push_str(thinking);
+                                        thinking_buffer.push_str(&inline_thinking);
+                                        stream_guard.add_token();
+                                        
+                                        let elapsed = last_emit.elapsed().as_millis() as u64;
+                                        if elapsed >= EMIT_INTERVAL_MS || thinking_buffer.len() >= MAX_BUFFER_CHARS {
+                                            let _ = events::emit(&window, &ChatThinkingEvent {
+                                                session_id: session_id.clone(),
+                                                content: std::mem::take(&mut thinking_buffer),
+                                            });
+                                            last_emit = std::time::Instant::now();
+                                        }
+                                    }
+                                    if !content.is_empty() {
+                                        full_content.push_str(&content);
+                                        token_buffer.push_str(&content);
+                                        
+                                        if std::mem::take(&mut title_pending) {
+                                            spawn_session_title(window.clone(), app_handle.clone(), session_id.clone(), model.clone(), messages[0].content.clone());
+                                        }
+                                        
+                                        stream_guard.add_token();
+                                        tokens_since_checkpoint += 1;
+                                        if tokens_since_checkpoint >= CHECKPOINT_EVERY_TOKENS {
+                                            pending.content.clone_from(&full_content);
+                                            pending.tokens += std::mem::take(&mut tokens_since_checkpoint);
+                                            pending.updated_at = Utc::now();
+                                            checkpoint_pending_message(&app_handle, &pending);
+                                        }
+                                        
+                                        // Emitir buffer quando: tempo >= 16ms OU buffer >= 50 chars
+                                        let elapsed = last_emit.elapsed().as_millis() as u64;
+                                        if elapsed >= EMIT_INTERVAL_MS || token_buffer.len() >= MAX_BUFFER_CHARS {
+                                            let token_event = ChatTokenEvent {
+                                                session_id: session_id.clone(),
+                                                content: std::mem::take(&mut token_buffer),
+                                                done: false,
+                                            };
+                                            
+                                            if let Err(e) = events::emit(&window, &token_event) {
+                                                log::warn!("Erro ao emitir token: {}", e);
+                                            }
+                                            last_emit = std::time::Instant::now();
+                                        }
+                                    }
+                                }
+                                
+                                // Verificar se stream terminou
+                                if is_done {
+                                    usage = Some(serde_json::json!({
+                                        "model": model,
+                                        "prompt_tokens": json.get("prompt_eval_count").and_then(|v| v.as_u64()),
+                                        "completion_tokens": json.get("eval_count").and_then(|v| v.as_u64()),
+                                        "total_duration_ms": request_started.elapsed().as_millis() as u64,
+                                        "first_token_ms": first_token_ms,
+                                    }));
+                                    
+                                    // Tag incompleta retida no fim da resposta
+                                    let (tail_content, tail_thinking) = think_splitter.finish();
+                                    full_content.push_str(&tail_content);
+                                    token_buffer.push_str(&tail_content);
+                                    full_thinking.push_str(&tail_thinking);
+                                    thinking_buffer.push_str(&tail_thinking);
+                                    
+                                    // Flush do buffer residual antes de finalizar
+                                    if !thinking_buffer.is_empty() {
+                                        let _ = events::emit(&window, &ChatThinkingEvent {
+                                            session_id: session_id.clone(),
+                                            content: std::mem::take(&mut thinking_buffer),
+                                        });
+                                    }
+                                    if !token_buffer.is_empty() {
+                                        let flush_event = ChatTokenEvent {
+                                            session_id: session_id.clone(),
+                                            content: std::mem::take(&mut token_buffer),
+                                            done: false,
+                                        };
+                                        let _ = events::emit(&window, &flush_event);
+                                    }
+                                    
+                                    // Emitir evento final
+                                    let final_event = ChatTokenEvent {
+                                        session_id: session_id.clone(),
+                                        content: String::new(),
+                                        done: true,
+                                    };
+                                    let _ = events::emit(&window, &final_event);
+                                    finished = true;
+                                    break;
+                                }
+                            }
+                            Err(e) => {
+                                log::debug!("Failed to parse JSON chunk: {} - Line: {}", e, line);
+                                // Continuar mesmo com erro de parse
+                            }
                         }
-                        Err(e) => Err(format!("Erro ao executar smart_search: {}", e)),
                     }
-                } else {
-                    Ok(results)
                 }
-            } else {
-                Ok(results)
+                
+                if finished {
+                    break;
+                }
+                // Stream encerrado sem `done`: o servidor caiu no meio da resposta
+                stream_error.unwrap_or_else(|| {
+                    stream_manager::StreamError::Disconnected("Ollama encerrou a resposta antes do fim".to_string())
+                })
             }
-        }
-        Err(e) => {
-            // Se multi-engine falhou completamente, tentar DuckDuckGo como último recurso
-            log::warn!("Multi-engine search failed: {}, trying DuckDuckGo fallback", e);
-            search_duckduckgo_metadata(&query, lim)
-                .await
-                .map_err(|e| format!("Erro ao buscar metadados: {}", e))
-        }
-    }
-}
-
-/// Faz scraping em lote de URLs fornecidas
-#[command]
-async fn scrape_urls(
-    urls: Vec<String>,
-    state: State<'_, BrowserState>,
-) -> Result<Vec<ScrapedContent>, String> {
-    if urls.is_empty() {
-        return Ok(Vec::new());
-    }
-
-    let browser = get_or_create_browser(state)?;
-
-    scrape_urls_bulk(urls, browser)
-        .await
-        .map_err(|e| format!("Erro ao extrair conteúdo das URLs: {}", e))
-}
-
-/// Reinicia o browser (útil se houver problemas)
-#[command]
-fn reset_browser(state: State<'_, BrowserState>) -> Result<(), String> {
-    let mut browser_opt = state.lock().map_err(|e| format!("Erro ao acessar estado do browser: {}", e))?;
-    // Limpar referência - o browser será dropado automaticamente
-    *browser_opt = None;
-    log::info!("Browser resetado - processo será encerrado quando não houver mais referências");
-    Ok(())
-}
-
-/// Força o encerramento apenas de processos Chrome/Chromium headless criados pelo app
-/// Seguro: não mata o navegador pessoal do usuário
-#[command]
-fn force_kill_browser() -> Result<u32, String> {
-    let mut system = System::new_all();
-    system.refresh_all();
-    
-    let mut killed_count = 0;
-    let process_names = vec!["chrome", "chromium", "chromedriver", "headless_shell"];
-    
-    for (pid, process) in system.processes() {
-        let name = process.name().to_string_lossy().to_lowercase();
+        };
         
-        // Verifica se o nome do processo corresponde
-        if !process_names.iter().any(|&pn| name.contains(pn)) {
-            continue;
+        // Tentar novamente apenas quedas de conexão, dentro do limite configurado
+        attempt += 1;
+        let recoverable = matches!(interruption, stream_manager::StreamError::Disconnected(_));
+        if !recoverable || !retry.allows(attempt) {
+            let error_msg = interruption.message().to_string();
+            let _ = events::emit(&window, &ChatErrorEvent {
+                session_id: session_id.clone(),
+                error: error_msg.clone(),
+            });
+            return Err(error_msg);
         }
         
-        // SAFE KILL: Estratégia conservadora para identificar processos headless
-        // No Windows, tentamos usar wmic para obter a linha de comando completa
-        #[cfg(target_os = "windows")]
-        let is_headless = {
-            use std::process::Command;
-            // Tenta obter a linha de comando do processo via wmic
-            let cmd_output = Command::new("wmic")
-                .args(&["process", "where", &format!("ProcessId={}", pid), "get", "CommandLine", "/format:list"])
-                .output();
-            
-            if let Ok(output) = cmd_output {
-                if let Ok(cmd_str) = String::from_utf8(output.stdout) {
-                    let cmd_lower = cmd_str.to_lowercase();
-                    // Só mata se tiver flags muito específicas de headless
-                    cmd_lower.contains("--headless") 
-                        || cmd_lower.contains("--remote-debugging-port")
-                        || (cmd_lower.contains("--disable-gpu") && cmd_lower.contains("--no-sandbox"))
-                } else {
-                    false // Se não conseguir ler, não mata (seguro)
-                }
-            } else {
-                // Se wmic falhar, usa heurística conservadora: só mata se o nome for muito específico
-                name.contains("headless_shell") || name.contains("chromedriver")
-            }
-        };
+        log::warn!(
+            "[Stream] Conexão com o Ollama interrompida ({}); tentativa {}/{}",
+            interruption.message(),
+            attempt,
+            retry.max_attempts
+        );
         
-        #[cfg(not(target_os = "windows"))]
-        let is_headless = {
-            // No Linux/Mac, tenta ler /proc/PID/cmdline
-            use std::fs;
-            if let Ok(cmdline) = fs::read_to_string(format!("/proc/{}/cmdline", pid)) {
-                let cmd_lower = cmdline.to_lowercase();
-                cmd_lower.contains("--headless") 
-                    || cmd_lower.contains("--remote-debugging-port")
-                    || (cmd_lower.contains("--disable-gpu") && cmd_lower.contains("--no-sandbox"))
-            } else {
-                // Se não conseguir ler, usa heurística conservadora
-                name.contains("headless_shell") || name.contains("chromedriver")
-            }
-        };
+        // Entregar o que já foi gerado antes de decidir o que fazer com a parcial
+        if !thinking_buffer.is_empty() {
+            let _ = events::emit(&window, &ChatThinkingEvent {
+                session_id: session_id.clone(),
+                content: std::mem::take(&mut thinking_buffer),
+            });
+        }
+        if !token_buffer.is_empty() {
+            let _ = events::emit(&window, &ChatTokenEvent {
+                session_id: session_id.clone(),
+                content: std::mem::take(&mut token_buffer),
+                done: false,
+            });
+        }
+        let discard_partial = retry.partial_answer == stream_manager::PartialAnswerPolicy::Discard;
+        let _ = events::emit(&window, &stream_manager::ChatRetryEvent {
+            session_id: session_id.clone(),
+            attempt,
+            max_attempts: retry.max_attempts,
+            reason: interruption.message().to_string(),
+            discard_partial,
+        });
         
-        if !is_headless {
-            log::debug!("Ignorando processo Chrome não-headless: PID {} ({})", pid, name);
-            continue;
+        if let Err(e) = stream_manager::reconnect(&app_handle, &retry, attempt).await {
+            let _ = events::emit(&window, &ChatErrorEvent {
+                session_id: session_id.clone(),
+                error: e.clone(),
+            });
+            return Err(e);
         }
         
-        // Processo identificado como headless - pode matar com segurança
-            #[cfg(target_os = "windows")]
-            {
-                use std::process::Command;
-                match Command::new("taskkill")
-                    .args(&["/F", "/PID", &pid.to_string()])
-                    .output()
-                {
-                    Ok(output) => {
-                        if output.status.success() {
-                            killed_count += 1;
-                        log::info!("Processo Chrome headless encerrado: PID {} ({})", pid, name);
-                        }
-                    }
-                    Err(e) => {
-                        log::warn!("Erro ao encerrar processo {}: {}", pid, e);
+        attempt_request = stream_manager::resume_request(&request, &full_content, retry.partial_answer);
+        if discard_partial {
+            full_content.clear();
+            full_thinking.clear();
+            think_splitter = post_process::ThinkSplitter::default();
+            pending.content.clear();
+            pending.tokens = 0;
+            tokens_since_checkpoint = 0;
+            checkpoint_pending_message(&app_handle, &pending);
+        }
+    }
+    
+    // Resposta sem nenhum token: gerar o título mesmo assim
+    if title_pending {
+        spawn_session_title(window.clone(), app_handle.clone(), session_id.clone(), model.clone(), messages[0].content.clone());
+    }
+    
+    // Pós-processamento (think, espaços, citações, regex) antes de salvar
+    let source_urls = messages
+        .iter()
+        .rev()
+        .filter_map(|msg| {
+            // Fontes nos metadados ou no resultado JSON de uma ferramenta (`web_search`, `fetch_page`)
+            let tool_result = (msg.role == "tool")
+                .then(|| serde_json::from_str::<serde_json::Value>(&msg.content).ok())
+                .flatten();
+            msg.metadata.as_ref().or(tool_result.as_ref()).map(citations::source_urls)
+        })
+        .find(|urls| !urls.is_empty())
+        .unwrap_or_default();
+    let mut assistant_metadata: serde_json::Value = serde_json::from_str(&generation_record.to_metadata())
+        .unwrap_or_else(|_| serde_json::json!({}));
+    if let Some(usage) = usage {
+        assistant_metadata["usage"] = usage;
+    }
+    
+    // Saída estruturada: validar, tentar de novo uma vez se inválida e guardar o objeto nos metadados
+    let processed = match &format {
+        Some(format) => {
+            let mut content = full_content.clone();
+            let mut result = structured_output::parse(format, &content);
+            if let Err(error) = &result {
+                log::warn!("[StructuredOutput] {}; nova tentativa", error);
+                match structured_output::retry(&ollama_client, &request, &content, error).await {
+                    Ok(retried) => {
+                        result = structured_output::parse(format, &retried);
+                        content = retried;
                     }
+                    Err(e) => log::warn!("[StructuredOutput] {}", e),
                 }
             }
-            
-            #[cfg(not(target_os = "windows"))]
-            {
-                use std::process::Command;
-                match Command::new("kill")
-                    .args(&["-9", &pid.to_string()])
-                    .output()
-                {
-                    Ok(output) => {
-                        if output.status.success() {
-                            killed_count += 1;
-                        log::info!("Processo Chrome headless encerrado: PID {} ({})", pid, name);
-                        }
-                    }
-                    Err(e) => {
-                        log::warn!("Erro ao encerrar processo {}: {}", pid, e);
+            match result {
+                Ok(value) => assistant_metadata["structured"] = value,
+                Err(error) => {
+                    let _ = events::emit(&window, &ChatWarningEvent {
+                        session_id: session_id.clone(),
+                        warning: error.clone(),
+                    });
+                    assistant_metadata["structured_error"] = serde_json::Value::String(error);
                 }
             }
+            // O JSON é gravado como veio (o pós-processamento de texto poderia alterá-lo)
+            post_process::Processed { content, reasoning: None }
+        }
+        None => post_process::run(&settings::current(&app_handle).post_processing, &full_content, &source_urls),
+    };
+    audit::record(audit::AuditKind::ModelResponse, &session_id, &model, Some(&full_content));
+    // Raciocínio transmitido à parte vem antes de eventuais blocos restantes no conteúdo
+    let reasoning = [Some(full_thinking.trim().to_string()), processed.reasoning.clone()]
+        .into_iter()
+        .flatten()
+        .filter(|r| !r.is_empty())
+        .collect::<Vec<_>>()
+        .join("\n\n");
+    if !reasoning.is_empty() {
+        assistant_metadata["reasoning"] = serde_json::Value::String(reasoning);
+    }
+    if processed.content != full_content {
+        let _ = events::emit(&window, &ChatPostProcessedEvent {
+            session_id: session_id.clone(),
+            content: processed.content.clone(),
+            reasoning: processed.reasoning.clone(),
+        });
+    }
+    
+    // 6. Persistir sessão e mensagens no SQLite
+    match Database::new(&app_handle) {
+        Ok(db) => {
+            let saved = persist_chat_turn(&db, &session_id, &messages, &processed.content, Some(assistant_metadata.to_string()), sent_at);
+            if saved && unread::window_hidden(&app_handle) {
+                // Resposta terminou com a janela oculta no tray ou minimizada
+                unread::record(&app_handle, &session_id);
+            }
+            // Rodada finalizada: o checkpoint não é mais necessário
+            if let Err(e) = db.delete_pending_message(&session_id) {
+                log::warn!("Erro ao remover checkpoint da resposta: {}", e);
+            }
+        }
+        Err(e) => {
+            log::warn!("Erro ao inicializar banco de dados: {}", e);
         }
     }
     
-    if killed_count > 0 {
-        log::info!("Total de {} processos Chrome headless encerrados (seguro)", killed_count);
-    } else {
-        log::info!("Nenhum processo Chrome headless encontrado para encerrar");
+    // 7. Fatos duradouros da rodada para a memória da sessão (respostas estruturadas ficam de fora)
+    if app_settings.memory.enabled && app_settings.memory.auto_extract && format.is_none() {
+        if let Some(user_message) = messages.iter().rev().find(|m| m.role == "user") {
+            memory::spawn_extraction(
+                app_handle.clone(),
+                session_id.clone(),
+                model.clone(),
+                user_message.content.clone(),
+                processed.content.clone(),
+            );
+        }
     }
     
-    Ok(killed_count)
+    Ok(session_id)
 }
 
-// ========== Storage Management Commands ==========
+/// Respostas em geração no momento (uma por sessão)
+#[command]
+fn list_active_streams() -> Vec<stream_manager::ActiveStream> {
+    stream_manager::list()
+}
 
-/// Exporta todas as sessões de chat para um arquivo ZIP
+/// Repete a geração de uma resposta com o mesmo prompt, seed e opções e compara o resultado
+/// (`model` opcional para comparar a mesma entrada em outro modelo)
 #[command]
-async fn export_chat_sessions(app_handle: AppHandle) -> Result<String, String> {
-    let chats_dir = get_chats_dir(&app_handle)?;
+async fn replay_message(app_handle: AppHandle, message_id: i64, model: Option<String>) -> Result<replay::ReplayResult, String> {
+    let _interactive = jobs::begin_interactive();
+    replay::replay(&app_handle, message_id, model).await
+}
+
+// ============== COMANDOS DE EMBEDDINGS ==============
+
+/// Baixa o modelo de embeddings se não existir
+#[command]
+async fn download_embedding_model(app_handle: AppHandle) -> Result<bool, String> {
+    let app_data_dir = app_handle.path()
+        .app_data_dir()
+        .map_err(|e| format!("Failed to get app data dir: {}", e))?;
     
-    // Criar nome do arquivo com timestamp
-    let timestamp = Utc::now().format("%Y%m%d_%H%M%S");
-    let export_dir = app_handle.path().app_data_dir()
+    match embeddings::ensure_model_files(&app_data_dir).await {
+        Ok(_) => {
+            log::info!("[Embeddings] Model files ready");
+            Ok(true)
+        }
+        Err(e) => {
+            log::error!("[Embeddings] Failed to ensure model files: {}", e);
+            Err(format!("Failed to download model: {}", e))
+        }
+    }
+}
+
+/// Verifica se o modelo de embeddings está disponível
+#[command]
+fn is_embedding_model_available(app_handle: AppHandle) -> Result<bool, String> {
+    let app_data_dir = app_handle.path()
+        .app_data_dir()
         .map_err(|e| format!("Failed to get app data dir: {}", e))?;
-    let zip_path = export_dir.join(format!("ollahub_export_{}.zip", timestamp));
     
-    // Criar arquivo ZIP
-    let file = fs::File::create(&zip_path)
-        .map_err(|e| format!("Failed to create ZIP file: {}", e))?;
+    Ok(embeddings::is_model_available(&app_data_dir))
+}
+
+/// Calcula scores de relevância para textos em relação a uma query
+#[command]
+fn calculate_relevance_scores(
+    app_handle: AppHandle,
+    query: String,
+    texts: Vec<String>,
+) -> Result<Vec<(usize, f32)>, String> {
+    if !resource_profile::current().embeddings_enabled {
+        return Err("Embeddings desativados no modo de baixo consumo".to_string());
+    }
     
-    let mut zip = ZipWriter::new(file);
-    let options = FileOptions::default()
-        .compression_method(CompressionMethod::Deflated)
-        .unix_permissions(0o755);
+    let app_data_dir = app_handle.path()
+        .app_data_dir()
+        .map_err(|e| format!("Failed to get app data dir: {}", e))?;
     
-    // Ler todos os arquivos JSON do diretório chats
-    let entries = fs::read_dir(&chats_dir)
-        .map_err(|e| format!("Failed to read chats dir: {}", e))?;
+    let model_arc = embeddings::get_or_init_model(&app_data_dir)
+        .map_err(|e| format!("Failed to load model: {}", e))?;
     
-    for entry in entries {
-        let entry = entry.map_err(|e| format!("Failed to read entry: {}", e))?;
-        let path = entry.path();
-        
-        if path.extension().and_then(|s| s.to_str()) == Some("json") {
-            let file_name = path.file_name()
-                .and_then(|n| n.to_str())
-                .ok_or_else(|| "Invalid file name".to_string())?;
-            
-            let file_content = fs::read_to_string(&path)
-                .map_err(|e| format!("Failed to read file {}: {}", file_name, e))?;
-            
-            zip.start_file(format!("chats/{}", file_name), options)
-                .map_err(|e| format!("Failed to add file to ZIP: {}", e))?;
-            zip.write_all(file_content.as_bytes())
-                .map_err(|e| format!("Failed to write file to ZIP: {}", e))?;
-        }
-    }
+    let mut model = model_arc.lock()
+        .map_err(|e| format!("Failed to lock model: {}", e))?;
     
-    zip.finish()
-        .map_err(|e| format!("Failed to finalize ZIP: {}", e))?;
+    let text_refs: Vec<&str> = texts.iter().map(|s| s.as_str()).collect();
     
-    Ok(format!("{}", zip_path.display()))
+    embeddings::rank_by_relevance(&mut model, &query, &text_refs)
+        .map_err(|e| format!("Failed to calculate relevance: {}", e))
 }
 
-/// Apaga todo o histórico de conversas
+/// Gera embedding para um texto
 #[command]
-fn clear_chat_history(app_handle: AppHandle) -> Result<(), String> {
-    use db::Database;
+fn generate_embedding(
+    app_handle: AppHandle,
+    text: String,
+) -> Result<Vec<f32>, String> {
+    if !resource_profile::current().embeddings_enabled {
+        return Err("Embeddings desativados no modo de baixo consumo".to_string());
+    }
     
-    let chats_dir = get_chats_dir(&app_handle)?;
+    let app_data_dir = app_handle.path()
+        .app_data_dir()
+        .map_err(|e| format!("Failed to get app data dir: {}", e))?;
     
-    // 1. Deletar todos os arquivos JSON
-    let entries = fs::read_dir(&chats_dir)
-        .map_err(|e| format!("Failed to read chats dir: {}", e))?;
+    let model_arc = embeddings::get_or_init_model(&app_data_dir)
+        .map_err(|e| format!("Failed to load model: {}", e))?;
     
-    let mut deleted_count = 0;
-    for entry in entries {
-        let entry = entry.map_err(|e| format!("Failed to read entry: {}", e))?;
-        let path = entry.path();
+    let mut model = model_arc.lock()
+        .map_err(|e| format!("Failed to lock model: {}", e))?;
+    
+    model.embed(&text)
+        .map_err(|e| format!("Failed to generate embedding: {}", e))
+}
+
+/// Poda o contexto mantendo apenas os parágrafos mais relevantes
+#[command]
+fn prune_context(
+    app_handle: AppHandle,
+    query: String,
+    context: String,
+    max_tokens: Option<usize>,
+    min_score: Option<f32>,
+) -> Result<String, String> {
+    let max_tokens = max_tokens.unwrap_or(2000);
+    let min_score = min_score.unwrap_or(0.3);
+    
+    let app_data_dir = app_handle.path()
+        .app_data_dir()
+        .map_err(|e| format!("Failed to get app data dir: {}", e))?;
+    
+    // Tentar usar embeddings se modelo disponível (e não estiver no modo de baixo consumo)
+    if resource_profile::current().embeddings_enabled && embeddings::is_model_available(&app_data_dir) {
+        let model_arc = embeddings::get_or_init_model(&app_data_dir)
+            .map_err(|e| format!("Failed to load model: {}", e))?;
         
-        if path.extension().and_then(|s| s.to_str()) == Some("json") {
-            fs::remove_file(&path)
-                .map_err(|e| format!("Failed to delete file {:?}: {}", path, e))?;
-            deleted_count += 1;
-        }
+        let mut model = model_arc.lock()
+            .map_err(|e| format!("Failed to lock model: {}", e))?;
+        
+        embeddings::prune_context(&mut model, &query, &context, max_tokens, min_score)
+            .map_err(|e| format!("Failed to prune context: {}", e))
+    } else {
+        // Fallback para BM25-like
+        log::info!("[PruneContext] Using BM25 fallback (embedding model not available or low-resource mode)");
+        Ok(embeddings::prune_context_bm25(&query, &context, max_tokens))
+    }
+}
+
+/// Gera em background os embeddings dos documentos RAG que ainda não têm.
+/// Retorna o id do job (progresso via `job-progress`, cancelamento via `cancel_job`).
+#[command]
+fn start_embedding_job(app_handle: AppHandle, session_id: Option<String>) -> Result<String, String> {
+    if !resource_profile::current().embeddings_enabled {
+        return Err("Embeddings desativados no modo de baixo consumo".to_string());
     }
     
-    // 2. Deletar todas as sessões do SQLite
-    match Database::new(&app_handle) {
-        Ok(db) => {
-            match db.list_sessions() {
-                Ok(sessions) => {
-                    let mut sqlite_deleted = 0;
-                    for session in sessions {
-                        if let Err(e) = db.delete_session(&session.id) {
-                            log::warn!("Failed to delete session {} from SQLite: {}", session.id, e);
-                        } else {
-                            sqlite_deleted += 1;
-                        }
-                    }
-                    log::info!("Deleted {} sessions from SQLite", sqlite_deleted);
-                }
-                Err(e) => {
-                    log::warn!("Failed to list sessions from SQLite: {}", e);
-                }
-            }
-        }
-        Err(e) => {
-            log::warn!("Failed to open database: {}", e);
-        }
+    let app_data_dir = app_handle.path()
+        .app_data_dir()
+        .map_err(|e| format!("Failed to get app data dir: {}", e))?;
+    if !embeddings::is_model_available(&app_data_dir) {
+        return Err("Modelo de embeddings não encontrado".to_string());
     }
     
-    log::info!("Deleted {} chat session files", deleted_count);
-    Ok(())
+    let pending = db::Database::new(&app_handle)
+        .map_err(|e| format!("Failed to open database: {}", e))?
+        .get_documents_without_embedding(session_id.as_deref())
+        .map_err(|e| format!("Failed to load documents: {}", e))?;
+    
+    let config = settings::current(&app_handle).embeddings;
+    let options = embeddings::PoolOptions {
+        concurrency: config.concurrency,
+        batch_size: config.batch_size,
+    };
+    
+    let job = jobs::start(&app_handle, "embeddings");
+    job.set_total(pending.len());
+    let job_id = job.id().to_string();
+    log::info!("[Embeddings] Job {} iniciado: {} documentos ({} workers, lotes de {})",
+        job_id, pending.len(), options.concurrency, options.batch_size);
+    
+    let worker_app = app_handle.clone();
+    std::thread::Builder::new()
+        .name("embedding-job".to_string())
+        .spawn(move || {
+            let result = db::Database::new(&worker_app)
+                .map_err(|e| format!("Failed to open database: {}", e))
+                .and_then(|db| {
+                    embeddings::embed_in_pool(&app_data_dir, pending, options, &job, |batch| {
+                        let rows: Vec<(String, Vec<u8>)> = batch
+                            .into_iter()
+                            .map(|(id, vector)| (id, paste::embedding_to_bytes(&vector)))
+                            .collect();
+                        db.set_document_embeddings(&rows)
+                            .map_err(|e| anyhow::anyhow!("Failed to save embeddings: {}", e))
+                    })
+                    .map_err(|e| format!("Failed to generate embeddings: {}", e))
+                });
+            
+            match &result {
+                Ok(count) => log::info!("[Embeddings] Job {} finalizado: {} documentos", job.id(), count),
+                Err(e) => log::error!("[Embeddings] Job {} falhou: {}", job.id(), e),
+            }
+            job.finish(result.map(|_| ()));
+        })
+        .map_err(|e| format!("Failed to spawn embedding job: {}", e))?;
+    
+    Ok(job_id)
 }
 
-/// Limpa sessões órfãs do SQLite que não têm arquivo JSON correspondente
+// ========== Digest Commands ==========
+
+/// Itens aguardando o próximo resumo diário
 #[command]
-fn cleanup_orphan_sessions(app_handle: AppHandle) -> Result<u32, String> {
-    use db::Database;
-    
-    let db = Database::new(&app_handle)
+fn get_pending_digest_items(app_handle: AppHandle) -> Result<Vec<db::DigestItem>, String> {
+    db::Database::new(&app_handle)
+        .and_then(|db| db.get_pending_digest_items())
+        .map_err(|e| format!("Failed to load digest items: {}", e))
+}
+
+/// Adiciona um item ao próximo resumo (feeds e atualizações de modelos detectados pelo frontend)
+#[command]
+fn add_digest_item(
+    app_handle: AppHandle,
+    kind: digests::DigestItemKind,
+    title: String,
+    content: String,
+    sources: Option<Vec<digests::DigestSource>>,
+) -> Result<i64, String> {
+    if title.trim().is_empty() {
+        return Err("O título do item não pode ser vazio".to_string());
+    }
+    db::Database::new(&app_handle)
+        .and_then(|db| db.add_digest_item(kind.as_str(), None, &title, &content, &sources.unwrap_or_default()))
+        .map_err(|e| format!("Failed to add digest item: {}", e))
+}
+
+/// Gera o resumo agora com os itens pendentes, sem esperar o horário agendado
+#[command]
+async fn run_digest_now(app_handle: AppHandle) -> Result<Option<String>, String> {
+    tokio::task::spawn_blocking(move || digests::run(&app_handle, "user"))
+        .await
+        .map_err(|e| format!("Digest task failed: {}", e))?
+}
+
+// ========== Job Commands ==========
+
+/// Lista os jobs de background em andamento e os finalizados recentemente
+#[command]
+fn list_jobs() -> Vec<jobs::JobInfo> {
+    jobs::list()
+}
+
+/// Solicita o cancelamento de um job em andamento
+#[command]
+fn cancel_job(job_id: String) -> Result<bool, String> {
+    Ok(jobs::cancel(&job_id))
+}
+
+// ========== Database Maintenance Commands ==========
+
+/// Executa a manutenção do banco e registra o resultado no audit log
+pub(crate) fn perform_db_maintenance(
+    app_handle: &AppHandle,
+    mode: db::MaintenanceMode,
+    actor: &str,
+) -> Result<db::MaintenanceReport, String> {
+    let db = db::Database::new(app_handle)
         .map_err(|e| format!("Failed to open database: {}", e))?;
     
-    let chats_dir = get_chats_dir(&app_handle)?;
-    let mut orphan_count = 0;
+    let report = db.run_maintenance(mode)
+        .map_err(|e| format!("Database maintenance failed: {}", e))?;
     
-    // Listar todas as sessões do SQLite
-    let sessions = db.list_sessions()
-        .map_err(|e| format!("Failed to list sessions: {}", e))?;
+    if report.integrity_ok {
+        log::info!(
+            "[DbMaintenance] {:?} concluída em {}ms: {} -> {} bytes (WAL {} -> {})",
+            mode, report.duration_ms, report.size_before, report.size_after,
+            report.wal_size_before, report.wal_size_after
+        );
+    } else {
+        log::error!("[DbMaintenance] Problemas de integridade encontrados: {:?}", report.findings);
+    }
     
-    for session in sessions {
-        let json_path = chats_dir.join(format!("{}.json", session.id));
-        
-        // Se não existe arquivo JSON correspondente, é uma sessão órfã
-        if !json_path.exists() {
-            log::info!("Found orphan session: {} (title: {}), removing from SQLite", session.id, session.title);
-            
-            if let Err(e) = db.delete_session(&session.id) {
-                log::warn!("Failed to delete orphan session {}: {}", session.id, e);
-            } else {
-                orphan_count += 1;
-            }
+    let detail = serde_json::to_string(&report).unwrap_or_default();
+    if let Err(e) = db.append_audit_log("db_maintenance", actor, &detail) {
+        log::warn!("Failed to write audit log: {}", e);
+    }
+    
+    Ok(report)
+}
+
+/// Checkpoint do WAL, VACUUM, REINDEX e verificação de integridade (mode: "quick" ou "full")
+#[command]
+async fn run_db_maintenance(
+    app_handle: AppHandle,
+    mode: Option<db::MaintenanceMode>,
+) -> Result<db::MaintenanceReport, String> {
+    let mode = mode.unwrap_or_default();
+    tokio::task::spawn_blocking(move || perform_db_maintenance(&app_handle, mode, "user"))
+        .await
+        .map_err(|e| format!("Database maintenance task failed: {}", e))?
+}
+
+/// Sincroniza agora com a pasta compartilhada: grava as mudanças deste dispositivo e
+/// importa as dos outros (funciona mesmo com a sincronização automática desativada)
+#[command]
+async fn sync_now(app_handle: AppHandle) -> Result<folder_sync::SyncReport, String> {
+    tokio::task::spawn_blocking(move || folder_sync::sync_now(&app_handle))
+        .await
+        .map_err(|e| format!("Sync task failed: {}", e))?
+}
+
+/// Importa o histórico exportado do ChatGPT, Claude ou Open WebUI (JSON ou o ZIP do export).
+/// Sem `format`, o formato é detectado pelo conteúdo
+#[command]
+async fn import_external_history(
+    app_handle: AppHandle,
+    path: String,
+    format: Option<external_import::ExternalFormat>,
+) -> Result<external_import::ExternalImportReport, String> {
+    tokio::task::spawn_blocking(move || external_import::import(&app_handle, std::path::Path::new(&path), format))
+        .await
+        .map_err(|e| format!("Import task failed: {}", e))?
+}
+
+// ========== Command Palette ==========
+
+/// Resposta de `run_palette_command`
+#[derive(serde::Serialize)]
+struct PaletteResponse {
+    matches: Vec<palette::PaletteMatch>,
+    /// Ação executada (apenas com `run`)
+    executed: Option<palette::PaletteAction>,
+}
+
+/// Busca ações da paleta de comandos; com `run`, executa o resultado mais relevante
+/// (a UI envia o `id` do item escolhido como consulta)
+#[command]
+async fn run_palette_command(
+    window: Window,
+    app_handle: AppHandle,
+    scheduler: State<'_, SchedulerState>,
+    query: String,
+    run: Option<bool>,
+) -> Result<PaletteResponse, String> {
+    let models: Vec<String> = tokio::task::spawn_blocking(list_local_models)
+        .await
+        .map_err(|e| format!("Failed to list models: {}", e))?
+        .into_iter()
+        .map(|m| m.name)
+        .collect();
+    let (tasks, scheduler_paused) = {
+        let sched = scheduler.lock().await;
+        let tasks: Vec<(String, String)> = sched.list_tasks().into_iter().map(|t| (t.id, t.label)).collect();
+        (tasks, sched.is_paused())
+    };
+    let settings_sections: Vec<String> = serde_json::to_value(AppSettings::default())
+        .ok()
+        .and_then(|v| v.as_object().map(|o| o.keys().filter(|k| *k != "version").cloned().collect()))
+        .unwrap_or_default();
+    let plugins = palette::plugin_actions();
+    let matches = palette::search(&query, &palette::PaletteContext {
+        models: &models,
+        tasks: &tasks,
+        scheduler_paused,
+        settings_sections: &settings_sections,
+        plugins: &plugins,
+    });
+
+    if !run.unwrap_or(false) {
+        return Ok(PaletteResponse { matches, executed: None });
+    }
+    let action = matches
+        .first()
+        .map(|m| m.entry.action.clone())
+        .ok_or_else(|| format!("Nenhuma ação encontrada para '{}'", query))?;
+    match &action {
+        palette::PaletteAction::NewChat { model } => {
+            let _ = app_handle.emit("palette-new-chat", model);
+        }
+        palette::PaletteAction::PullModel { model } => {
+            let (window, model) = (window.clone(), model.clone());
+            tauri::async_runtime::spawn(async move {
+                if let Err(e) = pull_model(window, model.clone()).await {
+                    log::warn!("[Palette] Falha ao baixar {}: {}", model, e);
+                }
+            });
+        }
+        palette::PaletteAction::RunTask { task_id } => {
+            let (app_handle, scheduler, task_id) = (app_handle.clone(), scheduler.inner().clone(), task_id.clone());
+            tauri::async_runtime::spawn(scheduler_loop::run_task_now(app_handle, scheduler, task_id));
+        }
+        palette::PaletteAction::ToggleScheduler => {
+            let mut sched = scheduler.lock().await;
+            let paused = !sched.is_paused();
+            sched.set_paused(paused);
+            let _ = app_handle.emit("scheduler-paused-changed", paused);
+        }
+        palette::PaletteAction::OpenSettings { section } => {
+            let _ = app_handle.emit("palette-open-settings", section);
+        }
+        palette::PaletteAction::Plugin { .. } => {
+            let _ = app_handle.emit("palette-action", &action);
         }
     }
-    
-    log::info!("Cleaned up {} orphan sessions from SQLite", orphan_count);
-    Ok(orphan_count)
+    Ok(PaletteResponse { matches, executed: Some(action) })
 }
 
-/// Retorna o caminho do diretório de dados do app
+/// Registra uma ação de extensão na paleta (executá-la emite `palette-action`)
 #[command]
-fn get_app_data_dir(app_handle: AppHandle) -> Result<String, String> {
-    let app_data_dir = app_handle.path().app_data_dir()
-        .map_err(|e| format!("Failed to get app data dir: {}", e))?;
-    Ok(format!("{}", app_data_dir.display()))
+fn register_palette_action(action: palette::PluginAction) -> Result<(), String> {
+    palette::register(action)
 }
 
-/// Salva um arquivo temporário e retorna o caminho
+// ========== Plugins ==========
+
+/// Instala um plugin a partir de uma pasta ou ZIP com `plugin.json` (fica desativado)
 #[command]
-fn save_temp_file(app_handle: AppHandle, data: Vec<u8>, extension: String) -> Result<String, String> {
-    use std::time::{SystemTime, UNIX_EPOCH};
-    
-    // Obter diretório temporário
-    let temp_dir = std::env::temp_dir();
-    
-    // Criar nome de arquivo único
-    let timestamp = SystemTime::now()
-        .duration_since(UNIX_EPOCH)
-        .unwrap()
-        .as_millis();
-    let filename = format!("ollama_model_{}.{}", timestamp, extension);
-    let temp_path = temp_dir.join(&filename);
-    
-    // Escrever arquivo
-    fs::write(&temp_path, data)
-        .map_err(|e| format!("Erro ao salvar arquivo temporário: {}", e))?;
-    
-    Ok(temp_path.to_string_lossy().to_string())
+fn install_plugin(
+    app_handle: AppHandle,
+    processes: State<'_, McpProcessMap>,
+    path: String,
+) -> Result<plugins::PluginInfo, String> {
+    plugins::install(&app_handle, processes.inner(), std::path::Path::new(&path))
 }
 
-/// Abre um dialog de seleção de arquivo GGUF usando dialog nativo do sistema
 #[command]
-async fn open_gguf_file_dialog() -> Result<Option<String>, String> {
-    use rfd::FileDialog;
-    
-    // No rfd, o filtro "*" não funciona corretamente no Windows.
-    // Para garantir que todos os arquivos sejam mostrados, vamos criar
-    // um dialog sem filtro algum. O dialog nativo do Windows mostrará
-    // todos os arquivos por padrão quando não há filtro.
-    let file = FileDialog::new()
-        .set_title("Selecionar modelo GGUF")
-        .pick_file();
-    
-    Ok(file.map(|p| p.to_string_lossy().to_string()))
+fn list_plugins(
+    app_handle: AppHandle,
+    processes: State<'_, McpProcessMap>,
+) -> Result<Vec<plugins::PluginInfo>, String> {
+    plugins::list(&app_handle, processes.inner())
 }
 
-// ========== Sources Config Commands ==========
+/// Ativa ou desativa um plugin; as ferramentas aparecem como o servidor `plugin:<nome>`
+#[command]
+fn enable_plugin(
+    app_handle: AppHandle,
+    processes: State<'_, McpProcessMap>,
+    name: String,
+    enabled: bool,
+) -> Result<plugins::PluginInfo, String> {
+    plugins::set_enabled(&app_handle, processes.inner(), &name, enabled)
+}
 
-/// Carrega a configuração de fontes de busca
+// ========== Events ==========
+
+/// Define quais eventos tipados o backend emite (`kinds` ausente volta a emitir todos) e
+/// retorna o contrato atual: tipos, versões e assinaturas
 #[command]
-fn load_sources_config_command(app_handle: AppHandle) -> Result<SourcesConfig, String> {
-    load_sources_config(&app_handle)
+fn subscribe_events(kinds: Option<Vec<events::EventKind>>) -> Vec<events::EventDescriptor> {
+    events::subscribe(kinds)
 }
 
-/// Salva a configuração de fontes de busca
+// ========== Audit ==========
+
+/// Exporta o log de auditoria do período ("24h", "30d", "all"...) em JSONL e confere a cadeia de hashes
 #[command]
-fn save_sources_config_command(app_handle: AppHandle, config: SourcesConfig) -> Result<(), String> {
-    save_sources_config(&app_handle, config)
+async fn export_audit_log(app_handle: AppHandle, range: Option<String>) -> Result<audit::AuditExport, String> {
+    let range = range.unwrap_or_else(|| "all".to_string());
+    tokio::task::spawn_blocking(move || audit::export(&app_handle, &range))
+        .await
+        .map_err(|e| format!("Audit export task failed: {}", e))?
 }
 
-// ========== Ollama Installer Download Commands ==========
+// ========== Onboarding Commands ==========
 
-/// Verifica se uma URL de download está disponível
+/// Hardware, estado do Ollama e modelos já instalados (ponto de partida do primeiro uso)
+async fn onboarding_snapshot(app_handle: &AppHandle) -> (onboarding::HardwareSummary, onboarding::OllamaStatus, Vec<String>) {
+    let (total_ram_mb, cpu_count, gpu) = tokio::task::spawn_blocking(|| {
+        let mut sys = System::new();
+        sys.refresh_memory();
+        sys.refresh_cpu_all();
+        (sys.total_memory() / 1024 / 1024, sys.cpus().len(), system_monitor::detect_all_gpus().into_iter().next())
+    })
+    .await
+    .unwrap_or((0, 0, None));
+    // O total medido pelo monitor é mais confiável que o informado na detecção
+    let vram_mb = match primary_vram_total_mb().await {
+        Some(vram) => Some(vram),
+        None => gpu.as_ref().and_then(|g| g.memory_mb),
+    };
+    let hardware = onboarding::HardwareSummary {
+        total_ram_mb,
+        cpu_count,
+        gpu_name: gpu.map(|g| g.name),
+        vram_mb,
+    };
+    
+    let running = check_ollama_running(app_handle.clone()).await;
+    let ollama = onboarding::OllamaStatus {
+        installed: check_ollama_installed(),
+        running,
+        version: get_ollama_version(app_handle.clone()).await.ok().flatten(),
+    };
+    let installed_models = if running {
+        tokio::task::spawn_blocking(list_local_models)
+            .await
+            .map(|models| models.into_iter().map(|m| m.name).collect())
+            .unwrap_or_default()
+    } else {
+        Vec::new()
+    };
+    (hardware, ollama, installed_models)
+}
+
+/// Modelos iniciais recomendados para o hardware (RAM, VRAM, GPU) com os motivos, o estado
+/// do Ollama e o próximo passo do primeiro uso
 #[command]
-async fn check_download_url(url: String) -> Result<bool, String> {
-    let client = reqwest::Client::builder()
-        .timeout(Duration::from_secs(5))
-        .build()
-        .map_err(|e| format!("Failed to create HTTP client: {}", e))?;
+async fn get_onboarding_recommendations(app_handle: AppHandle) -> Result<onboarding::OnboardingRecommendations, String> {
+    let (hardware, ollama, installed_models) = onboarding_snapshot(&app_handle).await;
+    let models = onboarding::recommend(&hardware, &installed_models);
+    let next_step = onboarding::next_step(&ollama, &installed_models);
+    Ok(onboarding::OnboardingRecommendations { hardware, ollama, models, next_step })
+}
+
+/// Executa um passo do primeiro uso: instalar o Ollama → iniciar → baixar o modelo → primeiro
+/// chat. Passos já cumpridos voltam como "skipped"; o download emite `download-progress`.
+#[command]
+async fn run_onboarding_step(
+    window: Window,
+    app_handle: AppHandle,
+    state: State<'_, SettingsState>,
+    step: onboarding::OnboardingStep,
+) -> Result<onboarding::OnboardingStepResult, String> {
+    use onboarding::{OnboardingStep, OnboardingStepKind};
+    
+    let kind = step.kind();
+    let (hardware, ollama, installed_models) = onboarding_snapshot(&app_handle).await;
+    let result = |status: &str, message: String, model: Option<String>, session_id: Option<String>, next_step: OnboardingStepKind| {
+        onboarding::OnboardingStepResult { step: kind, status: status.to_string(), message, model, session_id, next_step }
+    };
     
-    match client.head(&url).send().await {
-        Ok(response) => Ok(response.status().is_success()),
-        Err(_) => Ok(false),
+    match step {
+        OnboardingStep::InstallOllama => {
+            if ollama.installed {
+                return Ok(result("skipped", "Ollama já está instalado".to_string(), None, None, OnboardingStepKind::StartOllama));
+            }
+            let release = ollama_update::latest_release().await?;
+            let (url, filename) = ollama_update::installer_for(&release.version)
+                .ok_or_else(|| "Instalação automática não suportada nesta plataforma".to_string())?;
+            let installer_path = download_installer(url, filename, window, app_handle).await?;
+            run_installer(installer_path)?;
+            log::info!("[Onboarding] Instalador do Ollama {} executado", release.version);
+            Ok(result(
+                "started",
+                format!("Instalador do Ollama {} aberto; conclua a instalação e siga para o próximo passo", release.version),
+                None,
+                None,
+                OnboardingStepKind::StartOllama,
+            ))
+        }
+        OnboardingStep::StartOllama => {
+            if ollama.running {
+                return Ok(result("skipped", "Ollama já está rodando".to_string(), None, None, OnboardingStepKind::PullModel));
+            }
+            if !ollama.installed {
+                return Err("Ollama não está instalado".to_string());
+            }
+            start_ollama_server(app_handle.clone())?;
+            if !wait_for_ollama(&app_handle, true).await {
+                return Err("Ollama não respondeu após iniciar".to_string());
+            }
+            Ok(result("done", "Ollama iniciado".to_string(), None, None, OnboardingStepKind::PullModel))
+        }
+        OnboardingStep::PullModel { model } => {
+            if !ollama.running {
+                return Err("Ollama não está rodando".to_string());
+            }
+            let model = model
+                .or_else(|| onboarding::default_model(&hardware, &installed_models))
+                .ok_or_else(|| "Nenhum modelo inicial cabe na memória disponível".to_string())?;
+            if installed_models.iter().any(|m| settings::normalize_model_name(m) == settings::normalize_model_name(&model)) {
+                return Ok(result("skipped", format!("{} já está instalado", model), Some(model), None, OnboardingStepKind::FirstChat));
+            }
+            pull_model(window, model.clone()).await?;
+            log::info!("[Onboarding] Modelo {} baixado", model);
+            Ok(result("done", format!("{} baixado", model), Some(model), None, OnboardingStepKind::FirstChat))
+        }
+        OnboardingStep::FirstChat { model } => {
+            let model = model
+                .or_else(|| onboarding::recommend(&hardware, &installed_models).into_iter().find(|m| m.installed).map(|m| m.name))
+                .or_else(|| installed_models.first().cloned())
+                .ok_or_else(|| "Nenhum modelo instalado".to_string())?;
+            
+            // Carrega o modelo e confirma que ele responde antes de abrir a conversa
+            let language = settings::current(&app_handle).language;
+            let greeting = ollama_client::OllamaClient::new(Some(settings::current(&app_handle).ollama_url))
+                .query_ollama_headless(
+                    &model,
+                    Some(&format!("Responda no idioma {}.", language)),
+                    "Apresente-se em uma frase curta para um novo usuário.",
+                )
+                .await?;
+            
+            let session_id = uuid::Uuid::new_v4().to_string();
+            let now = Utc::now();
+            let db = db::Database::new(&app_handle)
+                .map_err(|e| format!("Failed to open database: {}", e))?;
+            db.create_session(&db::ChatSession {
+                id: session_id.clone(),
+                title: "Primeiro chat".to_string(),
+                emoji: emoji_picker::DEFAULT_EMOJI.to_string(),
+                created_at: now,
+                updated_at: now,
+            })
+            .map_err(|e| format!("Failed to create session: {}", e))?;
+            db.set_session_config(&session_id, &db::SessionConfig { model: Some(model.clone()), ..Default::default() })
+                .map_err(|e| format!("Failed to save session config: {}", e))?;
+            
+            if settings::current(&app_handle).default_model.is_none() {
+                apply_settings_patch(&app_handle, &state, &serde_json::json!({ "default_model": model }))?;
+            }
+            log::info!("[Onboarding] Primeiro chat criado com {}", model);
+            Ok(result("done", greeting.trim().to_string(), Some(model), Some(session_id), OnboardingStepKind::Done))
+        }
     }
 }
 
-/// Obtém o caminho do instalador local se existir
+// ========== Diagnostics Commands ==========
+
+/// Diagnóstico para a tela de Suporte: Ollama, modelos, SQLite, pasta de chats, Chrome
+/// headless, servidores MCP e espaço em disco, cada item com pass/warn/fail e a correção sugerida
 #[command]
-fn get_local_installer_path(filename: String, app_handle: AppHandle) -> Result<Option<String>, String> {
-    // Tentar no diretório do executável (dev e produção)
-    // Em desenvolvimento, os arquivos estão em public/ relativo ao projeto
-    // Em produção, tentamos encontrar o arquivo em vários locais possíveis
-    if let Ok(exe_dir) = app_handle.path().executable_dir() {
-        // Tentar vários caminhos possíveis
-        let possible_paths = vec![
-            // Caminho relativo ao executável (dev) - subir até a raiz do projeto
-            exe_dir.parent()
-                .and_then(|p| p.parent())
-                .map(|p| p.join("public").join("installers").join(&filename)),
-            // Caminho direto do executável (dev)
-            exe_dir.parent()
-                .map(|p| p.join("public").join("installers").join(&filename)),
-            // Caminho absoluto do workspace (dev) - tentar encontrar a raiz
-            exe_dir.parent()
-                .and_then(|p| p.parent())
-                .and_then(|p| p.parent())
-                .map(|p| p.join("public").join("installers").join(&filename)),
-            // Em produção, arquivos podem estar no diretório do executável
-            Some(exe_dir.join("installers").join(&filename)),
-            // Ou no diretório pai do executável
-            exe_dir.parent()
-                .map(|p| p.join("installers").join(&filename)),
-        ];
-        
-        for path_opt in possible_paths {
-            if let Some(path) = path_opt {
-                if path.exists() {
-                    return Ok(Some(path.to_string_lossy().to_string()));
+async fn run_diagnostics(
+    app_handle: AppHandle,
+    processes: State<'_, McpProcessMap>,
+) -> Result<diagnostics::DiagnosticsReport, String> {
+    Ok(diagnostics::run(&app_handle, processes.inner()).await)
+}
+
+// ========== Smart Paste Commands ==========
+
+/// Máximo de palavras de um anexo inseridas no prompt (acima disso, apenas os trechos relevantes)
+const PASTE_EXPAND_MAX_TOKENS: usize = 1500;
+/// Máximo de caracteres enviados ao modelo para resumir um anexo
+const PASTE_SUMMARY_MAX_CHARS: usize = 12000;
+
+/// Resultado do tratamento de um texto colado
+#[derive(serde::Serialize)]
+struct SmartPasteResult {
+    /// Texto a inserir no prompt: o original ou o token de referência
+    text: String,
+    attachment_id: Option<String>,
+    title: Option<String>,
+    char_count: usize,
+    summary: Option<String>,
+    embedded: bool,
+}
+
+/// Seleciona os trechos de `content` mais relevantes para `query` (embeddings ou BM25)
+fn select_relevant_text(app_handle: &AppHandle, query: &str, content: &str, max_tokens: usize) -> String {
+    if resource_profile::current().embeddings_enabled {
+        if let Ok(app_data_dir) = app_handle.path().app_data_dir() {
+            if embeddings::is_model_available(&app_data_dir) {
+                match embeddings::get_or_init_model(&app_data_dir) {
+                    Ok(model_arc) => {
+                        if let Ok(mut model) = model_arc.lock() {
+                            match embeddings::prune_context(&mut model, query, content, max_tokens, 0.0) {
+                                Ok(pruned) => return pruned,
+                                Err(e) => log::warn!("[SmartPaste] Falha ao podar com embeddings: {}", e),
+                            }
+                        }
+                    }
+                    Err(e) => log::warn!("[SmartPaste] Falha ao carregar modelo de embeddings: {}", e),
                 }
             }
         }
     }
+    embeddings::prune_context_bm25(query, content, max_tokens)
+}
+
+/// Expande os tokens `[[paste:<id>]]` de uma mensagem com o conteúdo dos anexos
+fn expand_paste_references(app_handle: &AppHandle, db: &db::Database, text: &str) -> String {
+    let ids = paste::find_references(text);
+    if ids.is_empty() {
+        return text.to_string();
+    }
     
-    Ok(None)
+    let query = paste::strip_references(text);
+    let mut expanded = HashMap::new();
+    
+    for id in ids {
+        match db.get_paste_attachment(&id) {
+            Ok(Some(attachment)) => {
+                let partial = attachment.content.split_whitespace().count() > PASTE_EXPAND_MAX_TOKENS;
+                let content = if !partial {
+                    attachment.content.clone()
+                } else if !query.is_empty() {
+                    select_relevant_text(app_handle, &query, &attachment.content, PASTE_EXPAND_MAX_TOKENS)
+                } else {
+                    // Sem pergunta para guiar a seleção: usar o início do anexo
+                    attachment.content.split_whitespace().take(PASTE_EXPAND_MAX_TOKENS).collect::<Vec<_>>().join(" ")
+                };
+                expanded.insert(
+                    id,
+                    paste::format_attachment_block(&attachment.title, attachment.summary.as_deref(), &content, partial),
+                );
+            }
+            Ok(None) => log::warn!("[SmartPaste] Anexo {} referenciado mas não encontrado", id),
+            Err(e) => log::warn!("[SmartPaste] Falha ao carregar anexo {}: {}", id, e),
+        }
+    }
+    
+    paste::expand_references(text, &expanded)
 }
 
-/// Faz download do instalador da URL oficial ou usa fallback local
+/// Salva um texto como anexo referenciável por `[[paste:<id>]]` (usado por colagens e arquivos soltos)
+fn store_paste_attachment(
+    app_handle: &AppHandle,
+    session_id: Option<String>,
+    title: String,
+    content: String,
+    summary: Option<String>,
+    embedding: Option<Vec<u8>>,
+) -> Result<db::PasteAttachment, String> {
+    let db = db::Database::new(app_handle)
+        .map_err(|e| format!("Failed to open database: {}", e))?;
+    
+    // Só vincular à sessão se ela já existir (chave estrangeira)
+    let session_id = match session_id {
+        Some(id) => db.get_session(&id)
+            .map_err(|e| format!("Failed to load session: {}", e))?
+            .map(|_| id),
+        None => None,
+    };
+    
+    let attachment = db::PasteAttachment {
+        id: uuid::Uuid::new_v4().to_string(),
+        session_id,
+        title,
+        content,
+        summary,
+        has_embedding: embedding.is_some(),
+        created_at: Utc::now(),
+    };
+    
+    db.save_paste_attachment(&attachment, embedding.as_deref())
+        .map_err(|e| format!("Failed to save paste attachment: {}", e))?;
+    
+    Ok(attachment)
+}
+
+/// Trata um texto colado: abaixo do limite é devolvido como está; acima, vira um anexo
+/// (opcionalmente resumido e/ou com embedding) e um token de referência para o prompt
 #[command]
-async fn download_installer(
-    url: String,
-    filename: String,
-    window: Window,
+async fn smart_paste(
     app_handle: AppHandle,
-) -> Result<String, String> {
-    use std::io::Write;
-    use futures_util::StreamExt;
+    session_id: Option<String>,
+    text: String,
+    summarize: Option<bool>,
+    embed: Option<bool>,
+    model: Option<String>,
+) -> Result<SmartPasteResult, String> {
+    use ollama_client::OllamaClient;
     
-    // Primeiro, tentar usar instalador local como fallback
-    if let Some(local_path) = get_local_installer_path(filename.clone(), app_handle.clone())? {
-        let local_path_buf = PathBuf::from(&local_path);
-        if local_path_buf.exists() {
-            // Copiar para app_data_dir/installers
-            let app_data_dir = app_handle.path().app_data_dir()
-                .map_err(|e| format!("Failed to get app data dir: {}", e))?;
-            let installers_dir = app_data_dir.join("installers");
-            
-            if !installers_dir.exists() {
-                fs::create_dir_all(&installers_dir)
-                    .map_err(|e| format!("Failed to create installers directory: {}", e))?;
-            }
-            
-            let dest_path = installers_dir.join(&filename);
-            fs::copy(&local_path_buf, &dest_path)
-                .map_err(|e| format!("Failed to copy local installer: {}", e))?;
-            
-            window.emit("installer-download-progress", serde_json::json!({
-                "progress": 100,
-                "status": "Concluído (versão local)"
-            })).ok();
-            
-            return Ok(dest_path.to_string_lossy().to_string());
-        }
+    let app_settings = settings::current(&app_handle);
+    let char_count = text.chars().count();
+    
+    if char_count <= app_settings.smart_paste_threshold {
+        return Ok(SmartPasteResult {
+            text,
+            attachment_id: None,
+            title: None,
+            char_count,
+            summary: None,
+            embedded: false,
+        });
     }
     
-    // Fazer download da URL
-    let client = reqwest::Client::builder()
-        .timeout(Duration::from_secs(300)) // 5 minutos de timeout
-        .build()
-        .map_err(|e| format!("Failed to create HTTP client: {}", e))?;
+    let title = paste::attachment_title(&text);
+    
+    let summary = if summarize.unwrap_or(false) {
+        match model.or(app_settings.default_model.clone()) {
+            Some(model) => {
+                let client = OllamaClient::new(Some(app_settings.ollama_url.clone()));
+                let excerpt: String = text.chars().take(PASTE_SUMMARY_MAX_CHARS).collect();
+                let system_prompt = format!(
+                    "Resuma o texto do usuário em no máximo 5 frases, em {}. Responda apenas com o resumo.",
+                    translation::language_name(&app_settings.language)
+                );
+                match client.query_ollama_headless(&model, Some(&system_prompt), &excerpt).await {
+                    Ok(summary) => Some(summary.trim().to_string()),
+                    Err(e) => {
+                        log::warn!("[SmartPaste] Falha ao resumir anexo: {}", e);
+                        None
+                    }
+                }
+            }
+            None => {
+                log::warn!("[SmartPaste] Resumo solicitado sem modelo definido");
+                None
+            }
+        }
+    } else {
+        None
+    };
     
-    let response = client
-        .get(&url)
-        .send()
-        .await
-        .map_err(|e| format!("Failed to download installer: {}", e))?;
+    let embedding = if embed.unwrap_or(false) && resource_profile::current().embeddings_enabled {
+        match generate_embedding(app_handle.clone(), text.clone()) {
+            Ok(vector) => Some(paste::embedding_to_bytes(&vector)),
+            Err(e) => {
+                log::warn!("[SmartPaste] Falha ao gerar embedding: {}", e);
+                None
+            }
+        }
+    } else {
+        None
+    };
     
-    if !response.status().is_success() {
-        return Err(format!("Download failed with status: {}", response.status()));
-    }
+    let attachment = store_paste_attachment(&app_handle, session_id, title.clone(), text, summary.clone(), embedding)?;
     
-    // Obter tamanho total do arquivo
-    let total_size = response.content_length().unwrap_or(0);
+    log::info!("[SmartPaste] Texto de {} caracteres salvo como anexo {}", char_count, attachment.id);
     
-    // Criar diretório de instaladores
-    let app_data_dir = app_handle.path().app_data_dir()
-        .map_err(|e| format!("Failed to get app data dir: {}", e))?;
-    let installers_dir = app_data_dir.join("installers");
+    Ok(SmartPasteResult {
+        text: paste::reference_token(&attachment.id),
+        attachment_id: Some(attachment.id),
+        title: Some(title),
+        char_count,
+        summary,
+        embedded: attachment.has_embedding,
+    })
+}
+
+/// Retorna um anexo de texto colado (para pré-visualização na UI)
+#[command]
+fn get_paste_attachment(app_handle: AppHandle, id: String) -> Result<db::PasteAttachment, String> {
+    let db = db::Database::new(&app_handle)
+        .map_err(|e| format!("Failed to open database: {}", e))?;
     
-    if !installers_dir.exists() {
-        fs::create_dir_all(&installers_dir)
-            .map_err(|e| format!("Failed to create installers directory: {}", e))?;
+    db.get_paste_attachment(&id)
+        .map_err(|e| format!("Failed to load paste attachment: {}", e))?
+        .ok_or_else(|| "Anexo não encontrado".to_string())
+}
+
+// ========== API Token Commands ==========
+
+/// Cria um token de API com escopo (chat_only, read_history, admin).
+/// O valor do token só é retornado nesta chamada.
+#[command]
+fn create_api_token(
+    app_handle: AppHandle,
+    name: String,
+    scope: api_tokens::ApiTokenScope,
+) -> Result<api_tokens::CreatedApiToken, String> {
+    if name.trim().is_empty() {
+        return Err("Nome do token não pode estar vazio".to_string());
     }
     
-    let dest_path = installers_dir.join(&filename);
-    let mut file = fs::File::create(&dest_path)
-        .map_err(|e| format!("Failed to create file: {}", e))?;
+    let db = db::Database::new(&app_handle)
+        .map_err(|e| format!("Failed to open database: {}", e))?;
+    api_tokens::create_token(&db, name.trim(), scope)
+}
+
+/// Lista tokens de API (sem os valores)
+#[command]
+fn list_api_tokens(app_handle: AppHandle) -> Result<Vec<db::ApiToken>, String> {
+    let db = db::Database::new(&app_handle)
+        .map_err(|e| format!("Failed to open database: {}", e))?;
+    db.list_api_tokens()
+        .map_err(|e| format!("Failed to list API tokens: {}", e))
+}
+
+/// Revoga um token de API
+#[command]
+fn revoke_api_token(app_handle: AppHandle, id: String) -> Result<(), String> {
+    let db = db::Database::new(&app_handle)
+        .map_err(|e| format!("Failed to open database: {}", e))?;
     
-    let mut downloaded: u64 = 0;
-    let mut stream = response.bytes_stream();
+    let revoked = db.revoke_api_token(&id)
+        .map_err(|e| format!("Failed to revoke API token: {}", e))?;
+    if !revoked {
+        return Err("Token não encontrado ou já revogado".to_string());
+    }
     
-    while let Some(item) = stream.next().await {
-        let chunk = item.map_err(|e| format!("Failed to read chunk: {}", e))?;
-        file.write_all(&chunk)
-            .map_err(|e| format!("Failed to write chunk: {}", e))?;
-        
-        downloaded += chunk.len() as u64;
-        
-        // Emitir progresso
-        let progress = if total_size > 0 {
-            (downloaded * 100) / total_size
-        } else {
-            0
-        };
-        
-        window.emit("installer-download-progress", serde_json::json!({
-            "progress": progress,
-            "downloaded": downloaded,
-            "total": total_size,
-            "status": format!("Baixando... {}%", progress)
-        })).ok();
+    db.append_audit_log("api_token_revoked", &id, "")
+        .map_err(|e| format!("Failed to write audit log: {}", e))?;
+    Ok(())
+}
+
+// ========== Resource Profile Commands ==========
+
+/// Retorna o perfil de recursos efetivo (modo de baixo consumo)
+#[command]
+fn get_resource_profile() -> ResourceProfile {
+    resource_profile::current()
+}
+
+/// Define o modo de baixo consumo (auto/on/off), persistindo em settings.json
+#[command]
+fn set_low_resource_mode(
+    app_handle: AppHandle,
+    state: State<'_, SettingsState>,
+    mode: LowResourceMode,
+) -> Result<ResourceProfile, String> {
+    let patch = serde_json::json!({ "low_resource_mode": mode });
+    apply_settings_patch(&app_handle, &state, &patch)?;
+    Ok(resource_profile::current())
+}
+
+// ========== Prompt Library Commands ==========
+
+/// Cria ou atualiza um preset de prompt (id ausente = novo preset)
+#[command]
+fn save_prompt_preset(
+    app_handle: AppHandle,
+    id: Option<String>,
+    name: String,
+    content: String,
+    variables: Option<HashMap<String, String>>,
+) -> Result<db::PromptPreset, String> {
+    if name.trim().is_empty() {
+        return Err("Nome do preset não pode estar vazio".to_string());
+    }
+    if content.trim().is_empty() {
+        return Err("Conteúdo do preset não pode estar vazio".to_string());
     }
     
-    window.emit("installer-download-progress", serde_json::json!({
-        "progress": 100,
-        "status": "Download concluído"
-    })).ok();
+    let db = db::Database::new(&app_handle)
+        .map_err(|e| format!("Failed to open database: {}", e))?;
     
-    log::info!("Instalador baixado para: {:?}", dest_path);
-    Ok(dest_path.to_string_lossy().to_string())
+    let now = Utc::now();
+    let existing = match &id {
+        Some(id) => db.get_prompt_preset(id)
+            .map_err(|e| format!("Failed to load prompt preset: {}", e))?,
+        None => None,
+    };
+    
+    let preset = db::PromptPreset {
+        id: id.unwrap_or_else(|| uuid::Uuid::new_v4().to_string()),
+        name: name.trim().to_string(),
+        content,
+        variables: variables.unwrap_or_default(),
+        created_at: existing.map(|p| p.created_at).unwrap_or(now),
+        updated_at: now,
+    };
+    
+    db.save_prompt_preset(&preset)
+        .map_err(|e| format!("Failed to save prompt preset: {}", e))?;
+    Ok(preset)
 }
 
-/// Executa o instalador baixado
+/// Lista os presets de prompt
 #[command]
-fn run_installer(file_path: String) -> Result<(), String> {
-    let path = PathBuf::from(&file_path);
+fn list_prompt_presets(app_handle: AppHandle) -> Result<Vec<db::PromptPreset>, String> {
+    let db = db::Database::new(&app_handle)
+        .map_err(|e| format!("Failed to open database: {}", e))?;
+    db.list_prompt_presets()
+        .map_err(|e| format!("Failed to list prompt presets: {}", e))
+}
+
+/// Remove um preset de prompt
+#[command]
+fn delete_prompt_preset(app_handle: AppHandle, id: String) -> Result<(), String> {
+    let db = db::Database::new(&app_handle)
+        .map_err(|e| format!("Failed to open database: {}", e))?;
+    db.delete_prompt_preset(&id)
+        .map_err(|e| format!("Failed to delete prompt preset: {}", e))
+}
+
+/// Aplica um preset a uma sessão (None remove). As variáveis são renderizadas
+/// a cada envio em chat_stream, com os valores informados aqui sobrescrevendo os padrões.
+#[command]
+fn apply_prompt_preset(
+    app_handle: AppHandle,
+    session_id: String,
+    preset_id: Option<String>,
+    variables: Option<HashMap<String, String>>,
+) -> Result<(), String> {
+    let db = db::Database::new(&app_handle)
+        .map_err(|e| format!("Failed to open database: {}", e))?;
     
-    if !path.exists() {
-        return Err(format!("Instalador não encontrado: {}", file_path));
+    if let Some(preset_id) = &preset_id {
+        db.get_prompt_preset(preset_id)
+            .map_err(|e| format!("Failed to load prompt preset: {}", e))?
+            .ok_or_else(|| "Preset não encontrado".to_string())?;
     }
     
-    #[cfg(target_os = "windows")]
-    {
-        // No Windows, executar o .exe diretamente
-        Command::new(&path)
-            .spawn()
-            .map_err(|e| format!("Failed to run installer: {}", e))?;
+    // Garantir que a sessão existe (o preset pode ser escolhido antes da primeira mensagem)
+    let session_exists = db.get_session(&session_id)
+        .map_err(|e| format!("Failed to load session: {}", e))?
+        .is_some();
+    if !session_exists {
+        let now = Utc::now();
+        db.create_session(&db::ChatSession {
+            id: session_id.clone(),
+            title: "Nova Conversa".to_string(),
+            emoji: "💬".to_string(),
+            created_at: now,
+            updated_at: now,
+        }).map_err(|e| format!("Failed to create session: {}", e))?;
     }
     
-    #[cfg(target_os = "linux")]
-    {
-        // No Linux, dar permissão de execução e executar
-        use std::os::unix::fs::PermissionsExt;
-        let mut perms = fs::metadata(&path)
-            .map_err(|e| format!("Failed to get file metadata: {}", e))?
-            .permissions();
-        perms.set_mode(0o755);
-        fs::set_permissions(&path, perms)
-            .map_err(|e| format!("Failed to set executable permissions: {}", e))?;
-        
-        Command::new(&path)
-            .spawn()
-            .map_err(|e| format!("Failed to run installer: {}", e))?;
+    let variables_json = match variables {
+        Some(vars) if !vars.is_empty() => Some(serde_json::to_string(&vars)
+            .map_err(|e| format!("Failed to serialize variables: {}", e))?),
+        _ => None,
+    };
+    
+    db.set_session_prompt_preset(&session_id, preset_id.as_deref(), variables_json.as_deref())
+        .map_err(|e| format!("Failed to apply prompt preset: {}", e))
+}
+
+// ========== Conversation Template Commands ==========
+
+/// Sessão criada a partir de um template, já com as mensagens iniciais
+#[derive(serde::Serialize)]
+struct TemplateSession {
+    session_id: String,
+    title: String,
+    config: db::SessionConfig,
+    messages: Vec<Message>,
+}
+
+/// Cria ou atualiza um template de conversa (id ausente = novo template)
+#[command]
+fn save_conversation_template(
+    app_handle: AppHandle,
+    template: db::ConversationTemplateInput,
+) -> Result<db::ConversationTemplate, String> {
+    let db::ConversationTemplateInput { id, name, description, system_prompt, messages, model, rag_scope } = template;
+    if name.trim().is_empty() {
+        return Err("Nome do template não pode estar vazio".to_string());
     }
     
-    #[cfg(target_os = "macos")]
-    {
-        // No macOS, executar o .zip (precisa ser extraído primeiro)
-        // Por enquanto, apenas abrir o arquivo
-        Command::new("open")
-            .arg(&path)
-            .spawn()
-            .map_err(|e| format!("Failed to open installer: {}", e))?;
+    if let Some(invalid) = messages.iter().find(|m| !["system", "user", "assistant"].contains(&m.role.as_str())) {
+        return Err(format!("Papel de mensagem inválido: {}", invalid.role));
     }
     
-    log::info!("Instalador executado: {:?}", path);
-    Ok(())
+    let db = db::Database::new(&app_handle)
+        .map_err(|e| format!("Failed to open database: {}", e))?;
+    
+    let now = Utc::now();
+    let existing = match &id {
+        Some(id) => db.get_conversation_template(id)
+            .map_err(|e| format!("Failed to load conversation template: {}", e))?,
+        None => None,
+    };
+    
+    let template = db::ConversationTemplate {
+        id: id.unwrap_or_else(|| uuid::Uuid::new_v4().to_string()),
+        name: name.trim().to_string(),
+        description,
+        system_prompt: system_prompt.filter(|p| !p.trim().is_empty()),
+        messages,
+        model: model.filter(|m| !m.trim().is_empty()),
+        rag_scope,
+        created_at: existing.map(|t| t.created_at).unwrap_or(now),
+        updated_at: now,
+    };
+    
+    db.save_conversation_template(&template)
+        .map_err(|e| format!("Failed to save conversation template: {}", e))?;
+    Ok(template)
 }
 
-/// Verifica se o instalador já foi baixado
+/// Lista os templates de conversa
 #[command]
-fn get_downloaded_installer_path(filename: String, app_handle: AppHandle) -> Result<Option<String>, String> {
-    let app_data_dir = app_handle.path().app_data_dir()
-        .map_err(|e| format!("Failed to get app data dir: {}", e))?;
-    let installer_path = app_data_dir.join("installers").join(&filename);
-    
-    if installer_path.exists() {
-        Ok(Some(installer_path.to_string_lossy().to_string()))
-    } else {
-        Ok(None)
-    }
+fn list_conversation_templates(app_handle: AppHandle) -> Result<Vec<db::ConversationTemplate>, String> {
+    let db = db::Database::new(&app_handle)
+        .map_err(|e| format!("Failed to open database: {}", e))?;
+    db.list_conversation_templates()
+        .map_err(|e| format!("Failed to list conversation templates: {}", e))
 }
 
-// ========== Export & Backup Commands ==========
+/// Remove um template de conversa
+#[command]
+fn delete_conversation_template(app_handle: AppHandle, id: String) -> Result<(), String> {
+    let db = db::Database::new(&app_handle)
+        .map_err(|e| format!("Failed to open database: {}", e))?;
+    db.delete_conversation_template(&id)
+        .map_err(|e| format!("Failed to delete conversation template: {}", e))
+}
 
-/// Exporta todos os dados do app (chats, tasks, sources, settings) para um arquivo ZIP
+/// Cria uma nova sessão a partir de um template: mensagens iniciais, modelo,
+/// system prompt e escopo de RAG já configurados
 #[command]
-async fn export_all_data(app_handle: AppHandle) -> Result<String, String> {
-    use walkdir::WalkDir;
-    
-    let app_data_dir = app_handle.path().app_data_dir()
-        .map_err(|e| format!("Failed to get app data dir: {}", e))?;
-    
-    // Criar nome do arquivo com timestamp
-    let timestamp = Utc::now().format("%Y%m%d_%H%M%S");
-    let zip_path = app_data_dir.join(format!("ollahub_backup_{}.zip", timestamp));
+fn create_session_from_template(
+    app_handle: AppHandle,
+    file_locks: State<'_, FileLockMap>,
+    id: String,
+) -> Result<TemplateSession, String> {
+    let template = {
+        let db = db::Database::new(&app_handle)
+            .map_err(|e| format!("Failed to open database: {}", e))?;
+        db.get_conversation_template(&id)
+            .map_err(|e| format!("Failed to load conversation template: {}", e))?
+            .ok_or_else(|| "Template não encontrado".to_string())?
+    };
     
-    // Criar arquivo ZIP
-    let file = fs::File::create(&zip_path)
-        .map_err(|e| format!("Failed to create ZIP file: {}", e))?;
+    let session_id = uuid::Uuid::new_v4().to_string();
+    let messages: Vec<Message> = template.messages.iter().map(|m| Message {
+        id: None,
+        role: m.role.clone(),
+        content: m.content.clone(),
+        metadata: None,
+    }).collect();
+    
+    // Persistir como qualquer sessão (JSON + SQLite) para aparecer na lista de conversas
+    save_chat_session(
+        app_handle.clone(),
+        file_locks,
+        session_id.clone(),
+        template.name.clone(),
+        messages,
+        None,
+        None,
+    )?;
     
-    let mut zip = ZipWriter::new(file);
-    let options = FileOptions::default()
-        .compression_method(CompressionMethod::Deflated)
-        .unix_permissions(0o755);
+    let db = db::Database::new(&app_handle)
+        .map_err(|e| format!("Failed to open database: {}", e))?;
     
-    // 1. Adicionar pasta chats/ inteira
-    let chats_dir = get_chats_dir(&app_handle)?;
-    if chats_dir.exists() {
-        for entry in WalkDir::new(&chats_dir) {
-            let entry = entry.map_err(|e| format!("Failed to read directory entry: {}", e))?;
-            let path = entry.path();
-            
-            if path.is_file() {
-                // Obter caminho relativo a partir de chats_dir
-                let relative_path = path.strip_prefix(&chats_dir)
-                    .map_err(|e| format!("Failed to get relative path: {}", e))?;
-                
-                // Construir caminho no ZIP como "chats/nome_arquivo.json"
-                let zip_path = format!("chats/{}", relative_path.to_string_lossy().replace('\\', "/"));
-                
-                let file_content = fs::read(path)
-                    .map_err(|e| format!("Failed to read file {:?}: {}", path, e))?;
-                
-                zip.start_file(zip_path, options)
-                    .map_err(|e| format!("Failed to add file to ZIP: {}", e))?;
-                zip.write_all(&file_content)
-                    .map_err(|e| format!("Failed to write file to ZIP: {}", e))?;
-            }
-        }
+    // save_chat_session não cria a sessão no SQLite se o template não tem mensagens
+    if db.get_session(&session_id)
+        .map_err(|e| format!("Failed to load session: {}", e))?
+        .is_none()
+    {
+        let now = Utc::now();
+        db.create_session(&db::ChatSession {
+            id: session_id.clone(),
+            title: template.name.clone(),
+            emoji: "💬".to_string(),
+            created_at: now,
+            updated_at: now,
+        }).map_err(|e| format!("Failed to create session: {}", e))?;
     }
     
-    // 2. Adicionar tasks.json
-    let tasks_file = app_data_dir.join("tasks.json");
-    if tasks_file.exists() {
-        let tasks_content = fs::read_to_string(&tasks_file)
-            .map_err(|e| format!("Failed to read tasks.json: {}", e))?;
-        
-        zip.start_file("tasks.json", options)
-            .map_err(|e| format!("Failed to add tasks.json to ZIP: {}", e))?;
-        zip.write_all(tasks_content.as_bytes())
-            .map_err(|e| format!("Failed to write tasks.json to ZIP: {}", e))?;
-    }
+    let config = db::SessionConfig {
+        template_id: Some(template.id.clone()),
+        model: template.model.clone(),
+        system_prompt: template.system_prompt.clone(),
+        rag_scope: template.rag_scope.clone(),
+        generation_options: None,
+    };
+    db.set_session_config(&session_id, &config)
+        .map_err(|e| format!("Failed to save session config: {}", e))?;
+    
+    // Recarregar para devolver as mensagens com seus IDs
+    let messages = db.get_messages(&session_id)
+        .map_err(|e| format!("Failed to load messages: {}", e))?
+        .into_iter()
+        .map(|msg| Message {
+            id: msg.id,
+            role: msg.role,
+            content: msg.content,
+            metadata: None,
+        })
+        .collect();
     
-    // 3. Adicionar sources.json
-    let sources_file = app_data_dir.join("sources.json");
-    if sources_file.exists() {
-        let sources_content = fs::read_to_string(&sources_file)
-            .map_err(|e| format!("Failed to read sources.json: {}", e))?;
-        
-        zip.start_file("sources.json", options)
-            .map_err(|e| format!("Failed to add sources.json to ZIP: {}", e))?;
-        zip.write_all(sources_content.as_bytes())
-            .map_err(|e| format!("Failed to write sources.json to ZIP: {}", e))?;
-    } else {
-        // Se não existir, criar um sources.json padrão no ZIP
-        let default_config = SourcesConfig::default();
-        let default_json = serde_json::to_string_pretty(&default_config)
-            .map_err(|e| format!("Failed to serialize default sources config: {}", e))?;
-        
-        zip.start_file("sources.json", options)
-            .map_err(|e| format!("Failed to add default sources.json to ZIP: {}", e))?;
-        zip.write_all(default_json.as_bytes())
-            .map_err(|e| format!("Failed to write default sources.json to ZIP: {}", e))?;
-    }
+    log::info!("Sessão {} criada a partir do template {}", session_id, template.name);
     
-    // 4. Adicionar settings.json (se existir)
-    let settings_file = app_data_dir.join("settings.json");
-    if settings_file.exists() {
-        let settings_content = fs::read_to_string(&settings_file)
-            .map_err(|e| format!("Failed to read settings.json: {}", e))?;
-        
-        zip.start_file("settings.json", options)
-            .map_err(|e| format!("Failed to add settings.json to ZIP: {}", e))?;
-        zip.write_all(settings_content.as_bytes())
-            .map_err(|e| format!("Failed to write settings.json to ZIP: {}", e))?;
+    Ok(TemplateSession {
+        session_id,
+        title: template.name,
+        config,
+        messages,
+    })
+}
+
+/// Retorna a configuração própria de uma sessão (modelo, system prompt, escopo RAG)
+#[command]
+fn get_session_config(app_handle: AppHandle, session_id: String) -> Result<db::SessionConfig, String> {
+    let db = db::Database::new(&app_handle)
+        .map_err(|e| format!("Failed to open database: {}", e))?;
+    Ok(db.get_session_config(&session_id)
+        .map_err(|e| format!("Failed to load session config: {}", e))?
+        .unwrap_or_default())
+}
+
+/// Define (ou remove, com `options = null`) as opções de geração padrão de uma sessão
+#[command]
+fn set_session_generation_options(
+    app_handle: AppHandle,
+    session_id: String,
+    options: Option<ollama_client::GenerationOptions>,
+) -> Result<db::SessionConfig, String> {
+    if let Some(options) = &options {
+        let mut errors = Vec::new();
+        options.validate("options", &mut errors);
+        if !errors.is_empty() {
+            return Err(errors.join("; "));
+        }
     }
     
-    // Finalizar ZIP
-    zip.finish()
-        .map_err(|e| format!("Failed to finalize ZIP: {}", e))?;
+    let db = db::Database::new(&app_handle)
+        .map_err(|e| format!("Failed to open database: {}", e))?;
+    let mut config = db.get_session_config(&session_id)
+        .map_err(|e| format!("Failed to load session config: {}", e))?
+        .ok_or_else(|| format!("Sessão não encontrada: {}", session_id))?;
+    config.generation_options = options.filter(|o| !o.is_empty());
+    db.set_session_config(&session_id, &config)
+        .map_err(|e| format!("Failed to save session config: {}", e))?;
+    Ok(config)
+}
+
+// ========== Share Commands ==========
+
+/// Renderiza a sessão como HTML autocontido (prompts de sistema omitidos por padrão)
+fn render_shared_session(app_handle: &AppHandle, session_id: &str, redact_system_prompts: bool) -> Result<(String, String), String> {
+    let db = db::Database::new(app_handle)
+        .map_err(|e| format!("Failed to open database: {}", e))?;
+    let session = db.get_session(session_id)
+        .map_err(|e| format!("Failed to load session: {}", e))?
+        .ok_or_else(|| format!("Sessão não encontrada: {}", session_id))?;
+    let messages = db.get_messages(session_id)
+        .map_err(|e| format!("Failed to load messages: {}", e))?;
+    let system_prompt = db.get_session_config(session_id)
+        .map_err(|e| format!("Failed to load session config: {}", e))?
+        .and_then(|config| config.system_prompt);
+    
+    let html = share::render_html(&session, system_prompt.as_deref(), &messages, redact_system_prompts);
+    Ok((session.title, html))
+}
+
+/// Exporta uma conversa como um único arquivo HTML para compartilhar.
+/// Sem `path`, abre o diálogo de salvar; retorna None se o usuário cancelar.
+#[command]
+async fn share_session(
+    app_handle: AppHandle,
+    session_id: String,
+    path: Option<String>,
+    redact_system_prompts: Option<bool>,
+) -> Result<Option<String>, String> {
+    use rfd::FileDialog;
     
-    log::info!("Backup completo exportado para: {}", zip_path.display());
-    Ok(format!("{}", zip_path.display()))
+    let (title, html) = render_shared_session(&app_handle, &session_id, redact_system_prompts.unwrap_or(true))?;
+    
+    let path = match path {
+        Some(path) => PathBuf::from(path),
+        None => {
+            let file_name: String = title
+                .chars()
+                .map(|c| if c.is_alphanumeric() || c == ' ' || c == '-' { c } else { '_' })
+                .collect();
+            match FileDialog::new()
+                .set_title("Compartilhar conversa")
+                .set_file_name(format!("{}.html", file_name.trim()))
+                .add_filter("HTML", &["html"])
+                .save_file()
+            {
+                Some(path) => path,
+                None => return Ok(None),
+            }
+        }
+    };
+    
+    fs::write(&path, html)
+        .map_err(|e| format!("Failed to write HTML file: {}", e))?;
+    log::info!("Sessão {} compartilhada em {:?}", session_id, path);
+    Ok(Some(path.to_string_lossy().to_string()))
+}
+
+/// Serve o HTML da conversa na rede local por alguns minutos (URL + QR code para abrir no celular)
+#[command]
+async fn preview_shared_session(
+    app_handle: AppHandle,
+    session_id: String,
+    redact_system_prompts: Option<bool>,
+) -> Result<share::SharePreview, String> {
+    let (_, html) = render_shared_session(&app_handle, &session_id, redact_system_prompts.unwrap_or(true))?;
+    share::start_preview(html).await
 }
 
-// ========== Logs Commands ==========
+/// Encerra o preview local da conversa compartilhada
+#[command]
+fn stop_share_preview() -> bool {
+    share::stop_preview()
+}
 
-/// Obtém as últimas N linhas dos logs do sistema
+// ========== Speech-to-Text Commands ==========
+
+/// Transcreve áudio (arquivo em `path` ou bytes gravados) com whisper.cpp.
+/// Trechos parciais chegam pelo evento `stt-partial` com o `request_id` informado.
 #[command]
-fn get_recent_logs(app_handle: AppHandle, lines: usize) -> Result<Vec<String>, String> {
-    // O tauri-plugin-log geralmente salva logs em app_data_dir/logs/
-    let app_data_dir = app_handle.path().app_data_dir()
-        .map_err(|e| format!("Failed to get app data dir: {}", e))?;
-    
-    let logs_dir = app_data_dir.join("logs");
-    
-    // Se o diretório de logs não existir, retornar vazio
-    if !logs_dir.exists() {
-        return Ok(Vec::new());
-    }
-    
-    // Procurar pelo arquivo de log mais recente
-    let mut log_files: Vec<_> = fs::read_dir(&logs_dir)
-        .map_err(|e| format!("Failed to read logs directory: {}", e))?
-        .filter_map(|entry| {
-            entry.ok().and_then(|e| {
-                let path = e.path();
-                if path.is_file() && path.extension().and_then(|s| s.to_str()) == Some("log") {
-                    Some((path, e.metadata().ok()?.modified().ok()?))
-                } else {
-                    None
-                }
-            })
-        })
-        .collect();
-    
-    // Ordenar por data de modificação (mais recente primeiro)
-    log_files.sort_by(|a, b| b.1.cmp(&a.1));
-    
-    // Ler o arquivo mais recente
-    if let Some((log_file_path, _)) = log_files.first() {
-        let content = fs::read_to_string(log_file_path)
-            .map_err(|e| format!("Failed to read log file: {}", e))?;
-        
-        // Dividir em linhas e pegar as últimas N
-        let all_lines: Vec<String> = content.lines().map(|s| s.to_string()).collect();
-        let total_lines = all_lines.len();
-        let start = if total_lines > lines { total_lines - lines } else { 0 };
-        
-        Ok(all_lines[start..].to_vec())
-    } else {
-        Ok(Vec::new())
-    }
+async fn transcribe_audio(
+    app_handle: AppHandle,
+    path: Option<String>,
+    bytes: Option<Vec<u8>>,
+    language: Option<String>,
+    request_id: Option<String>,
+) -> Result<stt::Transcription, String> {
+    let input = match (path, bytes) {
+        (Some(path), _) => stt::AudioInput::Path(PathBuf::from(path)),
+        (None, Some(bytes)) if !bytes.is_empty() => stt::AudioInput::Bytes(bytes),
+        _ => return Err("Informe o caminho ou os bytes do áudio".to_string()),
+    };
+    stt::transcribe(&app_handle, input, language, request_id).await
 }
 
-/// Recebe logs do frontend e os imprime no terminal
+/// Disponibilidade do whisper.cpp, do modelo configurado e do ffmpeg
 #[command]
-fn log_to_terminal(level: String, message: String) -> Result<(), String> {
-    match level.as_str() {
-        "info" => log::info!("{}", message),
-        "warn" => log::warn!("{}", message),
-        "error" => log::error!("{}", message),
-        "debug" => log::debug!("{}", message),
-        _ => log::info!("{}", message),
-    }
-    Ok(())
+fn get_stt_status(app_handle: AppHandle) -> Result<stt::SttStatus, String> {
+    stt::status(&app_handle)
 }
 
-// ========== System Monitor Commands ==========
+/// Baixa o modelo whisper (padrão: o configurado em `stt.model`)
+#[command]
+async fn download_stt_model(app_handle: AppHandle, model: Option<String>) -> Result<String, String> {
+    let model = model.unwrap_or_else(|| settings::current(&app_handle).stt.model);
+    let path = stt::download_model(&app_handle, &model).await?;
+    Ok(path.to_string_lossy().to_string())
+}
 
-/// Obtém estatísticas do sistema em tempo real
+// ========== Code Snippet Commands ==========
+
+/// Blocos de código cercados de uma mensagem, indicando quais podem ser executados
 #[command]
-fn get_system_stats(
-    monitor_state: State<'_, Arc<Mutex<SystemMonitorState>>>,
-) -> Result<SystemStats, String> {
-    let mut monitor = monitor_state.lock()
-        .map_err(|e| format!("Failed to lock monitor state: {}", e))?;
-    
-    Ok(monitor.get_stats())
+fn extract_code_blocks(app_handle: AppHandle, message_id: i64) -> Result<Vec<code_runner::CodeBlock>, String> {
+    let db = db::Database::new(&app_handle)
+        .map_err(|e| format!("Failed to open database: {}", e))?;
+    let message = db.get_message(message_id)
+        .map_err(|e| format!("Failed to load message: {}", e))?
+        .ok_or_else(|| format!("Mensagem não encontrada: {}", message_id))?;
+    Ok(code_runner::extract_code_blocks(&message.content))
 }
 
-/// Obtém estatísticas detalhadas de uma GPU específica
+/// Executa um snippet Python/Node/Shell em diretório temporário, com timeout e sem rede por padrão
 #[command]
-fn get_gpu_stats(gpu_id: Option<String>) -> Result<Option<GpuStats>, String> {
-    Ok(system_monitor::get_gpu_stats(gpu_id.as_deref()))
+async fn run_code_snippet(
+    language: String,
+    code: String,
+    timeout_secs: Option<u64>,
+    allow_network: Option<bool>,
+) -> Result<code_runner::RunResult, String> {
+    code_runner::run(&language, &code, timeout_secs, allow_network.unwrap_or(false)).await
 }
 
-// ========== Task Scheduler Commands ==========
+// ========== Workspace Commands ==========
 
+/// Anexa uma pasta local à sessão: indexa os arquivos de texto (respeitando .gitignore),
+/// inicia o job de embeddings e passa a observar alterações
 #[command]
-async fn create_task(
-    scheduler: State<'_, SchedulerState>,
-    label: String,
-    cron_schedule: String,
-    action: TaskAction,
-) -> Result<String, String> {
-    use uuid::Uuid;
+async fn attach_workspace(app_handle: AppHandle, session_id: String, path: String) -> Result<workspace::IndexReport, String> {
+    let root = PathBuf::from(&path).canonicalize()
+        .map_err(|e| format!("Failed to resolve workspace path: {}", e))?;
+    if !root.is_dir() {
+        return Err(format!("Não é uma pasta: {}", path));
+    }
     
-    let task = SentinelTask {
-        id: Uuid::new_v4().to_string(),
-        label,
-        cron_schedule,
-        action,
-        enabled: true,
-        last_run: None,
-        created_at: Utc::now(),
-        updated_at: Utc::now(),
-    };
+    let db = db::Database::new(&app_handle)
+        .map_err(|e| format!("Failed to open database: {}", e))?;
+    if db.get_session(&session_id).map_err(|e| format!("Failed to load session: {}", e))?.is_none() {
+        return Err(format!("Sessão não encontrada: {}", session_id));
+    }
+    // Trocar de pasta descarta o índice anterior
+    db.delete_workspace(&session_id)
+        .map_err(|e| format!("Failed to reset workspace: {}", e))?;
+    db.set_workspace(&session_id, &root.to_string_lossy())
+        .map_err(|e| format!("Failed to save workspace: {}", e))?;
+    
+    let index_root = root.clone();
+    let index_session = session_id.clone();
+    let mut report = tauri::async_runtime::spawn_blocking(move || workspace::index(&db, &index_session, &index_root))
+        .await
+        .map_err(|e| format!("Failed to join workspace index task: {}", e))??;
     
-    let mut sched = scheduler.lock().await;
-    sched.upsert_task(task.clone())?;
-    Ok(task.id)
+    workspace::watch(&app_handle, &session_id, root)?;
+    
+    report.embedding_job_id = match start_embedding_job(app_handle.clone(), Some(session_id)) {
+        Ok(job_id) => Some(job_id),
+        Err(e) => {
+            log::info!("[Workspace] Embeddings não gerados: {}", e);
+            None
+        }
+    };
+    Ok(report)
 }
 
+/// Desanexa a pasta da sessão e remove os trechos indexados
 #[command]
-async fn list_tasks(
-    scheduler: State<'_, SchedulerState>,
-) -> Result<Vec<SentinelTask>, String> {
-    let sched = scheduler.lock().await;
-    Ok(sched.list_tasks())
+fn detach_workspace(app_handle: AppHandle, session_id: String) -> Result<(), String> {
+    workspace::unwatch(&session_id);
+    db::Database::new(&app_handle)
+        .and_then(|db| db.delete_workspace(&session_id))
+        .map_err(|e| format!("Failed to detach workspace: {}", e))
 }
 
+/// Arquivos indexados do workspace da sessão
 #[command]
-async fn update_task(
-    scheduler: State<'_, SchedulerState>,
-    task: SentinelTask,
-) -> Result<(), String> {
-    let mut sched = scheduler.lock().await;
-    let mut updated = task;
-    updated.updated_at = Utc::now();
-    sched.upsert_task(updated)
+fn list_workspace_files(app_handle: AppHandle, session_id: String) -> Result<Vec<workspace::WorkspaceFile>, String> {
+    let files = db::Database::new(&app_handle)
+        .and_then(|db| db.list_workspace_files(&session_id))
+        .map_err(|e| format!("Failed to load workspace files: {}", e))?;
+    Ok(files.into_iter().map(|(path, chunks)| workspace::WorkspaceFile { path, chunks }).collect())
 }
 
+/// Trechos do workspace mais relevantes para a consulta (contexto para o chat)
 #[command]
-async fn delete_task(
-    scheduler: State<'_, SchedulerState>,
-    id: String,
-) -> Result<(), String> {
-    let mut sched = scheduler.lock().await;
-    sched.remove_task(&id)
+async fn search_workspace(
+    app_handle: AppHandle,
+    session_id: String,
+    query: String,
+    limit: Option<usize>,
+) -> Result<Vec<workspace::WorkspaceHit>, String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        workspace::search(&app_handle, &session_id, &query, limit.unwrap_or(8).clamp(1, 50))
+    })
+    .await
+    .map_err(|e| format!("Failed to join workspace search task: {}", e))?
 }
 
+/// Conteúdo atual de um arquivo do workspace (caminho relativo à pasta anexada)
 #[command]
-async fn toggle_task(
-    scheduler: State<'_, SchedulerState>,
-    id: String,
-    enabled: bool,
-) -> Result<(), String> {
-    let mut sched = scheduler.lock().await;
-    if let Some(mut task) = sched.get_task(&id).cloned() {
-        task.enabled = enabled;
-        task.updated_at = Utc::now();
-        sched.upsert_task(task)
-    } else {
-        Err("Task not found".to_string())
-    }
+fn read_workspace_file(app_handle: AppHandle, session_id: String, path: String) -> Result<String, String> {
+    let root = db::Database::new(&app_handle)
+        .and_then(|db| db.get_workspace(&session_id))
+        .map_err(|e| format!("Failed to load workspace: {}", e))?
+        .ok_or_else(|| "A sessão não tem workspace anexado".to_string())?
+        .root_path;
+    workspace::read_file(std::path::Path::new(&root), &path)
 }
 
+// ========== Citation Commands ==========
+
+/// Exporta as fontes citadas em uma sessão como BibTeX ou CSL-JSON
 #[command]
-fn classify_intent(query: String) -> String {
-    use intent_classifier::{IntentClassifier, QueryIntent};
-    let intent = IntentClassifier::classify(&query);
-    match intent {
-        QueryIntent::Factual => "factual".to_string(),
-        QueryIntent::Conversational => "conversational".to_string(),
-        QueryIntent::Technical => "technical".to_string(),
-        QueryIntent::Opinion => "opinion".to_string(),
-        QueryIntent::Calculation => "calculation".to_string(),
-        QueryIntent::Unknown => "unknown".to_string(),
+fn export_citations(
+    app_handle: AppHandle,
+    session_id: String,
+    format: citations::CitationFormat,
+) -> Result<String, String> {
+    let db = db::Database::new(&app_handle)
+        .map_err(|e| format!("Failed to open database: {}", e))?;
+    let messages = db.get_messages(&session_id)
+        .map_err(|e| format!("Failed to load messages: {}", e))?;
+
+    let sources = citations::collect_sources(&messages);
+    log::info!("Exportando {} citações da sessão {} ({:?})", sources.len(), session_id, format);
+
+    match format {
+        citations::CitationFormat::Bibtex => Ok(citations::to_bibtex(&sources)),
+        citations::CitationFormat::CslJson => citations::to_csl_json(&sources),
     }
 }
 
-/// Comando principal para streaming de chat via Rust
+/// Guarda o markdown completo das fontes usadas em uma resposta, para o usuário abrir
+/// depois exatamente o que o modelo leu (mesmo que a página mude ou saia do ar)
 #[command]
-async fn chat_stream(
-    window: Window,
+fn save_message_sources(
     app_handle: AppHandle,
     session_id: Option<String>,
-    messages: Vec<Message>,
-    model: String,
-    system_prompt: Option<String>,
-    enable_rag: Option<bool>,
-) -> Result<String, String> {
-    use uuid::Uuid;
-    use ollama_client::OllamaClient;
-    use futures_util::StreamExt;
-    use db::{Database, ChatSession, ChatMessage};
-    
-    // Gerar ou usar session_id existente
-    let session_id = session_id.unwrap_or_else(|| Uuid::new_v4().to_string());
-    let enable_rag = enable_rag.unwrap_or(false);
-    
-    // Verificar se é nova sessão (apenas 1 mensagem do usuário)
-    let is_new_session = messages.len() == 1 && messages[0].role == "user";
-    
-    // Variáveis para título e emoji (usadas depois na persistência)
-    let (title, emoji) = if is_new_session {
-        let user_input = &messages[0].content;
-        let ollama_client = OllamaClient::new(None);
-        
-        // Tentar gerar título (com timeout curto)
-        let generated_title = match tokio::time::timeout(
-            tokio::time::Duration::from_secs(5),
-            ollama_client.generate_title(&model, user_input)
-        ).await {
-            Ok(Ok(t)) => t,
-            Ok(Err(e)) => {
-                log::warn!("Erro ao gerar título: {}. Usando fallback.", e);
-                // Fallback: primeiras palavras da pergunta
-                user_input.split_whitespace().take(5).collect::<Vec<_>>().join(" ")
-            },
-            Err(_) => {
-                log::warn!("Timeout ao gerar título. Usando fallback.");
-                user_input.split_whitespace().take(5).collect::<Vec<_>>().join(" ")
-            }
-        };
-        
-        let generated_emoji = OllamaClient::generate_emoji(&generated_title);
-        
-        // Emitir evento de chat criado
-        let created_event = ChatCreatedEvent {
+    message_id: i64,
+    sources: Vec<ScrapedContent>,
+) -> Result<Vec<db::MessageSource>, String> {
+    let now = Utc::now();
+    let snapshots: Vec<db::MessageSource> = sources
+        .into_iter()
+        .filter(|source| !source.markdown.trim().is_empty())
+        .map(|source| db::MessageSource {
+            id: uuid::Uuid::new_v4().to_string(),
+            message_id,
             session_id: session_id.clone(),
-            title: generated_title.clone(),
-            emoji: generated_emoji.clone(),
-        };
-        
-        if let Err(e) = window.emit("chat-created", &created_event) {
-            log::warn!("Erro ao emitir evento chat-created: {}", e);
-        }
-        
-        (generated_title, generated_emoji)
-    } else {
-        (String::new(), "💬".to_string())
-    };
-    
-    // 2. Preparar mensagens para Ollama
-    let mut ollama_messages = Vec::new();
+            url: source.url,
+            title: source.title,
+            content: source.markdown,
+            created_at: now,
+        })
+        .collect();
     
-    // Adicionar system prompt se fornecido
-    if let Some(sys_prompt) = system_prompt {
-        ollama_messages.push(serde_json::json!({
-            "role": "system",
-            "content": sys_prompt
-        }));
+    let db = db::Database::new(&app_handle)
+        .map_err(|e| format!("Failed to open database: {}", e))?;
+    db.save_message_sources(&snapshots)
+        .map_err(|e| format!("Failed to save message sources: {}", e))?;
+    log::info!("{} fonte(s) salva(s) para a mensagem {}", snapshots.len(), message_id);
+    Ok(snapshots)
+}
+
+/// Cópias das fontes que embasaram uma mensagem
+#[command]
+fn get_message_sources(app_handle: AppHandle, message_id: i64) -> Result<Vec<db::MessageSource>, String> {
+    let db = db::Database::new(&app_handle)
+        .map_err(|e| format!("Failed to open database: {}", e))?;
+    db.get_message_sources(message_id)
+        .map_err(|e| format!("Failed to load message sources: {}", e))
+}
+
+// ========== Ollama Server Tuning Commands ==========
+
+/// Tempo máximo esperando o servidor parar/subir durante o reinício
+const OLLAMA_RESTART_TIMEOUT_SECS: u64 = 20;
+/// Máximo de requisições paralelas usadas no benchmark
+const TUNING_BENCHMARK_MAX_REQUESTS: usize = 4;
+
+/// Resultado da aplicação dos ajustes do servidor, com benchmark antes/depois
+#[derive(serde::Serialize)]
+struct OllamaTuningResult {
+    tuning: settings::OllamaServerTuning,
+    restarted: bool,
+    before: Option<ollama_client::BenchmarkResult>,
+    after: Option<ollama_client::BenchmarkResult>,
+}
+
+/// Aguarda o servidor ficar no estado `running` esperado
+async fn wait_for_ollama(app_handle: &AppHandle, running: bool) -> bool {
+    let deadline = std::time::Instant::now() + std::time::Duration::from_secs(OLLAMA_RESTART_TIMEOUT_SECS);
+    while std::time::Instant::now() < deadline {
+        if check_ollama_running(app_handle.clone()).await == running {
+            return true;
+        }
+        tokio::time::sleep(tokio::time::Duration::from_millis(500)).await;
     }
-    
-    // Converter mensagens para formato Ollama
-    for msg in &messages {
-        ollama_messages.push(serde_json::json!({
-            "role": msg.role,
-            "content": msg.content
-        }));
+    false
+}
+
+/// Reinicia o servidor Ollama com as variáveis de ambiente atuais das configurações.
+/// Retorna se o servidor voltou a responder.
+#[command]
+async fn restart_ollama_server(app_handle: AppHandle) -> Result<bool, String> {
+    ollama_server::stop_spawned();
+    let stopped = stop_ollama_processes();
+    log::info!("[OllamaServer] {} processo(s) do Ollama encerrado(s)", stopped);
+    if !wait_for_ollama(&app_handle, false).await {
+        return Err("Ollama não parou a tempo (pode estar rodando como serviço do sistema)".to_string());
     }
     
-    // 3. TODO: Classificar intent e aplicar RAG se necessário
-    // if enable_rag {
-    //     let intent = classify_intent(messages.last().unwrap().content.clone());
-    //     // Buscar contexto via RAG
-    //     // Injetar no system prompt
-    // }
-    
-    // 4. Fazer requisição streaming para Ollama
-    let ollama_client = OllamaClient::new(None);
-    ollama_client.check_connection().await?;
-    
-    let request = serde_json::json!({
-        "model": model,
-        "messages": ollama_messages,
-        "stream": true
-    });
+    start_ollama_server(app_handle.clone())?;
+    Ok(wait_for_ollama(&app_handle, true).await)
+}
+
+/// Encerra o servidor iniciado pelo app; com `force`, também outros processos `ollama`
+/// (um servidor rodando como serviço do sistema pode ser reiniciado pelo próprio serviço)
+#[command]
+async fn stop_ollama_server(app_handle: AppHandle, force: Option<bool>) -> Result<ollama_server::StopResult, String> {
+    let spawned_pid = ollama_server::stop_spawned();
+    let other_processes = if force.unwrap_or(false) { stop_ollama_processes() } else { 0 };
+    if spawned_pid.is_none() && other_processes == 0 {
+        return Err("Nenhum servidor Ollama iniciado pelo app está rodando (use force para encerrar os demais)".to_string());
+    }
+    wait_for_ollama(&app_handle, false).await;
+    Ok(ollama_server::StopResult { spawned_pid, other_processes })
+}
+
+/// Variáveis de ambiente do servidor e como aplicá-las a um Ollama instalado como serviço
+#[derive(serde::Serialize)]
+struct OllamaEnvInfo {
+    /// Variáveis configuradas (`ollama_env`)
+    vars: HashMap<String, String>,
+    /// Ambiente efetivo do `ollama serve` (ajustes de desempenho + variáveis configuradas)
+    effective: std::collections::BTreeMap<String, String>,
+    spawned_pid: Option<u32>,
+    service_hints: Vec<ollama_server::ServiceHint>,
+    restarted: bool,
+}
+
+fn ollama_env_info(app_settings: &AppSettings, restarted: bool) -> OllamaEnvInfo {
+    let effective = ollama_server::env_vars(app_settings);
+    OllamaEnvInfo {
+        vars: app_settings.ollama_env.clone(),
+        service_hints: ollama_server::service_hints(&effective),
+        effective,
+        spawned_pid: ollama_server::spawned_pid(),
+        restarted,
+    }
+}
+
+#[command]
+fn get_ollama_env(app_handle: AppHandle) -> OllamaEnvInfo {
+    ollama_env_info(&settings::current(&app_handle), false)
+}
+
+/// Salva as variáveis do `ollama serve` (OLLAMA_HOST, OLLAMA_MODELS, OLLAMA_NUM_PARALLEL...)
+/// e, com `restart`, reinicia o servidor para aplicá-las
+#[command]
+async fn configure_ollama_env(
+    app_handle: AppHandle,
+    state: State<'_, SettingsState>,
+    vars: HashMap<String, String>,
+    restart: Option<bool>,
+) -> Result<OllamaEnvInfo, String> {
+    // Substitui o mapa inteiro: chaves ausentes viram null no merge-patch
+    let mut env_patch = serde_json::Map::new();
+    for key in settings::current(&app_handle).ollama_env.keys() {
+        env_patch.insert(key.clone(), serde_json::Value::Null);
+    }
+    for (key, value) in vars {
+        env_patch.insert(key, serde_json::Value::String(value));
+    }
+    let patch = serde_json::json!({ "ollama_env": env_patch });
+    let saved = apply_settings_patch(&app_handle, &state, &patch)?;
+    if saved.ollama_env.contains_key("OLLAMA_HOST") {
+        log::warn!("[OllamaServer] OLLAMA_HOST configurado; ajuste também ollama_url se o endereço mudar");
+    }
     
-    // Usar reqwest diretamente para streaming
-    let client = reqwest::Client::builder()
-        .timeout(std::time::Duration::from_secs(300))
-        .build()
-        .map_err(|e| format!("Failed to create HTTP client: {}", e))?;
+    let restarted = restart.unwrap_or(false)
+        && check_ollama_installed()
+        && restart_ollama_server(app_handle.clone()).await?;
+    Ok(ollama_env_info(&saved, restarted))
+}
+
+/// Benchmark que não interrompe a aplicação dos ajustes se falhar
+async fn run_tuning_benchmark(
+    client: &ollama_client::OllamaClient,
+    model: Option<&str>,
+    requests: usize,
+) -> Option<ollama_client::BenchmarkResult> {
+    let model = model?;
+    match client.benchmark(model, requests).await {
+        Ok(result) => Some(result),
+        Err(e) => {
+            log::warn!("[OllamaTuning] Benchmark falhou: {}", e);
+            None
+        }
+    }
+}
+
+/// Retorna os ajustes do servidor Ollama salvos nas configurações
+#[command]
+fn get_ollama_server_tuning(app_handle: AppHandle) -> settings::OllamaServerTuning {
+    settings::current(&app_handle).ollama_tuning
+}
+
+/// Salva os ajustes do servidor, reinicia o Ollama para aplicá-los e, se `benchmark_model`
+/// for informado, compara a vazão antes e depois
+#[command]
+async fn apply_ollama_server_tuning(
+    app_handle: AppHandle,
+    state: State<'_, SettingsState>,
+    tuning: settings::OllamaServerTuning,
+    benchmark_model: Option<String>,
+) -> Result<OllamaTuningResult, String> {
+    let current = settings::current(&app_handle);
+    let client = ollama_client::OllamaClient::new(Some(current.ollama_url.clone()));
+    let requests = tuning.num_parallel.unwrap_or(1).clamp(1, TUNING_BENCHMARK_MAX_REQUESTS as u32) as usize;
+    
+    let was_running = check_ollama_running(app_handle.clone()).await;
+    let before = if was_running {
+        run_tuning_benchmark(&client, benchmark_model.as_deref(), requests).await
+    } else {
+        None
+    };
     
-    let url = "http://localhost:11434/api/chat";
-    let response = client
-        .post(url)
-        .json(&request)
-        .send()
-        .await
-        .map_err(|e| format!("Failed to send request to Ollama: {}", e))?;
+    let patch = serde_json::json!({ "ollama_tuning": serde_json::to_value(&tuning)
+        .map_err(|e| format!("Failed to serialize tuning: {}", e))? });
+    let saved = apply_settings_patch(&app_handle, &state, &patch)?.ollama_tuning;
     
-    if !response.status().is_success() {
-        let error_msg = format!("Ollama returned status: {}", response.status());
-        let error_event = ChatErrorEvent {
-            session_id: session_id.clone(),
-            error: error_msg.clone(),
-        };
-        let _ = window.emit("chat-error", &error_event);
-        return Err(error_msg);
+    if !check_ollama_installed() {
+        return Ok(OllamaTuningResult { tuning: saved, restarted: false, before, after: None });
     }
     
-    // 5. Processar stream e emitir tokens COM BUFFERING
-    // OTIMIZAÇÃO: Acumular tokens e emitir em batches para reduzir overhead da bridge
-    let mut stream = response.bytes_stream();
-    let mut buffer = String::new();
-    let mut full_content = String::new();
+    let restarted = restart_ollama_server(app_handle.clone()).await?;
+    let after = if restarted {
+        run_tuning_benchmark(&client, benchmark_model.as_deref(), requests).await
+    } else {
+        log::warn!("[OllamaTuning] Ollama reiniciado mas ainda não responde");
+        None
+    };
     
-    // Buffer de tokens para reduzir eventos na bridge
-    let mut token_buffer = String::new();
-    let mut last_emit = std::time::Instant::now();
-    const EMIT_INTERVAL_MS: u64 = 16; // ~60fps para sincronizar com RAF do frontend
-    const MAX_BUFFER_CHARS: usize = 50; // Emitir quando buffer tiver ~50 chars
+    Ok(OllamaTuningResult { tuning: saved, restarted, before, after })
+}
+
+// ========== Drop Handler Commands ==========
+
+/// Tamanho máximo de imagem enviada como entrada de visão
+const DROP_IMAGE_MAX_BYTES: u64 = 20 * 1024 * 1024;
+/// Tamanho máximo de arquivo de texto/PDF ingerido como anexo de contexto
+const DROP_DOCUMENT_MAX_BYTES: u64 = 5 * 1024 * 1024;
+
+/// Próximo passo para um arquivo solto na área de chat
+#[derive(serde::Serialize)]
+#[serde(tag = "action", rename_all = "snake_case")]
+enum DropAction {
+    /// Anexar a imagem à próxima mensagem (campo `images` do Ollama)
+    VisionInput {
+        path: String,
+        file_name: String,
+        mime: String,
+        base64: String,
+        size_bytes: u64,
+    },
+    /// Texto extraído salvo como anexo; inserir `token` no prompt
+    ContextAttachment {
+        path: String,
+        file_name: String,
+        attachment_id: String,
+        token: String,
+        title: String,
+        char_count: usize,
+    },
+    /// Confirmar o nome e chamar `install_gguf_model`
+    ModelImport {
+        path: String,
+        file_name: String,
+        suggested_name: String,
+        size_bytes: u64,
+        metadata: gguf::GgufMetadata,
+    },
+    Unsupported {
+        path: String,
+        reason: String,
+    },
+}
+
+/// Extrai o texto de um PDF ou arquivo de texto (fora da thread async)
+async fn read_document_text(path: std::path::PathBuf, is_pdf: bool) -> Result<String, String> {
+    tokio::task::spawn_blocking(move || {
+        if is_pdf {
+            pdf_extract::extract_text(&path).map_err(|e| format!("Failed to extract PDF text: {}", e))
+        } else {
+            std::fs::read(&path)
+                .map(|bytes| String::from_utf8_lossy(&bytes).into_owned())
+                .map_err(|e| format!("Failed to read file: {}", e))
+        }
+    })
+    .await
+    .map_err(|e| format!("Document extraction task failed: {}", e))?
+}
+
+/// Classifica um arquivo solto e executa a parte que cabe ao backend
+async fn handle_dropped_file(app_handle: &AppHandle, session_id: Option<String>, path: String) -> Result<DropAction, String> {
+    use base64::Engine;
+    use dropped_files::DroppedKind;
     
-    while let Some(chunk_result) = stream.next().await {
-        let chunk = chunk_result.map_err(|e| format!("Stream error: {}", e))?;
-        let chunk_str = String::from_utf8_lossy(&chunk);
-        buffer.push_str(&chunk_str);
-        
-        // Processar linhas completas (separadas por \n)
-        while let Some(pos) = buffer.find('\n') {
-            let line = buffer[..pos].trim().to_string();
-            buffer = buffer[pos + 1..].to_string();
-            
-            if line.is_empty() {
-                continue;
-            }
-            
-            // Tentar deserializar como JSON do Ollama
-            match serde_json::from_str::<serde_json::Value>(&line) {
-                Ok(json) => {
-                    // Verificar se stream terminou primeiro
-                    let is_done = json.get("done").and_then(|d| d.as_bool()) == Some(true);
-                    
-                    // Extrair conteúdo do chunk (Ollama envia tokens incrementais)
-                    if let Some(message) = json.get("message") {
-                        if let Some(content) = message.get("content").and_then(|c| c.as_str()) {
-                            if !content.is_empty() {
-                                full_content.push_str(content);
-                                token_buffer.push_str(content);
-                                
-                                // Emitir buffer quando: tempo >= 16ms OU buffer >= 50 chars
-                                let elapsed = last_emit.elapsed().as_millis() as u64;
-                                if elapsed >= EMIT_INTERVAL_MS || token_buffer.len() >= MAX_BUFFER_CHARS {
-                                    let token_event = ChatTokenEvent {
-                                        session_id: session_id.clone(),
-                                        content: std::mem::take(&mut token_buffer),
-                                        done: false,
-                                    };
-                                    
-                                    if let Err(e) = window.emit("chat-token", &token_event) {
-                                        log::warn!("Erro ao emitir token: {}", e);
-                                    }
-                                    last_emit = std::time::Instant::now();
-                                }
-                            }
-                        }
-                    }
-                    
-                    // Verificar se stream terminou
-                    if is_done {
-                        // Flush do buffer residual antes de finalizar
-                        if !token_buffer.is_empty() {
-                            let flush_event = ChatTokenEvent {
-                                session_id: session_id.clone(),
-                                content: std::mem::take(&mut token_buffer),
-                                done: false,
-                            };
-                            let _ = window.emit("chat-token", &flush_event);
-                        }
-                        
-                        // Emitir evento final
-                        let final_event = ChatTokenEvent {
-                            session_id: session_id.clone(),
-                            content: String::new(),
-                            done: true,
-                        };
-                        let _ = window.emit("chat-token", &final_event);
-                        break;
-                    }
-                }
-                Err(e) => {
-                    log::debug!("Failed to parse JSON chunk: {} - Line: {}", e, line);
-                    // Continuar mesmo com erro de parse
-                }
+    let file_path = paths::from_user_input(&path);
+    let metadata = std::fs::metadata(&file_path).map_err(|e| format!("Failed to read file metadata: {}", e))?;
+    if !metadata.is_file() {
+        return Ok(DropAction::Unsupported { path, reason: "Pastas não são suportadas".to_string() });
+    }
+    let size_bytes = metadata.len();
+    let file_name = file_path
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_else(|| path.clone());
+    
+    let header = dropped_files::read_header(&file_path).map_err(|e| format!("Failed to read file: {}", e))?;
+    
+    match dropped_files::classify(&file_path, &header) {
+        DroppedKind::Image(mime) => {
+            if size_bytes > DROP_IMAGE_MAX_BYTES {
+                return Ok(DropAction::Unsupported {
+                    path,
+                    reason: format!("Imagem maior que {} MB", DROP_IMAGE_MAX_BYTES / (1024 * 1024)),
+                });
             }
+            let bytes = std::fs::read(&file_path).map_err(|e| format!("Failed to read image: {}", e))?;
+            Ok(DropAction::VisionInput {
+                path,
+                file_name,
+                mime: mime.to_string(),
+                base64: base64::engine::general_purpose::STANDARD.encode(bytes),
+                size_bytes,
+            })
         }
-    }
-    
-    // 6. Persistir sessão e mensagens no SQLite
-    match Database::new(&app_handle) {
-        Ok(db) => {
-            let now = Utc::now();
-            
-            // Criar ou atualizar sessão
-            let session = if is_new_session && !title.is_empty() {
-                ChatSession {
-                    id: session_id.clone(),
-                    title,
-                    emoji,
-                    created_at: now,
-                    updated_at: now,
-                }
-            } else {
-                // Buscar sessão existente ou criar nova
-                match db.get_session(&session_id) {
-                    Ok(Some(mut existing)) => {
-                        existing.updated_at = now;
-                        existing
-                    }
-                    _ => ChatSession {
-                        id: session_id.clone(),
-                        title: "Nova Conversa".to_string(),
-                        emoji: "💬".to_string(),
-                        created_at: now,
-                        updated_at: now,
-                    }
-                }
-            };
-            
-            if let Err(e) = db.create_session(&session) {
-                log::warn!("Erro ao salvar sessão: {}", e);
+        kind @ (DroppedKind::Pdf | DroppedKind::Text) => {
+            if size_bytes > DROP_DOCUMENT_MAX_BYTES {
+                return Ok(DropAction::Unsupported {
+                    path,
+                    reason: format!("Documento maior que {} MB", DROP_DOCUMENT_MAX_BYTES / (1024 * 1024)),
+                });
             }
-            
-            // Salvar mensagens do usuário
-            for msg in &messages {
-                let chat_msg = ChatMessage {
-                    id: None,
-                    session_id: session_id.clone(),
-                    role: msg.role.clone(),
-                    content: msg.content.clone(),
-                    metadata: msg.metadata.as_ref().and_then(|m| serde_json::to_string(m).ok()),
-                    created_at: now,
-                };
-                
-                if let Err(e) = db.add_message(&chat_msg) {
-                    log::warn!("Erro ao salvar mensagem: {}", e);
-                }
+            let text = read_document_text(file_path, kind == DroppedKind::Pdf).await?;
+            let text = text.trim().to_string();
+            if text.is_empty() {
+                return Ok(DropAction::Unsupported { path, reason: "Nenhum texto encontrado no arquivo".to_string() });
             }
             
-            // Salvar mensagem final do assistente
-            if !full_content.is_empty() {
-                let assistant_msg = ChatMessage {
-                    id: None,
-                    session_id: session_id.clone(),
-                    role: "assistant".to_string(),
-                    content: full_content,
-                    metadata: None,
-                    created_at: Utc::now(),
-                };
-                
-                if let Err(e) = db.add_message(&assistant_msg) {
-                    log::warn!("Erro ao salvar mensagem do assistente: {}", e);
-                }
-            }
-        }
-        Err(e) => {
-            log::warn!("Erro ao inicializar banco de dados: {}", e);
+            let char_count = text.chars().count();
+            let attachment = store_paste_attachment(app_handle, session_id, file_name.clone(), text, None, None)?;
+            log::info!("[DropHandler] {} ({} caracteres) salvo como anexo {}", file_name, char_count, attachment.id);
+            
+            Ok(DropAction::ContextAttachment {
+                path,
+                file_name,
+                token: paste::reference_token(&attachment.id),
+                attachment_id: attachment.id,
+                title: attachment.title,
+                char_count,
+            })
         }
+        DroppedKind::Gguf => match inspect_gguf_file(path.clone()).await {
+            Ok(metadata) => Ok(DropAction::ModelImport {
+                suggested_name: dropped_files::suggested_model_name(&file_path),
+                path,
+                file_name,
+                size_bytes,
+                metadata,
+            }),
+            Err(reason) => Ok(DropAction::Unsupported { path, reason }),
+        },
+        DroppedKind::Unsupported(reason) => Ok(DropAction::Unsupported { path, reason }),
     }
+}
+
+/// Recebe os caminhos soltos na área de chat e devolve, para cada um, a ação seguinte
+/// (imagem → visão, PDF/texto → anexo de contexto, GGUF → importação de modelo)
+#[command]
+async fn handle_dropped_files(
+    app_handle: AppHandle,
+    session_id: Option<String>,
+    paths: Vec<String>,
+) -> Result<Vec<DropAction>, String> {
+    let mut actions = Vec::with_capacity(paths.len());
     
-    Ok(session_id)
+    for path in paths {
+        let action = match handle_dropped_file(&app_handle, session_id.clone(), path.clone()).await {
+            Ok(action) => action,
+            Err(e) => {
+                log::warn!("[DropHandler] Falha ao processar {}: {}", path, e);
+                DropAction::Unsupported { path, reason: e }
+            }
+        };
+        actions.push(action);
+    }
+    
+    Ok(actions)
 }
 
-// ============== COMANDOS DE EMBEDDINGS ==============
+// ========== Model Context Commands ==========
 
-/// Baixa o modelo de embeddings se não existir
-#[command]
-async fn download_embedding_model(app_handle: AppHandle) -> Result<bool, String> {
-    let app_data_dir = app_handle.path()
-        .app_data_dir()
-        .map_err(|e| format!("Failed to get app data dir: {}", e))?;
+/// Contexto máximo do modelo, override configurado e estimativa de VRAM
+#[derive(serde::Serialize)]
+struct ModelContextInfo {
+    model: String,
+    max_context: Option<u64>,
+    override_num_ctx: Option<u32>,
+    vram: Option<model_context::VramEstimate>,
+}
+
+/// VRAM total da GPU principal segundo o monitor do sistema
+async fn primary_vram_total_mb() -> Option<u64> {
+    tokio::task::spawn_blocking(|| system_monitor::get_gpu_stats(None).and_then(|stats| stats.vram_total_mb))
+        .await
+        .ok()
+        .flatten()
+}
+
+async fn build_model_context_info(app_handle: &AppHandle, model: &str) -> Result<ModelContextInfo, String> {
+    let current = settings::current(app_handle);
+    let client = ollama_client::OllamaClient::new(Some(current.ollama_url.clone()));
+    let spec = model_context::spec_for(&client, model).await?;
+    let override_num_ctx = current.model_context_overrides.get(model).copied();
     
-    match embeddings::ensure_model_files(&app_data_dir).await {
-        Ok(_) => {
-            log::info!("[Embeddings] Model files ready");
-            Ok(true)
-        }
-        Err(e) => {
-            log::error!("[Embeddings] Failed to ensure model files: {}", e);
-            Err(format!("Failed to download model: {}", e))
+    let vram = match override_num_ctx {
+        Some(num_ctx) => model_context::estimate_vram(&spec, num_ctx, primary_vram_total_mb().await),
+        None => None,
+    };
+    
+    Ok(ModelContextInfo {
+        model: model.to_string(),
+        max_context: spec.max_context,
+        override_num_ctx,
+        vram,
+    })
+}
+
+/// Detecta o contexto máximo do modelo (via /api/show) e retorna o override configurado
+#[command]
+async fn get_model_context_info(app_handle: AppHandle, model: String) -> Result<ModelContextInfo, String> {
+    build_model_context_info(&app_handle, &model).await
+}
+
+/// Define (ou remove, com `num_ctx = null`) o `num_ctx` usado para um modelo
+#[command]
+async fn set_model_context_override(
+    app_handle: AppHandle,
+    state: State<'_, SettingsState>,
+    model: String,
+    num_ctx: Option<u32>,
+) -> Result<ModelContextInfo, String> {
+    if let Some(num_ctx) = num_ctx {
+        let current = settings::current(&app_handle);
+        let client = ollama_client::OllamaClient::new(Some(current.ollama_url));
+        if let Some(max) = model_context::spec_for(&client, &model).await?.max_context {
+            if num_ctx as u64 > max {
+                return Err(format!("O modelo {} suporta no máximo {} tokens de contexto", model, max));
+            }
         }
     }
+    
+    let patch = serde_json::json!({ "model_context_overrides": { model.clone(): num_ctx } });
+    apply_settings_patch(&app_handle, &state, &patch)?;
+    
+    build_model_context_info(&app_handle, &model).await
 }
 
-/// Verifica se o modelo de embeddings está disponível
+// ========== Chat Template Commands ==========
+
+/// Templates de mensagens por padrão de nome de modelo
 #[command]
-fn is_embedding_model_available(app_handle: AppHandle) -> Result<bool, String> {
-    let app_data_dir = app_handle.path()
-        .app_data_dir()
-        .map_err(|e| format!("Failed to get app data dir: {}", e))?;
-    
-    Ok(embeddings::is_model_available(&app_data_dir))
+fn get_chat_templates(app_handle: AppHandle) -> HashMap<String, chat_templates::ChatTemplate> {
+    settings::current(&app_handle).chat_templates
 }
 
-/// Calcula scores de relevância para textos em relação a uma query
+/// Cria ou substitui o template de um padrão (`*` como curinga); sem `template`, remove
 #[command]
-fn calculate_relevance_scores(
+fn set_chat_template(
     app_handle: AppHandle,
-    query: String,
-    texts: Vec<String>,
-) -> Result<Vec<(usize, f32)>, String> {
-    let app_data_dir = app_handle.path()
-        .app_data_dir()
-        .map_err(|e| format!("Failed to get app data dir: {}", e))?;
+    state: State<'_, SettingsState>,
+    pattern: String,
+    template: Option<chat_templates::ChatTemplate>,
+) -> Result<HashMap<String, chat_templates::ChatTemplate>, String> {
+    let pattern = pattern.trim().to_string();
+    let value = match template {
+        Some(template) => serde_json::to_value(template)
+            .map_err(|e| format!("Failed to serialize chat template: {}", e))?,
+        None => serde_json::Value::Null,
+    };
+    // Campos ausentes do template serializam como null e são limpos pelo merge-patch
+    let patch = serde_json::json!({ "chat_templates": { pattern: value } });
+    apply_settings_patch(&app_handle, &state, &patch).map(|saved| saved.chat_templates)
+}
+
+// ========== Capability Commands ==========
+
+/// Catálogo dos comandos registrados com parâmetros, permissões e recursos necessários
+/// (usado pela tela de configurações, pela paleta de comandos e por integrações externas)
+#[command]
+fn list_capabilities() -> capabilities::CapabilityCatalog {
+    capabilities::catalog()
+}
+
+// ========== Settings Commands ==========
+
+/// Aplica um merge-patch às configurações, valida, persiste e emite `settings-changed`
+fn apply_settings_patch(
+    app_handle: &AppHandle,
+    state: &SettingsState,
+    patch: &serde_json::Value,
+) -> Result<AppSettings, String> {
+    let mut guard = state.lock()
+        .map_err(|e| format!("Failed to lock settings: {}", e))?;
     
-    let model_arc = embeddings::get_or_init_model(&app_data_dir)
-        .map_err(|e| format!("Failed to load model: {}", e))?;
+    let mut value = serde_json::to_value(&*guard)
+        .map_err(|e| format!("Failed to serialize settings: {}", e))?;
+    json_merge_patch(&mut value, patch);
     
-    let mut model = model_arc.lock()
-        .map_err(|e| format!("Failed to lock model: {}", e))?;
+    let updated: AppSettings = serde_json::from_value(value)
+        .map_err(|e| format!("Configurações inválidas: {}", e))?;
+    updated.validate()?;
     
-    let text_refs: Vec<&str> = texts.iter().map(|s| s.as_str()).collect();
+    if updated == *guard {
+        return Ok(updated);
+    }
     
-    embeddings::rank_by_relevance(&mut model, &query, &text_refs)
-        .map_err(|e| format!("Failed to calculate relevance: {}", e))
+    settings::save_settings(app_handle, &updated)?;
+    *guard = updated.clone();
+    drop(guard);
+    
+    let _ = app_handle.emit("settings-changed", &updated);
+    Ok(updated)
 }
 
-/// Gera embedding para um texto
+/// Liga ou desliga a inicialização com o sistema; com `start_minimized`, o app sobe só
+/// no tray (sem criar a janela) e as tasks agendadas rodam mesmo sem abri-lo
 #[command]
-fn generate_embedding(
+fn set_autostart(
     app_handle: AppHandle,
-    text: String,
-) -> Result<Vec<f32>, String> {
-    let app_data_dir = app_handle.path()
-        .app_data_dir()
-        .map_err(|e| format!("Failed to get app data dir: {}", e))?;
-    
-    let model_arc = embeddings::get_or_init_model(&app_data_dir)
-        .map_err(|e| format!("Failed to load model: {}", e))?;
-    
-    let mut model = model_arc.lock()
-        .map_err(|e| format!("Failed to lock model: {}", e))?;
-    
-    model.embed(&text)
-        .map_err(|e| format!("Failed to generate embedding: {}", e))
+    state: State<'_, SettingsState>,
+    enabled: bool,
+    start_minimized: Option<bool>,
+) -> Result<autostart::AutostartStatus, String> {
+    let start_minimized = start_minimized.unwrap_or_else(|| settings::current(&app_handle).autostart.start_minimized);
+    // O registro no sistema vem primeiro; as configurações guardam o estado que ele de fato assumiu
+    let status = autostart::apply(&app_handle, &autostart::AutostartSettings { enabled, start_minimized })?;
+    let persisted = autostart::AutostartSettings { enabled: status.enabled, start_minimized };
+    let patch = serde_json::json!({ "autostart": persisted });
+    apply_settings_patch(&app_handle, &state, &patch)?;
+    Ok(status)
 }
 
-/// Poda o contexto mantendo apenas os parágrafos mais relevantes
+/// Perfil do usuário compartilhado entre as sessões (vazio até o consentimento)
 #[command]
-fn prune_context(
+fn get_user_profile(app_handle: AppHandle) -> Result<user_profile::UserProfile, String> {
+    user_profile::load(&app_handle)
+}
+
+/// Dá o consentimento para guardar o perfil e usá-lo nos prompts, ou o revoga (`consent =
+/// false`), o que apaga o perfil
+#[command]
+fn set_user_profile_consent(app_handle: AppHandle, consent: bool) -> Result<user_profile::UserProfile, String> {
+    let mut profile = user_profile::load(&app_handle)?;
+    profile.set_consent(consent);
+    user_profile::save(&app_handle, &profile)?;
+    log::info!("[Profile] Consentimento do perfil do usuário: {}", consent);
+    Ok(profile)
+}
+
+/// Altera o perfil (campos ausentes ficam como estão, texto vazio limpa o campo)
+#[command]
+fn update_user_profile(app_handle: AppHandle, patch: user_profile::UserProfilePatch) -> Result<user_profile::UserProfile, String> {
+    let mut profile = user_profile::load(&app_handle)?;
+    profile.apply(patch)?;
+    user_profile::save(&app_handle, &profile)?;
+    Ok(profile)
+}
+
+/// Retorna as configurações do app
+#[command]
+fn get_settings(state: State<'_, SettingsState>) -> Result<AppSettings, String> {
+    state.lock()
+        .map(|s| s.clone())
+        .map_err(|e| format!("Failed to lock settings: {}", e))
+}
+
+/// Atualiza as configurações com semântica de JSON merge-patch (apenas os campos enviados)
+#[command]
+fn update_settings(
     app_handle: AppHandle,
-    query: String,
-    context: String,
-    max_tokens: Option<usize>,
-    min_score: Option<f32>,
-) -> Result<String, String> {
-    let max_tokens = max_tokens.unwrap_or(2000);
-    let min_score = min_score.unwrap_or(0.3);
-    
-    let app_data_dir = app_handle.path()
-        .app_data_dir()
-        .map_err(|e| format!("Failed to get app data dir: {}", e))?;
-    
-    // Tentar usar embeddings se modelo disponível
-    if embeddings::is_model_available(&app_data_dir) {
-        let model_arc = embeddings::get_or_init_model(&app_data_dir)
-            .map_err(|e| format!("Failed to load model: {}", e))?;
-        
-        let mut model = model_arc.lock()
-            .map_err(|e| format!("Failed to lock model: {}", e))?;
-        
-        embeddings::prune_context(&mut model, &query, &context, max_tokens, min_score)
-            .map_err(|e| format!("Failed to prune context: {}", e))
-    } else {
-        // Fallback para BM25-like
-        log::info!("[PruneContext] Using BM25 fallback (embedding model not available)");
-        Ok(embeddings::prune_context_bm25(&query, &context, max_tokens))
+    state: State<'_, SettingsState>,
+    patch: serde_json::Value,
+) -> Result<AppSettings, String> {
+    if !patch.is_object() {
+        return Err("O patch de configurações deve ser um objeto JSON".to_string());
     }
+    apply_settings_patch(&app_handle, &state, &patch)
 }
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
   tauri::Builder::default()
     .setup(|app| {
-      if cfg!(debug_assertions) {
-        app.handle().plugin(
-          tauri_plugin_log::Builder::default()
-            .level(log::LevelFilter::Info)
-            .build(),
-        )?;
-      }
+      // Logs com rotação por tamanho; o nível é aplicado em runtime a partir das configurações
+      let logging_settings = settings::load_settings(app.handle()).logging;
+      app.handle().plugin(logging::plugin(&logging_settings))?;
+      logging::init(app.handle(), &logging_settings);
       
       // Plugin de notificações
       app.handle().plugin(tauri_plugin_notification::init())?;
       
+      // Pool de conexões do SQLite compartilhado pelos comandos (schema inicializado uma vez)
+      // Sem banco nenhum comando funciona: falha na inicialização em vez de seguir sem o pool
+      let pool = db::create_pool(app.handle()).map_err(|e| {
+          log::error!("Falha ao inicializar banco de dados: {}", e);
+          e
+      })?;
+      app.manage(pool);
+      
+      // Plugin de área de transferência (usado pela ação CopyToClipboard das tasks)
+      app.handle().plugin(tauri_plugin_clipboard_manager::init())?;
+      
       // Plugin de atualização automática
       app.handle().plugin(tauri_plugin_updater::Builder::new().build())?;
       
-      // Modificar comportamento de fechar janela (ocultar ao invés de fechar)
-      if let Some(window) = app.get_webview_window("main") {
-        let window_clone = window.clone();
-        window.on_window_event(move |event| {
-            if let tauri::WindowEvent::CloseRequested { api, .. } = event {
-                // Ocultar ao invés de fechar
-                let _ = window_clone.hide();
-                api.prevent_close();
-            }
-        });
+      // Inicialização com o sistema (chave Run, LaunchAgent ou .desktop)
+      app.handle().plugin(autostart::plugin())?;
+      
+      // Carregar configurações do app
+      let app_settings = settings::load_settings(app.handle());
+      
+      // Em segundo plano (login com `start_minimized` ou `--background`) só scheduler e tray
+      // sobem; a janela é criada quando pedida pelo tray
+      let args: Vec<String> = std::env::args().collect();
+      if autostart::starts_in_background(&args, &app_settings.autostart) {
+          log::info!("Iniciando em segundo plano (sem janela)");
+      } else {
+          autostart::main_window(app.handle())?;
+      }
+      let ollama_url = app_settings.ollama_url.clone();
+      
+      // Proxy e regras de extração do scraper (definidos em sources.json)
+      match load_sources_config(app.handle()) {
+          Ok(sources) => {
+              web_scraper::set_extraction_rules(sources.extraction_rules);
+              web_scraper::set_proxy_config(sources.proxy);
+              web_scraper::set_search_apis(sources.search_apis);
+          }
+          Err(e) => log::warn!("Falha ao carregar configuração do scraper: {}", e),
       }
       
+      // Estatísticas dos motores de busca (rebaixamento de motores que só falham)
+      engine_health::init(app.handle());
+      audit::init(app.handle());
+      
+      // Idioma usado na expansão das consultas de busca
+      query_expansion::set_language(&app_settings.scraping.search_language, &app_settings.language);
+      
+      // Detectar hardware e aplicar perfil de recursos (modo de baixo consumo)
+      resource_profile::init(app_settings.low_resource_mode, app_settings.low_resource_thresholds);
+      
+      // Descartar truncamentos de sessão cuja janela de desfazer expirou
+      match db::Database::new(app.handle()) {
+          Ok(db) => purge_expired_truncations(&db, app_settings.truncate_undo_days),
+          Err(e) => log::warn!("Failed to open database for truncation cleanup: {}", e),
+      }
+      app.manage(Arc::new(Mutex::new(app_settings)) as SettingsState);
+      
+      // Respostas interrompidas por crash na execução anterior (avisadas após o frontend carregar)
+      let recovered = recover_pending_messages(app.handle());
+      if !recovered.is_empty() {
+          let recovered_handle = app.handle().clone();
+          tauri::async_runtime::spawn(async move {
+              tokio::time::sleep(tokio::time::Duration::from_secs(3)).await;
+              for event in recovered {
+                  let _ = events::emit(&recovered_handle, &event);
+              }
+          });
+      }
+      
+      // Pastas anexadas às sessões voltam a ser observadas
+      workspace::restore_watchers(app.handle());
+      
+      // Subsistemas que reagem a mudanças de configuração
+      let settings_handle = app.handle().clone();
+      app.listen("settings-changed", move |event| {
+          match serde_json::from_str::<AppSettings>(event.payload()) {
+              Ok(updated) => {
+                  logging::apply_settings(&updated.logging);
+                  query_expansion::set_language(&updated.scraping.search_language, &updated.language);
+                  let profile = resource_profile::apply_mode(updated.low_resource_mode, updated.low_resource_thresholds);
+                  let _ = settings_handle.emit("resource-profile-changed", &profile);
+              }
+              Err(e) => log::warn!("Payload inválido em settings-changed: {}", e),
+          }
+      });
+      
       // Inicializar scheduler
       let scheduler_service = match SchedulerService::new(app.handle().clone()) {
           Ok(service) => service,
@@ -3645,12 +7568,13 @@ pub fn run() {
       let scheduler_clone = scheduler_state.clone();
       
       // Inicializar Ollama automaticamente se estiver instalado
+      let ollama_app_handle = app.handle().clone();
       tauri::async_runtime::spawn(async move {
           // Aguardar um pouco para o app inicializar completamente
           tokio::time::sleep(tokio::time::Duration::from_secs(1)).await;
           
           // Tentar iniciar Ollama automaticamente
-          if let Err(e) = auto_start_ollama().await {
+          if let Err(e) = auto_start_ollama(ollama_app_handle).await {
               log::warn!("Falha ao iniciar Ollama automaticamente: {}", e);
           }
       });
@@ -3662,7 +7586,7 @@ pub fn run() {
               app_handle,
               scheduler_clone,
               None, // BrowserState não é mais necessário
-              None, // Ollama URL - pode vir do settings store
+              Some(ollama_url),
           ).await {
               log::error!("Erro ao iniciar scheduler: {}", e);
           }
@@ -3671,16 +7595,31 @@ pub fn run() {
       // Adicionar scheduler ao manage
       app.manage(scheduler_state.clone());
       
+      // Ícone no tray com status do Ollama e ações rápidas
+      if let Err(e) = tray::setup_tray(app) {
+          log::warn!("Falha ao criar ícone do tray: {}", e);
+      }
+      
+      // Badge com as respostas não lidas de execuções anteriores
+      unread::refresh(app.handle());
+      
+      // Sidecars dos plugins ativados
+      plugins::start_enabled(app.handle());
+      
       // Inicializar System Monitor State
       let monitor_state: Arc<Mutex<SystemMonitorState>> = Arc::new(Mutex::new(SystemMonitorState::new()));
       app.manage(monitor_state);
       
+      // Histórico de recursos (última hora em memória, opcionalmente no SQLite)
+      system_monitor::start_metrics_recorder(app.handle().clone());
+      
       Ok(())
     })
-    .manage(Arc::new(Mutex::new(None::<Arc<Browser>>)) as BrowserState)
     .manage(Arc::new(Mutex::new(HashMap::<String, Arc<Mutex<()>>>::new())) as FileLockMap)
     .invoke_handler(tauri::generate_handler![
         chat_stream,
+        replay_message,
+        list_active_streams,
         check_ollama_installed, 
         check_ollama_running,
         get_system_specs,
@@ -3688,18 +7627,60 @@ pub fn run() {
         check_if_model_installed,
         pull_model,
         install_gguf_model,
+        inspect_gguf_file,
+        export_model,
+        inspect_model_archive,
+        import_model_archive,
         save_temp_file,
         open_gguf_file_dialog,
         start_ollama_server,
+        stop_ollama_server,
+        restart_ollama_server,
+        get_ollama_env,
+        configure_ollama_env,
+        get_ollama_version,
+        check_ollama_update,
+        upgrade_ollama,
         start_system_monitor,
+        configure_system_monitor,
         get_gpu_stats,
+        get_metrics_history,
         list_local_models,
         delete_model,
+        set_model_pinned,
+        copy_model,
+        get_chat_templates,
+        set_chat_template,
+        rename_model,
         save_chat_session,
         load_chat_sessions,
         search_chat_sessions,
+        validate_search_query,
+        search_in_session,
+        set_session_tags,
+        move_session_to_folder,
+        archive_session,
+        set_session_template,
+        set_session_offline,
+        get_session_memory,
+        add_memory_item,
+        remove_memory_item,
+        duplicate_session,
+        get_unread_counts,
+        get_usage_analytics,
+        mark_session_read,
+        get_pending_digest_items,
+        add_digest_item,
+        run_digest_now,
+        list_capabilities,
         load_chat_history,
         load_chat_history_paginated,
+        load_chat_history_page,
+        get_message,
+        truncate_session_after,
+        undo_session_truncation,
+        list_session_truncations,
+        set_message_metadata,
         delete_chat_session,
         cleanup_orphan_sessions,
         load_mcp_config,
@@ -3718,16 +7699,54 @@ pub fn run() {
         search_and_extract_content,
         extract_url_content,
         search_web_metadata,
+        test_search_engine,
+        get_search_engine_health,
+        search_images,
+        download_image,
         scrape_urls,
+        cancel_scrape_job,
+        guard_web_context,
+        translate_text,
+        detect_text_language,
         reset_browser,
+        get_browser_pool_stats,
+        clear_scrape_cache,
         force_kill_browser,
+        get_storage_usage,
         export_chat_sessions,
         export_all_data,
+        // Workspace bundle commands
+        export_workspace_bundle,
+        import_workspace_bundle,
+        import_external_history,
+        run_palette_command,
+        register_palette_action,
+        install_plugin,
+        list_plugins,
+        enable_plugin,
+        subscribe_events,
+        export_audit_log,
+        get_onboarding_recommendations,
+        run_onboarding_step,
+        run_diagnostics,
+        // Cold storage commands
+        archive_old_sessions,
+        preview_retention_cleanup,
+        list_archived_sessions,
+        search_archived_sessions,
+        restore_from_archive,
         clear_chat_history,
         get_app_data_dir,
+        verify_storage_paths,
         load_sources_config_command,
         save_sources_config_command,
+        fetch_feeds,
+        export_sources_config,
+        import_sources_config,
         get_recent_logs,
+        set_log_level,
+        stream_logs,
+        export_logs,
         log_to_terminal,
         get_system_stats,
         create_task,
@@ -3735,6 +7754,8 @@ pub fn run() {
         update_task,
         delete_task,
         toggle_task,
+        pause_scheduler,
+        quit_app,
         check_download_url,
         get_local_installer_path,
         download_installer,
@@ -3748,7 +7769,68 @@ pub fn run() {
         is_embedding_model_available,
         calculate_relevance_scores,
         generate_embedding,
-        prune_context
+        prune_context,
+        start_embedding_job,
+        // Job commands
+        list_jobs,
+        cancel_job,
+        // Resource profile commands
+        get_resource_profile,
+        set_low_resource_mode,
+        set_autostart,
+        get_user_profile,
+        set_user_profile_consent,
+        update_user_profile,
+        // Settings commands
+        get_settings,
+        update_settings,
+        // Ollama server tuning commands
+        get_ollama_server_tuning,
+        apply_ollama_server_tuning,
+        // Model context commands
+        get_model_context_info,
+        set_model_context_override,
+        // Drop handler commands
+        handle_dropped_files,
+        // Prompt library commands
+        save_prompt_preset,
+        list_prompt_presets,
+        delete_prompt_preset,
+        apply_prompt_preset,
+        // Database maintenance commands
+        run_db_maintenance,
+        sync_now,
+        // Smart paste commands
+        smart_paste,
+        get_paste_attachment,
+        // Conversation template commands
+        save_conversation_template,
+        list_conversation_templates,
+        delete_conversation_template,
+        create_session_from_template,
+        get_session_config,
+        set_session_generation_options,
+        // Citation commands
+        share_session,
+        preview_shared_session,
+        stop_share_preview,
+        transcribe_audio,
+        get_stt_status,
+        download_stt_model,
+        extract_code_blocks,
+        run_code_snippet,
+        attach_workspace,
+        detach_workspace,
+        list_workspace_files,
+        search_workspace,
+        read_workspace_file,
+        export_citations,
+        save_message_sources,
+        get_message_sources,
+        // API token commands
+        create_api_token,
+        list_api_tokens,
+        revoke_api_token
     ])
     .manage(Arc::new(Mutex::new(HashMap::<String, McpProcessHandle>::new())) as McpProcessMap)
     .run(tauri::generate_context!())
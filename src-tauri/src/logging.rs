@@ -0,0 +1,370 @@
+use chrono::{DateTime, Local, Utc};
+use log::LevelFilter;
+use serde::{Deserialize, Serialize};
+use std::cell::Cell;
+use std::collections::VecDeque;
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, SystemTime};
+use tauri::plugin::TauriPlugin;
+use tauri::{AppHandle, Emitter, Manager, Wry};
+use tauri_plugin_log::{fern, RotationStrategy, Target, TargetKind, TimezoneStrategy};
+use zip::write::{FileOptions, ZipWriter};
+use zip::CompressionMethod;
+
+/// Nome do arquivo de log ativo (rotacionados viram `ollahub_<data>.log`)
+pub const LOG_FILE_NAME: &str = "ollahub";
+/// Evento emitido para o visualizador de logs ao vivo
+pub const LOG_EVENT: &str = "log-entry";
+/// Entradas recentes mantidas em memória (enviadas ao abrir o visualizador)
+const RECENT_ENTRIES: usize = 500;
+/// Arquivos incluídos no ZIP de diagnóstico
+const EXPORT_MAX_FILES: usize = 5;
+/// Crates muito verbosos em debug/trace
+const NOISY_TARGETS: &[&str] = &["hyper", "hyper_util", "reqwest", "tungstenite", "chromiumoxide", "tao", "h2"];
+
+static APP: OnceLock<AppHandle> = OnceLock::new();
+static STREAMING: AtomicBool = AtomicBool::new(false);
+static RECENT: Mutex<VecDeque<LogEntry>> = Mutex::new(VecDeque::new());
+
+thread_local! {
+    /// Evita recursão quando o próprio emit gera logs
+    static FORWARDING: Cell<bool> = const { Cell::new(false) };
+}
+
+/// Nível, rotação por tamanho e retenção dos arquivos de log
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct LoggingSettings {
+    /// "off", "error", "warn", "info", "debug" ou "trace"
+    #[serde(default = "default_level")]
+    pub level: String,
+    /// Tamanho a partir do qual o arquivo ativo é rotacionado
+    #[serde(default = "default_max_file_size_mb")]
+    pub max_file_size_mb: u64,
+    /// Arquivos mantidos (incluindo o ativo)
+    #[serde(default = "default_retention_files")]
+    pub retention_files: usize,
+    /// Arquivos rotacionados mais antigos que isso são removidos
+    #[serde(default = "default_retention_days")]
+    pub retention_days: u32,
+}
+
+fn default_level() -> String {
+    "info".to_string()
+}
+
+fn default_max_file_size_mb() -> u64 {
+    5
+}
+
+fn default_retention_files() -> usize {
+    10
+}
+
+fn default_retention_days() -> u32 {
+    14
+}
+
+impl Default for LoggingSettings {
+    fn default() -> Self {
+        Self {
+            level: default_level(),
+            max_file_size_mb: default_max_file_size_mb(),
+            retention_files: default_retention_files(),
+            retention_days: default_retention_days(),
+        }
+    }
+}
+
+impl LoggingSettings {
+    pub fn validate(&self, errors: &mut Vec<String>) {
+        if parse_level(&self.level).is_none() {
+            errors.push(format!("logging.level inválido: {}", self.level));
+        }
+        if !(1..=100).contains(&self.max_file_size_mb) {
+            errors.push("logging.max_file_size_mb deve estar entre 1 e 100".to_string());
+        }
+        if !(1..=100).contains(&self.retention_files) {
+            errors.push("logging.retention_files deve estar entre 1 e 100".to_string());
+        }
+        if !(1..=365).contains(&self.retention_days) {
+            errors.push("logging.retention_days deve estar entre 1 e 365".to_string());
+        }
+    }
+
+    pub fn level_filter(&self) -> LevelFilter {
+        parse_level(&self.level).unwrap_or(LevelFilter::Info)
+    }
+}
+
+pub fn parse_level(level: &str) -> Option<LevelFilter> {
+    match level.trim().to_lowercase().as_str() {
+        "off" => Some(LevelFilter::Off),
+        "error" => Some(LevelFilter::Error),
+        "warn" | "warning" => Some(LevelFilter::Warn),
+        "info" => Some(LevelFilter::Info),
+        "debug" => Some(LevelFilter::Debug),
+        "trace" => Some(LevelFilter::Trace),
+        _ => None,
+    }
+}
+
+/// Linha de log enviada ao visualizador ao vivo
+#[derive(Serialize, Clone, Debug)]
+pub struct LogEntry {
+    pub timestamp: DateTime<Utc>,
+    pub level: String,
+    pub target: String,
+    pub message: String,
+}
+
+/// Plugin de log com arquivo rotacionado, stdout e encaminhamento para o frontend.
+/// O filtro do plugin fica em Trace; o nível efetivo é o `log::max_level` (alterável em runtime).
+pub fn plugin(settings: &LoggingSettings) -> TauriPlugin<Wry> {
+    let mut builder = tauri_plugin_log::Builder::new()
+        .clear_targets()
+        .targets([
+            Target::new(TargetKind::Stdout),
+            Target::new(TargetKind::LogDir { file_name: Some(LOG_FILE_NAME.to_string()) }),
+            Target::new(TargetKind::Dispatch(
+                fern::Dispatch::new().chain(fern::Output::call(forward)),
+            )),
+        ])
+        .level(LevelFilter::Trace)
+        .max_file_size(settings.max_file_size_mb as u128 * 1024 * 1024)
+        .rotation_strategy(RotationStrategy::KeepAll)
+        .timezone_strategy(TimezoneStrategy::UseLocal);
+    for target in NOISY_TARGETS {
+        builder = builder.level_for(*target, LevelFilter::Info);
+    }
+    builder.build()
+}
+
+/// Aplica o nível configurado e remove arquivos fora da retenção (chamado após registrar o plugin)
+pub fn init(app: &AppHandle, settings: &LoggingSettings) {
+    let _ = APP.set(app.clone());
+    log::set_max_level(settings.level_filter());
+    match prune(app, settings) {
+        Ok(0) => {}
+        Ok(removed) => log::info!("[Logs] {} arquivo(s) de log antigo(s) removido(s)", removed),
+        Err(e) => log::warn!("[Logs] {}", e),
+    }
+}
+
+/// Aplica o nível das configurações (ignora se já estiver em uso)
+pub fn apply_settings(settings: &LoggingSettings) {
+    if log::max_level() != settings.level_filter() {
+        let _ = set_level(&settings.level);
+    }
+}
+
+/// Troca o nível de log sem reiniciar o app
+pub fn set_level(level: &str) -> Result<LevelFilter, String> {
+    let filter = parse_level(level).ok_or_else(|| format!("Nível de log inválido: {}", level))?;
+    log::set_max_level(filter);
+    log::info!("[Logs] Nível alterado para {}", filter);
+    Ok(filter)
+}
+
+/// Liga/desliga o envio de `log-entry` ao frontend; retorna as entradas recentes para preencher a tela
+pub fn set_streaming(enabled: bool) -> Vec<LogEntry> {
+    STREAMING.store(enabled, Ordering::Relaxed);
+    if !enabled {
+        return Vec::new();
+    }
+    RECENT.lock().map(|recent| recent.iter().cloned().collect()).unwrap_or_default()
+}
+
+fn forward(record: &log::Record) {
+    if FORWARDING.with(|f| f.replace(true)) {
+        return;
+    }
+    let entry = LogEntry {
+        timestamp: Utc::now(),
+        level: record.level().to_string(),
+        target: record.target().to_string(),
+        message: record.args().to_string(),
+    };
+    if let Ok(mut recent) = RECENT.lock() {
+        if recent.len() >= RECENT_ENTRIES {
+            recent.pop_front();
+        }
+        recent.push_back(entry.clone());
+    }
+    if STREAMING.load(Ordering::Relaxed) {
+        if let Some(app) = APP.get() {
+            let _ = app.emit(LOG_EVENT, &entry);
+        }
+    }
+    FORWARDING.with(|f| f.set(false));
+}
+
+pub fn logs_dir(app: &AppHandle) -> Result<PathBuf, String> {
+    app.path()
+        .app_log_dir()
+        .map_err(|e| format!("Failed to get log dir: {}", e))
+}
+
+/// Arquivos `.log` do diretório de logs, do mais recente para o mais antigo
+pub fn log_files(app: &AppHandle) -> Result<Vec<(PathBuf, SystemTime)>, String> {
+    let dir = logs_dir(app)?;
+    if !dir.exists() {
+        return Ok(Vec::new());
+    }
+    let mut files: Vec<(PathBuf, SystemTime)> = fs::read_dir(&dir)
+        .map_err(|e| format!("Failed to read logs directory: {}", e))?
+        .filter_map(|entry| {
+            let entry = entry.ok()?;
+            let path = entry.path();
+            if path.is_file() && path.extension().and_then(|s| s.to_str()) == Some("log") {
+                Some((path, entry.metadata().ok()?.modified().ok()?))
+            } else {
+                None
+            }
+        })
+        .collect();
+    files.sort_by(|a, b| b.1.cmp(&a.1));
+    Ok(files)
+}
+
+/// Arquivos a remover: além dos `keep` mais recentes ou mais antigos que `max_age`.
+/// O arquivo ativo nunca é removido.
+pub fn retention_victims(
+    files: &[(PathBuf, SystemTime)],
+    keep: usize,
+    max_age: Duration,
+    now: SystemTime,
+) -> Vec<PathBuf> {
+    let active = format!("{}.log", LOG_FILE_NAME);
+    files
+        .iter()
+        .filter(|(path, _)| path.file_name().and_then(|n| n.to_str()) != Some(active.as_str()))
+        .enumerate()
+        .filter(|(index, (_, modified))| {
+            // `keep` inclui o arquivo ativo
+            *index + 1 >= keep || now.duration_since(*modified).map_or(false, |age| age > max_age)
+        })
+        .map(|(_, (path, _))| path.clone())
+        .collect()
+}
+
+/// Aplica a política de retenção; retorna quantos arquivos foram removidos
+pub fn prune(app: &AppHandle, settings: &LoggingSettings) -> Result<usize, String> {
+    let files = log_files(app)?;
+    let max_age = Duration::from_secs(settings.retention_days as u64 * 24 * 3600);
+    let mut removed = 0;
+    for path in retention_victims(&files, settings.retention_files, max_age, SystemTime::now()) {
+        match fs::remove_file(&path) {
+            Ok(()) => removed += 1,
+            Err(e) => log::warn!("[Logs] Falha ao remover {:?}: {}", path, e),
+        }
+    }
+    Ok(removed)
+}
+
+/// Últimas `lines` linhas, continuando nos arquivos rotacionados se o ativo for curto
+pub fn recent_lines(app: &AppHandle, lines: usize) -> Result<Vec<String>, String> {
+    let mut collected: Vec<String> = Vec::new();
+    for (path, _) in log_files(app)? {
+        if collected.len() >= lines {
+            break;
+        }
+        let content = fs::read_to_string(&path)
+            .map_err(|e| format!("Failed to read log file: {}", e))?;
+        let needed = lines - collected.len();
+        let file_lines: Vec<&str> = content.lines().collect();
+        let start = file_lines.len().saturating_sub(needed);
+        let mut chunk: Vec<String> = file_lines[start..].iter().map(|s| s.to_string()).collect();
+        // Arquivos mais antigos vêm antes
+        chunk.append(&mut collected);
+        collected = chunk;
+    }
+    Ok(collected)
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct LogExport {
+    pub path: String,
+    pub files: usize,
+    pub bytes: u64,
+}
+
+/// ZIP para relatórios de bug: logs recentes + `system.json` com specs e configuração de log
+pub fn export(app: &AppHandle, zip_path: &Path, system_info: &serde_json::Value) -> Result<LogExport, String> {
+    if let Some(parent) = zip_path.parent() {
+        if !parent.as_os_str().is_empty() {
+            fs::create_dir_all(parent)
+                .map_err(|e| format!("Failed to create export directory: {}", e))?;
+        }
+    }
+    let file = fs::File::create(zip_path)
+        .map_err(|e| format!("Failed to create ZIP file: {}", e))?;
+    let mut zip = ZipWriter::new(file);
+    let options = FileOptions::default().compression_method(CompressionMethod::Deflated);
+
+    let mut files = 0;
+    for (path, _) in log_files(app)?.into_iter().take(EXPORT_MAX_FILES) {
+        let Some(name) = path.file_name().and_then(|n| n.to_str()).map(|n| n.to_string()) else {
+            continue;
+        };
+        let content = fs::read(&path)
+            .map_err(|e| format!("Failed to read log file {}: {}", name, e))?;
+        zip.start_file(format!("logs/{}", name), options)
+            .map_err(|e| format!("Failed to add file to ZIP: {}", e))?;
+        zip.write_all(&content)
+            .map_err(|e| format!("Failed to write file to ZIP: {}", e))?;
+        files += 1;
+    }
+
+    let info = serde_json::json!({
+        "exported_at": Local::now().to_rfc3339(),
+        "app_version": app.package_info().version.to_string(),
+        "log_level": log::max_level().to_string(),
+        "system": system_info,
+    });
+    zip.start_file("system.json", options)
+        .map_err(|e| format!("Failed to add file to ZIP: {}", e))?;
+    zip.write_all(
+        serde_json::to_string_pretty(&info)
+            .map_err(|e| format!("Failed to serialize system info: {}", e))?
+            .as_bytes(),
+    )
+    .map_err(|e| format!("Failed to write file to ZIP: {}", e))?;
+
+    zip.finish()
+        .map_err(|e| format!("Failed to finalize ZIP: {}", e))?;
+
+    let bytes = fs::metadata(zip_path).map(|m| m.len()).unwrap_or(0);
+    log::info!("[Logs] {} arquivo(s) exportado(s) para {:?}", files, zip_path);
+    Ok(LogExport {
+        path: zip_path.to_string_lossy().to_string(),
+        files,
+        bytes,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_retention_victims() {
+        let now = SystemTime::now();
+        let day = Duration::from_secs(24 * 3600);
+        let files = vec![
+            (PathBuf::from("ollahub.log"), now),
+            (PathBuf::from("ollahub_3.log"), now - day),
+            (PathBuf::from("ollahub_2.log"), now - day * 2),
+            (PathBuf::from("ollahub_1.log"), now - day * 30),
+        ];
+        // Mantém 3 (ativo + 2 rotacionados); o de 30 dias sai pelos dois critérios
+        assert_eq!(retention_victims(&files, 3, day * 14, now), vec![PathBuf::from("ollahub_1.log")]);
+        assert_eq!(retention_victims(&files, 1, day * 14, now).len(), 3);
+        assert!(retention_victims(&files, 10, day * 60, now).is_empty());
+        assert_eq!(parse_level("WARN"), Some(LevelFilter::Warn));
+        assert!(parse_level("verbose").is_none());
+    }
+}
@@ -0,0 +1,301 @@
+//! Memória da sessão: fatos duradouros lembrados entre as mensagens
+//!
+//! Os itens ficam em `memory_context` no JSON da conversa (`chats/<id>.json`) e entram no
+//! system prompt de `chat_stream`. Além de `add_memory_item`/`remove_memory_item`, ao fim de
+//! cada resposta um passe curto do modelo extrai fatos novos da rodada ("o nome do usuário é
+//! Ana", "o projeto usa Rust") e os acrescenta, sem repetir os já lembrados.
+
+use crate::events;
+use crate::ollama_client::OllamaClient;
+use crate::{ChatSession, FileLockMap};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::sync::{Arc, Mutex};
+use tauri::{AppHandle, Manager};
+
+/// Tamanho máximo de um item (em caracteres)
+const MAX_ITEM_CHARS: usize = 200;
+/// Trecho da resposta enviado ao passe de extração
+const EXTRACTION_EXCERPT_CHARS: usize = 2000;
+const EXTRACTION_TIMEOUT_SECS: u64 = 30;
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct MemorySettings {
+    /// Inclui a memória da sessão no system prompt
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+    /// Extrai fatos automaticamente ao fim de cada resposta
+    #[serde(default = "default_true")]
+    pub auto_extract: bool,
+    /// Modelo do passe de extração (None: o modelo da conversa)
+    #[serde(default)]
+    pub extraction_model: Option<String>,
+    /// Itens por sessão; ao passar do limite, os mais antigos saem
+    #[serde(default = "default_max_items")]
+    pub max_items: usize,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+fn default_max_items() -> usize {
+    20
+}
+
+impl Default for MemorySettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            auto_extract: true,
+            extraction_model: None,
+            max_items: default_max_items(),
+        }
+    }
+}
+
+/// Memória de uma sessão alterada (item manual ou extração automática)
+#[derive(Serialize, Clone, Debug)]
+pub struct SessionMemoryUpdatedEvent {
+    pub session_id: String,
+    pub items: Vec<String>,
+    /// Itens acrescentados nesta alteração
+    pub added: Vec<String>,
+    /// "manual" ou "extraction"
+    pub source: String,
+}
+
+/// Item limpo (marcador de lista e espaços repetidos removidos); None se vazio
+pub fn normalize_item(item: &str) -> Option<String> {
+    let item = item.trim().trim_start_matches(['-', '*', '•']).trim();
+    let item = item.split_whitespace().collect::<Vec<_>>().join(" ");
+    if item.is_empty() {
+        return None;
+    }
+    Some(item.chars().take(MAX_ITEM_CHARS).collect())
+}
+
+/// Acrescenta os itens que ainda não estão na memória (sem diferenciar maiúsculas) e descarta
+/// os mais antigos acima de `max_items`. Devolve os itens acrescentados.
+pub fn merge(items: &mut Vec<String>, new_items: impl IntoIterator<Item = String>, max_items: usize) -> Vec<String> {
+    let mut added = Vec::new();
+    for item in new_items {
+        let Some(item) = normalize_item(&item) else { continue };
+        if items.iter().any(|existing| existing.to_lowercase() == item.to_lowercase()) {
+            continue;
+        }
+        items.push(item.clone());
+        added.push(item);
+    }
+    if items.len() > max_items {
+        let excess = items.len() - max_items;
+        items.drain(..excess);
+        added.retain(|item| items.contains(item));
+    }
+    added
+}
+
+/// Fatos na resposta do passe de extração: um array JSON de strings, com ou sem bloco de
+/// código ao redor
+pub fn parse_facts(response: &str) -> Vec<String> {
+    let (Some(start), Some(end)) = (response.find('['), response.rfind(']')) else {
+        return Vec::new();
+    };
+    if end < start {
+        return Vec::new();
+    }
+    serde_json::from_str::<Vec<serde_json::Value>>(&response[start..=end])
+        .map(|values| {
+            values
+                .iter()
+                .filter_map(|value| value.as_str())
+                .filter_map(normalize_item)
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Trecho do system prompt com a memória (None sem itens)
+pub fn prompt_block(items: &[String]) -> Option<String> {
+    if items.is_empty() {
+        return None;
+    }
+    let lines = items.iter().map(|item| format!("- {}", item)).collect::<Vec<_>>().join("\n");
+    Some(format!("Fatos lembrados desta conversa (use quando forem relevantes):\n{}", lines))
+}
+
+fn session_lock(app_handle: &AppHandle, session_id: &str) -> Result<Arc<Mutex<()>>, String> {
+    let file_locks = app_handle.state::<FileLockMap>();
+    let mut locks_map = file_locks
+        .lock()
+        .map_err(|e| format!("Failed to lock file locks map: {}", e))?;
+    Ok(locks_map.entry(session_id.to_string()).or_default().clone())
+}
+
+/// Memória gravada da sessão
+pub fn get(app_handle: &AppHandle, session_id: &str) -> Result<Vec<String>, String> {
+    let lock = session_lock(app_handle, session_id)?;
+    let _guard = lock
+        .lock()
+        .map_err(|e| format!("Failed to acquire file lock for session {}: {}", session_id, e))?;
+    let path = crate::get_chats_dir(app_handle)?.join(format!("{}.json", session_id));
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    let content = fs::read_to_string(&path).map_err(|e| format!("Failed to read session file: {}", e))?;
+    let session: ChatSession =
+        serde_json::from_str(&content).map_err(|e| format!("Failed to parse session file: {}", e))?;
+    Ok(session.memory_context)
+}
+
+/// Altera a memória da sessão sob o lock do arquivo e grava o JSON se algo mudou
+fn update<F>(app_handle: &AppHandle, session_id: &str, change: F) -> Result<Vec<String>, String>
+where
+    F: FnOnce(&mut Vec<String>) -> Result<bool, String>,
+{
+    let lock = session_lock(app_handle, session_id)?;
+    let _guard = lock
+        .lock()
+        .map_err(|e| format!("Failed to acquire file lock for session {}: {}", session_id, e))?;
+    let path = crate::get_chats_dir(app_handle)?.join(format!("{}.json", session_id));
+    let content = fs::read_to_string(&path).map_err(|_| "Session not found".to_string())?;
+    let mut session: ChatSession =
+        serde_json::from_str(&content).map_err(|e| format!("Failed to parse session file: {}", e))?;
+
+    if change(&mut session.memory_context)? {
+        let json = serde_json::to_string_pretty(&session)
+            .map_err(|e| format!("Failed to serialize session: {}", e))?;
+        let temp_path = path.with_extension("json.tmp");
+        fs::write(&temp_path, json).map_err(|e| format!("Failed to write temp session file: {}", e))?;
+        fs::rename(&temp_path, &path).map_err(|e| format!("Failed to rename temp file to session file: {}", e))?;
+    }
+    Ok(session.memory_context)
+}
+
+fn notify(app_handle: &AppHandle, session_id: &str, items: &[String], added: Vec<String>, source: &str) {
+    let event = SessionMemoryUpdatedEvent {
+        session_id: session_id.to_string(),
+        items: items.to_vec(),
+        added,
+        source: source.to_string(),
+    };
+    if let Err(e) = events::emit(app_handle, &event) {
+        log::warn!("Erro ao emitir evento session-memory-updated: {}", e);
+    }
+}
+
+pub fn add(app_handle: &AppHandle, session_id: &str, item: &str) -> Result<Vec<String>, String> {
+    let item = normalize_item(item).ok_or_else(|| "Memory item is empty".to_string())?;
+    let max_items = crate::settings::current(app_handle).memory.max_items;
+    let mut added = Vec::new();
+    let items = update(app_handle, session_id, |items| {
+        added = merge(items, [item], max_items);
+        Ok(!added.is_empty())
+    })?;
+    if !added.is_empty() {
+        notify(app_handle, session_id, &items, added, "manual");
+    }
+    Ok(items)
+}
+
+pub fn remove(app_handle: &AppHandle, session_id: &str, item: &str) -> Result<Vec<String>, String> {
+    let target = item.trim().to_lowercase();
+    let items = update(app_handle, session_id, |items| {
+        let before = items.len();
+        items.retain(|existing| existing.to_lowercase() != target);
+        if items.len() == before {
+            return Err("Memory item not found".to_string());
+        }
+        Ok(true)
+    })?;
+    notify(app_handle, session_id, &items, Vec::new(), "manual");
+    Ok(items)
+}
+
+/// Extrai em segundo plano os fatos duradouros da última rodada e os acrescenta à memória
+pub fn spawn_extraction(app_handle: AppHandle, session_id: String, model: String, user_message: String, answer: String) {
+    tauri::async_runtime::spawn(async move {
+        let settings = crate::settings::current(&app_handle).memory;
+        let model = settings.extraction_model.clone().unwrap_or(model);
+        let known = get(&app_handle, &session_id).unwrap_or_default();
+
+        let system_prompt = "Você extrai fatos duradouros de uma conversa: dados do usuário \
+            (nome, preferências, idioma), do projeto ou do contexto que continuarão valendo nas \
+            próximas mensagens. Ignore perguntas pontuais, opiniões e o conteúdo da resposta em si. \
+            Responda apenas com um array JSON de frases curtas na terceira pessoa, no idioma da \
+            conversa. Sem fatos novos, responda [].";
+        let known_block = if known.is_empty() {
+            "(nenhum)".to_string()
+        } else {
+            known.iter().map(|item| format!("- {}", item)).collect::<Vec<_>>().join("\n")
+        };
+        let excerpt: String = answer.chars().take(EXTRACTION_EXCERPT_CHARS).collect();
+        let prompt = format!(
+            "Fatos já lembrados:\n{}\n\nMensagem do usuário:\n{}\n\nResposta do assistente:\n{}",
+            known_block, user_message, excerpt
+        );
+
+        let client = OllamaClient::new(Some(crate::settings::current(&app_handle).ollama_url));
+        let response = match tokio::time::timeout(
+            tokio::time::Duration::from_secs(EXTRACTION_TIMEOUT_SECS),
+            client.query_ollama_headless(&model, Some(system_prompt), &prompt),
+        )
+        .await
+        {
+            Ok(Ok(response)) => response,
+            Ok(Err(e)) => {
+                log::warn!("[Memory] Falha ao extrair fatos: {}", e);
+                return;
+            }
+            Err(_) => {
+                log::warn!("[Memory] Timeout ao extrair fatos");
+                return;
+            }
+        };
+
+        let facts = parse_facts(&response);
+        if facts.is_empty() {
+            return;
+        }
+        let mut added = Vec::new();
+        // Sem o JSON da conversa (ainda não salvo pela UI), não há onde guardar a memória
+        match update(&app_handle, &session_id, |items| {
+            added = merge(items, facts, settings.max_items);
+            Ok(!added.is_empty())
+        }) {
+            Ok(items) if !added.is_empty() => {
+                log::info!("[Memory] {} fato(s) lembrado(s) na sessão {}", added.len(), session_id);
+                notify(&app_handle, &session_id, &items, added, "extraction");
+            }
+            Ok(_) => {}
+            Err(e) => log::debug!("[Memory] Memória da sessão {} não atualizada: {}", session_id, e),
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_and_merge_facts() {
+        let response = "```json\n[\"O nome do usuário é Ana\", \"  O projeto usa   Rust \", 3, \"\"]\n```";
+        let facts = parse_facts(response);
+        assert_eq!(facts, vec!["O nome do usuário é Ana", "O projeto usa Rust"]);
+        assert!(parse_facts("Nenhum fato novo.").is_empty());
+        assert!(parse_facts("[]").is_empty());
+
+        let mut items = vec!["o projeto usa rust".to_string()];
+        let added = merge(&mut items, facts, 20);
+        assert_eq!(added, vec!["O nome do usuário é Ana"]);
+        assert_eq!(items.len(), 2);
+
+        // Acima do limite, os mais antigos saem
+        let added = merge(&mut items, vec!["- Prefere respostas curtas".to_string()], 2);
+        assert_eq!(added, vec!["Prefere respostas curtas"]);
+        assert_eq!(items, vec!["O nome do usuário é Ana", "Prefere respostas curtas"]);
+
+        assert!(prompt_block(&[]).is_none());
+        assert!(prompt_block(&items).unwrap().ends_with("- Prefere respostas curtas"));
+    }
+}
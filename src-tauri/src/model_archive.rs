@@ -0,0 +1,392 @@
+//! Pacote portátil de um modelo do Ollama (manifest + blobs) para copiar entre máquinas
+//!
+//! O arquivo é um ZIP sem compressão (os pesos não comprimem) com `ollahub-model.json`,
+//! `manifests/<registro>/<namespace>/<modelo>/<tag>` e `blobs/sha256-<hex>`, a mesma
+//! estrutura de `~/.ollama/models`.
+
+use crate::jobs::JobHandle;
+use crate::storage_usage::{blob_file_name, manifest_relative_path, ollama_models_dir};
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::io::{Read, Write};
+use std::path::{Component, Path, PathBuf};
+use tauri::AppHandle;
+use zip::write::{FileOptions, ZipWriter};
+use zip::{CompressionMethod, ZipArchive};
+
+const INDEX_FILE: &str = "ollahub-model.json";
+const FORMAT_VERSION: u32 = 1;
+/// Intervalo mínimo de bytes entre eventos de progresso
+const PROGRESS_STEP_BYTES: u64 = 64 * 1024 * 1024;
+const CANCELLED: &str = "Operação cancelada";
+
+/// Índice gravado no pacote
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ArchiveIndex {
+    pub format_version: u32,
+    pub name: String,
+    pub exported_at: String,
+    /// Caminho do manifest relativo a `manifests/` (com `/`)
+    pub manifest_path: String,
+    pub blobs: Vec<ArchiveBlob>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ArchiveBlob {
+    pub digest: String,
+    pub size: u64,
+}
+
+/// Resultado de exportar/importar um pacote
+#[derive(Serialize, Clone, Debug)]
+pub struct ArchiveReport {
+    pub name: String,
+    pub path: String,
+    pub bytes: u64,
+    pub blobs: usize,
+    /// Blobs que já existiam no destino (importação)
+    pub skipped_blobs: usize,
+}
+
+/// Payload do evento `model-archive-progress`
+#[derive(Serialize, Clone, Debug)]
+pub struct ArchiveProgress {
+    pub operation_id: String,
+    pub bytes_done: u64,
+    pub bytes_total: u64,
+    pub files_done: usize,
+    pub files_total: usize,
+}
+
+struct Progress<'a> {
+    app_handle: &'a AppHandle,
+    job: &'a JobHandle,
+    state: ArchiveProgress,
+    last_emitted: u64,
+}
+
+impl<'a> Progress<'a> {
+    fn new(app_handle: &'a AppHandle, job: &'a JobHandle, bytes_total: u64, files_total: usize) -> Self {
+        job.set_total(files_total);
+        Progress {
+            app_handle,
+            job,
+            state: ArchiveProgress {
+                operation_id: job.id().to_string(),
+                bytes_done: 0,
+                bytes_total,
+                files_done: 0,
+                files_total,
+            },
+            last_emitted: 0,
+        }
+    }
+
+    fn add_bytes(&mut self, bytes: u64) {
+        self.state.bytes_done += bytes;
+        if self.state.bytes_done - self.last_emitted >= PROGRESS_STEP_BYTES {
+            self.last_emitted = self.state.bytes_done;
+            let _ = crate::events::emit(self.app_handle, &self.state);
+        }
+    }
+
+    fn file_done(&mut self) {
+        self.state.files_done += 1;
+        self.job.advance(1);
+        let _ = crate::events::emit(self.app_handle, &self.state);
+    }
+}
+
+/// Copia em blocos verificando o cancelamento; retorna o sha256 do conteúdo
+fn copy_hashed(input: &mut impl Read, output: &mut impl Write, progress: &mut Progress) -> Result<String, String> {
+    let mut hasher = Sha256::new();
+    let mut buffer = vec![0u8; 1024 * 1024];
+    loop {
+        if progress.job.is_cancelled() {
+            return Err(CANCELLED.to_string());
+        }
+        let read = input.read(&mut buffer)
+            .map_err(|e| format!("Failed to read blob: {}", e))?;
+        if read == 0 {
+            break;
+        }
+        hasher.update(&buffer[..read]);
+        output.write_all(&buffer[..read])
+            .map_err(|e| format!("Failed to write blob: {}", e))?;
+        progress.add_bytes(read as u64);
+    }
+    let hex: String = hasher.finalize().iter().map(|b| format!("{:02x}", b)).collect();
+    Ok(format!("sha256:{}", hex))
+}
+
+fn models_dir(app_handle: &AppHandle) -> Result<PathBuf, String> {
+    ollama_models_dir(&crate::settings::current(app_handle)).ok_or_else(|| "Não foi possível determinar o diretório de modelos do Ollama".to_string())
+}
+
+/// Digests referenciados pelo manifest (config + camadas)
+fn manifest_digests(manifest: &serde_json::Value) -> Vec<String> {
+    let config = manifest.get("config").into_iter();
+    let layers = manifest.get("layers").and_then(|l| l.as_array()).into_iter().flatten();
+    let mut digests: Vec<String> = config
+        .chain(layers)
+        .filter_map(|layer| layer.get("digest")?.as_str().map(str::to_string))
+        .collect();
+    digests.dedup();
+    digests
+}
+
+/// Digests do manifest que não estão em `index.blobs` (o manifest apontaria para blobs que
+/// não foram importados nem conferidos)
+fn missing_blobs(manifest: &serde_json::Value, index: &ArchiveIndex) -> Vec<String> {
+    manifest_digests(manifest)
+        .into_iter()
+        .filter(|digest| !index.blobs.iter().any(|blob| &blob.digest == digest))
+        .collect()
+}
+
+/// Digest no formato "sha256:<64 hex>" (impede caminhos arbitrários ao importar)
+fn valid_digest(digest: &str) -> bool {
+    digest
+        .strip_prefix("sha256:")
+        .is_some_and(|hex| hex.len() == 64 && hex.chars().all(|c| c.is_ascii_hexdigit()))
+}
+
+/// Caminho relativo seguro vindo do pacote (apenas componentes normais)
+fn safe_relative(path: &str) -> Option<PathBuf> {
+    let relative = PathBuf::from(path);
+    let normal = relative.components().all(|c| matches!(c, Component::Normal(_)));
+    (normal && relative.components().count() == 4).then_some(relative)
+}
+
+/// Escreve o índice, o manifest e os blobs (copiados em blocos) no ZIP
+fn write_archive(
+    path: &Path,
+    entries: &[(String, Vec<u8>)],
+    blobs_dir: &Path,
+    blobs: &[ArchiveBlob],
+    progress: &mut Progress,
+) -> Result<(), String> {
+    let file = fs::File::create(path)
+        .map_err(|e| format!("Failed to create archive: {}", e))?;
+    let mut zip = ZipWriter::new(std::io::BufWriter::new(file));
+    let options = FileOptions::default()
+        .compression_method(CompressionMethod::Stored)
+        .large_file(true);
+
+    for (name, content) in entries {
+        zip.start_file(name.as_str(), options)
+            .map_err(|e| format!("Failed to write archive: {}", e))?;
+        zip.write_all(content)
+            .map_err(|e| format!("Failed to write archive: {}", e))?;
+    }
+    for blob in blobs {
+        let file_name = blob_file_name(&blob.digest);
+        let mut input = fs::File::open(blobs_dir.join(&file_name))
+            .map_err(|e| format!("Failed to read blob {}: {}", blob.digest, e))?;
+        zip.start_file(format!("blobs/{}", file_name), options)
+            .map_err(|e| format!("Failed to write archive: {}", e))?;
+        copy_hashed(&mut input, &mut zip, progress)?;
+        progress.file_done();
+    }
+    zip.finish()
+        .map_err(|e| format!("Failed to finalize archive: {}", e))?
+        .flush()
+        .map_err(|e| format!("Failed to finalize archive: {}", e))
+}
+
+/// Empacota o modelo `name` em `dest`
+pub fn export(app_handle: &AppHandle, job: &JobHandle, name: &str, dest: &Path) -> Result<ArchiveReport, String> {
+    let models_dir = models_dir(app_handle)?;
+    let relative = manifest_relative_path(name)
+        .ok_or_else(|| format!("Nome de modelo inválido: {}", name))?;
+    let manifest_file = models_dir.join("manifests").join(&relative);
+    let manifest_content = fs::read(&manifest_file)
+        .map_err(|_| format!("Modelo não encontrado: {}", name))?;
+    let manifest: serde_json::Value = serde_json::from_slice(&manifest_content)
+        .map_err(|e| format!("Failed to parse manifest: {}", e))?;
+
+    let mut blobs = Vec::new();
+    for digest in manifest_digests(&manifest) {
+        let path = models_dir.join("blobs").join(blob_file_name(&digest));
+        let size = fs::metadata(&path)
+            .map_err(|_| format!("Blob ausente no diretório do Ollama: {}", digest))?
+            .len();
+        blobs.push(ArchiveBlob { digest, size });
+    }
+
+    let manifest_path = relative.iter().map(|c| c.to_string_lossy()).collect::<Vec<_>>().join("/");
+    let index = ArchiveIndex {
+        format_version: FORMAT_VERSION,
+        name: name.to_string(),
+        exported_at: Utc::now().to_rfc3339(),
+        manifest_path: manifest_path.clone(),
+        blobs: blobs.clone(),
+    };
+    let index_json = serde_json::to_vec_pretty(&index)
+        .map_err(|e| format!("Failed to serialize archive index: {}", e))?;
+
+    // Grava num temporário para não deixar um pacote incompleto com o nome final
+    let tmp = dest.with_extension("partial");
+    let bytes_total = blobs.iter().map(|b| b.size).sum();
+    let mut progress = Progress::new(app_handle, job, bytes_total, blobs.len());
+    let entries = [
+        (INDEX_FILE.to_string(), index_json),
+        (format!("manifests/{}", manifest_path), manifest_content),
+    ];
+    if let Err(e) = write_archive(&tmp, &entries, &models_dir.join("blobs"), &blobs, &mut progress) {
+        let _ = fs::remove_file(&tmp);
+        return Err(e);
+    }
+    fs::rename(&tmp, dest)
+        .map_err(|e| format!("Failed to save archive: {}", e))?;
+
+    Ok(ArchiveReport {
+        name: name.to_string(),
+        path: dest.to_string_lossy().to_string(),
+        bytes: bytes_total,
+        blobs: blobs.len(),
+        skipped_blobs: 0,
+    })
+}
+
+/// Lê o índice de um pacote sem importar
+pub fn read_index(path: &Path) -> Result<ArchiveIndex, String> {
+    let file = fs::File::open(path)
+        .map_err(|e| format!("Failed to open archive: {}", e))?;
+    let mut archive = ZipArchive::new(file)
+        .map_err(|e| format!("Failed to read archive: {}", e))?;
+    index_from(&mut archive)
+}
+
+fn index_from<R: Read + std::io::Seek>(archive: &mut ZipArchive<R>) -> Result<ArchiveIndex, String> {
+    let entry = archive.by_name(INDEX_FILE)
+        .map_err(|_| "Pacote de modelo inválido (índice ausente)".to_string())?;
+    let index: ArchiveIndex = serde_json::from_reader(entry)
+        .map_err(|e| format!("Failed to parse archive index: {}", e))?;
+    if index.format_version > FORMAT_VERSION {
+        return Err(format!("Versão do pacote não suportada: {}", index.format_version));
+    }
+    if safe_relative(&index.manifest_path).is_none() {
+        return Err(format!("Caminho de manifest inválido: {}", index.manifest_path));
+    }
+    if let Some(blob) = index.blobs.iter().find(|b| !valid_digest(&b.digest)) {
+        return Err(format!("Digest inválido no pacote: {}", blob.digest));
+    }
+    Ok(index)
+}
+
+/// Importa o pacote para o diretório do Ollama, conferindo o sha256 de cada blob.
+/// Blobs já presentes são reaproveitados; o manifest é conferido antes e gravado por último.
+pub fn import(app_handle: &AppHandle, job: &JobHandle, path: &Path, overwrite: bool) -> Result<ArchiveReport, String> {
+    let file = fs::File::open(path)
+        .map_err(|e| format!("Failed to open archive: {}", e))?;
+    let mut archive = ZipArchive::new(file)
+        .map_err(|e| format!("Failed to read archive: {}", e))?;
+    let index = index_from(&mut archive)?;
+
+    let models_dir = models_dir(app_handle)?;
+    let relative = safe_relative(&index.manifest_path)
+        .ok_or_else(|| format!("Caminho de manifest inválido: {}", index.manifest_path))?;
+    let manifest_file = models_dir.join("manifests").join(&relative);
+    if manifest_file.exists() && !overwrite {
+        return Err(format!("O modelo {} já existe (use overwrite para substituir)", index.name));
+    }
+    let mut manifest = Vec::new();
+    archive.by_name(&format!("manifests/{}", index.manifest_path))
+        .map_err(|_| "Manifest ausente no pacote".to_string())?
+        .read_to_end(&mut manifest)
+        .map_err(|e| format!("Failed to read manifest: {}", e))?;
+    let manifest_json: serde_json::Value = serde_json::from_slice(&manifest)
+        .map_err(|e| format!("Failed to parse manifest: {}", e))?;
+    if let Some(missing) = missing_blobs(&manifest_json, &index).first() {
+        return Err(format!("Manifest referencia um blob fora do pacote: {}", missing));
+    }
+    let blobs_dir = models_dir.join("blobs");
+    fs::create_dir_all(&blobs_dir)
+        .map_err(|e| format!("Failed to create blobs directory: {}", e))?;
+
+    let pending: Vec<&ArchiveBlob> = index.blobs
+        .iter()
+        .filter(|blob| {
+            let existing = fs::metadata(blobs_dir.join(blob_file_name(&blob.digest)));
+            !existing.is_ok_and(|m| m.len() == blob.size)
+        })
+        .collect();
+    let skipped_blobs = index.blobs.len() - pending.len();
+    let bytes_total = pending.iter().map(|b| b.size).sum();
+    let mut progress = Progress::new(app_handle, job, bytes_total, pending.len());
+
+    for blob in pending {
+        let file_name = blob_file_name(&blob.digest);
+        let mut entry = archive.by_name(&format!("blobs/{}", file_name))
+            .map_err(|_| format!("Blob ausente no pacote: {}", blob.digest))?;
+        let target = blobs_dir.join(&file_name);
+        let partial = blobs_dir.join(format!("{}-partial", file_name));
+        let mut output = fs::File::create(&partial)
+            .map_err(|e| format!("Failed to create blob: {}", e))?;
+        let digest = copy_hashed(&mut entry, &mut output, &mut progress);
+        drop(output);
+        match digest {
+            Ok(digest) if digest == blob.digest => {
+                fs::rename(&partial, &target)
+                    .map_err(|e| format!("Failed to save blob: {}", e))?;
+            }
+            other => {
+                let _ = fs::remove_file(&partial);
+                return Err(match other {
+                    Ok(digest) => format!("Blob corrompido: esperado {}, obtido {}", blob.digest, digest),
+                    Err(e) => e,
+                });
+            }
+        }
+        progress.file_done();
+    }
+
+    if let Some(parent) = manifest_file.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|e| format!("Failed to create manifests directory: {}", e))?;
+    }
+    fs::write(&manifest_file, manifest)
+        .map_err(|e| format!("Failed to write manifest: {}", e))?;
+
+    Ok(ArchiveReport {
+        name: index.name,
+        path: path.to_string_lossy().to_string(),
+        bytes: bytes_total,
+        blobs: index.blobs.len(),
+        skipped_blobs,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_archive_paths_and_digests() {
+        let digest = format!("sha256:{}", "a".repeat(64));
+        assert!(valid_digest(&digest));
+        assert!(!valid_digest("sha256:../../etc/passwd"));
+
+        assert!(safe_relative("registry.ollama.ai/library/llama3/latest").is_some());
+        assert!(safe_relative("registry.ollama.ai/../../../x").is_none());
+        assert!(safe_relative("/etc/a/b/c").is_none());
+
+        let manifest = serde_json::json!({
+            "config": { "digest": "sha256:1" },
+            "layers": [{ "digest": "sha256:2" }, { "digest": "sha256:3" }],
+        });
+        assert_eq!(manifest_digests(&manifest), vec!["sha256:1", "sha256:2", "sha256:3"]);
+
+        let index = ArchiveIndex {
+            format_version: FORMAT_VERSION,
+            name: "llama3".to_string(),
+            exported_at: String::new(),
+            manifest_path: "registry.ollama.ai/library/llama3/latest".to_string(),
+            blobs: vec![ArchiveBlob { digest: "sha256:1".to_string(), size: 1 }, ArchiveBlob { digest: "sha256:3".to_string(), size: 1 }],
+        };
+        assert_eq!(missing_blobs(&manifest, &index), vec!["sha256:2"]);
+    }
+}
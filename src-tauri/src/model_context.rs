@@ -0,0 +1,133 @@
+use crate::ollama_client::OllamaClient;
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::sync::{Mutex, OnceLock};
+
+/// Bytes por elemento do KV cache (f16, padrão do Ollama)
+const KV_BYTES_PER_ELEMENT: u64 = 2;
+/// Margem para buffers de computação além de pesos + KV cache
+const VRAM_OVERHEAD_FACTOR: f64 = 1.1;
+
+/// Especificações de contexto dos modelos já consultados via /api/show
+static SPECS: OnceLock<Mutex<HashMap<String, ModelContextSpec>>> = OnceLock::new();
+
+/// Dados do modelo relevantes para o tamanho de contexto
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct ModelContextSpec {
+    /// Contexto máximo suportado (`<arquitetura>.context_length`)
+    pub max_context: Option<u64>,
+    /// Tamanho do KV cache por token de contexto
+    pub kv_bytes_per_token: Option<u64>,
+    /// Tamanho dos pesos do modelo instalado
+    pub size_bytes: Option<u64>,
+}
+
+/// Estimativa de VRAM para um `num_ctx`
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct VramEstimate {
+    pub num_ctx: u32,
+    pub required_mb: u64,
+    pub available_mb: Option<u64>,
+    pub exceeds: bool,
+}
+
+/// Lê um inteiro de `model_info` (alguns modelos usam um valor por camada; usa o maior)
+fn info_u64(info: &Value, key: &str) -> Option<u64> {
+    match info.get(key)? {
+        Value::Array(values) => values.iter().filter_map(|v| v.as_u64()).max(),
+        value => value.as_u64(),
+    }
+}
+
+/// Tamanho do KV cache por token: camadas * cabeças KV * (dim. chave + dim. valor) * bytes
+fn kv_bytes_per_token(info: &Value, key: &dyn Fn(&str) -> String) -> Option<u64> {
+    let layers = info_u64(info, &key("block_count"))?;
+    let heads = info_u64(info, &key("attention.head_count"))?;
+    let kv_heads = info_u64(info, &key("attention.head_count_kv")).unwrap_or(heads);
+    let head_dim = info_u64(info, &key("embedding_length"))? / heads.max(1);
+    let key_len = info_u64(info, &key("attention.key_length")).unwrap_or(head_dim);
+    let value_len = info_u64(info, &key("attention.value_length")).unwrap_or(head_dim);
+    Some(layers * kv_heads * (key_len + value_len) * KV_BYTES_PER_ELEMENT)
+}
+
+/// Extrai contexto máximo e tamanho do KV cache da resposta de /api/show
+pub fn parse_show_response(show: &Value) -> ModelContextSpec {
+    let info = match show.get("model_info") {
+        Some(info) => info,
+        None => return ModelContextSpec::default(),
+    };
+    let arch = match info.get("general.architecture").and_then(|v| v.as_str()) {
+        Some(arch) => arch,
+        None => return ModelContextSpec::default(),
+    };
+    let key = |name: &str| format!("{}.{}", arch, name);
+
+    ModelContextSpec {
+        max_context: info_u64(info, &key("context_length")),
+        kv_bytes_per_token: kv_bytes_per_token(info, &key),
+        size_bytes: None,
+    }
+}
+
+/// Especificação do modelo (consulta o Ollama apenas na primeira vez)
+pub async fn spec_for(client: &OllamaClient, model: &str) -> Result<ModelContextSpec, String> {
+    let cache = SPECS.get_or_init(|| Mutex::new(HashMap::new()));
+    if let Some(spec) = cache.lock().ok().and_then(|c| c.get(model).cloned()) {
+        return Ok(spec);
+    }
+
+    let show = client.show_model(model).await?;
+    let mut spec = parse_show_response(&show);
+    spec.size_bytes = client.model_size(model).await.unwrap_or(None);
+
+    if let Ok(mut cache) = cache.lock() {
+        cache.insert(model.to_string(), spec.clone());
+    }
+    Ok(spec)
+}
+
+/// Estima a VRAM necessária (pesos + KV cache) e compara com a VRAM total da GPU
+pub fn estimate_vram(spec: &ModelContextSpec, num_ctx: u32, vram_total_mb: Option<u64>) -> Option<VramEstimate> {
+    let kv_bytes = spec.kv_bytes_per_token? * num_ctx as u64;
+    let total_bytes = (spec.size_bytes.unwrap_or(0) + kv_bytes) as f64 * VRAM_OVERHEAD_FACTOR;
+    let required_mb = (total_bytes / (1024.0 * 1024.0)).ceil() as u64;
+
+    Some(VramEstimate {
+        num_ctx,
+        required_mb,
+        available_mb: vram_total_mb,
+        exceeds: vram_total_mb.map(|total| required_mb > total).unwrap_or(false),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_show_and_estimate() {
+        let show = serde_json::json!({
+            "model_info": {
+                "general.architecture": "llama",
+                "llama.context_length": 131072,
+                "llama.block_count": 32,
+                "llama.embedding_length": 4096,
+                "llama.attention.head_count": 32,
+                "llama.attention.head_count_kv": 8
+            }
+        });
+        let mut spec = parse_show_response(&show);
+        assert_eq!(spec.max_context, Some(131072));
+        // 32 camadas * 8 cabeças kv * (128 + 128) * 2 bytes
+        assert_eq!(spec.kv_bytes_per_token, Some(131072));
+
+        spec.size_bytes = Some(4 * 1024 * 1024 * 1024);
+        let small = estimate_vram(&spec, 8192, Some(8192)).unwrap();
+        assert_eq!(small.required_mb, 5632);
+        assert!(!small.exceeds);
+        assert!(estimate_vram(&spec, 65536, Some(8192)).unwrap().exceeds);
+
+        assert_eq!(parse_show_response(&serde_json::json!({})), ModelContextSpec::default());
+    }
+}
@@ -0,0 +1,112 @@
+//! Modo offline por sessão: conversa garantidamente local
+//!
+//! Numa sessão com `offline` ligado, `chat_stream` não usa RAG, buscas e páginas web não são
+//! acessadas e ferramentas que podem usar a rede são recusadas. Cada tentativa bloqueada
+//! emite `policy-violation` para a UI mostrar o que foi impedido.
+
+use crate::capabilities::Permission;
+use serde::Serialize;
+use tauri::AppHandle;
+
+/// Tipo de saída para a rede barrada
+#[derive(Serialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum Egress {
+    Rag,
+    WebSearch,
+    WebFetch,
+    Tool,
+}
+
+impl Egress {
+    fn label(self) -> &'static str {
+        match self {
+            Egress::Rag => "RAG",
+            Egress::WebSearch => "busca web",
+            Egress::WebFetch => "acesso à URL",
+            Egress::Tool => "ferramenta com acesso à rede",
+        }
+    }
+}
+
+/// Payload de `policy-violation`
+#[derive(Serialize, Clone, Debug)]
+pub struct PolicyViolationEvent {
+    pub session_id: String,
+    pub egress: Egress,
+    /// Consulta, URL ou `servidor/ferramenta`
+    pub target: String,
+}
+
+/// Se a ferramenta pode acessar a rede. Embutidas: só as de web; plugins WASM: conforme a
+/// permissão `network` do manifesto; sidecars e servidores MCP comuns contam como de rede.
+pub fn tool_uses_network(server: &str, tool: &str, plugin_permissions: Option<&[Permission]>) -> bool {
+    if server == crate::builtin_tools::SERVER_NAME {
+        return matches!(tool, "web_search" | "fetch_page");
+    }
+    plugin_permissions.map_or(true, |permissions| permissions.contains(&Permission::Network))
+}
+
+pub fn is_offline(app_handle: &AppHandle, session_id: &str) -> bool {
+    crate::db::Database::new(app_handle)
+        .and_then(|db| db.is_session_offline(session_id))
+        .unwrap_or_else(|e| {
+            log::warn!("[Offline] Falha ao ler o modo da sessão {}: {}", session_id, e);
+            false
+        })
+}
+
+/// Avisa a UI e devolve o erro da tentativa barrada
+pub fn violation(app_handle: &AppHandle, session_id: &str, egress: Egress, target: &str) -> String {
+    log::warn!("[Offline] Sessão {}: {} bloqueado ({})", session_id, egress.label(), target);
+    let event = PolicyViolationEvent { session_id: session_id.to_string(), egress, target: target.to_string() };
+    if let Err(e) = crate::events::emit(app_handle, &event) {
+        log::warn!("[Offline] Falha ao emitir policy-violation: {}", e);
+    }
+    format!("Sessão em modo offline: {} bloqueado", egress.label())
+}
+
+/// Recusa a saída para a rede se a sessão estiver em modo offline (sem sessão: liberado)
+pub fn guard(app_handle: &AppHandle, session_id: Option<&str>, egress: Egress, target: &str) -> Result<(), String> {
+    match session_id {
+        Some(session_id) if is_offline(app_handle, session_id) => Err(violation(app_handle, session_id, egress, target)),
+        _ => Ok(()),
+    }
+}
+
+/// `guard` para chamadas de ferramenta, só quando a ferramenta pode usar a rede
+pub fn guard_tool(app_handle: &AppHandle, session_id: Option<&str>, server: &str, tool: &str) -> Result<(), String> {
+    let Some(session_id) = session_id else { return Ok(()) };
+    let permissions = crate::plugins::trusted_permissions(app_handle, server);
+    if !tool_uses_network(server, tool, permissions.as_deref()) {
+        return Ok(());
+    }
+    guard(app_handle, Some(session_id), Egress::Tool, &format!("{}/{}", server, tool))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tool_uses_network() {
+        assert!(tool_uses_network("builtin", "web_search", None));
+        assert!(!tool_uses_network("builtin", "fs_read", None));
+        // MCP comum não declara capacidades
+        assert!(tool_uses_network("filesystem", "read_file", None));
+        assert!(tool_uses_network("plugin:weather", "now", Some(&[Permission::Network])));
+
+        let mut notes: crate::plugins::PluginManifest = serde_json::from_value(serde_json::json!({
+            "name": "notes",
+            "version": "0.1.0",
+            "runtime": { "type": "sidecar", "command": "./bin/notes" },
+            "permissions": ["process"]
+        }))
+        .unwrap();
+        // Sidecar é um processo nativo: a declaração não impede o acesso à rede
+        assert!(tool_uses_network("plugin:notes", "search", notes.trusted_permissions()));
+        notes.runtime = crate::plugins::PluginRuntime::Wasm { module: "notes.wasm".to_string() };
+        notes.permissions = vec![Permission::FileSystem];
+        assert!(!tool_uses_network("plugin:notes", "search", notes.trusted_permissions()));
+    }
+}
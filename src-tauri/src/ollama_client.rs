@@ -1,4 +1,5 @@
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 
 /// Mensagem para o Ollama API
 #[derive(Debug, Serialize)]
@@ -27,6 +28,108 @@ struct OllamaMessageResponse {
     content: String,
 }
 
+/// Estatísticas retornadas por /api/generate (durações em nanossegundos)
+#[derive(Debug, Deserialize)]
+struct OllamaGenerateStats {
+    #[serde(default)]
+    eval_count: u64,
+    #[serde(default)]
+    eval_duration: u64,
+    #[serde(default)]
+    load_duration: u64,
+}
+
+/// Resultado de um benchmark curto de geração
+#[derive(Debug, Clone, Serialize)]
+pub struct BenchmarkResult {
+    pub model: String,
+    /// Requisições disparadas em paralelo
+    pub requests: usize,
+    /// Tempo total até a última resposta
+    pub total_secs: f64,
+    /// Tokens gerados por segundo somando todas as requisições
+    pub tokens_per_sec: f64,
+    /// Tempo médio de carregamento do modelo
+    pub avg_load_secs: f64,
+}
+
+/// Opções de geração repassadas ao Ollama em `options` (None = padrão do modelo)
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct GenerationOptions {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub temperature: Option<f32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub top_k: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub top_p: Option<f32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub repeat_penalty: Option<f32>,
+    /// Máximo de tokens gerados (-1 = sem limite)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub num_predict: Option<i32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stop: Option<Vec<String>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub seed: Option<i64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub num_ctx: Option<u32>,
+    /// Camadas enviadas para a GPU (0 = só CPU)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub num_gpu: Option<i32>,
+}
+
+impl GenerationOptions {
+    /// Completa os campos ausentes com os de `fallback`
+    pub fn or(self, fallback: &GenerationOptions) -> Self {
+        Self {
+            temperature: self.temperature.or(fallback.temperature),
+            top_k: self.top_k.or(fallback.top_k),
+            top_p: self.top_p.or(fallback.top_p),
+            repeat_penalty: self.repeat_penalty.or(fallback.repeat_penalty),
+            num_predict: self.num_predict.or(fallback.num_predict),
+            stop: self.stop.or_else(|| fallback.stop.clone()),
+            seed: self.seed.or(fallback.seed),
+            num_ctx: self.num_ctx.or(fallback.num_ctx),
+            num_gpu: self.num_gpu.or(fallback.num_gpu),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// Valida os valores (`prefix` identifica a origem nas mensagens de erro)
+    pub fn validate(&self, prefix: &str, errors: &mut Vec<String>) {
+        if self.temperature.is_some_and(|t| !(0.0..=2.0).contains(&t)) {
+            errors.push(format!("{}.temperature deve estar entre 0 e 2", prefix));
+        }
+        if self.top_p.is_some_and(|p| !(0.0..=1.0).contains(&p)) {
+            errors.push(format!("{}.top_p deve estar entre 0 e 1", prefix));
+        }
+        if self.repeat_penalty.is_some_and(|p| !(0.0..=2.0).contains(&p)) {
+            errors.push(format!("{}.repeat_penalty deve estar entre 0 e 2", prefix));
+        }
+        if self.num_predict.is_some_and(|n| n < -2 || n == 0) {
+            errors.push(format!("{}.num_predict deve ser positivo (ou -1 para sem limite)", prefix));
+        }
+        if self.num_ctx.is_some_and(|n| n < 512) {
+            errors.push(format!("{}.num_ctx deve ser pelo menos 512", prefix));
+        }
+        if self.num_gpu.is_some_and(|n| n < -1) {
+            errors.push(format!("{}.num_gpu deve ser -1 (automático) ou maior", prefix));
+        }
+        if let Some(stop) = &self.stop {
+            if stop.iter().any(|s| s.is_empty()) {
+                errors.push(format!("{}.stop não pode conter sequências vazias", prefix));
+            }
+        }
+    }
+}
+
+/// Prompt fixo usado no benchmark (resposta curta e previsível)
+const BENCHMARK_PROMPT: &str = "Liste os números de 1 a 30 separados por vírgula.";
+const BENCHMARK_MAX_TOKENS: u32 = 64;
+
 /// Cliente Ollama headless (para execução em background)
 pub struct OllamaClient {
     pub(crate) base_url: String,
@@ -63,6 +166,46 @@ impl OllamaClient {
         }
     }
     
+    /// Detalhes do modelo (`model_info`, `details`, `parameters`) via /api/show
+    pub async fn show_model(&self, model: &str) -> Result<serde_json::Value, String> {
+        let url = format!("{}/api/show", self.base_url);
+        let response = self.client
+            .post(&url)
+            .json(&serde_json::json!({ "model": model }))
+            .send()
+            .await
+            .map_err(|e| format!("Failed to connect to Ollama: {}", e))?;
+        
+        if !response.status().is_success() {
+            return Err(format!("Ollama returned status: {}", response.status()));
+        }
+        response.json()
+            .await
+            .map_err(|e| format!("Failed to parse model details: {}", e))
+    }
+    
+    /// Tamanho em bytes de um modelo instalado (None se não estiver na lista)
+    pub async fn model_size(&self, model: &str) -> Result<Option<u64>, String> {
+        let url = format!("{}/api/tags", self.base_url);
+        let tags: serde_json::Value = self.client
+            .get(&url)
+            .send()
+            .await
+            .map_err(|e| format!("Failed to connect to Ollama: {}", e))?
+            .json()
+            .await
+            .map_err(|e| format!("Failed to parse model list: {}", e))?;
+        
+        Ok(tags.get("models")
+            .and_then(|m| m.as_array())
+            .and_then(|models| models.iter().find(|m| {
+                m.get("name").and_then(|n| n.as_str()) == Some(model)
+                    || m.get("model").and_then(|n| n.as_str()) == Some(model)
+            }))
+            .and_then(|m| m.get("size"))
+            .and_then(|s| s.as_u64()))
+    }
+    
     /// Envia prompt para o Ollama e retorna resposta completa (não streaming)
     pub async fn query_ollama_headless(
         &self,
@@ -95,6 +238,9 @@ impl OllamaClient {
             stream: true, // Streaming para economizar memória
         };
         
+        let prompt = serde_json::to_string(&request.messages).unwrap_or_default();
+        crate::audit::record(crate::audit::AuditKind::ModelPrompt, "headless", model, Some(&prompt));
+        
         let url = format!("{}/api/chat", self.base_url);
         let response = self.client
             .post(&url)
@@ -146,17 +292,21 @@ impl OllamaClient {
             return Err("Empty response from Ollama".to_string());
         }
         
+        crate::audit::record(crate::audit::AuditKind::ModelResponse, "headless", model, Some(&full_response));
         Ok(full_response.trim().to_string())
     }
     
     /// Gera um título curto (3-5 palavras) para a pergunta do usuário
-    pub async fn generate_title(&self, model: &str, user_input: &str) -> Result<String, String> {
-        let system_prompt = "Você é um gerador de títulos. Responda APENAS com um título de 3-5 palavras que resuma a pergunta. Nada mais, sem explicações.";
+    pub async fn generate_title(&self, model: &str, user_input: &str, language: &str) -> Result<String, String> {
+        let system_prompt = format!(
+            "Você é um gerador de títulos. Responda APENAS com um título de 3-5 palavras em {} que resuma a pergunta. Nada mais, sem explicações.",
+            crate::translation::language_name(language)
+        );
         
         let messages = vec![
             OllamaMessage {
                 role: "system".to_string(),
-                content: system_prompt.to_string(),
+                content: system_prompt,
             },
             OllamaMessage {
                 role: "user".to_string(),
@@ -170,6 +320,9 @@ impl OllamaClient {
             stream: true,
         };
         
+        let prompt = serde_json::to_string(&request.messages).unwrap_or_default();
+        crate::audit::record(crate::audit::AuditKind::ModelPrompt, "title", model, Some(&prompt));
+        
         let url = format!("{}/api/chat", self.base_url);
         let response = self.client
             .post(&url)
@@ -223,6 +376,7 @@ impl OllamaClient {
             }
         }
         
+        crate::audit::record(crate::audit::AuditKind::ModelResponse, "title", model, Some(&full_response));
         let title = full_response.trim().to_string();
         
         // Fallback se título estiver vazio ou muito longo
@@ -235,22 +389,134 @@ impl OllamaClient {
         }
     }
     
-    /// Gera emoji baseado no título
-    pub fn generate_emoji(title: &str) -> String {
-        let title_lower = title.to_lowercase();
+    /// Mede a vazão do servidor com `requests` gerações curtas em paralelo
+    pub async fn benchmark(&self, model: &str, requests: usize) -> Result<BenchmarkResult, String> {
+        let requests = requests.max(1);
+        let url = format!("{}/api/generate", self.base_url);
+        let body = serde_json::json!({
+            "model": model,
+            "prompt": BENCHMARK_PROMPT,
+            "stream": false,
+            "options": { "num_predict": BENCHMARK_MAX_TOKENS, "temperature": 0 },
+        });
         
-        // Keywords para emojis
-        if title_lower.contains("código") || title_lower.contains("program") || title_lower.contains("code") {
-            "💻".to_string()
-        } else if title_lower.contains("pergunta") || title_lower.contains("question") || title_lower.contains("como") {
-            "❓".to_string()
-        } else if title_lower.contains("explica") || title_lower.contains("explain") {
-            "📚".to_string()
-        } else if title_lower.contains("ajuda") || title_lower.contains("help") {
-            "🆘".to_string()
-        } else {
-            "💬".to_string() // Default
+        let started = std::time::Instant::now();
+        let calls = (0..requests).map(|_| async {
+            let response = self.client
+                .post(&url)
+                .json(&body)
+                .send()
+                .await
+                .map_err(|e| format!("Failed to send benchmark request: {}", e))?;
+            if !response.status().is_success() {
+                return Err(format!("Ollama returned status: {}", response.status()));
+            }
+            response.json::<OllamaGenerateStats>()
+                .await
+                .map_err(|e| format!("Failed to parse benchmark response: {}", e))
+        });
+        let stats = futures_util::future::try_join_all(calls).await?;
+        let total_secs = started.elapsed().as_secs_f64();
+        
+        let tokens: u64 = stats.iter().map(|s| s.eval_count).sum();
+        let avg_load_secs = stats.iter().map(|s| s.load_duration as f64 / 1e9).sum::<f64>() / requests as f64;
+        let tokens_per_sec = if total_secs > 0.0 { tokens as f64 / total_secs } else { 0.0 };
+        log::debug!(
+            "[Benchmark] {}: {} tokens em {:.2}s (eval médio {:.2}s)",
+            model,
+            tokens,
+            total_secs,
+            stats.iter().map(|s| s.eval_duration as f64 / 1e9).sum::<f64>() / requests as f64
+        );
+        
+        Ok(BenchmarkResult {
+            model: model.to_string(),
+            requests,
+            total_secs,
+            tokens_per_sec,
+            avg_load_secs,
+        })
+    }
+    
+    /// Se o servidor já tem o blob (`digest` no formato "sha256:<hex>")
+    pub async fn blob_exists(&self, digest: &str) -> Result<bool, String> {
+        let url = format!("{}/api/blobs/{}", self.base_url, digest);
+        let response = self.client
+            .head(&url)
+            .send()
+            .await
+            .map_err(|e| format!("Failed to connect to Ollama: {}", e))?;
+        Ok(response.status().is_success())
+    }
+    
+    /// Envia um blob via /api/blobs (o servidor confere o digest do conteúdo recebido)
+    pub async fn push_blob(&self, digest: &str, body: reqwest::Body, timeout: std::time::Duration) -> Result<(), String> {
+        let url = format!("{}/api/blobs/{}", self.base_url, digest);
+        let response = self.client
+            .post(&url)
+            .body(body)
+            .timeout(timeout)
+            .send()
+            .await
+            .map_err(|e| format!("Failed to upload blob: {}", e))?;
+        if !response.status().is_success() {
+            let status = response.status();
+            let detail = response.text().await.unwrap_or_default();
+            return Err(format!("Ollama returned status {}: {}", status, detail.trim()));
+        }
+        Ok(())
+    }
+    
+    /// Cria um modelo a partir de blobs já enviados (`files`: nome do arquivo → digest)
+    pub async fn create_from_files(&self, model: &str, files: &HashMap<String, String>) -> Result<(), String> {
+        let url = format!("{}/api/create", self.base_url);
+        let body = serde_json::json!({
+            "model": model,
+            "files": files,
+            "stream": false,
+        });
+        let response = self.client
+            .post(&url)
+            .json(&body)
+            .send()
+            .await
+            .map_err(|e| format!("Failed to create model: {}", e))?;
+        if !response.status().is_success() {
+            let status = response.status();
+            let detail = response.text().await.unwrap_or_default();
+            return Err(format!("Ollama returned status {}: {}", status, detail.trim()));
         }
+        Ok(())
     }
 }
 
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_generation_options_merge_and_serialize() {
+        let defaults = GenerationOptions {
+            temperature: Some(0.7),
+            num_ctx: Some(4096),
+            stop: Some(vec!["</s>".to_string()]),
+            ..GenerationOptions::default()
+        };
+        let call = GenerationOptions { temperature: Some(0.2), num_predict: Some(256), ..GenerationOptions::default() };
+        let merged = call.or(&defaults);
+        assert_eq!(merged.temperature, Some(0.2));
+        assert_eq!(merged.num_ctx, Some(4096));
+        assert_eq!(merged.num_predict, Some(256));
+
+        let json = serde_json::to_value(&merged).unwrap();
+        assert_eq!(json["stop"][0], "</s>");
+        assert!(json.get("seed").is_none());
+        assert!(GenerationOptions::default().is_empty());
+
+        let mut errors = Vec::new();
+        GenerationOptions { temperature: Some(3.0), num_predict: Some(0), ..GenerationOptions::default() }
+            .validate("options", &mut errors);
+        assert_eq!(errors.len(), 2);
+    }
+}
@@ -0,0 +1,148 @@
+//! Ciclo de vida do `ollama serve` iniciado pelo app e variáveis de ambiente do servidor
+
+use crate::settings::AppSettings;
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap};
+use std::process::Child;
+use std::sync::Mutex;
+
+/// Variáveis aceitas além das `OLLAMA_*` (proxy e seleção de GPU)
+const EXTRA_ALLOWED_VARS: &[&str] = &[
+    "HTTP_PROXY", "HTTPS_PROXY", "NO_PROXY",
+    "CUDA_VISIBLE_DEVICES", "HIP_VISIBLE_DEVICES", "ROCR_VISIBLE_DEVICES", "GPU_DEVICE_ORDINAL",
+];
+
+/// Processo do servidor iniciado por nós (servidores de serviço do sistema não entram aqui)
+static SPAWNED: Mutex<Option<Child>> = Mutex::new(None);
+
+/// Como aplicar as variáveis quando o Ollama roda como serviço do sistema
+#[derive(Serialize, Clone, Debug, PartialEq)]
+pub struct ServiceHint {
+    /// "systemd", "launchd" ou "windows"
+    pub platform: String,
+    pub instructions: String,
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct StopResult {
+    /// PID do processo iniciado pelo app que foi encerrado
+    pub spawned_pid: Option<u32>,
+    /// Outros processos `ollama` encerrados (apenas com `force`)
+    pub other_processes: u32,
+}
+
+pub fn validate_env(vars: &HashMap<String, String>, errors: &mut Vec<String>) {
+    for key in vars.keys() {
+        let valid_name = !key.is_empty() && key.chars().all(|c| c.is_ascii_uppercase() || c.is_ascii_digit() || c == '_');
+        if !valid_name || !(key.starts_with("OLLAMA_") || EXTRA_ALLOWED_VARS.contains(&key.as_str())) {
+            errors.push(format!("ollama_env: variável não permitida: {}", key));
+        }
+    }
+}
+
+/// Variáveis do processo: ajustes de desempenho, sobrescritos pelas variáveis configuradas
+pub fn env_vars(settings: &AppSettings) -> BTreeMap<String, String> {
+    let mut vars: BTreeMap<String, String> = settings.ollama_tuning
+        .env_vars()
+        .into_iter()
+        .map(|(key, value)| (key.to_string(), value))
+        .collect();
+    vars.extend(settings.ollama_env.iter().map(|(k, v)| (k.clone(), v.clone())));
+    vars
+}
+
+/// Guarda o processo iniciado, substituindo um anterior que já tenha terminado
+pub fn track(child: Child) {
+    if let Ok(mut spawned) = SPAWNED.lock() {
+        log::info!("[OllamaServer] Servidor iniciado pelo app: PID {}", child.id());
+        *spawned = Some(child);
+    }
+}
+
+/// PID do servidor iniciado pelo app, se ainda estiver rodando
+pub fn spawned_pid() -> Option<u32> {
+    let mut spawned = SPAWNED.lock().ok()?;
+    let child = spawned.as_mut()?;
+    match child.try_wait() {
+        Ok(None) => Some(child.id()),
+        _ => {
+            *spawned = None;
+            None
+        }
+    }
+}
+
+/// Encerra o servidor iniciado pelo app
+pub fn stop_spawned() -> Option<u32> {
+    let mut child = SPAWNED.lock().ok()?.take()?;
+    let pid = child.id();
+    if matches!(child.try_wait(), Ok(Some(_))) {
+        return None;
+    }
+    match child.kill() {
+        Ok(()) => {
+            let _ = child.wait();
+            log::info!("[OllamaServer] Servidor PID {} encerrado", pid);
+            Some(pid)
+        }
+        Err(e) => {
+            log::warn!("[OllamaServer] Falha ao encerrar PID {}: {}", pid, e);
+            None
+        }
+    }
+}
+
+fn quote(value: &str) -> String {
+    format!("\"{}\"", value.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+/// Instruções para aplicar as variáveis ao Ollama instalado como serviço
+/// (o app só controla o ambiente do processo que ele mesmo inicia)
+pub fn service_hints(vars: &BTreeMap<String, String>) -> Vec<ServiceHint> {
+    if vars.is_empty() {
+        return Vec::new();
+    }
+    let systemd = format!(
+        "sudo systemctl edit ollama.service\n\n[Service]\n{}\n\nsudo systemctl daemon-reload && sudo systemctl restart ollama",
+        vars.iter().map(|(k, v)| format!("Environment={}", quote(&format!("{}={}", k, v)))).collect::<Vec<_>>().join("\n")
+    );
+    let launchd = format!(
+        "{}\n\nReinicie o app do Ollama em seguida",
+        vars.iter().map(|(k, v)| format!("launchctl setenv {} {}", k, quote(v))).collect::<Vec<_>>().join("\n")
+    );
+    let windows = format!(
+        "{}\n\nFeche o Ollama na bandeja do sistema e abra novamente",
+        vars.iter().map(|(k, v)| format!("setx {} {}", k, quote(v))).collect::<Vec<_>>().join("\n")
+    );
+    vec![
+        ServiceHint { platform: "systemd".to_string(), instructions: systemd },
+        ServiceHint { platform: "launchd".to_string(), instructions: launchd },
+        ServiceHint { platform: "windows".to_string(), instructions: windows },
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_env_validation_and_service_hints() {
+        let mut errors = Vec::new();
+        let vars = HashMap::from([
+            ("OLLAMA_HOST".to_string(), "0.0.0.0:11434".to_string()),
+            ("CUDA_VISIBLE_DEVICES".to_string(), "0".to_string()),
+        ]);
+        validate_env(&vars, &mut errors);
+        assert!(errors.is_empty());
+        validate_env(&HashMap::from([("PATH".to_string(), "/tmp".to_string())]), &mut errors);
+        validate_env(&HashMap::from([("ollama_host".to_string(), "x".to_string())]), &mut errors);
+        assert_eq!(errors.len(), 2);
+
+        let vars = BTreeMap::from([("OLLAMA_MODELS".to_string(), "/data/models".to_string())]);
+        let hints = service_hints(&vars);
+        assert_eq!(hints.len(), 3);
+        assert!(hints[0].instructions.contains("Environment=\"OLLAMA_MODELS=/data/models\""));
+        assert!(hints[1].instructions.contains("launchctl setenv OLLAMA_MODELS \"/data/models\""));
+        assert!(service_hints(&BTreeMap::new()).is_empty());
+    }
+}
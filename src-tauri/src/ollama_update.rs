@@ -0,0 +1,175 @@
+//! Verificação de versão do Ollama e dados da última release no GitHub
+
+use serde::{Deserialize, Serialize};
+use std::process::Command;
+use std::time::Duration;
+
+const LATEST_RELEASE_URL: &str = "https://api.github.com/repos/ollama/ollama/releases/latest";
+const RELEASE_DOWNLOAD_URL: &str = "https://github.com/ollama/ollama/releases/download";
+const LINUX_INSTALL_SCRIPT_URL: &str = "https://ollama.com/install.sh";
+
+#[derive(Deserialize)]
+struct GithubRelease {
+    tag_name: String,
+    html_url: String,
+    published_at: Option<String>,
+}
+
+/// Última release publicada do Ollama
+#[derive(Serialize, Clone, Debug)]
+pub struct LatestRelease {
+    pub version: String,
+    pub release_url: String,
+    pub published_at: Option<String>,
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct OllamaUpdateInfo {
+    pub installed_version: Option<String>,
+    pub latest_version: String,
+    pub update_available: bool,
+    pub release_url: String,
+    pub published_at: Option<String>,
+    /// Instalador da plataforma atual (repassado a `download_installer`)
+    pub installer_url: Option<String>,
+    pub installer_filename: Option<String>,
+}
+
+/// Extrai `x.y.z` de textos como "ollama version is 0.5.7" ou "v0.5.7-rc1"
+pub fn parse_version(text: &str) -> Option<(u64, u64, u64)> {
+    text.split_whitespace().rev().find_map(|token| {
+        let core = token.trim_start_matches('v').split(&['-', '+'][..]).next()?;
+        let mut parts = core.split('.').map(|p| p.parse::<u64>().ok());
+        let major = parts.next()??;
+        let minor = parts.next()??;
+        let patch = parts.next().unwrap_or(Some(0))?;
+        Some((major, minor, patch))
+    })
+}
+
+pub fn format_version(version: (u64, u64, u64)) -> String {
+    format!("{}.{}.{}", version.0, version.1, version.2)
+}
+
+/// Se `latest` é mais nova que `installed` (versões ilegíveis nunca contam como atualização)
+pub fn is_newer(latest: &str, installed: &str) -> bool {
+    match (parse_version(latest), parse_version(installed)) {
+        (Some(latest), Some(installed)) => latest > installed,
+        _ => false,
+    }
+}
+
+/// Versão do Ollama instalado segundo `ollama --version`
+pub fn installed_version() -> Option<String> {
+    let output = Command::new("ollama").arg("--version").output().ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    // Sem servidor rodando, o CLI imprime avisos antes da linha "client version is x.y.z"
+    let text = format!(
+        "{}\n{}",
+        String::from_utf8_lossy(&output.stderr),
+        String::from_utf8_lossy(&output.stdout)
+    );
+    text.lines()
+        .filter(|line| line.contains("version"))
+        .find_map(parse_version)
+        .map(format_version)
+}
+
+/// Versão reportada pelo servidor em execução (`/api/version`)
+pub async fn server_version(base_url: &str) -> Option<String> {
+    #[derive(Deserialize)]
+    struct VersionResponse {
+        version: String,
+    }
+    let response = reqwest::get(format!("{}/api/version", base_url.trim_end_matches('/'))).await.ok()?;
+    let body: VersionResponse = response.json().await.ok()?;
+    parse_version(&body.version).map(format_version)
+}
+
+pub async fn latest_release() -> Result<LatestRelease, String> {
+    let client = reqwest::Client::builder()
+        .timeout(Duration::from_secs(10))
+        .user_agent(concat!("OllaHub/", env!("CARGO_PKG_VERSION")))
+        .build()
+        .map_err(|e| format!("Failed to create HTTP client: {}", e))?;
+    let response = client
+        .get(LATEST_RELEASE_URL)
+        .header("Accept", "application/vnd.github+json")
+        .send()
+        .await
+        .map_err(|e| format!("Failed to fetch latest Ollama release: {}", e))?;
+    if !response.status().is_success() {
+        return Err(format!("GitHub respondeu com status {}", response.status()));
+    }
+    let release: GithubRelease = response
+        .json()
+        .await
+        .map_err(|e| format!("Failed to parse GitHub release: {}", e))?;
+    let version = parse_version(&release.tag_name)
+        .map(format_version)
+        .ok_or_else(|| format!("Versão inválida na release: {}", release.tag_name))?;
+    Ok(LatestRelease {
+        version,
+        release_url: release.html_url,
+        published_at: release.published_at,
+    })
+}
+
+/// URL e nome de arquivo do instalador da versão para a plataforma atual.
+/// O nome inclui a versão para não coincidir com os instaladores locais de fallback.
+pub fn installer_for(version: &str) -> Option<(String, String)> {
+    if cfg!(target_os = "windows") {
+        Some((
+            format!("{}/v{}/OllamaSetup.exe", RELEASE_DOWNLOAD_URL, version),
+            format!("OllamaSetup-{}.exe", version),
+        ))
+    } else if cfg!(target_os = "macos") {
+        Some((
+            format!("{}/v{}/Ollama-darwin.zip", RELEASE_DOWNLOAD_URL, version),
+            format!("Ollama-darwin-{}.zip", version),
+        ))
+    } else if cfg!(target_os = "linux") {
+        // O script oficial sempre instala a última versão
+        Some((LINUX_INSTALL_SCRIPT_URL.to_string(), format!("install-{}.sh", version)))
+    } else {
+        None
+    }
+}
+
+pub async fn check_update(installed_version: Option<String>) -> Result<OllamaUpdateInfo, String> {
+    let latest = latest_release().await?;
+    let update_available = installed_version
+        .as_deref()
+        .map(|installed| is_newer(&latest.version, installed))
+        .unwrap_or(false);
+    let installer = installer_for(&latest.version);
+    Ok(OllamaUpdateInfo {
+        installed_version,
+        update_available,
+        installer_url: installer.as_ref().map(|(url, _)| url.clone()),
+        installer_filename: installer.map(|(_, filename)| filename),
+        latest_version: latest.version,
+        release_url: latest.release_url,
+        published_at: latest.published_at,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_and_compare_versions() {
+        assert_eq!(parse_version("ollama version is 0.5.7"), Some((0, 5, 7)));
+        assert_eq!(parse_version("Warning: client version is 0.12.3"), Some((0, 12, 3)));
+        assert_eq!(parse_version("v0.6.0-rc1"), Some((0, 6, 0)));
+        assert_eq!(parse_version("v1.2"), Some((1, 2, 0)));
+        assert_eq!(parse_version("ollama version is unknown"), None);
+
+        assert!(is_newer("v0.12.0", "0.9.6"));
+        assert!(!is_newer("0.5.7", "0.5.7"));
+        assert!(!is_newer("0.5.7", "garbage"));
+    }
+}
@@ -0,0 +1,251 @@
+//! Primeiro uso: recomendação de modelos pelo hardware e passos guiados
+//!
+//! `get_onboarding_recommendations` cruza RAM, VRAM e GPU detectadas com um catálogo de
+//! modelos iniciais (tamanhos do download quantizado q4 do Ollama) e devolve a lista
+//! ordenada com os motivos. `run_onboarding_step` conduz o fluxo instalar o Ollama →
+//! baixar o modelo → primeiro chat.
+
+use serde::{Deserialize, Serialize};
+
+/// Memória além do arquivo do modelo (contexto padrão e runtime)
+const RUNTIME_OVERHEAD_MB: u64 = 1024;
+/// Fração da RAM que um modelo rodando na CPU pode ocupar
+const RAM_BUDGET_PERCENT: u64 = 70;
+/// Recomendações devolvidas
+pub const MAX_RECOMMENDATIONS: usize = 5;
+
+/// Modelo do catálogo inicial
+struct StarterModel {
+    name: &'static str,
+    /// Bilhões de parâmetros (usado como indicador de qualidade)
+    params_b: f32,
+    size_mb: u64,
+    description: &'static str,
+    /// Modelo de propósito específico (código, visão): entra depois dos gerais
+    specialized: bool,
+}
+
+const CATALOG: &[StarterModel] = &[
+    StarterModel { name: "llama3.2:1b", params_b: 1.2, size_mb: 1300, description: "Muito leve, respostas rápidas até em CPU", specialized: false },
+    StarterModel { name: "llama3.2:3b", params_b: 3.2, size_mb: 2000, description: "Leve e equilibrado para uso geral", specialized: false },
+    StarterModel { name: "phi3.5", params_b: 3.8, size_mb: 2200, description: "Compacto, bom em raciocínio", specialized: false },
+    StarterModel { name: "qwen2.5:7b", params_b: 7.6, size_mb: 4700, description: "Ótimo em português e em seguir instruções", specialized: false },
+    StarterModel { name: "llama3.1:8b", params_b: 8.0, size_mb: 4900, description: "Uso geral, boa qualidade", specialized: false },
+    StarterModel { name: "qwen2.5-coder:7b", params_b: 7.6, size_mb: 4700, description: "Especializado em código", specialized: true },
+    StarterModel { name: "deepseek-r1:8b", params_b: 8.0, size_mb: 4900, description: "Raciocínio passo a passo (mais lento)", specialized: true },
+    StarterModel { name: "gemma2:9b", params_b: 9.2, size_mb: 5400, description: "Respostas bem escritas, uso geral", specialized: false },
+    StarterModel { name: "mistral-nemo:12b", params_b: 12.2, size_mb: 7100, description: "Contexto longo, uso geral", specialized: false },
+    StarterModel { name: "qwen2.5:14b", params_b: 14.8, size_mb: 9000, description: "Alta qualidade, precisa de GPU com bastante VRAM", specialized: false },
+    StarterModel { name: "qwen2.5:32b", params_b: 32.8, size_mb: 20000, description: "Qualidade máxima entre os iniciais", specialized: false },
+];
+
+/// Hardware considerado na recomendação
+#[derive(Serialize, Clone, Debug, Default)]
+pub struct HardwareSummary {
+    pub total_ram_mb: u64,
+    pub cpu_count: usize,
+    pub gpu_name: Option<String>,
+    pub vram_mb: Option<u64>,
+}
+
+/// Onde o modelo roda com o hardware detectado
+#[derive(Serialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ModelFit {
+    /// Inteiro na VRAM
+    Gpu,
+    /// Dividido entre GPU e RAM
+    Partial,
+    Cpu,
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct ModelRecommendation {
+    pub name: String,
+    pub description: String,
+    pub size_mb: u64,
+    /// "4.7 GB"
+    pub size: String,
+    pub fit: ModelFit,
+    pub installed: bool,
+    pub reasons: Vec<String>,
+    #[serde(skip)]
+    score: f32,
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct OllamaStatus {
+    pub installed: bool,
+    pub running: bool,
+    pub version: Option<String>,
+}
+
+/// Resposta de `get_onboarding_recommendations`
+#[derive(Serialize, Clone, Debug)]
+pub struct OnboardingRecommendations {
+    pub hardware: HardwareSummary,
+    pub ollama: OllamaStatus,
+    pub models: Vec<ModelRecommendation>,
+    /// Próximo passo do fluxo
+    pub next_step: OnboardingStepKind,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum OnboardingStepKind {
+    InstallOllama,
+    StartOllama,
+    PullModel,
+    FirstChat,
+    Done,
+}
+
+/// Passo pedido pela UI; sem `model`, usa a primeira recomendação
+#[derive(Deserialize, Clone, Debug)]
+#[serde(tag = "step", rename_all = "snake_case")]
+pub enum OnboardingStep {
+    InstallOllama,
+    StartOllama,
+    PullModel { model: Option<String> },
+    FirstChat { model: Option<String> },
+}
+
+impl OnboardingStep {
+    pub fn kind(&self) -> OnboardingStepKind {
+        match self {
+            OnboardingStep::InstallOllama => OnboardingStepKind::InstallOllama,
+            OnboardingStep::StartOllama => OnboardingStepKind::StartOllama,
+            OnboardingStep::PullModel { .. } => OnboardingStepKind::PullModel,
+            OnboardingStep::FirstChat { .. } => OnboardingStepKind::FirstChat,
+        }
+    }
+}
+
+/// Resultado de `run_onboarding_step`
+#[derive(Serialize, Clone, Debug)]
+pub struct OnboardingStepResult {
+    pub step: OnboardingStepKind,
+    /// "done", "skipped" ou "started" (instalador aberto, concluído fora do app)
+    pub status: String,
+    pub message: String,
+    pub model: Option<String>,
+    /// Sessão criada pelo primeiro chat
+    pub session_id: Option<String>,
+    pub next_step: OnboardingStepKind,
+}
+
+pub fn format_size(size_mb: u64) -> String {
+    if size_mb >= 1024 {
+        format!("{:.1} GB", size_mb as f64 / 1024.0)
+    } else {
+        format!("{} MB", size_mb)
+    }
+}
+
+fn gb(mb: u64) -> String {
+    format!("{:.0} GB", mb as f64 / 1024.0)
+}
+
+/// Próximo passo conforme o estado do Ollama e os modelos instalados
+pub fn next_step(ollama: &OllamaStatus, installed_models: &[String]) -> OnboardingStepKind {
+    if !ollama.installed && !ollama.running {
+        OnboardingStepKind::InstallOllama
+    } else if !ollama.running {
+        OnboardingStepKind::StartOllama
+    } else if installed_models.is_empty() {
+        OnboardingStepKind::PullModel
+    } else {
+        OnboardingStepKind::FirstChat
+    }
+}
+
+fn is_installed(name: &str, installed_models: &[String]) -> bool {
+    installed_models.iter().any(|m| m == name || m.strip_suffix(":latest") == Some(name))
+}
+
+/// Modelos do catálogo que cabem no hardware, do mais indicado para o menos
+pub fn recommend(hardware: &HardwareSummary, installed_models: &[String]) -> Vec<ModelRecommendation> {
+    let ram_budget = hardware.total_ram_mb * RAM_BUDGET_PERCENT / 100;
+    let vram = hardware.vram_mb.unwrap_or(0);
+
+    let mut models: Vec<ModelRecommendation> = CATALOG
+        .iter()
+        .filter_map(|model| {
+            let required = model.size_mb + RUNTIME_OVERHEAD_MB;
+            let mut reasons = Vec::new();
+            let (fit, mut score) = if vram > 0 && required <= vram {
+                reasons.push(format!("Cabe inteiro na VRAM ({} de {})", format_size(required), gb(vram)));
+                (ModelFit::Gpu, 100.0 + model.params_b * 4.0)
+            } else if vram > 0 && required <= vram + ram_budget / 2 {
+                reasons.push(format!("Parte na GPU ({} de VRAM), parte na RAM: mais lento", gb(vram)));
+                (ModelFit::Partial, 50.0 + model.params_b * 2.0)
+            } else if required <= ram_budget {
+                reasons.push(format!("Roda na CPU com {} de RAM", gb(hardware.total_ram_mb)));
+                // Sem GPU, modelos menores respondem em tempo aceitável
+                (ModelFit::Cpu, 40.0 - (model.params_b - 3.0).abs() * 4.0)
+            } else {
+                return None;
+            };
+
+            if model.specialized {
+                score -= 30.0;
+            }
+            let installed = is_installed(model.name, installed_models);
+            if installed {
+                score += 20.0;
+                reasons.push("Já instalado".to_string());
+            } else {
+                reasons.push(format!("Download de {}", format_size(model.size_mb)));
+            }
+            reasons.push(model.description.to_string());
+
+            Some(ModelRecommendation {
+                name: model.name.to_string(),
+                description: model.description.to_string(),
+                size_mb: model.size_mb,
+                size: format_size(model.size_mb),
+                fit,
+                installed,
+                reasons,
+                score,
+            })
+        })
+        .collect();
+
+    models.sort_by(|a, b| b.score.partial_cmp(&a.score).unwrap_or(std::cmp::Ordering::Equal));
+    models.truncate(MAX_RECOMMENDATIONS);
+    models
+}
+
+/// Modelo usado pelos passos sem `model` explícito
+pub fn default_model(hardware: &HardwareSummary, installed_models: &[String]) -> Option<String> {
+    recommend(hardware, installed_models).into_iter().next().map(|m| m.name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_recommend_by_hardware() {
+        let gpu = HardwareSummary { total_ram_mb: 32 * 1024, cpu_count: 16, gpu_name: Some("RTX 4070".to_string()), vram_mb: Some(12 * 1024) };
+        let models = recommend(&gpu, &[]);
+        assert_eq!(models[0].fit, ModelFit::Gpu);
+        assert_eq!(models[0].name, "qwen2.5:14b");
+        assert!(models.len() <= MAX_RECOMMENDATIONS);
+
+        // Notebook sem GPU: modelos pequenos na CPU, nada acima do orçamento de RAM
+        let laptop = HardwareSummary { total_ram_mb: 8 * 1024, cpu_count: 4, gpu_name: None, vram_mb: None };
+        let models = recommend(&laptop, &[]);
+        assert!(models.iter().all(|m| m.fit == ModelFit::Cpu && m.size_mb < 5000));
+        assert_eq!(models[0].name, "llama3.2:3b");
+
+        // Já instalado sobe na lista
+        let models = recommend(&laptop, &["phi3.5:latest".to_string()]);
+        assert_eq!(models[0].name, "phi3.5");
+        assert!(models[0].installed);
+
+        assert!(recommend(&HardwareSummary { total_ram_mb: 1024, ..Default::default() }, &[]).is_empty());
+        assert_eq!(format_size(4700), "4.6 GB");
+    }
+}
@@ -0,0 +1,274 @@
+//! Paleta de comandos: ações do backend com busca fuzzy
+//!
+//! A lista é montada a cada consulta a partir do estado real (modelos instalados, tasks,
+//! scheduler, seções das configurações), então a paleta da UI nunca oferece algo que o
+//! backend não sabe fazer. Extensões registram ações próprias com `register`; ao serem
+//! executadas elas apenas emitem `palette-action` para quem as registrou.
+
+use serde::{Deserialize, Serialize};
+use std::sync::{Mutex, OnceLock};
+
+/// Máximo de resultados devolvidos à UI
+pub const MAX_MATCHES: usize = 20;
+/// Prefixos que transformam o resto da consulta no nome do modelo a baixar
+/// (`pull_model:` é o id da própria ação)
+const PULL_PREFIXES: &[&str] = &["pull_model:", "pull ", "baixar ", "download "];
+
+/// Ação registrada por uma extensão
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct PluginAction {
+    pub id: String,
+    pub title: String,
+    #[serde(default)]
+    pub subtitle: Option<String>,
+    #[serde(default)]
+    pub keywords: Vec<String>,
+    /// Quem registrou (ex.: nome da extensão)
+    pub source: String,
+}
+
+#[derive(Serialize, Clone, Debug, PartialEq)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum PaletteAction {
+    NewChat { model: String },
+    PullModel { model: String },
+    RunTask { task_id: String },
+    ToggleScheduler,
+    OpenSettings { section: String },
+    Plugin { id: String, source: String },
+}
+
+impl PaletteAction {
+    /// ID estável usado pela UI para executar um resultado escolhido
+    pub fn id(&self) -> String {
+        match self {
+            PaletteAction::NewChat { model } => format!("new_chat:{}", model),
+            PaletteAction::PullModel { model } => format!("pull_model:{}", model),
+            PaletteAction::RunTask { task_id } => format!("run_task:{}", task_id),
+            PaletteAction::ToggleScheduler => "toggle_scheduler".to_string(),
+            PaletteAction::OpenSettings { section } => format!("open_settings:{}", section),
+            PaletteAction::Plugin { id, .. } => format!("plugin:{}", id),
+        }
+    }
+}
+
+#[derive(Serialize, Clone, Debug, PartialEq)]
+pub struct PaletteEntry {
+    pub id: String,
+    pub title: String,
+    pub subtitle: Option<String>,
+    pub category: String,
+    #[serde(skip)]
+    pub keywords: Vec<String>,
+    pub action: PaletteAction,
+}
+
+/// Resultado da busca, do mais relevante para o menos
+#[derive(Serialize, Clone, Debug)]
+pub struct PaletteMatch {
+    #[serde(flatten)]
+    pub entry: PaletteEntry,
+    pub score: i64,
+}
+
+/// Estado usado para montar a lista de ações
+pub struct PaletteContext<'a> {
+    pub models: &'a [String],
+    /// (id, label) das tasks
+    pub tasks: &'a [(String, String)],
+    pub scheduler_paused: bool,
+    pub settings_sections: &'a [String],
+    pub plugins: &'a [PluginAction],
+}
+
+fn registry() -> &'static Mutex<Vec<PluginAction>> {
+    static REGISTRY: OnceLock<Mutex<Vec<PluginAction>>> = OnceLock::new();
+    REGISTRY.get_or_init(|| Mutex::new(Vec::new()))
+}
+
+/// Registra (ou substitui, pelo id) uma ação de extensão
+pub fn register(action: PluginAction) -> Result<(), String> {
+    if action.id.trim().is_empty() || action.title.trim().is_empty() {
+        return Err("Ação da paleta precisa de id e título".to_string());
+    }
+    let mut actions = registry().lock().map_err(|_| "Palette registry poisoned".to_string())?;
+    actions.retain(|a| a.id != action.id);
+    actions.push(action);
+    Ok(())
+}
+
+pub fn plugin_actions() -> Vec<PluginAction> {
+    registry().lock().map(|actions| actions.clone()).unwrap_or_default()
+}
+
+/// Pontuação fuzzy de `query` em `text` (None se os caracteres não aparecem em ordem).
+/// Favorece letras consecutivas, início de palavra e o começo do texto.
+pub fn fuzzy_score(query: &str, text: &str) -> Option<i64> {
+    let query: Vec<char> = query.to_lowercase().chars().filter(|c| !c.is_whitespace()).collect();
+    if query.is_empty() {
+        return Some(0);
+    }
+    let text: Vec<char> = text.to_lowercase().chars().collect();
+    let mut score = 0i64;
+    let mut qi = 0;
+    let mut previous: Option<usize> = None;
+    for (ti, c) in text.iter().enumerate() {
+        if qi == query.len() {
+            break;
+        }
+        if *c != query[qi] {
+            continue;
+        }
+        score += 1;
+        if previous.is_some_and(|p| p + 1 == ti) {
+            score += 5;
+        }
+        if ti == 0 || !text[ti - 1].is_alphanumeric() {
+            score += 8;
+        }
+        previous = Some(ti);
+        qi += 1;
+    }
+    if qi < query.len() {
+        return None;
+    }
+    // Textos curtos ganham de longos com o mesmo casamento
+    Some(score * 10 - text.len() as i64)
+}
+
+fn entry(title: String, subtitle: Option<String>, category: &str, keywords: &[&str], action: PaletteAction) -> PaletteEntry {
+    PaletteEntry {
+        id: action.id(),
+        title,
+        subtitle,
+        category: category.to_string(),
+        keywords: keywords.iter().map(|k| k.to_string()).collect(),
+        action,
+    }
+}
+
+/// Todas as ações disponíveis para a consulta (sem filtrar)
+pub fn entries(query: &str, context: &PaletteContext) -> Vec<PaletteEntry> {
+    let mut entries = Vec::new();
+    for model in context.models {
+        entries.push(entry(
+            format!("Novo chat com {}", model),
+            None,
+            "chat",
+            &["new chat", "conversa"],
+            PaletteAction::NewChat { model: model.clone() },
+        ));
+    }
+
+    let lower = query.trim_start().to_lowercase();
+    if let Some(prefix) = PULL_PREFIXES.iter().find(|p| lower.starts_with(**p)) {
+        let model = query.trim_start()[prefix.len()..].trim();
+        if !model.is_empty() {
+            entries.push(entry(
+                format!("Baixar modelo {}", model),
+                Some("ollama pull".to_string()),
+                "models",
+                &[lower.trim()],
+                PaletteAction::PullModel { model: model.to_string() },
+            ));
+        }
+    }
+
+    for (id, label) in context.tasks {
+        entries.push(entry(
+            format!("Executar task: {}", label),
+            None,
+            "tasks",
+            &["run task", "rodar"],
+            PaletteAction::RunTask { task_id: id.clone() },
+        ));
+    }
+
+    let title = if context.scheduler_paused { "Retomar scheduler" } else { "Pausar scheduler" };
+    entries.push(entry(title.to_string(), None, "tasks", &["toggle scheduler", "agendador"], PaletteAction::ToggleScheduler));
+
+    for section in context.settings_sections {
+        entries.push(entry(
+            format!("Abrir configurações: {}", section),
+            None,
+            "settings",
+            &["settings", "preferências"],
+            PaletteAction::OpenSettings { section: section.clone() },
+        ));
+    }
+
+    for plugin in context.plugins {
+        let keywords: Vec<&str> = plugin.keywords.iter().map(String::as_str).collect();
+        entries.push(entry(
+            plugin.title.clone(),
+            plugin.subtitle.clone(),
+            &plugin.source,
+            &keywords,
+            PaletteAction::Plugin { id: plugin.id.clone(), source: plugin.source.clone() },
+        ));
+    }
+    entries
+}
+
+/// Ações que casam com a consulta, ordenadas por relevância. O id exato de uma ação
+/// (devolvido em buscas anteriores) sempre vem primeiro.
+pub fn search(query: &str, context: &PaletteContext) -> Vec<PaletteMatch> {
+    let query_trimmed = query.trim();
+    let mut matches: Vec<PaletteMatch> = entries(query, context)
+        .into_iter()
+        .filter_map(|entry| {
+            let score = if entry.id == query_trimmed {
+                i64::MAX
+            } else {
+                std::iter::once(entry.title.as_str())
+                    .chain(entry.keywords.iter().map(String::as_str))
+                    .filter_map(|text| fuzzy_score(query_trimmed, text))
+                    .max()?
+            };
+            Some(PaletteMatch { entry, score })
+        })
+        .collect();
+    matches.sort_by(|a, b| b.score.cmp(&a.score));
+    matches.truncate(MAX_MATCHES);
+    matches
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_search_palette() {
+        assert!(fuzzy_score("nchl", "Novo chat com llama3").is_some());
+        assert!(fuzzy_score("xyz", "Novo chat").is_none());
+        assert!(fuzzy_score("chat", "Novo chat").unwrap() > fuzzy_score("chat", "c h a t").unwrap());
+
+        let models = vec!["llama3".to_string(), "qwen2".to_string()];
+        let tasks = vec![("t1".to_string(), "Resumo diário".to_string())];
+        let sections = vec!["scraping".to_string(), "logging".to_string()];
+        let plugins = vec![PluginAction {
+            id: "notes.open".to_string(),
+            title: "Abrir notas".to_string(),
+            subtitle: None,
+            keywords: vec![],
+            source: "notes".to_string(),
+        }];
+        let context = PaletteContext {
+            models: &models,
+            tasks: &tasks,
+            scheduler_paused: true,
+            settings_sections: &sections,
+            plugins: &plugins,
+        };
+
+        assert_eq!(search("chat qwen", &context)[0].entry.id, "new_chat:qwen2");
+        assert_eq!(search("resumo", &context)[0].entry.action, PaletteAction::RunTask { task_id: "t1".to_string() });
+        assert_eq!(search("retomar", &context)[0].entry.id, "toggle_scheduler");
+        assert_eq!(search("config log", &context)[0].entry.id, "open_settings:logging");
+        assert_eq!(search("pull mistral:7b", &context)[0].entry.id, "pull_model:mistral:7b");
+        assert_eq!(search("notas", &context)[0].entry.category, "notes");
+        assert_eq!(search("run_task:t1", &context)[0].score, i64::MAX);
+        assert_eq!(search("pull_model:phi3", &context)[0].score, i64::MAX);
+        assert!(search("", &context).len() <= MAX_MATCHES);
+    }
+}
@@ -0,0 +1,113 @@
+use std::collections::HashMap;
+
+/// Início do token de referência inserido no prompt no lugar do texto colado
+const REFERENCE_PREFIX: &str = "[[paste:";
+const REFERENCE_SUFFIX: &str = "]]";
+/// Tamanho máximo do título gerado a partir do conteúdo
+const MAX_TITLE_CHARS: usize = 60;
+
+/// Token que substitui o texto colado no prompt (`[[paste:<id>]]`)
+pub fn reference_token(id: &str) -> String {
+    format!("{}{}{}", REFERENCE_PREFIX, id, REFERENCE_SUFFIX)
+}
+
+/// IDs de anexos referenciados em um texto (sem duplicatas, na ordem em que aparecem)
+pub fn find_references(text: &str) -> Vec<String> {
+    let mut ids = Vec::new();
+    let mut rest = text;
+
+    while let Some(start) = rest.find(REFERENCE_PREFIX) {
+        let after = &rest[start + REFERENCE_PREFIX.len()..];
+        match after.find(REFERENCE_SUFFIX) {
+            Some(end) => {
+                let id = after[..end].trim();
+                if !id.is_empty() && !ids.iter().any(|i| i == id) {
+                    ids.push(id.to_string());
+                }
+                rest = &after[end + REFERENCE_SUFFIX.len()..];
+            }
+            None => break,
+        }
+    }
+
+    ids
+}
+
+/// Substitui os tokens pelos blocos expandidos. Tokens sem anexo conhecido são mantidos.
+pub fn expand_references(text: &str, expanded: &HashMap<String, String>) -> String {
+    let mut result = text.to_string();
+    for (id, block) in expanded {
+        result = result.replace(&reference_token(id), block);
+    }
+    result
+}
+
+/// Texto da mensagem sem os tokens (usado como query para escolher os trechos relevantes)
+pub fn strip_references(text: &str) -> String {
+    let mut result = text.to_string();
+    for id in find_references(text) {
+        result = result.replace(&reference_token(&id), "");
+    }
+    result.trim().to_string()
+}
+
+/// Título curto a partir da primeira linha não vazia do conteúdo
+pub fn attachment_title(content: &str) -> String {
+    let first_line = content
+        .lines()
+        .map(|l| l.trim())
+        .find(|l| !l.is_empty())
+        .unwrap_or("Texto colado");
+
+    if first_line.chars().count() > MAX_TITLE_CHARS {
+        format!("{}…", first_line.chars().take(MAX_TITLE_CHARS).collect::<String>())
+    } else {
+        first_line.to_string()
+    }
+}
+
+/// Bloco inserido no prompt enviado ao modelo no lugar do token
+pub fn format_attachment_block(title: &str, summary: Option<&str>, content: &str, partial: bool) -> String {
+    let mut block = format!("<anexo titulo=\"{}\">\n", title.replace('"', "'"));
+    if let Some(summary) = summary {
+        block.push_str(&format!("Resumo: {}\n\n", summary.trim()));
+    }
+    if partial {
+        block.push_str("(Trechos mais relevantes do anexo)\n");
+    }
+    block.push_str(content.trim());
+    block.push_str("\n</anexo>");
+    block
+}
+
+/// Serializa um embedding como bytes little-endian (coluna BLOB)
+pub fn embedding_to_bytes(embedding: &[f32]) -> Vec<u8> {
+    embedding.iter().flat_map(|v| v.to_le_bytes()).collect()
+}
+
+/// Inverso de `embedding_to_bytes`
+pub fn embedding_from_bytes(bytes: &[u8]) -> Vec<f32> {
+    bytes
+        .chunks_exact(4)
+        .map(|b| f32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reference_tokens() {
+        let text = format!("Analise isto: {} e {} {}", reference_token("a1"), reference_token("b2"), reference_token("a1"));
+        assert_eq!(find_references(&text), vec!["a1".to_string(), "b2".to_string()]);
+        assert_eq!(strip_references(&text), "Analise isto:  e");
+
+        let mut expanded = HashMap::new();
+        expanded.insert("a1".to_string(), "<A>".to_string());
+        assert_eq!(
+            expand_references(&text, &expanded),
+            format!("Analise isto: <A> e {} <A>", reference_token("b2"))
+        );
+    }
+}
@@ -0,0 +1,146 @@
+use serde::Serialize;
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+
+/// Limite clássico do Windows (MAX_PATH) para caminhos sem o prefixo `\\?\`
+pub const WINDOWS_MAX_PATH: usize = 260;
+/// Margem para nomes de arquivos criados dentro do diretório (ex: `{uuid}.json`)
+const CHILD_NAME_MARGIN: usize = 60;
+
+const VERBATIM_PREFIX: &str = r"\\?\";
+const VERBATIM_UNC_PREFIX: &str = r"\\?\UNC\";
+
+/// Caminho recebido do frontend (dialog, drag & drop, texto colado): remove aspas e
+/// espaços nas pontas e converte URLs `file://` (com acentos percent-encoded)
+pub fn from_user_input(raw: &str) -> PathBuf {
+    let trimmed = raw.trim().trim_matches('"').trim();
+    if trimmed.starts_with("file://") {
+        if let Some(path) = url::Url::parse(trimmed).ok().and_then(|u| u.to_file_path().ok()) {
+            return path;
+        }
+    }
+    PathBuf::from(trimmed)
+}
+
+/// Caminho para ferramentas externas (Ollama, mensagens): sem o prefixo `\\?\`
+/// que o Windows adiciona em caminhos canônicos/longos e que muitos programas não entendem
+pub fn without_verbatim_prefix(path: &Path) -> PathBuf {
+    let raw = path.to_string_lossy();
+    if let Some(rest) = raw.strip_prefix(VERBATIM_UNC_PREFIX) {
+        PathBuf::from(format!(r"\\{}", rest))
+    } else if let Some(rest) = raw.strip_prefix(VERBATIM_PREFIX) {
+        PathBuf::from(rest)
+    } else {
+        path.to_path_buf()
+    }
+}
+
+/// Nome de entrada ZIP portátil (sempre `/`, independente do separador do sistema)
+pub fn zip_entry_name(prefix: &str, relative: &Path) -> String {
+    let parts: Vec<String> = relative
+        .components()
+        .filter_map(|c| match c {
+            Component::Normal(part) => Some(part.to_string_lossy().to_string()),
+            _ => None,
+        })
+        .collect();
+    if prefix.is_empty() {
+        parts.join("/")
+    } else {
+        format!("{}/{}", prefix.trim_end_matches('/'), parts.join("/"))
+    }
+}
+
+/// Diagnóstico de um diretório usado pelo app
+#[derive(Serialize, Clone, Debug)]
+pub struct PathCheck {
+    pub label: String,
+    pub path: String,
+    pub exists: bool,
+    pub writable: bool,
+    /// Comprimento em unidades UTF-16 (a medida usada pelo Windows)
+    pub length: usize,
+    pub non_ascii: bool,
+    pub issues: Vec<String>,
+}
+
+pub fn check_path(label: &str, path: &Path) -> PathCheck {
+    let display = without_verbatim_prefix(path);
+    let length = display.as_os_str().to_string_lossy().encode_utf16().count();
+    let valid_unicode = path.to_str().is_some();
+    let non_ascii = !display.to_string_lossy().is_ascii();
+    let exists = path.exists();
+    // Diretórios criados sob demanda: basta poder criá-los no ancestral existente mais próximo
+    let writable = path
+        .ancestors()
+        .find(|dir| dir.exists())
+        .is_some_and(probe_writable);
+
+    let mut issues = Vec::new();
+    if !valid_unicode {
+        issues.push("Caminho com caracteres que não são Unicode válido".to_string());
+    }
+    if !writable {
+        issues.push(if exists {
+            "Sem permissão de escrita".to_string()
+        } else {
+            "Diretório não existe e não pode ser criado".to_string()
+        });
+    }
+    if cfg!(windows) && length + CHILD_NAME_MARGIN >= WINDOWS_MAX_PATH {
+        issues.push(format!(
+            "Caminho longo ({} caracteres): arquivos internos podem passar do limite de {} do Windows",
+            length, WINDOWS_MAX_PATH
+        ));
+    }
+    if non_ascii {
+        issues.push("Contém caracteres não ASCII (acentos); ferramentas externas antigas podem falhar".to_string());
+    }
+
+    PathCheck {
+        label: label.to_string(),
+        path: display.to_string_lossy().to_string(),
+        exists,
+        writable,
+        length,
+        non_ascii,
+        issues,
+    }
+}
+
+/// Cria e remove um arquivo de teste no diretório
+fn probe_writable(dir: &Path) -> bool {
+    let probe = dir.join(format!(".ollahub_write_test_{}", std::process::id()));
+    match fs::write(&probe, b"ok") {
+        Ok(()) => {
+            let _ = fs::remove_file(&probe);
+            true
+        }
+        Err(_) => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_path_normalization() {
+        assert_eq!(
+            without_verbatim_prefix(Path::new(r"\\?\C:\Users\João\m.gguf")),
+            PathBuf::from(r"C:\Users\João\m.gguf")
+        );
+        assert_eq!(
+            without_verbatim_prefix(Path::new(r"\\?\UNC\server\share\a")),
+            PathBuf::from(r"\\server\share\a")
+        );
+        assert_eq!(zip_entry_name("chats", Path::new("sub/ação.json")), "chats/sub/ação.json");
+        assert_eq!(from_user_input("  \"/tmp/a b.gguf\" "), PathBuf::from("/tmp/a b.gguf"));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_file_url_input() {
+        assert_eq!(from_user_input("file:///home/jo%C3%A3o/x.gguf"), PathBuf::from("/home/joão/x.gguf"));
+    }
+}
@@ -0,0 +1,563 @@
+//! Plugins da comunidade: ferramentas extras sem precisar de um fork
+//!
+//! Cada plugin fica em `plugins/<nome>/` com um `plugin.json` (nome, runtime, ferramentas e
+//! permissões declaradas). Há dois runtimes:
+//! - `sidecar`: um executável que fala o mesmo JSON-RPC por stdio dos servidores MCP; roda
+//!   no mapa de processos MCP como o servidor `plugin:<nome>`.
+//! - `wasm`: um módulo WebAssembly executado no wasmtime, sem acesso a arquivos ou rede.
+//!   Exporta `memory`, `alloc(len) -> ptr` e `call(ptr, len) -> i64` (ptr << 32 | len);
+//!   recebe `{"tool", "arguments"}` e devolve o resultado em JSON (ou `{"error": "..."}`).
+//!
+//! Plugins são instalados desativados; `enable_plugin` liga e, para sidecars, inicia o processo.
+
+use crate::capabilities::Permission;
+use crate::{McpProcessHandle, McpProcessMap, McpTool};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeSet, HashMap};
+use std::fs;
+use std::io::Read;
+use std::path::{Component, Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::sync::{Arc, Mutex};
+use tauri::{AppHandle, Manager};
+
+/// Prefixo do nome de servidor das ferramentas de plugins
+pub const SERVER_PREFIX: &str = "plugin:";
+const MANIFEST_FILE: &str = "plugin.json";
+/// Plugins ativados (fica na raiz de `plugins/`)
+const STATE_FILE: &str = "enabled.json";
+/// Limites de um pacote ZIP de plugin (evita zip bombs)
+const MAX_ARCHIVE_ENTRIES: usize = 10_000;
+const MAX_EXTRACTED_BYTES: u64 = 512 * 1024 * 1024;
+/// Limite de instruções de uma chamada WASM (evita loops infinitos)
+#[cfg(feature = "wasm-plugins")]
+const WASM_FUEL: u64 = 2_000_000_000;
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum PluginRuntime {
+    Sidecar {
+        /// Caminho relativo à pasta do plugin (`./bin/tool`) ou comando no PATH
+        command: String,
+        #[serde(default)]
+        args: Vec<String>,
+        #[serde(default)]
+        env: HashMap<String, String>,
+    },
+    Wasm {
+        /// Caminho do `.wasm` relativo à pasta do plugin
+        module: String,
+    },
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct PluginManifest {
+    pub name: String,
+    pub version: String,
+    #[serde(default)]
+    pub description: String,
+    pub runtime: PluginRuntime,
+    /// Ferramentas expostas (obrigatórias no WASM; sidecars as informam via `tools/list`)
+    #[serde(default)]
+    pub tools: Vec<McpTool>,
+    /// Permissões declaradas, exibidas antes de ativar
+    #[serde(default)]
+    pub permissions: Vec<Permission>,
+}
+
+/// Item de `list_plugins`
+#[derive(Serialize, Clone, Debug)]
+pub struct PluginInfo {
+    pub manifest: PluginManifest,
+    pub enabled: bool,
+    /// Processo do sidecar ativo (sempre false no WASM)
+    pub running: bool,
+    pub path: String,
+}
+
+#[derive(Serialize, Deserialize, Default)]
+struct PluginState {
+    #[serde(default)]
+    enabled: BTreeSet<String>,
+}
+
+/// Nome do servidor MCP de um plugin
+pub fn server_name(plugin: &str) -> String {
+    format!("{}{}", SERVER_PREFIX, plugin)
+}
+
+/// Caminho de um arquivo do plugin, sem sair da pasta dele
+pub fn resolve_in_plugin(dir: &Path, relative: &str) -> Result<PathBuf, String> {
+    let path = Path::new(relative);
+    if path.is_absolute() || path.components().any(|c| !matches!(c, Component::Normal(_) | Component::CurDir)) {
+        return Err(format!("Caminho fora da pasta do plugin: {}", relative));
+    }
+    Ok(dir.join(path))
+}
+
+pub fn validate_manifest(manifest: &PluginManifest) -> Result<(), String> {
+    let name_ok = !manifest.name.is_empty()
+        && manifest.name.len() <= 64
+        && manifest.name.chars().all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-' || c == '_');
+    if !name_ok {
+        return Err(format!("Nome de plugin inválido: '{}' (use a-z, 0-9, - e _)", manifest.name));
+    }
+    if manifest.version.trim().is_empty() {
+        return Err("O manifesto precisa de uma versão".to_string());
+    }
+    match &manifest.runtime {
+        PluginRuntime::Sidecar { command, .. } => {
+            if command.trim().is_empty() {
+                return Err("Sidecar sem comando".to_string());
+            }
+            if !manifest.permissions.contains(&Permission::Process) {
+                return Err("Plugins sidecar precisam declarar a permissão 'process'".to_string());
+            }
+        }
+        PluginRuntime::Wasm { module } => {
+            resolve_in_plugin(Path::new(""), module)?;
+            if manifest.tools.is_empty() {
+                return Err("Plugins WASM precisam declarar as ferramentas no manifesto".to_string());
+            }
+        }
+    }
+    Ok(())
+}
+
+pub fn plugins_dir(app_handle: &AppHandle) -> Result<PathBuf, String> {
+    let dir = app_handle
+        .path()
+        .app_data_dir()
+        .map_err(|e| format!("Failed to get app data dir: {}", e))?
+        .join("plugins");
+    fs::create_dir_all(&dir).map_err(|e| format!("Failed to create plugins dir: {}", e))?;
+    Ok(dir)
+}
+
+fn load_state(dir: &Path) -> PluginState {
+    fs::read_to_string(dir.join(STATE_FILE))
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+fn save_state(dir: &Path, state: &PluginState) -> Result<(), String> {
+    let json = serde_json::to_string_pretty(state).map_err(|e| format!("Failed to serialize plugin state: {}", e))?;
+    fs::write(dir.join(STATE_FILE), json).map_err(|e| format!("Failed to write plugin state: {}", e))
+}
+
+fn read_manifest(dir: &Path) -> Result<PluginManifest, String> {
+    let content = fs::read_to_string(dir.join(MANIFEST_FILE))
+        .map_err(|e| format!("Failed to read {}: {}", MANIFEST_FILE, e))?;
+    let manifest: PluginManifest =
+        serde_json::from_str(&content).map_err(|e| format!("Failed to parse {}: {}", MANIFEST_FILE, e))?;
+    validate_manifest(&manifest)?;
+    Ok(manifest)
+}
+
+/// Manifesto de um plugin instalado
+fn installed(app_handle: &AppHandle, name: &str) -> Result<(PathBuf, PluginManifest), String> {
+    let dir = plugins_dir(app_handle)?.join(name);
+    let manifest = read_manifest(&dir).map_err(|e| format!("Plugin '{}': {}", name, e))?;
+    Ok((dir, manifest))
+}
+
+fn is_running(processes: &McpProcessMap, name: &str) -> bool {
+    processes
+        .lock()
+        .ok()
+        .and_then(|mut map| map.get_mut(&server_name(name)).map(|h| matches!(h.child.try_wait(), Ok(None))))
+        .unwrap_or(false)
+}
+
+pub fn list(app_handle: &AppHandle, processes: &McpProcessMap) -> Result<Vec<PluginInfo>, String> {
+    let dir = plugins_dir(app_handle)?;
+    let state = load_state(&dir);
+    let entries = fs::read_dir(&dir).map_err(|e| format!("Failed to read plugins dir: {}", e))?;
+    let mut plugins = Vec::new();
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if !path.is_dir() || entry.file_name().to_string_lossy().starts_with('.') {
+            continue;
+        }
+        match read_manifest(&path) {
+            Ok(manifest) => plugins.push(PluginInfo {
+                enabled: state.enabled.contains(&manifest.name),
+                running: is_running(processes, &manifest.name),
+                path: path.to_string_lossy().to_string(),
+                manifest,
+            }),
+            Err(e) => log::warn!("[Plugins] Ignorando {}: {}", path.display(), e),
+        }
+    }
+    plugins.sort_by(|a, b| a.manifest.name.cmp(&b.manifest.name));
+    Ok(plugins)
+}
+
+fn copy_dir(from: &Path, to: &Path) -> Result<(), String> {
+    for entry in walkdir::WalkDir::new(from) {
+        let entry = entry.map_err(|e| format!("Failed to read plugin files: {}", e))?;
+        let relative = entry.path().strip_prefix(from).map_err(|e| e.to_string())?;
+        let target = to.join(relative);
+        if entry.file_type().is_dir() {
+            fs::create_dir_all(&target).map_err(|e| format!("Failed to create plugin dir: {}", e))?;
+        } else {
+            fs::copy(entry.path(), &target).map_err(|e| format!("Failed to copy plugin file: {}", e))?;
+        }
+    }
+    Ok(())
+}
+
+fn extract_zip(archive_path: &Path, to: &Path) -> Result<(), String> {
+    let file = fs::File::open(archive_path).map_err(|e| format!("Failed to open plugin archive: {}", e))?;
+    let mut archive = zip::ZipArchive::new(file).map_err(|e| format!("Failed to read plugin archive: {}", e))?;
+    if archive.len() > MAX_ARCHIVE_ENTRIES {
+        return Err(format!("Plugin archive has too many entries ({}, max {})", archive.len(), MAX_ARCHIVE_ENTRIES));
+    }
+    let mut extracted: u64 = 0;
+    for i in 0..archive.len() {
+        let mut entry = archive.by_index(i).map_err(|e| format!("Failed to read plugin archive: {}", e))?;
+        let Some(relative) = entry.enclosed_name().map(Path::to_path_buf) else {
+            continue;
+        };
+        let target = to.join(relative);
+        if entry.is_dir() {
+            fs::create_dir_all(&target).map_err(|e| format!("Failed to create plugin dir: {}", e))?;
+            continue;
+        }
+        if let Some(parent) = target.parent() {
+            fs::create_dir_all(parent).map_err(|e| format!("Failed to create plugin dir: {}", e))?;
+        }
+        let mut out = fs::File::create(&target).map_err(|e| format!("Failed to extract plugin file: {}", e))?;
+        // O tamanho declarado no ZIP pode mentir: conta o que foi de fato descompactado
+        let remaining = MAX_EXTRACTED_BYTES - extracted;
+        let written = std::io::copy(&mut (&mut entry).take(remaining + 1), &mut out)
+            .map_err(|e| format!("Failed to extract plugin file: {}", e))?;
+        if written > remaining {
+            return Err(format!("Plugin archive expands beyond {} MB", MAX_EXTRACTED_BYTES / (1024 * 1024)));
+        }
+        extracted += written;
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            if let Some(mode) = entry.unix_mode() {
+                let _ = fs::set_permissions(&target, fs::Permissions::from_mode(mode));
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Instala (ou atualiza) um plugin a partir de uma pasta ou de um ZIP com o `plugin.json`
+/// na raiz (ou em uma única pasta de topo). O plugin fica desativado.
+pub fn install(app_handle: &AppHandle, processes: &McpProcessMap, source: &Path) -> Result<PluginInfo, String> {
+    let dir = plugins_dir(app_handle)?;
+    let staging = dir.join(format!(".install-{}", uuid::Uuid::new_v4()));
+    let result = (|| -> Result<PluginInfo, String> {
+        if source.is_dir() {
+            copy_dir(source, &staging)?;
+        } else {
+            extract_zip(source, &staging)?;
+        }
+        let mut root = staging.clone();
+        if !root.join(MANIFEST_FILE).exists() {
+            let subdirs: Vec<PathBuf> = fs::read_dir(&staging)
+                .map_err(|e| format!("Failed to read plugin files: {}", e))?
+                .flatten()
+                .map(|e| e.path())
+                .filter(|p| p.is_dir())
+                .collect();
+            match subdirs.as_slice() {
+                [single] if single.join(MANIFEST_FILE).exists() => root = single.clone(),
+                _ => return Err(format!("{} não encontrado no plugin", MANIFEST_FILE)),
+            }
+        }
+        let manifest = read_manifest(&root)?;
+        if let PluginRuntime::Wasm { module } = &manifest.runtime {
+            if !resolve_in_plugin(&root, module)?.is_file() {
+                return Err(format!("Módulo WASM não encontrado: {}", module));
+            }
+        }
+
+        stop(processes, &manifest.name);
+        let target = dir.join(&manifest.name);
+        if target.exists() {
+            fs::remove_dir_all(&target).map_err(|e| format!("Failed to replace plugin: {}", e))?;
+        }
+        fs::rename(&root, &target).map_err(|e| format!("Failed to install plugin: {}", e))?;
+
+        let mut state = load_state(&dir);
+        state.enabled.remove(&manifest.name);
+        save_state(&dir, &state)?;
+        log::info!("[Plugins] '{}' {} instalado", manifest.name, manifest.version);
+        Ok(PluginInfo { manifest, enabled: false, running: false, path: target.to_string_lossy().to_string() })
+    })();
+    let _ = fs::remove_dir_all(&staging);
+    result
+}
+
+fn stop(processes: &McpProcessMap, name: &str) {
+    if let Ok(mut map) = processes.lock() {
+        if let Some(mut handle) = map.remove(&server_name(name)) {
+            let _ = handle.child.kill();
+            let _ = handle.child.wait();
+        }
+    }
+}
+
+fn start_sidecar(processes: &McpProcessMap, dir: &Path, manifest: &PluginManifest) -> Result<u32, String> {
+    let PluginRuntime::Sidecar { command, args, env } = &manifest.runtime else {
+        return Ok(0);
+    };
+    // Caminhos relativos apontam para dentro do plugin; nomes simples vêm do PATH
+    let program = if command.contains('/') || command.contains('\\') {
+        resolve_in_plugin(dir, command)?
+    } else {
+        PathBuf::from(command)
+    };
+    let mut cmd = Command::new(&program);
+    cmd.args(args)
+        .envs(env)
+        .current_dir(dir)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null());
+
+    #[cfg(target_os = "windows")]
+    {
+        use std::os::windows::process::CommandExt;
+        const CREATE_NO_WINDOW: u32 = 0x08000000;
+        cmd.creation_flags(CREATE_NO_WINDOW);
+    }
+
+    let child = cmd
+        .spawn()
+        .map_err(|e| format!("Erro ao iniciar plugin '{}': {}", manifest.name, e))?;
+    let pid = child.id();
+    stop(processes, &manifest.name);
+    processes
+        .lock()
+        .map_err(|e| format!("Failed to lock processes map: {}", e))?
+        .insert(server_name(&manifest.name), McpProcessHandle { child, request_id: Arc::new(Mutex::new(0)) });
+    log::info!("[Plugins] Sidecar '{}' iniciado (pid {})", manifest.name, pid);
+    Ok(pid)
+}
+
+/// Ativa ou desativa um plugin (sidecars são iniciados ou encerrados na hora)
+pub fn set_enabled(app_handle: &AppHandle, processes: &McpProcessMap, name: &str, enabled: bool) -> Result<PluginInfo, String> {
+    let (plugin_dir, manifest) = installed(app_handle, name)?;
+    if enabled {
+        start_sidecar(processes, &plugin_dir, &manifest)?;
+    } else {
+        stop(processes, name);
+    }
+
+    let dir = plugins_dir(app_handle)?;
+    let mut state = load_state(&dir);
+    if enabled {
+        state.enabled.insert(manifest.name.clone());
+    } else {
+        state.enabled.remove(&manifest.name);
+    }
+    save_state(&dir, &state)?;
+    Ok(PluginInfo {
+        running: is_running(processes, name),
+        enabled,
+        path: plugin_dir.to_string_lossy().to_string(),
+        manifest,
+    })
+}
+
+/// Inicia os sidecars ativados (na abertura do app)
+pub fn start_enabled(app_handle: &AppHandle) {
+    let processes = app_handle.state::<McpProcessMap>().inner().clone();
+    let Ok(dir) = plugins_dir(app_handle) else { return };
+    for name in load_state(&dir).enabled {
+        match read_manifest(&dir.join(&name)) {
+            Ok(manifest) => {
+                if let Err(e) = start_sidecar(&processes, &dir.join(&name), &manifest) {
+                    log::warn!("[Plugins] {}", e);
+                }
+            }
+            Err(e) => log::warn!("[Plugins] Ignorando '{}': {}", name, e),
+        }
+    }
+}
+
+/// Ferramentas dos plugins WASM ativados, com o nome de servidor de cada um
+pub fn wasm_tools(app_handle: &AppHandle) -> Vec<(String, McpTool)> {
+    let Ok(dir) = plugins_dir(app_handle) else { return Vec::new() };
+    load_state(&dir)
+        .enabled
+        .into_iter()
+        .filter_map(|name| read_manifest(&dir.join(&name)).ok())
+        .filter(|manifest| matches!(manifest.runtime, PluginRuntime::Wasm { .. }))
+        .flat_map(|manifest| {
+            let server = server_name(&manifest.name);
+            manifest.tools.into_iter().map(move |tool| (server.clone(), tool))
+        })
+        .collect()
+}
+
+/// Plugin WASM ativado por trás de um nome de servidor (None para sidecars e MCP comuns)
+pub fn wasm_plugin(app_handle: &AppHandle, server: &str) -> Option<(PathBuf, PluginManifest)> {
+    let name = server.strip_prefix(SERVER_PREFIX)?;
+    let dir = plugins_dir(app_handle).ok()?;
+    if !load_state(&dir).enabled.contains(name) {
+        return None;
+    }
+    let (plugin_dir, manifest) = installed(app_handle, name).ok()?;
+    matches!(manifest.runtime, PluginRuntime::Wasm { .. }).then_some((plugin_dir, manifest))
+}
+
+impl PluginManifest {
+    /// Permissões garantidas pelo runtime: só as do WASM, que roda sem imports. Um sidecar é
+    /// um processo nativo com acesso livre à rede, qualquer que seja a declaração.
+    pub fn trusted_permissions(&self) -> Option<&[Permission]> {
+        matches!(self.runtime, PluginRuntime::Wasm { .. }).then_some(self.permissions.as_slice())
+    }
+}
+
+/// Permissões garantidas do plugin por trás de um nome de servidor (`None` para sidecars)
+pub fn trusted_permissions(app_handle: &AppHandle, server: &str) -> Option<Vec<Permission>> {
+    let name = server.strip_prefix(SERVER_PREFIX)?;
+    let (_, manifest) = installed(app_handle, name).ok()?;
+    manifest.trusted_permissions().map(<[Permission]>::to_vec)
+}
+
+/// Executa uma ferramenta de um plugin WASM (bloqueante)
+pub fn call_wasm(
+    plugin_dir: &Path,
+    manifest: &PluginManifest,
+    tool_name: &str,
+    arguments: serde_json::Value,
+) -> Result<serde_json::Value, String> {
+    let PluginRuntime::Wasm { module } = &manifest.runtime else {
+        return Err(format!("Plugin '{}' não é WASM", manifest.name));
+    };
+    if !manifest.tools.iter().any(|t| t.name == tool_name) {
+        return Err(format!("Ferramenta '{}' não declarada pelo plugin '{}'", tool_name, manifest.name));
+    }
+    let input = serde_json::to_vec(&serde_json::json!({ "tool": tool_name, "arguments": arguments }))
+        .map_err(|e| format!("Failed to serialize plugin input: {}", e))?;
+    let output = run_wasm(&resolve_in_plugin(plugin_dir, module)?, &input)?;
+    let result: serde_json::Value =
+        serde_json::from_slice(&output).map_err(|e| format!("Resposta inválida do plugin '{}': {}", manifest.name, e))?;
+    if let Some(error) = result.get("error").and_then(|e| e.as_str()) {
+        return Err(format!("Plugin '{}': {}", manifest.name, error));
+    }
+    Ok(result)
+}
+
+/// Engine e módulos já compilados, reaproveitados entre chamadas (recompila se o `.wasm` mudar)
+#[cfg(feature = "wasm-plugins")]
+struct WasmCache {
+    engine: wasmtime::Engine,
+    modules: HashMap<PathBuf, (std::time::SystemTime, wasmtime::Module)>,
+}
+
+#[cfg(feature = "wasm-plugins")]
+static WASM_CACHE: Mutex<Option<WasmCache>> = Mutex::new(None);
+
+#[cfg(feature = "wasm-plugins")]
+fn compiled_module(module_path: &Path) -> Result<(wasmtime::Engine, wasmtime::Module), String> {
+    use wasmtime::{Config, Engine, Module};
+
+    let mut guard = WASM_CACHE.lock().unwrap_or_else(|e| e.into_inner());
+    if guard.is_none() {
+        let mut config = Config::new();
+        config.consume_fuel(true);
+        let engine = Engine::new(&config).map_err(|e| format!("Failed to create WASM engine: {}", e))?;
+        *guard = Some(WasmCache { engine, modules: HashMap::new() });
+    }
+    let Some(cache) = guard.as_mut() else {
+        return Err("Failed to create WASM engine".to_string());
+    };
+
+    let modified = fs::metadata(module_path).and_then(|m| m.modified()).ok();
+    if let Some((compiled_at, module)) = cache.modules.get(module_path) {
+        if modified == Some(*compiled_at) {
+            return Ok((cache.engine.clone(), module.clone()));
+        }
+    }
+    let module = Module::from_file(&cache.engine, module_path).map_err(|e| format!("Failed to load WASM module: {}", e))?;
+    if let Some(modified) = modified {
+        cache.modules.insert(module_path.to_path_buf(), (modified, module.clone()));
+    }
+    Ok((cache.engine.clone(), module))
+}
+
+#[cfg(feature = "wasm-plugins")]
+fn run_wasm(module_path: &Path, input: &[u8]) -> Result<Vec<u8>, String> {
+    use wasmtime::{Linker, Store};
+
+    let (engine, module) = compiled_module(module_path)?;
+    let mut store = Store::new(&engine, ());
+    store.set_fuel(WASM_FUEL).map_err(|e| format!("Failed to set WASM fuel: {}", e))?;
+    // Sem imports: o módulo não tem acesso a arquivos, rede ou relógio
+    let instance = Linker::new(&engine)
+        .instantiate(&mut store, &module)
+        .map_err(|e| format!("Failed to instantiate WASM module: {}", e))?;
+
+    let memory = instance
+        .get_memory(&mut store, "memory")
+        .ok_or_else(|| "Módulo WASM não exporta 'memory'".to_string())?;
+    let alloc = instance
+        .get_typed_func::<i32, i32>(&mut store, "alloc")
+        .map_err(|e| format!("Módulo WASM sem 'alloc': {}", e))?;
+    let call = instance
+        .get_typed_func::<(i32, i32), i64>(&mut store, "call")
+        .map_err(|e| format!("Módulo WASM sem 'call': {}", e))?;
+
+    let len = i32::try_from(input.len()).map_err(|_| "Entrada grande demais para o plugin".to_string())?;
+    let ptr = alloc.call(&mut store, len).map_err(|e| format!("WASM alloc failed: {}", e))?;
+    memory
+        .write(&mut store, ptr as usize, input)
+        .map_err(|e| format!("Failed to write WASM memory: {}", e))?;
+    let packed = call.call(&mut store, (ptr, len)).map_err(|e| format!("WASM call failed: {}", e))? as u64;
+    let (out_ptr, out_len) = ((packed >> 32) as usize, (packed & 0xffff_ffff) as usize);
+    let mut output = vec![0u8; out_len];
+    memory
+        .read(&store, out_ptr, &mut output)
+        .map_err(|e| format!("Failed to read WASM memory: {}", e))?;
+    Ok(output)
+}
+
+#[cfg(not(feature = "wasm-plugins"))]
+fn run_wasm(_module_path: &Path, _input: &[u8]) -> Result<Vec<u8>, String> {
+    Err("Esta versão foi compilada sem suporte a plugins WASM".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate_manifest() {
+        let mut manifest: PluginManifest = serde_json::from_value(serde_json::json!({
+            "name": "weather",
+            "version": "0.1.0",
+            "runtime": { "type": "sidecar", "command": "./bin/weather" },
+            "permissions": ["process", "network"]
+        }))
+        .unwrap();
+        assert!(validate_manifest(&manifest).is_ok());
+
+        manifest.permissions = vec![Permission::Network];
+        assert!(validate_manifest(&manifest).is_err());
+
+        manifest.name = "Weather Tool".to_string();
+        assert!(validate_manifest(&manifest).is_err());
+
+        manifest.name = "weather".to_string();
+        manifest.runtime = PluginRuntime::Wasm { module: "../evil.wasm".to_string() };
+        assert!(validate_manifest(&manifest).is_err());
+        manifest.runtime = PluginRuntime::Wasm { module: "tool.wasm".to_string() };
+        assert!(validate_manifest(&manifest).is_err(), "WASM sem ferramentas declaradas");
+
+        let dir = Path::new("/plugins/weather");
+        assert_eq!(resolve_in_plugin(dir, "./bin/tool").unwrap(), dir.join("./bin/tool"));
+        assert!(resolve_in_plugin(dir, "/usr/bin/env").is_err());
+        assert_eq!(server_name("weather"), "plugin:weather");
+    }
+}
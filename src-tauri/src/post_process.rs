@@ -0,0 +1,293 @@
+//! Pós-processamento da resposta do modelo antes de ser salva
+//!
+//! A resposta é transmitida ao frontend como chega; ao final, a cadeia configurada em
+//! `post_processing` (blocos `<think>`, espaços repetidos, links de citações e substituições
+//! por regex do usuário) produz o conteúdo gravado no histórico.
+
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+const THINK_OPEN: &str = "<think>";
+const THINK_CLOSE: &str = "</think>";
+
+/// Substituição por regex definida pelo usuário (sintaxe do crate `regex`, `$1` nos grupos)
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct RegexReplacement {
+    pub pattern: String,
+    #[serde(default)]
+    pub replacement: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct PostProcessSettings {
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+    /// Remove blocos `<think>...</think>` de modelos de raciocínio
+    #[serde(default = "default_true")]
+    pub strip_think: bool,
+    /// Guarda o raciocínio removido nos metadados da mensagem (`reasoning`)
+    #[serde(default = "default_true")]
+    pub keep_think_in_metadata: bool,
+    /// Junta linhas em branco e espaços repetidos (fora de blocos de código)
+    #[serde(default = "default_true")]
+    pub collapse_whitespace: bool,
+    /// Transforma `[n]` em link para a n-ésima fonte da última pesquisa
+    #[serde(default = "default_true")]
+    pub link_citations: bool,
+    /// Aplicadas em ordem, por último
+    #[serde(default)]
+    pub replacements: Vec<RegexReplacement>,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+impl Default for PostProcessSettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            strip_think: true,
+            keep_think_in_metadata: true,
+            collapse_whitespace: true,
+            link_citations: true,
+            replacements: Vec::new(),
+        }
+    }
+}
+
+impl PostProcessSettings {
+    pub fn validate(&self, errors: &mut Vec<String>) {
+        for (index, rule) in self.replacements.iter().enumerate() {
+            if rule.pattern.is_empty() {
+                errors.push(format!("post_processing.replacements[{}]: padrão vazio", index));
+            } else if let Err(e) = Regex::new(&rule.pattern) {
+                errors.push(format!("post_processing.replacements[{}]: regex inválida: {}", index, e));
+            }
+        }
+    }
+}
+
+/// Resultado da cadeia
+#[derive(Debug, Clone, PartialEq)]
+pub struct Processed {
+    pub content: String,
+    /// Raciocínio removido (quando `keep_think_in_metadata`)
+    pub reasoning: Option<String>,
+}
+
+/// Separa os blocos `<think>` do texto. Alguns templates já abrem o bloco no prompt,
+/// então um `</think>` sem abertura marca o fim do raciocínio.
+pub fn split_think(text: &str) -> (String, Vec<String>) {
+    let mut reasoning = Vec::new();
+    let mut rest = text;
+
+    if let Some(close) = rest.find(THINK_CLOSE) {
+        if !rest[..close].contains(THINK_OPEN) {
+            reasoning.push(rest[..close].trim().to_string());
+            rest = &rest[close + THINK_CLOSE.len()..];
+        }
+    }
+
+    let mut content = String::new();
+    while let Some(open) = rest.find(THINK_OPEN) {
+        content.push_str(&rest[..open]);
+        let after = &rest[open + THINK_OPEN.len()..];
+        match after.find(THINK_CLOSE) {
+            Some(close) => {
+                reasoning.push(after[..close].trim().to_string());
+                rest = &after[close + THINK_CLOSE.len()..];
+            }
+            // Bloco não fechado (geração interrompida): tudo a seguir é raciocínio
+            None => {
+                reasoning.push(after.trim().to_string());
+                rest = "";
+            }
+        }
+    }
+    content.push_str(rest);
+    reasoning.retain(|r| !r.is_empty());
+    (content, reasoning)
+}
+
+/// Separa os blocos `<think>` de uma resposta recebida em partes; as tags podem chegar
+/// quebradas entre chunks, então um possível começo de tag fica retido até o próximo
+#[derive(Debug, Default)]
+pub struct ThinkSplitter {
+    in_think: bool,
+    held: String,
+}
+
+impl ThinkSplitter {
+    /// Retorna (conteúdo, raciocínio) do trecho
+    pub fn push(&mut self, chunk: &str) -> (String, String) {
+        let mut text = std::mem::take(&mut self.held);
+        text.push_str(chunk);
+        let (mut content, mut thinking) = (String::new(), String::new());
+        let mut rest = text.as_str();
+        loop {
+            let tag = if self.in_think { THINK_CLOSE } else { THINK_OPEN };
+            let out = if self.in_think { &mut thinking } else { &mut content };
+            match rest.find(tag) {
+                Some(index) => {
+                    out.push_str(&rest[..index]);
+                    rest = &rest[index + tag.len()..];
+                    self.in_think = !self.in_think;
+                }
+                None => {
+                    let keep = (1..tag.len()).rev().find(|n| rest.ends_with(&tag[..*n])).unwrap_or(0);
+                    out.push_str(&rest[..rest.len() - keep]);
+                    self.held = rest[rest.len() - keep..].to_string();
+                    break;
+                }
+            }
+        }
+        (content, thinking)
+    }
+
+    /// Libera o trecho retido no fim da resposta
+    pub fn finish(&mut self) -> (String, String) {
+        let held = std::mem::take(&mut self.held);
+        if self.in_think { (String::new(), held) } else { (held, String::new()) }
+    }
+}
+
+/// Aplica `transform` a cada linha fora de blocos de código cercados por ```
+fn map_outside_code(text: &str, mut transform: impl FnMut(&str) -> String) -> String {
+    let mut in_code = false;
+    text.split('\n')
+        .map(|line| {
+            if line.trim_start().starts_with("```") {
+                in_code = !in_code;
+                return line.to_string();
+            }
+            if in_code { line.to_string() } else { transform(line) }
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Espaços no fim das linhas, espaços repetidos no meio e linhas em branco consecutivas
+/// (a indentação e os blocos de código são preservados)
+pub fn collapse_whitespace(text: &str) -> String {
+    let collapsed = map_outside_code(text, |line| {
+        let trimmed = line.trim_end();
+        let indent_len = trimmed.len() - trimmed.trim_start().len();
+        let (indent, body) = trimmed.split_at(indent_len);
+        let mut result = indent.to_string();
+        let mut previous_space = false;
+        for c in body.chars() {
+            let space = c == ' ' || c == '\t';
+            if !(space && previous_space) {
+                result.push(if space { ' ' } else { c });
+            }
+            previous_space = space;
+        }
+        result
+    });
+
+    let mut output = String::with_capacity(collapsed.len());
+    let mut blank_run = 0;
+    let mut in_code = false;
+    for line in collapsed.split('\n') {
+        if line.trim_start().starts_with("```") {
+            in_code = !in_code;
+        }
+        blank_run = if line.is_empty() && !in_code { blank_run + 1 } else { 0 };
+        if blank_run <= 1 {
+            output.push_str(line);
+            output.push('\n');
+        }
+    }
+    output.trim().to_string()
+}
+
+/// `[n]` → `[n](url)` para as fontes numeradas (marcas já com link ou fora do intervalo ficam como estão)
+pub fn link_citations(text: &str, source_urls: &[String]) -> String {
+    if source_urls.is_empty() {
+        return text.to_string();
+    }
+    let marker = Regex::new(r"\[(\d{1,3})\](\()?").expect("valid citation regex");
+    map_outside_code(text, |line| {
+        marker
+            .replace_all(line, |caps: &regex::Captures| {
+                let already_linked = caps.get(2).is_some();
+                let url = caps[1]
+                    .parse::<usize>()
+                    .ok()
+                    .filter(|n| *n >= 1)
+                    .and_then(|n| source_urls.get(n - 1));
+                match url {
+                    Some(url) if !already_linked => format!("[{}]({})", &caps[1], url),
+                    _ => caps[0].to_string(),
+                }
+            })
+            .into_owned()
+    })
+}
+
+/// Executa a cadeia configurada sobre a resposta completa
+pub fn run(settings: &PostProcessSettings, text: &str, source_urls: &[String]) -> Processed {
+    if !settings.enabled {
+        return Processed { content: text.to_string(), reasoning: None };
+    }
+
+    let mut content = text.to_string();
+    let mut reasoning = None;
+    if settings.strip_think {
+        let (stripped, blocks) = split_think(&content);
+        content = stripped;
+        if settings.keep_think_in_metadata && !blocks.is_empty() {
+            reasoning = Some(blocks.join("\n\n"));
+        }
+    }
+    if settings.collapse_whitespace {
+        content = collapse_whitespace(&content);
+    }
+    if settings.link_citations {
+        content = link_citations(&content, source_urls);
+    }
+    for rule in &settings.replacements {
+        // Regras inválidas são barradas na validação das configurações
+        if let Ok(regex) = Regex::new(&rule.pattern) {
+            content = regex.replace_all(&content, rule.replacement.as_str()).into_owned();
+        }
+    }
+
+    Processed { content, reasoning }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_post_process_chain() {
+        let settings = PostProcessSettings {
+            replacements: vec![RegexReplacement { pattern: r"(?i)\bcolour\b".to_string(), replacement: "color".to_string() }],
+            ..PostProcessSettings::default()
+        };
+        let urls = vec!["https://a.example".to_string(), "https://b.example".to_string()];
+        let text = "<think>\nplanejando\n</think>\n\nThe  colour is blue [2].\n\n\n\nSee [1](x) and [7].\n```\nkeep   [1]\n\n\n```";
+
+        let processed = run(&settings, text, &urls);
+        assert_eq!(processed.reasoning.as_deref(), Some("planejando"));
+        assert_eq!(
+            processed.content,
+            "The color is blue [2](https://b.example).\n\nSee [1](x) and [7].\n```\nkeep   [1]\n\n\n```"
+        );
+
+        let (content, reasoning) = split_think("raciocínio</think>resposta");
+        assert_eq!((content.as_str(), reasoning), ("resposta", vec!["raciocínio".to_string()]));
+
+        let mut splitter = ThinkSplitter::default();
+        let parts: Vec<(String, String)> = ["<thi", "nk>passo 1</th", "ink>Resposta <", "b>"]
+            .iter()
+            .map(|chunk| splitter.push(chunk))
+            .chain(std::iter::once(splitter.finish()))
+            .collect();
+        let content: String = parts.iter().map(|(c, _)| c.as_str()).collect();
+        let thinking: String = parts.iter().map(|(_, t)| t.as_str()).collect();
+        assert_eq!((content.as_str(), thinking.as_str()), ("Resposta <b>", "passo 1"));
+    }
+}
@@ -0,0 +1,93 @@
+use crate::db::Database;
+use chrono::Local;
+use std::collections::HashMap;
+
+/// Substitui placeholders `{{nome}}` pelos valores informados.
+/// Placeholders sem valor são mantidos como estão para ficarem visíveis ao usuário.
+pub fn render_template(template: &str, vars: &HashMap<String, String>) -> String {
+    let mut rendered = String::with_capacity(template.len());
+    let mut rest = template;
+
+    while let Some(start) = rest.find("{{") {
+        rendered.push_str(&rest[..start]);
+        let after = &rest[start + 2..];
+
+        match after.find("}}") {
+            Some(end) => {
+                let name = after[..end].trim();
+                match vars.get(name) {
+                    Some(value) => rendered.push_str(value),
+                    None => rendered.push_str(&rest[start..start + 2 + end + 2]),
+                }
+                rest = &after[end + 2..];
+            }
+            None => {
+                rendered.push_str(&rest[start..]);
+                rest = "";
+            }
+        }
+    }
+    rendered.push_str(rest);
+
+    rendered
+}
+
+/// Variáveis disponíveis em qualquer preset (podem ser sobrescritas pelo preset ou pela sessão)
+pub fn builtin_variables(language: &str) -> HashMap<String, String> {
+    let now = Local::now();
+    let mut vars = HashMap::new();
+    vars.insert("language".to_string(), language.to_string());
+    vars.insert("date".to_string(), now.format("%Y-%m-%d").to_string());
+    vars.insert("time".to_string(), now.format("%H:%M").to_string());
+    vars
+}
+
+/// Renderiza o preset aplicado à sessão (se houver) no momento do envio
+pub fn resolve_session_prompt(db: &Database, session_id: &str, language: &str) -> Option<String> {
+    let (preset_id, session_vars) = match db.get_session_prompt_preset(session_id) {
+        Ok(Some(binding)) => binding,
+        Ok(None) => return None,
+        Err(e) => {
+            log::warn!("Falha ao buscar preset da sessão {}: {}", session_id, e);
+            return None;
+        }
+    };
+
+    let preset = match db.get_prompt_preset(&preset_id) {
+        Ok(Some(p)) => p,
+        Ok(None) => {
+            log::warn!("Preset {} aplicado à sessão {} não existe mais", preset_id, session_id);
+            return None;
+        }
+        Err(e) => {
+            log::warn!("Falha ao carregar preset {}: {}", preset_id, e);
+            return None;
+        }
+    };
+
+    // Precedência: variáveis da sessão > padrões do preset > variáveis embutidas
+    let mut vars = builtin_variables(language);
+    vars.extend(preset.variables);
+    vars.extend(session_vars);
+
+    Some(render_template(&preset.content, &vars))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_template() {
+        let mut vars = HashMap::new();
+        vars.insert("language".to_string(), "Português".to_string());
+        vars.insert("tone".to_string(), "formal".to_string());
+
+        assert_eq!(
+            render_template("Responda em {{language}} com tom {{ tone }}.", &vars),
+            "Responda em Português com tom formal."
+        );
+        assert_eq!(render_template("Olá {{nome}}!", &vars), "Olá {{nome}}!");
+        assert_eq!(render_template("Sem fim {{tone", &vars), "Sem fim {{tone");
+    }
+}
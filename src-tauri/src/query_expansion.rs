@@ -0,0 +1,148 @@
+//! Expansão de consultas por idioma (stopwords e sinônimos em `data/query_expansion`)
+//!
+//! O idioma vem de `scraping.search_language`; em "auto" é detectado na própria consulta
+//! (entre os idiomas com recursos) e, se a detecção não for confiável, cai no idioma do app.
+
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::sync::{OnceLock, RwLock};
+use whatlang::{Detector, Lang};
+
+/// Valor de `search_language` que ativa a detecção automática
+pub const AUTO_LANGUAGE: &str = "auto";
+/// Variantes geradas por consulta (incluindo a original)
+pub const MAX_VARIANTS: usize = 4;
+
+const RESOURCES: &[(&str, Lang, &str)] = &[
+    ("pt", Lang::Por, include_str!("../data/query_expansion/pt.json")),
+    ("en", Lang::Eng, include_str!("../data/query_expansion/en.json")),
+    ("es", Lang::Spa, include_str!("../data/query_expansion/es.json")),
+    ("fr", Lang::Fra, include_str!("../data/query_expansion/fr.json")),
+    ("de", Lang::Deu, include_str!("../data/query_expansion/de.json")),
+];
+
+#[derive(Deserialize, Debug, Default)]
+struct LanguageResources {
+    #[serde(default)]
+    stopwords: Vec<String>,
+    /// Grupos de termos equivalentes
+    #[serde(default)]
+    synonyms: Vec<Vec<String>>,
+}
+
+/// (configurado, idioma do app)
+static LANGUAGE: OnceLock<RwLock<(String, String)>> = OnceLock::new();
+static LOADED: OnceLock<HashMap<&'static str, LanguageResources>> = OnceLock::new();
+
+fn resources() -> &'static HashMap<&'static str, LanguageResources> {
+    LOADED.get_or_init(|| {
+        RESOURCES
+            .iter()
+            .map(|(code, _, data)| {
+                let parsed = serde_json::from_str(data).unwrap_or_else(|e| {
+                    log::warn!("[QueryExpansion] Recursos de '{}' inválidos: {}", code, e);
+                    LanguageResources::default()
+                });
+                (*code, parsed)
+            })
+            .collect()
+    })
+}
+
+/// Códigos de idioma com stopwords/sinônimos
+pub fn supported_languages() -> Vec<&'static str> {
+    RESOURCES.iter().map(|(code, _, _)| *code).collect()
+}
+
+/// Atualiza o idioma das buscas (`scraping.search_language`) e o idioma do app usado como reserva
+pub fn set_language(search_language: &str, app_language: &str) {
+    let lock = LANGUAGE.get_or_init(|| RwLock::new((AUTO_LANGUAGE.to_string(), "pt".to_string())));
+    if let Ok(mut current) = lock.write() {
+        *current = (normalize(search_language), normalize(app_language));
+    }
+}
+
+fn normalize(code: &str) -> String {
+    code.split(['-', '_']).next().unwrap_or(code).trim().to_lowercase()
+}
+
+/// Detecta o idioma da consulta entre os suportados (None se não for confiável)
+pub fn detect(query: &str) -> Option<&'static str> {
+    let allowlist = RESOURCES.iter().map(|(_, lang, _)| *lang).collect();
+    let info = Detector::with_allowlist(allowlist).detect(query)?;
+    if !info.is_reliable() {
+        return None;
+    }
+    RESOURCES.iter().find(|(_, lang, _)| *lang == info.lang()).map(|(code, _, _)| *code)
+}
+
+/// Idioma usado para expandir a consulta
+pub fn resolve_language(query: &str) -> String {
+    let (configured, app_language) = LANGUAGE
+        .get()
+        .and_then(|lock| lock.read().ok().map(|pair| pair.clone()))
+        .unwrap_or_else(|| (AUTO_LANGUAGE.to_string(), "pt".to_string()));
+    if configured != AUTO_LANGUAGE {
+        return configured;
+    }
+    detect(query).map(str::to_string).unwrap_or(app_language)
+}
+
+/// Variantes da consulta: a original primeiro, depois sem stopwords e com sinônimos
+/// (sem repetições, no máximo `MAX_VARIANTS`)
+pub fn expand_query_semantic(query: &str, language: &str) -> Vec<String> {
+    let query = query.trim();
+    let mut variants = vec![query.to_string()];
+    let Some(resources) = resources().get(normalize(language).as_str()) else {
+        return variants;
+    };
+
+    let words: Vec<&str> = query.split_whitespace().collect();
+    let is_stopword = |word: &str| resources.stopwords.iter().any(|s| s == &word.to_lowercase());
+
+    let keywords: Vec<&str> = words.iter().copied().filter(|w| !is_stopword(w)).collect();
+    if keywords.len() > 1 && keywords.len() < words.len() {
+        variants.push(keywords.join(" "));
+    }
+
+    // Troca uma palavra por vez pelos demais termos do grupo
+    for (index, word) in words.iter().enumerate() {
+        let lower = word.to_lowercase();
+        let Some(group) = resources.synonyms.iter().find(|group| group.contains(&lower)) else {
+            continue;
+        };
+        for synonym in group.iter().filter(|s| **s != lower) {
+            let mut replaced: Vec<&str> = words.clone();
+            replaced[index] = synonym.as_str();
+            variants.push(replaced.join(" "));
+        }
+    }
+
+    let mut seen = std::collections::HashSet::new();
+    variants.retain(|v| !v.is_empty() && seen.insert(v.to_lowercase()));
+    variants.truncate(MAX_VARIANTS);
+    variants
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_expand_query_semantic() {
+        assert_eq!(
+            expand_query_semantic("resultado da pesquisa", "pt-BR"),
+            vec![
+                "resultado da pesquisa",
+                "resultado pesquisa",
+                "achado da pesquisa",
+                "descoberta da pesquisa",
+            ]
+        );
+        // "resultados" não é o termo "resultado"
+        assert_eq!(expand_query_semantic("resultados", "pt"), vec!["resultados"]);
+        assert_eq!(expand_query_semantic("the best tutorial", "en")[1], "best tutorial");
+        assert_eq!(expand_query_semantic("qualquer coisa", "xx"), vec!["qualquer coisa"]);
+        assert!(supported_languages().iter().all(|code| resources()[code].stopwords.len() > 10));
+    }
+}
@@ -0,0 +1,116 @@
+use crate::settings::ModelRateLimit;
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant};
+
+/// Chave das configurações aplicada a modelos sem limite próprio
+pub const DEFAULT_LIMIT_KEY: &str = "*";
+
+/// Requisições recentes por modelo (janela deslizante)
+static WINDOWS: OnceLock<Mutex<HashMap<String, VecDeque<Instant>>>> = OnceLock::new();
+
+/// Origem de uma geração automatizada. O chat interativo nunca passa pelo limitador.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RequestOrigin {
+    Scheduler,
+}
+
+impl RequestOrigin {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RequestOrigin::Scheduler => "scheduler",
+        }
+    }
+}
+
+/// Limite efetivo de um modelo (o específico ou o padrão `*`)
+fn limit_for<'a>(limits: &'a HashMap<String, ModelRateLimit>, model: &str) -> Option<&'a ModelRateLimit> {
+    limits.get(model).or_else(|| limits.get(DEFAULT_LIMIT_KEY))
+}
+
+/// Registra a requisição em `window` se couber no limite; senão retorna quanto falta esperar
+fn try_record(window: &mut VecDeque<Instant>, limit: &ModelRateLimit, now: Instant) -> Result<(), Duration> {
+    let period = Duration::from_secs(limit.window_secs);
+    while let Some(oldest) = window.front() {
+        if now.duration_since(*oldest) >= period {
+            window.pop_front();
+        } else {
+            break;
+        }
+    }
+
+    if window.len() >= limit.max_requests as usize {
+        let oldest = window.front().copied().unwrap_or(now);
+        return Err(period.saturating_sub(now.duration_since(oldest)));
+    }
+
+    window.push_back(now);
+    Ok(())
+}
+
+/// Reserva uma requisição para `model`, falhando se o limite configurado foi atingido
+pub fn acquire(
+    limits: &HashMap<String, ModelRateLimit>,
+    model: &str,
+    origin: RequestOrigin,
+) -> Result<(), String> {
+    let limit = match limit_for(limits, model) {
+        Some(limit) => limit,
+        None => return Ok(()),
+    };
+
+    let windows = WINDOWS.get_or_init(|| Mutex::new(HashMap::new()));
+    let mut windows = windows
+        .lock()
+        .map_err(|e| format!("Failed to lock rate limiter: {}", e))?;
+    let window = windows.entry(model.to_string()).or_default();
+
+    try_record(window, limit, Instant::now()).map_err(|retry_after| {
+        log::warn!(
+            "[RateLimit] Limite do modelo {} atingido ({} req/{}s) para {}",
+            model,
+            limit.max_requests,
+            limit.window_secs,
+            origin.as_str()
+        );
+        format!(
+            "Limite de requisições do modelo {} atingido ({} a cada {}s). Tente novamente em {}s",
+            model,
+            limit.max_requests,
+            limit.window_secs,
+            retry_after.as_secs().max(1)
+        )
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sliding_window() {
+        let limit = ModelRateLimit { max_requests: 2, window_secs: 60 };
+        let mut window = VecDeque::new();
+        let start = Instant::now();
+
+        assert!(try_record(&mut window, &limit, start).is_ok());
+        assert!(try_record(&mut window, &limit, start + Duration::from_secs(10)).is_ok());
+        let retry = try_record(&mut window, &limit, start + Duration::from_secs(20)).unwrap_err();
+        assert_eq!(retry, Duration::from_secs(40));
+        assert!(try_record(&mut window, &limit, start + Duration::from_secs(61)).is_ok());
+    }
+
+    #[test]
+    fn test_limit_is_per_model() {
+        let limits = HashMap::from([(
+            DEFAULT_LIMIT_KEY.to_string(),
+            ModelRateLimit { max_requests: 1, window_secs: 60 },
+        )]);
+
+        assert!(acquire(&limits, "rate-limit-test-a", RequestOrigin::Scheduler).is_ok());
+        assert!(acquire(&limits, "rate-limit-test-a", RequestOrigin::Scheduler).is_err());
+        // Outro modelo tem a própria janela, mesmo caindo no limite padrão
+        assert!(acquire(&limits, "rate-limit-test-b", RequestOrigin::Scheduler).is_ok());
+        assert!(acquire(&HashMap::new(), "rate-limit-test-a", RequestOrigin::Scheduler).is_ok());
+    }
+}
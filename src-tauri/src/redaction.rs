@@ -0,0 +1,234 @@
+//! Remoção de dados pessoais das consultas antes de irem para motores de busca externos
+//!
+//! E-mails, telefones, chaves de API e os nomes do dicionário do usuário são retirados da
+//! consulta; a versão enviada é mostrada à UI pelo evento `search-query-redacted`. Com
+//! `require_confirmation`, as buscas da UI só seguem depois que o usuário aprova a consulta
+//! limpa (chamando de novo com `confirm_redaction: true`).
+
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::sync::OnceLock;
+use tauri::AppHandle;
+
+/// Prefixo do erro devolvido quando a busca aguarda a confirmação do usuário
+pub const CONFIRMATION_REQUIRED: &str = "REDACTION_CONFIRMATION_REQUIRED";
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct RedactionSettings {
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+    #[serde(default = "default_true")]
+    pub emails: bool,
+    #[serde(default = "default_true")]
+    pub phones: bool,
+    #[serde(default = "default_true")]
+    pub api_keys: bool,
+    /// Nomes (pessoas, empresas, projetos) que nunca saem da máquina
+    #[serde(default)]
+    pub dictionary: Vec<String>,
+    /// Pede confirmação antes de buscar quando algo foi removido
+    #[serde(default)]
+    pub require_confirmation: bool,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+impl Default for RedactionSettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            emails: true,
+            phones: true,
+            api_keys: true,
+            dictionary: Vec::new(),
+            require_confirmation: false,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum RedactionKind {
+    Email,
+    Phone,
+    ApiKey,
+    Name,
+}
+
+/// Quantas ocorrências de um tipo foram removidas
+#[derive(Serialize, Clone, Debug, PartialEq)]
+pub struct RedactionCount {
+    pub kind: RedactionKind,
+    pub count: usize,
+}
+
+#[derive(Serialize, Clone, Debug, PartialEq)]
+pub struct Redacted {
+    pub query: String,
+    pub redactions: Vec<RedactionCount>,
+}
+
+/// Payload de `search-query-redacted` (a consulta original não é reenviada)
+#[derive(Serialize, Clone, Debug)]
+pub struct SearchQueryRedactedEvent {
+    /// Consulta como será enviada
+    pub query: String,
+    pub redactions: Vec<RedactionCount>,
+    /// A busca ficou parada aguardando `confirm_redaction`
+    pub requires_confirmation: bool,
+    /// Origem da busca ("search", "metadata", "images", "tool", "task")
+    pub source: String,
+}
+
+/// Padrões fixos, na ordem aplicada (chaves antes de telefones: muitas têm sequências de dígitos)
+fn patterns() -> &'static [(RedactionKind, Regex)] {
+    static PATTERNS: OnceLock<Vec<(RedactionKind, Regex)>> = OnceLock::new();
+    PATTERNS.get_or_init(|| {
+        vec![
+            (
+                RedactionKind::ApiKey,
+                Regex::new(concat!(
+                    r"\b(?:sk-[A-Za-z0-9_-]{16,}|sk_(?:live|test)_[A-Za-z0-9]{16,}|gh[pousr]_[A-Za-z0-9]{20,}",
+                    r"|github_pat_[A-Za-z0-9_]{20,}|glpat-[A-Za-z0-9_-]{20,}|AKIA[0-9A-Z]{16}|AIza[0-9A-Za-z_-]{35}",
+                    r"|xox[abprs]-[A-Za-z0-9-]{10,}|eyJ[A-Za-z0-9_-]{8,}\.[A-Za-z0-9_-]{8,}\.[A-Za-z0-9_-]{8,}",
+                    // Hex solto é hash de commit ou checksum; só conta como chave depois de `key=`, `token:`...
+                    r"|(?i:api[_-]?key|token|secret)[=:]\s*[A-Fa-f0-9]{32,})\b",
+                ))
+                .expect("valid api key regex"),
+            ),
+            (
+                RedactionKind::Email,
+                Regex::new(r"\b[A-Za-z0-9._%+-]+@[A-Za-z0-9.-]+\.[A-Za-z]{2,}\b").expect("valid email regex"),
+            ),
+            (
+                // DDD obrigatório (com ou sem parênteses e DDI) para não pegar anos e números soltos
+                RedactionKind::Phone,
+                Regex::new(r"(?:\+\d{1,3}[\s.-]?)?(?:\(\d{2,3}\)\s?|\b\d{2,3}[\s.-]?)\d{4,5}[\s.-]?\d{4}\b")
+                    .expect("valid phone regex"),
+            ),
+        ]
+    })
+}
+
+fn kind_enabled(kind: RedactionKind, settings: &RedactionSettings) -> bool {
+    match kind {
+        RedactionKind::Email => settings.emails,
+        RedactionKind::Phone => settings.phones,
+        RedactionKind::ApiKey => settings.api_keys,
+        RedactionKind::Name => !settings.dictionary.is_empty(),
+    }
+}
+
+fn dictionary_regex(dictionary: &[String]) -> Option<Regex> {
+    let terms: Vec<String> = dictionary
+        .iter()
+        .map(|term| term.trim())
+        .filter(|term| !term.is_empty())
+        .map(regex::escape)
+        .collect();
+    if terms.is_empty() {
+        return None;
+    }
+    Regex::new(&format!(r"(?i)\b(?:{})\b", terms.join("|"))).ok()
+}
+
+/// Remove da consulta o que as configurações pedem (sem efeito com `enabled` desligado)
+pub fn redact(query: &str, settings: &RedactionSettings) -> Redacted {
+    let mut redactions = Vec::new();
+    if !settings.enabled {
+        return Redacted { query: query.to_string(), redactions };
+    }
+
+    let mut text = query.to_string();
+    let dictionary = dictionary_regex(&settings.dictionary).map(|re| (RedactionKind::Name, re));
+    for (kind, regex) in patterns().iter().chain(dictionary.iter()) {
+        if !kind_enabled(*kind, settings) {
+            continue;
+        }
+        let count = regex.find_iter(&text).count();
+        if count > 0 {
+            text = regex.replace_all(&text, " ").to_string();
+            redactions.push(RedactionCount { kind: *kind, count });
+        }
+    }
+
+    let query = if redactions.is_empty() {
+        query.to_string()
+    } else {
+        text.split_whitespace().collect::<Vec<_>>().join(" ")
+    };
+    Redacted { query, redactions }
+}
+
+/// Consulta a enviar aos motores de busca. Avisa a UI quando algo foi removido e, com
+/// `require_confirmation`, recusa a busca até ela ser confirmada.
+pub fn sanitize_query(app_handle: &AppHandle, query: &str, source: &str, confirmed: bool) -> Result<String, String> {
+    let settings = crate::settings::current(app_handle).redaction;
+    let redacted = redact(query, &settings);
+    if redacted.redactions.is_empty() {
+        return Ok(redacted.query);
+    }
+
+    let requires_confirmation = settings.require_confirmation && !confirmed;
+    log::info!(
+        "[Redaction] {} tipo(s) removidos da consulta ({}){}",
+        redacted.redactions.len(),
+        source,
+        if requires_confirmation { ", aguardando confirmação" } else { "" }
+    );
+    let event = SearchQueryRedactedEvent {
+        query: redacted.query.clone(),
+        redactions: redacted.redactions,
+        requires_confirmation,
+        source: source.to_string(),
+    };
+    if let Err(e) = crate::events::emit(app_handle, &event) {
+        log::warn!("[Redaction] Falha ao emitir search-query-redacted: {}", e);
+    }
+
+    if requires_confirmation {
+        return Err(format!("{}: confirme a consulta \"{}\" antes de buscar", CONFIRMATION_REQUIRED, event.query));
+    }
+    if event.query.is_empty() {
+        return Err("Consulta vazia após remover dados pessoais".to_string());
+    }
+    Ok(event.query)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_redact_query() {
+        let settings = RedactionSettings { dictionary: vec!["Maria Souza".to_string()], ..Default::default() };
+
+        let redacted = redact("contato joao.silva@empresa.com.br (11) 91234-5678 preço", &settings);
+        assert_eq!(redacted.query, "contato preço");
+        assert_eq!(
+            redacted.redactions,
+            vec![
+                RedactionCount { kind: RedactionKind::Email, count: 1 },
+                RedactionCount { kind: RedactionKind::Phone, count: 1 },
+            ]
+        );
+
+        let redacted = redact("erro 401 com sk-abcdefghijklmnop1234 para maria souza", &settings);
+        assert_eq!(redacted.query, "erro 401 com para");
+        assert_eq!(redacted.redactions.len(), 2);
+
+        // Hashes de commit e checksums ficam; hex atribuído a uma chave sai
+        let sha = "9fceb02d0ae598e95dc970b74767f19372d61af8";
+        assert!(redact(&format!("commit {} revert", sha), &settings).redactions.is_empty());
+        assert_eq!(redact(&format!("falha token={} login", sha), &settings).query, "falha login");
+
+        // Anos e números curtos ficam
+        assert!(redact("copa 2026 jogos de 2022 2018", &settings).redactions.is_empty());
+        assert!(redact("+55 11 3333-4444", &settings).redactions.iter().any(|r| r.kind == RedactionKind::Phone));
+
+        let off = RedactionSettings { enabled: false, ..settings };
+        assert_eq!(redact("a@b.com", &off).query, "a@b.com");
+    }
+}
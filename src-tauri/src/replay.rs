@@ -0,0 +1,185 @@
+use crate::db::Database;
+use crate::ollama_client::{GenerationOptions, OllamaClient};
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use tauri::AppHandle;
+
+/// Chave da metadata da mensagem do assistente com os parâmetros da geração
+const METADATA_KEY: &str = "generation";
+
+/// Parâmetros usados para gerar uma resposta (suficientes para repeti-la)
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct GenerationRecord {
+    pub model: String,
+    pub seed: i64,
+    /// Opções efetivas enviadas ao Ollama (incluindo o seed)
+    pub options: GenerationOptions,
+    /// System prompt final (preset + sessão) no momento do envio
+    pub system_prompt: Option<String>,
+    /// SHA-256 das mensagens enviadas, para detectar se o prompt mudou desde então
+    pub prompt_hash: String,
+}
+
+impl GenerationRecord {
+    /// Metadata JSON gravada na mensagem do assistente
+    pub fn to_metadata(&self) -> String {
+        serde_json::json!({ METADATA_KEY: self }).to_string()
+    }
+
+    pub fn from_metadata(metadata: Option<&str>) -> Option<Self> {
+        let value: serde_json::Value = serde_json::from_str(metadata?).ok()?;
+        serde_json::from_value(value.get(METADATA_KEY)?.clone()).ok()
+    }
+}
+
+/// Resultado de `replay_message`
+#[derive(Serialize, Clone, Debug)]
+pub struct ReplayResult {
+    pub message_id: i64,
+    pub model: String,
+    pub seed: i64,
+    pub original: String,
+    pub replayed: String,
+    pub identical: bool,
+    /// Posição (em caracteres) da primeira diferença
+    pub diverges_at: Option<usize>,
+    /// As mensagens reconstruídas diferem das enviadas originalmente (preset/pastes editados)
+    pub prompt_changed: bool,
+    pub duration_ms: u64,
+}
+
+/// Seed aleatório positivo (faixa aceita por todos os backends do Ollama)
+pub fn random_seed() -> i64 {
+    rand::thread_rng().gen_range(1..=i32::MAX as i64)
+}
+
+pub fn prompt_hash(messages: &[serde_json::Value]) -> String {
+    let serialized = serde_json::to_string(messages).unwrap_or_default();
+    Sha256::digest(serialized.as_bytes()).iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// Índice do primeiro caractere diferente (None se os textos forem iguais)
+pub fn first_divergence(a: &str, b: &str) -> Option<usize> {
+    let mut a_chars = a.chars();
+    let mut b_chars = b.chars();
+    let mut index = 0;
+    loop {
+        match (a_chars.next(), b_chars.next()) {
+            (None, None) => return None,
+            (x, y) if x != y => return Some(index),
+            _ => index += 1,
+        }
+    }
+}
+
+/// Repete a geração de uma mensagem do assistente com o mesmo prompt, seed e opções.
+/// `model` permite comparar a mesma entrada em outro modelo.
+pub async fn replay(app_handle: &AppHandle, message_id: i64, model: Option<String>) -> Result<ReplayResult, String> {
+    let (record, original, messages) = {
+        let db = Database::new(app_handle)
+            .map_err(|e| format!("Failed to open database: {}", e))?;
+        let message = db.get_message(message_id)
+            .map_err(|e| format!("Failed to load message: {}", e))?
+            .ok_or_else(|| format!("Mensagem não encontrada: {}", message_id))?;
+        if message.role != "assistant" {
+            return Err("Apenas respostas do assistente podem ser repetidas".to_string());
+        }
+        let record = GenerationRecord::from_metadata(message.metadata.as_deref())
+            .ok_or_else(|| "A mensagem não tem os parâmetros de geração gravados (anterior ao replay)".to_string())?;
+
+        // Contexto: mensagens anteriores da sessão, como foram enviadas (pastes expandidos)
+        let mut messages = Vec::new();
+        if let Some(system_prompt) = &record.system_prompt {
+            messages.push(serde_json::json!({ "role": "system", "content": system_prompt }));
+        }
+        for previous in db.get_messages(&message.session_id)
+            .map_err(|e| format!("Failed to load messages: {}", e))?
+            .into_iter()
+            .filter(|m| m.id.is_some_and(|id| id < message_id))
+        {
+            messages.push(serde_json::json!({
+                "role": previous.role,
+                "content": crate::expand_paste_references(app_handle, &db, &previous.content),
+            }));
+        }
+        (record, message.content, messages)
+    };
+
+    let prompt_changed = prompt_hash(&messages) != record.prompt_hash;
+    let model = model.unwrap_or_else(|| record.model.clone());
+    let mut messages = messages;
+    crate::chat_templates::apply_for_model(&crate::settings::current(app_handle).chat_templates, &model, &mut messages);
+    let request = serde_json::json!({
+        "model": model,
+        "messages": messages,
+        "stream": false,
+        "options": record.options,
+    });
+
+    let client = OllamaClient::new(Some(crate::settings::current(app_handle).ollama_url));
+    client.check_connection().await?;
+    crate::audit::record(crate::audit::AuditKind::ModelPrompt, "replay", &model, Some(&request["messages"].to_string()));
+    let started = std::time::Instant::now();
+    let response = client.client
+        .post(format!("{}/api/chat", client.base_url))
+        .json(&request)
+        .send()
+        .await
+        .map_err(|e| format!("Failed to send request to Ollama: {}", e))?;
+    if !response.status().is_success() {
+        return Err(format!("Ollama returned status: {}", response.status()));
+    }
+    let body: serde_json::Value = response.json().await
+        .map_err(|e| format!("Failed to parse Ollama response: {}", e))?;
+    let replayed = body.pointer("/message/content")
+        .and_then(|c| c.as_str())
+        .unwrap_or_default()
+        .to_string();
+    crate::audit::record(crate::audit::AuditKind::ModelResponse, "replay", &model, Some(&replayed));
+
+    let diverges_at = first_divergence(&original, &replayed);
+    log::info!(
+        "[Replay] Mensagem {} repetida com seed {} em {}: {}",
+        message_id,
+        record.seed,
+        model,
+        if diverges_at.is_none() { "idêntica" } else { "diferente" }
+    );
+
+    Ok(ReplayResult {
+        message_id,
+        model,
+        seed: record.seed,
+        original,
+        replayed,
+        identical: diverges_at.is_none(),
+        diverges_at,
+        prompt_changed,
+        duration_ms: started.elapsed().as_millis() as u64,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_generation_record_and_divergence() {
+        let record = GenerationRecord {
+            model: "llama3".to_string(),
+            seed: 42,
+            options: GenerationOptions { seed: Some(42), ..GenerationOptions::default() },
+            system_prompt: None,
+            prompt_hash: prompt_hash(&[serde_json::json!({ "role": "user", "content": "oi" })]),
+        };
+        let metadata = record.to_metadata();
+        assert_eq!(GenerationRecord::from_metadata(Some(&metadata)), Some(record));
+        assert!(GenerationRecord::from_metadata(Some("{\"recovered\":true}")).is_none());
+
+        assert_eq!(first_divergence("olá mundo", "olá mundo"), None);
+        assert_eq!(first_divergence("olá mundo", "olá munda"), Some(8));
+        assert_eq!(first_divergence("olá", "olá!"), Some(3));
+        assert!((1..=i32::MAX as i64).contains(&random_seed()));
+    }
+}
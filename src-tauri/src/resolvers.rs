@@ -0,0 +1,350 @@
+//! Respostas diretas de APIs estruturadas para consultas factuais
+//!
+//! Antes dos buscadores, `smart_search` consulta os resolvers (câmbio, clima, Wikipedia e
+//! Wikidata) quando o `IntentClassifier` vê uma pergunta factual. Uma resposta com confiança
+//! alta dispensa os buscadores; as demais entram como fontes extras junto com os resultados.
+
+use crate::intent_classifier::{IntentClassifier, QueryIntent};
+use crate::web_scraper::ScrapedContent;
+use regex::Regex;
+use serde::Serialize;
+use serde_json::Value;
+use std::time::Duration;
+
+const FETCH_TIMEOUT: Duration = Duration::from_secs(8);
+/// A partir desta confiança os buscadores genéricos não são consultados
+pub const CONFIDENT: f32 = 0.8;
+
+/// Resposta de um resolver
+#[derive(Serialize, Clone, Debug, PartialEq)]
+pub struct Resolved {
+    /// "currency", "weather", "wikipedia" ou "wikidata"
+    pub resolver: &'static str,
+    pub title: String,
+    /// Página legível da fonte (citada na resposta)
+    pub url: String,
+    pub snippet: String,
+    pub confidence: f32,
+}
+
+impl Resolved {
+    /// Mesmo formato das páginas extraídas, para entrar no contexto junto com elas
+    pub fn into_scraped(self) -> ScrapedContent {
+        let markdown = format!("# {}\n\n{}\n\nFonte: {}", self.title, self.snippet, self.url);
+        ScrapedContent {
+            title: self.title,
+            url: self.url,
+            content: self.snippet,
+            markdown,
+            original_language: None,
+            published_at: Some(chrono::Utc::now().to_rfc3339()),
+        }
+    }
+}
+
+/// Moedas reconhecidas (termo em minúsculas → código ISO)
+const CURRENCIES: &[(&str, &str)] = &[
+    ("usd", "USD"), ("dólar", "USD"), ("dolar", "USD"), ("dólares", "USD"), ("dolares", "USD"), ("dollar", "USD"), ("dollars", "USD"),
+    ("eur", "EUR"), ("euro", "EUR"), ("euros", "EUR"),
+    ("brl", "BRL"), ("reais", "BRL"),
+    ("gbp", "GBP"), ("libra", "GBP"), ("libras", "GBP"), ("pound", "GBP"), ("pounds", "GBP"),
+    ("jpy", "JPY"), ("iene", "JPY"), ("ienes", "JPY"), ("yen", "JPY"),
+    ("cad", "CAD"), ("aud", "AUD"), ("chf", "CHF"), ("cny", "CNY"), ("yuan", "CNY"),
+    ("ars", "ARS"), ("mxn", "MXN"),
+];
+/// Com uma moeda só, a pergunta precisa de um destes termos
+const CURRENCY_TRIGGERS: &[&str] = &[
+    "cotação", "cotacao", "câmbio", "cambio", "converter", "conversão", "conversao", "quanto está",
+    "quanto vale", "exchange", "rate", "convert", "cotización", "cotizacion",
+];
+const WEATHER_TRIGGERS: &[&str] = &[
+    "previsão do tempo", "previsao do tempo", "tempo em", "clima em", "temperatura em", "vai chover",
+    "weather", "forecast", "temperature in", "clima", "tiempo en",
+];
+/// Palavras de tempo no fim do nome da cidade ("em Lisboa hoje")
+const TIME_WORDS: &[&str] = &["hoje", "amanhã", "amanha", "agora", "today", "tomorrow", "now", "hoy", "mañana"];
+
+/// Conversão pedida: (valor, de, para)
+pub fn parse_currency_query(query: &str) -> Option<(f64, &'static str, &'static str)> {
+    let lower = query.to_lowercase();
+    let words: Vec<&str> = lower
+        .split(|c: char| !c.is_alphanumeric() && c != '.' && c != ',')
+        .filter(|w| !w.is_empty())
+        .collect();
+    let mut found: Vec<&'static str> = Vec::new();
+    for word in &words {
+        if let Some((_, code)) = CURRENCIES.iter().find(|(term, _)| term == word) {
+            if !found.contains(code) {
+                found.push(code);
+            }
+        }
+    }
+    let has_trigger = CURRENCY_TRIGGERS
+        .iter()
+        .any(|t| words.contains(t) || (t.contains(' ') && lower.contains(t)));
+    let (from, to) = match found.as_slice() {
+        [from, to, ..] => (*from, *to),
+        [from] if has_trigger => (*from, if *from == "BRL" { "USD" } else { "BRL" }),
+        _ => return None,
+    };
+    let amount = Regex::new(r"(\d+(?:[.,]\d+)?)")
+        .expect("valid amount regex")
+        .captures(&lower)
+        .and_then(|caps| caps[1].replace(',', ".").parse::<f64>().ok())
+        .filter(|v| *v > 0.0)
+        .unwrap_or(1.0);
+    Some((amount, from, to))
+}
+
+/// Cidade de uma pergunta sobre o clima
+pub fn parse_weather_query(query: &str) -> Option<String> {
+    let lower = query.to_lowercase();
+    if !WEATHER_TRIGGERS.iter().any(|t| lower.contains(t)) {
+        return None;
+    }
+    // A última preposição que deixa um nome de lugar ("clima em Belo Horizonte para hoje")
+    let preposition = Regex::new(r"(?i)\b(?:em|in|en|para|no|na|at|for)\s+").expect("valid preposition regex");
+    let trimmed = query.trim().trim_end_matches(['?', '!', '.']);
+    let starts: Vec<usize> = preposition.find_iter(trimmed).map(|m| m.end()).collect();
+    for start in starts.into_iter().rev() {
+        let mut words: Vec<&str> = trimmed[start..].split_whitespace().collect();
+        while words.last().is_some_and(|w| {
+            let w = w.to_lowercase();
+            TIME_WORDS.contains(&w.as_str()) || preposition.is_match(&format!("{} ", w))
+        }) {
+            words.pop();
+        }
+        let city = words.join(" ");
+        if !city.is_empty() && !["tempo", "clima", "weather"].contains(&city.to_lowercase().as_str()) {
+            return Some(city);
+        }
+    }
+    None
+}
+
+/// Assunto de perguntas de definição ("o que é X", "quem foi X", "what is X")
+pub fn parse_subject(query: &str) -> Option<String> {
+    let prefix = Regex::new(
+        r"(?i)^\s*(?:o\s+que\s+(?:é|e|são|sao|foi|significa)|quem\s+(?:é|e|foi|são|era)|qual\s+(?:é|foi)|what\s+(?:is|are|was|were)|who\s+(?:is|was|were)|qu[eé]\s+es|qui[eé]n\s+(?:es|fue)|defina|define|definição\s+de|significado\s+de|meaning\s+of)\s+",
+    )
+    .expect("valid subject regex");
+    let rest = prefix.find(query).map(|m| &query[m.end()..])?;
+    let article = Regex::new(r"(?i)^(?:o|a|os|as|um|uma|the|an|el|la|los|las)\s+").expect("valid article regex");
+    let subject = article.replace(rest.trim(), "");
+    let subject = subject.trim().trim_end_matches(['?', '!', '.']).trim();
+    (!subject.is_empty() && subject.split_whitespace().count() <= 6).then(|| subject.to_string())
+}
+
+fn http_client() -> Result<reqwest::Client, String> {
+    crate::web_scraper::client_builder(None)
+        .timeout(FETCH_TIMEOUT)
+        .user_agent(concat!("OllaHub/", env!("CARGO_PKG_VERSION"), " (https://github.com/evandrodevbr/OllaHub)"))
+        .build()
+        .map_err(|e| format!("Failed to create HTTP client: {}", e))
+}
+
+async fn get_json(client: &reqwest::Client, url: &str) -> Result<Value, String> {
+    let response = client
+        .get(url)
+        .send()
+        .await
+        .map_err(|e| format!("Failed to fetch {}: {}", url, e))?;
+    if !response.status().is_success() {
+        return Err(format!("Failed to fetch {}: HTTP {}", url, response.status()));
+    }
+    response.json().await.map_err(|e| format!("Failed to parse {}: {}", url, e))
+}
+
+async fn resolve_currency(client: &reqwest::Client, query: &str) -> Result<Option<Resolved>, String> {
+    let Some((amount, from, to)) = parse_currency_query(query) else {
+        return Ok(None);
+    };
+    let body = get_json(client, &format!("https://open.er-api.com/v6/latest/{}", from)).await?;
+    let Some(rate) = body["rates"][to].as_f64() else {
+        return Ok(None);
+    };
+    let updated = body["time_last_update_utc"].as_str().unwrap_or_default();
+    Ok(Some(Resolved {
+        resolver: "currency",
+        title: format!("Cotação {}/{}", from, to),
+        url: format!("https://www.exchangerate-api.com/?from={}&to={}", from, to),
+        snippet: format!(
+            "{:.2} {} = {:.2} {} (1 {} = {:.4} {}; atualizado em {})",
+            amount, from, amount * rate, to, from, rate, to, updated
+        ),
+        confidence: 0.95,
+    }))
+}
+
+async fn resolve_weather(client: &reqwest::Client, query: &str, language: &str) -> Result<Option<Resolved>, String> {
+    let Some(city) = parse_weather_query(query) else {
+        return Ok(None);
+    };
+    let places = get_json(client, &format!(
+        "https://geocoding-api.open-meteo.com/v1/search?name={}&count=1&language={}",
+        urlencoding::encode(&city),
+        language
+    ))
+    .await?;
+    let place = &places["results"][0];
+    let (Some(latitude), Some(longitude)) = (place["latitude"].as_f64(), place["longitude"].as_f64()) else {
+        return Ok(None);
+    };
+    let name = [place["name"].as_str(), place["admin1"].as_str(), place["country"].as_str()]
+        .into_iter()
+        .flatten()
+        .collect::<Vec<_>>()
+        .join(", ");
+
+    let forecast = get_json(client, &format!(
+        "https://api.open-meteo.com/v1/forecast?latitude={}&longitude={}&current=temperature_2m,relative_humidity_2m,wind_speed_10m&daily=temperature_2m_max,temperature_2m_min,precipitation_probability_max&timezone=auto&forecast_days=3",
+        latitude, longitude
+    ))
+    .await?;
+    let current = &forecast["current"];
+    let mut lines = vec![format!(
+        "Agora: {}°C, umidade {}%, vento {} km/h",
+        current["temperature_2m"], current["relative_humidity_2m"], current["wind_speed_10m"]
+    )];
+    let daily = &forecast["daily"];
+    for (index, day) in daily["time"].as_array().into_iter().flatten().enumerate() {
+        lines.push(format!(
+            "{}: mín {}°C, máx {}°C, chance de chuva {}%",
+            day.as_str().unwrap_or_default(),
+            daily["temperature_2m_min"][index],
+            daily["temperature_2m_max"][index],
+            daily["precipitation_probability_max"][index]
+        ));
+    }
+    Ok(Some(Resolved {
+        resolver: "weather",
+        title: format!("Previsão do tempo: {}", name),
+        url: format!("https://open-meteo.com/en/docs#latitude={}&longitude={}", latitude, longitude),
+        snippet: lines.join("\n"),
+        confidence: 0.95,
+    }))
+}
+
+async fn resolve_wikipedia(client: &reqwest::Client, subject: &str, language: &str) -> Result<Option<Resolved>, String> {
+    let search = get_json(client, &format!(
+        "https://{}.wikipedia.org/w/rest.php/v1/search/title?q={}&limit=1",
+        language,
+        urlencoding::encode(subject)
+    ))
+    .await?;
+    let Some(key) = search["pages"][0]["key"].as_str() else {
+        return Ok(None);
+    };
+    let summary = get_json(client, &format!(
+        "https://{}.wikipedia.org/api/rest_v1/page/summary/{}",
+        language,
+        urlencoding::encode(key)
+    ))
+    .await?;
+    let extract = summary["extract"].as_str().unwrap_or_default().trim();
+    if extract.is_empty() || summary["type"].as_str() == Some("disambiguation") {
+        return Ok(None);
+    }
+    let title = summary["title"].as_str().unwrap_or(key).to_string();
+    let exact = title.to_lowercase() == subject.to_lowercase();
+    Ok(Some(Resolved {
+        resolver: "wikipedia",
+        url: summary["content_urls"]["desktop"]["page"]
+            .as_str()
+            .map(str::to_string)
+            .unwrap_or_else(|| format!("https://{}.wikipedia.org/wiki/{}", language, key)),
+        title,
+        snippet: extract.to_string(),
+        confidence: if exact { 0.9 } else { 0.6 },
+    }))
+}
+
+async fn resolve_wikidata(client: &reqwest::Client, subject: &str, language: &str) -> Result<Option<Resolved>, String> {
+    let body = get_json(client, &format!(
+        "https://www.wikidata.org/w/api.php?action=wbsearchentities&search={}&language={}&uselang={}&format=json&limit=1",
+        urlencoding::encode(subject),
+        language,
+        language
+    ))
+    .await?;
+    let entity = &body["search"][0];
+    let (Some(id), Some(label)) = (entity["id"].as_str(), entity["label"].as_str()) else {
+        return Ok(None);
+    };
+    let Some(description) = entity["description"].as_str() else {
+        return Ok(None);
+    };
+    Ok(Some(Resolved {
+        resolver: "wikidata",
+        title: label.to_string(),
+        url: format!("https://www.wikidata.org/wiki/{}", id),
+        snippet: format!("{}: {}", label, description),
+        confidence: 0.5,
+    }))
+}
+
+/// Consulta os resolvers que se aplicam à pergunta (vazio para intenções não factuais)
+pub async fn resolve(query: &str) -> Vec<Resolved> {
+    if !matches!(IntentClassifier::classify(query), QueryIntent::Factual | QueryIntent::Calculation) {
+        return Vec::new();
+    }
+    let client = match http_client() {
+        Ok(client) => client,
+        Err(e) => {
+            log::warn!("[Resolvers] {}", e);
+            return Vec::new();
+        }
+    };
+    let language = crate::query_expansion::resolve_language(query);
+
+    let attempt = |name: &str, result: Result<Option<Resolved>, String>| match result {
+        Ok(found) => found,
+        Err(e) => {
+            log::warn!("[Resolvers:{}] {}", name, e);
+            None
+        }
+    };
+    if let Some(found) = attempt("currency", resolve_currency(&client, query).await) {
+        return vec![found];
+    }
+    if let Some(found) = attempt("weather", resolve_weather(&client, query, &language).await) {
+        return vec![found];
+    }
+    let Some(subject) = parse_subject(query) else {
+        return Vec::new();
+    };
+    match attempt("wikipedia", resolve_wikipedia(&client, &subject, &language).await) {
+        Some(found) => vec![found],
+        None => attempt("wikidata", resolve_wikidata(&client, &subject, &language).await)
+            .into_iter()
+            .collect(),
+    }
+}
+
+/// Alguma resposta dispensa os buscadores
+pub fn is_confident(resolved: &[Resolved]) -> bool {
+    resolved.iter().any(|r| r.confidence >= CONFIDENT)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_factual_queries() {
+        assert_eq!(parse_currency_query("Cotação do dólar hoje"), Some((1.0, "USD", "BRL")));
+        assert_eq!(parse_currency_query("converter 250,50 euros para reais"), Some((250.5, "EUR", "BRL")));
+        assert_eq!(parse_currency_query("100 BRL to USD"), Some((100.0, "BRL", "USD")));
+        assert_eq!(parse_currency_query("o que é um número real?"), None);
+
+        assert_eq!(parse_weather_query("Previsão do tempo em São Paulo amanhã?").as_deref(), Some("São Paulo"));
+        assert_eq!(parse_weather_query("weather in Lisbon today").as_deref(), Some("Lisbon"));
+        assert_eq!(parse_weather_query("clima em Belo Horizonte para hoje").as_deref(), Some("Belo Horizonte"));
+        assert_eq!(parse_weather_query("quem foi Santos Dumont"), None);
+
+        assert_eq!(parse_subject("O que é o Ollama?").as_deref(), Some("Ollama"));
+        assert_eq!(parse_subject("quem foi Santos Dumont").as_deref(), Some("Santos Dumont"));
+        assert_eq!(parse_subject("What is a large language model?").as_deref(), Some("large language model"));
+        assert_eq!(parse_subject("como instalar o ollama"), None);
+    }
+}
@@ -0,0 +1,211 @@
+use serde::{Deserialize, Serialize};
+use std::sync::{OnceLock, RwLock};
+use sysinfo::{CpuRefreshKind, System};
+
+const BYTES_PER_GB: u64 = 1024 * 1024 * 1024;
+
+const NORMAL_MONITOR_INTERVAL_MS: u64 = 2000;
+const LOW_MONITOR_INTERVAL_MS: u64 = 5000;
+const NORMAL_MAX_TABS: usize = 5;
+
+static PROFILE: OnceLock<RwLock<ResourceProfile>> = OnceLock::new();
+
+/// Como o modo de baixo consumo é decidido
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum LowResourceMode {
+    /// Decidido pelos limites de RAM/CPU
+    #[default]
+    Auto,
+    /// Forçado ligado pelo usuário
+    On,
+    /// Forçado desligado pelo usuário
+    Off,
+}
+
+/// Limites de hardware abaixo dos quais o modo `Auto` liga o baixo consumo
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub struct LowResourceThresholds {
+    /// RAM total mínima (GB)
+    #[serde(default = "default_min_memory_gb")]
+    pub min_memory_gb: u64,
+    /// CPUs lógicas mínimas
+    #[serde(default = "default_min_cpu_count")]
+    pub min_cpu_count: usize,
+}
+
+fn default_min_memory_gb() -> u64 {
+    8
+}
+
+fn default_min_cpu_count() -> usize {
+    4
+}
+
+impl Default for LowResourceThresholds {
+    fn default() -> Self {
+        Self {
+            min_memory_gb: default_min_memory_gb(),
+            min_cpu_count: default_min_cpu_count(),
+        }
+    }
+}
+
+impl LowResourceThresholds {
+    pub fn validate(&self, errors: &mut Vec<String>) {
+        if !(1..=256).contains(&self.min_memory_gb) {
+            errors.push("low_resource_thresholds.min_memory_gb deve estar entre 1 e 256".to_string());
+        }
+        if !(1..=64).contains(&self.min_cpu_count) {
+            errors.push("low_resource_thresholds.min_cpu_count deve estar entre 1 e 64".to_string());
+        }
+    }
+}
+
+/// Perfil de recursos efetivo, consultado por embeddings, scraping e monitor
+#[derive(Debug, Clone, Serialize)]
+pub struct ResourceProfile {
+    pub mode: LowResourceMode,
+    pub thresholds: LowResourceThresholds,
+    pub low_resource: bool,
+    /// Motivos detectados (mesmo quando o modo é forçado)
+    pub reasons: Vec<String>,
+    pub total_memory: u64,
+    pub cpu_count: usize,
+    pub embeddings_enabled: bool,
+    /// Quando false, o scraping usa apenas HTTP (sem headless Chrome)
+    pub headless_scraping_enabled: bool,
+    pub monitor_interval_ms: u64,
+    pub max_concurrent_tabs: usize,
+}
+
+impl ResourceProfile {
+    /// Monta o perfil a partir do modo e dos limites escolhidos e do hardware detectado
+    pub fn build(mode: LowResourceMode, thresholds: LowResourceThresholds, total_memory: u64, cpu_count: usize) -> Self {
+        let reasons = detect_low_resource_reasons(&thresholds, total_memory, cpu_count);
+        let low_resource = match mode {
+            LowResourceMode::Auto => !reasons.is_empty(),
+            LowResourceMode::On => true,
+            LowResourceMode::Off => false,
+        };
+
+        Self {
+            mode,
+            thresholds,
+            low_resource,
+            reasons,
+            total_memory,
+            cpu_count,
+            embeddings_enabled: !low_resource,
+            headless_scraping_enabled: !low_resource,
+            monitor_interval_ms: if low_resource { LOW_MONITOR_INTERVAL_MS } else { NORMAL_MONITOR_INTERVAL_MS },
+            max_concurrent_tabs: if low_resource { 1 } else { NORMAL_MAX_TABS },
+        }
+    }
+}
+
+/// Retorna os motivos pelos quais a máquina é considerada de poucos recursos
+pub fn detect_low_resource_reasons(thresholds: &LowResourceThresholds, total_memory: u64, cpu_count: usize) -> Vec<String> {
+    let mut reasons = Vec::new();
+
+    if total_memory > 0 && total_memory < thresholds.min_memory_gb * BYTES_PER_GB {
+        reasons.push(format!(
+            "RAM total de {:.1} GB abaixo do limite de {} GB",
+            total_memory as f64 / BYTES_PER_GB as f64,
+            thresholds.min_memory_gb
+        ));
+    }
+
+    if cpu_count > 0 && cpu_count < thresholds.min_cpu_count {
+        reasons.push(format!(
+            "{} CPUs lógicas abaixo do limite de {}",
+            cpu_count, thresholds.min_cpu_count
+        ));
+    }
+
+    reasons
+}
+
+/// Perfil atual (perfil normal se ainda não inicializado)
+pub fn current() -> ResourceProfile {
+    PROFILE
+        .get()
+        .and_then(|lock| lock.read().ok().map(|p| p.clone()))
+        .unwrap_or_else(|| ResourceProfile::build(LowResourceMode::Off, LowResourceThresholds::default(), 0, 0))
+}
+
+/// RAM total e CPUs lógicas (roda no setup: sem processos, discos nem GPUs do `get_system_specs`)
+fn detect_hardware() -> (u64, usize) {
+    let mut system = System::new();
+    system.refresh_memory();
+    system.refresh_cpu_list(CpuRefreshKind::nothing());
+    (system.total_memory(), system.cpus().len())
+}
+
+fn store(profile: ResourceProfile) {
+    let lock = PROFILE.get_or_init(|| RwLock::new(profile.clone()));
+    if let Ok(mut guard) = lock.write() {
+        *guard = profile;
+    }
+}
+
+/// Detecta o hardware e aplica o modo e os limites das configurações (chamado no setup)
+pub fn init(mode: LowResourceMode, thresholds: LowResourceThresholds) -> ResourceProfile {
+    let (total_memory, cpu_count) = detect_hardware();
+    let profile = ResourceProfile::build(mode, thresholds, total_memory, cpu_count);
+
+    if profile.low_resource {
+        log::info!("[ResourceProfile] Modo de baixo consumo ativo ({:?}): {:?}", mode, profile.reasons);
+    }
+
+    store(profile.clone());
+    profile
+}
+
+/// Reaplica o perfil com um novo modo ou novos limites (ex: após `settings-changed`)
+pub fn apply_mode(mode: LowResourceMode, thresholds: LowResourceThresholds) -> ResourceProfile {
+    let previous = current();
+    if previous.mode == mode && previous.thresholds == thresholds && previous.total_memory > 0 {
+        return previous;
+    }
+
+    let (total_memory, cpu_count) = if previous.total_memory > 0 {
+        (previous.total_memory, previous.cpu_count)
+    } else {
+        detect_hardware()
+    };
+    let profile = ResourceProfile::build(mode, thresholds, total_memory, cpu_count);
+    store(profile.clone());
+
+    log::info!("[ResourceProfile] Modo alterado para {:?} (low_resource={})", mode, profile.low_resource);
+    profile
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const GB: u64 = 1024 * 1024 * 1024;
+
+    #[test]
+    fn test_auto_mode_thresholds() {
+        let defaults = LowResourceThresholds::default();
+        assert!(ResourceProfile::build(LowResourceMode::Auto, defaults, 4 * GB, 8).low_resource);
+        assert!(ResourceProfile::build(LowResourceMode::Auto, defaults, 16 * GB, 2).low_resource);
+
+        let normal = ResourceProfile::build(LowResourceMode::Auto, defaults, 16 * GB, 8);
+        assert!(!normal.low_resource);
+        assert!(normal.embeddings_enabled);
+
+        // Limites configurados
+        let strict = LowResourceThresholds { min_memory_gb: 32, min_cpu_count: 4 };
+        let profile = ResourceProfile::build(LowResourceMode::Auto, strict, 16 * GB, 8);
+        assert!(profile.low_resource);
+        assert_eq!(profile.reasons, vec!["RAM total de 16.0 GB abaixo do limite de 32 GB"]);
+
+        let forced = ResourceProfile::build(LowResourceMode::On, defaults, 32 * GB, 16);
+        assert!(forced.low_resource);
+        assert!(!forced.headless_scraping_enabled);
+        assert_eq!(forced.max_concurrent_tabs, 1);
+    }
+}
@@ -0,0 +1,263 @@
+//! Política de retenção das sessões (`retention` nas configurações)
+//!
+//! Com a política ativa, uma verificação periódica do scheduler grava as sessões fora dela
+//! em um ZIP em `retention/` (mesmo formato do arquivo frio, com o JSON legado) e só depois
+//! as remove do SQLite e de `chats/`. Sessões marcadas como template ficam de fora.
+//! `preview_retention_cleanup` mostra o que seria removido.
+
+use crate::cold_storage::{self, ArchivedPayload};
+use crate::db::{ChatSession, Database};
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::fs;
+use std::path::PathBuf;
+use tauri::{AppHandle, Manager};
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum RetentionMode {
+    /// Nada é removido
+    #[default]
+    Forever,
+    /// Remove sessões sem atividade há mais de `max_age_days`
+    Days,
+    /// Mantém só as `max_sessions` com atividade mais recente
+    Sessions,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct RetentionSettings {
+    #[serde(default)]
+    pub mode: RetentionMode,
+    #[serde(default = "default_max_age_days")]
+    pub max_age_days: u32,
+    #[serde(default = "default_max_sessions")]
+    pub max_sessions: u32,
+}
+
+fn default_max_age_days() -> u32 {
+    365
+}
+
+fn default_max_sessions() -> u32 {
+    500
+}
+
+impl Default for RetentionSettings {
+    fn default() -> Self {
+        Self {
+            mode: RetentionMode::default(),
+            max_age_days: default_max_age_days(),
+            max_sessions: default_max_sessions(),
+        }
+    }
+}
+
+impl RetentionSettings {
+    pub fn validate(&self, errors: &mut Vec<String>) {
+        if !(1..=3650).contains(&self.max_age_days) {
+            errors.push("retention.max_age_days deve estar entre 1 e 3650".to_string());
+        }
+        if !(1..=100_000).contains(&self.max_sessions) {
+            errors.push("retention.max_sessions deve estar entre 1 e 100000".to_string());
+        }
+    }
+}
+
+/// Sessão que a política removeria
+#[derive(Serialize, Clone, Debug)]
+pub struct RetentionCandidate {
+    pub session_id: String,
+    pub title: String,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Resposta de `preview_retention_cleanup`
+#[derive(Serialize, Clone, Debug)]
+pub struct RetentionPreview {
+    pub settings: RetentionSettings,
+    pub total_sessions: usize,
+    /// Da atividade mais antiga para a mais recente
+    pub sessions: Vec<RetentionCandidate>,
+}
+
+/// Resultado de uma limpeza
+#[derive(Serialize, Clone, Debug, Default)]
+pub struct RetentionReport {
+    pub deleted: usize,
+    /// ZIP com as sessões removidas (None se nada foi removido)
+    pub archive_file: Option<String>,
+    pub errors: Vec<String>,
+}
+
+/// Sessões fora da política, da atividade mais antiga para a mais recente
+pub fn expired(mut sessions: Vec<ChatSession>, settings: &RetentionSettings, now: DateTime<Utc>) -> Vec<ChatSession> {
+    sessions.sort_by(|a, b| b.updated_at.cmp(&a.updated_at));
+    let mut expired: Vec<ChatSession> = match settings.mode {
+        RetentionMode::Forever => Vec::new(),
+        RetentionMode::Days => {
+            let cutoff = now - Duration::days(settings.max_age_days as i64);
+            sessions.into_iter().filter(|s| s.updated_at < cutoff).collect()
+        }
+        RetentionMode::Sessions => sessions.into_iter().skip(settings.max_sessions as usize).collect(),
+    };
+    expired.reverse();
+    expired
+}
+
+/// Sessões sujeitas à política (templates nunca são removidos)
+fn retainable_sessions(db: &Database) -> rusqlite::Result<Vec<ChatSession>> {
+    let organization = db.get_sessions_organization()?;
+    Ok(db
+        .list_sessions()?
+        .into_iter()
+        .filter(|s| !organization.get(&s.id).is_some_and(|org| org.template))
+        .collect())
+}
+
+pub fn retention_dir(app_handle: &AppHandle) -> Result<PathBuf, String> {
+    let dir = app_handle
+        .path()
+        .app_data_dir()
+        .map_err(|e| format!("Failed to get app data dir: {}", e))?
+        .join("retention");
+    fs::create_dir_all(&dir).map_err(|e| format!("Failed to create retention dir: {}", e))?;
+    Ok(dir)
+}
+
+/// O que a política (a informada ou a configurada) removeria agora
+pub fn preview(app_handle: &AppHandle, settings: RetentionSettings) -> Result<RetentionPreview, String> {
+    let sessions = Database::new(app_handle)
+        .and_then(|db| retainable_sessions(&db))
+        .map_err(|e| format!("Failed to list sessions: {}", e))?;
+    let total_sessions = sessions.len();
+    let candidates = expired(sessions, &settings, Utc::now())
+        .into_iter()
+        .map(|s| RetentionCandidate {
+            session_id: s.id,
+            title: s.title,
+            created_at: s.created_at,
+            updated_at: s.updated_at,
+        })
+        .collect();
+    Ok(RetentionPreview { settings, total_sessions, sessions: candidates })
+}
+
+/// Arquiva em ZIP e remove as sessões fora da política
+pub fn run(app_handle: &AppHandle, settings: &RetentionSettings, actor: &str) -> Result<RetentionReport, String> {
+    let db = Database::new(app_handle).map_err(|e| format!("Failed to open database: {}", e))?;
+    let sessions = retainable_sessions(&db).map_err(|e| format!("Failed to list sessions: {}", e))?;
+    let now = Utc::now();
+    let candidates = expired(sessions, settings, now);
+    let mut report = RetentionReport::default();
+    if candidates.is_empty() {
+        return Ok(report);
+    }
+
+    let chats_dir = crate::get_chats_dir(app_handle)?;
+    let mut archived = Vec::new();
+    let mut additions = Vec::new();
+    for session in candidates {
+        let snapshot = match db.snapshot_session(&session.id) {
+            Ok(Some(snapshot)) => snapshot,
+            Ok(None) => continue,
+            Err(e) => {
+                report.errors.push(format!("{}: {}", session.id, e));
+                continue;
+            }
+        };
+        let payload = ArchivedPayload {
+            archived_at: now,
+            snapshot,
+            legacy_json: fs::read_to_string(chats_dir.join(format!("{}.json", session.id))).ok(),
+        };
+        match serde_json::to_vec(&payload) {
+            Ok(bytes) => {
+                additions.push((cold_storage::entry_name(&session.id), bytes));
+                archived.push(session.id);
+            }
+            Err(e) => report.errors.push(format!("{}: {}", session.id, e)),
+        }
+    }
+    if archived.is_empty() {
+        return Ok(report);
+    }
+
+    // O ZIP precisa estar gravado antes de qualquer remoção
+    let archive_path = retention_dir(app_handle)?.join(format!("retention-{}.zip", now.format("%Y%m%d-%H%M%S")));
+    cold_storage::update_archive(&archive_path, &HashSet::new(), &additions)?;
+
+    for session_id in &archived {
+        if let Err(e) = db.delete_session(session_id) {
+            report.errors.push(format!("{}: {}", session_id, e));
+            continue;
+        }
+        let json_path = chats_dir.join(format!("{}.json", session_id));
+        if json_path.exists() {
+            if let Err(e) = fs::remove_file(&json_path) {
+                log::warn!("[Retention] Falha ao remover JSON da sessão {}: {}", session_id, e);
+            }
+        }
+        report.deleted += 1;
+    }
+
+    let file_name = archive_path.file_name().map(|n| n.to_string_lossy().to_string());
+    let detail = format!("{} sessões removidas, arquivadas em {}", report.deleted, file_name.as_deref().unwrap_or_default());
+    if let Err(e) = db.append_audit_log("retention_cleanup", actor, &detail) {
+        log::warn!("[Retention] Falha ao registrar limpeza no log de auditoria: {}", e);
+    }
+    log::info!("[Retention] {}", detail);
+    report.archive_file = Some(archive_path.to_string_lossy().to_string());
+    Ok(report)
+}
+
+/// Verificação periódica do scheduler (não faz nada com a política "forever")
+pub fn maybe_run(app_handle: &AppHandle) {
+    let settings = crate::settings::current(app_handle).retention;
+    if settings.mode == RetentionMode::Forever {
+        return;
+    }
+    match run(app_handle, &settings, "scheduler") {
+        Ok(report) => {
+            for error in &report.errors {
+                log::warn!("[Retention] {}", error);
+            }
+        }
+        Err(e) => log::warn!("[Retention] Limpeza falhou: {}", e),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn session(id: &str, days_ago: i64, now: DateTime<Utc>) -> ChatSession {
+        ChatSession {
+            id: id.to_string(),
+            title: id.to_string(),
+            emoji: String::new(),
+            created_at: now - Duration::days(days_ago),
+            updated_at: now - Duration::days(days_ago),
+        }
+    }
+
+    #[test]
+    fn test_expired_sessions() {
+        let now = Utc::now();
+        let sessions = vec![session("a", 10, now), session("b", 400, now), session("c", 1, now), session("d", 30, now)];
+        let ids = |list: Vec<ChatSession>| list.into_iter().map(|s| s.id).collect::<Vec<_>>();
+
+        let mut settings = RetentionSettings::default();
+        assert!(expired(sessions.clone(), &settings, now).is_empty());
+
+        settings.mode = RetentionMode::Days;
+        settings.max_age_days = 20;
+        assert_eq!(ids(expired(sessions.clone(), &settings, now)), ["b", "d"]);
+
+        settings.mode = RetentionMode::Sessions;
+        settings.max_sessions = 1;
+        assert_eq!(ids(expired(sessions, &settings, now)), ["b", "d", "a"]);
+    }
+}
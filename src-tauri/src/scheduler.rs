@@ -3,6 +3,7 @@ use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::fs;
 use std::path::PathBuf;
+use std::str::FromStr;
 use std::sync::Arc;
 use tokio::sync::Mutex;
 use tauri::{AppHandle, Manager};
@@ -26,6 +27,139 @@ pub enum TaskAction {
         prompt: String,
         model: String,
     },
+    /// Relatório periódico: pesquisa o assunto nas categorias de fontes configuradas, resume os
+    /// melhores resultados com o modelo e acrescenta o relatório à sessão "Relatórios"
+    ResearchReport {
+        query: String,
+        model: String,
+        /// IDs das categorias de `SourcesConfig` (vazio: todas as ativas)
+        #[serde(default)]
+        categories: Vec<String>,
+        #[serde(default = "default_report_results")]
+        max_results: usize,
+        /// Prompt com `{query}`, `{date}` e `{sources}` (ausente: prompt padrão de relatório)
+        #[serde(default)]
+        prompt_template: Option<String>,
+    },
+    /// Geração só com o modelo, sem busca (diário, resumo de standup...). O template aceita
+    /// `{{date}}`, `{{time}}`, `{{weekday}}`, `{{label}}`, `{{last_result}}` (saída da última
+    /// execução da task), `{{previous_output}}` e `{{source_excerpts}}` (saída e trechos das
+    /// fontes da ação anterior do pipeline)
+    GeneratePrompt {
+        model: String,
+        prompt_template: String,
+        /// Acumula prompt e resposta na sessão da task (desligado: a saída só segue para as
+        /// post_actions)
+        #[serde(default = "default_save_to_session")]
+        save_to_session: bool,
+    },
+    /// Gravar a saída da ação anterior em arquivo (ex: relatório Markdown numa pasta do Obsidian)
+    ///
+    /// `path_template` aceita `{date}`, `{time}`, `{datetime}`, `{year}`, `{month}`, `{day}`,
+    /// `{label}` e `{date:<formato chrono>}`. Caminhos relativos são resolvidos em `app_data_dir/reports`.
+    WriteFile {
+        path_template: String,
+        #[serde(default)]
+        format: OutputFormat,
+    },
+    /// Copiar a saída da ação anterior para a área de transferência
+    CopyToClipboard,
+}
+
+impl TaskAction {
+    /// Indica se a ação apenas consome a saída de uma ação anterior
+    pub fn is_output_action(&self) -> bool {
+        matches!(self, TaskAction::WriteFile { .. } | TaskAction::CopyToClipboard)
+    }
+}
+
+fn default_report_results() -> usize {
+    8
+}
+
+fn default_save_to_session() -> bool {
+    true
+}
+
+/// Caracteres da saída guardados em `last_result`
+const MAX_LAST_RESULT_CHARS: usize = 8000;
+
+/// O que fazer com um horário que passou com o app fechado (ou o computador dormindo)
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum MissedRunPolicy {
+    /// Ignora o horário perdido e espera o próximo
+    Skip,
+    /// Executa a task uma vez ao iniciar, por mais horários que tenham sido perdidos
+    #[default]
+    RunOnceOnStart,
+}
+
+/// Configurações do scheduler de tasks
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct SchedulerSettings {
+    /// Política padrão para horários perdidos (a task pode sobrescrever)
+    #[serde(default)]
+    pub missed_runs: MissedRunPolicy,
+    /// Tasks executando ao mesmo tempo; as demais esperam uma vaga (ex: ao acordar do sono
+    /// com vários horários vencidos)
+    #[serde(default = "default_max_concurrent_tasks")]
+    pub max_concurrent_tasks: usize,
+}
+
+fn default_max_concurrent_tasks() -> usize {
+    2
+}
+
+impl Default for SchedulerSettings {
+    fn default() -> Self {
+        Self {
+            missed_runs: MissedRunPolicy::default(),
+            max_concurrent_tasks: default_max_concurrent_tasks(),
+        }
+    }
+}
+
+impl SchedulerSettings {
+    pub fn validate(&self, errors: &mut Vec<String>) {
+        if !(1..=8).contains(&self.max_concurrent_tasks) {
+            errors.push("scheduler.max_concurrent_tasks deve estar entre 1 e 8".to_string());
+        }
+    }
+}
+
+/// Primeiro horário da agenda perdido desde a última tentativa, com sucesso ou não (ou desde
+/// a criação da task), se algum já passou
+pub fn missed_run(
+    cron_schedule: &str,
+    last_attempt: Option<DateTime<Utc>>,
+    created_at: DateTime<Utc>,
+    now: DateTime<Utc>,
+) -> Option<DateTime<Utc>> {
+    let schedule = cron::Schedule::from_str(cron_schedule).ok()?;
+    let reference = last_attempt.unwrap_or(created_at);
+    schedule.after(&reference).next().filter(|next| *next <= now)
+}
+
+/// Formato de saída para ações que gravam o resultado de uma task
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum OutputFormat {
+    #[default]
+    Markdown,
+    Text,
+    Json,
+}
+
+impl OutputFormat {
+    /// Extensão padrão do arquivo para o formato
+    pub fn extension(&self) -> &'static str {
+        match self {
+            OutputFormat::Markdown => "md",
+            OutputFormat::Text => "txt",
+            OutputFormat::Json => "json",
+        }
+    }
 }
 
 /// Estrutura de uma Task agendada
@@ -35,8 +169,18 @@ pub struct SentinelTask {
     pub label: String,
     pub cron_schedule: String, // Ex: "0 8 * * *" (Todo dia às 8h)
     pub action: TaskAction,
+    /// Ações executadas em sequência após `action`, recebendo a saída dela
+    /// (ex: `WriteFile` e `CopyToClipboard`)
+    #[serde(default)]
+    pub post_actions: Vec<TaskAction>,
     pub enabled: bool,
     pub last_run: Option<DateTime<Utc>>,
+    /// Saída da última execução bem-sucedida (`{{last_result}}` de `GeneratePrompt`)
+    #[serde(default)]
+    pub last_result: Option<String>,
+    /// Política para horários perdidos desta task (None: `scheduler.missed_runs`)
+    #[serde(default)]
+    pub missed_run_policy: Option<MissedRunPolicy>,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
@@ -50,6 +194,8 @@ pub struct SchedulerService {
     tasks_file: PathBuf,
     #[allow(dead_code)]
     app_handle: Option<AppHandle>, // Mantido para uso futuro (notificações, etc)
+    /// Quando pausado, os jobs continuam agendados mas não executam
+    paused: bool,
 }
 
 impl SchedulerService {
@@ -92,6 +238,7 @@ impl SchedulerService {
             tasks,
             tasks_file,
             app_handle: Some(app_handle),
+            paused: false,
         })
     }
     
@@ -136,15 +283,29 @@ impl SchedulerService {
         self.tasks.get(id)
     }
     
-    /// Atualiza última execução de uma task
-    pub fn update_last_run(&mut self, id: &str, timestamp: DateTime<Utc>) -> Result<(), String> {
+    /// Atualiza última execução de uma task (e a saída dela, quando houver)
+    pub fn update_last_run(&mut self, id: &str, timestamp: DateTime<Utc>, last_result: Option<String>) -> Result<(), String> {
         if let Some(task) = self.tasks.get_mut(id) {
             task.last_run = Some(timestamp);
+            if let Some(result) = last_result {
+                task.last_result = Some(result.chars().take(MAX_LAST_RESULT_CHARS).collect());
+            }
             self.save_tasks()?;
         }
         Ok(())
     }
     
+    /// Pausa ou retoma a execução de todas as tasks
+    pub fn set_paused(&mut self, paused: bool) {
+        self.paused = paused;
+        log::info!("Scheduler {}", if paused { "pausado" } else { "retomado" });
+    }
+    
+    /// Indica se o scheduler está pausado
+    pub fn is_paused(&self) -> bool {
+        self.paused
+    }
+    
     /// Obtém tasks habilitadas
     pub fn get_enabled_tasks(&self) -> Vec<&SentinelTask> {
         self.tasks.values().filter(|t| t.enabled).collect()
@@ -162,3 +323,37 @@ pub fn get_tasks_file_path(app_handle: &AppHandle) -> Result<PathBuf, String> {
     Ok(app_data_dir.join("tasks.json"))
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn test_missed_run() {
+        let daily = "0 0 8 * * *";
+        let created_at = Utc.with_ymd_and_hms(2026, 10, 1, 12, 0, 0).unwrap();
+        let last_run = Some(Utc.with_ymd_and_hms(2026, 10, 2, 8, 0, 5).unwrap());
+
+        // Ainda não chegou o próximo horário
+        let now = Utc.with_ymd_and_hms(2026, 10, 3, 7, 59, 0).unwrap();
+        assert_eq!(missed_run(daily, last_run, created_at, now), None);
+
+        // App fechado por dois dias: devolve o primeiro horário perdido
+        let now = Utc.with_ymd_and_hms(2026, 10, 5, 9, 0, 0).unwrap();
+        assert_eq!(
+            missed_run(daily, last_run, created_at, now),
+            Some(Utc.with_ymd_and_hms(2026, 10, 3, 8, 0, 0).unwrap())
+        );
+
+        // Nunca executada: conta a partir da criação
+        assert_eq!(
+            missed_run(daily, None, created_at, now),
+            Some(Utc.with_ymd_and_hms(2026, 10, 2, 8, 0, 0).unwrap())
+        );
+        assert_eq!(missed_run("cron inválido", None, created_at, now), None);
+
+        // Tentativa que falhou no último horário conta como execução
+        let failed_attempt = Some(Utc.with_ymd_and_hms(2026, 10, 5, 8, 0, 0).unwrap());
+        assert_eq!(missed_run(daily, last_run.max(failed_attempt), created_at, now), None);
+    }
+}
@@ -1,9 +1,284 @@
-use crate::scheduler::SchedulerState;
+use crate::scheduler::{missed_run, MissedRunPolicy, SchedulerState, SentinelTask, TaskAction};
 use crate::task_executor::execute_task;
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
 use tokio_cron_scheduler::{Job, JobScheduler};
-use std::sync::Arc;
+use std::sync::{Arc, Mutex, OnceLock};
 use tauri::AppHandle;
 use chrono::Utc;
+use sysinfo::System;
+
+/// Frequência com que o scheduler verifica se deve rodar a manutenção automática do banco
+const DB_MAINTENANCE_CHECK_CRON: &str = "0 */30 * * * *";
+/// Frequência da verificação do horário do resumo diário (a agenda fica nas configurações)
+const DIGEST_CHECK_CRON: &str = "0 * * * * *";
+/// Frequência da limpeza de arquivos de log fora da política de retenção
+const LOG_RETENTION_CRON: &str = "0 15 * * * *";
+/// Frequência da limpeza de sessões fora da política de retenção
+const SESSION_RETENTION_CRON: &str = "0 45 * * * *";
+/// Frequência da sincronização pela pasta compartilhada (quando ativa)
+const FOLDER_SYNC_CRON: &str = "0 */5 * * * *";
+/// Intervalo mínimo entre manutenções automáticas
+const DB_MAINTENANCE_INTERVAL_HOURS: i64 = 24;
+/// Uso de CPU abaixo do qual o sistema é considerado ocioso
+const IDLE_CPU_THRESHOLD: f32 = 25.0;
+/// Tentativas extras de uma task que falhou por falta de conexão
+const MAX_CONNECTIVITY_RETRIES: u32 = 3;
+
+/// Task aguardando a volta da rede para ser executada novamente
+#[derive(Debug, Clone)]
+struct PendingRetry {
+    task_id: String,
+    attempt: u32,
+    ollama_url: Option<String>,
+}
+
+/// Fila de tasks que falharam por falta de conexão
+static RETRY_QUEUE: OnceLock<Mutex<Vec<PendingRetry>>> = OnceLock::new();
+
+fn retry_queue() -> &'static Mutex<Vec<PendingRetry>> {
+    RETRY_QUEUE.get_or_init(|| Mutex::new(Vec::new()))
+}
+
+/// Vagas para tasks simultâneas (`scheduler.max_concurrent_tasks`). Quando o limite muda, um
+/// semáforo novo passa a valer; as tasks em andamento terminam com as vagas do anterior.
+static TASK_SLOTS: Mutex<Option<(usize, Arc<Semaphore>)>> = Mutex::new(None);
+
+fn task_slots(limit: usize) -> Arc<Semaphore> {
+    let mut slots = TASK_SLOTS.lock().unwrap_or_else(|e| e.into_inner());
+    match slots.as_ref() {
+        Some((current, semaphore)) if *current == limit => semaphore.clone(),
+        _ => {
+            let semaphore = Arc::new(Semaphore::new(limit));
+            *slots = Some((limit, semaphore.clone()));
+            semaphore
+        }
+    }
+}
+
+/// Espera uma vaga para executar a task (a vaga é liberada quando o permit é solto)
+async fn acquire_task_slot(app_handle: &AppHandle, task_id: &str) -> Option<OwnedSemaphorePermit> {
+    let limit = crate::settings::current(app_handle).scheduler.max_concurrent_tasks.max(1);
+    let semaphore = task_slots(limit);
+    if semaphore.available_permits() == 0 {
+        log::info!("Task {} aguardando vaga ({} task(s) em execução)", task_id, limit);
+    }
+    semaphore.acquire_owned().await.ok()
+}
+
+/// Tasks que dependem de acesso à internet (pesquisa e scraping)
+fn needs_network(task: &SentinelTask) -> bool {
+    matches!(task.action, TaskAction::SearchAndSummarize { .. } | TaskAction::ResearchReport { .. })
+}
+
+/// Coloca a task na fila de retentativa (substitui uma entrada anterior da mesma task)
+fn enqueue_retry(task_id: &str, attempt: u32, ollama_url: Option<String>) {
+    if attempt > MAX_CONNECTIVITY_RETRIES {
+        log::warn!("[RetryQueue] Task {} excedeu {} tentativas, aguardando próximo horário", task_id, MAX_CONNECTIVITY_RETRIES);
+        return;
+    }
+    if let Ok(mut queue) = retry_queue().lock() {
+        queue.retain(|p| p.task_id != task_id);
+        queue.push(PendingRetry {
+            task_id: task_id.to_string(),
+            attempt,
+            ollama_url,
+        });
+        log::info!("[RetryQueue] Task {} aguardando conexão (tentativa {})", task_id, attempt);
+    }
+}
+
+/// Remove a task da fila (ela vai rodar agora, pelo horário normal ou por retentativa)
+fn dequeue_retry(task_id: &str) {
+    if let Ok(mut queue) = retry_queue().lock() {
+        queue.retain(|p| p.task_id != task_id);
+    }
+}
+
+/// Executa as tasks pendentes quando o watcher de conectividade informa que a rede voltou
+async fn retry_pending_tasks(app_handle: AppHandle, scheduler: SchedulerState) {
+    let pending: Vec<PendingRetry> = match retry_queue().lock() {
+        Ok(mut queue) => queue.drain(..).collect(),
+        Err(_) => return,
+    };
+    if pending.is_empty() {
+        return;
+    }
+    
+    log::info!("[RetryQueue] Rede restabelecida, reexecutando {} task(s)", pending.len());
+    for retry in pending {
+        run_scheduled_task(app_handle.clone(), scheduler.clone(), retry.task_id, retry.ollama_url, retry.attempt).await;
+    }
+}
+
+/// Executa a task agora, fora do horário (mesmas regras de pausa e retentativa)
+pub async fn run_task_now(app_handle: AppHandle, scheduler: SchedulerState, task_id: String) {
+    let ollama_url = Some(crate::settings::current(&app_handle).ollama_url);
+    run_scheduled_task(app_handle, scheduler, task_id, ollama_url, 0).await;
+}
+
+/// Executa uma task agendada; falhas por falta de conexão entram na fila de retentativa
+async fn run_scheduled_task(
+    app_handle: AppHandle,
+    scheduler: SchedulerState,
+    task_id: String,
+    ollama_url: Option<String>,
+    attempt: u32,
+) {
+    dequeue_retry(&task_id);
+    
+    // Obter task atualizada (ignorar se o scheduler estiver pausado)
+    let task_opt = {
+        let sched = scheduler.lock().await;
+        if sched.is_paused() {
+            log::info!("Scheduler pausado, pulando task {}", task_id);
+            return;
+        }
+        sched.get_task(&task_id).cloned()
+    };
+    
+    let task = match task_opt {
+        Some(task) => task,
+        None => {
+            log::warn!("Task {} não encontrada", task_id);
+            return;
+        }
+    };
+    if !task.enabled {
+        log::info!("Task {} está desabilitada, pulando", task_id);
+        return;
+    }
+    
+    // Rede sabidamente fora: nem tenta, espera o watcher avisar que a conexão voltou
+    if needs_network(&task) && !crate::connectivity::is_online() {
+        enqueue_retry(&task_id, attempt + 1, ollama_url);
+        return;
+    }
+    
+    // Limitar tasks simultâneas; a pausa pode ter chegado enquanto a task esperava a vaga
+    let _slot = acquire_task_slot(&app_handle, &task_id).await;
+    if scheduler.lock().await.is_paused() {
+        log::info!("Scheduler pausado, pulando task {}", task_id);
+        return;
+    }
+    
+    // Executar task (o browser, se necessário, vem do pool durante o scraping)
+    let started_at = Utc::now();
+    let started = std::time::Instant::now();
+    let result = execute_task(&task, app_handle.clone(), ollama_url.clone()).await;
+    let recorded = crate::db::Database::new(&app_handle).and_then(|db| {
+        db.record_task_run(&task_id, result.as_ref().err().map(String::as_str), started.elapsed().as_millis() as u64, started_at)
+    });
+    if let Err(e) = recorded {
+        log::warn!("Falha ao registrar execução da task {}: {}", task_id, e);
+    }
+    match result {
+        Ok(output) => {
+            // Atualizar last_run e a saída usada por `{{last_result}}`
+            let mut sched = scheduler.lock().await;
+            let _ = sched.update_last_run(&task_id, Utc::now(), output.map(|o| o.content));
+            log::info!("Task {} executada com sucesso", task_id);
+        }
+        Err(e) => {
+            log::error!("Erro ao executar task {}: {}", task_id, e);
+            // Falha por falta de conexão: o watcher reexecuta a task quando a rede voltar
+            if needs_network(&task) && !crate::connectivity::check_online().await {
+                crate::connectivity::mark_offline();
+                enqueue_retry(&task_id, attempt + 1, ollama_url);
+            }
+        }
+    }
+}
+
+/// Trata os horários que passaram com o app fechado, conforme a política de cada task
+async fn catch_up_missed_runs(app_handle: AppHandle, scheduler: SchedulerState, ollama_url: Option<String>) {
+    let default_policy = crate::settings::current(&app_handle).scheduler.missed_runs;
+    // `last_run` só muda no sucesso: uma task que falhou no último horário não roda de novo
+    let attempts = crate::db::Database::new(&app_handle)
+        .and_then(|db| db.last_task_attempts())
+        .unwrap_or_else(|e| {
+            log::warn!("Falha ao carregar as últimas execuções das tasks: {}", e);
+            Default::default()
+        });
+    let now = Utc::now();
+    let missed: Vec<(String, String, MissedRunPolicy, chrono::DateTime<Utc>)> = {
+        let sched = scheduler.lock().await;
+        sched
+            .get_enabled_tasks()
+            .into_iter()
+            .filter_map(|task| {
+                let last_attempt = task.last_run.max(attempts.get(&task.id).copied());
+                let missed_at = missed_run(&task.cron_schedule, last_attempt, task.created_at, now)?;
+                let policy = task.missed_run_policy.unwrap_or(default_policy);
+                Some((task.id.clone(), task.label.clone(), policy, missed_at))
+            })
+            .collect()
+    };
+    
+    let mut runs = Vec::new();
+    for (task_id, label, policy, missed_at) in missed {
+        match policy {
+            MissedRunPolicy::Skip => {
+                log::info!("Task '{}' perdeu o horário de {}, aguardando o próximo", label, missed_at);
+            }
+            MissedRunPolicy::RunOnceOnStart => {
+                log::info!("Task '{}' perdeu o horário de {}, executando agora", label, missed_at);
+                runs.push(tokio::spawn(run_scheduled_task(
+                    app_handle.clone(),
+                    scheduler.clone(),
+                    task_id,
+                    ollama_url.clone(),
+                    0,
+                )));
+            }
+        }
+    }
+    for run in runs {
+        if let Err(e) = run.await {
+            log::warn!("Execução atrasada de task falhou: {}", e);
+        }
+    }
+}
+
+/// Verifica se o uso de CPU está baixo o suficiente para tarefas pesadas
+fn system_is_idle() -> bool {
+    let mut sys = System::new();
+    sys.refresh_cpu_usage();
+    std::thread::sleep(sysinfo::MINIMUM_CPU_UPDATE_INTERVAL);
+    sys.refresh_cpu_usage();
+    sys.global_cpu_usage() < IDLE_CPU_THRESHOLD
+}
+
+/// Roda a manutenção do banco se estiver habilitada, vencida e o sistema ocioso
+async fn maybe_run_db_maintenance(app_handle: &AppHandle, scheduler: &SchedulerState) {
+    if !crate::settings::current(app_handle).auto_db_maintenance {
+        return;
+    }
+    if scheduler.lock().await.is_paused() {
+        return;
+    }
+    
+    let app_handle = app_handle.clone();
+    let result = tokio::task::spawn_blocking(move || {
+        let last_run = crate::db::Database::new(&app_handle)
+            .and_then(|db| db.last_audit_log_time("db_maintenance"))
+            .map_err(|e| format!("Failed to read last maintenance: {}", e))?;
+        
+        let due = last_run
+            .map(|t| Utc::now() - t >= chrono::Duration::hours(DB_MAINTENANCE_INTERVAL_HOURS))
+            .unwrap_or(true);
+        if !due || !system_is_idle() {
+            return Ok(());
+        }
+        
+        crate::perform_db_maintenance(&app_handle, crate::db::MaintenanceMode::Full, "scheduler").map(|_| ())
+    }).await;
+    
+    match result {
+        Ok(Err(e)) => log::warn!("[DbMaintenance] Manutenção automática falhou: {}", e),
+        Err(e) => log::warn!("[DbMaintenance] Tarefa de manutenção falhou: {}", e),
+        Ok(Ok(())) => {}
+    }
+}
 
 /// Inicia o loop do scheduler
 pub async fn start_scheduler_loop(
@@ -24,6 +299,12 @@ pub async fn start_scheduler_loop(
         ollama_url.clone(),
     ).await?;
     
+    // Reexecutar tasks que falharam por falta de conexão assim que a rede voltar
+    let scheduler_for_retry = scheduler_state.clone();
+    crate::connectivity::start_watcher(app_handle.clone(), move |app_handle| {
+        retry_pending_tasks(app_handle, scheduler_for_retry.clone())
+    });
+    
     // Iniciar scheduler em background
     tokio::spawn(async move {
         if let Err(e) = sched.start().await {
@@ -31,6 +312,9 @@ pub async fn start_scheduler_loop(
         }
     });
     
+    // Horários perdidos com o app fechado (as execuções respeitam o limite de tasks simultâneas)
+    tokio::spawn(catch_up_missed_runs(app_handle, scheduler_state, ollama_url));
+    
     log::info!("Scheduler loop iniciado");
     Ok(())
 }
@@ -72,46 +356,7 @@ pub async fn reload_scheduled_tasks(
             
             Box::pin(async move {
                 log::info!("Executando task agendada: {} ({})", task_label, task_id);
-                
-                // Obter task atualizada
-                let task_opt = {
-                    let sched = scheduler.lock().await;
-                    sched.get_task(&task_id).cloned()
-                };
-                
-                if let Some(task) = task_opt {
-                    if !task.enabled {
-                        log::info!("Task {} está desabilitada, pulando", task_id);
-                        return;
-                    }
-                    
-                    // Obter browser - usando lazy initialization global
-                    let browser_arc = {
-                        use crate::web_scraper::get_or_create_browser;
-                        match get_or_create_browser() {
-                            Ok(b) => b,
-                            Err(e) => {
-                                log::error!("Erro ao obter browser para task {}: {}", task_id, e);
-                                return;
-                            }
-                        }
-                    };
-                    
-                    // Executar task
-                    match execute_task(&task, app_handle.clone(), browser_arc, ollama_url).await {
-                        Ok(_) => {
-                            // Atualizar last_run
-                            let mut sched = scheduler.lock().await;
-                            let _ = sched.update_last_run(&task_id, Utc::now());
-                            log::info!("Task {} executada com sucesso", task_id);
-                        }
-                        Err(e) => {
-                            log::error!("Erro ao executar task {}: {}", task_id, e);
-                        }
-                    }
-                } else {
-                    log::warn!("Task {} não encontrada", task_id);
-                }
+                run_scheduled_task(app_handle, scheduler, task_id, ollama_url, 0).await;
             })
         })
         .map_err(|e| format!("Failed to create job for task {}: {}", task_id_for_log, e))?;
@@ -122,6 +367,94 @@ pub async fn reload_scheduled_tasks(
         log::info!("Task '{}' agendada com cron: {}", task_label_for_log, cron_expr);
     }
     
+    // Manutenção automática do banco (checa configuração e ociosidade a cada execução)
+    let app_handle_maintenance = app_handle.clone();
+    let scheduler_maintenance = scheduler_state.clone();
+    let maintenance_job = Job::new_async(DB_MAINTENANCE_CHECK_CRON, move |_uuid, _l| {
+        let app_handle = app_handle_maintenance.clone();
+        let scheduler = scheduler_maintenance.clone();
+        Box::pin(async move {
+            maybe_run_db_maintenance(&app_handle, &scheduler).await;
+        })
+    })
+    .map_err(|e| format!("Failed to create database maintenance job: {}", e))?;
+    
+    sched.add(maintenance_job).await
+        .map_err(|e| format!("Failed to add job to scheduler: {}", e))?;
+    
+    // Resumo diário: a agenda é lida das configurações a cada verificação
+    let app_handle_digest = app_handle.clone();
+    let scheduler_digest = scheduler_state.clone();
+    let digest_job = Job::new_async(DIGEST_CHECK_CRON, move |_uuid, _l| {
+        let app_handle = app_handle_digest.clone();
+        let scheduler = scheduler_digest.clone();
+        Box::pin(async move {
+            if scheduler.lock().await.is_paused() {
+                return;
+            }
+            if let Err(e) = tokio::task::spawn_blocking(move || crate::digests::maybe_run(&app_handle)).await {
+                log::warn!("[Digest] Tarefa do resumo falhou: {}", e);
+            }
+        })
+    })
+    .map_err(|e| format!("Failed to create digest job: {}", e))?;
+    
+    sched.add(digest_job).await
+        .map_err(|e| format!("Failed to add job to scheduler: {}", e))?;
+    
+    // Retenção dos logs (arquivos rotacionados pelo plugin de log)
+    let app_handle_logs = app_handle.clone();
+    let log_retention_job = Job::new_async(LOG_RETENTION_CRON, move |_uuid, _l| {
+        let app_handle = app_handle_logs.clone();
+        Box::pin(async move {
+            let settings = crate::settings::current(&app_handle).logging;
+            match crate::logging::prune(&app_handle, &settings) {
+                Ok(0) => {}
+                Ok(removed) => log::info!("[Logs] {} arquivo(s) de log antigo(s) removido(s)", removed),
+                Err(e) => log::warn!("[Logs] Falha ao aplicar retenção: {}", e),
+            }
+        })
+    })
+    .map_err(|e| format!("Failed to create log retention job: {}", e))?;
+    
+    sched.add(log_retention_job).await
+        .map_err(|e| format!("Failed to add job to scheduler: {}", e))?;
+    
+    // Retenção das sessões (arquiva em ZIP e remove as antigas)
+    let app_handle_retention = app_handle.clone();
+    let scheduler_retention = scheduler_state.clone();
+    let session_retention_job = Job::new_async(SESSION_RETENTION_CRON, move |_uuid, _l| {
+        let app_handle = app_handle_retention.clone();
+        let scheduler = scheduler_retention.clone();
+        Box::pin(async move {
+            if scheduler.lock().await.is_paused() {
+                return;
+            }
+            if let Err(e) = tokio::task::spawn_blocking(move || crate::retention::maybe_run(&app_handle)).await {
+                log::warn!("[Retention] Tarefa de retenção falhou: {}", e);
+            }
+        })
+    })
+    .map_err(|e| format!("Failed to create session retention job: {}", e))?;
+    
+    sched.add(session_retention_job).await
+        .map_err(|e| format!("Failed to add job to scheduler: {}", e))?;
+    
+    // Sincronização entre dispositivos (a pasta e a ativação ficam nas configurações)
+    let app_handle_sync = app_handle.clone();
+    let folder_sync_job = Job::new_async(FOLDER_SYNC_CRON, move |_uuid, _l| {
+        let app_handle = app_handle_sync.clone();
+        Box::pin(async move {
+            if let Err(e) = tokio::task::spawn_blocking(move || crate::folder_sync::maybe_run(&app_handle)).await {
+                log::warn!("[Sync] Tarefa de sincronização falhou: {}", e);
+            }
+        })
+    })
+    .map_err(|e| format!("Failed to create folder sync job: {}", e))?;
+    
+    sched.add(folder_sync_job).await
+        .map_err(|e| format!("Failed to add job to scheduler: {}", e))?;
+    
     Ok(())
 }
 
@@ -0,0 +1,638 @@
+use crate::audit::AuditSettings;
+use crate::redaction::RedactionSettings;
+use crate::autostart::AutostartSettings;
+use crate::memory::MemorySettings;
+use crate::content_guard::ContentGuardSettings;
+use crate::builtin_tools::BuiltinToolsSettings;
+use crate::chat_templates::ChatTemplate;
+use crate::digests::DigestSettings;
+use crate::folder_sync::SyncSettings;
+use crate::logging::LoggingSettings;
+use crate::ollama_client::GenerationOptions;
+use crate::post_process::PostProcessSettings;
+use crate::resource_profile::{LowResourceMode, LowResourceThresholds};
+use crate::retention::RetentionSettings;
+use crate::scheduler::SchedulerSettings;
+use crate::stream_manager::StreamRetrySettings;
+use crate::stt::SttSettings;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use tauri::{AppHandle, Manager};
+
+/// Estado das configurações (gerenciado pelo Tauri)
+pub type SettingsState = Arc<Mutex<AppSettings>>;
+
+/// Limites de scraping usados pela busca web
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct ScrapingSettings {
+    #[serde(default = "default_max_concurrent_tabs")]
+    pub max_concurrent_tabs: usize,
+    #[serde(default = "default_total_sources_limit")]
+    pub total_sources_limit: usize,
+    #[serde(default)]
+    pub excluded_domains: Vec<String>,
+    /// Idioma das variantes de consulta ("auto" detecta pela própria consulta)
+    #[serde(default = "default_search_language")]
+    pub search_language: String,
+}
+
+impl Default for ScrapingSettings {
+    fn default() -> Self {
+        Self {
+            max_concurrent_tabs: default_max_concurrent_tabs(),
+            total_sources_limit: default_total_sources_limit(),
+            excluded_domains: Vec::new(),
+            search_language: default_search_language(),
+        }
+    }
+}
+
+fn default_search_language() -> String {
+    crate::query_expansion::AUTO_LANGUAGE.to_string()
+}
+
+/// Paralelismo dos jobs de geração de embeddings em lote
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct EmbeddingSettings {
+    /// Workers simultâneos (cada um carrega sua própria sessão ONNX)
+    #[serde(default = "default_embedding_concurrency")]
+    pub concurrency: usize,
+    /// Textos processados por lote antes de gravar e reportar progresso
+    #[serde(default = "default_embedding_batch_size")]
+    pub batch_size: usize,
+}
+
+impl Default for EmbeddingSettings {
+    fn default() -> Self {
+        Self {
+            concurrency: default_embedding_concurrency(),
+            batch_size: default_embedding_batch_size(),
+        }
+    }
+}
+
+/// Ajustes do servidor Ollama iniciado pelo app (None mantém o padrão do Ollama)
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Default)]
+pub struct OllamaServerTuning {
+    /// OLLAMA_NUM_PARALLEL: requisições simultâneas por modelo
+    #[serde(default)]
+    pub num_parallel: Option<u32>,
+    /// OLLAMA_MAX_LOADED_MODELS: modelos mantidos carregados ao mesmo tempo
+    #[serde(default)]
+    pub max_loaded_models: Option<u32>,
+    /// OLLAMA_FLASH_ATTENTION
+    #[serde(default)]
+    pub flash_attention: Option<bool>,
+    /// OLLAMA_CONTEXT_LENGTH: contexto padrão quando a requisição não define num_ctx
+    #[serde(default)]
+    pub context_length: Option<u32>,
+}
+
+impl OllamaServerTuning {
+    /// Variáveis de ambiente a definir ao iniciar `ollama serve`
+    pub fn env_vars(&self) -> Vec<(&'static str, String)> {
+        let mut vars = Vec::new();
+        if let Some(n) = self.num_parallel {
+            vars.push(("OLLAMA_NUM_PARALLEL", n.to_string()));
+        }
+        if let Some(n) = self.max_loaded_models {
+            vars.push(("OLLAMA_MAX_LOADED_MODELS", n.to_string()));
+        }
+        if let Some(enabled) = self.flash_attention {
+            vars.push(("OLLAMA_FLASH_ATTENTION", if enabled { "1" } else { "0" }.to_string()));
+        }
+        if let Some(n) = self.context_length {
+            vars.push(("OLLAMA_CONTEXT_LENGTH", n.to_string()));
+        }
+        vars
+    }
+}
+
+/// Histórico de métricas de recursos (a última hora fica sempre em memória)
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct MetricsHistorySettings {
+    /// Grava as amostras no SQLite para consultar intervalos além da última hora
+    #[serde(default)]
+    pub persist: bool,
+    #[serde(default = "default_metrics_retention_hours")]
+    pub retention_hours: u32,
+}
+
+impl Default for MetricsHistorySettings {
+    fn default() -> Self {
+        Self {
+            persist: false,
+            retention_hours: default_metrics_retention_hours(),
+        }
+    }
+}
+
+/// Limite de requisições de um modelo em gerações automatizadas (tarefas agendadas)
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct ModelRateLimit {
+    pub max_requests: u32,
+    pub window_secs: u64,
+}
+
+/// Configurações gerais do app (settings.json)
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct AppSettings {
+    #[serde(default = "default_version")]
+    pub version: u32,
+    /// "system", "light" ou "dark"
+    #[serde(default = "default_theme")]
+    pub theme: String,
+    /// Idioma da interface e das respostas (ex: "pt", "en")
+    #[serde(default = "default_language")]
+    pub language: String,
+    #[serde(default = "default_ollama_url")]
+    pub ollama_url: String,
+    #[serde(default)]
+    pub default_model: Option<String>,
+    #[serde(default)]
+    pub scraping: ScrapingSettings,
+    #[serde(default)]
+    pub low_resource_mode: LowResourceMode,
+    /// RAM e CPUs mínimas antes do modo `Auto` ligar o baixo consumo
+    #[serde(default)]
+    pub low_resource_thresholds: LowResourceThresholds,
+    #[serde(default)]
+    pub telemetry_opt_in: bool,
+    /// Dias em que mensagens removidas por truncamento podem ser restauradas
+    #[serde(default = "default_truncate_undo_days")]
+    pub truncate_undo_days: u32,
+    /// Acima deste tamanho o texto colado vira anexo referenciado no prompt
+    #[serde(default = "default_smart_paste_threshold")]
+    pub smart_paste_threshold: usize,
+    /// Limites por modelo (chave "*" vale para os demais). O chat interativo não é limitado.
+    #[serde(default)]
+    pub model_rate_limits: HashMap<String, ModelRateLimit>,
+    /// Executa a manutenção do banco automaticamente quando o sistema está ocioso
+    #[serde(default)]
+    pub auto_db_maintenance: bool,
+    #[serde(default)]
+    pub embeddings: EmbeddingSettings,
+    #[serde(default)]
+    pub ollama_tuning: OllamaServerTuning,
+    /// Variáveis extras do `ollama serve` iniciado pelo app (OLLAMA_HOST, OLLAMA_MODELS, proxy...)
+    #[serde(default)]
+    pub ollama_env: HashMap<String, String>,
+    /// `num_ctx` enviado ao Ollama por modelo (sem entrada usa o padrão do servidor)
+    #[serde(default)]
+    pub model_context_overrides: HashMap<String, u32>,
+    /// Formato das mensagens por padrão de nome de modelo (ex.: "gemma*"), ver `chat_templates`
+    #[serde(default)]
+    pub chat_templates: HashMap<String, ChatTemplate>,
+    /// Modelos fixados: `delete_model` só os remove com `force`, e limpezas os ignoram
+    #[serde(default)]
+    pub pinned_models: Vec<String>,
+    /// Resumo diário com as saídas das tasks agendadas
+    #[serde(default)]
+    pub digest: DigestSettings,
+    #[serde(default)]
+    pub metrics_history: MetricsHistorySettings,
+    #[serde(default)]
+    pub logging: LoggingSettings,
+    /// Retomada do chat quando a conexão com o Ollama cai no meio da resposta
+    #[serde(default)]
+    pub stream_retry: StreamRetrySettings,
+    /// Respostas geradas ao mesmo tempo (uma por sessão)
+    #[serde(default = "default_max_concurrent_streams")]
+    pub max_concurrent_streams: usize,
+    /// Opções de geração padrão do chat (a sessão e a chamada podem sobrescrever)
+    #[serde(default)]
+    pub generation_defaults: GenerationOptions,
+    /// Ditado de prompts com whisper.cpp
+    #[serde(default)]
+    pub stt: SttSettings,
+    /// Ferramentas embutidas (arquivos e web) oferecidas ao modelo
+    #[serde(default)]
+    pub builtin_tools: BuiltinToolsSettings,
+    /// Filtros aplicados à resposta antes de salvar no histórico
+    #[serde(default)]
+    pub post_processing: PostProcessSettings,
+    /// Remoção automática de sessões antigas (arquivadas em ZIP antes)
+    #[serde(default)]
+    pub retention: RetentionSettings,
+    /// Sincronização das conversas e configurações por uma pasta compartilhada
+    #[serde(default)]
+    pub sync: SyncSettings,
+    /// Registro opt-in de prompts, respostas, ferramentas e sites acessados
+    #[serde(default)]
+    pub audit: AuditSettings,
+    /// Dados pessoais removidos das consultas antes dos motores de busca
+    #[serde(default)]
+    pub redaction: RedactionSettings,
+    /// Inicialização com o sistema (registrada por `set_autostart`)
+    #[serde(default)]
+    pub autostart: AutostartSettings,
+    /// Memória das sessões (fatos lembrados e extração automática)
+    #[serde(default)]
+    pub memory: MemorySettings,
+    /// Defesas contra prompt injection no conteúdo extraído da web
+    #[serde(default)]
+    pub content_guard: ContentGuardSettings,
+    /// Horários perdidos e limite de tasks agendadas simultâneas
+    #[serde(default)]
+    pub scheduler: SchedulerSettings,
+}
+
+fn default_version() -> u32 {
+    1
+}
+
+fn default_theme() -> String {
+    "system".to_string()
+}
+
+fn default_language() -> String {
+    "pt".to_string()
+}
+
+fn default_ollama_url() -> String {
+    "http://localhost:11434".to_string()
+}
+
+fn default_truncate_undo_days() -> u32 {
+    7
+}
+
+fn default_max_concurrent_streams() -> usize {
+    2
+}
+
+fn default_smart_paste_threshold() -> usize {
+    4000
+}
+
+fn default_embedding_concurrency() -> usize {
+    2
+}
+
+fn default_embedding_batch_size() -> usize {
+    16
+}
+
+fn default_metrics_retention_hours() -> u32 {
+    24
+}
+
+fn default_max_concurrent_tabs() -> usize {
+    5
+}
+
+fn default_total_sources_limit() -> usize {
+    10
+}
+
+impl Default for AppSettings {
+    fn default() -> Self {
+        Self {
+            version: default_version(),
+            theme: default_theme(),
+            language: default_language(),
+            ollama_url: default_ollama_url(),
+            default_model: None,
+            scraping: ScrapingSettings::default(),
+            low_resource_mode: LowResourceMode::default(),
+            low_resource_thresholds: LowResourceThresholds::default(),
+            telemetry_opt_in: false,
+            truncate_undo_days: default_truncate_undo_days(),
+            smart_paste_threshold: default_smart_paste_threshold(),
+            model_rate_limits: HashMap::new(),
+            auto_db_maintenance: false,
+            embeddings: EmbeddingSettings::default(),
+            ollama_tuning: OllamaServerTuning::default(),
+            ollama_env: HashMap::new(),
+            model_context_overrides: HashMap::new(),
+            chat_templates: HashMap::new(),
+            pinned_models: Vec::new(),
+            digest: DigestSettings::default(),
+            metrics_history: MetricsHistorySettings::default(),
+            logging: LoggingSettings::default(),
+            stream_retry: StreamRetrySettings::default(),
+            max_concurrent_streams: default_max_concurrent_streams(),
+            generation_defaults: GenerationOptions::default(),
+            stt: SttSettings::default(),
+            builtin_tools: BuiltinToolsSettings::default(),
+            post_processing: PostProcessSettings::default(),
+            retention: RetentionSettings::default(),
+            sync: SyncSettings::default(),
+            audit: AuditSettings::default(),
+            redaction: RedactionSettings::default(),
+            autostart: AutostartSettings::default(),
+            memory: MemorySettings::default(),
+            content_guard: ContentGuardSettings::default(),
+            scheduler: SchedulerSettings::default(),
+        }
+    }
+}
+
+/// Nome do modelo com a tag explícita (`llama3` e `llama3:latest` são o mesmo modelo)
+pub fn normalize_model_name(name: &str) -> String {
+    let name = name.trim();
+    if name.contains(':') {
+        name.to_string()
+    } else {
+        format!("{}:latest", name)
+    }
+}
+
+impl AppSettings {
+    pub fn is_model_pinned(&self, name: &str) -> bool {
+        let name = normalize_model_name(name);
+        self.pinned_models.iter().any(|pinned| normalize_model_name(pinned) == name)
+    }
+
+    /// Valida as configurações, retornando todos os problemas encontrados
+    pub fn validate(&self) -> Result<(), String> {
+        let mut errors = Vec::new();
+
+        if !["system", "light", "dark"].contains(&self.theme.as_str()) {
+            errors.push(format!("Tema inválido: {}", self.theme));
+        }
+
+        match url::Url::parse(&self.ollama_url) {
+            Ok(url) if url.scheme() == "http" || url.scheme() == "https" => {}
+            _ => errors.push(format!("URL do Ollama inválida: {}", self.ollama_url)),
+        }
+
+        if self.language.trim().is_empty() {
+            errors.push("Idioma não pode estar vazio".to_string());
+        }
+
+        if let Some(model) = &self.default_model {
+            if model.trim().is_empty() {
+                errors.push("Modelo padrão não pode ser vazio (use null)".to_string());
+            }
+        }
+
+        if !(1..=20).contains(&self.scraping.max_concurrent_tabs) {
+            errors.push("scraping.max_concurrent_tabs deve estar entre 1 e 20".to_string());
+        }
+
+        if !(1..=50).contains(&self.scraping.total_sources_limit) {
+            errors.push("scraping.total_sources_limit deve estar entre 1 e 50".to_string());
+        }
+
+        let search_language = self.scraping.search_language.as_str();
+        if search_language != crate::query_expansion::AUTO_LANGUAGE
+            && !crate::query_expansion::supported_languages().contains(&search_language)
+        {
+            errors.push(format!(
+                "scraping.search_language deve ser \"auto\" ou um de {:?}",
+                crate::query_expansion::supported_languages()
+            ));
+        }
+
+        self.low_resource_thresholds.validate(&mut errors);
+
+        if !(1..=90).contains(&self.truncate_undo_days) {
+            errors.push("truncate_undo_days deve estar entre 1 e 90".to_string());
+        }
+
+        if self.smart_paste_threshold < 500 {
+            errors.push("smart_paste_threshold deve ser pelo menos 500".to_string());
+        }
+
+        if !(1..=8).contains(&self.embeddings.concurrency) {
+            errors.push("embeddings.concurrency deve estar entre 1 e 8".to_string());
+        }
+
+        if !(1..=256).contains(&self.embeddings.batch_size) {
+            errors.push("embeddings.batch_size deve estar entre 1 e 256".to_string());
+        }
+
+        let tuning = &self.ollama_tuning;
+        if tuning.num_parallel.is_some_and(|n| !(1..=16).contains(&n)) {
+            errors.push("ollama_tuning.num_parallel deve estar entre 1 e 16".to_string());
+        }
+        if tuning.max_loaded_models.is_some_and(|n| !(1..=8).contains(&n)) {
+            errors.push("ollama_tuning.max_loaded_models deve estar entre 1 e 8".to_string());
+        }
+        if tuning.context_length.is_some_and(|n| !(512..=131072).contains(&n)) {
+            errors.push("ollama_tuning.context_length deve estar entre 512 e 131072".to_string());
+        }
+
+        crate::chat_templates::validate(&self.chat_templates, &mut errors);
+        for (model, num_ctx) in &self.model_context_overrides {
+            if *num_ctx < 512 {
+                errors.push(format!("model_context_overrides.{}: num_ctx deve ser pelo menos 512", model));
+            }
+        }
+
+        for (model, limit) in &self.model_rate_limits {
+            if limit.max_requests == 0 || limit.window_secs == 0 {
+                errors.push(format!("model_rate_limits.{}: max_requests e window_secs devem ser maiores que 0", model));
+            }
+        }
+
+        self.digest.validate(&mut errors);
+
+        if !(1..=720).contains(&self.metrics_history.retention_hours) {
+            errors.push("metrics_history.retention_hours deve estar entre 1 e 720".to_string());
+        }
+
+        crate::ollama_server::validate_env(&self.ollama_env, &mut errors);
+        self.logging.validate(&mut errors);
+        self.stream_retry.validate(&mut errors);
+        if !(1..=8).contains(&self.max_concurrent_streams) {
+            errors.push("max_concurrent_streams deve estar entre 1 e 8".to_string());
+        }
+        self.generation_defaults.validate("generation_defaults", &mut errors);
+        self.stt.validate(&mut errors);
+        self.builtin_tools.validate(&mut errors);
+        self.post_processing.validate(&mut errors);
+        self.retention.validate(&mut errors);
+        self.sync.validate(&mut errors);
+        self.content_guard.validate(&mut errors);
+        self.scheduler.validate(&mut errors);
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors.join("; "))
+        }
+    }
+}
+
+/// Helper para obter o caminho do arquivo settings.json
+pub fn get_settings_path(app_handle: &AppHandle) -> Result<PathBuf, String> {
+    let app_data_dir = app_handle
+        .path()
+        .app_data_dir()
+        .map_err(|e| format!("Failed to get app data dir: {}", e))?;
+
+    Ok(app_data_dir.join("settings.json"))
+}
+
+/// Carrega as configurações do arquivo (padrão se não existir ou estiver inválido)
+pub fn load_settings(app_handle: &AppHandle) -> AppSettings {
+    match get_settings_path(app_handle) {
+        Ok(path) => load_settings_from(&path),
+        Err(e) => {
+            log::warn!("{}", e);
+            AppSettings::default()
+        }
+    }
+}
+
+fn load_settings_from(path: &Path) -> AppSettings {
+    let mut settings = read_settings_file(path);
+    if migrate_legacy_resource_profile(path, &mut settings) {
+        log::info!("[Settings] Modo de baixo consumo importado de {}", LEGACY_RESOURCE_PROFILE_FILE);
+    }
+    settings
+}
+
+fn read_settings_file(path: &Path) -> AppSettings {
+    if !path.exists() {
+        return AppSettings::default();
+    }
+
+    match fs::read_to_string(path) {
+        Ok(content) => match serde_json::from_str::<AppSettings>(&content) {
+            Ok(settings) => settings,
+            Err(e) => {
+                log::warn!("Failed to parse settings.json: {}. Usando configurações padrão.", e);
+                AppSettings::default()
+            }
+        },
+        Err(e) => {
+            log::warn!("Failed to read settings.json: {}. Usando configurações padrão.", e);
+            AppSettings::default()
+        }
+    }
+}
+
+/// Arquivo em que versões antigas guardavam o modo de baixo consumo
+const LEGACY_RESOURCE_PROFILE_FILE: &str = "resource_profile.json";
+
+#[derive(Deserialize)]
+struct LegacyResourceProfile {
+    #[serde(default)]
+    mode: LowResourceMode,
+}
+
+/// Importa o modo do antigo `resource_profile.json` (ao lado de settings.json) se as configurações
+/// ainda estiverem no padrão, salva e renomeia o arquivo antigo para que não seja lido de novo
+fn migrate_legacy_resource_profile(settings_path: &Path, settings: &mut AppSettings) -> bool {
+    let Some(legacy_path) = settings_path.parent().map(|dir| dir.join(LEGACY_RESOURCE_PROFILE_FILE)) else {
+        return false;
+    };
+    if !legacy_path.exists() {
+        return false;
+    }
+
+    let legacy_mode = fs::read_to_string(&legacy_path)
+        .ok()
+        .and_then(|content| serde_json::from_str::<LegacyResourceProfile>(&content).ok())
+        .map(|profile| profile.mode);
+    let migrated = match legacy_mode {
+        Some(mode) if settings.low_resource_mode == LowResourceMode::default() && mode != settings.low_resource_mode => {
+            let mut updated = settings.clone();
+            updated.low_resource_mode = mode;
+            if let Err(e) = save_settings_to(settings_path, &updated) {
+                log::warn!("[Settings] Falha ao migrar {}: {}", LEGACY_RESOURCE_PROFILE_FILE, e);
+                return false;
+            }
+            *settings = updated;
+            true
+        }
+        _ => false,
+    };
+
+    if let Err(e) = fs::rename(&legacy_path, legacy_path.with_extension("json.migrated")) {
+        log::warn!("[Settings] Falha ao renomear {}: {}", LEGACY_RESOURCE_PROFILE_FILE, e);
+    }
+    migrated
+}
+
+/// Salva as configurações no arquivo (atomic write)
+pub fn save_settings(app_handle: &AppHandle, settings: &AppSettings) -> Result<(), String> {
+    save_settings_to(&get_settings_path(app_handle)?, settings)
+}
+
+fn save_settings_to(path: &Path, settings: &AppSettings) -> Result<(), String> {
+    if let Some(parent) = path.parent() {
+        if !parent.exists() {
+            fs::create_dir_all(parent)
+                .map_err(|e| format!("Failed to create config directory: {}", e))?;
+        }
+    }
+
+    let json = serde_json::to_string_pretty(settings)
+        .map_err(|e| format!("Failed to serialize settings: {}", e))?;
+
+    // Escrever em arquivo temporário primeiro, depois renomear (atomic write)
+    let temp_path = path.with_extension("json.tmp");
+    fs::write(&temp_path, json)
+        .map_err(|e| format!("Failed to write temp settings file: {}", e))?;
+
+    fs::rename(&temp_path, path)
+        .map_err(|e| format!("Failed to rename temp file to settings.json: {}", e))?;
+
+    Ok(())
+}
+
+/// Configurações atuais a partir do estado gerenciado (ou do disco, se ainda não gerenciado)
+pub fn current(app_handle: &AppHandle) -> AppSettings {
+    match app_handle.try_state::<SettingsState>() {
+        Some(state) => state
+            .lock()
+            .map(|s| s.clone())
+            .unwrap_or_else(|_| load_settings(app_handle)),
+        None => load_settings(app_handle),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_settings_defaults_and_validation() {
+        let settings: AppSettings = serde_json::from_str("{}").unwrap();
+        assert_eq!(settings, AppSettings::default());
+        assert!(settings.validate().is_ok());
+
+        let mut invalid = AppSettings::default();
+        invalid.theme = "neon".to_string();
+        invalid.ollama_url = "localhost".to_string();
+        invalid.scraping.max_concurrent_tabs = 0;
+        let err = invalid.validate().unwrap_err();
+        assert!(err.contains("Tema"));
+        assert!(err.contains("URL"));
+        assert!(err.contains("max_concurrent_tabs"));
+
+        let pinned = AppSettings { pinned_models: vec!["llama3".to_string()], ..AppSettings::default() };
+        assert!(pinned.is_model_pinned("llama3:latest"));
+        assert!(!pinned.is_model_pinned("llama3:70b"));
+    }
+
+    #[test]
+    fn test_legacy_resource_profile_migration() {
+        let dir = std::env::temp_dir().join(format!("ollahub-settings-{}", uuid::Uuid::new_v4()));
+        fs::create_dir_all(&dir).unwrap();
+        let settings_path = dir.join("settings.json");
+        let legacy_path = dir.join(LEGACY_RESOURCE_PROFILE_FILE);
+        fs::write(&legacy_path, r#"{"mode":"on"}"#).unwrap();
+
+        // Primeira carga importa o modo, persiste e tira o arquivo antigo do caminho
+        assert_eq!(load_settings_from(&settings_path).low_resource_mode, LowResourceMode::On);
+        assert!(!legacy_path.exists());
+        assert_eq!(read_settings_file(&settings_path).low_resource_mode, LowResourceMode::On);
+
+        // Um arquivo antigo reaparecendo não sobrescreve a escolha já feita nas configurações
+        let mut settings = read_settings_file(&settings_path);
+        settings.low_resource_mode = LowResourceMode::Off;
+        save_settings_to(&settings_path, &settings).unwrap();
+        fs::write(&legacy_path, r#"{"mode":"on"}"#).unwrap();
+        assert_eq!(load_settings_from(&settings_path).low_resource_mode, LowResourceMode::Off);
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
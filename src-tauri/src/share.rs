@@ -0,0 +1,269 @@
+use crate::db::{ChatMessage, ChatSession};
+use chrono::{DateTime, Utc};
+use rand::distributions::Alphanumeric;
+use rand::Rng;
+use serde::Serialize;
+use std::sync::Mutex;
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpListener;
+use tokio::sync::oneshot;
+
+/// Tempo que o preview local fica disponível
+const PREVIEW_TTL: Duration = Duration::from_secs(10 * 60);
+/// Tamanho do token aleatório no caminho da URL do preview
+const PREVIEW_TOKEN_LEN: usize = 24;
+/// Limite de leitura da requisição HTTP (só a linha inicial importa)
+const MAX_REQUEST_BYTES: usize = 8 * 1024;
+
+/// Texto exibido no lugar de prompts de sistema omitidos
+const REDACTED_SYSTEM_PROMPT: &str = "[prompt de sistema omitido]";
+
+/// Preview em andamento (apenas um por vez; iniciar outro encerra o anterior)
+static PREVIEW: Mutex<Option<oneshot::Sender<()>>> = Mutex::new(None);
+
+/// Preview servido na rede local
+#[derive(Serialize, Clone, Debug)]
+pub struct SharePreview {
+    pub url: String,
+    /// QR code da URL em SVG (para abrir no celular)
+    pub qr_svg: String,
+    pub expires_at: DateTime<Utc>,
+}
+
+pub fn escape_html(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+fn role_label(role: &str) -> &str {
+    match role {
+        "user" => "Você",
+        "assistant" => "Assistente",
+        "system" => "Sistema",
+        other => other,
+    }
+}
+
+const STYLE: &str = "
+body{margin:0;background:#f6f7f9;color:#1f2328;font:15px/1.6 -apple-system,BlinkMacSystemFont,'Segoe UI',Roboto,sans-serif}
+main{max-width:820px;margin:0 auto;padding:32px 16px}
+header h1{font-size:22px;margin:0 0 4px}
+header p{margin:0 0 24px;color:#656d76;font-size:13px}
+.msg{border-radius:10px;padding:12px 16px;margin:0 0 12px;background:#fff;border:1px solid #d0d7de}
+.msg.user{background:#eef4ff;border-color:#c8daf8}
+.msg.system{background:#fff8e6;border-color:#f0d998;font-style:italic}
+.role{font-size:12px;font-weight:600;text-transform:uppercase;color:#656d76;margin-bottom:4px}
+.content{white-space:pre-wrap;word-wrap:break-word}
+footer{margin-top:24px;color:#8c959f;font-size:12px;text-align:center}
+@media (prefers-color-scheme:dark){body{background:#0d1117;color:#e6edf3}.msg{background:#161b22;border-color:#30363d}
+.msg.user{background:#14233a;border-color:#1f3b63}.msg.system{background:#2a2414;border-color:#5c4a1a}}
+";
+
+/// HTML autocontido da conversa (CSS inline, sem scripts nem recursos externos)
+pub fn render_html(
+    session: &ChatSession,
+    system_prompt: Option<&str>,
+    messages: &[ChatMessage],
+    redact_system_prompts: bool,
+) -> String {
+    let mut body = String::new();
+    let system_block = |content: &str| {
+        let text = if redact_system_prompts { REDACTED_SYSTEM_PROMPT } else { content };
+        format!(
+            "<section class=\"msg system\"><div class=\"role\">{}</div><div class=\"content\">{}</div></section>\n",
+            role_label("system"),
+            escape_html(text)
+        )
+    };
+
+    if let Some(prompt) = system_prompt.filter(|p| !p.trim().is_empty()) {
+        body.push_str(&system_block(prompt));
+    }
+    for message in messages {
+        if message.role == "system" {
+            body.push_str(&system_block(&message.content));
+            continue;
+        }
+        body.push_str(&format!(
+            "<section class=\"msg {}\"><div class=\"role\">{}</div><div class=\"content\">{}</div></section>\n",
+            escape_html(&message.role),
+            escape_html(role_label(&message.role)),
+            escape_html(&message.content)
+        ));
+    }
+
+    format!(
+        "<!DOCTYPE html>\n<html lang=\"pt\">\n<head>\n<meta charset=\"utf-8\">\n\
+         <meta name=\"viewport\" content=\"width=device-width, initial-scale=1\">\n\
+         <title>{title}</title>\n<style>{style}</style>\n</head>\n<body>\n<main>\n\
+         <header><h1>{emoji} {title}</h1><p>{count} mensagens · {date}</p></header>\n\
+         {body}<footer>Exportado do OllaHub em {exported}</footer>\n</main>\n</body>\n</html>\n",
+        title = escape_html(&session.title),
+        emoji = escape_html(&session.emoji),
+        style = STYLE,
+        count = messages.len(),
+        date = session.created_at.format("%d/%m/%Y %H:%M"),
+        body = body,
+        exported = Utc::now().format("%d/%m/%Y %H:%M UTC"),
+    )
+}
+
+/// IP da interface usada para sair para a rede (nenhum pacote é enviado pelo UDP "connect")
+fn local_ip() -> Option<std::net::IpAddr> {
+    let socket = std::net::UdpSocket::bind("0.0.0.0:0").ok()?;
+    socket.connect("8.8.8.8:80").ok()?;
+    socket.local_addr().ok().map(|addr| addr.ip())
+}
+
+fn qr_svg(url: &str) -> Result<String, String> {
+    let code = qrcode::QrCode::new(url.as_bytes())
+        .map_err(|e| format!("Failed to generate QR code: {}", e))?;
+    Ok(code
+        .render::<qrcode::render::svg::Color>()
+        .min_dimensions(200, 200)
+        .build())
+}
+
+/// Encerra o preview em andamento, se houver
+pub fn stop_preview() -> bool {
+    match PREVIEW.lock().ok().and_then(|mut preview| preview.take()) {
+        Some(shutdown) => {
+            let _ = shutdown.send(());
+            true
+        }
+        None => false,
+    }
+}
+
+/// Serve o HTML na rede local por tempo limitado, em uma URL com token aleatório
+pub async fn start_preview(html: String) -> Result<SharePreview, String> {
+    stop_preview();
+
+    let listener = TcpListener::bind("0.0.0.0:0")
+        .await
+        .map_err(|e| format!("Failed to start preview server: {}", e))?;
+    let port = listener
+        .local_addr()
+        .map_err(|e| format!("Failed to get preview address: {}", e))?
+        .port();
+    let token: String = rand::thread_rng()
+        .sample_iter(&Alphanumeric)
+        .take(PREVIEW_TOKEN_LEN)
+        .map(char::from)
+        .collect();
+    let host = local_ip().map(|ip| ip.to_string()).unwrap_or_else(|| "127.0.0.1".to_string());
+    let url = format!("http://{}:{}/{}", host, port, token);
+    let qr_svg = qr_svg(&url)?;
+
+    let (shutdown_tx, mut shutdown_rx) = oneshot::channel();
+    if let Ok(mut preview) = PREVIEW.lock() {
+        *preview = Some(shutdown_tx);
+    }
+
+    let path = format!("/{}", token);
+    tauri::async_runtime::spawn(async move {
+        let expires = tokio::time::sleep(PREVIEW_TTL);
+        tokio::pin!(expires);
+        loop {
+            tokio::select! {
+                _ = &mut expires => break,
+                _ = &mut shutdown_rx => break,
+                accepted = listener.accept() => {
+                    let Ok((stream, addr)) = accepted else { continue };
+                    log::debug!("[Share] Preview acessado por {}", addr);
+                    let html = html.clone();
+                    let path = path.clone();
+                    tauri::async_runtime::spawn(async move {
+                        if let Err(e) = serve(stream, &path, &html).await {
+                            log::debug!("[Share] Erro ao responder preview: {}", e);
+                        }
+                    });
+                }
+            }
+        }
+        log::info!("[Share] Preview encerrado");
+    });
+
+    log::info!("[Share] Preview disponível na porta {} por {} min", port, PREVIEW_TTL.as_secs() / 60);
+    Ok(SharePreview {
+        url,
+        qr_svg,
+        expires_at: Utc::now() + chrono::Duration::seconds(PREVIEW_TTL.as_secs() as i64),
+    })
+}
+
+async fn serve(mut stream: tokio::net::TcpStream, path: &str, html: &str) -> std::io::Result<()> {
+    let mut buffer = vec![0u8; MAX_REQUEST_BYTES];
+    let read = stream.read(&mut buffer).await?;
+    let request = String::from_utf8_lossy(&buffer[..read]);
+    let mut parts = request.lines().next().unwrap_or_default().split_whitespace();
+    let (method, target) = (parts.next(), parts.next());
+
+    let (status, content_type, body) = if method == Some("GET") && target == Some(path) {
+        ("200 OK", "text/html; charset=utf-8", html)
+    } else {
+        ("404 Not Found", "text/plain; charset=utf-8", "Not found")
+    };
+    let response = format!(
+        "HTTP/1.1 {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nCache-Control: no-store\r\n\
+         X-Content-Type-Options: nosniff\r\nConnection: close\r\n\r\n",
+        status,
+        content_type,
+        body.len()
+    );
+    stream.write_all(response.as_bytes()).await?;
+    stream.write_all(body.as_bytes()).await?;
+    stream.shutdown().await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_html_escapes_and_redacts() {
+        let now = Utc::now();
+        let session = ChatSession {
+            id: "s1".to_string(),
+            title: "<script>alert(1)</script>".to_string(),
+            emoji: "💬".to_string(),
+            created_at: now,
+            updated_at: now,
+        };
+        let message = |role: &str, content: &str| ChatMessage {
+            id: None,
+            session_id: "s1".to_string(),
+            role: role.to_string(),
+            content: content.to_string(),
+            metadata: None,
+            created_at: now,
+        };
+        let messages = vec![
+            message("system", "segredo interno"),
+            message("user", "a < b && c"),
+            message("assistant", "sim"),
+        ];
+
+        let html = render_html(&session, Some("prompt do preset"), &messages, true);
+        assert!(!html.contains("<script>"));
+        assert!(html.contains("&lt;script&gt;"));
+        assert!(html.contains("a &lt; b &amp;&amp; c"));
+        assert!(!html.contains("segredo interno"));
+        assert!(!html.contains("prompt do preset"));
+
+        let html = render_html(&session, Some("prompt do preset"), &messages, false);
+        assert!(html.contains("segredo interno"));
+        assert!(html.contains("prompt do preset"));
+    }
+}
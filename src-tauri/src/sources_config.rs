@@ -3,6 +3,7 @@ use std::fs;
 use std::path::PathBuf;
 use tauri::{AppHandle, Manager};
 use chrono::Utc;
+use std::collections::HashMap;
 
 /// Categoria de fonte de busca
 #[derive(Serialize, Deserialize, Clone, Debug)]
@@ -11,6 +12,15 @@ pub struct SourceCategory {
     pub name: String,
     pub base_sites: Vec<String>,
     pub enabled: bool,
+    /// Peso na divisão de `total_sources_limit` entre as categorias (ver `smart_search`)
+    #[serde(default = "default_weight")]
+    pub weight: u32,
+}
+
+pub const MAX_CATEGORY_WEIGHT: u32 = 100;
+
+pub fn default_weight() -> u32 {
+    1
 }
 
 /// Configuração completa de fontes de busca
@@ -21,6 +31,185 @@ pub struct SourcesConfig {
     pub categories: Vec<SourceCategory>,
     #[serde(default = "default_last_updated")]
     pub last_updated: String,
+    #[serde(default)]
+    pub proxy: ProxyConfig,
+    #[serde(default)]
+    pub search_apis: SearchApiConfig,
+    #[serde(default)]
+    pub extraction_rules: Vec<ExtractionRule>,
+    #[serde(default)]
+    pub feeds: Vec<FeedSource>,
+}
+
+/// Feed RSS/Atom acompanhado (ver `feeds::fetch_feeds`)
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct FeedSource {
+    pub url: String,
+    #[serde(default)]
+    pub title: Option<String>,
+    /// ID da categoria de fontes à qual o feed pertence
+    pub category: String,
+    #[serde(default = "default_feed_enabled")]
+    pub enabled: bool,
+}
+
+fn default_feed_enabled() -> bool {
+    true
+}
+
+impl FeedSource {
+    pub fn validate(&self) -> Result<(), String> {
+        match url::Url::parse(self.url.trim()) {
+            Ok(url) if url.scheme() == "http" || url.scheme() == "https" => Ok(()),
+            _ => Err(format!("URL de feed inválida: {}", self.url)),
+        }
+    }
+}
+
+/// Regra de extração para um domínio (seletores CSS usados no lugar do readability)
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct ExtractionRule {
+    /// Domínio ao qual a regra se aplica (subdomínios incluídos, ex: "stackoverflow.com")
+    pub domain: String,
+    #[serde(default)]
+    pub title_selector: Option<String>,
+    /// Elementos com o conteúdo principal (todos os que casarem, em ordem)
+    #[serde(default)]
+    pub content_selector: Option<String>,
+    /// Elementos removidos antes da extração (menus, assinaturas, comentários...)
+    #[serde(default)]
+    pub exclude_selectors: Vec<String>,
+}
+
+impl ExtractionRule {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.domain.trim().is_empty() || self.domain.contains('/') {
+            return Err(format!("Domínio inválido na regra de extração: \"{}\"", self.domain));
+        }
+        let selectors = self
+            .title_selector
+            .iter()
+            .chain(self.content_selector.iter())
+            .chain(self.exclude_selectors.iter());
+        for selector in selectors {
+            scraper::Selector::parse(selector)
+                .map_err(|e| format!("Seletor CSS inválido \"{}\" ({}): {:?}", selector, self.domain, e))?;
+        }
+        Ok(())
+    }
+    
+    /// Verifica se a regra vale para o host (domínio exato ou subdomínio)
+    pub fn matches_host(&self, host: &str) -> bool {
+        let domain = self.domain.trim().trim_start_matches("www.").to_lowercase();
+        let host = host.to_lowercase();
+        let host = host.trim_start_matches("www.");
+        host == domain || host.ends_with(&format!(".{}", domain))
+    }
+}
+
+/// Motores de busca via API (usados quando configurados, além dos motores por scraping)
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct SearchApiConfig {
+    /// Instância SearxNG (ex: "http://localhost:8080"), com o formato JSON habilitado
+    #[serde(default)]
+    pub searx_url: Option<String>,
+    /// Chave da Brave Search API
+    #[serde(default)]
+    pub brave_api_key: Option<String>,
+    /// Motores na ordem de consulta, com ativação e chave/URL próprias
+    /// (vazio: ordem padrão de `SearchEngine::default_order`)
+    #[serde(default)]
+    pub engines: Vec<EngineSetting>,
+}
+
+/// Configuração de um motor de busca
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct EngineSetting {
+    /// "google", "bing", "yahoo", "duckduckgo", "startpage", "searx" ou "brave"
+    pub name: String,
+    #[serde(default = "default_engine_enabled")]
+    pub enabled: bool,
+    /// Chave de API (Brave); tem precedência sobre `brave_api_key`
+    #[serde(default)]
+    pub api_key: Option<String>,
+    /// URL base própria: instância SearxNG ou domínio alternativo do motor (ex: "https://www.google.com.br/search")
+    #[serde(default)]
+    pub base_url: Option<String>,
+}
+
+fn default_engine_enabled() -> bool {
+    true
+}
+
+pub const ENGINE_NAMES: &[&str] = &["google", "bing", "yahoo", "duckduckgo", "startpage", "searx", "brave"];
+
+fn validate_http_url(value: &str, what: &str) -> Result<(), String> {
+    match url::Url::parse(value.trim()) {
+        Ok(url) if url.scheme() == "http" || url.scheme() == "https" => Ok(()),
+        _ => Err(format!("{} inválida: {}", what, value)),
+    }
+}
+
+impl SearchApiConfig {
+    pub fn validate(&self) -> Result<(), String> {
+        if let Some(searx_url) = self.searx_url.as_deref().filter(|u| !u.trim().is_empty()) {
+            validate_http_url(searx_url, "URL da instância SearxNG")?;
+        }
+        for engine in &self.engines {
+            if !ENGINE_NAMES.contains(&engine.name.as_str()) {
+                return Err(format!("Motor de busca desconhecido: \"{}\"", engine.name));
+            }
+            if self.engines.iter().filter(|e| e.name == engine.name).count() > 1 {
+                return Err(format!("Motor de busca repetido: \"{}\"", engine.name));
+            }
+            if let Some(base_url) = engine.base_url.as_deref().filter(|u| !u.trim().is_empty()) {
+                validate_http_url(base_url, &format!("URL base do motor {}", engine.name))?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Configuração própria do motor, se houver
+    pub fn engine(&self, name: &str) -> Option<&EngineSetting> {
+        self.engines.iter().find(|e| e.name == name)
+    }
+
+    /// Motores sem configuração própria contam como ativos
+    pub fn is_engine_enabled(&self, name: &str) -> bool {
+        self.engine(name).map_or(true, |e| e.enabled)
+    }
+}
+
+/// Valor de `engine_overrides` que faz o motor acessar a rede sem proxy
+pub const PROXY_DIRECT: &str = "direct";
+
+/// Proxy usado pelo scraper (clientes HTTP e headless browser)
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct ProxyConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    /// URLs de proxy (`http://`, `https://`, `socks5://`, com usuário/senha opcionais).
+    /// Com mais de uma, as requisições alternam entre elas (rodízio).
+    #[serde(default)]
+    pub proxies: Vec<String>,
+    /// Proxy por motor de busca (ex: "google" -> URL, ou "direct" para não usar proxy)
+    #[serde(default)]
+    pub engine_overrides: HashMap<String, String>,
+}
+
+impl ProxyConfig {
+    /// Verifica se todas as URLs de proxy são válidas
+    pub fn validate(&self) -> Result<(), String> {
+        let overrides = self.engine_overrides.values().filter(|v| v.as_str() != PROXY_DIRECT);
+        for proxy in self.proxies.iter().chain(overrides) {
+            reqwest::Proxy::all(proxy.as_str())
+                .map_err(|e| format!("Proxy inválido \"{}\": {}", proxy, e))?;
+        }
+        if self.enabled && self.proxies.is_empty() && self.engine_overrides.is_empty() {
+            return Err("Proxy ativado sem nenhuma URL configurada".to_string());
+        }
+        Ok(())
+    }
 }
 
 fn default_version() -> u32 {
@@ -36,6 +225,10 @@ impl Default for SourcesConfig {
         Self {
             version: 1,
             last_updated: Utc::now().to_rfc3339(),
+            proxy: ProxyConfig::default(),
+            search_apis: SearchApiConfig::default(),
+            extraction_rules: Vec::new(),
+            feeds: Vec::new(),
             categories: vec![
                 SourceCategory {
                     id: "academico".to_string(),
@@ -63,6 +256,7 @@ impl Default for SourcesConfig {
                         "worldscientific.com".to_string(),
                     ],
                     enabled: true,
+                    weight: 1,
                 },
                 SourceCategory {
                     id: "tech".to_string(),
@@ -90,6 +284,7 @@ impl Default for SourcesConfig {
                         "css-tricks.com".to_string(),
                     ],
                     enabled: true,
+                    weight: 1,
                 },
                 SourceCategory {
                     id: "news".to_string(),
@@ -117,6 +312,7 @@ impl Default for SourcesConfig {
                         "techtudo.com.br".to_string(),
                     ],
                     enabled: true,
+                    weight: 1,
                 },
                 SourceCategory {
                     id: "financeiro".to_string(),
@@ -144,6 +340,7 @@ impl Default for SourcesConfig {
                         "imf.org".to_string(),
                     ],
                     enabled: true,
+                    weight: 1,
                 },
             ],
         }
@@ -183,6 +380,23 @@ pub fn load_sources_config(app_handle: &AppHandle) -> Result<SourcesConfig, Stri
 
 /// Salva a configuração de fontes no arquivo
 pub fn save_sources_config(app_handle: &AppHandle, config: SourcesConfig) -> Result<(), String> {
+    for category in &config.categories {
+        if category.weight == 0 || category.weight > MAX_CATEGORY_WEIGHT {
+            return Err(format!(
+                "Peso inválido na categoria \"{}\": {} (use de 1 a {})",
+                category.name, category.weight, MAX_CATEGORY_WEIGHT
+            ));
+        }
+    }
+    config.proxy.validate()?;
+    config.search_apis.validate()?;
+    for rule in &config.extraction_rules {
+        rule.validate()?;
+    }
+    for feed in &config.feeds {
+        feed.validate()?;
+    }
+    
     let config_path = get_sources_config_path(app_handle)?;
     
     // Garantir que o diretório pai existe
@@ -210,7 +424,260 @@ pub fn save_sources_config(app_handle: &AppHandle, config: SourcesConfig) -> Res
     fs::rename(&temp_path, &config_path)
         .map_err(|e| format!("Failed to rename temp file to sources.json: {}", e))?;
     
+    crate::web_scraper::set_extraction_rules(config_to_save.extraction_rules);
+    crate::web_scraper::set_proxy_config(config_to_save.proxy);
+    crate::web_scraper::set_search_apis(config_to_save.search_apis);
+    
     log::info!("Sources config salvo com sucesso em {:?}", config_path);
     Ok(())
 }
 
+
+/// Formato de importação/exportação das categorias
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum SourcesFileFormat {
+    Opml,
+    Json,
+}
+
+/// Arquivo JSON exportado (também aceita um sources.json completo na importação)
+#[derive(Serialize, Deserialize)]
+struct SourcesExport {
+    #[serde(default = "default_version")]
+    version: u32,
+    categories: Vec<SourceCategory>,
+}
+
+/// Resultado de uma importação
+#[derive(Serialize, Clone, Debug, Default, PartialEq)]
+pub struct SourcesImportSummary {
+    pub added: usize,
+    pub updated: usize,
+    pub sites: usize,
+}
+
+/// Categoria para outlines OPML sem pasta
+const IMPORTED_CATEGORY_ID: &str = "importadas";
+
+fn xml_escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
+
+fn xml_unescape(text: &str) -> String {
+    text.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&apos;", "'")
+        .replace("&#39;", "'")
+        .replace("&amp;", "&")
+}
+
+/// Categorias em OPML 2.0: uma pasta por categoria, um link por site
+/// (id, peso e estado vão em atributos próprios para a volta ser sem perdas)
+pub fn to_opml(categories: &[SourceCategory]) -> String {
+    let mut opml = String::from(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<opml version=\"2.0\">\n  <head>\n    <title>OllaHub - Fontes de busca</title>\n  </head>\n  <body>\n",
+    );
+    for category in categories {
+        opml.push_str(&format!(
+            "    <outline text=\"{}\" ollahubId=\"{}\" ollahubWeight=\"{}\" ollahubEnabled=\"{}\">\n",
+            xml_escape(&category.name),
+            xml_escape(&category.id),
+            category.weight,
+            category.enabled
+        ));
+        for site in &category.base_sites {
+            opml.push_str(&format!(
+                "      <outline text=\"{}\" type=\"link\" url=\"https://{}\"/>\n",
+                xml_escape(site),
+                xml_escape(site)
+            ));
+        }
+        opml.push_str("    </outline>\n");
+    }
+    opml.push_str("  </body>\n</opml>\n");
+    opml
+}
+
+/// Site no formato de `base_sites` (host + caminho, sem esquema nem "www.")
+fn site_from_url(url: &str) -> Option<String> {
+    let trimmed = url.trim();
+    let parsed = url::Url::parse(trimmed)
+        .or_else(|_| url::Url::parse(&format!("https://{}", trimmed)))
+        .ok()?;
+    let host = parsed.host_str()?.trim_start_matches("www.").to_lowercase();
+    let path = parsed.path().trim_end_matches('/');
+    Some(format!("{}{}", host, path))
+}
+
+fn slug(name: &str) -> String {
+    let slug: String = name
+        .to_lowercase()
+        .chars()
+        .map(|c| if c.is_alphanumeric() { c } else { '-' })
+        .collect();
+    let slug = slug.split('-').filter(|part| !part.is_empty()).collect::<Vec<_>>().join("-");
+    if slug.is_empty() { IMPORTED_CATEGORY_ID.to_string() } else { slug }
+}
+
+/// Lê categorias de um OPML: outlines com filhos viram categorias e os links dos filhos
+/// (`xmlUrl`, `htmlUrl` ou `url`) viram sites; links soltos vão para "Importadas"
+pub fn parse_opml(content: &str) -> Result<Vec<SourceCategory>, String> {
+    if !content.contains("<opml") {
+        return Err("Arquivo não é um OPML".to_string());
+    }
+    let tag = regex::Regex::new(r"(?s)<(/?)outline\b(.*?)(/?)>").expect("valid outline regex");
+    let attribute = regex::Regex::new(r#"([\w:-]+)\s*=\s*(?:"([^"]*)"|'([^']*)')"#).expect("valid attribute regex");
+
+    let mut categories: Vec<SourceCategory> = Vec::new();
+    let mut loose = Vec::new();
+    // Pilha das outlines abertas: índice da categoria, quando for uma pasta
+    let mut open: Vec<Option<usize>> = Vec::new();
+
+    for caps in tag.captures_iter(content) {
+        if &caps[1] == "/" {
+            open.pop();
+            continue;
+        }
+        let attrs: HashMap<String, String> = attribute
+            .captures_iter(&caps[2])
+            .map(|a| {
+                let value = a.get(2).or_else(|| a.get(3)).map(|v| v.as_str()).unwrap_or_default();
+                (a[1].to_string(), xml_unescape(value))
+            })
+            .collect();
+        let self_closing = &caps[3] == "/";
+        let link = ["xmlUrl", "htmlUrl", "url"].iter().find_map(|key| attrs.get(*key)).and_then(|u| site_from_url(u));
+
+        match link {
+            Some(site) => {
+                match open.iter().rev().find_map(|entry| *entry) {
+                    Some(index) => categories[index].base_sites.push(site),
+                    None => loose.push(site),
+                }
+                if !self_closing {
+                    open.push(None);
+                }
+            }
+            None if !self_closing => {
+                let name = attrs.get("text").or_else(|| attrs.get("title")).cloned().unwrap_or_default();
+                let name = if name.trim().is_empty() { "Importadas".to_string() } else { name.trim().to_string() };
+                categories.push(SourceCategory {
+                    id: attrs.get("ollahubId").cloned().unwrap_or_else(|| slug(&name)),
+                    name,
+                    base_sites: Vec::new(),
+                    enabled: attrs.get("ollahubEnabled").map(|v| v != "false").unwrap_or(true),
+                    weight: attrs
+                        .get("ollahubWeight")
+                        .and_then(|w| w.parse().ok())
+                        .unwrap_or_else(default_weight)
+                        .clamp(1, MAX_CATEGORY_WEIGHT),
+                });
+                open.push(Some(categories.len() - 1));
+            }
+            None => {}
+        }
+    }
+
+    if !loose.is_empty() {
+        categories.push(SourceCategory {
+            id: IMPORTED_CATEGORY_ID.to_string(),
+            name: "Importadas".to_string(),
+            base_sites: loose,
+            enabled: true,
+            weight: default_weight(),
+        });
+    }
+    categories.retain(|c| !c.base_sites.is_empty());
+    if categories.is_empty() {
+        return Err("Nenhum site encontrado no OPML".to_string());
+    }
+    Ok(categories)
+}
+
+/// Serializa as categorias no formato pedido
+pub fn export_categories(categories: &[SourceCategory], format: SourcesFileFormat) -> Result<String, String> {
+    match format {
+        SourcesFileFormat::Opml => Ok(to_opml(categories)),
+        SourcesFileFormat::Json => serde_json::to_string_pretty(&SourcesExport {
+            version: default_version(),
+            categories: categories.to_vec(),
+        })
+        .map_err(|e| format!("Failed to serialize sources export: {}", e)),
+    }
+}
+
+/// Lê categorias de um arquivo OPML ou JSON (detectado pelo conteúdo)
+pub fn import_categories(content: &str) -> Result<Vec<SourceCategory>, String> {
+    if content.trim_start().starts_with('{') {
+        let export: SourcesExport = serde_json::from_str(content)
+            .map_err(|e| format!("Failed to parse sources file: {}", e))?;
+        Ok(export.categories)
+    } else {
+        parse_opml(content)
+    }
+}
+
+/// Mescla as categorias importadas (mesmo id: sites somados, nome/peso/estado do arquivo);
+/// com `replace`, as categorias atuais são descartadas
+pub fn merge_categories(existing: &mut Vec<SourceCategory>, imported: Vec<SourceCategory>, replace: bool) -> SourcesImportSummary {
+    if replace {
+        existing.clear();
+    }
+    let mut summary = SourcesImportSummary::default();
+    for category in imported {
+        summary.sites += category.base_sites.len();
+        match existing.iter_mut().find(|c| c.id == category.id) {
+            Some(current) => {
+                for site in category.base_sites {
+                    if !current.base_sites.iter().any(|s| s.eq_ignore_ascii_case(&site)) {
+                        current.base_sites.push(site);
+                    }
+                }
+                current.name = category.name;
+                current.weight = category.weight;
+                current.enabled = category.enabled;
+                summary.updated += 1;
+            }
+            None => {
+                existing.push(category);
+                summary.added += 1;
+            }
+        }
+    }
+    summary
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_opml_round_trip_and_merge() {
+        let mut categories = SourcesConfig::default().categories;
+        categories[0].weight = 5;
+        categories[0].name = "Acadêmico & <Papers>".to_string();
+        let parsed = parse_opml(&to_opml(&categories)).unwrap();
+        assert_eq!(parsed.len(), categories.len());
+        assert_eq!(parsed[0].name, "Acadêmico & <Papers>");
+        assert_eq!((parsed[0].id.as_str(), parsed[0].weight), ("academico", 5));
+        assert_eq!(parsed[1].base_sites, categories[1].base_sites);
+
+        let feeds = r#"<?xml version="1.0"?><opml version="1.0"><body>
+            <outline text="Blogs de IA"><outline text="A" type="rss" xmlUrl="https://www.example.org/feed/"/></outline>
+            <outline text="Solto" htmlUrl="http://news.example.com"/>
+        </body></opml>"#;
+        let imported = parse_opml(feeds).unwrap();
+        assert_eq!(imported[0].id, "blogs-de-ia");
+        assert_eq!(imported[0].base_sites, vec!["example.org/feed".to_string()]);
+        assert_eq!(imported[1].base_sites, vec!["news.example.com".to_string()]);
+
+        let summary = merge_categories(&mut categories, imported, false);
+        assert_eq!(summary, SourcesImportSummary { added: 2, updated: 0, sites: 2 });
+    }
+}
@@ -0,0 +1,380 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use tauri::{AppHandle, Manager};
+
+/// Registro padrão do Ollama (omitido nos nomes: `llama3:latest` em vez do caminho completo)
+const DEFAULT_REGISTRY: &str = "registry.ollama.ai";
+const DEFAULT_NAMESPACE: &str = "library";
+
+/// Espaço ocupado por uma área de armazenamento
+#[derive(Serialize, Clone, Debug)]
+pub struct StorageCategory {
+    pub id: &'static str,
+    pub label: &'static str,
+    pub path: Option<String>,
+    pub bytes: u64,
+    pub files: u64,
+    /// Comando que libera espaço nesta área (a UI oferece a ação ao lado do tamanho)
+    pub cleanup_command: Option<&'static str>,
+}
+
+/// Blob (camada) de um modelo do Ollama
+#[derive(Serialize, Clone, Debug)]
+pub struct BlobUsage {
+    pub digest: String,
+    pub media_type: String,
+    pub bytes: u64,
+    /// Usado também por outro modelo (não libera espaço ao remover só este)
+    pub shared: bool,
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct ModelUsage {
+    pub name: String,
+    pub bytes: u64,
+    /// Espaço liberado ao remover o modelo (blobs exclusivos)
+    pub unique_bytes: u64,
+    pub modified_at: Option<DateTime<Utc>>,
+    pub pinned: bool,
+    pub blobs: Vec<BlobUsage>,
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct StorageUsage {
+    pub total_bytes: u64,
+    pub categories: Vec<StorageCategory>,
+    pub models_dir: Option<String>,
+    /// Modelos do maior para o menor
+    pub models: Vec<ModelUsage>,
+    /// Blobs sem manifest (downloads interrompidos ou modelos removidos pela metade)
+    pub orphan_blobs: usize,
+    pub orphan_blobs_bytes: u64,
+}
+
+#[derive(Deserialize)]
+struct ManifestLayer {
+    digest: String,
+    #[serde(default, rename = "mediaType")]
+    media_type: String,
+    #[serde(default)]
+    size: u64,
+}
+
+#[derive(Deserialize)]
+struct Manifest {
+    #[serde(default)]
+    config: Option<ManifestLayer>,
+    #[serde(default)]
+    layers: Vec<ManifestLayer>,
+}
+
+/// Diretório de modelos do Ollama: `OLLAMA_MODELS` de `ollama_env` (o do servidor iniciado
+/// pelo app), o do ambiente do processo ou `~/.ollama/models`
+pub fn ollama_models_dir(settings: &crate::settings::AppSettings) -> Option<PathBuf> {
+    let configured = settings.ollama_env.get("OLLAMA_MODELS").map(|dir| dir.trim()).filter(|dir| !dir.is_empty());
+    if let Some(dir) = configured {
+        return Some(PathBuf::from(dir));
+    }
+    match std::env::var_os("OLLAMA_MODELS") {
+        Some(dir) if !dir.is_empty() => Some(PathBuf::from(dir)),
+        _ => dirs::home_dir().map(|home| home.join(".ollama").join("models")),
+    }
+}
+
+/// Tamanho total e quantidade de arquivos de um diretório (0 se não existir)
+pub fn dir_size(path: &Path) -> (u64, u64) {
+    walkdir::WalkDir::new(path)
+        .into_iter()
+        .filter_map(|e| e.ok())
+        .filter(|e| e.file_type().is_file())
+        .fold((0, 0), |(bytes, files), e| {
+            (bytes + e.metadata().map(|m| m.len()).unwrap_or(0), files + 1)
+        })
+}
+
+fn file_size(path: &Path) -> u64 {
+    fs::metadata(path).map(|m| m.len()).unwrap_or(0)
+}
+
+/// Nome do modelo a partir do caminho do manifest relativo a `manifests/`
+/// (`registry.ollama.ai/library/llama3/latest` → `llama3:latest`)
+pub fn model_name_from_manifest(relative: &Path) -> Option<String> {
+    let parts: Vec<String> = relative
+        .components()
+        .map(|c| c.as_os_str().to_string_lossy().to_string())
+        .collect();
+    let [registry, namespace, model, tag] = parts.as_slice() else {
+        return None;
+    };
+    let name = if registry == DEFAULT_REGISTRY && namespace == DEFAULT_NAMESPACE {
+        model.clone()
+    } else if registry == DEFAULT_REGISTRY {
+        format!("{}/{}", namespace, model)
+    } else {
+        format!("{}/{}/{}", registry, namespace, model)
+    };
+    Some(format!("{}:{}", name, tag))
+}
+
+/// Caminho do manifest relativo a `manifests/` a partir do nome do modelo
+/// (inverso de `model_name_from_manifest`; sem tag usa `latest`)
+pub fn manifest_relative_path(name: &str) -> Option<PathBuf> {
+    let (model, tag) = match name.rsplit_once(':') {
+        Some((model, tag)) if !tag.contains('/') => (model, tag),
+        _ => (name, "latest"),
+    };
+    let parts: Vec<&str> = model.split('/').collect();
+    let parts = match parts.as_slice() {
+        [model] => vec![DEFAULT_REGISTRY, DEFAULT_NAMESPACE, *model],
+        [namespace, model] => vec![DEFAULT_REGISTRY, *namespace, *model],
+        [registry, namespace, model] => vec![*registry, *namespace, *model],
+        _ => return None,
+    };
+    let valid = |part: &str| !part.is_empty() && part != "." && part != ".." && !part.contains('\\');
+    if !parts.iter().chain(std::iter::once(&tag)).all(|part| valid(part)) {
+        return None;
+    }
+    Some(parts.iter().chain(std::iter::once(&tag)).collect())
+}
+
+/// Arquivo do blob em `blobs/` (`sha256:abc` → `sha256-abc`)
+pub fn blob_file_name(digest: &str) -> String {
+    digest.replace(':', "-")
+}
+
+/// Modelos e blobs órfãos do diretório do Ollama
+fn scan_models(models_dir: &Path, pinned: &dyn Fn(&str) -> bool) -> (Vec<ModelUsage>, usize, u64) {
+    let manifests_dir = models_dir.join("manifests");
+    let blobs_dir = models_dir.join("blobs");
+
+    let mut parsed: Vec<(String, Option<DateTime<Utc>>, Vec<ManifestLayer>)> = Vec::new();
+    for entry in walkdir::WalkDir::new(&manifests_dir).into_iter().filter_map(|e| e.ok()) {
+        if !entry.file_type().is_file() {
+            continue;
+        }
+        let Some(name) = entry
+            .path()
+            .strip_prefix(&manifests_dir)
+            .ok()
+            .and_then(model_name_from_manifest)
+        else {
+            continue;
+        };
+        let manifest: Manifest = match fs::read_to_string(entry.path())
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+        {
+            Some(manifest) => manifest,
+            None => {
+                log::debug!("[Storage] Manifest ilegível: {:?}", entry.path());
+                continue;
+            }
+        };
+        let modified_at = entry
+            .metadata()
+            .ok()
+            .and_then(|m| m.modified().ok())
+            .map(DateTime::<Utc>::from);
+        let layers = manifest.config.into_iter().chain(manifest.layers).collect();
+        parsed.push((name, modified_at, layers));
+    }
+
+    // Quantos modelos referenciam cada blob
+    let mut references: HashMap<String, usize> = HashMap::new();
+    for (_, _, layers) in &parsed {
+        let unique: HashSet<&str> = layers.iter().map(|l| l.digest.as_str()).collect();
+        for digest in unique {
+            *references.entry(digest.to_string()).or_default() += 1;
+        }
+    }
+
+    let mut models: Vec<ModelUsage> = parsed
+        .into_iter()
+        .map(|(name, modified_at, layers)| {
+            let blobs: Vec<BlobUsage> = layers
+                .into_iter()
+                .map(|layer| {
+                    // Tamanho real do arquivo (o manifest pode estar desatualizado)
+                    let on_disk = file_size(&blobs_dir.join(blob_file_name(&layer.digest)));
+                    BlobUsage {
+                        shared: references.get(&layer.digest).copied().unwrap_or(0) > 1,
+                        bytes: if on_disk > 0 { on_disk } else { layer.size },
+                        digest: layer.digest,
+                        media_type: layer.media_type,
+                    }
+                })
+                .collect();
+            ModelUsage {
+                pinned: pinned(&name),
+                bytes: blobs.iter().map(|b| b.bytes).sum(),
+                unique_bytes: blobs.iter().filter(|b| !b.shared).map(|b| b.bytes).sum(),
+                name,
+                modified_at,
+                blobs,
+            }
+        })
+        .collect();
+    models.sort_by(|a, b| b.bytes.cmp(&a.bytes));
+
+    let referenced: HashSet<String> = references.keys().map(|d| blob_file_name(d)).collect();
+    let (mut orphan_blobs, mut orphan_bytes) = (0, 0);
+    if let Ok(entries) = fs::read_dir(&blobs_dir) {
+        for entry in entries.flatten() {
+            let file_name = entry.file_name().to_string_lossy().to_string();
+            if !referenced.contains(&file_name) {
+                orphan_blobs += 1;
+                orphan_bytes += entry.metadata().map(|m| m.len()).unwrap_or(0);
+            }
+        }
+    }
+
+    (models, orphan_blobs, orphan_bytes)
+}
+
+fn category(
+    id: &'static str,
+    label: &'static str,
+    path: &Path,
+    cleanup_command: Option<&'static str>,
+) -> StorageCategory {
+    let (bytes, files) = dir_size(path);
+    StorageCategory {
+        id,
+        label,
+        path: Some(crate::paths::without_verbatim_prefix(path).to_string_lossy().to_string()),
+        bytes,
+        files,
+        cleanup_command,
+    }
+}
+
+/// Levantamento do espaço usado pelo app e pelos modelos do Ollama
+pub fn collect(app_handle: &AppHandle) -> Result<StorageUsage, String> {
+    let app_data_dir = app_handle
+        .path()
+        .app_data_dir()
+        .map_err(|e| format!("Failed to get app data dir: {}", e))?;
+    let settings = crate::settings::current(app_handle);
+
+    let mut categories = Vec::new();
+
+    // Banco SQLite com WAL e SHM (o WAL cresce até o próximo checkpoint)
+    let db_path = app_data_dir.join("ollahub.db");
+    let db_files = [db_path.clone(), db_path.with_extension("db-wal"), db_path.with_extension("db-shm")];
+    let db_sizes: Vec<u64> = db_files.iter().map(|p| file_size(p)).collect();
+    categories.push(StorageCategory {
+        id: "database",
+        label: "Banco de dados (SQLite + WAL)",
+        path: Some(crate::paths::without_verbatim_prefix(&db_path).to_string_lossy().to_string()),
+        bytes: db_sizes.iter().sum(),
+        files: db_sizes.iter().filter(|s| **s > 0).count() as u64,
+        cleanup_command: Some("run_db_maintenance"),
+    });
+
+    // Cache do scraper fica dentro do banco: tamanho aproximado pelo conteúdo guardado
+    let (cache_pages, cache_bytes) = crate::db::Database::new(app_handle)
+        .and_then(|db| db.scrape_cache_usage())
+        .unwrap_or_else(|e| {
+            log::warn!("[Storage] Falha ao medir o cache do scraper: {}", e);
+            (0, 0)
+        });
+    categories.push(StorageCategory {
+        id: "scrape_cache",
+        label: "Cache de páginas (dentro do banco)",
+        path: None,
+        bytes: cache_bytes,
+        files: cache_pages,
+        cleanup_command: Some("clear_scrape_cache"),
+    });
+
+    categories.push(category("chats", "Conversas (JSON)", &crate::get_chats_dir(app_handle)?, Some("archive_old_sessions")));
+    categories.push(category("archive", "Arquivo frio", &app_data_dir.join("archive"), None));
+    categories.push(category("retention", "Sessões removidas pela retenção", &app_data_dir.join("retention"), None));
+    categories.push(category("audit", "Exportações do log de auditoria", &app_data_dir.join("audit"), None));
+    categories.push(category("logs", "Logs", &crate::logging::logs_dir(app_handle)?, None));
+    categories.push(category("installers", "Instaladores baixados", &app_data_dir.join("installers"), None));
+    categories.push(category("embeddings", "Modelo de embeddings", &app_data_dir.join("models"), None));
+    categories.push(category("reports", "Relatórios das tasks", &app_data_dir.join("reports"), None));
+
+    let models_dir = ollama_models_dir(&settings);
+    let (models, orphan_blobs, orphan_blobs_bytes) = match &models_dir {
+        Some(dir) if dir.exists() => scan_models(dir, &|name| settings.is_model_pinned(name)),
+        _ => (Vec::new(), 0, 0),
+    };
+    categories.push(StorageCategory {
+        id: "ollama_models",
+        label: "Modelos do Ollama",
+        path: models_dir.as_ref().map(|d| d.to_string_lossy().to_string()),
+        bytes: models.iter().map(|m| m.unique_bytes).sum::<u64>()
+            + shared_bytes(&models)
+            + orphan_blobs_bytes,
+        files: models.iter().map(|m| m.blobs.len() as u64).sum(),
+        cleanup_command: Some("delete_model"),
+    });
+
+    // O cache do scraper já está contado no tamanho do banco
+    let total_bytes = categories
+        .iter()
+        .filter(|c| c.id != "scrape_cache")
+        .map(|c| c.bytes)
+        .sum();
+
+    Ok(StorageUsage {
+        total_bytes,
+        categories,
+        models_dir: models_dir.map(|d| d.to_string_lossy().to_string()),
+        models,
+        orphan_blobs,
+        orphan_blobs_bytes,
+    })
+}
+
+/// Blobs compartilhados contados uma única vez
+fn shared_bytes(models: &[ModelUsage]) -> u64 {
+    let mut seen = HashSet::new();
+    models
+        .iter()
+        .flat_map(|m| m.blobs.iter())
+        .filter(|b| b.shared && seen.insert(b.digest.as_str()))
+        .map(|b| b.bytes)
+        .sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_manifest_names() {
+        assert_eq!(
+            model_name_from_manifest(Path::new("registry.ollama.ai/library/llama3/latest")).as_deref(),
+            Some("llama3:latest")
+        );
+        assert_eq!(
+            model_name_from_manifest(Path::new("registry.ollama.ai/user/custom/q4")).as_deref(),
+            Some("user/custom:q4")
+        );
+        assert_eq!(
+            model_name_from_manifest(Path::new("hf.co/org/repo/Q4_K_M")).as_deref(),
+            Some("hf.co/org/repo:Q4_K_M")
+        );
+        assert!(model_name_from_manifest(Path::new("llama3/latest")).is_none());
+        assert_eq!(
+            manifest_relative_path("user/custom:q4"),
+            Some(PathBuf::from("registry.ollama.ai/user/custom/q4"))
+        );
+        assert_eq!(
+            manifest_relative_path("llama3"),
+            Some(PathBuf::from("registry.ollama.ai/library/llama3/latest"))
+        );
+        assert!(manifest_relative_path("../../etc:passwd").is_none());
+        assert_eq!(blob_file_name("sha256:abc"), "sha256-abc");
+
+        let mut settings = crate::settings::AppSettings::default();
+        settings.ollama_env.insert("OLLAMA_MODELS".to_string(), "/data/models".to_string());
+        assert_eq!(ollama_models_dir(&settings), Some(PathBuf::from("/data/models")));
+    }
+}
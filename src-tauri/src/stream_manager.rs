@@ -0,0 +1,300 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tauri::AppHandle;
+
+/// Espera máxima pelo Ollama voltar a responder após uma queda
+const RECONNECT_TIMEOUT: Duration = Duration::from_secs(20);
+/// Intervalo entre verificações enquanto aguarda o Ollama
+const RECONNECT_POLL: Duration = Duration::from_millis(500);
+/// Espera antes da primeira tentativa (dobra a cada nova tentativa)
+const RETRY_BASE_DELAY: Duration = Duration::from_secs(1);
+
+/// O que fazer com a resposta parcial ao retomar o streaming
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum PartialAnswerPolicy {
+    /// Reenvia a conversa e gera a resposta do zero (o frontend limpa o texto parcial)
+    #[default]
+    Discard,
+    /// Reenvia a conversa com a resposta parcial como prefixo para o modelo continuar
+    Keep,
+}
+
+/// Retomada automática do chat quando o Ollama reinicia ou a conexão cai no meio da resposta
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct StreamRetrySettings {
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+    #[serde(default = "default_max_attempts")]
+    pub max_attempts: u32,
+    /// Inicia o Ollama se ele não estiver rodando após a queda
+    #[serde(default = "default_enabled")]
+    pub auto_start_ollama: bool,
+    #[serde(default)]
+    pub partial_answer: PartialAnswerPolicy,
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+fn default_max_attempts() -> u32 {
+    3
+}
+
+impl Default for StreamRetrySettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            max_attempts: default_max_attempts(),
+            auto_start_ollama: true,
+            partial_answer: PartialAnswerPolicy::default(),
+        }
+    }
+}
+
+impl StreamRetrySettings {
+    pub fn validate(&self, errors: &mut Vec<String>) {
+        if self.max_attempts > 10 {
+            errors.push("stream_retry.max_attempts deve ser no máximo 10".to_string());
+        }
+    }
+
+    /// Se a tentativa `attempt` (a partir de 1) ainda é permitida
+    pub fn allows(&self, attempt: u32) -> bool {
+        self.enabled && attempt <= self.max_attempts
+    }
+}
+
+/// Falha ao enviar ou ler o streaming do Ollama
+#[derive(Debug, Clone)]
+pub enum StreamError {
+    /// Conexão recusada/interrompida ou servidor reiniciando: vale tentar de novo
+    Disconnected(String),
+    /// Erro da requisição em si (modelo inexistente, payload inválido)
+    Fatal(String),
+}
+
+impl StreamError {
+    pub fn message(&self) -> &str {
+        match self {
+            StreamError::Disconnected(msg) | StreamError::Fatal(msg) => msg,
+        }
+    }
+
+    /// Classifica um erro do reqwest (envio ou leitura do corpo)
+    pub fn from_reqwest(context: &str, e: &reqwest::Error) -> Self {
+        let msg = format!("{}: {}", context, e);
+        if e.is_connect() || e.is_timeout() || e.is_body() || e.is_request() || e.is_decode() {
+            StreamError::Disconnected(msg)
+        } else {
+            StreamError::Fatal(msg)
+        }
+    }
+
+    /// Status HTTP de erro: 502/503/504 indicam servidor reiniciando ou sobrecarregado
+    pub fn from_status(status: reqwest::StatusCode) -> Self {
+        let msg = format!("Ollama returned status: {}", status);
+        match status.as_u16() {
+            502..=504 => StreamError::Disconnected(msg),
+            _ => StreamError::Fatal(msg),
+        }
+    }
+}
+
+/// Evento `chat-retry` emitido antes de cada nova tentativa
+#[derive(Serialize, Clone, Debug)]
+pub struct ChatRetryEvent {
+    pub session_id: String,
+    pub attempt: u32,
+    pub max_attempts: u32,
+    pub reason: String,
+    /// O frontend deve limpar o texto parcial exibido (política `discard`)
+    pub discard_partial: bool,
+}
+
+/// Resposta em geração (item de `list_active_streams`)
+#[derive(Serialize, Clone, Debug)]
+pub struct ActiveStream {
+    pub session_id: String,
+    pub model: String,
+    pub started_at: DateTime<Utc>,
+    pub tokens: u64,
+}
+
+/// Evento `active-streams-changed` ao iniciar ou terminar uma resposta
+#[derive(Serialize, Clone, Debug)]
+pub struct ActiveStreamsChangedEvent {
+    pub streams: Vec<ActiveStream>,
+}
+
+struct StreamEntry {
+    session_id: String,
+    model: String,
+    started_at: DateTime<Utc>,
+    tokens: Arc<AtomicU64>,
+}
+
+/// Registro dos streamings ativos: no máximo um por sessão, limitado por `max_concurrent_streams`
+static ACTIVE_STREAMS: Mutex<Vec<StreamEntry>> = Mutex::new(Vec::new());
+
+/// Mantido enquanto o streaming da sessão roda; ao ser solto remove a sessão do registro
+pub struct StreamGuard {
+    app_handle: AppHandle,
+    session_id: String,
+    tokens: Arc<AtomicU64>,
+}
+
+impl StreamGuard {
+    pub fn add_token(&self) {
+        self.tokens.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+impl Drop for StreamGuard {
+    fn drop(&mut self) {
+        if let Ok(mut streams) = ACTIVE_STREAMS.lock() {
+            streams.retain(|entry| entry.session_id != self.session_id);
+        }
+        let _ = crate::events::emit(&self.app_handle, &ActiveStreamsChangedEvent { streams: list() });
+    }
+}
+
+/// Registra o streaming da sessão, recusando uma segunda resposta simultânea na mesma
+/// sessão ou acima do limite de gerações em paralelo
+pub fn register(app_handle: &AppHandle, session_id: &str, model: &str, max_concurrent: usize) -> Result<StreamGuard, String> {
+    let tokens = Arc::new(AtomicU64::new(0));
+    {
+        let mut streams = ACTIVE_STREAMS.lock()
+            .map_err(|e| format!("Failed to lock stream registry: {}", e))?;
+        if streams.iter().any(|entry| entry.session_id == session_id) {
+            return Err("Esta conversa já está gerando uma resposta".to_string());
+        }
+        if streams.len() >= max_concurrent {
+            return Err(format!("Limite de {} respostas simultâneas atingido", max_concurrent));
+        }
+        streams.push(StreamEntry {
+            session_id: session_id.to_string(),
+            model: model.to_string(),
+            started_at: Utc::now(),
+            tokens: tokens.clone(),
+        });
+    }
+    let _ = crate::events::emit(app_handle, &ActiveStreamsChangedEvent { streams: list() });
+    Ok(StreamGuard {
+        app_handle: app_handle.clone(),
+        session_id: session_id.to_string(),
+        tokens,
+    })
+}
+
+pub fn list() -> Vec<ActiveStream> {
+    ACTIVE_STREAMS
+        .lock()
+        .map(|streams| {
+            streams
+                .iter()
+                .map(|entry| ActiveStream {
+                    session_id: entry.session_id.clone(),
+                    model: entry.model.clone(),
+                    started_at: entry.started_at,
+                    tokens: entry.tokens.load(Ordering::Relaxed),
+                })
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Envia a requisição de streaming ao Ollama
+pub async fn send(
+    client: &reqwest::Client,
+    url: &str,
+    request: &serde_json::Value,
+) -> Result<reqwest::Response, StreamError> {
+    let response = client
+        .post(url)
+        .json(request)
+        .send()
+        .await
+        .map_err(|e| StreamError::from_reqwest("Failed to send request to Ollama", &e))?;
+    if response.status().is_success() {
+        Ok(response)
+    } else {
+        Err(StreamError::from_status(response.status()))
+    }
+}
+
+/// Espera antes da tentativa `attempt` (1s, 2s, 4s...)
+pub fn backoff(attempt: u32) -> Duration {
+    RETRY_BASE_DELAY * 2u32.saturating_pow(attempt.saturating_sub(1).min(5))
+}
+
+/// Aguarda o Ollama voltar (iniciando-o se configurado) antes de uma nova tentativa
+pub async fn reconnect(app_handle: &AppHandle, settings: &StreamRetrySettings, attempt: u32) -> Result<(), String> {
+    tokio::time::sleep(backoff(attempt)).await;
+
+    if crate::check_ollama_running(app_handle.clone()).await {
+        return Ok(());
+    }
+
+    if settings.auto_start_ollama && crate::check_ollama_installed() {
+        log::info!("[Stream] Ollama parado após a queda; iniciando novamente");
+        crate::start_ollama_server(app_handle.clone())?;
+    }
+
+    let deadline = tokio::time::Instant::now() + RECONNECT_TIMEOUT;
+    while tokio::time::Instant::now() < deadline {
+        tokio::time::sleep(RECONNECT_POLL).await;
+        if crate::check_ollama_running(app_handle.clone()).await {
+            return Ok(());
+        }
+    }
+    Err("O Ollama não voltou a responder após a queda da conexão".to_string())
+}
+
+/// Requisição para a nova tentativa: a conversa original, com a resposta parcial como
+/// mensagem do assistente quando a política é `keep` (o Ollama continua a partir dela)
+pub fn resume_request(
+    base_request: &serde_json::Value,
+    partial: &str,
+    policy: PartialAnswerPolicy,
+) -> serde_json::Value {
+    let mut request = base_request.clone();
+    if policy == PartialAnswerPolicy::Keep && !partial.is_empty() {
+        if let Some(messages) = request.get_mut("messages").and_then(|m| m.as_array_mut()) {
+            messages.push(serde_json::json!({ "role": "assistant", "content": partial }));
+        }
+    }
+    request
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resume_request_and_backoff() {
+        let base = serde_json::json!({
+            "model": "llama3",
+            "messages": [{ "role": "user", "content": "oi" }],
+            "stream": true
+        });
+        assert_eq!(resume_request(&base, "Olá, tu", PartialAnswerPolicy::Discard), base);
+        let kept = resume_request(&base, "Olá, tu", PartialAnswerPolicy::Keep);
+        let messages = kept["messages"].as_array().unwrap();
+        assert_eq!(messages.len(), 2);
+        assert_eq!(messages[1]["role"], "assistant");
+        assert_eq!(resume_request(&base, "", PartialAnswerPolicy::Keep), base);
+
+        assert_eq!(backoff(1), Duration::from_secs(1));
+        assert_eq!(backoff(3), Duration::from_secs(4));
+
+        let settings = StreamRetrySettings::default();
+        assert!(settings.allows(3));
+        assert!(!settings.allows(4));
+        assert!(!StreamRetrySettings { enabled: false, ..settings }.allows(1));
+    }
+}
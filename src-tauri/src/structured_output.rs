@@ -0,0 +1,97 @@
+//! Saída estruturada: `format` do /api/chat (`"json"` ou um JSON Schema)
+//!
+//! O Ollama restringe a geração ao formato pedido, mas modelos menores ainda podem errar
+//! o schema; a resposta final é validada aqui e, se inválida, pedida de novo uma vez.
+
+use crate::ollama_client::OllamaClient;
+use jsonschema::JSONSchema;
+use serde_json::Value;
+
+/// Confere o parâmetro `format` antes do envio
+pub fn validate_format(format: &Value) -> Result<(), String> {
+    match format {
+        Value::String(s) if s == "json" => Ok(()),
+        Value::Object(_) => JSONSchema::compile(format)
+            .map(|_| ())
+            .map_err(|e| format!("JSON Schema inválido: {}", e)),
+        _ => Err("format deve ser \"json\" ou um JSON Schema (objeto)".to_string()),
+    }
+}
+
+/// Interpreta a resposta como JSON (tolerando cerca ```json) e valida contra o schema
+pub fn parse(format: &Value, content: &str) -> Result<Value, String> {
+    let trimmed = content.trim();
+    let body = trimmed
+        .strip_prefix("```json")
+        .or_else(|| trimmed.strip_prefix("```"))
+        .and_then(|rest| rest.trim_end().strip_suffix("```"))
+        .unwrap_or(trimmed);
+    let value: Value = serde_json::from_str(body.trim())
+        .map_err(|e| format!("Resposta não é JSON válido: {}", e))?;
+
+    if format.is_object() {
+        let schema = JSONSchema::compile(format)
+            .map_err(|e| format!("JSON Schema inválido: {}", e))?;
+        if let Err(errors) = schema.validate(&value) {
+            let details: Vec<String> = errors
+                .map(|e| {
+                    let path = e.instance_path.to_string();
+                    if path.is_empty() { e.to_string() } else { format!("{}: {}", path, e) }
+                })
+                .collect();
+            return Err(format!("Resposta fora do schema: {}", details.join("; ")));
+        };
+    }
+    Ok(value)
+}
+
+/// Nova tentativa sem streaming, mostrando ao modelo a resposta inválida e o erro
+pub async fn retry(client: &OllamaClient, request: &Value, invalid: &str, error: &str) -> Result<String, String> {
+    let mut request = request.clone();
+    request["stream"] = Value::Bool(false);
+    if let Some(messages) = request.get_mut("messages").and_then(|m| m.as_array_mut()) {
+        messages.push(serde_json::json!({ "role": "assistant", "content": invalid }));
+        messages.push(serde_json::json!({
+            "role": "user",
+            "content": format!("{}. Responda novamente apenas com o JSON corrigido, sem texto adicional.", error)
+        }));
+    }
+
+    let model = request["model"].as_str().unwrap_or_default().to_string();
+    crate::audit::record(crate::audit::AuditKind::ModelPrompt, "structured_retry", &model, Some(&request["messages"].to_string()));
+    let response = client.client
+        .post(format!("{}/api/chat", client.base_url))
+        .json(&request)
+        .send()
+        .await
+        .map_err(|e| format!("Failed to retry structured output: {}", e))?;
+    if !response.status().is_success() {
+        return Err(format!("Failed to retry structured output: HTTP {}", response.status()));
+    }
+    let body: Value = response.json()
+        .await
+        .map_err(|e| format!("Failed to parse retry response: {}", e))?;
+    let content = body["message"]["content"].as_str().unwrap_or_default().to_string();
+    crate::audit::record(crate::audit::AuditKind::ModelResponse, "structured_retry", &model, Some(&content));
+    Ok(content)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_against_schema() {
+        let schema = serde_json::json!({
+            "type": "object",
+            "properties": { "age": { "type": "integer" } },
+            "required": ["age"]
+        });
+        assert!(validate_format(&schema).is_ok());
+        assert!(validate_format(&Value::String("xml".to_string())).is_err());
+
+        assert_eq!(parse(&schema, "```json\n{\"age\": 3}\n```").unwrap()["age"], 3);
+        assert!(parse(&schema, "{\"age\": \"três\"}").unwrap_err().contains("/age"));
+        assert!(parse(&Value::String("json".to_string()), "idade: 3").is_err());
+    }
+}
@@ -0,0 +1,408 @@
+//! Transcrição de voz (speech-to-text) com whisper.cpp
+//!
+//! Executa o `whisper-cli` do whisper.cpp com um modelo ggml baixado para a pasta de
+//! modelos do app. O Ollama ainda não serve modelos whisper; quando servir, este módulo
+//! é o ponto de troca do backend.
+
+use futures_util::StreamExt;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+use tauri::{AppHandle, Emitter, Manager};
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+
+/// Modelos ggml publicados pelo whisper.cpp
+pub const MODELS: &[&str] = &[
+    "tiny", "tiny.en", "base", "base.en", "small", "small.en",
+    "medium", "medium.en", "large-v3", "large-v3-turbo",
+];
+const MODEL_BASE_URL: &str = "https://huggingface.co/ggerganov/whisper.cpp/resolve/main";
+/// Nomes do executável do whisper.cpp procurados no PATH (`main` foi renomeado para `whisper-cli`)
+const BINARY_NAMES: &[&str] = &["whisper-cli", "whisper-cpp"];
+
+/// Configuração da transcrição de voz
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct SttSettings {
+    #[serde(default = "default_model")]
+    pub model: String,
+    /// Idioma padrão (`auto` para detectar)
+    #[serde(default = "default_language")]
+    pub language: String,
+    #[serde(default = "default_threads")]
+    pub threads: u32,
+    /// Caminho explícito do `whisper-cli` (senão: pasta `bin` do app, depois o PATH)
+    #[serde(default)]
+    pub binary_path: Option<String>,
+}
+
+fn default_model() -> String {
+    "base".to_string()
+}
+
+fn default_language() -> String {
+    "auto".to_string()
+}
+
+fn default_threads() -> u32 {
+    4
+}
+
+impl Default for SttSettings {
+    fn default() -> Self {
+        Self {
+            model: default_model(),
+            language: default_language(),
+            threads: default_threads(),
+            binary_path: None,
+        }
+    }
+}
+
+impl SttSettings {
+    pub fn validate(&self, errors: &mut Vec<String>) {
+        if !MODELS.contains(&self.model.as_str()) {
+            errors.push(format!("stt.model inválido: {}", self.model));
+        }
+        if !(1..=32).contains(&self.threads) {
+            errors.push("stt.threads deve estar entre 1 e 32".to_string());
+        }
+    }
+}
+
+/// Trecho transcrito com o intervalo no áudio
+#[derive(Serialize, Clone, Debug, PartialEq)]
+pub struct SttSegment {
+    pub start_ms: u64,
+    pub end_ms: u64,
+    pub text: String,
+}
+
+/// Evento `stt-partial` emitido a cada trecho transcrito
+#[derive(Serialize, Clone, Debug)]
+pub struct SttPartialEvent {
+    pub request_id: Option<String>,
+    pub segment: SttSegment,
+    /// Transcrição acumulada até aqui
+    pub text: String,
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct Transcription {
+    pub text: String,
+    pub language: String,
+    pub segments: Vec<SttSegment>,
+    pub duration_ms: u64,
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct SttStatus {
+    pub binary_path: Option<String>,
+    pub model: String,
+    pub model_downloaded: bool,
+    /// Necessário para áudio em WebM/MP4 (formato gravado pelo navegador)
+    pub ffmpeg_available: bool,
+}
+
+pub fn models_dir(app_data_dir: &Path) -> PathBuf {
+    app_data_dir.join("models").join("whisper")
+}
+
+pub fn model_path(app_data_dir: &Path, model: &str) -> PathBuf {
+    models_dir(app_data_dir).join(format!("ggml-{}.bin", model))
+}
+
+fn app_data_dir(app_handle: &AppHandle) -> Result<PathBuf, String> {
+    app_handle.path().app_data_dir()
+        .map_err(|e| format!("Failed to get app data dir: {}", e))
+}
+
+/// Executável no PATH (com a extensão da plataforma)
+pub fn find_in_path(name: &str) -> Option<PathBuf> {
+    let file_name = format!("{}{}", name, std::env::consts::EXE_SUFFIX);
+    std::env::split_paths(&std::env::var_os("PATH")?)
+        .map(|dir| dir.join(&file_name))
+        .find(|candidate| candidate.is_file())
+}
+
+/// Executável do whisper.cpp: configuração, pasta `bin` do app (bundled) ou PATH
+pub fn find_binary(app_data_dir: &Path, settings: &SttSettings) -> Option<PathBuf> {
+    if let Some(path) = settings.binary_path.as_ref().map(PathBuf::from) {
+        return path.is_file().then_some(path);
+    }
+    BINARY_NAMES
+        .iter()
+        .map(|name| app_data_dir.join("bin").join(format!("{}{}", name, std::env::consts::EXE_SUFFIX)))
+        .find(|candidate| candidate.is_file())
+        .or_else(|| BINARY_NAMES.iter().find_map(|name| find_in_path(name)))
+}
+
+pub fn status(app_handle: &AppHandle) -> Result<SttStatus, String> {
+    let settings = crate::settings::current(app_handle).stt;
+    let data_dir = app_data_dir(app_handle)?;
+    Ok(SttStatus {
+        binary_path: find_binary(&data_dir, &settings).map(|p| p.to_string_lossy().to_string()),
+        model_downloaded: model_path(&data_dir, &settings.model).is_file(),
+        model: settings.model,
+        ffmpeg_available: find_in_path("ffmpeg").is_some(),
+    })
+}
+
+/// Baixa o modelo ggml, emitindo `stt-model-progress` com a porcentagem
+pub async fn download_model(app_handle: &AppHandle, model: &str) -> Result<PathBuf, String> {
+    if !MODELS.contains(&model) {
+        return Err(format!("Modelo whisper desconhecido: {}", model));
+    }
+    let path = model_path(&app_data_dir(app_handle)?, model);
+    if path.is_file() {
+        return Ok(path);
+    }
+    std::fs::create_dir_all(models_dir(&app_data_dir(app_handle)?))
+        .map_err(|e| format!("Failed to create whisper models dir: {}", e))?;
+
+    let url = format!("{}/ggml-{}.bin", MODEL_BASE_URL, model);
+    log::info!("[STT] Baixando modelo {} de {}", model, url);
+    let response = reqwest::get(&url).await
+        .map_err(|e| format!("Failed to download whisper model: {}", e))?;
+    if !response.status().is_success() {
+        return Err(format!("Download failed with status: {}", response.status()));
+    }
+    let total = response.content_length().unwrap_or(0);
+
+    // Baixa para .part e renomeia no fim, para um download interrompido não parecer completo
+    let partial_path = path.with_extension("bin.part");
+    let mut file = tokio::fs::File::create(&partial_path).await
+        .map_err(|e| format!("Failed to create model file: {}", e))?;
+    let mut stream = response.bytes_stream();
+    let mut downloaded: u64 = 0;
+    let mut last_percent = 0;
+    while let Some(chunk) = stream.next().await {
+        let chunk = chunk.map_err(|e| format!("Failed to download whisper model: {}", e))?;
+        file.write_all(&chunk).await
+            .map_err(|e| format!("Failed to write model file: {}", e))?;
+        downloaded += chunk.len() as u64;
+        if total > 0 {
+            let percent = downloaded * 100 / total;
+            if percent != last_percent {
+                last_percent = percent;
+                let _ = app_handle.emit("stt-model-progress", serde_json::json!({
+                    "model": model,
+                    "downloaded": downloaded,
+                    "total": total,
+                    "percent": percent,
+                }));
+            }
+        }
+    }
+    file.flush().await
+        .map_err(|e| format!("Failed to write model file: {}", e))?;
+    drop(file);
+    tokio::fs::rename(&partial_path, &path).await
+        .map_err(|e| format!("Failed to finalize model file: {}", e))?;
+
+    log::info!("[STT] Modelo {} baixado ({} bytes)", model, downloaded);
+    Ok(path)
+}
+
+/// Formato do áudio pelo cabeçalho, entre os que o whisper.cpp lê diretamente
+pub fn native_format(header: &[u8]) -> Option<&'static str> {
+    if header.len() >= 12 && &header[0..4] == b"RIFF" && &header[8..12] == b"WAVE" {
+        Some("wav")
+    } else if header.starts_with(b"fLaC") {
+        Some("flac")
+    } else if header.starts_with(b"OggS") {
+        Some("ogg")
+    } else if header.starts_with(b"ID3") || (header.len() >= 2 && header[0] == 0xFF && header[1] & 0xE0 == 0xE0) {
+        Some("mp3")
+    } else {
+        None
+    }
+}
+
+/// Linha de saída do whisper-cli: `[00:00:01.240 --> 00:00:03.500]   texto`
+pub fn parse_segment(line: &str) -> Option<SttSegment> {
+    let rest = line.trim_start().strip_prefix('[')?;
+    let (range, text) = rest.split_once(']')?;
+    let (start, end) = range.split_once("-->")?;
+    Some(SttSegment {
+        start_ms: parse_timestamp(start.trim())?,
+        end_ms: parse_timestamp(end.trim())?,
+        text: text.trim().to_string(),
+    })
+}
+
+fn parse_timestamp(value: &str) -> Option<u64> {
+    let (hms, millis) = value.split_once('.')?;
+    let mut parts = hms.split(':').map(|p| p.parse::<u64>().ok());
+    let (h, m, s) = (parts.next()??, parts.next()??, parts.next()??);
+    Some(((h * 60 + m) * 60 + s) * 1000 + millis.parse::<u64>().ok()?)
+}
+
+/// Converte o áudio para WAV 16 kHz mono com o ffmpeg
+async fn convert_with_ffmpeg(input: &Path, output: &Path) -> Result<(), String> {
+    let ffmpeg = find_in_path("ffmpeg")
+        .ok_or("Formato de áudio não suportado sem o ffmpeg instalado (use WAV, MP3, FLAC ou OGG)")?;
+    let mut cmd = tokio::process::Command::new(ffmpeg);
+    cmd.args(["-y", "-loglevel", "error", "-i"])
+        .arg(input)
+        .args(["-ar", "16000", "-ac", "1"])
+        .arg(output);
+    #[cfg(target_os = "windows")]
+    {
+        const CREATE_NO_WINDOW: u32 = 0x08000000;
+        cmd.creation_flags(CREATE_NO_WINDOW);
+    }
+    let output = cmd.output().await
+        .map_err(|e| format!("Failed to run ffmpeg: {}", e))?;
+    if !output.status.success() {
+        return Err(format!("Failed to convert audio: {}", String::from_utf8_lossy(&output.stderr).trim()));
+    }
+    Ok(())
+}
+
+/// Áudio de entrada: arquivo existente ou bytes gravados pelo frontend
+pub enum AudioInput {
+    Path(PathBuf),
+    Bytes(Vec<u8>),
+}
+
+/// Transcreve o áudio, emitindo `stt-partial` a cada trecho reconhecido
+pub async fn transcribe(
+    app_handle: &AppHandle,
+    input: AudioInput,
+    language: Option<String>,
+    request_id: Option<String>,
+) -> Result<Transcription, String> {
+    let settings = crate::settings::current(app_handle).stt;
+    let data_dir = app_data_dir(app_handle)?;
+    let binary = find_binary(&data_dir, &settings)
+        .ok_or("whisper.cpp não encontrado: instale o whisper-cli ou configure stt.binary_path")?;
+    let model = model_path(&data_dir, &settings.model);
+    if !model.is_file() {
+        return Err(format!("Modelo whisper '{}' não baixado", settings.model));
+    }
+    let language = language.unwrap_or_else(|| settings.language.clone());
+
+    // Arquivos temporários removidos ao final, com sucesso ou erro
+    let work_id = uuid::Uuid::new_v4();
+    let mut temp_files = Vec::new();
+    let source = match input {
+        AudioInput::Path(path) => path,
+        AudioInput::Bytes(bytes) => {
+            let path = std::env::temp_dir().join(format!("ollahub-stt-{}.audio", work_id));
+            std::fs::write(&path, &bytes)
+                .map_err(|e| format!("Failed to write audio file: {}", e))?;
+            temp_files.push(path.clone());
+            path
+        }
+    };
+
+    let result = async {
+        let mut header = [0u8; 12];
+        let read = {
+            use std::io::Read;
+            std::fs::File::open(&source)
+                .and_then(|mut f| f.read(&mut header))
+                .map_err(|e| format!("Failed to read audio file: {}", e))?
+        };
+        let audio = if native_format(&header[..read]).is_some() {
+            source.clone()
+        } else {
+            let wav = std::env::temp_dir().join(format!("ollahub-stt-{}.wav", work_id));
+            temp_files.push(wav.clone());
+            convert_with_ffmpeg(&source, &wav).await?;
+            wav
+        };
+        run_whisper(app_handle, &binary, &model, &audio, &language, settings.threads, request_id).await
+    }
+    .await;
+
+    for path in temp_files {
+        let _ = std::fs::remove_file(path);
+    }
+    result
+}
+
+async fn run_whisper(
+    app_handle: &AppHandle,
+    binary: &Path,
+    model: &Path,
+    audio: &Path,
+    language: &str,
+    threads: u32,
+    request_id: Option<String>,
+) -> Result<Transcription, String> {
+    let started = Instant::now();
+    let mut cmd = tokio::process::Command::new(binary);
+    cmd.arg("-m").arg(model)
+        .arg("-f").arg(audio)
+        .arg("-l").arg(language)
+        .arg("-t").arg(threads.to_string())
+        .arg("--no-prints")
+        .stdout(std::process::Stdio::piped())
+        .stderr(std::process::Stdio::piped())
+        .kill_on_drop(true);
+    #[cfg(target_os = "windows")]
+    {
+        const CREATE_NO_WINDOW: u32 = 0x08000000;
+        cmd.creation_flags(CREATE_NO_WINDOW);
+    }
+
+    let mut child = cmd.spawn()
+        .map_err(|e| format!("Failed to start whisper.cpp: {}", e))?;
+    let stdout = child.stdout.take().ok_or("Failed to capture whisper.cpp output")?;
+    let mut lines = BufReader::new(stdout).lines();
+
+    let mut segments = Vec::new();
+    let mut text = String::new();
+    while let Some(line) = lines.next_line().await
+        .map_err(|e| format!("Failed to read whisper.cpp output: {}", e))?
+    {
+        let Some(segment) = parse_segment(&line) else { continue };
+        if segment.text.is_empty() {
+            continue;
+        }
+        if !text.is_empty() {
+            text.push(' ');
+        }
+        text.push_str(&segment.text);
+        let _ = crate::events::emit(app_handle, &SttPartialEvent {
+            request_id: request_id.clone(),
+            segment: segment.clone(),
+            text: text.clone(),
+        });
+        segments.push(segment);
+    }
+
+    let output = child.wait_with_output().await
+        .map_err(|e| format!("Failed to wait for whisper.cpp: {}", e))?;
+    if !output.status.success() {
+        return Err(format!("whisper.cpp failed: {}", String::from_utf8_lossy(&output.stderr).trim()));
+    }
+
+    log::info!("[STT] {} trechos transcritos em {:?}", segments.len(), started.elapsed());
+    Ok(Transcription {
+        text,
+        language: language.to_string(),
+        segments,
+        duration_ms: started.elapsed().as_millis() as u64,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_segment_and_native_format() {
+        assert_eq!(
+            parse_segment("[00:01:02.500 --> 00:01:04.000]   Olá, tudo bem?"),
+            Some(SttSegment { start_ms: 62_500, end_ms: 64_000, text: "Olá, tudo bem?".to_string() })
+        );
+        assert_eq!(parse_segment("whisper_init_from_file: loading model"), None);
+        assert_eq!(parse_segment("[BLANK_AUDIO]"), None);
+
+        assert_eq!(native_format(b"RIFF\x24\x00\x00\x00WAVEfmt "), Some("wav"));
+        assert_eq!(native_format(b"ID3\x04"), Some("mp3"));
+        assert_eq!(native_format(&[0x1A, 0x45, 0xDF, 0xA3]), None); // WebM
+    }
+}
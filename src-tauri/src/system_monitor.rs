@@ -1,5 +1,11 @@
-use serde::Serialize;
-use sysinfo::System;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Mutex, OnceLock};
+use std::time::Duration;
+use sysinfo::{ProcessesToUpdate, System};
+use tauri::{AppHandle, Emitter};
 
 /// Informações sobre uma GPU
 #[derive(Serialize, Clone, Debug)]
@@ -72,15 +78,11 @@ impl SystemMonitorState {
     }
     
     pub fn get_stats(&mut self) -> SystemStats {
-        // Refresh system info
-        self.system.refresh_all();
-        
-        // Refresh CPU para cálculo preciso
-        self.system.refresh_cpu_all();
-        
-        // Pequeno delay para cálculo preciso de CPU
-        std::thread::sleep(std::time::Duration::from_millis(100));
+        // Sem sleep: o uso de CPU é calculado em relação à chamada anterior
+        // (a primeira leitura após o início pode vir zerada)
+        self.system.refresh_memory();
         self.system.refresh_cpu_all();
+        self.system.refresh_processes(ProcessesToUpdate::All, true);
         
         // CPU usage global
         let cpu_usage = self.system.global_cpu_usage();
@@ -656,12 +658,19 @@ pub fn get_gpu_stats(gpu_id: Option<&str>) -> Option<GpuStats> {
         gpus.first()
     }?;
     
+    // Índice entre as GPUs do mesmo vendor (ordem usada pelas ferramentas de cada fabricante)
+    let vendor_index = gpus
+        .iter()
+        .filter(|g| g.vendor == target_gpu.vendor)
+        .position(|g| g.id == target_gpu.id)
+        .unwrap_or(0);
+
     // Tentar obter stats detalhados baseado no vendor
     if let Some(vendor) = &target_gpu.vendor {
         match vendor.as_str() {
             "NVIDIA" => get_nvidia_gpu_stats(target_gpu),
-            "AMD" => get_amd_gpu_stats(target_gpu),
-            "Intel" => get_intel_gpu_stats(target_gpu),
+            "AMD" => get_amd_gpu_stats(target_gpu, vendor_index),
+            "Intel" => get_intel_gpu_stats(target_gpu, vendor_index),
             _ => get_generic_gpu_stats(target_gpu),
         }
     } else {
@@ -774,19 +783,313 @@ fn count_nvidia_gpu_processes() -> Result<usize, String> {
     Ok(count)
 }
 
-/// Obtém estatísticas de GPU AMD (implementação básica)
-fn get_amd_gpu_stats(gpu: &GpuInfo) -> Option<GpuStats> {
-    log::info!("Coletando stats da GPU AMD: {} (suporte limitado)", gpu.name);
-    // AMD requer rocm-smi ou outras ferramentas específicas
-    // Por enquanto, retornar stats genéricos
-    get_generic_gpu_stats(gpu)
+/// Métricas coletadas por uma ferramenta de vendor (rocm-smi, intel_gpu_top, xpu-smi, sysfs)
+#[derive(Clone, Debug, Default, PartialEq)]
+struct VendorGpuMetrics {
+    vram_used_mb: Option<u64>,
+    vram_total_mb: Option<u64>,
+    usage_percent: Option<f32>,
+    graphics_usage_percent: Option<f32>,
+    compute_usage_percent: Option<f32>,
+    temperature_celsius: Option<f32>,
+    power_watts: Option<f32>,
+    power_max_watts: Option<f32>,
+    fan_speed_percent: Option<f32>,
+}
+
+impl VendorGpuMetrics {
+    fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// Completa campos ausentes com os de outra fonte
+    fn merge(self, other: VendorGpuMetrics) -> VendorGpuMetrics {
+        VendorGpuMetrics {
+            vram_used_mb: self.vram_used_mb.or(other.vram_used_mb),
+            vram_total_mb: self.vram_total_mb.or(other.vram_total_mb),
+            usage_percent: self.usage_percent.or(other.usage_percent),
+            graphics_usage_percent: self.graphics_usage_percent.or(other.graphics_usage_percent),
+            compute_usage_percent: self.compute_usage_percent.or(other.compute_usage_percent),
+            temperature_celsius: self.temperature_celsius.or(other.temperature_celsius),
+            power_watts: self.power_watts.or(other.power_watts),
+            power_max_watts: self.power_max_watts.or(other.power_max_watts),
+            fan_speed_percent: self.fan_speed_percent.or(other.fan_speed_percent),
+        }
+    }
+
+    fn into_stats(self, gpu: &GpuInfo, api: &str) -> GpuStats {
+        let vram_total_mb = self.vram_total_mb.or(gpu.memory_mb);
+        let vram_percent = match (self.vram_used_mb, vram_total_mb) {
+            (Some(used), Some(total)) if total > 0 => Some((used as f32 / total as f32) * 100.0),
+            _ => None,
+        };
+        GpuStats {
+            id: gpu.id.clone(),
+            name: gpu.name.clone(),
+            vendor: gpu.vendor.clone(),
+            vram_used_mb: self.vram_used_mb,
+            vram_total_mb,
+            vram_percent,
+            compute_usage_percent: self.compute_usage_percent.or(self.usage_percent),
+            graphics_usage_percent: self.graphics_usage_percent.or(self.usage_percent),
+            overall_usage_percent: self.usage_percent,
+            temperature_celsius: self.temperature_celsius,
+            power_watts: self.power_watts,
+            power_max_watts: self.power_max_watts,
+            fan_speed_percent: self.fan_speed_percent,
+            api: Some(api.to_string()),
+            ..Default::default()
+        }
+    }
+}
+
+/// Executa uma ferramenta externa e retorna o stdout quando ela termina com sucesso
+fn run_tool(program: &str, args: &[&str]) -> Option<String> {
+    let output = std::process::Command::new(program).args(args).output().ok()?;
+    if !output.status.success() {
+        log::debug!("{} terminou com status {}", program, output.status);
+        return None;
+    }
+    String::from_utf8(output.stdout).ok()
+}
+
+/// Número dentro de um valor textual das ferramentas ("45.0", "12%", "30.0 W")
+fn parse_number(value: &serde_json::Value) -> Option<f64> {
+    match value {
+        serde_json::Value::Number(n) => n.as_f64(),
+        serde_json::Value::String(s) => {
+            let numeric: String = s
+                .trim()
+                .chars()
+                .take_while(|c| c.is_ascii_digit() || *c == '.' || *c == '-')
+                .collect();
+            numeric.parse::<f64>().ok()
+        }
+        _ => None,
+    }
+}
+
+/// Primeiro campo cujo nome contém todos os trechos (os nomes variam entre versões do rocm-smi)
+fn find_field(card: &serde_json::Map<String, serde_json::Value>, parts: &[&str]) -> Option<f64> {
+    card.iter()
+        .find(|(key, _)| {
+            let key = key.to_lowercase();
+            parts.iter().all(|p| key.contains(p))
+        })
+        .and_then(|(_, value)| parse_number(value))
+}
+
+/// Interpreta a saída de `rocm-smi --showuse --showtemp --showmeminfo vram --showpower --showfan --json`
+fn parse_rocm_smi_json(stdout: &str, index: usize) -> Option<VendorGpuMetrics> {
+    let root: serde_json::Value = serde_json::from_str(stdout.trim()).ok()?;
+    let cards = root.as_object()?;
+    let card = cards
+        .get(&format!("card{}", index))
+        .or_else(|| cards.iter().filter(|(k, _)| k.starts_with("card")).nth(index).map(|(_, v)| v))?
+        .as_object()?;
+
+    let bytes_to_mb = |b: f64| (b / (1024.0 * 1024.0)) as u64;
+    let metrics = VendorGpuMetrics {
+        vram_used_mb: find_field(card, &["vram", "used"]).map(bytes_to_mb),
+        vram_total_mb: find_field(card, &["vram", "total memory"]).map(bytes_to_mb),
+        usage_percent: find_field(card, &["gpu use (%)"]).map(|v| v as f32),
+        temperature_celsius: find_field(card, &["temperature", "edge"])
+            .or_else(|| find_field(card, &["temperature", "junction"]))
+            .map(|v| v as f32),
+        power_watts: find_field(card, &["average", "power"])
+            .or_else(|| find_field(card, &["power", "(w)"]))
+            .map(|v| v as f32),
+        fan_speed_percent: find_field(card, &["fan speed (%)"]).map(|v| v as f32),
+        ..Default::default()
+    };
+    (!metrics.is_empty()).then_some(metrics)
+}
+
+/// Métricas expostas pelo driver amdgpu em `/sys/class/drm/cardN/device`
+#[cfg(target_os = "linux")]
+fn read_amdgpu_sysfs(index: usize) -> Option<VendorGpuMetrics> {
+    use std::fs;
+    use std::path::Path;
+
+    let read_u64 = |path: &Path| fs::read_to_string(path).ok().and_then(|s| s.trim().parse::<u64>().ok());
+
+    // Somente placas do driver amdgpu (têm mem_info_vram_total), na ordem dos cards
+    let mut devices: Vec<_> = fs::read_dir("/sys/class/drm")
+        .ok()?
+        .flatten()
+        .map(|e| e.path())
+        .filter(|p| {
+            let name = p.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+            name.starts_with("card") && !name.contains('-') && p.join("device/mem_info_vram_total").exists()
+        })
+        .collect();
+    devices.sort();
+    let device = devices.get(index)?.join("device");
+
+    let hwmon = fs::read_dir(device.join("hwmon")).ok().and_then(|mut d| d.next()).and_then(|e| e.ok()).map(|e| e.path());
+    let metrics = VendorGpuMetrics {
+        vram_used_mb: read_u64(&device.join("mem_info_vram_used")).map(|b| b / (1024 * 1024)),
+        vram_total_mb: read_u64(&device.join("mem_info_vram_total")).map(|b| b / (1024 * 1024)),
+        usage_percent: read_u64(&device.join("gpu_busy_percent")).map(|v| v as f32),
+        temperature_celsius: hwmon.as_ref().and_then(|h| read_u64(&h.join("temp1_input"))).map(|v| v as f32 / 1000.0),
+        power_watts: hwmon
+            .as_ref()
+            .and_then(|h| read_u64(&h.join("power1_average")).or_else(|| read_u64(&h.join("power1_input"))))
+            .map(|v| v as f32 / 1_000_000.0),
+        power_max_watts: hwmon.as_ref().and_then(|h| read_u64(&h.join("power1_cap"))).map(|v| v as f32 / 1_000_000.0),
+        ..Default::default()
+    };
+    (!metrics.is_empty()).then_some(metrics)
+}
+
+#[cfg(not(target_os = "linux"))]
+fn read_amdgpu_sysfs(_index: usize) -> Option<VendorGpuMetrics> {
+    None
 }
 
-/// Obtém estatísticas de GPU Intel (implementação básica)
-fn get_intel_gpu_stats(gpu: &GpuInfo) -> Option<GpuStats> {
-    log::info!("Coletando stats da GPU Intel: {} (suporte limitado)", gpu.name);
-    // Intel requer intel_gpu_top ou outras ferramentas específicas
-    get_generic_gpu_stats(gpu)
+/// Obtém estatísticas de GPU AMD via rocm-smi, com fallback para o sysfs do amdgpu
+fn get_amd_gpu_stats(gpu: &GpuInfo, index: usize) -> Option<GpuStats> {
+    log::info!("Coletando stats da GPU AMD: {}", gpu.name);
+
+    let rocm = run_tool(
+        "rocm-smi",
+        &["--showuse", "--showtemp", "--showmeminfo", "vram", "--showpower", "--showfan", "--json"],
+    )
+    .and_then(|stdout| parse_rocm_smi_json(&stdout, index));
+    let sysfs = read_amdgpu_sysfs(index);
+
+    match (rocm, sysfs) {
+        (Some(rocm), sysfs) => Some(rocm.merge(sysfs.unwrap_or_default()).into_stats(gpu, "ROCm")),
+        (None, Some(sysfs)) => Some(sysfs.into_stats(gpu, "amdgpu")),
+        (None, None) => {
+            log::warn!("rocm-smi/sysfs indisponíveis, usando stats genéricos para {}", gpu.name);
+            get_generic_gpu_stats(gpu)
+        }
+    }
+}
+
+/// Primeiro objeto JSON completo da saída do `intel_gpu_top -J` (um array que cresce a cada amostra)
+fn first_json_object(stdout: &str) -> Option<&str> {
+    let start = stdout.find('{')?;
+    let mut depth = 0usize;
+    let mut in_string = false;
+    let mut escaped = false;
+    for (offset, c) in stdout[start..].char_indices() {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' if in_string => escaped = true,
+            '"' => in_string = !in_string,
+            '{' if !in_string => depth += 1,
+            '}' if !in_string => {
+                depth -= 1;
+                if depth == 0 {
+                    return Some(&stdout[start..=start + offset]);
+                }
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
+/// Interpreta uma amostra do `intel_gpu_top -J` (ocupação por engine e potência)
+fn parse_intel_gpu_top_json(stdout: &str) -> Option<VendorGpuMetrics> {
+    let sample: serde_json::Value = serde_json::from_str(first_json_object(stdout)?).ok()?;
+    let engines = sample.get("engines")?.as_object()?;
+
+    let busy = |prefix: &str| {
+        engines
+            .iter()
+            .filter(|(name, _)| name.starts_with(prefix))
+            .filter_map(|(_, e)| e.get("busy").and_then(parse_number))
+            .fold(None, |max: Option<f64>, v| Some(max.map_or(v, |m| m.max(v))))
+            .map(|v| v as f32)
+    };
+    let overall = engines
+        .values()
+        .filter_map(|e| e.get("busy").and_then(parse_number))
+        .fold(None, |max: Option<f64>, v| Some(max.map_or(v, |m| m.max(v))))
+        .map(|v| v as f32);
+
+    let power = sample.get("power");
+    Some(VendorGpuMetrics {
+        usage_percent: overall,
+        graphics_usage_percent: busy("Render/3D"),
+        compute_usage_percent: busy("Compute").or_else(|| busy("Render/3D")),
+        power_watts: power
+            .and_then(|p| p.get("GPU").or_else(|| p.get("gpu")))
+            .and_then(parse_number)
+            .map(|v| v as f32),
+        ..Default::default()
+    })
+}
+
+/// Uma amostra do intel_gpu_top (o processo roda continuamente, então é encerrado após
+/// a primeira amostra completa)
+fn sample_intel_gpu_top() -> Option<VendorGpuMetrics> {
+    use std::io::Read;
+    use std::process::{Command, Stdio};
+    use std::time::Duration;
+
+    let mut child = Command::new("intel_gpu_top")
+        .args(["-J", "-s", "500", "-o", "-"])
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .ok()?;
+    std::thread::sleep(Duration::from_millis(1200));
+    let _ = child.kill();
+    let mut stdout = String::new();
+    child.stdout.take()?.read_to_string(&mut stdout).ok()?;
+    let _ = child.wait();
+    parse_intel_gpu_top_json(&stdout)
+}
+
+/// Interpreta o CSV do `xpu-smi dump` (Level Zero sysman) usando os nomes das colunas
+fn parse_xpu_smi_dump(stdout: &str, index: usize) -> Option<VendorGpuMetrics> {
+    let mut lines = stdout.lines().map(str::trim).filter(|l| !l.is_empty());
+    let header: Vec<String> = lines.next()?.split(',').map(|h| h.trim().to_lowercase()).collect();
+    let device_column = header.iter().position(|h| h == "deviceid");
+    let row: Vec<&str> = lines
+        .map(|l| l.split(',').map(str::trim).collect::<Vec<_>>())
+        .find(|cols| device_column.map_or(true, |c| cols.get(c).and_then(|v| v.parse::<usize>().ok()) == Some(index)))?;
+
+    let column = |name: &str| {
+        header
+            .iter()
+            .position(|h| h.starts_with(name))
+            .and_then(|i| row.get(i))
+            .and_then(|v| v.parse::<f64>().ok())
+    };
+    let metrics = VendorGpuMetrics {
+        usage_percent: column("gpu utilization").map(|v| v as f32),
+        power_watts: column("gpu power").map(|v| v as f32),
+        temperature_celsius: column("gpu core temperature").map(|v| v as f32),
+        vram_used_mb: column("gpu memory used").map(|v| v as u64),
+        ..Default::default()
+    };
+    (!metrics.is_empty()).then_some(metrics)
+}
+
+/// Obtém estatísticas de GPU Intel via xpu-smi (Level Zero sysman) ou intel_gpu_top
+fn get_intel_gpu_stats(gpu: &GpuInfo, index: usize) -> Option<GpuStats> {
+    log::info!("Coletando stats da GPU Intel: {}", gpu.name);
+
+    // Métricas: 0 utilização, 1 potência, 3 temperatura do core, 18 memória usada
+    let device = index.to_string();
+    let sysman = run_tool("xpu-smi", &["dump", "-d", &device, "-m", "0,1,3,18", "-n", "1"])
+        .and_then(|stdout| parse_xpu_smi_dump(&stdout, index));
+    if let Some(metrics) = sysman {
+        return Some(metrics.into_stats(gpu, "Level Zero"));
+    }
+
+    match sample_intel_gpu_top() {
+        Some(metrics) => Some(metrics.into_stats(gpu, "i915")),
+        None => {
+            log::warn!("xpu-smi/intel_gpu_top indisponíveis, usando stats genéricos para {}", gpu.name);
+            get_generic_gpu_stats(gpu)
+        }
+    }
 }
 
 /// Retorna stats genéricos quando não há suporte específico
@@ -800,3 +1103,495 @@ fn get_generic_gpu_stats(gpu: &GpuInfo) -> Option<GpuStats> {
     })
 }
 
+/// Intervalo padrão do monitor (o perfil de baixo consumo impõe um mínimo maior)
+pub const DEFAULT_MONITOR_INTERVAL_MS: u64 = 2000;
+const MIN_MONITOR_INTERVAL_MS: u64 = 250;
+const MAX_MONITOR_INTERVAL_MS: u64 = 60_000;
+
+/// Métricas que o monitor coleta e envia no evento `system-stats-v2`
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum MonitorMetric {
+    Cpu,
+    Ram,
+    /// Stats da GPU principal (executa nvidia-smi/rocm-smi a cada amostra)
+    Gpu,
+    Processes,
+    Uptime,
+}
+
+/// Configuração efetiva do monitor
+#[derive(Serialize, Clone, Debug, PartialEq)]
+pub struct MonitorConfig {
+    pub enabled: bool,
+    pub interval_ms: u64,
+    pub enabled_metrics: Vec<MonitorMetric>,
+}
+
+impl Default for MonitorConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            interval_ms: DEFAULT_MONITOR_INTERVAL_MS,
+            enabled_metrics: vec![MonitorMetric::Cpu, MonitorMetric::Ram],
+        }
+    }
+}
+
+/// Amostra enviada no evento `system-stats-v2` (métricas desabilitadas ficam ausentes)
+#[derive(Serialize, Clone, Debug)]
+pub struct SystemStatsV2 {
+    pub timestamp: DateTime<Utc>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cpu_usage: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cpu_name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ram_used: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ram_total: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ram_percent: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub gpu: Option<GpuStats>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub processes_count: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub uptime: Option<u64>,
+}
+
+/// Payload do evento legado `system-stats` (hooks antigos do frontend)
+#[derive(Serialize, Clone, Debug)]
+struct LegacySystemStats {
+    cpu_usage: f32,
+    memory_used: u64,
+    memory_total: u64,
+}
+
+struct MonitorControl {
+    config: MonitorConfig,
+    /// Também emite `system-stats` (ativado por `start_system_monitor`)
+    legacy_event: bool,
+    /// Incrementada a cada reconfiguração; a task com geração antiga encerra
+    generation: u64,
+}
+
+static MONITOR: OnceLock<Mutex<MonitorControl>> = OnceLock::new();
+static LATEST_STATS: OnceLock<Mutex<Option<SystemStatsV2>>> = OnceLock::new();
+
+fn monitor_control() -> &'static Mutex<MonitorControl> {
+    MONITOR.get_or_init(|| {
+        Mutex::new(MonitorControl {
+            config: MonitorConfig::default(),
+            legacy_event: false,
+            generation: 0,
+        })
+    })
+}
+
+fn latest_stats() -> &'static Mutex<Option<SystemStatsV2>> {
+    LATEST_STATS.get_or_init(|| Mutex::new(None))
+}
+
+/// Última amostra do monitor, se ainda estiver dentro de dois intervalos
+pub fn latest_monitor_stats() -> Option<SystemStatsV2> {
+    let interval_ms = monitor_control().lock().ok()?.config.interval_ms;
+    let stats = latest_stats().lock().ok()?.clone()?;
+    let age = Utc::now() - stats.timestamp;
+    (age.num_milliseconds() <= (interval_ms * 2) as i64).then_some(stats)
+}
+
+/// Coleta uma amostra apenas das métricas habilitadas (sem sleep: o uso de CPU é
+/// calculado em relação ao refresh anterior do mesmo `System`)
+fn sample_stats(system: &mut System, metrics: &[MonitorMetric]) -> SystemStatsV2 {
+    let has = |m: MonitorMetric| metrics.contains(&m);
+    let mut stats = SystemStatsV2 {
+        timestamp: Utc::now(),
+        cpu_usage: None,
+        cpu_name: None,
+        ram_used: None,
+        ram_total: None,
+        ram_percent: None,
+        gpu: None,
+        processes_count: None,
+        uptime: None,
+    };
+
+    if has(MonitorMetric::Cpu) {
+        system.refresh_cpu_usage();
+        stats.cpu_usage = Some(system.global_cpu_usage());
+        stats.cpu_name = system.cpus().first().map(|cpu| cpu.name().to_string());
+    }
+    if has(MonitorMetric::Ram) {
+        system.refresh_memory();
+        let total = system.total_memory();
+        let used = system.used_memory();
+        stats.ram_used = Some(used);
+        stats.ram_total = Some(total);
+        stats.ram_percent = Some(if total > 0 { (used as f32 / total as f32) * 100.0 } else { 0.0 });
+    }
+    if has(MonitorMetric::Processes) {
+        system.refresh_processes(ProcessesToUpdate::All, true);
+        stats.processes_count = Some(system.processes().len());
+    }
+    if has(MonitorMetric::Uptime) {
+        stats.uptime = Some(System::uptime());
+    }
+    if has(MonitorMetric::Gpu) {
+        stats.gpu = get_gpu_stats(None);
+    }
+    stats
+}
+
+/// Liga, desliga ou reconfigura o monitor. Sem métricas habilitadas o monitor para
+/// (ex: painel oculto), eliminando o custo da coleta.
+pub fn configure_monitor(
+    app_handle: &AppHandle,
+    interval_ms: Option<u64>,
+    enabled_metrics: Option<Vec<MonitorMetric>>,
+    legacy_event: bool,
+) -> MonitorConfig {
+    let (config, generation) = {
+        let mut control = match monitor_control().lock() {
+            Ok(control) => control,
+            Err(poisoned) => poisoned.into_inner(),
+        };
+        if let Some(interval_ms) = interval_ms {
+            control.config.interval_ms = interval_ms.clamp(MIN_MONITOR_INTERVAL_MS, MAX_MONITOR_INTERVAL_MS);
+        }
+        if let Some(mut metrics) = enabled_metrics {
+            metrics.dedup();
+            control.config.enabled_metrics = metrics;
+        }
+        control.config.enabled = !control.config.enabled_metrics.is_empty();
+        control.legacy_event |= legacy_event;
+        control.generation += 1;
+        (control.config.clone(), control.generation)
+    };
+
+    if config.enabled {
+        spawn_monitor_task(app_handle.clone(), generation);
+        log::info!(
+            "[Monitor] Ativo a cada {} ms: {:?}",
+            config.interval_ms,
+            config.enabled_metrics
+        );
+    } else {
+        log::info!("[Monitor] Desativado");
+    }
+    config
+}
+
+pub fn monitor_config() -> MonitorConfig {
+    monitor_control()
+        .lock()
+        .map(|control| control.config.clone())
+        .unwrap_or_default()
+}
+
+fn spawn_monitor_task(app_handle: AppHandle, generation: u64) {
+    tauri::async_runtime::spawn(async move {
+        let mut system = Some(System::new());
+        loop {
+            let (config, legacy_event) = match monitor_control().lock() {
+                Ok(control) if control.generation == generation && control.config.enabled => {
+                    (control.config.clone(), control.legacy_event)
+                }
+                _ => break,
+            };
+
+            // Coleta fora do runtime async (GPU executa ferramentas externas)
+            let Some(mut sys) = system.take() else { break };
+            let metrics = config.enabled_metrics.clone();
+            let Ok((sys, stats)) = tokio::task::spawn_blocking(move || {
+                let stats = sample_stats(&mut sys, &metrics);
+                (sys, stats)
+            })
+            .await
+            else {
+                break;
+            };
+            system = Some(sys);
+
+            if let Ok(mut latest) = latest_stats().lock() {
+                *latest = Some(stats.clone());
+            }
+            let _ = crate::events::emit(&app_handle, &stats);
+            if legacy_event {
+                let _ = app_handle.emit(
+                    "system-stats",
+                    LegacySystemStats {
+                        cpu_usage: stats.cpu_usage.unwrap_or_default(),
+                        memory_used: stats.ram_used.unwrap_or_default(),
+                        memory_total: stats.ram_total.unwrap_or_default(),
+                    },
+                );
+            }
+
+            // O perfil de baixo consumo impõe um intervalo mínimo maior
+            let profile = crate::resource_profile::current();
+            let interval_ms = if profile.low_resource {
+                config.interval_ms.max(profile.monitor_interval_ms)
+            } else {
+                config.interval_ms
+            };
+            tokio::time::sleep(Duration::from_millis(interval_ms)).await;
+        }
+    });
+}
+
+/// Janela do histórico mantido em memória
+pub const METRICS_HISTORY_WINDOW: Duration = Duration::from_secs(60 * 60);
+/// Intervalo entre amostras do histórico (o modo de baixo consumo pode aumentar)
+const METRICS_SAMPLE_INTERVAL: Duration = Duration::from_secs(5);
+/// VRAM é coletada a cada N amostras (nvidia-smi/rocm-smi são caros para rodar a cada 5s)
+const VRAM_SAMPLE_EVERY: u32 = 6;
+/// Amostras acumuladas antes de gravar no SQLite (quando a persistência está ativa)
+const METRICS_FLUSH_EVERY: usize = 12;
+/// Pontos máximos retornados por `get_metrics_history` (intervalos longos são agregados)
+pub const MAX_HISTORY_POINTS: usize = 720;
+
+/// Amostra do histórico de recursos
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct MetricSample {
+    pub timestamp: DateTime<Utc>,
+    pub cpu_percent: f32,
+    pub ram_used_mb: u64,
+    pub ram_total_mb: u64,
+    pub vram_used_mb: Option<u64>,
+    pub vram_total_mb: Option<u64>,
+    /// Memória residente somada dos processos do Ollama (`ollama`, `ollama_llama_server`)
+    pub ollama_rss_mb: u64,
+    pub ollama_cpu_percent: f32,
+    pub ollama_processes: usize,
+}
+
+static METRICS_HISTORY: OnceLock<Mutex<VecDeque<MetricSample>>> = OnceLock::new();
+static RECORDER_STARTED: AtomicBool = AtomicBool::new(false);
+
+fn metrics_history_buffer() -> &'static Mutex<VecDeque<MetricSample>> {
+    METRICS_HISTORY.get_or_init(|| Mutex::new(VecDeque::new()))
+}
+
+/// Processos do servidor e dos runners do Ollama
+fn is_ollama_process(name: &str) -> bool {
+    let name = name.to_lowercase();
+    let name = name.strip_suffix(".exe").unwrap_or(&name);
+    name == "ollama" || name.starts_with("ollama_") || name.starts_with("ollama-")
+}
+
+/// Adiciona uma amostra descartando as que saíram da janela
+fn push_sample(sample: MetricSample) {
+    if let Ok(mut buffer) = metrics_history_buffer().lock() {
+        let cutoff = sample.timestamp - chrono::Duration::from_std(METRICS_HISTORY_WINDOW).unwrap_or_default();
+        while buffer.front().is_some_and(|s| s.timestamp < cutoff) {
+            buffer.pop_front();
+        }
+        buffer.push_back(sample);
+    }
+}
+
+fn collect_sample(system: &mut System, vram: (Option<u64>, Option<u64>)) -> MetricSample {
+    system.refresh_cpu_usage();
+    system.refresh_memory();
+    system.refresh_processes(ProcessesToUpdate::All, true);
+
+    let (mut ollama_rss, mut ollama_cpu, mut ollama_processes) = (0u64, 0f32, 0usize);
+    for process in system.processes().values() {
+        if is_ollama_process(&process.name().to_string_lossy()) {
+            ollama_rss += process.memory();
+            ollama_cpu += process.cpu_usage();
+            ollama_processes += 1;
+        }
+    }
+
+    MetricSample {
+        timestamp: Utc::now(),
+        cpu_percent: system.global_cpu_usage(),
+        ram_used_mb: system.used_memory() / (1024 * 1024),
+        ram_total_mb: system.total_memory() / (1024 * 1024),
+        vram_used_mb: vram.0,
+        vram_total_mb: vram.1,
+        ollama_rss_mb: ollama_rss / (1024 * 1024),
+        ollama_cpu_percent: ollama_cpu,
+        ollama_processes,
+    }
+}
+
+/// Inicia (uma única vez) a coleta do histórico em background. Com
+/// `metrics_history.persist` ativo, as amostras também vão para o SQLite.
+pub fn start_metrics_recorder(app_handle: AppHandle) {
+    if RECORDER_STARTED.swap(true, Ordering::SeqCst) {
+        return;
+    }
+
+    std::thread::spawn(move || {
+        let mut system = System::new();
+        let mut vram = (None, None);
+        let mut pending: Vec<MetricSample> = Vec::new();
+        let mut tick: u32 = 0;
+
+        loop {
+            if tick % VRAM_SAMPLE_EVERY == 0 {
+                if let Some(stats) = get_gpu_stats(None) {
+                    vram = (stats.vram_used_mb, stats.vram_total_mb);
+                }
+            }
+            tick = tick.wrapping_add(1);
+
+            let sample = collect_sample(&mut system, vram);
+            push_sample(sample.clone());
+
+            let settings = crate::settings::current(&app_handle).metrics_history;
+            if settings.persist {
+                pending.push(sample);
+                if pending.len() >= METRICS_FLUSH_EVERY {
+                    let cutoff = Utc::now() - chrono::Duration::hours(settings.retention_hours as i64);
+                    let result = crate::db::Database::new(&app_handle).and_then(|db| {
+                        db.insert_metric_samples(&pending)?;
+                        db.prune_metric_samples(&cutoff)
+                    });
+                    if let Err(e) = result {
+                        log::warn!("[Metrics] Falha ao gravar histórico: {}", e);
+                    }
+                    pending.clear();
+                }
+            } else {
+                pending.clear();
+            }
+
+            let interval = Duration::from_millis(crate::resource_profile::current().monitor_interval_ms)
+                .max(METRICS_SAMPLE_INTERVAL);
+            std::thread::sleep(interval);
+        }
+    });
+}
+
+/// Intervalo do histórico: "30m", "1h", "6h", "2d" (número sem unidade = minutos)
+pub fn parse_range(range: &str) -> Result<Duration, String> {
+    let range = range.trim().to_lowercase();
+    let split = range.find(|c: char| !c.is_ascii_digit()).unwrap_or(range.len());
+    let (value, unit) = range.split_at(split);
+    let value: u64 = value
+        .parse()
+        .map_err(|_| format!("Intervalo inválido: {}", range))?;
+    let secs = match unit.trim() {
+        "s" => value,
+        "" | "m" | "min" => value * 60,
+        "h" => value * 3600,
+        "d" => value * 86400,
+        other => return Err(format!("Unidade de intervalo desconhecida: {}", other)),
+    };
+    if secs == 0 {
+        return Err("O intervalo deve ser maior que zero".to_string());
+    }
+    Ok(Duration::from_secs(secs))
+}
+
+/// Reduz a série a no máximo `max_points`, usando o pico de cada grupo de amostras
+/// (médias esconderiam justamente os picos que explicam uma geração lenta)
+pub fn downsample(samples: Vec<MetricSample>, max_points: usize) -> Vec<MetricSample> {
+    if max_points == 0 || samples.len() <= max_points {
+        return samples;
+    }
+    let bucket = samples.len().div_ceil(max_points);
+    samples
+        .chunks(bucket)
+        .filter_map(|chunk| {
+            let mut peak = chunk.last()?.clone();
+            for s in chunk {
+                peak.cpu_percent = peak.cpu_percent.max(s.cpu_percent);
+                peak.ram_used_mb = peak.ram_used_mb.max(s.ram_used_mb);
+                peak.vram_used_mb = peak.vram_used_mb.max(s.vram_used_mb);
+                peak.ollama_rss_mb = peak.ollama_rss_mb.max(s.ollama_rss_mb);
+                peak.ollama_cpu_percent = peak.ollama_cpu_percent.max(s.ollama_cpu_percent);
+                peak.ollama_processes = peak.ollama_processes.max(s.ollama_processes);
+            }
+            Some(peak)
+        })
+        .collect()
+}
+
+/// Histórico do intervalo pedido: memória para a última hora, SQLite (se persistido) além disso
+pub fn metrics_history(app_handle: &AppHandle, range: Duration) -> Result<Vec<MetricSample>, String> {
+    let since = Utc::now() - chrono::Duration::from_std(range).map_err(|e| format!("Intervalo inválido: {}", e))?;
+
+    let mut samples: Vec<MetricSample> = metrics_history_buffer()
+        .lock()
+        .map_err(|e| format!("Failed to lock metrics history: {}", e))?
+        .iter()
+        .filter(|s| s.timestamp >= since)
+        .cloned()
+        .collect();
+
+    if range > METRICS_HISTORY_WINDOW && crate::settings::current(app_handle).metrics_history.persist {
+        let oldest_in_memory = samples.first().map(|s| s.timestamp).unwrap_or_else(Utc::now);
+        let mut persisted = crate::db::Database::new(app_handle)
+            .and_then(|db| db.get_metric_samples(&since))
+            .map_err(|e| format!("Failed to load metrics history: {}", e))?;
+        persisted.retain(|s| s.timestamp < oldest_in_memory);
+        persisted.extend(samples);
+        samples = persisted;
+    }
+
+    Ok(downsample(samples, MAX_HISTORY_POINTS))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_vendor_tool_output() {
+        let rocm = r#"{"card0": {"GPU use (%)": "37", "Temperature (Sensor edge) (C)": "52.0",
+            "VRAM Total Memory (B)": "17163091968", "VRAM Total Used Memory (B)": "1073741824",
+            "Average Graphics Package Power (W)": "41.0"}}"#;
+        let amd = parse_rocm_smi_json(rocm, 0).unwrap();
+        assert_eq!(amd.usage_percent, Some(37.0));
+        assert_eq!(amd.temperature_celsius, Some(52.0));
+        assert_eq!(amd.vram_used_mb, Some(1024));
+        assert_eq!(amd.vram_total_mb, Some(16368));
+        assert_eq!(amd.power_watts, Some(41.0));
+
+        let intel = "[\n{\"period\": {\"duration\": 500.0}, \"power\": {\"GPU\": 3.5},
+            \"engines\": {\"Render/3D/0\": {\"busy\": 20.5}, \"Video/0\": {\"busy\": 60.0}}},\n{\"period\"";
+        let intel = parse_intel_gpu_top_json(intel).unwrap();
+        assert_eq!(intel.usage_percent, Some(60.0));
+        assert_eq!(intel.graphics_usage_percent, Some(20.5));
+        assert_eq!(intel.power_watts, Some(3.5));
+
+        let xpu = "Timestamp, DeviceId, GPU Utilization (%), GPU Power (W), GPU Core Temperature (Celsius Degree), GPU Memory Used (MiB)\n06:14:46.000,    0, 12.00, 28.50, 45.00, 512.00";
+        let xpu = parse_xpu_smi_dump(xpu, 0).unwrap();
+        assert_eq!(xpu.temperature_celsius, Some(45.0));
+        assert_eq!(xpu.vram_used_mb, Some(512));
+    }
+
+    #[test]
+    fn test_metrics_range_and_downsample() {
+        assert_eq!(parse_range("30m").unwrap(), Duration::from_secs(1800));
+        assert_eq!(parse_range("2h").unwrap(), Duration::from_secs(7200));
+        assert_eq!(parse_range("45").unwrap(), Duration::from_secs(2700));
+        assert!(parse_range("1w").is_err());
+        assert!(parse_range("0m").is_err());
+        assert!(is_ollama_process("ollama_llama_server.exe"));
+        assert!(!is_ollama_process("llama"));
+
+        let samples: Vec<MetricSample> = (0..10)
+            .map(|i| MetricSample {
+                timestamp: Utc::now(),
+                cpu_percent: if i == 3 { 95.0 } else { 10.0 },
+                ram_used_mb: 1000,
+                ram_total_mb: 8000,
+                vram_used_mb: None,
+                vram_total_mb: None,
+                ollama_rss_mb: i,
+                ollama_cpu_percent: 0.0,
+                ollama_processes: 1,
+            })
+            .collect();
+        let reduced = downsample(samples, 3);
+        assert_eq!(reduced.len(), 3);
+        assert_eq!(reduced[0].cpu_percent, 95.0);
+        assert_eq!(reduced[2].ollama_rss_mb, 9);
+    }
+}
@@ -1,49 +1,135 @@
-use crate::scheduler::{SentinelTask, TaskAction};
+use crate::digests::{DigestItemKind, DigestSource};
+use crate::scheduler::{OutputFormat, SentinelTask, TaskAction};
 use crate::ollama_client::OllamaClient;
-use crate::web_scraper::search_and_scrape;
+use crate::rate_limit::{self, RequestOrigin};
+use crate::web_scraper::{ScrapedContent, search_and_scrape, search_and_scrape_static, search_and_scrape_with_config, SearchCategory, SearchConfig};
 use crate::{Message, ChatSession, get_chats_dir};
-use std::sync::Arc;
 use std::fs;
-use headless_chrome::Browser;
-use chrono::Utc;
-use tauri::AppHandle;
+use std::path::{Path, PathBuf};
+use chrono::{DateTime, Datelike, Local, Utc, Weekday};
+use regex::Regex;
+use tauri::{AppHandle, Manager};
 use tauri_plugin_notification::NotificationExt;
+use tauri_plugin_clipboard_manager::ClipboardExt;
 use sysinfo::System;
 
-/// Executa uma task agendada
+/// Sessão fixa onde os relatórios de `ResearchReport` são acumulados
+const REPORTS_SESSION_ID: &str = "ollahub-reports";
+const REPORTS_SESSION_TITLE: &str = "Relatórios";
+/// Caracteres de cada fonte incluídos no prompt do relatório
+const REPORT_SOURCE_CHARS: usize = 4000;
+
+const DEFAULT_REPORT_PROMPT: &str = "Crie um relatório sobre '{query}' com base nas fontes abaixo ({date}).\n\n\
+## FONTES\n{sources}\n\n\
+Organize em:\n\
+1. Destaques (tópicos curtos)\n\
+2. Detalhes por assunto, citando as fontes como [n]\n\
+3. O que acompanhar nos próximos dias";
+
+/// Conteúdo produzido por uma ação, repassado às ações seguintes da task
+#[derive(Debug, Clone)]
+pub struct TaskOutput {
+    pub title: String,
+    pub content: String,
+    /// Pares (título, url) das fontes usadas
+    pub sources: Vec<(String, String)>,
+    /// Trechos numerados das fontes (`{{source_excerpts}}` de `GeneratePrompt`)
+    pub source_excerpts: Vec<String>,
+}
+
+/// Executa uma task agendada e devolve a saída da última ação do pipeline
 pub async fn execute_task(
     task: &SentinelTask,
     app_handle: AppHandle,
-    browser: Arc<Browser>,
     ollama_url: Option<String>,
-) -> Result<(), String> {
+) -> Result<Option<TaskOutput>, String> {
     log::info!("Executando task: {} ({})", task.label, task.id);
     
     let client = OllamaClient::new(ollama_url);
     
-    match &task.action {
+    // A ação principal e as post_actions formam um pipeline: cada ação recebe a saída da anterior
+    let mut output: Option<TaskOutput> = None;
+    for action in std::iter::once(&task.action).chain(task.post_actions.iter()) {
+        output = execute_action(
+            task,
+            action,
+            output,
+            &app_handle,
+            &client,
+        ).await?;
+    }
+    
+    Ok(output)
+}
+
+/// Executa uma única ação do pipeline da task
+async fn execute_action(
+    task: &SentinelTask,
+    action: &TaskAction,
+    previous: Option<TaskOutput>,
+    app_handle: &AppHandle,
+    client: &OllamaClient,
+) -> Result<Option<TaskOutput>, String> {
+    match action {
         TaskAction::SearchAndSummarize { query, model, max_results } => {
             execute_search_and_summarize(
                 task,
                 query,
                 model,
                 *max_results,
-                &app_handle,
-                browser,
-                &client,
-            ).await
+                app_handle,
+                client,
+            ).await.map(Some)
         }
         TaskAction::JustPing { message } => {
-            execute_just_ping(task, message, &app_handle).await
+            execute_just_ping(task, message, app_handle).await?;
+            Ok(previous)
         }
         TaskAction::CustomPrompt { prompt, model } => {
             execute_custom_prompt(
                 task,
                 prompt,
                 model,
-                &app_handle,
-                &client,
-            ).await
+                app_handle,
+                client,
+            ).await.map(Some)
+        }
+        TaskAction::ResearchReport { query, model, categories, max_results, prompt_template } => {
+            execute_research_report(
+                task,
+                query,
+                model,
+                ReportScope { category_ids: categories, max_results: *max_results, prompt_template: prompt_template.as_deref() },
+                app_handle,
+                client,
+            ).await.map(Some)
+        }
+        TaskAction::GeneratePrompt { model, prompt_template, save_to_session } => {
+            execute_generate_prompt(
+                task,
+                model,
+                prompt_template,
+                *save_to_session,
+                previous.as_ref(),
+                app_handle,
+                client,
+            ).await.map(Some)
+        }
+        TaskAction::WriteFile { path_template, format } => {
+            let output = previous
+                .ok_or_else(|| "WriteFile requer uma ação anterior que produza conteúdo".to_string())?;
+            execute_write_file(task, &output, path_template, *format, app_handle)?;
+            Ok(Some(output))
+        }
+        TaskAction::CopyToClipboard => {
+            let output = previous
+                .ok_or_else(|| "CopyToClipboard requer uma ação anterior que produza conteúdo".to_string())?;
+            app_handle
+                .clipboard()
+                .write_text(output.content.clone())
+                .map_err(|e| format!("Erro ao copiar para a área de transferência: {}", e))?;
+            log::info!("Saída da task {} copiada para a área de transferência", task.id);
+            Ok(Some(output))
         }
     }
 }
@@ -55,12 +141,18 @@ async fn execute_search_and_summarize(
     model: &str,
     max_results: usize,
     app_handle: &AppHandle,
-    browser: Arc<Browser>,
     ollama_client: &OllamaClient,
-) -> Result<(), String> {
+) -> Result<TaskOutput, String> {
+    // Checar o limite antes de gastar tempo com o scraping
+    rate_limit::acquire(&crate::settings::current(app_handle).model_rate_limits, model, RequestOrigin::Scheduler)?;
+    
     // 1. Buscar conteúdo na web
     log::info!("Buscando conteúdo para: {}", query);
-    let scraped = search_and_scrape(query, max_results, browser, vec![])
+    // Cache de páginas compartilhado entre execuções agendadas (TTL padrão)
+    let cache = crate::web_scraper::PageCache::new(app_handle, None);
+    // A consulta foi escrita pelo usuário na task: não há o que confirmar, só limpar
+    let query = &crate::redaction::sanitize_query(app_handle, query, "task", true)?;
+    let scraped = search_and_scrape(query, max_results, vec![], Some(cache), None)
         .await
         .map_err(|e| format!("Erro ao buscar conteúdo: {}", e))?;
     
@@ -68,12 +160,8 @@ async fn execute_search_and_summarize(
         return Err("Nenhum resultado encontrado na busca".to_string());
     }
     
-    // 2. Combinar conteúdo em markdown
-    let web_context: String = scraped
-        .iter()
-        .map(|s| format!("---\nTítulo: {}\nURL: {}\n---\n\n{}", s.title, s.url, s.markdown))
-        .collect::<Vec<_>>()
-        .join("\n\n");
+    // 2. Combinar conteúdo em markdown (limpo e delimitado, dentro da fração do contexto)
+    let web_context = crate::content_guard::build_context(app_handle, None, "task", model, scraped.clone()).context;
     
     // 3. Criar prompt para o Ollama
     let system_prompt = format!(
@@ -104,8 +192,16 @@ async fn execute_search_and_summarize(
     // 5. Salvar como sessão de chat
     let session_id = uuid::Uuid::new_v4().to_string();
     
+    let output = TaskOutput {
+        title: task.label.clone(),
+        content: summary.clone(),
+        sources: scraped.iter().map(|s| (s.title.clone(), s.url.clone())).collect(),
+        source_excerpts: source_excerpts(app_handle, &scraped),
+    };
+    
     let messages = vec![
         Message {
+            id: None,
             role: "user".to_string(),
             content: format!("Pesquisa agendada: {}", query),
             metadata: Some(serde_json::json!({
@@ -115,6 +211,7 @@ async fn execute_search_and_summarize(
             })),
         },
         Message {
+            id: None,
             role: "assistant".to_string(),
             content: summary,
             metadata: Some(serde_json::json!({
@@ -122,6 +219,7 @@ async fn execute_search_and_summarize(
                 "sources": scraped.iter().map(|s| serde_json::json!({
                     "title": s.title,
                     "url": s.url,
+                    "published_at": s.published_at,
                 })).collect::<Vec<_>>(),
             })),
         },
@@ -134,6 +232,15 @@ async fn execute_search_and_summarize(
         &format!("[Agendado] {}", task.label),
         messages,
     )?;
+    crate::unread::record(app_handle, &session_id);
+    crate::digests::record(
+        app_handle,
+        DigestItemKind::ResearchBrief,
+        Some(&task.id),
+        &task.label,
+        &output.content,
+        &output.sources.iter().map(|(title, url)| DigestSource { title: title.clone(), url: url.clone() }).collect::<Vec<_>>(),
+    );
     
     // 6. Enviar notificação
     app_handle
@@ -145,7 +252,187 @@ async fn execute_search_and_summarize(
         .map_err(|e| format!("Erro ao enviar notificação: {}", e))?;
     
     log::info!("Task {} executada com sucesso. Sessão salva: {}", task.id, session_id);
-    Ok(())
+    Ok(output)
+}
+
+/// Resolve `{query}`, `{date}` e `{sources}` no template do relatório
+fn render_report_prompt(template: &str, query: &str, date: &str, sources: &str) -> String {
+    template
+        .replace("{query}", query)
+        .replace("{date}", date)
+        .replace("{sources}", sources)
+}
+
+/// Fontes e formato de um relatório de pesquisa
+struct ReportScope<'a> {
+    category_ids: &'a [String],
+    max_results: usize,
+    prompt_template: Option<&'a str>,
+}
+
+/// Executa o relatório sobre as categorias de fontes curadas
+async fn execute_research_report(
+    task: &SentinelTask,
+    query: &str,
+    model: &str,
+    scope: ReportScope<'_>,
+    app_handle: &AppHandle,
+    ollama_client: &OllamaClient,
+) -> Result<TaskOutput, String> {
+    let ReportScope { category_ids, max_results, prompt_template } = scope;
+    rate_limit::acquire(&crate::settings::current(app_handle).model_rate_limits, model, RequestOrigin::Scheduler)?;
+    
+    // 1. Categorias escolhidas (ou todas as ativas) da configuração de fontes
+    let sources_config = crate::sources_config::load_sources_config(app_handle)?;
+    let categories: Vec<SearchCategory> = sources_config
+        .categories
+        .iter()
+        .filter(|c| if category_ids.is_empty() { c.enabled } else { category_ids.contains(&c.id) })
+        .map(|c| SearchCategory {
+            id: c.id.clone(),
+            name: c.name.clone(),
+            base_sites: c.base_sites.clone(),
+            enabled: true,
+            weight: c.weight,
+        })
+        .collect();
+    if categories.is_empty() {
+        return Err("Nenhuma categoria de fontes ativa para o relatório".to_string());
+    }
+    
+    // 2. Itens novos dos feeds das categorias, depois busca e extração dos melhores resultados
+    let category_ids: Vec<String> = categories.iter().map(|c| c.id.clone()).collect();
+    let feed_items = crate::feeds::fetch_feeds(app_handle, &category_ids, false)
+        .await
+        .unwrap_or_else(|e| {
+            log::warn!("Relatório '{}': feeds indisponíveis: {}", query, e);
+            Vec::new()
+        });
+    log::info!("Relatório '{}': {} itens de feeds, buscando em {} categorias", query, feed_items.len(), categories.len());
+    let config = SearchConfig {
+        max_concurrent_tabs: 3,
+        total_sources_limit: max_results,
+        categories,
+        user_custom_sites: Vec::new(),
+        excluded_domains: Vec::new(),
+    };
+    let search_query = crate::redaction::sanitize_query(app_handle, query, "task", true)?;
+    let scraped = if crate::resource_profile::current().headless_scraping_enabled {
+        let cache = crate::web_scraper::PageCache::new(app_handle, None);
+        search_and_scrape_with_config(&search_query, &config, Some(cache), None).await
+    } else {
+        search_and_scrape_static(&search_query, &config, None).await
+    }
+    .map_err(|e| format!("Erro ao buscar conteúdo: {}", e))?;
+    let scraped: Vec<_> = feed_items
+        .into_iter()
+        .map(crate::feeds::FeedItem::into_scraped)
+        .chain(scraped)
+        .take(max_results)
+        .collect();
+    let scraped = crate::content_guard::guard_scraped(app_handle, None, "task", scraped);
+    if scraped.is_empty() {
+        return Err("Nenhum resultado encontrado nas fontes do relatório".to_string());
+    }
+    
+    // 3. Resumo com o modelo
+    let excerpts = source_excerpts(app_handle, &scraped);
+    let sources_text = excerpts.join("\n\n---\n\n");
+    let date = Local::now().format("%d/%m/%Y").to_string();
+    let user_prompt = render_report_prompt(
+        prompt_template.filter(|t| !t.trim().is_empty()).unwrap_or(DEFAULT_REPORT_PROMPT),
+        query,
+        &date,
+        &sources_text,
+    );
+    let system_prompt = format!(
+        "Você é um analista que escreve relatórios objetivos a partir de fontes da web.\nDATA ATUAL: {}",
+        Utc::now().format("%d/%m/%Y %H:%M")
+    );
+    log::info!("Enviando relatório para Ollama (modelo: {})", model);
+    let report = ollama_client
+        .query_ollama_headless(model, Some(&system_prompt), &user_prompt)
+        .await
+        .map_err(|e| format!("Erro ao consultar Ollama: {}", e))?;
+    
+    let output = TaskOutput {
+        title: format!("{} — {}", task.label, date),
+        content: report.clone(),
+        sources: scraped.iter().map(|s| (s.title.clone(), s.url.clone())).collect(),
+        source_excerpts: excerpts,
+    };
+    
+    // 4. Acrescentar à sessão "Relatórios"
+    let mut messages = load_task_session_messages(app_handle, REPORTS_SESSION_ID);
+    messages.push(Message {
+        id: None,
+        role: "user".to_string(),
+        content: format!("Relatório agendado: {} ({})", task.label, date),
+        metadata: Some(serde_json::json!({
+            "task_id": task.id,
+            "task_label": task.label,
+            "query": query,
+            "sources_count": scraped.len(),
+        })),
+    });
+    messages.push(Message {
+        id: None,
+        role: "assistant".to_string(),
+        content: report,
+        metadata: Some(serde_json::json!({
+            "task_id": task.id,
+            "sources": scraped.iter().map(|s| serde_json::json!({
+                "title": s.title,
+                "url": s.url,
+                "published_at": s.published_at,
+            })).collect::<Vec<_>>(),
+        })),
+    });
+    save_task_session_internal(app_handle, REPORTS_SESSION_ID, REPORTS_SESSION_TITLE, messages)?;
+    crate::unread::record(app_handle, REPORTS_SESSION_ID);
+    crate::digests::record(
+        app_handle,
+        DigestItemKind::ResearchBrief,
+        Some(&task.id),
+        &output.title,
+        &output.content,
+        &output.sources.iter().map(|(title, url)| DigestSource { title: title.clone(), url: url.clone() }).collect::<Vec<_>>(),
+    );
+    
+    app_handle
+        .notification()
+        .builder()
+        .title("Relatório Pronto")
+        .body(&format!("{} foi adicionado à sessão {}.", task.label, REPORTS_SESSION_TITLE))
+        .show()
+        .map_err(|e| format!("Erro ao enviar notificação: {}", e))?;
+    
+    log::info!("Relatório da task {} salvo na sessão {}", task.id, REPORTS_SESSION_ID);
+    Ok(output)
+}
+
+/// Trechos numerados das fontes ("[n] título\nURL: ...\n\ntrecho"), sem instruções suspeitas
+fn source_excerpts(app_handle: &AppHandle, scraped: &[ScrapedContent]) -> Vec<String> {
+    let guard = crate::settings::current(app_handle).content_guard;
+    scraped
+        .iter()
+        .enumerate()
+        .map(|(i, s)| {
+            let markdown = crate::content_guard::sanitize(&s.markdown, &guard).text;
+            let excerpt: String = markdown.chars().take(REPORT_SOURCE_CHARS).collect();
+            format!("[{}] {}\nURL: {}\n\n{}", i + 1, s.title, s.url, excerpt)
+        })
+        .collect()
+}
+
+/// Mensagens já gravadas numa sessão de task (vazio se ela ainda não existe)
+fn load_task_session_messages(app_handle: &AppHandle, session_id: &str) -> Vec<Message> {
+    get_chats_dir(app_handle)
+        .ok()
+        .and_then(|dir| fs::read_to_string(dir.join(format!("{}.json", session_id))).ok())
+        .and_then(|content| serde_json::from_str::<ChatSession>(&content).ok())
+        .map(|session| session.messages)
+        .unwrap_or_default()
 }
 
 /// Executa apenas ping/notificação
@@ -167,7 +454,7 @@ async fn execute_just_ping(
 }
 
 /// Helper para salvar sessão de task (sem usar State do Tauri)
-fn save_task_session_internal(
+pub(crate) fn save_task_session_internal(
     app_handle: &AppHandle,
     session_id: &str,
     title: &str,
@@ -227,7 +514,9 @@ async fn execute_custom_prompt(
     model: &str,
     app_handle: &AppHandle,
     ollama_client: &OllamaClient,
-) -> Result<(), String> {
+) -> Result<TaskOutput, String> {
+    rate_limit::acquire(&crate::settings::current(app_handle).model_rate_limits, model, RequestOrigin::Scheduler)?;
+    
     let response = ollama_client
         .query_ollama_headless(model, None, prompt)
         .await
@@ -235,8 +524,15 @@ async fn execute_custom_prompt(
     
     // Salvar como sessão
     let session_id = uuid::Uuid::new_v4().to_string();
+    let output = TaskOutput {
+        title: task.label.clone(),
+        content: response.clone(),
+        sources: Vec::new(),
+        source_excerpts: Vec::new(),
+    };
     let messages = vec![
         Message {
+            id: None,
             role: "user".to_string(),
             content: prompt.to_string(),
             metadata: Some(serde_json::json!({
@@ -245,6 +541,7 @@ async fn execute_custom_prompt(
             })),
         },
         Message {
+            id: None,
             role: "assistant".to_string(),
             content: response,
             metadata: Some(serde_json::json!({
@@ -259,6 +556,8 @@ async fn execute_custom_prompt(
         &format!("[Agendado] {}", task.label),
         messages,
     )?;
+    crate::unread::record(app_handle, &session_id);
+    crate::digests::record(app_handle, DigestItemKind::TaskOutput, Some(&task.id), &task.label, &output.content, &[]);
     
     // Notificação
     app_handle
@@ -269,6 +568,311 @@ async fn execute_custom_prompt(
         .show()
         .map_err(|e| format!("Erro ao enviar notificação: {}", e))?;
     
+    Ok(output)
+}
+
+fn weekday_name(weekday: Weekday) -> &'static str {
+    match weekday {
+        Weekday::Mon => "segunda-feira",
+        Weekday::Tue => "terça-feira",
+        Weekday::Wed => "quarta-feira",
+        Weekday::Thu => "quinta-feira",
+        Weekday::Fri => "sexta-feira",
+        Weekday::Sat => "sábado",
+        Weekday::Sun => "domingo",
+    }
+}
+
+/// Resolve as variáveis `{{nome}}` do template de `GeneratePrompt` (desconhecidas ficam como estão)
+fn render_generate_prompt(template: &str, variables: &[(&str, &str)]) -> String {
+    static VARIABLE: std::sync::OnceLock<Regex> = std::sync::OnceLock::new();
+    let regex = VARIABLE.get_or_init(|| Regex::new(r"\{\{\s*([a-z_]+)\s*\}\}").expect("valid template regex"));
+    regex
+        .replace_all(template, |caps: &regex::Captures| {
+            variables
+                .iter()
+                .find(|(name, _)| *name == &caps[1])
+                .map(|(_, value)| value.to_string())
+                .unwrap_or_else(|| caps[0].to_string())
+        })
+        .to_string()
+}
+
+/// Executa uma geração só com o modelo a partir do template da task
+async fn execute_generate_prompt(
+    task: &SentinelTask,
+    model: &str,
+    prompt_template: &str,
+    save_to_session: bool,
+    previous: Option<&TaskOutput>,
+    app_handle: &AppHandle,
+    ollama_client: &OllamaClient,
+) -> Result<TaskOutput, String> {
+    rate_limit::acquire(&crate::settings::current(app_handle).model_rate_limits, model, RequestOrigin::Scheduler)?;
+    
+    let now = Local::now();
+    let date = now.format("%d/%m/%Y").to_string();
+    let time = now.format("%H:%M").to_string();
+    let source_excerpts = previous.map(|p| p.source_excerpts.join("\n\n---\n\n")).unwrap_or_default();
+    let prompt = render_generate_prompt(
+        prompt_template,
+        &[
+            ("date", date.as_str()),
+            ("time", time.as_str()),
+            ("weekday", weekday_name(now.weekday())),
+            ("label", task.label.as_str()),
+            ("last_result", task.last_result.as_deref().unwrap_or("")),
+            ("previous_output", previous.map(|p| p.content.as_str()).unwrap_or("")),
+            ("source_excerpts", source_excerpts.as_str()),
+        ],
+    );
+    if prompt.trim().is_empty() {
+        return Err("GeneratePrompt: o prompt ficou vazio".to_string());
+    }
+    
+    log::info!("Gerando com o Ollama para a task {} (modelo: {})", task.id, model);
+    let response = ollama_client
+        .query_ollama_headless(model, None, &prompt)
+        .await
+        .map_err(|e| format!("Erro ao consultar Ollama: {}", e))?;
+    
+    let output = TaskOutput {
+        title: task.label.clone(),
+        content: response.clone(),
+        sources: previous.map(|p| p.sources.clone()).unwrap_or_default(),
+        source_excerpts: Vec::new(),
+    };
+    
+    // Uma sessão por task: as execuções se acumulam (ex: o diário de cada dia)
+    if save_to_session {
+        let session_id = format!("task-{}", task.id);
+        let mut messages = load_task_session_messages(app_handle, &session_id);
+        messages.push(Message {
+            id: None,
+            role: "user".to_string(),
+            content: prompt,
+            metadata: Some(serde_json::json!({
+                "task_id": task.id,
+                "task_label": task.label,
+            })),
+        });
+        messages.push(Message {
+            id: None,
+            role: "assistant".to_string(),
+            content: response,
+            metadata: Some(serde_json::json!({
+                "task_id": task.id,
+                "model": model,
+            })),
+        });
+        save_task_session_internal(app_handle, &session_id, &format!("[Agendado] {}", task.label), messages)?;
+        crate::unread::record(app_handle, &session_id);
+    }
+    crate::digests::record(app_handle, DigestItemKind::TaskOutput, Some(&task.id), &task.label, &output.content, &[]);
+    
+    app_handle
+        .notification()
+        .builder()
+        .title("Task Executada")
+        .body(&format!("{} foi executada com sucesso!", task.label))
+        .show()
+        .map_err(|e| format!("Erro ao enviar notificação: {}", e))?;
+    
+    Ok(output)
+}
+
+/// Grava a saída da task em arquivo, resolvendo o template de caminho
+fn execute_write_file(
+    task: &SentinelTask,
+    output: &TaskOutput,
+    path_template: &str,
+    format: OutputFormat,
+    app_handle: &AppHandle,
+) -> Result<(), String> {
+    let now = Local::now();
+    let mut path = render_path_template(path_template, &task.label, &now)?;
+    
+    if path.is_relative() {
+        let app_data_dir = app_handle
+            .path()
+            .app_data_dir()
+            .map_err(|e| format!("Failed to get app data dir: {}", e))?;
+        path = app_data_dir.join("reports").join(path);
+    }
+    
+    if path.extension().is_none() {
+        path.set_extension(format.extension());
+    }
+    
+    let content = format_task_output(task, output, format, &now.with_timezone(&Utc))?;
+    write_file_atomic(&path, &content)?;
+    
+    log::info!("Saída da task {} gravada em {:?}", task.id, path);
+    Ok(())
+}
+
+/// Resolve placeholders de data e do label da task no template de caminho
+fn render_path_template(template: &str, label: &str, now: &DateTime<Local>) -> Result<PathBuf, String> {
+    use std::fmt::Write;
+    
+    let mut rendered = String::new();
+    let mut rest = template;
+    
+    while let Some(start) = rest.find('{') {
+        rendered.push_str(&rest[..start]);
+        let after = &rest[start + 1..];
+        let end = after
+            .find('}')
+            .ok_or_else(|| format!("Template de caminho inválido (chave não fechada): {}", template))?;
+        let placeholder = &after[..end];
+        
+        let value = match placeholder {
+            "date" => now.format("%Y-%m-%d").to_string(),
+            "time" => now.format("%H-%M-%S").to_string(),
+            "datetime" => now.format("%Y-%m-%d_%H-%M-%S").to_string(),
+            "year" => now.format("%Y").to_string(),
+            "month" => now.format("%m").to_string(),
+            "day" => now.format("%d").to_string(),
+            "label" => sanitize_file_name(label),
+            other => {
+                let fmt = other
+                    .strip_prefix("date:")
+                    .ok_or_else(|| format!("Placeholder desconhecido no template: {{{}}}", other))?;
+                let mut formatted = String::new();
+                write!(formatted, "{}", now.format(fmt))
+                    .map_err(|_| format!("Formato de data inválido no template: {}", fmt))?;
+                formatted
+            }
+        };
+        
+        rendered.push_str(&value);
+        rest = &after[end + 1..];
+    }
+    rendered.push_str(rest);
+    
+    // Expandir ~ para o diretório do usuário
+    if let Some(stripped) = rendered.strip_prefix("~/").or_else(|| rendered.strip_prefix("~\\")) {
+        if let Some(home) = dirs::home_dir() {
+            return Ok(home.join(stripped));
+        }
+    }
+    
+    Ok(PathBuf::from(rendered))
+}
+
+/// Remove caracteres inválidos em nomes de arquivo (Windows é o mais restritivo)
+fn sanitize_file_name(name: &str) -> String {
+    let sanitized: String = name
+        .chars()
+        .map(|c| match c {
+            '<' | '>' | ':' | '"' | '/' | '\\' | '|' | '?' | '*' => '_',
+            c if c.is_control() => '_',
+            c => c,
+        })
+        .collect();
+    sanitized.trim().trim_end_matches('.').to_string()
+}
+
+/// Serializa a saída da task no formato escolhido
+fn format_task_output(
+    task: &SentinelTask,
+    output: &TaskOutput,
+    format: OutputFormat,
+    generated_at: &DateTime<Utc>,
+) -> Result<String, String> {
+    match format {
+        OutputFormat::Markdown => {
+            let mut md = format!(
+                "# {}\n\n_Gerado em {}_\n\n{}\n",
+                output.title,
+                generated_at.with_timezone(&Local).format("%d/%m/%Y %H:%M"),
+                output.content.trim_end()
+            );
+            if !output.sources.is_empty() {
+                md.push_str("\n## Fontes\n\n");
+                for (title, url) in &output.sources {
+                    md.push_str(&format!("- [{}]({})\n", title, url));
+                }
+            }
+            Ok(md)
+        }
+        OutputFormat::Text => {
+            let mut text = format!("{}\n\n{}\n", output.title, output.content.trim_end());
+            if !output.sources.is_empty() {
+                text.push_str("\nFontes:\n");
+                for (title, url) in &output.sources {
+                    text.push_str(&format!("- {} ({})\n", title, url));
+                }
+            }
+            Ok(text)
+        }
+        OutputFormat::Json => {
+            let value = serde_json::json!({
+                "task_id": task.id,
+                "task_label": task.label,
+                "title": output.title,
+                "content": output.content,
+                "sources": output.sources.iter().map(|(title, url)| serde_json::json!({
+                    "title": title,
+                    "url": url,
+                })).collect::<Vec<_>>(),
+                "generated_at": generated_at.to_rfc3339(),
+            });
+            serde_json::to_string_pretty(&value)
+                .map_err(|e| format!("Failed to serialize task output: {}", e))
+        }
+    }
+}
+
+/// Escrita atômica: arquivo temporário no mesmo diretório + rename
+fn write_file_atomic(path: &Path, content: &str) -> Result<(), String> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|e| format!("Failed to create output directory: {}", e))?;
+    }
+    
+    let mut temp_name = path.as_os_str().to_owned();
+    temp_name.push(".tmp");
+    let temp_path = PathBuf::from(temp_name);
+    
+    fs::write(&temp_path, content)
+        .map_err(|e| format!("Failed to write temp output file: {}", e))?;
+    
+    fs::rename(&temp_path, path)
+        .map_err(|e| format!("Failed to rename temp file to output file: {}", e))?;
+    
     Ok(())
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn test_render_path_template() {
+        let now = Local.with_ymd_and_hms(2024, 3, 9, 7, 5, 0).unwrap();
+        let path = render_path_template("/vault/{date:%Y/%m}/{date}-{label}.md", "Notícias: IA", &now).unwrap();
+        assert_eq!(path, PathBuf::from("/vault/2024/03/2024-03-09-Notícias_ IA.md"));
+
+        assert!(render_path_template("/vault/{unknown}.md", "x", &now).is_err());
+        assert!(render_path_template("/vault/{date.md", "x", &now).is_err());
+    }
+
+    #[test]
+    fn test_render_report_prompt() {
+        let prompt = render_report_prompt(DEFAULT_REPORT_PROMPT, "IA local", "09/03/2024", "[1] Fonte");
+        assert!(prompt.starts_with("Crie um relatório sobre 'IA local' com base nas fontes abaixo (09/03/2024)."));
+        assert!(prompt.contains("## FONTES\n[1] Fonte\n"));
+    }
+
+    #[test]
+    fn test_render_generate_prompt() {
+        let prompt = render_generate_prompt(
+            "Diário de {{weekday}}, {{ date }}.\nOntem: {{last_result}}\n{{unknown}}",
+            &[("date", "09/03/2024"), ("weekday", "sábado"), ("last_result", "")],
+        );
+        assert_eq!(prompt, "Diário de sábado, 09/03/2024.\nOntem: \n{{unknown}}");
+        assert_eq!(weekday_name(Weekday::Mon), "segunda-feira");
+    }
+}
@@ -0,0 +1,216 @@
+use crate::ollama_client::OllamaClient;
+use crate::web_scraper::ScrapedContent;
+
+/// Máximo de caracteres enviados ao modelo por chamada (a fonte é traduzida em trechos)
+const MAX_TRANSLATION_CHARS: usize = 6000;
+/// Trechos traduzidos por fonte (evita traduções gigantes); o restante segue no idioma original
+const MAX_TRANSLATION_CHUNKS: usize = 4;
+/// Confiança mínima do detector para considerar o idioma confiável
+const MIN_DETECTION_CONFIDENCE: f64 = 0.5;
+
+/// Detecta o idioma de um texto e retorna o código ISO 639-1 (ou 639-3 quando não houver)
+pub fn detect_language(text: &str) -> Option<String> {
+    let info = whatlang::detect(text)?;
+    if !info.is_reliable() && info.confidence() < MIN_DETECTION_CONFIDENCE {
+        return None;
+    }
+    Some(lang_to_code(info.lang()).to_string())
+}
+
+/// Converte whatlang::Lang para código curto
+pub fn lang_to_code(lang: whatlang::Lang) -> &'static str {
+    use whatlang::Lang;
+    match lang {
+        Lang::Por => "pt",
+        Lang::Eng => "en",
+        Lang::Spa => "es",
+        Lang::Fra => "fr",
+        Lang::Deu => "de",
+        Lang::Ita => "it",
+        Lang::Nld => "nl",
+        Lang::Rus => "ru",
+        Lang::Ukr => "uk",
+        Lang::Pol => "pl",
+        Lang::Tur => "tr",
+        Lang::Cmn => "zh",
+        Lang::Jpn => "ja",
+        Lang::Kor => "ko",
+        Lang::Ara => "ar",
+        Lang::Hin => "hi",
+        other => other.code(),
+    }
+}
+
+/// Nome legível do idioma para usar nos prompts
+pub fn language_name(code: &str) -> &str {
+    match code {
+        "pt" => "Português",
+        "en" => "English",
+        "es" => "Español",
+        "fr" => "Français",
+        "de" => "Deutsch",
+        "it" => "Italiano",
+        "nl" => "Nederlands",
+        "ru" => "Русский",
+        "zh" => "中文",
+        "ja" => "日本語",
+        "ko" => "한국어",
+        other => other,
+    }
+}
+
+/// Normaliza códigos como "pt-BR" / "PT" para "pt"
+fn normalize_code(code: &str) -> String {
+    code.split(['-', '_'])
+        .next()
+        .unwrap_or(code)
+        .trim()
+        .to_lowercase()
+}
+
+/// Traduz um texto para o idioma alvo usando um modelo local do Ollama
+pub async fn translate_text(
+    client: &OllamaClient,
+    model: &str,
+    text: &str,
+    target_language: &str,
+) -> Result<String, String> {
+    let target = normalize_code(target_language);
+    let system_prompt = format!(
+        "Você é um tradutor profissional. Traduza o texto do usuário para {}. \
+        Preserve a formatação Markdown, links, números e nomes próprios. \
+        Responda APENAS com a tradução, sem comentários.",
+        language_name(&target)
+    );
+
+    client
+        .query_ollama_headless(model, Some(&system_prompt), text)
+        .await
+}
+
+/// Divide o texto em trechos de até `max_chars` caracteres, cortando de preferência entre
+/// parágrafos ou linhas
+fn split_chunks(text: &str, max_chars: usize) -> Vec<String> {
+    let mut chunks = Vec::new();
+    let mut rest = text;
+    while !rest.is_empty() {
+        if rest.chars().count() <= max_chars {
+            chunks.push(rest.to_string());
+            break;
+        }
+        let limit = rest.char_indices().nth(max_chars).map_or(rest.len(), |(index, _)| index);
+        let window = &rest[..limit];
+        let paragraph = window.rfind("\n\n").map(|index| index + 2).filter(|&cut| cut > limit / 2);
+        let cut = paragraph
+            .or_else(|| window.rfind('\n').map(|index| index + 1).filter(|&cut| cut > limit / 2))
+            .unwrap_or(limit);
+        chunks.push(rest[..cut].to_string());
+        rest = &rest[cut..];
+    }
+    chunks
+}
+
+/// Traduz fontes extraídas que não estejam no idioma alvo, antes de irem para o contexto/RAG.
+/// Fontes que falham na tradução são mantidas no idioma original.
+pub async fn translate_scraped_contents(
+    client: &OllamaClient,
+    model: &str,
+    contents: Vec<ScrapedContent>,
+    target_language: &str,
+) -> Vec<ScrapedContent> {
+    let target = normalize_code(target_language);
+    let mut results = Vec::with_capacity(contents.len());
+
+    for mut item in contents {
+        let detected = match detect_language(&item.content) {
+            Some(lang) => lang,
+            None => {
+                results.push(item);
+                continue;
+            }
+        };
+
+        if detected == target {
+            results.push(item);
+            continue;
+        }
+
+        // Trechos além do limite (ou após uma falha) ficam no idioma original, marcados
+        let chunks = split_chunks(&item.content, MAX_TRANSLATION_CHARS);
+        let mut translated_parts = Vec::new();
+        for chunk in chunks.iter().take(MAX_TRANSLATION_CHUNKS) {
+            match translate_text(client, model, chunk, &target).await {
+                Ok(translated) => translated_parts.push(translated),
+                Err(e) => {
+                    log::warn!("[Translation] Falha ao traduzir {}: {}", item.url, e);
+                    break;
+                }
+            }
+        }
+        item.original_language = Some(detected.clone());
+        if translated_parts.is_empty() {
+            results.push(item);
+            continue;
+        }
+
+        let untranslated = chunks[translated_parts.len()..].concat();
+        let mut translated = translated_parts.join("\n\n");
+        if !untranslated.trim().is_empty() {
+            translated.push_str(&format!(
+                "\n\n[Trecho a seguir não traduzido, no idioma original ({})]\n\n{}",
+                detected, untranslated
+            ));
+        }
+        log::info!(
+            "[Translation] {} traduzido de {} para {} ({} de {} trecho(s))",
+            item.url, detected, target, translated_parts.len(), chunks.len()
+        );
+        item.markdown = format!(
+            "---\nTitle: {}\nSource: {}\nOriginal-Language: {}\nTranslated-To: {}\nTranslated-Parts: {}/{}\n---\n\n{}",
+            item.title,
+            item.url,
+            detected,
+            target,
+            translated_parts.len(),
+            chunks.len(),
+            translated
+        );
+        item.content = translated;
+
+        results.push(item);
+    }
+
+    results
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_detect_language() {
+        assert_eq!(
+            detect_language("O rato roeu a roupa do rei de Roma e a rainha ficou muito brava com isso").as_deref(),
+            Some("pt")
+        );
+        assert_eq!(
+            detect_language("The quick brown fox jumps over the lazy dog while the farmer is sleeping").as_deref(),
+            Some("en")
+        );
+        assert_eq!(normalize_code("pt-BR"), "pt");
+    }
+
+    #[test]
+    fn test_split_chunks() {
+        let text = format!("{}\n\n{}\n{}", "a".repeat(40), "b".repeat(30), "c".repeat(30));
+        let chunks = split_chunks(&text, 50);
+        assert_eq!(chunks, vec![format!("{}\n\n", "a".repeat(40)), format!("{}\n", "b".repeat(30)), "c".repeat(30)]);
+        assert_eq!(chunks.concat(), text);
+
+        // Sem quebra de linha: corte no limite, sem perder caracteres multibyte
+        let chunks = split_chunks(&"é".repeat(25), 10);
+        assert_eq!(chunks.len(), 3);
+        assert_eq!(chunks.concat(), "é".repeat(25));
+        assert!(split_chunks("", 10).is_empty());
+    }
+}
@@ -0,0 +1,201 @@
+use crate::scheduler::SchedulerState;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+use tauri::menu::{Menu, MenuItem, PredefinedMenuItem};
+use tauri::tray::{MouseButton, MouseButtonState, TrayIcon, TrayIconBuilder, TrayIconEvent};
+use tauri::{App, AppHandle, Emitter, Manager, Wry};
+
+/// Intervalo de verificação do status do Ollama exibido no tray
+const STATUS_POLL_INTERVAL: Duration = Duration::from_secs(10);
+
+pub const TRAY_ID: &str = "main-tray";
+
+/// Último status do Ollama verificado (reaproveitado ao atualizar o tooltip fora do polling)
+static OLLAMA_RUNNING: AtomicBool = AtomicBool::new(false);
+
+/// Itens do menu que mudam de texto conforme o estado
+#[derive(Clone)]
+struct TrayItems {
+    status: MenuItem<Wry>,
+    toggle_ollama: MenuItem<Wry>,
+    toggle_scheduler: MenuItem<Wry>,
+}
+
+/// Mostra e foca a janela principal (criando-a se o app subiu em segundo plano)
+pub fn show_main_window(app: &AppHandle) {
+    match crate::autostart::main_window(app) {
+        Ok(window) => {
+            let _ = window.show();
+            let _ = window.unminimize();
+            let _ = window.set_focus();
+        }
+        Err(e) => log::warn!("[Tray] Falha ao abrir a janela principal: {}", e),
+    }
+}
+
+/// Cria o ícone do tray com status do Ollama e ações rápidas
+pub fn setup_tray(app: &App) -> tauri::Result<()> {
+    let status = MenuItem::with_id(app, "status", "Ollama: verificando...", false, None::<&str>)?;
+    let show = MenuItem::with_id(app, "show", "Mostrar OllaHub", true, None::<&str>)?;
+    let open_last_chat = MenuItem::with_id(app, "open_last_chat", "Abrir último chat", true, None::<&str>)?;
+    let toggle_ollama = MenuItem::with_id(app, "toggle_ollama", "Iniciar Ollama", true, None::<&str>)?;
+    let toggle_scheduler = MenuItem::with_id(app, "toggle_scheduler", "Pausar tarefas", true, None::<&str>)?;
+    let quit = MenuItem::with_id(app, "quit", "Sair", true, None::<&str>)?;
+
+    let menu = Menu::with_items(
+        app,
+        &[
+            &status,
+            &PredefinedMenuItem::separator(app)?,
+            &show,
+            &open_last_chat,
+            &PredefinedMenuItem::separator(app)?,
+            &toggle_ollama,
+            &toggle_scheduler,
+            &PredefinedMenuItem::separator(app)?,
+            &quit,
+        ],
+    )?;
+
+    let items = TrayItems {
+        status,
+        toggle_ollama,
+        toggle_scheduler,
+    };
+    let items_for_menu = items.clone();
+
+    let mut builder = TrayIconBuilder::with_id(TRAY_ID)
+        .tooltip("OllaHub")
+        .menu(&menu)
+        .show_menu_on_left_click(false)
+        .on_menu_event(move |app, event| {
+            handle_menu_event(app, event.id.as_ref(), &items_for_menu);
+        })
+        .on_tray_icon_event(|tray, event| {
+            if let TrayIconEvent::Click {
+                button: MouseButton::Left,
+                button_state: MouseButtonState::Up,
+                ..
+            } = event
+            {
+                show_main_window(tray.app_handle());
+            }
+        });
+
+    if let Some(icon) = app.default_window_icon() {
+        builder = builder.icon(icon.clone());
+    }
+
+    let tray = builder.build(app)?;
+
+    // Atualizar status do Ollama periodicamente
+    let app_handle = app.handle().clone();
+    tauri::async_runtime::spawn(async move {
+        loop {
+            refresh_status(&app_handle, &tray, &items).await;
+            tokio::time::sleep(STATUS_POLL_INTERVAL).await;
+        }
+    });
+
+    Ok(())
+}
+
+/// Atualiza textos do menu e tooltip conforme o estado atual
+async fn refresh_status(app: &AppHandle, tray: &TrayIcon, items: &TrayItems) {
+    let running = crate::check_ollama_running(app.clone()).await;
+    OLLAMA_RUNNING.store(running, Ordering::Relaxed);
+
+    let _ = items.status.set_text(if running { "Ollama: rodando" } else { "Ollama: parado" });
+    let _ = items.toggle_ollama.set_text(if running { "Parar Ollama" } else { "Iniciar Ollama" });
+    let _ = tray.set_tooltip(Some(tooltip(running, crate::unread::total())));
+
+    let paused = {
+        let scheduler = app.state::<SchedulerState>();
+        let sched = scheduler.lock().await;
+        sched.is_paused()
+    };
+    let _ = items.toggle_scheduler.set_text(if paused { "Retomar tarefas" } else { "Pausar tarefas" });
+}
+
+fn tooltip(running: bool, unread: i64) -> String {
+    let status = if running { "OllaHub — Ollama rodando" } else { "OllaHub — Ollama parado" };
+    match unread {
+        0 => status.to_string(),
+        1 => format!("{} — 1 resposta não lida", status),
+        n => format!("{} — {} respostas não lidas", status, n),
+    }
+}
+
+/// Contador de respostas não lidas no ícone do app e no tray
+pub fn set_unread_badge(app: &AppHandle, total: i64) {
+    let count = (total > 0).then_some(total);
+    if let Some(window) = app.get_webview_window("main") {
+        // Não suportado em todas as plataformas (ex: Windows); o tray continua mostrando
+        if let Err(e) = window.set_badge_count(count) {
+            log::debug!("[Tray] Badge não suportado: {}", e);
+        }
+    }
+    if let Some(tray) = app.tray_by_id(TRAY_ID) {
+        let _ = tray.set_title(count.map(|n| n.to_string()));
+        let _ = tray.set_tooltip(Some(tooltip(OLLAMA_RUNNING.load(Ordering::Relaxed), total)));
+    }
+}
+
+fn handle_menu_event(app: &AppHandle, id: &str, items: &TrayItems) {
+    match id {
+        "show" => show_main_window(app),
+        "open_last_chat" => {
+            show_main_window(app);
+            match crate::db::Database::new(app).and_then(|db| db.list_sessions()) {
+                Ok(sessions) => {
+                    if let Some(last) = sessions.first() {
+                        let _ = app.emit("tray-open-chat", &last.id);
+                    }
+                }
+                Err(e) => log::warn!("[Tray] Falha ao buscar último chat: {}", e),
+            }
+        }
+        "toggle_ollama" => {
+            let app = app.clone();
+            let items = items.clone();
+            tauri::async_runtime::spawn(async move {
+                if crate::check_ollama_running(app.clone()).await {
+                    let stopped = crate::stop_ollama_processes();
+                    log::info!("[Tray] {} processo(s) do Ollama encerrado(s)", stopped);
+                } else if let Err(e) = crate::start_ollama_server(app.clone()) {
+                    log::error!("[Tray] Falha ao iniciar Ollama: {}", e);
+                } else {
+                    // Dar tempo para o servidor subir antes de atualizar o status
+                    tokio::time::sleep(Duration::from_secs(2)).await;
+                }
+
+                if let Some(tray) = app.tray_by_id(TRAY_ID) {
+                    refresh_status(&app, &tray, &items).await;
+                }
+            });
+        }
+        "toggle_scheduler" => {
+            let app = app.clone();
+            let items = items.clone();
+            tauri::async_runtime::spawn(async move {
+                let paused = {
+                    let scheduler = app.state::<SchedulerState>();
+                    let mut sched = scheduler.lock().await;
+                    let paused = !sched.is_paused();
+                    sched.set_paused(paused);
+                    paused
+                };
+                let _ = app.emit("scheduler-paused-changed", paused);
+
+                if let Some(tray) = app.tray_by_id(TRAY_ID) {
+                    refresh_status(&app, &tray, &items).await;
+                }
+            });
+        }
+        "quit" => {
+            log::info!("[Tray] Encerrando aplicação");
+            app.exit(0);
+        }
+        _ => {}
+    }
+}
@@ -0,0 +1,70 @@
+use crate::db::Database;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicI64, Ordering};
+use tauri::{AppHandle, Emitter, Manager};
+
+/// Total de não lidas exibido no tray (atualizado a cada mudança)
+static UNREAD_TOTAL: AtomicI64 = AtomicI64::new(0);
+
+/// Respostas não vistas pelo usuário, por sessão
+#[derive(Serialize, Clone, Debug, Default)]
+pub struct UnreadCounts {
+    pub sessions: HashMap<String, i64>,
+    pub total: i64,
+}
+
+pub fn total() -> i64 {
+    UNREAD_TOTAL.load(Ordering::Relaxed)
+}
+
+/// Janela principal oculta no tray ou minimizada (o usuário não está vendo a resposta)
+pub fn window_hidden(app: &AppHandle) -> bool {
+    match app.get_webview_window("main") {
+        Some(window) => !window.is_visible().unwrap_or(true) || window.is_minimized().unwrap_or(false),
+        None => true,
+    }
+}
+
+pub fn counts(app: &AppHandle) -> Result<UnreadCounts, String> {
+    let rows = Database::new(app)
+        .and_then(|db| db.get_unread_counts())
+        .map_err(|e| format!("Failed to load unread counts: {}", e))?;
+    let total = rows.iter().map(|(_, count)| count).sum();
+    Ok(UnreadCounts {
+        sessions: rows.into_iter().collect(),
+        total,
+    })
+}
+
+/// Registra uma resposta concluída em background (task agendada ou janela oculta)
+pub fn record(app: &AppHandle, session_id: &str) {
+    if let Err(e) = Database::new(app).and_then(|db| db.add_unread(session_id)) {
+        log::warn!("[Unread] Falha ao registrar não lida em {}: {}", session_id, e);
+        return;
+    }
+    refresh(app);
+}
+
+pub fn mark_read(app: &AppHandle, session_id: &str) -> Result<UnreadCounts, String> {
+    Database::new(app)
+        .and_then(|db| db.mark_session_read(session_id))
+        .map_err(|e| format!("Failed to mark session as read: {}", e))?;
+    let counts = counts(app)?;
+    publish(app, &counts);
+    Ok(counts)
+}
+
+/// Recalcula os contadores e atualiza badge, tray e frontend
+pub fn refresh(app: &AppHandle) {
+    match counts(app) {
+        Ok(counts) => publish(app, &counts),
+        Err(e) => log::warn!("[Unread] {}", e),
+    }
+}
+
+fn publish(app: &AppHandle, counts: &UnreadCounts) {
+    UNREAD_TOTAL.store(counts.total, Ordering::Relaxed);
+    crate::tray::set_unread_badge(app, counts.total);
+    let _ = app.emit("unread-changed", counts);
+}
@@ -0,0 +1,232 @@
+//! Perfil do usuário compartilhado entre as sessões
+//!
+//! Preferências, nível de conhecimento, idioma e tom preferidos, gravados em
+//! `user_profile.json` (local, fora do settings.json e da sincronização). Nada é gravado sem
+//! consentimento explícito (`set_user_profile_consent`); revogá-lo apaga o perfil. Com o
+//! perfil ativo, `chat_stream` coloca um resumo compacto no início do system prompt.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::PathBuf;
+use tauri::{AppHandle, Manager};
+
+/// Prefixo do erro de `update_user_profile` sem consentimento
+pub const CONSENT_REQUIRED: &str = "USER_PROFILE_CONSENT_REQUIRED";
+/// Preferências guardadas
+const MAX_PREFERENCES: usize = 20;
+const MAX_FIELD_CHARS: usize = 200;
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ExpertiseLevel {
+    Beginner,
+    Intermediate,
+    Expert,
+}
+
+impl ExpertiseLevel {
+    fn describe(self) -> &'static str {
+        match self {
+            ExpertiseLevel::Beginner => "iniciante (explique termos técnicos)",
+            ExpertiseLevel::Intermediate => "intermediário",
+            ExpertiseLevel::Expert => "avançado (vá direto ao ponto)",
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct UserProfile {
+    /// Consentimento para guardar e usar o perfil
+    #[serde(default)]
+    pub consent: bool,
+    #[serde(default)]
+    pub consented_at: Option<DateTime<Utc>>,
+    /// Inclui o perfil no system prompt (desligável sem apagar o perfil)
+    #[serde(default)]
+    pub enabled: bool,
+    #[serde(default)]
+    pub preferred_language: Option<String>,
+    /// "direto", "formal", "descontraído"...
+    #[serde(default)]
+    pub tone: Option<String>,
+    #[serde(default)]
+    pub expertise: Option<ExpertiseLevel>,
+    #[serde(default)]
+    pub preferences: Vec<String>,
+    #[serde(default)]
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+/// Alteração parcial do perfil: campos ausentes ficam como estão, texto vazio limpa o campo
+#[derive(Deserialize, Clone, Debug, Default)]
+pub struct UserProfilePatch {
+    pub enabled: Option<bool>,
+    pub preferred_language: Option<String>,
+    pub tone: Option<String>,
+    pub expertise: Option<ExpertiseLevel>,
+    /// Substitui a lista inteira
+    pub preferences: Option<Vec<String>>,
+}
+
+fn clean(value: &str) -> Option<String> {
+    let value = value.split_whitespace().collect::<Vec<_>>().join(" ");
+    if value.is_empty() {
+        return None;
+    }
+    Some(value.chars().take(MAX_FIELD_CHARS).collect())
+}
+
+impl UserProfile {
+    /// Aplica a alteração (exige consentimento)
+    pub fn apply(&mut self, patch: UserProfilePatch) -> Result<(), String> {
+        if !self.consent {
+            return Err(format!("{}: enable the user profile with set_user_profile_consent first", CONSENT_REQUIRED));
+        }
+        if let Some(enabled) = patch.enabled {
+            self.enabled = enabled;
+        }
+        if let Some(language) = patch.preferred_language {
+            self.preferred_language = clean(&language);
+        }
+        if let Some(tone) = patch.tone {
+            self.tone = clean(&tone);
+        }
+        if patch.expertise.is_some() {
+            self.expertise = patch.expertise;
+        }
+        if let Some(preferences) = patch.preferences {
+            let mut cleaned: Vec<String> = Vec::new();
+            for preference in preferences.iter().filter_map(|p| clean(p)) {
+                if !cleaned.iter().any(|p| p.to_lowercase() == preference.to_lowercase()) {
+                    cleaned.push(preference);
+                }
+            }
+            cleaned.truncate(MAX_PREFERENCES);
+            self.preferences = cleaned;
+        }
+        self.updated_at = Some(Utc::now());
+        Ok(())
+    }
+
+    /// Dá ou revoga o consentimento; revogar apaga todo o perfil
+    pub fn set_consent(&mut self, consent: bool) {
+        if consent {
+            if !self.consent {
+                self.consent = true;
+                self.enabled = true;
+                self.consented_at = Some(Utc::now());
+            }
+        } else {
+            *self = UserProfile::default();
+        }
+    }
+
+    /// Resumo do perfil para o início do system prompt (None se desativado ou vazio)
+    pub fn prompt_prefix(&self) -> Option<String> {
+        if !self.consent || !self.enabled {
+            return None;
+        }
+        let mut parts = Vec::new();
+        if let Some(language) = &self.preferred_language {
+            parts.push(format!("idioma preferido: {}", crate::translation::language_name(language)));
+        }
+        if let Some(tone) = &self.tone {
+            parts.push(format!("tom: {}", tone));
+        }
+        if let Some(expertise) = self.expertise {
+            parts.push(format!("nível: {}", expertise.describe()));
+        }
+        if !self.preferences.is_empty() {
+            parts.push(format!("preferências: {}", self.preferences.join("; ")));
+        }
+        if parts.is_empty() {
+            return None;
+        }
+        Some(format!("Perfil do usuário: {}.", parts.join(" | ")))
+    }
+}
+
+fn profile_path(app_handle: &AppHandle) -> Result<PathBuf, String> {
+    let app_data_dir = app_handle
+        .path()
+        .app_data_dir()
+        .map_err(|e| format!("Failed to get app data dir: {}", e))?;
+    Ok(app_data_dir.join("user_profile.json"))
+}
+
+/// Perfil gravado (o padrão, sem consentimento, se ainda não existe)
+pub fn load(app_handle: &AppHandle) -> Result<UserProfile, String> {
+    let path = profile_path(app_handle)?;
+    if !path.exists() {
+        return Ok(UserProfile::default());
+    }
+    let content = fs::read_to_string(&path).map_err(|e| format!("Failed to read user_profile.json: {}", e))?;
+    serde_json::from_str(&content).map_err(|e| format!("Failed to parse user_profile.json: {}", e))
+}
+
+/// Grava o perfil; sem consentimento, apaga o arquivo
+pub fn save(app_handle: &AppHandle, profile: &UserProfile) -> Result<(), String> {
+    let path = profile_path(app_handle)?;
+    if !profile.consent {
+        if path.exists() {
+            fs::remove_file(&path).map_err(|e| format!("Failed to remove user_profile.json: {}", e))?;
+        }
+        return Ok(());
+    }
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| format!("Failed to create app data dir: {}", e))?;
+    }
+    let json = serde_json::to_string_pretty(profile).map_err(|e| format!("Failed to serialize user profile: {}", e))?;
+    fs::write(&path, json).map_err(|e| format!("Failed to write user_profile.json: {}", e))
+}
+
+/// Prefixo do system prompt para `chat_stream`
+pub fn prompt_prefix(app_handle: &AppHandle) -> Option<String> {
+    match load(app_handle) {
+        Ok(profile) => profile.prompt_prefix(),
+        Err(e) => {
+            log::warn!("[Profile] Perfil do usuário ignorado: {}", e);
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_consent_and_prompt_prefix() {
+        let mut profile = UserProfile::default();
+        let patch = UserProfilePatch { tone: Some("direto".to_string()), ..Default::default() };
+        assert!(profile.apply(patch.clone()).unwrap_err().starts_with(CONSENT_REQUIRED));
+
+        profile.set_consent(true);
+        assert!(profile.enabled);
+        assert!(profile.prompt_prefix().is_none());
+
+        profile.apply(patch).unwrap();
+        profile
+            .apply(UserProfilePatch {
+                preferred_language: Some("pt".to_string()),
+                expertise: Some(ExpertiseLevel::Expert),
+                preferences: Some(vec!["Usa Rust".to_string(), " usa  rust ".to_string(), String::new()]),
+                ..Default::default()
+            })
+            .unwrap();
+        assert_eq!(profile.preferences, vec!["Usa Rust"]);
+        assert_eq!(
+            profile.prompt_prefix().unwrap(),
+            "Perfil do usuário: idioma preferido: Português | tom: direto | nível: avançado (vá direto ao ponto) | preferências: Usa Rust."
+        );
+
+        // Texto vazio limpa o campo; desativar mantém o perfil mas tira do prompt
+        profile.apply(UserProfilePatch { tone: Some("  ".to_string()), enabled: Some(false), ..Default::default() }).unwrap();
+        assert!(profile.tone.is_none());
+        assert!(profile.prompt_prefix().is_none());
+
+        profile.set_consent(false);
+        assert_eq!(profile, UserProfile::default());
+    }
+}
@@ -2,45 +2,137 @@ use anyhow::Result;
 use headless_chrome::{Browser, LaunchOptions, Tab};
 use reqwest::header::USER_AGENT;
 use scraper::{Html, Selector};
-use std::sync::{Arc, Mutex, OnceLock};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, OnceLock, RwLock};
 use std::time::Duration;
 use url::Url;
 use rand::Rng;
 use tokio::sync::Semaphore;
 use regex::Regex;
 use std::time::Instant;
+use tauri::AppHandle;
+use crate::sources_config::{ExtractionRule, ProxyConfig, SearchApiConfig, PROXY_DIRECT};
+use crate::engine_health::{self, Outcome};
+use crate::query_expansion;
+use crate::resolvers::{self, Resolved};
+
+/// Regras de extração por domínio definidas em sources.json
+static EXTRACTION_RULES: OnceLock<RwLock<Vec<ExtractionRule>>> = OnceLock::new();
+
+pub fn set_extraction_rules(rules: Vec<ExtractionRule>) {
+    let lock = EXTRACTION_RULES.get_or_init(|| RwLock::new(Vec::new()));
+    if let Ok(mut current) = lock.write() {
+        if !rules.is_empty() {
+            log::info!("[Extraction] {} regra(s) de extração por domínio carregada(s)", rules.len());
+        }
+        *current = rules;
+    }
+}
 
-/// Lazy-initialized global browser instance
-/// Evita criar o browser no startup, economizando ~500MB de RAM até ser necessário
-static LAZY_BROWSER: OnceLock<Mutex<Option<Arc<Browser>>>> = OnceLock::new();
+/// Regra mais específica (domínio mais longo) que vale para a URL
+fn extraction_rule_for(url: &str) -> Option<ExtractionRule> {
+    let host = Url::parse(url).ok()?.host_str()?.to_string();
+    let rules = EXTRACTION_RULES.get()?.read().ok()?;
+    rules
+        .iter()
+        .filter(|rule| rule.matches_host(&host))
+        .max_by_key(|rule| rule.domain.len())
+        .cloned()
+}
 
-/// Obtém ou cria a instância global do browser (lazy initialization)
-pub fn get_or_create_browser() -> Result<Arc<Browser>> {
-    let mutex = LAZY_BROWSER.get_or_init(|| Mutex::new(None));
-    let mut guard = mutex.lock().map_err(|e| anyhow::anyhow!("Browser mutex poisoned: {}", e))?;
-    
-    if guard.is_none() {
-        log::info!("[LazyBrowser] Initializing headless browser on first use...");
-        let browser = create_browser()?;
-        *guard = Some(Arc::new(browser));
-        log::info!("[LazyBrowser] Browser initialized successfully");
+/// Proxy configurado em sources.json (atualizado ao salvar a configuração de fontes)
+static PROXY_CONFIG: OnceLock<RwLock<ProxyConfig>> = OnceLock::new();
+/// Posição do rodízio entre os proxies da lista
+static PROXY_CURSOR: AtomicUsize = AtomicUsize::new(0);
+
+/// Aplica a configuração de proxy; o browser é recriado para usar o novo proxy
+pub fn set_proxy_config(config: ProxyConfig) {
+    let lock = PROXY_CONFIG.get_or_init(|| RwLock::new(ProxyConfig::default()));
+    if let Ok(mut current) = lock.write() {
+        if *current == config {
+            return;
+        }
+        log::info!(
+            "[Proxy] Configuração atualizada (ativo: {}, {} proxies, {} overrides)",
+            config.enabled,
+            config.proxies.len(),
+            config.engine_overrides.len()
+        );
+        *current = config;
     }
-    
-    Ok(guard.as_ref().unwrap().clone())
+    crate::browser_pool::clear();
 }
 
-/// Limpa a instância do browser (para liberar memória quando não em uso)
-pub fn clear_browser() {
-    if let Some(mutex) = LAZY_BROWSER.get() {
-        if let Ok(mut guard) = mutex.lock() {
-            if guard.is_some() {
-                log::info!("[LazyBrowser] Clearing browser instance to free memory");
-                *guard = None;
-            }
+/// Proxy da próxima requisição: override do motor, senão o próximo da lista (rodízio)
+fn next_proxy(engine: Option<SearchEngine>) -> Option<String> {
+    let config = PROXY_CONFIG.get()?.read().ok()?;
+    if !config.enabled {
+        return None;
+    }
+    if let Some(engine) = engine {
+        let name = engine.as_str().to_lowercase();
+        if let Some(proxy) = config.engine_overrides.get(&name) {
+            return if proxy == PROXY_DIRECT { None } else { Some(proxy.clone()) };
+        }
+    }
+    if config.proxies.is_empty() {
+        return None;
+    }
+    let index = PROXY_CURSOR.fetch_add(1, Ordering::Relaxed) % config.proxies.len();
+    Some(config.proxies[index].clone())
+}
+
+/// Motores de busca configurados em sources.json (ordem, ativação, chaves e URLs próprias)
+static SEARCH_APIS: OnceLock<RwLock<SearchApiConfig>> = OnceLock::new();
+
+pub fn set_search_apis(config: SearchApiConfig) {
+    let lock = SEARCH_APIS.get_or_init(|| RwLock::new(SearchApiConfig::default()));
+    if let Ok(mut current) = lock.write() {
+        if !config.engines.is_empty() {
+            log::info!(
+                "[SearchEngine] Motores configurados: {:?}",
+                config.engines.iter().filter(|e| e.enabled).map(|e| e.name.as_str()).collect::<Vec<_>>()
+            );
         }
+        *current = config;
+    }
+}
+
+pub(crate) fn current_search_apis() -> SearchApiConfig {
+    SEARCH_APIS
+        .get()
+        .and_then(|lock| lock.read().ok().map(|config| config.clone()))
+        .unwrap_or_default()
+}
+
+/// Builder de cliente HTTP com o proxy configurado (se houver)
+pub(crate) fn client_builder(engine: Option<SearchEngine>) -> reqwest::ClientBuilder {
+    let builder = reqwest::Client::builder();
+    match next_proxy(engine) {
+        Some(proxy_url) => match reqwest::Proxy::all(proxy_url.as_str()) {
+            Ok(proxy) => builder.proxy(proxy),
+            Err(e) => {
+                log::warn!("[Proxy] URL inválida, seguindo sem proxy: {}", e);
+                builder
+            }
+        },
+        None => builder,
     }
 }
 
+/// Argumento `--proxy-server` do Chrome (sem credenciais, que o Chrome não aceita na URL)
+fn chrome_proxy_arg(proxy_url: &str) -> Option<String> {
+    let mut url = Url::parse(proxy_url).ok()?;
+    if !url.username().is_empty() {
+        log::warn!("[Proxy] O headless browser não suporta proxy com autenticação; credenciais ignoradas");
+        let _ = url.set_username("");
+        let _ = url.set_password(None);
+    }
+    let host = url.host_str()?;
+    let port = url.port_or_known_default().map(|p| format!(":{}", p)).unwrap_or_default();
+    Some(format!("--proxy-server={}://{}{}", url.scheme(), host, port))
+}
+
 /// Resultado da extração de conteúdo de uma URL
 #[derive(serde::Serialize, serde::Deserialize, Clone, Debug)]
 pub struct ScrapedContent {
@@ -48,6 +140,12 @@ pub struct ScrapedContent {
     pub url: String,
     pub content: String,
     pub markdown: String,
+    /// Idioma original quando o conteúdo foi traduzido (ou detectado e mantido)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub original_language: Option<String>,
+    /// Data de publicação extraída dos metadados da página (quando disponível)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub published_at: Option<String>,
 }
 
 /// Metadados de resultado de busca (leve, sem abrir página)
@@ -65,6 +163,8 @@ pub struct SearchCategory {
     pub name: String,
     pub base_sites: Vec<String>,
     pub enabled: bool,
+    #[serde(default = "crate::sources_config::default_weight")]
+    pub weight: u32,
 }
 
 /// Configuração completa de busca
@@ -89,18 +189,27 @@ fn default_total_sources() -> usize {
     100
 }
 
+/// Endpoint da Brave Search API (busca web)
+const BRAVE_API_URL: &str = "https://api.search.brave.com/res/v1/web/search";
+/// Máximo de resultados por requisição aceito pela Brave Search API
+const BRAVE_API_MAX_COUNT: usize = 20;
+
 /// Enum para identificar diferentes motores de busca
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum SearchEngine {
     Google,
     Bing,
     Yahoo,
     DuckDuckGo,
     Startpage,
+    /// Instância SearxNG (API JSON, sem scraping de HTML)
+    Searx { base_url: String },
+    /// Brave Search API (requer chave)
+    BraveApi { api_key: String },
 }
 
 impl SearchEngine {
-    /// Converte string para SearchEngine
+    /// Converte string para SearchEngine (apenas motores que não precisam de configuração)
     pub fn from_str(s: &str) -> Option<Self> {
         match s.to_lowercase().as_str() {
             "google" => Some(SearchEngine::Google),
@@ -112,6 +221,81 @@ impl SearchEngine {
         }
     }
 
+    /// Converte string para SearchEngine, incluindo os motores via API configurados em sources.json
+    pub fn from_config(s: &str, apis: &SearchApiConfig) -> Option<Self> {
+        match s.to_lowercase().as_str() {
+            "searx" | "searxng" => SearchEngine::searx(apis),
+            "brave" | "brave_api" | "braveapi" => SearchEngine::brave_api(apis),
+            _ => SearchEngine::from_str(s),
+        }
+    }
+
+    pub fn searx(apis: &SearchApiConfig) -> Option<Self> {
+        apis.engine("searx")
+            .and_then(|e| e.base_url.as_deref())
+            .filter(|u| !u.trim().is_empty())
+            .or(apis.searx_url.as_deref())
+            .map(|u| u.trim().trim_end_matches('/'))
+            .filter(|u| !u.is_empty())
+            .map(|u| SearchEngine::Searx { base_url: u.to_string() })
+    }
+
+    pub fn brave_api(apis: &SearchApiConfig) -> Option<Self> {
+        apis.engine("brave")
+            .and_then(|e| e.api_key.as_deref())
+            .filter(|k| !k.trim().is_empty())
+            .or(apis.brave_api_key.as_deref())
+            .map(|k| k.trim())
+            .filter(|k| !k.is_empty())
+            .map(|k| SearchEngine::BraveApi { api_key: k.to_string() })
+    }
+
+    /// Ordem padrão: SearxNG (se configurado), motores por scraping e Brave API como último recurso
+    pub fn default_order(apis: &SearchApiConfig) -> Vec<Self> {
+        let mut engines: Vec<SearchEngine> = SearchEngine::searx(apis).into_iter().collect();
+        engines.extend([
+            SearchEngine::Google,
+            SearchEngine::Bing,
+            SearchEngine::Yahoo,
+            SearchEngine::DuckDuckGo,
+            SearchEngine::Startpage,
+        ]);
+        engines.extend(SearchEngine::brave_api(apis));
+        engines
+    }
+
+    /// Ordem configurada em `search_apis.engines` (só os ativos e com chave/URL quando exigidas);
+    /// sem configuração, a ordem padrão
+    pub fn configured_order(apis: &SearchApiConfig) -> Vec<Self> {
+        if apis.engines.is_empty() {
+            return SearchEngine::default_order(apis);
+        }
+        apis.engines
+            .iter()
+            .filter(|setting| setting.enabled)
+            .filter_map(|setting| {
+                let engine = SearchEngine::from_config(&setting.name, apis);
+                if engine.is_none() {
+                    log::warn!("[SearchEngine] {} ignorado: falta a chave de API ou a URL", setting.name);
+                }
+                engine
+            })
+            .collect()
+    }
+
+    /// Nome usado em `search_apis.engines`
+    pub fn key(&self) -> &'static str {
+        match self {
+            SearchEngine::Google => "google",
+            SearchEngine::Bing => "bing",
+            SearchEngine::Yahoo => "yahoo",
+            SearchEngine::DuckDuckGo => "duckduckgo",
+            SearchEngine::Startpage => "startpage",
+            SearchEngine::Searx { .. } => "searx",
+            SearchEngine::BraveApi { .. } => "brave",
+        }
+    }
+
     /// Retorna nome do motor como string
     pub fn as_str(&self) -> &'static str {
         match self {
@@ -120,17 +304,50 @@ impl SearchEngine {
             SearchEngine::Yahoo => "Yahoo",
             SearchEngine::DuckDuckGo => "DuckDuckGo",
             SearchEngine::Startpage => "Startpage",
+            SearchEngine::Searx { .. } => "Searx",
+            SearchEngine::BraveApi { .. } => "Brave",
         }
     }
 
-    /// Retorna URL base de busca
-    fn base_url(&self) -> &'static str {
-        match self {
+    /// Retorna URL base de busca (a URL própria configurada para o motor, se houver)
+    fn base_url(&self) -> String {
+        let default = match self {
             SearchEngine::Google => "https://www.google.com/search",
             SearchEngine::Bing => "https://www.bing.com/search",
             SearchEngine::Yahoo => "https://search.yahoo.com/search",
             SearchEngine::DuckDuckGo => "https://html.duckduckgo.com/html",
             SearchEngine::Startpage => "https://www.startpage.com/sp/search",
+            SearchEngine::Searx { base_url } => return base_url.clone(),
+            SearchEngine::BraveApi { .. } => BRAVE_API_URL,
+        };
+        current_search_apis()
+            .engine(self.key())
+            .and_then(|e| e.base_url.clone())
+            .map(|u| u.trim().trim_end_matches('/').to_string())
+            .filter(|u| !u.is_empty())
+            .unwrap_or_else(|| default.to_string())
+    }
+
+    /// Parâmetros de idioma/região da busca conforme o idioma detectado na query
+    /// (string vazia quando o idioma não é detectado com confiança)
+    fn language_params(&self, query: &str) -> String {
+        let lang = match crate::translation::detect_language(query) {
+            Some(lang) => lang,
+            None => return String::new(),
+        };
+
+        match self {
+            SearchEngine::Google => format!("&hl={}", lang),
+            SearchEngine::Bing => format!("&setlang={}", lang),
+            SearchEngine::Yahoo => String::new(),
+            SearchEngine::DuckDuckGo => duckduckgo_region(&lang)
+                .map(|region| format!("&kl={}", region))
+                .unwrap_or_default(),
+            SearchEngine::Startpage => startpage_language(&lang)
+                .map(|language| format!("&language={}", language))
+                .unwrap_or_default(),
+            SearchEngine::Searx { .. } => format!("&language={}", lang),
+            SearchEngine::BraveApi { .. } => format!("&search_lang={}", lang),
         }
     }
 
@@ -233,6 +450,13 @@ impl SearchEngine {
                     ".snippet",
                 ],
             },
+            // Motores via API retornam JSON
+            SearchEngine::Searx { .. } | SearchEngine::BraveApi { .. } => SearchSelectors {
+                container: Vec::new(),
+                title: Vec::new(),
+                url: Vec::new(),
+                snippet: Vec::new(),
+            },
         }
     }
 
@@ -279,9 +503,39 @@ fn get_random_user_agent() -> &'static str {
     USER_AGENTS[index]
 }
 
+/// Região do DuckDuckGo (`kl`) para um idioma
+fn duckduckgo_region(lang: &str) -> Option<&'static str> {
+    match lang {
+        "pt" => Some("br-pt"),
+        "en" => Some("us-en"),
+        "es" => Some("es-es"),
+        "fr" => Some("fr-fr"),
+        "de" => Some("de-de"),
+        "it" => Some("it-it"),
+        "nl" => Some("nl-nl"),
+        "ru" => Some("ru-ru"),
+        "ja" => Some("jp-jp"),
+        _ => None,
+    }
+}
+
+/// Idioma do Startpage para um código ISO 639-1
+fn startpage_language(lang: &str) -> Option<&'static str> {
+    match lang {
+        "pt" => Some("portugues"),
+        "en" => Some("english"),
+        "es" => Some("espanol"),
+        "fr" => Some("francais"),
+        "de" => Some("deutsch"),
+        "it" => Some("italiano"),
+        _ => None,
+    }
+}
+
 /// Busca no DuckDuckGo e retorna URLs dos resultados
 pub async fn search_duckduckgo(query: &str, limit: usize) -> Result<Vec<String>> {
-    let client = reqwest::Client::builder()
+    crate::audit::record(crate::audit::AuditKind::WebSearch, SearchEngine::DuckDuckGo.key(), query, None);
+    let client = client_builder(Some(SearchEngine::DuckDuckGo))
         .timeout(Duration::from_secs(10))
         .build()?;
     let user_agent = get_random_user_agent();
@@ -293,12 +547,15 @@ pub async fn search_duckduckgo(query: &str, limit: usize) -> Result<Vec<String>>
         ".web-result__link",
         "a.result__a",
     ];
+    let language_params = SearchEngine::DuckDuckGo.language_params(query);
     for _ in 0..max_pages {
         if links.len() >= limit { break; }
         let url = format!(
-            "https://html.duckduckgo.com/html/?q={}&s={}",
+            "{}/?q={}&s={}{}",
+            SearchEngine::DuckDuckGo.base_url(),
             urlencoding::encode(query),
-            offset
+            offset,
+            language_params
         );
         let res = client
             .get(&url)
@@ -334,14 +591,15 @@ pub async fn search_duckduckgo(query: &str, limit: usize) -> Result<Vec<String>>
 
 /// Busca no Google retornando apenas metadados (título, URL, snippet)
 pub async fn search_google_metadata(query: &str, limit: usize) -> Result<Vec<SearchResultMetadata>> {
-    let client = reqwest::Client::builder()
+    let client = client_builder(Some(SearchEngine::Google))
         .timeout(Duration::from_secs(10))
         .build()?;
 
-    let url = format!("{}?q={}&num={}",
+    let url = format!("{}?q={}&num={}{}",
         SearchEngine::Google.base_url(),
         urlencoding::encode(query),
-        limit.min(100)
+        limit.min(100),
+        SearchEngine::Google.language_params(query)
     );
 
     let user_agent = get_random_user_agent();
@@ -433,14 +691,15 @@ pub async fn search_google_metadata(query: &str, limit: usize) -> Result<Vec<Sea
 
 /// Busca no Bing retornando apenas metadados (título, URL, snippet)
 pub async fn search_bing_metadata(query: &str, limit: usize) -> Result<Vec<SearchResultMetadata>> {
-    let client = reqwest::Client::builder()
+    let client = client_builder(Some(SearchEngine::Bing))
         .timeout(Duration::from_secs(10))
         .build()?;
 
-    let url = format!("{}?q={}&count={}",
+    let url = format!("{}?q={}&count={}{}",
         SearchEngine::Bing.base_url(),
         urlencoding::encode(query),
-        limit.min(50)
+        limit.min(50),
+        SearchEngine::Bing.language_params(query)
     );
 
     let user_agent = get_random_user_agent();
@@ -528,14 +787,15 @@ pub async fn search_bing_metadata(query: &str, limit: usize) -> Result<Vec<Searc
 
 /// Busca no Yahoo retornando apenas metadados (título, URL, snippet)
 pub async fn search_yahoo_metadata(query: &str, limit: usize) -> Result<Vec<SearchResultMetadata>> {
-    let client = reqwest::Client::builder()
+    let client = client_builder(Some(SearchEngine::Yahoo))
         .timeout(Duration::from_secs(10))
         .build()?;
 
-    let url = format!("{}?p={}&n={}",
+    let url = format!("{}?p={}&n={}{}",
         SearchEngine::Yahoo.base_url(),
         urlencoding::encode(query),
-        limit.min(40)
+        limit.min(40),
+        SearchEngine::Yahoo.language_params(query)
     );
 
     let user_agent = get_random_user_agent();
@@ -623,13 +883,14 @@ pub async fn search_yahoo_metadata(query: &str, limit: usize) -> Result<Vec<Sear
 
 /// Busca no Startpage retornando apenas metadados (título, URL, snippet)
 pub async fn search_startpage_metadata(query: &str, limit: usize) -> Result<Vec<SearchResultMetadata>> {
-    let client = reqwest::Client::builder()
+    let client = client_builder(Some(SearchEngine::Startpage))
         .timeout(Duration::from_secs(10))
         .build()?;
 
-    let url = format!("{}?query={}&page=1",
+    let url = format!("{}?query={}&page=1{}",
         SearchEngine::Startpage.base_url(),
-        urlencoding::encode(query)
+        urlencoding::encode(query),
+        SearchEngine::Startpage.language_params(query)
     );
 
     let user_agent = get_random_user_agent();
@@ -717,12 +978,14 @@ pub async fn search_startpage_metadata(query: &str, limit: usize) -> Result<Vec<
 
 /// Busca no DuckDuckGo retornando apenas metadados (título, URL, snippet)
 pub async fn search_duckduckgo_metadata(query: &str, limit: usize) -> Result<Vec<SearchResultMetadata>> {
-    let client = reqwest::Client::builder()
+    let client = client_builder(Some(SearchEngine::DuckDuckGo))
         .timeout(Duration::from_secs(10))
         .build()?;
 
-    let url = format!("https://html.duckduckgo.com/html/?q={}",
-        urlencoding::encode(query));
+    let url = format!("{}/?q={}{}",
+        SearchEngine::DuckDuckGo.base_url(),
+        urlencoding::encode(query),
+        SearchEngine::DuckDuckGo.language_params(query));
 
     let user_agent = get_random_user_agent();
     let res = client
@@ -824,6 +1087,137 @@ pub async fn search_duckduckgo_metadata(query: &str, limit: usize) -> Result<Vec
     Ok(results)
 }
 
+/// Busca em uma instância SearxNG via API JSON (`format=json` precisa estar habilitado)
+pub async fn search_searx_metadata(engine: &SearchEngine, query: &str, limit: usize) -> Result<Vec<SearchResultMetadata>> {
+    let client = client_builder(Some(engine.clone()))
+        .timeout(Duration::from_secs(10))
+        .build()?;
+    
+    let url = format!(
+        "{}/search?q={}&format=json{}",
+        engine.base_url(),
+        urlencoding::encode(&engine.normalize_query(query)),
+        engine.language_params(query)
+    );
+    
+    let start_time = Instant::now();
+    log::info!("[SearchEngine:Searx] Query: '{}', Attempting...", query);
+    
+    let response = client
+        .get(&url)
+        .header(USER_AGENT, get_random_user_agent())
+        .header("Accept", "application/json")
+        .send()
+        .await?;
+    
+    if response.status() == reqwest::StatusCode::FORBIDDEN {
+        return Err(anyhow::anyhow!("Instância SearxNG recusou a busca: habilite o formato JSON em settings.yml (search.formats)"));
+    }
+    if !response.status().is_success() {
+        return Err(anyhow::anyhow!("SearxNG retornou HTTP {}", response.status()));
+    }
+    
+    let body: serde_json::Value = response.json().await?;
+    let results = parse_searx_results(&body, limit);
+    
+    log::info!("[SearchEngine:Searx] Found {} results ({}ms)", results.len(), start_time.elapsed().as_millis());
+    Ok(results)
+}
+
+/// Busca na Brave Search API (alternativa estável quando os selectors de HTML quebram)
+pub async fn search_brave_metadata(engine: &SearchEngine, query: &str, limit: usize) -> Result<Vec<SearchResultMetadata>> {
+    let api_key = match engine {
+        SearchEngine::BraveApi { api_key } => api_key,
+        _ => return Err(anyhow::anyhow!("Motor inválido para a Brave Search API")),
+    };
+    
+    let client = client_builder(Some(engine.clone()))
+        .timeout(Duration::from_secs(10))
+        .build()?;
+    
+    let url = format!(
+        "{}?q={}&count={}{}",
+        engine.base_url(),
+        urlencoding::encode(&engine.normalize_query(query)),
+        limit.clamp(1, BRAVE_API_MAX_COUNT),
+        engine.language_params(query)
+    );
+    
+    let start_time = Instant::now();
+    log::info!("[SearchEngine:Brave] Query: '{}', Attempting...", query);
+    
+    let response = client
+        .get(&url)
+        .header("Accept", "application/json")
+        .header("X-Subscription-Token", api_key)
+        .send()
+        .await?;
+    
+    match response.status() {
+        reqwest::StatusCode::UNAUTHORIZED | reqwest::StatusCode::FORBIDDEN => {
+            return Err(anyhow::anyhow!("Chave da Brave Search API inválida"));
+        }
+        reqwest::StatusCode::TOO_MANY_REQUESTS => {
+            return Err(anyhow::anyhow!("Limite de requisições da Brave Search API atingido"));
+        }
+        status if !status.is_success() => {
+            return Err(anyhow::anyhow!("Brave Search API retornou HTTP {}", status));
+        }
+        _ => {}
+    }
+    
+    let body: serde_json::Value = response.json().await?;
+    let results = parse_brave_results(&body, limit);
+    
+    log::info!("[SearchEngine:Brave] Found {} results ({}ms)", results.len(), start_time.elapsed().as_millis());
+    Ok(results)
+}
+
+/// Remove tags HTML (ex: `<strong>`) dos snippets das APIs
+pub(crate) fn strip_html_tags(text: &str) -> String {
+    let without_tags = Regex::new(r"<[^>]+>")
+        .map(|re| re.replace_all(text, "").to_string())
+        .unwrap_or_else(|_| text.to_string());
+    html_escape_decode(&without_tags)
+}
+
+pub(crate) fn html_escape_decode(text: &str) -> String {
+    text.replace("&quot;", "\"")
+        .replace("&#39;", "'")
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&amp;", "&")
+}
+
+/// Converte uma lista de resultados JSON (`url`, `title` e o campo de snippet indicado)
+fn parse_api_results(items: Option<&Vec<serde_json::Value>>, snippet_field: &str, limit: usize) -> Vec<SearchResultMetadata> {
+    items
+        .map(|items| {
+            items
+                .iter()
+                .filter_map(|item| {
+                    let url = item.get("url")?.as_str()?.to_string();
+                    if !url.starts_with("http") || is_ad_or_tracker_url(&url) {
+                        return None;
+                    }
+                    let title = item.get("title").and_then(|t| t.as_str()).map(strip_html_tags).unwrap_or_else(|| url.clone());
+                    let snippet = item.get(snippet_field).and_then(|t| t.as_str()).map(strip_html_tags).unwrap_or_default();
+                    Some(SearchResultMetadata { title, url, snippet })
+                })
+                .take(limit)
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+fn parse_searx_results(body: &serde_json::Value, limit: usize) -> Vec<SearchResultMetadata> {
+    parse_api_results(body.get("results").and_then(|r| r.as_array()), "content", limit)
+}
+
+fn parse_brave_results(body: &serde_json::Value, limit: usize) -> Vec<SearchResultMetadata> {
+    parse_api_results(body.pointer("/web/results").and_then(|r| r.as_array()), "description", limit)
+}
+
 /// Calcula score de relevância baseado em matches de palavras-chave
 fn calculate_relevance_score(result: &SearchResultMetadata, query: &str) -> f32 {
     let query_lower = query.to_lowercase();
@@ -860,82 +1254,212 @@ fn calculate_relevance_score(result: &SearchResultMetadata, query: &str) -> f32
     (base_score + title_bonus + snippet_bonus).min(1.0)
 }
 
-/// Busca multi-engine com fallback automático
+/// Consulta um motor e registra a tentativa
+async fn search_engine_attempt(
+    engine: SearchEngine,
+    query: &str,
+    limit: usize,
+) -> (SearchAttemptLog, Vec<SearchResultMetadata>) {
+    crate::audit::record(crate::audit::AuditKind::WebSearch, engine.key(), query, None);
+    let start_time = Instant::now();
+    let result = match &engine {
+        SearchEngine::Google => search_google_metadata(query, limit).await,
+        SearchEngine::Bing => search_bing_metadata(query, limit).await,
+        SearchEngine::Yahoo => search_yahoo_metadata(query, limit).await,
+        SearchEngine::DuckDuckGo => search_duckduckgo_metadata(query, limit).await,
+        SearchEngine::Startpage => search_startpage_metadata(query, limit).await,
+        SearchEngine::Searx { .. } => search_searx_metadata(&engine, query, limit).await,
+        SearchEngine::BraveApi { .. } => search_brave_metadata(&engine, query, limit).await,
+    };
+    
+    let mut attempt_log = SearchAttemptLog {
+        engine,
+        query: query.to_string(),
+        success: false,
+        results_count: 0,
+        duration_ms: start_time.elapsed().as_millis() as u64,
+        error: None,
+    };
+    match result {
+        Ok(results) => {
+            attempt_log.success = true;
+            attempt_log.results_count = results.len();
+            let outcome = if results.is_empty() { Outcome::Empty } else { Outcome::Success };
+            engine_health::record(attempt_log.engine.key(), outcome);
+            (attempt_log, results)
+        }
+        Err(e) => {
+            attempt_log.error = Some(format!("{}", e));
+            engine_health::record(attempt_log.engine.key(), Outcome::Error(format!("{}", e)));
+            (attempt_log, Vec::new())
+        }
+    }
+}
+
+/// Remove da lista os motores rebaixados e sonda em segundo plano os que estão na hora;
+/// uma sondagem com resultados devolve o motor às buscas
+fn healthy_engines(engines: Vec<SearchEngine>) -> Vec<SearchEngine> {
+    let (active, due) = engine_health::partition(engines);
+    for engine in due {
+        log::info!("[EngineHealth] Sondando {} (rebaixado)", engine.as_str());
+        tauri::async_runtime::spawn(async move {
+            search_engine_attempt(engine, PROBE_QUERY, 5).await;
+        });
+    }
+    active
+}
+
+/// Consulta usada por `test_search_engine`
+const PROBE_QUERY: &str = "ollama local llm";
+
+/// Quantos elementos cada selector encontrou na página de resultados
+#[derive(serde::Serialize, Clone, Debug, PartialEq)]
+pub struct SelectorHealth {
+    /// "container", "title", "url" ou "snippet"
+    pub kind: &'static str,
+    pub selector: String,
+    pub matches: usize,
+}
+
+/// Resultado de `test_search_engine`
+#[derive(serde::Serialize, Clone, Debug)]
+pub struct EngineProbe {
+    pub engine: String,
+    pub ok: bool,
+    pub results: usize,
+    pub duration_ms: u64,
+    pub error: Option<String>,
+    /// Vazio para motores via API
+    pub selectors: Vec<SelectorHealth>,
+    pub sample: Vec<SearchResultMetadata>,
+}
+
+fn selector_health(selectors: &SearchSelectors, html: &str) -> Vec<SelectorHealth> {
+    let document = Html::parse_document(html);
+    let groups = [
+        ("container", &selectors.container),
+        ("title", &selectors.title),
+        ("url", &selectors.url),
+        ("snippet", &selectors.snippet),
+    ];
+    let mut health = Vec::new();
+    for (kind, list) in groups {
+        for selector in list {
+            let matches = Selector::parse(selector)
+                .map(|parsed| document.select(&parsed).count())
+                .unwrap_or(0);
+            health.push(SelectorHealth { kind, selector: selector.to_string(), matches });
+        }
+    }
+    health
+}
+
+async fn fetch_probe_page(engine: &SearchEngine) -> Result<String> {
+    let client = client_builder(Some(engine.clone()))
+        .timeout(Duration::from_secs(10))
+        .build()?;
+    let url = format!("{}?q={}", engine.base_url(), urlencoding::encode(PROBE_QUERY));
+    Ok(client
+        .get(&url)
+        .header(USER_AGENT, get_random_user_agent())
+        .send()
+        .await?
+        .text()
+        .await?)
+}
+
+/// Busca de teste em um motor: resultados, tempo e quantos elementos cada selector encontrou
+pub async fn probe_engine(engine: SearchEngine) -> EngineProbe {
+    let (attempt, results) = search_engine_attempt(engine.clone(), PROBE_QUERY, 5).await;
+    let selectors = engine.selectors();
+    let health = if selectors.container.is_empty() {
+        Vec::new()
+    } else {
+        match fetch_probe_page(&engine).await {
+            Ok(html) => selector_health(&selectors, &html),
+            Err(e) => {
+                log::warn!("[SearchEngine:{}] Falha ao baixar a página de teste: {}", engine.as_str(), e);
+                Vec::new()
+            }
+        }
+    };
+    let error = attempt.error.or_else(|| {
+        results.is_empty().then(|| "Nenhum resultado (selectors desatualizados ou bloqueio do motor)".to_string())
+    });
+    EngineProbe {
+        engine: engine.as_str().to_string(),
+        ok: error.is_none(),
+        results: results.len(),
+        duration_ms: attempt.duration_ms,
+        error,
+        selectors: health,
+        sample: results.into_iter().take(3).collect(),
+    }
+}
+
+/// Busca multi-engine: todos os motores são consultados em paralelo e os que ainda
+/// não responderam são cancelados assim que `min_results` é atingido
 pub async fn search_multi_engine_metadata(
     query: &str,
     limit: usize,
     engine_order: &[SearchEngine],
     min_results: usize,
 ) -> Result<Vec<SearchResultMetadata>> {
+    use futures_util::stream::{FuturesUnordered, StreamExt};
+    
     let mut all_results: Vec<SearchResultMetadata> = Vec::new();
     let mut seen_urls = std::collections::HashSet::new();
     let mut attempt_logs: Vec<SearchAttemptLog> = Vec::new();
     
+    let engine_order = healthy_engines(engine_order.to_vec());
+    
     log::info!("[MultiEngine] Starting search for: '{}'", query);
     log::info!("[MultiEngine] Engine order: {:?}", engine_order.iter().map(|e| e.as_str()).collect::<Vec<_>>());
     log::info!("[MultiEngine] Min results required: {}", min_results);
     
-    for engine in engine_order {
-        let start_time = Instant::now();
-        let mut attempt_log = SearchAttemptLog {
-            engine: *engine,
-            query: query.to_string(),
-            success: false,
-            results_count: 0,
-            duration_ms: 0,
-            error: None,
-        };
-        
-        let result = match *engine {
-            SearchEngine::Google => search_google_metadata(query, limit).await,
-            SearchEngine::Bing => search_bing_metadata(query, limit).await,
-            SearchEngine::Yahoo => search_yahoo_metadata(query, limit).await,
-            SearchEngine::DuckDuckGo => search_duckduckgo_metadata(query, limit).await,
-            SearchEngine::Startpage => search_startpage_metadata(query, limit).await,
-        };
+    let mut pending: FuturesUnordered<_> = engine_order
+        .iter()
+        .cloned()
+        .map(|engine| search_engine_attempt(engine, query, limit))
+        .collect();
+    
+    while let Some((mut attempt_log, mut engine_results)) = pending.next().await {
+        let engine = attempt_log.engine.as_str();
         
-        attempt_log.duration_ms = start_time.elapsed().as_millis() as u64;
+        if let Some(error_msg) = &attempt_log.error {
+            log::warn!("[MultiEngine:{}] Failed: {} ({}ms)", 
+                engine, error_msg, attempt_log.duration_ms);
+            attempt_logs.push(attempt_log);
+            continue;
+        }
         
-        match result {
-            Ok(mut engine_results) => {
-                // Filtrar duplicatas
-                engine_results.retain(|r| {
-                    if seen_urls.contains(&r.url) {
-                        false
-                    } else {
-                        seen_urls.insert(r.url.clone());
-                        true
-                    }
-                });
-                
-                attempt_log.results_count = engine_results.len();
-                attempt_log.success = true;
-                
-                if !engine_results.is_empty() {
-                    log::info!("[MultiEngine:{}] Found {} unique results ({}ms)", 
-                        engine.as_str(), engine_results.len(), attempt_log.duration_ms);
-                    all_results.extend(engine_results);
-                    
-                    // Se atingiu mínimo necessário, pode parar
-                    if all_results.len() >= min_results {
-                        log::info!("[MultiEngine] Minimum results ({}) reached, stopping early", min_results);
-                        break;
-                    }
-                } else {
-                    log::warn!("[MultiEngine:{}] No results found ({}ms), trying next engine...", 
-                        engine.as_str(), attempt_log.duration_ms);
-                }
-            }
-            Err(e) => {
-                let error_msg = format!("{}", e);
-                attempt_log.error = Some(error_msg.clone());
-                log::warn!("[MultiEngine:{}] Failed: {} ({}ms), trying next engine...", 
-                    engine.as_str(), error_msg, attempt_log.duration_ms);
+        // Filtrar duplicatas (variantes AMP/mobile/utm contam como a mesma página)
+        engine_results.retain_mut(|r| {
+            if let Some(canonical) = canonicalize_url(&r.url) {
+                r.url = canonical;
             }
-        }
+            seen_urls.insert(url_dedup_key(&r.url))
+        });
+        attempt_log.results_count = engine_results.len();
         
+        if engine_results.is_empty() {
+            log::warn!("[MultiEngine:{}] No results found ({}ms)", 
+                engine, attempt_log.duration_ms);
+        } else {
+            log::info!("[MultiEngine:{}] Found {} unique results ({}ms)", 
+                engine, engine_results.len(), attempt_log.duration_ms);
+            all_results.extend(engine_results);
+        }
         attempt_logs.push(attempt_log);
+        
+        // Atingiu o mínimo: descartar (cancelar) os motores que ainda não responderam
+        if all_results.len() >= min_results && !pending.is_empty() {
+            log::info!("[MultiEngine] Minimum results ({}) reached, cancelling {} pending engine(s)", 
+                min_results, pending.len());
+            break;
+        }
     }
+    drop(pending);
     
     // Ranquear resultados por relevância
     let mut scored_results: Vec<(SearchResultMetadata, f32)> = all_results
@@ -974,66 +1498,6 @@ pub async fn search_multi_engine_metadata(
     Ok(final_results)
 }
 
-/// Expande query semanticamente (adiciona sinônimos, remove stopwords)
-pub fn expand_query_semantic(query: &str, language: &str) -> Vec<String> {
-    let mut variants = Vec::new();
-    
-    // Query original sempre incluída
-    variants.push(query.trim().to_string());
-    
-    // Stopwords por idioma
-    let stopwords: Vec<&str> = match language {
-        "pt-BR" | "pt" => vec!["o", "a", "os", "as", "de", "do", "da", "dos", "das", "em", "no", "na", "nos", "nas", "para", "por", "com", "sem", "que", "qual", "quais"],
-        "en" => vec!["the", "a", "an", "and", "or", "but", "in", "on", "at", "to", "for", "of", "with", "by"],
-        "es" => vec!["el", "la", "los", "las", "de", "del", "en", "un", "una", "para", "por", "con", "sin"],
-        _ => vec![],
-    };
-    
-    // Remover stopwords
-    let words: Vec<&str> = query.split_whitespace()
-        .filter(|w| !stopwords.contains(&w.to_lowercase().as_str()))
-        .collect();
-    
-    if words.len() > 1 {
-        let without_stopwords = words.join(" ");
-        if without_stopwords != query.trim() {
-            variants.push(without_stopwords);
-        }
-    }
-    
-    // Sinônimos comuns (básico - pode ser expandido)
-    let synonyms: Vec<(&str, &str)> = match language {
-        "pt-BR" | "pt" => vec![
-            ("pesquisa", "estudo investigação"),
-            ("resultado", "achado descoberta"),
-            ("acadêmico", "científico universitário"),
-        ],
-        "en" => vec![
-            ("research", "study investigation"),
-            ("result", "finding discovery"),
-            ("academic", "scientific scholarly"),
-        ],
-        _ => vec![],
-    };
-    
-    // Adicionar variantes com sinônimos
-    for (original, replacements) in synonyms {
-        if query.to_lowercase().contains(original) {
-            for replacement in replacements.split_whitespace() {
-                let variant = query.to_lowercase().replace(original, replacement);
-                if variant != query.to_lowercase() {
-                    variants.push(variant);
-                }
-            }
-        }
-    }
-    
-    // Remover duplicatas e retornar
-    variants.sort();
-    variants.dedup();
-    variants
-}
-
 /// Extrai a URL real do redirecionamento do DuckDuckGo
 fn extract_real_url(ddg_redirect: &str) -> Option<String> {
     // DuckDuckGo usa formato: /l/?kh=-1&uddg=<URL_ENCODED>
@@ -1129,23 +1593,148 @@ fn clean_url(url: &str) -> Option<String> {
             if let Some(encoded_url) = url.get(uddg_start + 5..) {
                 if let Some(ampersand) = encoded_url.find('&') {
                     if let Ok(decoded) = urlencoding::decode(&encoded_url[..ampersand]) {
-                        return Some(decoded.to_string());
+                        return canonicalize_url(&decoded);
                     }
                 } else if let Ok(decoded) = urlencoding::decode(encoded_url) {
-                    return Some(decoded.to_string());
+                    return canonicalize_url(&decoded);
                 }
             }
         }
     }
     
-    // Se já for URL limpa, validar formato
+    // Se já for URL limpa, validar formato e remover rastreamento/AMP
     if url.starts_with("http://") || url.starts_with("https://") {
-        Some(url.to_string())
+        canonicalize_url(url)
     } else {
         None
     }
 }
 
+/// Parâmetros de rastreamento removidos das URLs (prefixos terminam com `_`)
+const TRACKING_PARAMS: &[&str] = &[
+    "utm_", "fbclid", "gclid", "dclid", "yclid", "msclkid", "mc_cid", "mc_eid", "igshid", "_ga", "_gl",
+    "ref_src", "ref_url", "spm", "cmpid", "ocid", "amp", "outputtype", "guccounter",
+];
+/// Subdomínios que apontam para a mesma página do domínio principal
+const MIRROR_SUBDOMAINS: &[&str] = &["www.", "m.", "mobile.", "amp."];
+/// Caracteres do texto normalizado usados no hash de conteúdo
+const CONTENT_HASH_CHARS: usize = 5000;
+
+fn is_tracking_param(name: &str) -> bool {
+    let name = name.to_lowercase();
+    TRACKING_PARAMS.iter().any(|p| if p.ends_with('_') { name.starts_with(p) } else { name == *p })
+}
+
+/// Remove parâmetros de rastreamento e o fragmento, e resolve links do cache AMP do Google
+/// (`*.cdn.ampproject.org/c/s/site.com/...`). O host é mantido para a URL continuar acessível.
+pub fn canonicalize_url(url: &str) -> Option<String> {
+    let mut parsed = Url::parse(url).ok()?;
+    if parsed.scheme() != "http" && parsed.scheme() != "https" {
+        return None;
+    }
+
+    if parsed.host_str().map(|h| h.ends_with(".cdn.ampproject.org")).unwrap_or(false) {
+        let path = parsed.path().to_string();
+        let rest = path.trim_start_matches('/');
+        let (scheme, rest) = if let Some(rest) = rest.strip_prefix("c/s/") {
+            ("https", rest)
+        } else if let Some(rest) = rest.strip_prefix("c/") {
+            ("http", rest)
+        } else {
+            ("https", rest)
+        };
+        let query = parsed.query().map(|q| format!("?{}", q)).unwrap_or_default();
+        parsed = Url::parse(&format!("{}://{}{}", scheme, rest, query)).ok()?;
+    }
+
+    let kept: Vec<(String, String)> = parsed
+        .query_pairs()
+        .filter(|(name, _)| !is_tracking_param(name))
+        .map(|(name, value)| (name.into_owned(), value.into_owned()))
+        .collect();
+    if kept.is_empty() {
+        parsed.set_query(None);
+    } else {
+        parsed.query_pairs_mut().clear().extend_pairs(kept);
+    }
+    parsed.set_fragment(None);
+
+    Some(parsed.to_string())
+}
+
+/// Chave para detectar a mesma página em variantes (http/https, www/m/amp, `/amp`, barra final,
+/// parâmetros de rastreamento e ordem dos parâmetros)
+pub fn url_dedup_key(url: &str) -> String {
+    let canonical = match canonicalize_url(url).and_then(|u| Url::parse(&u).ok()) {
+        Some(parsed) => parsed,
+        None => return url.trim().to_lowercase(),
+    };
+
+    let mut host = canonical.host_str().unwrap_or_default().to_lowercase();
+    while let Some(stripped) = MIRROR_SUBDOMAINS.iter().find_map(|p| host.strip_prefix(p)) {
+        host = stripped.to_string();
+    }
+
+    let mut path = canonical.path().to_string();
+    for suffix in ["/amp/", "/amp", ".amp"] {
+        if let Some(stripped) = path.strip_suffix(suffix) {
+            path = stripped.to_string();
+            break;
+        }
+    }
+    if let Some(stripped) = path.strip_prefix("/amp/") {
+        path = format!("/{}", stripped);
+    }
+    let path = path.trim_end_matches('/');
+
+    let mut params: Vec<(String, String)> = canonical.query_pairs().map(|(k, v)| (k.into_owned(), v.into_owned())).collect();
+    params.sort();
+    let query = params.iter().map(|(k, v)| format!("{}={}", k, v)).collect::<Vec<_>>().join("&");
+
+    if query.is_empty() {
+        format!("{}{}", host, path)
+    } else {
+        format!("{}{}?{}", host, path, query)
+    }
+}
+
+/// Hash do texto normalizado (minúsculo, só letras e números) para achar o mesmo artigo em URLs diferentes
+fn content_hash(content: &ScrapedContent) -> String {
+    use sha2::{Digest, Sha256};
+    let text = if content.content.trim().is_empty() { &content.markdown } else { &content.content };
+    let normalized: String = text
+        .split_whitespace()
+        .flat_map(|word| word.chars().filter(|c| c.is_alphanumeric()).flat_map(char::to_lowercase).chain(std::iter::once(' ')))
+        .take(CONTENT_HASH_CHARS)
+        .collect();
+    Sha256::digest(normalized.trim().as_bytes()).iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// Remove páginas repetidas após o scraping (mesma URL canônica ou mesmo conteúdo),
+/// mantendo a primeira ocorrência (a de melhor posição na busca)
+pub fn dedupe_scraped(results: Vec<ScrapedContent>) -> Vec<ScrapedContent> {
+    let mut seen_urls = std::collections::HashSet::new();
+    let mut seen_hashes = std::collections::HashSet::new();
+    let total = results.len();
+
+    let unique: Vec<ScrapedContent> = results
+        .into_iter()
+        .filter(|content| {
+            let url_new = seen_urls.insert(url_dedup_key(&content.url));
+            let hash_new = seen_hashes.insert(content_hash(content));
+            if !(url_new && hash_new) {
+                log::debug!("[Dedup] Fonte duplicada descartada: {}", content.url);
+            }
+            url_new && hash_new
+        })
+        .collect();
+
+    if unique.len() < total {
+        log::info!("[Dedup] {} fontes duplicadas removidas", total - unique.len());
+    }
+    unique
+}
+
 /// Busca usando query "site:" para categorias específicas
 async fn search_with_site_filter(query: &str, sites: &[String], limit: usize) -> Result<Vec<String>> {
     if sites.is_empty() {
@@ -1159,35 +1748,138 @@ async fn search_with_site_filter(query: &str, sites: &[String], limit: usize) ->
     
     let site_query = format!("({}) {}", site_filters.join(" OR "), query);
     
-    search_duckduckgo(&site_query, limit).await
+    search_urls(&site_query, limit).await
 }
 
-/// Busca inteligente híbrida: geral + curada por categorias
-pub async fn smart_search(query: &str, config: &SearchConfig) -> Result<Vec<String>> {
-    let mut all_urls = Vec::new();
-    let mut seen_urls = std::collections::HashSet::new();
-    
-    // 1. Busca geral no DuckDuckGo (ignorando anúncios)
-    log::info!("Executando busca geral para: {}", query);
-    let general_urls = search_duckduckgo(query, config.total_sources_limit).await?;
-    
-    for url in general_urls {
-        if let Some(cleaned) = clean_url(&url) {
-            if !is_domain_blocked(&cleaned, &config.excluded_domains) {
-                if seen_urls.insert(cleaned.clone()) {
-                    all_urls.push(cleaned);
-                }
-            }
+/// URLs para o smart_search: DuckDuckGo (HTML leve, com paginação) quando ativo; senão,
+/// o primeiro motor configurado que retornar resultados
+async fn search_urls(query: &str, limit: usize) -> Result<Vec<String>> {
+    let engines = healthy_engines(SearchEngine::configured_order(&current_search_apis()));
+    if engines.is_empty() || engines.contains(&SearchEngine::DuckDuckGo) {
+        return search_duckduckgo(query, limit).await;
+    }
+    let mut last_error = None;
+    for engine in engines {
+        let (attempt, results) = search_engine_attempt(engine, query, limit).await;
+        if !results.is_empty() {
+            return Ok(results.into_iter().map(|r| r.url).collect());
         }
+        last_error = attempt.error;
     }
-    
-    // 2. Busca direta por categorias ativas (site: filters)
-    for category in &config.categories {
+    Err(anyhow::anyhow!(last_error.unwrap_or_else(|| "Nenhum motor de busca retornou resultados".to_string())))
+}
+
+/// Peso da busca geral (e dos sites customizados) frente aos pesos das categorias
+const GENERAL_SEARCH_WEIGHT: u32 = 1;
+
+/// Divide `limit` proporcionalmente aos pesos (maiores restos recebem as vagas que sobram)
+fn allocate_slots(limit: usize, weights: &[u32]) -> Vec<usize> {
+    let total: u64 = weights.iter().map(|w| *w as u64).sum();
+    if total == 0 {
+        return vec![0; weights.len()];
+    }
+    let exact: Vec<(usize, u64)> = weights
+        .iter()
+        .map(|w| ((limit as u64 * *w as u64 / total) as usize, limit as u64 * *w as u64 % total))
+        .collect();
+    let mut slots: Vec<usize> = exact.iter().map(|(quota, _)| *quota).collect();
+    let mut remaining = limit - slots.iter().sum::<usize>();
+    let mut by_remainder: Vec<usize> = (0..weights.len()).collect();
+    by_remainder.sort_by(|a, b| exact[*b].1.cmp(&exact[*a].1).then(weights[*b].cmp(&weights[*a])));
+    for index in by_remainder {
+        if remaining == 0 {
+            break;
+        }
+        slots[index] += 1;
+        remaining -= 1;
+    }
+    slots
+}
+
+/// Junta as URLs de cada grupo respeitando as cotas; vagas não usadas por um grupo
+/// são preenchidas com as sobras dos outros, na ordem dos grupos
+fn merge_by_quota(groups: Vec<Vec<String>>, quotas: &[usize], limit: usize) -> Vec<String> {
+    let mut merged = Vec::new();
+    let mut leftovers = Vec::new();
+    for (urls, quota) in groups.into_iter().zip(quotas) {
+        let mut urls = urls.into_iter();
+        merged.extend(urls.by_ref().take(*quota));
+        leftovers.extend(urls);
+    }
+    let missing = limit.saturating_sub(merged.len());
+    merged.extend(leftovers.into_iter().take(missing));
+    merged
+}
+
+/// Resultado do `smart_search`
+#[derive(Debug, Default)]
+pub struct SmartSearchResults {
+    /// Respostas diretas das APIs estruturadas (ver `resolvers`)
+    pub resolved: Vec<Resolved>,
+    pub urls: Vec<String>,
+}
+
+impl SmartSearchResults {
+    /// Respostas dos resolvers como fontes, na frente das páginas extraídas
+    fn with_scraped(self, scraped: Vec<ScrapedContent>) -> Vec<ScrapedContent> {
+        self.resolved.into_iter().map(Resolved::into_scraped).chain(scraped).collect()
+    }
+}
+
+/// Busca inteligente híbrida: geral + curada por categorias.
+/// O `total_sources_limit` é dividido entre a busca geral, cada categoria ativa
+/// (proporcional ao `weight`) e os sites customizados. Perguntas factuais passam antes
+/// pelos resolvers; uma resposta com confiança alta dispensa os buscadores.
+pub async fn smart_search(query: &str, config: &SearchConfig) -> Result<SmartSearchResults> {
+    let resolved = resolvers::resolve(query).await;
+    if resolvers::is_confident(&resolved) {
+        log::info!(
+            "Resposta direta de {:?} para: {} (buscadores ignorados)",
+            resolved.iter().map(|r| r.resolver).collect::<Vec<_>>(),
+            query
+        );
+        return Ok(SmartSearchResults { resolved, urls: Vec::new() });
+    }
+    
+    let mut seen_urls = std::collections::HashSet::new();
+    let mut groups: Vec<Vec<String>> = Vec::new();
+    let mut weights: Vec<u32> = Vec::new();
+    let mut keep_new = |urls: Vec<String>| -> Vec<String> {
+        urls.iter()
+            .filter_map(|url| clean_url(url))
+            .filter(|cleaned| !is_domain_blocked(cleaned, &config.excluded_domains))
+            .filter(|cleaned| seen_urls.insert(url_dedup_key(cleaned)))
+            .collect()
+    };
+    
+    // 1. Busca geral (ignorando anúncios), com as variantes da consulta no idioma detectado/configurado
+    let language = query_expansion::resolve_language(query);
+    let variants = query_expansion::expand_query_semantic(query, &language);
+    log::info!("Executando busca geral para: {} (idioma {}, {} variantes)", query, language, variants.len());
+    let mut general = Vec::new();
+    for (index, variant) in variants.iter().enumerate() {
+        match search_urls(variant, config.total_sources_limit).await {
+            Ok(urls) => general.extend(keep_new(urls)),
+            // Só a consulta original é obrigatória
+            Err(e) if index == 0 => return Err(e),
+            Err(e) => log::warn!("Erro ao buscar variante '{}': {}", variant, e),
+        }
+    }
+    groups.push(general);
+    weights.push(GENERAL_SEARCH_WEIGHT);
+    
+    // 2. Busca direta por categorias ativas (site: filters)
+    for category in &config.categories {
         if !category.enabled || category.base_sites.is_empty() {
             continue;
         }
         
-        log::info!("Buscando em categoria '{}' ({} sites)", category.name, category.base_sites.len());
+        log::info!(
+            "Buscando em categoria '{}' ({} sites, peso {})",
+            category.name,
+            category.base_sites.len(),
+            category.weight
+        );
         
         // Limitar sites por categoria para não exceder o limite total
         let sites_to_search = category.base_sites.iter()
@@ -1197,15 +1889,8 @@ pub async fn smart_search(query: &str, config: &SearchConfig) -> Result<Vec<Stri
         
         match search_with_site_filter(query, &sites_to_search, config.total_sources_limit).await {
             Ok(category_urls) => {
-                for url in category_urls {
-                    if let Some(cleaned) = clean_url(&url) {
-                        if !is_domain_blocked(&cleaned, &config.excluded_domains) {
-                            if seen_urls.insert(cleaned.clone()) {
-                                all_urls.push(cleaned);
-                            }
-                        }
-                    }
-                }
+                groups.push(keep_new(category_urls));
+                weights.push(category.weight);
             }
             Err(e) => {
                 log::warn!("Erro ao buscar categoria '{}': {}", category.name, e);
@@ -1218,15 +1903,8 @@ pub async fn smart_search(query: &str, config: &SearchConfig) -> Result<Vec<Stri
         log::info!("Buscando em {} sites customizados", config.user_custom_sites.len());
         match search_with_site_filter(query, &config.user_custom_sites, config.total_sources_limit).await {
             Ok(custom_urls) => {
-                for url in custom_urls {
-                    if let Some(cleaned) = clean_url(&url) {
-                        if !is_domain_blocked(&cleaned, &config.excluded_domains) {
-                            if seen_urls.insert(cleaned.clone()) {
-                                all_urls.push(cleaned);
-                            }
-                        }
-                    }
-                }
+                groups.push(keep_new(custom_urls));
+                weights.push(GENERAL_SEARCH_WEIGHT);
             }
             Err(e) => {
                 log::warn!("Erro ao buscar sites customizados: {}", e);
@@ -1234,19 +1912,105 @@ pub async fn smart_search(query: &str, config: &SearchConfig) -> Result<Vec<Stri
         }
     }
     
-    // Limitar ao total_sources_limit
-    all_urls.truncate(config.total_sources_limit);
+    // Dividir o total_sources_limit entre os grupos conforme os pesos
+    let quotas = allocate_slots(config.total_sources_limit, &weights);
+    let all_urls = merge_by_quota(groups, &quotas, config.total_sources_limit);
     
     log::info!("Total de {} URLs únicas coletadas", all_urls.len());
-    Ok(all_urls)
+    Ok(SmartSearchResults { resolved, urls: all_urls })
+}
+
+/// Etapa de uma URL dentro de um job de scraping
+#[derive(serde::Serialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ScrapeUrlStatus {
+    Started,
+    Completed,
+    Failed,
+}
+
+/// Payload do evento `scrape-progress`
+#[derive(serde::Serialize, Clone, Debug)]
+pub struct ScrapeProgressEvent {
+    pub job_id: String,
+    pub url: String,
+    pub status: ScrapeUrlStatus,
+    /// Caracteres de markdown extraídos (apenas em `completed`)
+    pub chars: Option<usize>,
+    pub elapsed_ms: Option<u64>,
+    pub error: Option<String>,
+}
+
+/// Acompanhamento de um job de scraping em lote: emite `scrape-progress` por URL
+/// e expõe o cancelamento solicitado via `cancel_scrape_job`
+#[derive(Clone)]
+pub struct ScrapeProgress {
+    app_handle: AppHandle,
+    job: crate::jobs::JobHandle,
+}
+
+impl ScrapeProgress {
+    pub fn new(app_handle: &AppHandle, job: crate::jobs::JobHandle) -> Self {
+        Self { app_handle: app_handle.clone(), job }
+    }
+    
+    pub fn is_cancelled(&self) -> bool {
+        self.job.is_cancelled()
+    }
+    
+    pub fn add_total(&self, count: usize) {
+        self.job.add_total(count);
+    }
+    
+    fn emit(&self, url: &str, status: ScrapeUrlStatus, chars: Option<usize>, elapsed: Option<Duration>, error: Option<String>) {
+        let event = ScrapeProgressEvent {
+            job_id: self.job.id().to_string(),
+            url: url.to_string(),
+            status,
+            chars,
+            elapsed_ms: elapsed.map(|e| e.as_millis() as u64),
+            error,
+        };
+        let _ = crate::events::emit(&self.app_handle, &event);
+    }
+    
+    pub fn started(&self, url: &str) {
+        self.emit(url, ScrapeUrlStatus::Started, None, None, None);
+    }
+    
+    pub fn finished<T>(&self, url: &str, started_at: Instant, result: &Result<T>, chars: impl FnOnce(&T) -> usize) {
+        match result {
+            Ok(value) => self.emit(url, ScrapeUrlStatus::Completed, Some(chars(value)), Some(started_at.elapsed()), None),
+            Err(e) => self.emit(url, ScrapeUrlStatus::Failed, None, Some(started_at.elapsed()), Some(e.to_string())),
+        }
+        self.job.advance(1);
+    }
+}
+
+/// `scrape_url` com eventos de início/fim quando há um job acompanhando
+async fn scrape_url_tracked(
+    url: &str,
+    cache: Option<&PageCache>,
+    progress: Option<&ScrapeProgress>,
+) -> Result<ScrapedContent> {
+    let started_at = Instant::now();
+    if let Some(progress) = progress {
+        progress.started(url);
+    }
+    let result = scrape_url(url, cache).await;
+    if let Some(progress) = progress {
+        progress.finished(url, started_at, &result, |c| c.markdown.chars().count());
+    }
+    result
 }
 
 /// Busca e extrai conteúdo de múltiplas URLs em paralelo com Semaphore
 pub async fn search_and_scrape(
     query: &str,
     limit: usize,
-    browser: Arc<Browser>,
     excluded_domains: Vec<String>,
+    cache: Option<PageCache>,
+    progress: Option<ScrapeProgress>,
 ) -> Result<Vec<ScrapedContent>> {
     // Configuração padrão (backward compatibility)
     let config = SearchConfig {
@@ -1257,33 +2021,50 @@ pub async fn search_and_scrape(
         excluded_domains,
     };
     
-    search_and_scrape_with_config(query, &config, browser).await
+    search_and_scrape_with_config(query, &config, cache, progress).await
 }
 
 /// Versão nova com SearchConfig completo
 pub async fn search_and_scrape_with_config(
     query: &str,
     config: &SearchConfig,
-    browser: Arc<Browser>,
+    cache: Option<PageCache>,
+    progress: Option<ScrapeProgress>,
 ) -> Result<Vec<ScrapedContent>> {
     // 1. Busca inteligente híbrida
-    let urls = smart_search(query, config).await?;
+    let found = smart_search(query, config).await?;
+    let urls = found.urls.clone();
     
     if urls.is_empty() {
-        log::warn!("Nenhuma URL encontrada para a query: {}", query);
-        return Ok(Vec::new());
+        if found.resolved.is_empty() {
+            log::warn!("Nenhuma URL encontrada para a query: {}", query);
+        }
+        return Ok(found.with_scraped(Vec::new()));
     }
 
-    // 2. Scraping paralelo com Semaphore (limita abas simultâneas)
-    let semaphore = Arc::new(Semaphore::new(config.max_concurrent_tabs));
+    // 2. Scraping paralelo com Semaphore (limita abas simultâneas, respeitando o perfil de recursos)
+    let max_tabs = config
+        .max_concurrent_tabs
+        .min(crate::resource_profile::current().max_concurrent_tabs)
+        .max(1);
+    let semaphore = Arc::new(Semaphore::new(max_tabs));
     let mut handles = Vec::new();
+    let cancelled = || progress.as_ref().is_some_and(|p| p.is_cancelled());
+    if let Some(progress) = &progress {
+        progress.add_total(urls.len());
+    }
     
     for url in urls.clone() {
-        let browser_clone = browser.clone();
         let permit = semaphore.clone().acquire_owned().await.unwrap();
+        if cancelled() {
+            break;
+        }
         let url_clone = url.clone();
-        let handle = tokio::task::spawn_blocking(move || {
-            let res = fetch_and_convert_sync(&browser_clone, &url_clone);
+        let cache = cache.clone();
+        let progress = progress.clone();
+        // Cache e HTTP primeiro; a aba do Chrome só é aberta para páginas renderizadas via JS
+        let handle = tokio::spawn(async move {
+            let res = scrape_url_tracked(&url_clone, cache.as_ref(), progress.as_ref()).await;
             drop(permit);
             (url_clone, res)
         });
@@ -1292,9 +2073,11 @@ pub async fn search_and_scrape_with_config(
 
     // 3. Coletar resultados (ignorar erros individuais, continuar com sucessos)
     let mut results = Vec::new();
-    let mut failed_urls = Vec::new();
-    let mut connection_closed = false;
     for handle in handles {
+        if cancelled() {
+            handle.abort();
+            continue;
+        }
         match handle.await {
             Ok((_, Ok(content))) => {
                 // Filtrar conteúdo muito curto (< 200 caracteres)
@@ -1314,14 +2097,9 @@ pub async fn search_and_scrape_with_config(
             Ok((url, Err(e))) => {
                 let err_msg = format!("{}", e);
                 if err_msg.contains("Timeout") || err_msg.contains("ERR_HTTP") {
-                    log::debug!("URL ignorada (timeout/erro HTTP): {}", err_msg);
-                    failed_urls.push(url);
+                    log::debug!("URL ignorada (timeout/erro HTTP): {}: {}", url, err_msg);
                 } else {
-                    log::warn!("Erro ao processar URL: {}", e);
-                    if err_msg.contains("underlying connection is closed") {
-                        connection_closed = true;
-                        failed_urls.push(url);
-                    }
+                    log::warn!("Erro ao processar URL {}: {}", url, e);
                 }
             }
             Err(e) => {
@@ -1330,38 +2108,7 @@ pub async fn search_and_scrape_with_config(
         }
     }
     
-    if connection_closed && !failed_urls.is_empty() {
-        let retry_concurrency = std::cmp::min(3, config.max_concurrent_tabs.max(1));
-        let semaphore = Arc::new(Semaphore::new(retry_concurrency));
-        let browser_new = get_or_create_browser()?;
-        let mut retry_handles = Vec::new();
-        for url in failed_urls.clone() {
-            let browser_clone = browser_new.clone();
-            let permit = semaphore.clone().acquire_owned().await.unwrap();
-            let url_clone = url.clone();
-            let handle = tokio::task::spawn_blocking(move || {
-                let res = fetch_and_convert_sync(&browser_clone, &url_clone);
-                drop(permit);
-                (url_clone, res)
-            });
-            retry_handles.push(handle);
-        }
-        for h in retry_handles {
-            match h.await {
-                Ok((_, Ok(content))) => {
-                    let content_length = content.content.chars().count();
-                    let markdown_length = content.markdown.chars().count();
-                    if content_length >= 200 || markdown_length >= 200 {
-                        results.push(content);
-                    }
-                }
-                Ok((url, Err(e))) => {
-                    log::warn!("Falha após retry para URL {}: {}", url, e);
-                }
-                Err(e) => log::warn!("Erro na task de retry: {}", e),
-            }
-        }
-    }
+    let results = dedupe_scraped(found.with_scraped(results));
     
     if results.is_empty() {
         log::warn!("Nenhuma fonte foi extraída com sucesso para a query: {}", query);
@@ -1372,11 +2119,38 @@ pub async fn search_and_scrape_with_config(
     Ok(results)
 }
 
+/// Resultado de uma busca HTTP (sem headless browser)
+enum StaticFetch {
+    /// Conteúdo extraído e ETag da resposta
+    Content(ScrapedContent, Option<String>),
+    /// Servidor confirmou que a versão em cache ainda vale (HTTP 304)
+    NotModified,
+    /// Falha, conteúdo insuficiente ou página que depende de JS
+    Insufficient,
+}
+
 /// Scraping estático usando apenas reqwest (sem headless browser)
 /// Muito mais rápido (~100ms vs ~3s) e consome menos RAM
 /// Retorna None se o conteúdo for insuficiente (SPA/JavaScript-heavy)
 pub async fn scrape_url_static(url: &str) -> Result<Option<ScrapedContent>> {
-    let client = reqwest::Client::builder()
+    match fetch_static(url, None).await? {
+        StaticFetch::Content(content, _) => Ok(Some(content)),
+        _ => Ok(None),
+    }
+}
+
+/// Busca HTTP com revalidação opcional via `If-None-Match`
+async fn fetch_static(url: &str, etag: Option<&str>) -> Result<StaticFetch> {
+    crate::audit::record(crate::audit::AuditKind::WebFetch, "http", url, None);
+    // Vídeos do YouTube: a transcrição no lugar da página (sem legenda, segue o scraping normal)
+    if crate::youtube::video_id(url).is_some() {
+        match crate::youtube::fetch_transcript(url).await {
+            Ok(content) => return Ok(StaticFetch::Content(content, None)),
+            Err(e) => log::debug!("[StaticScrape] Sem transcrição para {}: {}", url, e),
+        }
+    }
+    
+    let client = client_builder(None)
         .timeout(Duration::from_secs(8))
         .redirect(reqwest::redirect::Policy::limited(5))
         .build()?;
@@ -1386,90 +2160,520 @@ pub async fn scrape_url_static(url: &str) -> Result<Option<ScrapedContent>> {
     
     log::debug!("[StaticScrape] Fetching: {}", url);
     
-    let response = match client
+    let mut request = client
         .get(url)
         .header(USER_AGENT, user_agent)
-        .header("Accept", "text/html,application/xhtml+xml,application/xml;q=0.9,*/*;q=0.8")
-        .header("Accept-Language", "pt-BR,pt;q=0.9,en-US;q=0.8,en;q=0.7")
-        .send()
-        .await
-    {
+        .header("Accept", "text/html,application/xhtml+xml,application/xml;q=0.9,application/pdf,text/plain,application/json;q=0.8,*/*;q=0.7")
+        .header("Accept-Language", "pt-BR,pt;q=0.9,en-US;q=0.8,en;q=0.7");
+    if let Some(etag) = etag {
+        request = request.header(reqwest::header::IF_NONE_MATCH, etag);
+    }
+    
+    let response = match request.send().await {
         Ok(r) => r,
         Err(e) => {
             log::debug!("[StaticScrape] Request failed for {}: {}", url, e);
-            return Ok(None);
+            return Ok(StaticFetch::Insufficient);
         }
     };
     
+    if response.status() == reqwest::StatusCode::NOT_MODIFIED {
+        log::debug!("[StaticScrape] Not modified: {}", url);
+        return Ok(StaticFetch::NotModified);
+    }
+    
     if !response.status().is_success() {
         log::debug!("[StaticScrape] HTTP {} for {}", response.status(), url);
-        return Ok(None);
+        return Ok(StaticFetch::Insufficient);
+    }
+    
+    let content_type = response
+        .headers()
+        .get(reqwest::header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .map(|v| v.to_string());
+    let response_etag = response
+        .headers()
+        .get(reqwest::header::ETAG)
+        .and_then(|v| v.to_str().ok())
+        .map(|v| v.to_string());
+    
+    // PDFs, texto puro e JSON têm extração própria; imagens etc. são descartadas
+    let kind = DocumentKind::detect(content_type.as_deref(), url);
+    if kind != DocumentKind::Html {
+        if kind == DocumentKind::Unsupported {
+            log::debug!("[StaticScrape] Unsupported content type {:?} for {}", content_type, url);
+            return Ok(StaticFetch::Insufficient);
+        }
+        if response.content_length().map(|len| len > MAX_DOCUMENT_BYTES).unwrap_or(false) {
+            log::debug!("[StaticScrape] Document too large: {}", url);
+            return Ok(StaticFetch::Insufficient);
+        }
+        let bytes = match response.bytes().await {
+            Ok(b) if b.len() as u64 <= MAX_DOCUMENT_BYTES => b.to_vec(),
+            Ok(_) => return Ok(StaticFetch::Insufficient),
+            Err(e) => {
+                log::debug!("[StaticScrape] Failed to read body for {}: {}", url, e);
+                return Ok(StaticFetch::Insufficient);
+            }
+        };
+        let url_owned = url.to_string();
+        let extracted = tokio::task::spawn_blocking(move || extract_document(&url_owned, kind, &bytes))
+            .await
+            .map_err(|e| anyhow::anyhow!("Erro na task: {}", e))?;
+        return match extracted {
+            Ok(content) => {
+                log::info!("[StaticScrape] Extracted {:?} document {} ({} chars, {}ms)",
+                    kind, url, content.content.len(), start_time.elapsed().as_millis());
+                Ok(StaticFetch::Content(content, response_etag))
+            }
+            Err(e) => {
+                log::debug!("[StaticScrape] Document extraction failed for {}: {}", url, e);
+                Ok(StaticFetch::Insufficient)
+            }
+        };
     }
     
     let html = match response.text().await {
         Ok(t) => t,
         Err(e) => {
             log::debug!("[StaticScrape] Failed to read body for {}: {}", url, e);
-            return Ok(None);
+            return Ok(StaticFetch::Insufficient);
         }
     };
     
     let duration = start_time.elapsed().as_millis();
     
-    // Usar extract_paragraph_fallback existente
-    let result = extract_paragraph_fallback(url, &html);
+    // Readability (com fallback de parágrafos); páginas que dependem de JS ficam para o headless
+    match extract_readable(url, &html) {
+        Ok(content) if !looks_js_rendered(&html, content.markdown.chars().count()) => {
+            log::info!("[StaticScrape] Success for {} ({} chars, {}ms)", 
+                url, content.content.len(), duration);
+            Ok(StaticFetch::Content(content, response_etag))
+        }
+        Ok(_) => {
+            log::debug!("[StaticScrape] Page looks JS-rendered: {} ({}ms)", url, duration);
+            Ok(StaticFetch::Insufficient)
+        }
+        Err(e) => {
+            log::debug!("[StaticScrape] Extraction failed for {}: {} ({}ms)", url, e, duration);
+            Ok(StaticFetch::Insufficient)
+        }
+    }
+}
+
+/// Tamanho máximo de documentos não-HTML (PDF, texto, JSON) baixados para extração
+const MAX_DOCUMENT_BYTES: u64 = 20 * 1024 * 1024;
+/// Limite de caracteres do texto extraído de documentos (PDFs longos viram várias páginas de contexto)
+const MAX_DOCUMENT_CHARS: usize = 200_000;
+
+/// Tipo de conteúdo de uma resposta HTTP, para escolher o extrator
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum DocumentKind {
+    Html,
+    Pdf,
+    PlainText,
+    Json,
+    Unsupported,
+}
+
+impl DocumentKind {
+    /// Usa o `Content-Type`; sem ele (ou com `octet-stream`), a extensão da URL
+    fn detect(content_type: Option<&str>, url: &str) -> Self {
+        let mime = content_type
+            .and_then(|ct| ct.split(';').next())
+            .map(|ct| ct.trim().to_lowercase())
+            .unwrap_or_default();
+        
+        match mime.as_str() {
+            m if m.contains("html") => return DocumentKind::Html,
+            "application/pdf" | "application/x-pdf" => return DocumentKind::Pdf,
+            m if m == "application/json" || m.ends_with("+json") => return DocumentKind::Json,
+            m if m.starts_with("text/") => return DocumentKind::PlainText,
+            "" | "application/octet-stream" | "binary/octet-stream" => {}
+            _ => return DocumentKind::Unsupported,
+        }
+        
+        let path = url::Url::parse(url)
+            .map(|u| u.path().to_lowercase())
+            .unwrap_or_default();
+        if path.ends_with(".pdf") {
+            DocumentKind::Pdf
+        } else if path.ends_with(".json") {
+            DocumentKind::Json
+        } else if path.ends_with(".txt") || path.ends_with(".md") {
+            DocumentKind::PlainText
+        } else if mime.is_empty() {
+            DocumentKind::Html
+        } else {
+            DocumentKind::Unsupported
+        }
+    }
+}
+
+/// Extrai texto de documentos não-HTML no mesmo formato de `ScrapedContent`
+fn extract_document(url: &str, kind: DocumentKind, bytes: &[u8]) -> Result<ScrapedContent> {
+    // O título vem do texto bruto, antes de juntar as linhas
+    let (title, text, markdown) = match kind {
+        DocumentKind::Pdf => {
+            let raw = pdf_extract::extract_text_from_mem(bytes)
+                .map_err(|e| anyhow::anyhow!("Falha ao extrair PDF: {}", e))?;
+            let text = normalize_document_text(&raw);
+            (document_title(url, &raw), text.clone(), text)
+        }
+        DocumentKind::PlainText => {
+            let raw = String::from_utf8_lossy(bytes);
+            let text = normalize_document_text(&raw);
+            (document_title(url, &raw), text.clone(), text)
+        }
+        DocumentKind::Json => {
+            let value: serde_json::Value = serde_json::from_slice(bytes)
+                .map_err(|e| anyhow::anyhow!("JSON inválido: {}", e))?;
+            let pretty = serde_json::to_string_pretty(&value)?;
+            (document_title(url, ""), pretty.clone(), format!("```json\n{}\n```", pretty))
+        }
+        DocumentKind::Html | DocumentKind::Unsupported => {
+            return Err(anyhow::anyhow!("Tipo de documento sem extrator: {:?}", kind));
+        }
+    };
     
-    if let Some(ref content) = result {
-        log::info!("[StaticScrape] Success for {} ({} chars, {}ms)", 
-            url, content.content.len(), duration);
-    } else {
-        log::debug!("[StaticScrape] Insufficient content for {} ({}ms)", url, duration);
+    if text.trim().is_empty() {
+        return Err(anyhow::anyhow!("Documento sem texto extraível"));
     }
     
-    Ok(result)
+    let truncate = |s: String| -> String {
+        if s.chars().count() > MAX_DOCUMENT_CHARS {
+            s.chars().take(MAX_DOCUMENT_CHARS).collect()
+        } else {
+            s
+        }
+    };
+    
+    Ok(ScrapedContent {
+        title,
+        url: url.to_string(),
+        content: truncate(text),
+        markdown: truncate(markdown),
+        original_language: None,
+        published_at: None,
+    })
+}
+
+/// Junta linhas quebradas pelo layout do PDF e remove excesso de linhas vazias
+fn normalize_document_text(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut blank_lines = 0;
+    for line in text.lines() {
+        let line = line.trim_end();
+        if line.trim().is_empty() {
+            blank_lines += 1;
+            if blank_lines == 1 && !out.is_empty() {
+                out.push_str("\n\n");
+            }
+            continue;
+        }
+        if blank_lines == 0 && !out.is_empty() {
+            out.push(' ');
+        }
+        blank_lines = 0;
+        out.push_str(line.trim_start());
+    }
+    out.trim().to_string()
 }
 
-/// Busca e extrai conteúdo de uma única URL (híbrido: tenta estático primeiro)
+/// Título do documento: primeira linha curta do texto, ou o nome do arquivo na URL
+fn document_title(url: &str, text: &str) -> String {
+    let first_line = text
+        .lines()
+        .map(|l| l.trim())
+        .find(|l| !l.is_empty());
+    if let Some(line) = first_line {
+        if line.chars().count() <= 200 {
+            return line.to_string();
+        }
+    }
+    url::Url::parse(url)
+        .ok()
+        .and_then(|u| u.path_segments().and_then(|mut s| s.next_back()).map(|s| s.to_string()))
+        .filter(|s| !s.is_empty())
+        .unwrap_or_else(|| url.to_string())
+}
+
+/// Abaixo deste tamanho de texto o resultado HTTP é considerado insuficiente
+const STATIC_MIN_TEXT_CHARS: usize = 500;
+/// Acima deste tamanho os marcadores de JS são ignorados (muitos sites têm `<noscript>` de analytics)
+const JS_MARKER_MAX_TEXT_CHARS: usize = 2000;
+
+/// Indica se a página parece depender de JavaScript para renderizar o conteúdo
+fn looks_js_rendered(html: &str, text_chars: usize) -> bool {
+    if text_chars < STATIC_MIN_TEXT_CHARS {
+        return true;
+    }
+    if text_chars >= JS_MARKER_MAX_TEXT_CHARS {
+        return false;
+    }
+    
+    let lower = html.to_lowercase();
+    let markers = [
+        "enable javascript",
+        "javascript is disabled",
+        "requires javascript",
+        "habilite o javascript",
+        "ative o javascript",
+        "<div id=\"root\"></div>",
+        "<div id=\"app\"></div>",
+        "<div id=\"__next\"></div>",
+        "<app-root></app-root>",
+    ];
+    markers.iter().any(|marker| lower.contains(marker))
+}
+
+/// TTL padrão do cache de páginas quando `max_age` não é informado
+pub const DEFAULT_PAGE_CACHE_MAX_AGE_SECS: u64 = 6 * 60 * 60;
+
+/// Cache persistente de páginas extraídas (tabela `scrape_cache`), consultado antes de baixar a URL
+#[derive(Clone)]
+pub struct PageCache {
+    app_handle: AppHandle,
+    max_age: chrono::Duration,
+}
+
+/// Estado de uma URL no cache
+enum CacheLookup {
+    Fresh(ScrapedContent),
+    /// Expirada, mas pode ser revalidada com o ETag
+    Stale(ScrapedContent, Option<String>),
+    Miss,
+}
+
+/// Chave do cache: SHA-256 (hex) da URL
+fn page_cache_key(url: &str) -> String {
+    use sha2::{Digest, Sha256};
+    Sha256::digest(url.as_bytes()).iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+impl PageCache {
+    /// `max_age_secs = 0` ignora o que estiver em cache (mas ainda atualiza o cache)
+    pub fn new(app_handle: &AppHandle, max_age_secs: Option<u64>) -> Self {
+        let secs = max_age_secs.unwrap_or(DEFAULT_PAGE_CACHE_MAX_AGE_SECS).min(u32::MAX as u64) as i64;
+        Self {
+            app_handle: app_handle.clone(),
+            max_age: chrono::Duration::seconds(secs),
+        }
+    }
+    
+    fn lookup(&self, url: &str) -> CacheLookup {
+        let page = match crate::db::Database::new(&self.app_handle)
+            .and_then(|db| db.get_cached_page(&page_cache_key(url)))
+        {
+            Ok(Some(page)) => page,
+            Ok(None) => return CacheLookup::Miss,
+            Err(e) => {
+                log::warn!("[PageCache] Falha ao ler cache de {}: {}", url, e);
+                return CacheLookup::Miss;
+            }
+        };
+        
+        let fresh = chrono::Utc::now() - page.fetched_at < self.max_age;
+        let content = ScrapedContent {
+            title: page.title,
+            url: page.url,
+            content: page.content,
+            markdown: page.markdown,
+            original_language: None,
+            published_at: page.published_at,
+        };
+        if fresh {
+            CacheLookup::Fresh(content)
+        } else {
+            CacheLookup::Stale(content, page.etag)
+        }
+    }
+    
+    fn store(&self, content: &ScrapedContent, etag: Option<String>) {
+        let page = crate::db::CachedPage {
+            url: content.url.clone(),
+            title: content.title.clone(),
+            content: content.content.clone(),
+            markdown: content.markdown.clone(),
+            published_at: content.published_at.clone(),
+            etag,
+            fetched_at: chrono::Utc::now(),
+        };
+        let result = crate::db::Database::new(&self.app_handle)
+            .and_then(|db| db.save_cached_page(&page_cache_key(&content.url), &page));
+        if let Err(e) = result {
+            log::warn!("[PageCache] Falha ao gravar cache de {}: {}", content.url, e);
+        }
+    }
+    
+    fn touch(&self, url: &str) {
+        let result = crate::db::Database::new(&self.app_handle)
+            .and_then(|db| db.touch_cached_page(&page_cache_key(url)));
+        if let Err(e) = result {
+            log::warn!("[PageCache] Falha ao renovar cache de {}: {}", url, e);
+        }
+    }
+}
+
+/// Busca e extrai conteúdo de uma única URL (híbrido: cache, depois estático, depois headless)
 pub async fn scrape_url(
     url: &str,
-    browser: Arc<Browser>,
+    cache: Option<&PageCache>,
 ) -> Result<ScrapedContent> {
+    let mut stale = None;
+    if let Some(cache) = cache {
+        match cache.lookup(url) {
+            CacheLookup::Fresh(content) => {
+                log::debug!("[PageCache] Hit: {}", url);
+                return Ok(content);
+            }
+            CacheLookup::Stale(content, etag) => stale = Some((content, etag)),
+            CacheLookup::Miss => {}
+        }
+    }
+    
     // OTIMIZAÇÃO: Tentar scraping estático primeiro (muito mais rápido)
-    if let Ok(Some(content)) = scrape_url_static(url).await {
-        // Se conseguiu conteúdo suficiente (>500 chars), usar resultado estático
-        if content.content.len() > 500 {
-            log::info!("[ScrapeHybrid] Using static result for {} ({} chars)", url, content.content.len());
+    // Só retorna conteúdo quando a página não parece depender de JS
+    let etag = stale.as_ref().and_then(|(_, etag)| etag.as_deref());
+    match fetch_static(url, etag).await {
+        Ok(StaticFetch::Content(content, etag)) => {
+            log::info!("[ScrapeHybrid] Using static result for {} ({} chars)", url, content.markdown.len());
+            if let Some(cache) = cache {
+                cache.store(&content, etag);
+            }
             return Ok(content);
         }
+        Ok(StaticFetch::NotModified) => {
+            if let (Some(cache), Some((content, _))) = (cache, stale) {
+                log::debug!("[PageCache] Revalidated: {}", url);
+                cache.touch(url);
+                return Ok(content);
+            }
+        }
+        _ => {}
+    }
+    
+    if !crate::resource_profile::current().headless_scraping_enabled {
+        return Err(anyhow::anyhow!("Conteúdo insuficiente via HTTP e headless desativado no modo de baixo consumo: {}", url));
     }
     
     // Fallback: usar headless browser para SPAs/JS-heavy pages
     log::info!("[ScrapeHybrid] Falling back to headless for {}", url);
-    let browser_clone = browser.clone();
-    let url_str = url.to_string();
-    tokio::task::spawn_blocking(move || {
-        fetch_and_convert_sync(&browser_clone, &url_str)
-    })
-    .await
-    .map_err(|e| anyhow::anyhow!("Erro na task: {}", e))?
+    let content = fetch_headless(url).await?;
+    
+    if let Some(cache) = cache {
+        cache.store(&content, None);
+    }
+    Ok(content)
+}
+
+/// Renderiza a página em um browser do pool. Se a instância tiver caído, ela é
+/// descartada e a página é tentada mais uma vez em outra instância.
+async fn fetch_headless(url: &str) -> Result<ScrapedContent> {
+    let mut attempt = 0;
+    loop {
+        let browser = crate::browser_pool::acquire()?;
+        let browser_clone = browser.clone();
+        let url_str = url.to_string();
+        let result = tokio::task::spawn_blocking(move || {
+            fetch_and_convert_sync(&browser_clone, &url_str)
+        })
+        .await
+        .map_err(|e| anyhow::anyhow!("Erro na task: {}", e))?;
+        
+        match result {
+            Err(e) if attempt == 0 && crate::browser_pool::is_connection_error(&e) => {
+                log::warn!("[ScrapeHybrid] Browser indisponível para {} ({}), tentando outra instância", url, e);
+                crate::browser_pool::discard(&browser);
+                attempt += 1;
+            }
+            result => return result,
+        }
+    }
+}
+
+/// Busca e extrai conteúdo usando apenas HTTP (modo de baixo consumo, sem headless)
+pub async fn search_and_scrape_static(
+    query: &str,
+    config: &SearchConfig,
+    progress: Option<ScrapeProgress>,
+) -> Result<Vec<ScrapedContent>> {
+    let mut found = smart_search(query, config).await?;
+    let urls = std::mem::take(&mut found.urls);
+    
+    if urls.is_empty() {
+        if found.resolved.is_empty() {
+            log::warn!("Nenhuma URL encontrada para a query: {}", query);
+        }
+        return Ok(found.with_scraped(Vec::new()));
+    }
+    
+    let scraped = scrape_urls_static(urls, progress).await?;
+    Ok(found.with_scraped(scraped))
+}
+
+/// Extrai conteúdo de múltiplas URLs apenas via HTTP, uma por vez
+pub async fn scrape_urls_static(urls: Vec<String>, progress: Option<ScrapeProgress>) -> Result<Vec<ScrapedContent>> {
+    let mut results = Vec::new();
+    if let Some(progress) = &progress {
+        progress.add_total(urls.len());
+    }
+    
+    for url in urls {
+        if progress.as_ref().is_some_and(|p| p.is_cancelled()) {
+            break;
+        }
+        let started_at = Instant::now();
+        if let Some(progress) = &progress {
+            progress.started(&url);
+        }
+        let result = scrape_url_static(&url).await;
+        if let Some(progress) = &progress {
+            progress.finished(&url, started_at, &result, |c| c.as_ref().map(|c| c.markdown.chars().count()).unwrap_or(0));
+        }
+        match result {
+            Ok(Some(content)) => {
+                let content_len = content.content.chars().count();
+                let md_len = content.markdown.chars().count();
+                if content_len < 200 && md_len < 200 {
+                    log::debug!("Descartado por conteúdo curto: {}", content.url);
+                } else {
+                    results.push(content);
+                }
+            }
+            Ok(None) => log::debug!("[StaticScrape] Sem conteúdo suficiente: {}", url),
+            Err(e) => log::warn!("Erro ao processar URL {}: {}", url, e),
+        }
+    }
+    
+    Ok(dedupe_scraped(results))
 }
 
 /// Extrai conteúdo de múltiplas URLs já definidas (bulk)
 pub async fn scrape_urls_bulk(
     urls: Vec<String>,
-    browser: Arc<Browser>,
+    cache: Option<PageCache>,
+    progress: Option<ScrapeProgress>,
 ) -> Result<Vec<ScrapedContent>> {
     if urls.is_empty() { return Ok(Vec::new()); }
-    let concurrency = 5usize;
+    let concurrency = crate::resource_profile::current().max_concurrent_tabs.clamp(1, 5);
     let semaphore = Arc::new(Semaphore::new(concurrency));
     let mut handles = Vec::new();
+    let cancelled = || progress.as_ref().is_some_and(|p| p.is_cancelled());
+    if let Some(progress) = &progress {
+        progress.add_total(urls.len());
+    }
 
     for url in urls {
-        let browser_clone = browser.clone();
         let permit = semaphore.clone().acquire_owned().await.unwrap();
-        let url_clone = url.clone();
-        let handle = tokio::task::spawn_blocking(move || {
-            let res = fetch_and_convert_sync(&browser_clone, &url_clone);
+        if cancelled() {
+            break;
+        }
+        let cache = cache.clone();
+        let progress = progress.clone();
+        let handle = tokio::spawn(async move {
+            let res = scrape_url_tracked(&url, cache.as_ref(), progress.as_ref()).await;
             drop(permit);
             res
         });
@@ -1478,6 +2682,10 @@ pub async fn scrape_urls_bulk(
 
     let mut results = Vec::new();
     for h in handles {
+        if cancelled() {
+            h.abort();
+            continue;
+        }
         match h.await {
             Ok(Ok(content)) => {
                 let content_len = content.content.chars().count();
@@ -1500,7 +2708,7 @@ pub async fn scrape_urls_bulk(
         }
     }
 
-    Ok(results)
+    Ok(dedupe_scraped(results))
 }
 
 /// Extrai conteúdo de uma URL e converte para Markdown (versão síncrona)
@@ -1523,6 +2731,7 @@ fn fetch_and_convert_sync(browser: &Browser, url: &str) -> Result<ScrapedContent
     // Timeout reduzido para navegação
     tab.set_default_timeout(Duration::from_secs(8));
     
+    crate::audit::record(crate::audit::AuditKind::WebFetch, "browser", url, None);
     // Tentar navegar com tratamento de erro HTTP
     match tab.navigate_to(url) {
         Ok(_) => {},
@@ -1604,7 +2813,93 @@ fn fetch_and_convert_sync(browser: &Browser, url: &str) -> Result<ScrapedContent
         }
     };
     
-    // Limpeza com Readability (remove ads, menus, footers)
+    extract_readable(url, &content)
+}
+
+/// Remove do HTML os elementos que casam com os seletores de exclusão
+fn remove_excluded(html: &str, exclude_selectors: &[String]) -> String {
+    let document = Html::parse_document(html);
+    let mut cleaned = document.root_element().html();
+    for raw in exclude_selectors {
+        let selector = match Selector::parse(raw) {
+            Ok(selector) => selector,
+            Err(_) => continue,
+        };
+        for element in document.select(&selector) {
+            cleaned = cleaned.replacen(&element.html(), "", 1);
+        }
+    }
+    cleaned
+}
+
+fn select_text(html: &str, raw_selector: &str) -> Option<String> {
+    let document = Html::parse_document(html);
+    let selector = Selector::parse(raw_selector).ok()?;
+    let text = document
+        .select(&selector)
+        .next()?
+        .text()
+        .collect::<Vec<_>>()
+        .join(" ");
+    let text = text.split_whitespace().collect::<Vec<_>>().join(" ");
+    (!text.is_empty()).then_some(text)
+}
+
+/// Extração pelos seletores da regra do domínio. `None` quando o seletor de conteúdo
+/// não encontra nada (a página segue para o readability).
+fn extract_with_rule(url: &str, html: &str, rule: &ExtractionRule) -> Option<ScrapedContent> {
+    let content_selector = Selector::parse(rule.content_selector.as_deref()?).ok()?;
+    let cleaned = remove_excluded(html, &rule.exclude_selectors);
+    let document = Html::parse_document(&cleaned);
+    let content: String = document
+        .select(&content_selector)
+        .map(|element| element.html())
+        .collect::<Vec<_>>()
+        .join("\n");
+    let markdown = html2text::from_read(content.as_bytes(), 80);
+    if markdown.trim().is_empty() {
+        log::warn!("[Extraction] Seletor de conteúdo da regra {} não encontrou nada em {}", rule.domain, url);
+        return None;
+    }
+    
+    let title = rule
+        .title_selector
+        .as_deref()
+        .and_then(|selector| select_text(&cleaned, selector))
+        .or_else(|| fallback_title(html))
+        .unwrap_or_else(|| "Fonte externa sem título".to_string());
+    
+    Some(ScrapedContent {
+        title: title.clone(),
+        url: url.to_string(),
+        content,
+        markdown: format!("---\nTitle: {}\nSource: {}\n---\n\n{}", title, url, markdown),
+        original_language: None,
+        published_at: extract_published_date(html),
+    })
+}
+
+/// Limpeza com Readability (remove ads, menus, footers) com fallback de parágrafos.
+/// Regras de extração do domínio (sources.json) têm prioridade sobre o readability.
+fn extract_readable(url: &str, content: &str) -> Result<ScrapedContent> {
+    let rule = extraction_rule_for(url);
+    let cleaned;
+    let content = match &rule {
+        Some(rule) => {
+            if let Some(scraped) = extract_with_rule(url, content, rule) {
+                log::debug!("[Extraction] Regra {} aplicada para {}", rule.domain, url);
+                return Ok(scraped);
+            }
+            cleaned = remove_excluded(content, &rule.exclude_selectors);
+            cleaned.as_str()
+        }
+        None => content,
+    };
+    let rule_title = rule
+        .as_ref()
+        .and_then(|rule| rule.title_selector.as_deref())
+        .and_then(|selector| select_text(content, selector));
+    
     let mut reader = std::io::Cursor::new(content.as_bytes());
     let url_obj = match Url::parse(url) {
         Ok(u) => u,
@@ -1619,14 +2914,16 @@ fn fetch_and_convert_sync(browser: &Browser, url: &str) -> Result<ScrapedContent
             let markdown = html2text::from_read(product.content.as_bytes(), 80);
             // Se o markdown for muito curto, significa que o readability pode ter falhado
             if markdown.trim().chars().count() < 400 {
-                if let Some(fallback) = extract_paragraph_fallback(url, &content) {
+                if let Some(fallback) = extract_paragraph_fallback(url, content) {
                     log::info!("Fallback de parágrafos aplicado para {}", url);
                     return Ok(fallback);
                 }
             }
             
-            let title = if product.title.is_empty() {
-                fallback_title(&content).unwrap_or_else(|| "Fonte externa sem título".to_string())
+            let title = if let Some(title) = rule_title {
+                title
+            } else if product.title.is_empty() {
+                fallback_title(content).unwrap_or_else(|| "Fonte externa sem título".to_string())
             } else {
                 product.title.clone()
             };
@@ -1641,11 +2938,13 @@ fn fetch_and_convert_sync(browser: &Browser, url: &str) -> Result<ScrapedContent
                     url,
                     markdown
                 ),
+                original_language: None,
+                published_at: extract_published_date(content),
             })
         }
         Err(e) => {
             log::warn!("Falha ao extrair conteúdo legível de {}: {}. Tentando fallback...", url, e);
-            if let Some(fallback) = extract_paragraph_fallback(url, &content) {
+            if let Some(fallback) = extract_paragraph_fallback(url, content) {
                 return Ok(fallback);
             }
             Err(anyhow::anyhow!("Falha ao processar conteúdo: {}", e))
@@ -1783,19 +3082,25 @@ fn disable_media_autoplay(tab: &Tab) -> Result<()> {
     }
 }
 
-/// Cria uma instância do Browser (singleton para reutilização)
+/// Cria uma instância do Browser (gerenciada pelo `browser_pool`)
 pub fn create_browser() -> Result<Browser> {
     use std::ffi::OsStr;
     
     // Argumentos do Chrome para bloquear autoplay de mídia
     // Nota: O bloqueio principal será feito via JavaScript injection, mas esses args ajudam
-    let chrome_args: Vec<&OsStr> = vec![
+    let mut chrome_args: Vec<&OsStr> = vec![
         OsStr::new("--autoplay-policy=document-user-activation-required"), // Exige interação do usuário para autoplay
         OsStr::new("--disable-background-media-playback"), // Desabilita reprodução de mídia em segundo plano
         OsStr::new("--mute-audio"), // Silencia todo áudio (mais agressivo, mas garante silêncio)
         OsStr::new("--disable-features=AutoplayIgnoreWebAudio"), // Desabilita autoplay de Web Audio
     ];
     
+    // O proxy do Chrome vale para o processo inteiro: o rodízio acontece a cada novo browser
+    let proxy_arg = next_proxy(None).and_then(|proxy| chrome_proxy_arg(&proxy));
+    if let Some(arg) = &proxy_arg {
+        chrome_args.push(OsStr::new(arg));
+    }
+    
     let options = LaunchOptions {
         headless: true,
         args: chrome_args,
@@ -1841,6 +3146,8 @@ fn extract_paragraph_fallback(url: &str, html: &str) -> Option<ScrapedContent> {
             url,
             fallback_body
         ),
+        original_language: None,
+        published_at: extract_published_date(html),
     })
 }
 
@@ -1870,3 +3177,182 @@ fn fallback_title(html: &str) -> Option<String> {
     None
 }
 
+/// Data de publicação a partir das meta tags mais comuns (Open Graph, Dublin Core, schema.org)
+pub(crate) fn extract_published_date(html: &str) -> Option<String> {
+    use scraper::{Html, Selector};
+    
+    let document = Html::parse_document(html);
+    let selectors = [
+        "meta[property='article:published_time']",
+        "meta[name='article:published_time']",
+        "meta[itemprop='datePublished']",
+        "meta[name='date']",
+        "meta[name='DC.date.issued']",
+        "meta[name='dc.date']",
+        "meta[name='pubdate']",
+        "meta[name='publish-date']",
+        "time[itemprop='datePublished']",
+        "time[datetime]",
+    ];
+    
+    for raw in selectors {
+        let selector = match Selector::parse(raw) {
+            Ok(s) => s,
+            Err(_) => continue,
+        };
+        for node in document.select(&selector) {
+            let value = node
+                .value()
+                .attr("content")
+                .or_else(|| node.value().attr("datetime"))
+                .map(|v| v.trim())
+                .unwrap_or("");
+            if let Some(date) = crate::citations::normalize_date(value) {
+                return Some(date);
+            }
+        }
+    }
+    
+    None
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_selector_health() {
+        let selectors = SearchSelectors {
+            container: vec![".result"],
+            title: vec!["h3", ".missing"],
+            url: vec!["a[href]"],
+            snippet: vec!["p::invalid"],
+        };
+        let html = r#"<div class="result"><h3>A</h3><a href="https://a">a</a></div><div class="result"><h3>B</h3></div>"#;
+        let health: Vec<(&str, usize)> = selector_health(&selectors, html)
+            .iter()
+            .map(|h| (h.kind, h.matches))
+            .collect();
+        assert_eq!(health, vec![("container", 2), ("title", 2), ("title", 0), ("url", 1), ("snippet", 0)]);
+    }
+
+    #[test]
+    fn test_weighted_source_allocation() {
+        assert_eq!(allocate_slots(10, &[1, 3, 1]), vec![2, 6, 2]);
+        assert_eq!(allocate_slots(4, &[1, 1, 1]).iter().sum::<usize>(), 4);
+
+        let urls = |prefix: &str, n: usize| (0..n).map(|i| format!("{}{}", prefix, i)).collect::<Vec<_>>();
+        let merged = merge_by_quota(vec![urls("g", 5), urls("a", 1)], &[2, 3], 5);
+        assert_eq!(merged, vec!["g0", "g1", "a0", "g2", "g3"]);
+    }
+
+    #[test]
+    fn test_looks_js_rendered() {
+        let spa = "<html><body><noscript>Please enable JavaScript</noscript><div id=\"root\"></div></body></html>";
+        assert!(looks_js_rendered(spa, 120));
+        assert!(looks_js_rendered(spa, 900));
+
+        let article = "<html><body><noscript><iframe src=\"gtm\"></iframe></noscript><p>...</p></body></html>";
+        assert!(!looks_js_rendered(article, 900));
+        assert!(!looks_js_rendered(spa, 5000));
+    }
+
+    #[test]
+    fn test_document_kind_and_normalize() {
+        assert_eq!(DocumentKind::detect(Some("application/pdf"), "https://x.org/a"), DocumentKind::Pdf);
+        assert_eq!(DocumentKind::detect(Some("application/octet-stream"), "https://arxiv.org/pdf/2401.1.pdf"), DocumentKind::Pdf);
+        assert_eq!(DocumentKind::detect(Some("application/vnd.api+json; charset=utf-8"), "https://x.org"), DocumentKind::Json);
+        assert_eq!(DocumentKind::detect(Some("text/plain"), "https://x.org/r.txt"), DocumentKind::PlainText);
+        assert_eq!(DocumentKind::detect(Some("text/html; charset=utf-8"), "https://x.org/a.pdf"), DocumentKind::Html);
+        assert_eq!(DocumentKind::detect(None, "https://x.org/page"), DocumentKind::Html);
+        assert_eq!(DocumentKind::detect(Some("image/png"), "https://x.org/a.png"), DocumentKind::Unsupported);
+
+        let text = "Attention Is All\nYou Need\n\n\n\nAbstract  \n  The dominant models";
+        assert_eq!(normalize_document_text(text), "Attention Is All You Need\n\nAbstract The dominant models");
+        assert_eq!(document_title("https://x.org/paper.json", ""), "paper.json");
+    }
+
+    #[test]
+    fn test_parse_api_results() {
+        let searx = serde_json::json!({"results": [
+            {"url": "https://a.org/x", "title": "A", "content": "snippet &amp; more"},
+            {"title": "sem url"}
+        ]});
+        let results = parse_searx_results(&searx, 10);
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].snippet, "snippet & more");
+
+        let brave = serde_json::json!({"web": {"results": [
+            {"url": "https://b.org", "title": "<strong>B</strong>", "description": "x <strong>y</strong>"},
+            {"url": "https://c.org", "title": "C", "description": ""}
+        ]}});
+        let results = parse_brave_results(&brave, 1);
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].title, "B");
+        assert_eq!(results[0].snippet, "x y");
+
+        let apis = SearchApiConfig { searx_url: Some("http://localhost:8080/".into()), ..SearchApiConfig::default() };
+        let order = SearchEngine::default_order(&apis);
+        assert_eq!(order[0], SearchEngine::Searx { base_url: "http://localhost:8080".into() });
+        assert_eq!(order.len(), 6);
+        assert_eq!(SearchEngine::from_config("brave", &apis), None);
+    }
+
+    #[test]
+    fn test_url_canonicalization_and_dedupe() {
+        assert_eq!(
+            canonicalize_url("https://www.site.com/news/a?utm_source=x&id=2&fbclid=y#top").as_deref(),
+            Some("https://www.site.com/news/a?id=2")
+        );
+        assert_eq!(
+            canonicalize_url("https://site-com.cdn.ampproject.org/c/s/site.com/news/a/amp").as_deref(),
+            Some("https://site.com/news/a/amp")
+        );
+        let key = url_dedup_key("https://www.site.com/news/a/?b=1&a=2");
+        assert_eq!(key, "site.com/news/a?a=2&b=1");
+        assert_eq!(url_dedup_key("http://m.site.com/news/a/amp?a=2&utm_medium=s&b=1"), key);
+        assert_eq!(url_dedup_key("https://amp.site.com/news/a?amp=1&b=1&a=2"), key);
+
+        let page = |url: &str, text: &str| ScrapedContent {
+            title: String::new(),
+            url: url.to_string(),
+            content: text.to_string(),
+            markdown: text.to_string(),
+            original_language: None,
+            published_at: None,
+        };
+        let unique = dedupe_scraped(vec![
+            page("https://a.com/x", "Same   article, text!"),
+            page("https://b.com/y", "same article text"),
+            page("https://www.a.com/x/", "different"),
+            page("https://c.com/z", "another one"),
+        ]);
+        assert_eq!(unique.iter().map(|p| p.url.as_str()).collect::<Vec<_>>(), vec!["https://a.com/x", "https://c.com/z"]);
+    }
+
+    #[test]
+    fn test_chrome_proxy_arg() {
+        assert_eq!(chrome_proxy_arg("http://user:pw@10.0.0.1:3128").as_deref(), Some("--proxy-server=http://10.0.0.1:3128"));
+        assert_eq!(chrome_proxy_arg("socks5://proxy.local:1080").as_deref(), Some("--proxy-server=socks5://proxy.local:1080"));
+        assert_eq!(chrome_proxy_arg("not a url"), None);
+    }
+
+    #[test]
+    fn test_extract_with_rule() {
+        let html = "<html><head><title>Fórum</title></head><body><nav>Menu</nav>\
+            <h2 class=\"q\">Como usar Rust?</h2><div class=\"post\">Resposta um<span class=\"sig\">assinatura</span></div>\
+            <div class=\"post\">Resposta dois</div></body></html>";
+        let rule = ExtractionRule {
+            domain: "forum.example.com".to_string(),
+            title_selector: Some("h2.q".to_string()),
+            content_selector: Some("div.post".to_string()),
+            exclude_selectors: vec![".sig".to_string()],
+        };
+        assert!(rule.matches_host("www.forum.example.com") && !rule.matches_host("example.com"));
+        let scraped = extract_with_rule("https://forum.example.com/t/1", html, &rule).unwrap();
+        assert_eq!(scraped.title, "Como usar Rust?");
+        assert!(scraped.markdown.contains("Resposta um") && scraped.markdown.contains("Resposta dois"));
+        assert!(!scraped.markdown.contains("assinatura") && !scraped.markdown.contains("Menu"));
+    }
+}
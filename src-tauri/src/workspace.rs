@@ -0,0 +1,456 @@
+//! Pasta local anexada a uma sessão ("conversar com o repositório")
+//!
+//! Os arquivos de texto (respeitando .gitignore) são divididos em trechos e guardados em
+//! `rag_documents` com `kind = 'workspace'`; um watcher reindexa os arquivos alterados.
+
+use crate::db::Database;
+use crate::{embeddings, paste, resource_profile};
+use ignore::gitignore::{Gitignore, GitignoreBuilder};
+use ignore::WalkBuilder;
+use notify::{RecommendedWatcher, RecursiveMode, Watcher};
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc;
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Manager};
+
+/// Arquivos maiores são ignorados (gerados, minificados, dumps)
+const MAX_FILE_BYTES: u64 = 512 * 1024;
+const MAX_FILES: usize = 5000;
+/// Trechos sem embedding embedados por busca (o resto usa palavras-chave até o job chegar neles)
+const MAX_INLINE_EMBEDDINGS: usize = 16;
+/// Linhas por trecho indexado
+const CHUNK_LINES: usize = 60;
+/// Espera por mais eventos do watcher antes de reindexar (salvamentos geram rajadas)
+const WATCH_DEBOUNCE: Duration = Duration::from_millis(500);
+
+/// Watchers ativos por sessão (soltar o watcher encerra a thread de reindexação)
+static WATCHERS: Mutex<Vec<(String, RecommendedWatcher)>> = Mutex::new(Vec::new());
+
+#[derive(Serialize, Clone, Debug)]
+pub struct IndexReport {
+    pub session_id: String,
+    pub root_path: String,
+    pub files: usize,
+    pub chunks: usize,
+    /// Arquivos binários, grandes demais ou acima do limite de arquivos
+    pub skipped: usize,
+    /// Job de embeddings iniciado para os trechos novos
+    pub embedding_job_id: Option<String>,
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct WorkspaceFile {
+    pub path: String,
+    pub chunks: i64,
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct WorkspaceHit {
+    pub path: String,
+    pub title: String,
+    pub content: String,
+    pub score: f32,
+}
+
+/// Evento `workspace-updated` após reindexar arquivos alterados
+#[derive(Serialize, Clone, Debug)]
+struct WorkspaceUpdatedEvent {
+    session_id: String,
+    files: Vec<String>,
+}
+
+/// Divide o arquivo em trechos de `CHUNK_LINES` linhas, com título `caminho:início-fim`
+pub fn chunk_file(path: &str, content: &str) -> Vec<(String, String)> {
+    let lines: Vec<&str> = content.lines().collect();
+    lines
+        .chunks(CHUNK_LINES)
+        .enumerate()
+        .filter(|(_, chunk)| chunk.iter().any(|line| !line.trim().is_empty()))
+        .map(|(index, chunk)| {
+            let start = index * CHUNK_LINES + 1;
+            let title = format!("{}:{}-{}", path, start, start + chunk.len() - 1);
+            (title, chunk.join("\n"))
+        })
+        .collect()
+}
+
+/// Conteúdo do arquivo se for texto UTF-8 dentro do limite de tamanho
+fn read_text(path: &Path) -> Option<String> {
+    if std::fs::metadata(path).ok()?.len() > MAX_FILE_BYTES {
+        return None;
+    }
+    let bytes = std::fs::read(path).ok()?;
+    if bytes.iter().take(8000).any(|b| *b == 0) {
+        return None;
+    }
+    String::from_utf8(bytes).ok()
+}
+
+/// Caminho relativo com `/` (igual em todas as plataformas)
+fn relative_path(root: &Path, path: &Path) -> Option<String> {
+    let relative = path.strip_prefix(root).ok()?;
+    Some(relative.components().map(|c| c.as_os_str().to_string_lossy()).collect::<Vec<_>>().join("/"))
+}
+
+/// Arquivos de regras lidos em cada pasta, do mais forte ao mais fraco (como no `WalkBuilder`)
+const IGNORE_FILES: [&str; 3] = [".ignore", ".gitignore", ".git/info/exclude"];
+
+/// Regras da indexação inicial (`walker`) para os arquivos vindos do watcher: `.ignore`,
+/// `.gitignore` e `.git/info/exclude` de cada pasta acima do arquivo e o excludes global do git
+struct IgnoreRules {
+    global: Gitignore,
+    /// Regras por pasta, na ordem de `IGNORE_FILES`
+    dirs: HashMap<PathBuf, [Gitignore; 3]>,
+}
+
+impl IgnoreRules {
+    fn new() -> Self {
+        Self { global: Gitignore::global().0, dirs: HashMap::new() }
+    }
+
+    /// Descarta as regras lidas (um arquivo de regras mudou)
+    fn reload(&mut self) {
+        self.dirs.clear();
+    }
+
+    fn dir(&mut self, dir: &Path) -> &[Gitignore; 3] {
+        self.dirs.entry(dir.to_path_buf()).or_insert_with(|| {
+            IGNORE_FILES.map(|file| {
+                let mut builder = GitignoreBuilder::new(dir);
+                builder.add(dir.join(file));
+                builder.build().unwrap_or_else(|_| Gitignore::empty())
+            })
+        })
+    }
+
+    /// Tipo de arquivo antes de profundidade: um `.ignore` vence qualquer `.gitignore`, e entre
+    /// arquivos do mesmo tipo vale a pasta mais próxima
+    fn is_ignored(&mut self, root: &Path, path: &Path) -> bool {
+        let dirs: Vec<PathBuf> = path.ancestors().skip(1).map(Path::to_path_buf).collect();
+        for kind in 0..IGNORE_FILES.len() {
+            for dir in &dirs {
+                let Ok(relative) = path.strip_prefix(dir) else { continue };
+                let matched = self.dir(dir)[kind].matched_path_or_any_parents(relative, false);
+                if matched.is_ignore() {
+                    return true;
+                }
+                if matched.is_whitelist() {
+                    return false;
+                }
+            }
+        }
+        path.strip_prefix(root)
+            .is_ok_and(|relative| self.global.matched_path_or_any_parents(relative, false).is_ignore())
+    }
+}
+
+/// Percurso da indexação inicial (mesmas regras de `IgnoreRules`)
+fn walker(root: &Path) -> ignore::Walk {
+    WalkBuilder::new(root)
+        .hidden(true)
+        .ignore(true)
+        .git_ignore(true)
+        .git_global(true)
+        .git_exclude(true)
+        .require_git(false)
+        .build()
+}
+
+/// Mesmo critério da indexação inicial para arquivos vindos do watcher
+fn should_index(root: &Path, rules: &mut IgnoreRules, path: &Path) -> bool {
+    let Ok(relative) = path.strip_prefix(root) else { return false };
+    let hidden = relative.components().any(|c| c.as_os_str().to_string_lossy().starts_with('.'));
+    !hidden && !rules.is_ignored(root, path)
+}
+
+/// Indexa todos os arquivos de texto da pasta, removendo do índice os que não existem mais
+pub fn index(db: &Database, session_id: &str, root: &Path) -> Result<IndexReport, String> {
+    let previous: HashSet<String> = db.list_workspace_files(session_id)
+        .map_err(|e| format!("Failed to load workspace files: {}", e))?
+        .into_iter()
+        .map(|(path, _)| path)
+        .collect();
+
+    let mut seen = HashSet::new();
+    let (mut files, mut chunks, mut skipped) = (0, 0, 0);
+    for entry in walker(root).filter_map(|e| e.ok()) {
+        if !entry.file_type().is_some_and(|t| t.is_file()) {
+            continue;
+        }
+        let Some(relative) = relative_path(root, entry.path()) else { continue };
+        if files >= MAX_FILES {
+            skipped += 1;
+            continue;
+        }
+        let Some(content) = read_text(entry.path()) else {
+            skipped += 1;
+            continue;
+        };
+        let file_chunks = chunk_file(&relative, &content);
+        db.replace_workspace_file(session_id, &relative, &file_chunks)
+            .map_err(|e| format!("Failed to index {}: {}", relative, e))?;
+        files += 1;
+        chunks += file_chunks.len();
+        seen.insert(relative);
+    }
+
+    for stale in previous.difference(&seen) {
+        db.replace_workspace_file(session_id, stale, &[])
+            .map_err(|e| format!("Failed to remove {}: {}", stale, e))?;
+    }
+    db.mark_workspace_indexed(session_id)
+        .map_err(|e| format!("Failed to update workspace: {}", e))?;
+
+    log::info!("[Workspace] {} indexado: {} arquivos, {} trechos, {} ignorados", root.display(), files, chunks, skipped);
+    Ok(IndexReport {
+        session_id: session_id.to_string(),
+        root_path: root.to_string_lossy().to_string(),
+        files,
+        chunks,
+        skipped,
+        embedding_job_id: None,
+    })
+}
+
+/// Reindexa os arquivos alterados (criados, editados ou removidos)
+fn reindex_paths(app_handle: &AppHandle, session_id: &str, root: &Path, rules: &mut IgnoreRules, paths: HashSet<PathBuf>) {
+    let db = match Database::new(app_handle) {
+        Ok(db) => db,
+        Err(e) => {
+            log::warn!("[Workspace] Falha ao abrir o banco para reindexar: {}", e);
+            return;
+        }
+    };
+    let mut indexed: HashSet<String> = match db.list_workspace_files(session_id) {
+        Ok(files) => files.into_iter().map(|(path, _)| path).collect(),
+        Err(e) => {
+            log::warn!("[Workspace] Falha ao carregar os arquivos do workspace: {}", e);
+            return;
+        }
+    };
+    let rules_changed = paths.iter().any(|path| {
+        path.file_name().is_some_and(|name| name == ".ignore" || name == ".gitignore" || name == "exclude")
+    });
+    if rules_changed {
+        rules.reload();
+    }
+    let mut updated = Vec::new();
+    for path in paths {
+        if path.is_dir() || !should_index(root, rules, &path) {
+            continue;
+        }
+        let Some(relative) = relative_path(root, &path) else { continue };
+        let chunks = read_text(&path).map(|content| chunk_file(&relative, &content)).unwrap_or_default();
+        // Arquivo novo acima do limite da indexação inicial
+        if !chunks.is_empty() && !indexed.contains(&relative) && indexed.len() >= MAX_FILES {
+            continue;
+        }
+        match db.replace_workspace_file(session_id, &relative, &chunks) {
+            Ok(()) => {
+                if chunks.is_empty() {
+                    indexed.remove(&relative);
+                } else {
+                    indexed.insert(relative.clone());
+                }
+                updated.push(relative);
+            }
+            Err(e) => log::warn!("[Workspace] Falha ao reindexar {}: {}", relative, e),
+        }
+    }
+    if !updated.is_empty() {
+        log::debug!("[Workspace] {} arquivo(s) reindexado(s) na sessão {}", updated.len(), session_id);
+        let _ = app_handle.emit("workspace-updated", WorkspaceUpdatedEvent {
+            session_id: session_id.to_string(),
+            files: updated,
+        });
+    }
+}
+
+/// Observa a pasta e reindexa os arquivos alterados (substitui o watcher anterior da sessão)
+pub fn watch(app_handle: &AppHandle, session_id: &str, root: PathBuf) -> Result<(), String> {
+    let (tx, rx) = mpsc::channel::<PathBuf>();
+    let mut watcher = notify::recommended_watcher(move |result: notify::Result<notify::Event>| {
+        if let Ok(event) = result {
+            for path in event.paths {
+                let _ = tx.send(path);
+            }
+        }
+    })
+    .map_err(|e| format!("Failed to create workspace watcher: {}", e))?;
+    watcher.watch(&root, RecursiveMode::Recursive)
+        .map_err(|e| format!("Failed to watch workspace: {}", e))?;
+
+    let app = app_handle.clone();
+    let session = session_id.to_string();
+    std::thread::Builder::new()
+        .name("workspace-watch".to_string())
+        .spawn(move || {
+            let mut rules = IgnoreRules::new();
+            // recv falha quando o watcher é solto (detach ou troca da pasta)
+            while let Ok(first) = rx.recv() {
+                let mut changed = HashSet::from([first]);
+                while let Ok(path) = rx.recv_timeout(WATCH_DEBOUNCE) {
+                    changed.insert(path);
+                }
+                reindex_paths(&app, &session, &root, &mut rules, changed);
+            }
+        })
+        .map_err(|e| format!("Failed to spawn workspace watcher: {}", e))?;
+
+    if let Ok(mut watchers) = WATCHERS.lock() {
+        watchers.retain(|(id, _)| id != session_id);
+        watchers.push((session_id.to_string(), watcher));
+    }
+    Ok(())
+}
+
+pub fn unwatch(session_id: &str) {
+    if let Ok(mut watchers) = WATCHERS.lock() {
+        watchers.retain(|(id, _)| id != session_id);
+    }
+}
+
+/// Volta a observar as pastas anexadas (chamado na inicialização)
+pub fn restore_watchers(app_handle: &AppHandle) {
+    let workspaces = match Database::new(app_handle).and_then(|db| db.list_workspaces()) {
+        Ok(workspaces) => workspaces,
+        Err(e) => {
+            log::warn!("[Workspace] Falha ao carregar workspaces: {}", e);
+            return;
+        }
+    };
+    for workspace in workspaces {
+        let root = PathBuf::from(&workspace.root_path);
+        if !root.is_dir() {
+            log::warn!("[Workspace] Pasta da sessão {} não existe mais: {}", workspace.session_id, workspace.root_path);
+            continue;
+        }
+        if let Err(e) = watch(app_handle, &workspace.session_id, root) {
+            log::warn!("[Workspace] {}", e);
+        }
+    }
+}
+
+/// Pontuação por palavras-chave (fallback sem modelo de embeddings)
+fn keyword_score(terms: &[String], content: &str) -> f32 {
+    if terms.is_empty() {
+        return 0.0;
+    }
+    let content = content.to_lowercase();
+    let matched = terms.iter().filter(|term| content.contains(term.as_str())).count();
+    matched as f32 / terms.len() as f32
+}
+
+/// Trechos mais relevantes do workspace para a consulta. Até `MAX_INLINE_EMBEDDINGS` trechos
+/// ainda sem embedding (alterados depois do job) são embedados aqui e gravados; os demais
+/// são pontuados por palavras-chave.
+pub fn search(app_handle: &AppHandle, session_id: &str, query: &str, limit: usize) -> Result<Vec<WorkspaceHit>, String> {
+    let db = Database::new(app_handle)
+        .map_err(|e| format!("Failed to open database: {}", e))?;
+    let chunks = db.get_workspace_chunks(session_id)
+        .map_err(|e| format!("Failed to load workspace chunks: {}", e))?;
+
+    let app_data_dir = app_handle.path().app_data_dir()
+        .map_err(|e| format!("Failed to get app data dir: {}", e))?;
+    let use_embeddings = resource_profile::current().embeddings_enabled && embeddings::is_model_available(&app_data_dir);
+
+    let terms: Vec<String> = query
+        .to_lowercase()
+        .split(|c: char| !c.is_alphanumeric() && c != '_')
+        .filter(|t| t.len() > 1)
+        .map(|t| t.to_string())
+        .collect();
+
+    let scores: Vec<f32> = if use_embeddings {
+        let model = embeddings::get_or_init_model(&app_data_dir).map_err(|e| e.to_string())?;
+        let mut model = model.lock().map_err(|e| format!("Failed to lock embedding model: {}", e))?;
+        let query_embedding = model.embed(query).map_err(|e| e.to_string())?;
+        let mut computed = Vec::new();
+        let scores = chunks
+            .iter()
+            .map(|chunk| {
+                let vector = match &chunk.embedding {
+                    Some(bytes) => paste::embedding_from_bytes(bytes),
+                    None if computed.len() < MAX_INLINE_EMBEDDINGS => match model.embed(&chunk.content) {
+                        Ok(vector) => {
+                            computed.push((chunk.id.clone(), paste::embedding_to_bytes(&vector)));
+                            vector
+                        }
+                        Err(_) => return keyword_score(&terms, &chunk.content),
+                    },
+                    None => return keyword_score(&terms, &chunk.content),
+                };
+                embeddings::cosine_similarity(&query_embedding, &vector)
+            })
+            .collect();
+        if !computed.is_empty() {
+            if let Err(e) = db.set_document_embeddings(&computed) {
+                log::warn!("[Workspace] Falha ao gravar embeddings: {}", e);
+            }
+        }
+        scores
+    } else {
+        chunks.iter().map(|chunk| keyword_score(&terms, &chunk.content)).collect()
+    };
+
+    let mut hits: Vec<WorkspaceHit> = chunks
+        .into_iter()
+        .zip(scores)
+        .filter(|(_, score)| *score > 0.0)
+        .map(|(chunk, score)| WorkspaceHit {
+            path: chunk.path,
+            title: chunk.title,
+            content: chunk.content,
+            score,
+        })
+        .collect();
+    hits.sort_by(|a, b| b.score.partial_cmp(&a.score).unwrap_or(std::cmp::Ordering::Equal));
+    hits.truncate(limit);
+    Ok(hits)
+}
+
+/// Lê um arquivo do workspace, recusando caminhos fora da pasta anexada
+pub fn read_file(root: &Path, relative: &str) -> Result<String, String> {
+    let root = root.canonicalize()
+        .map_err(|e| format!("Failed to resolve workspace: {}", e))?;
+    let path = root.join(relative).canonicalize()
+        .map_err(|e| format!("Failed to resolve file: {}", e))?;
+    if !path.starts_with(&root) {
+        return Err("Caminho fora do workspace".to_string());
+    }
+    read_text(&path).ok_or_else(|| "Arquivo binário ou grande demais".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_chunk_file_and_ignore_rules() {
+        let content: String = (1..=130).map(|i| format!("linha {}\n", i)).collect();
+        let chunks = chunk_file("src/main.rs", &content);
+        assert_eq!(chunks.len(), 3);
+        assert_eq!(chunks[0].0, "src/main.rs:1-60");
+        assert_eq!(chunks[2].0, "src/main.rs:121-130");
+        assert!(chunks[1].1.starts_with("linha 61\n"));
+        assert!(chunk_file("vazio.txt", "\n\n  \n").is_empty());
+
+        let root = std::env::temp_dir().join(format!("ollahub-workspace-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(root.join("src/gen")).unwrap();
+        std::fs::write(root.join(".gitignore"), "target/\n*.log\n").unwrap();
+        std::fs::write(root.join("src/.gitignore"), "gen/\n").unwrap();
+        std::fs::write(root.join("src/.ignore"), "!keep.log\n").unwrap();
+        let mut rules = IgnoreRules::new();
+        assert!(should_index(&root, &mut rules, &root.join("src/lib.rs")));
+        assert!(!should_index(&root, &mut rules, &root.join("target/debug/app.d")));
+        assert!(!should_index(&root, &mut rules, &root.join("src/gen/schema.rs")), ".gitignore aninhado");
+        assert!(!should_index(&root, &mut rules, &root.join("debug.log")));
+        assert!(should_index(&root, &mut rules, &root.join("src/keep.log")), ".ignore vence .gitignore");
+        assert!(!should_index(&root, &mut rules, &root.join(".git/HEAD")));
+        assert!(!should_index(&root, &mut rules, Path::new("/outro/lib.rs")));
+        let _ = std::fs::remove_dir_all(&root);
+    }
+}
@@ -0,0 +1,177 @@
+use crate::db::{ConversationTemplate, PromptPreset};
+use crate::settings::AppSettings;
+use crate::sources_config::SourcesConfig;
+use crate::McpServerConfig;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Versão do formato do bundle (incrementar em mudanças incompatíveis)
+pub const BUNDLE_FORMAT_VERSION: u32 = 1;
+/// Valor gravado no lugar de variáveis de ambiente sensíveis dos servidores MCP
+pub const REDACTED_SECRET: &str = "<redacted>";
+
+/// Trechos de nomes de variáveis de ambiente tratados como segredo
+const SECRET_ENV_MARKERS: &[&str] = &["TOKEN", "SECRET", "KEY", "PASSWORD", "PASSWD", "PASS", "AUTH", "CREDENTIAL", "COOKIE"];
+
+/// Configuração do workspace em um único arquivo portátil
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct WorkspaceBundle {
+    pub format_version: u32,
+    pub exported_at: DateTime<Utc>,
+    #[serde(default)]
+    pub mcp_servers: HashMap<String, McpServerConfig>,
+    #[serde(default)]
+    pub sources: Option<SourcesConfig>,
+    #[serde(default)]
+    pub prompt_presets: Vec<PromptPreset>,
+    #[serde(default)]
+    pub conversation_templates: Vec<ConversationTemplate>,
+    #[serde(default)]
+    pub settings: Option<AppSettings>,
+    /// Segredos removidos na exportação (`servidor.VARIAVEL`)
+    #[serde(default)]
+    pub redacted_secrets: Vec<String>,
+}
+
+/// Resultado da importação exibido ao usuário
+#[derive(Serialize, Clone, Debug, Default)]
+pub struct WorkspaceImportSummary {
+    pub mcp_servers: usize,
+    pub sources_imported: bool,
+    pub prompt_presets: usize,
+    pub conversation_templates: usize,
+    pub settings_applied: bool,
+    /// Segredos que não vieram no bundle e não existiam nesta máquina (precisam ser preenchidos)
+    pub missing_secrets: Vec<String>,
+}
+
+pub fn is_secret_env(name: &str) -> bool {
+    let upper = name.to_uppercase();
+    SECRET_ENV_MARKERS.iter().any(|marker| upper.contains(marker))
+}
+
+/// Substitui variáveis sensíveis por `REDACTED_SECRET` e devolve a lista do que foi removido
+pub fn strip_secrets(servers: &mut HashMap<String, McpServerConfig>) -> Vec<String> {
+    let mut redacted = Vec::new();
+    for (server, config) in servers.iter_mut() {
+        if let Some(env) = config.env.as_mut() {
+            for (name, value) in env.iter_mut() {
+                if is_secret_env(name) && !value.is_empty() {
+                    *value = REDACTED_SECRET.to_string();
+                    redacted.push(format!("{}.{}", server, name));
+                }
+            }
+        }
+    }
+    redacted.sort();
+    redacted
+}
+
+/// Mescla os servidores do bundle na configuração local. Segredos removidos são
+/// preenchidos com o valor local do mesmo servidor; os que faltarem são retornados.
+pub fn merge_mcp_servers(
+    local: &mut HashMap<String, McpServerConfig>,
+    imported: HashMap<String, McpServerConfig>,
+) -> Vec<String> {
+    let mut missing = Vec::new();
+
+    for (server, mut config) in imported {
+        let local_env = local.get(&server).and_then(|c| c.env.clone()).unwrap_or_default();
+        if let Some(env) = config.env.as_mut() {
+            env.retain(|name, value| {
+                if value != REDACTED_SECRET {
+                    return true;
+                }
+                match local_env.get(name) {
+                    Some(local_value) if local_value != REDACTED_SECRET => {
+                        *value = local_value.clone();
+                        true
+                    }
+                    _ => {
+                        missing.push(format!("{}.{}", server, name));
+                        false
+                    }
+                }
+            });
+        }
+        local.insert(server, config);
+    }
+
+    missing.sort();
+    missing
+}
+
+/// Remove as chaves de API dos motores de busca das fontes exportadas
+pub fn strip_source_secrets(sources: &mut SourcesConfig) -> Vec<String> {
+    let mut redacted = Vec::new();
+    if let Some(key) = sources.search_apis.brave_api_key.as_mut().filter(|key| !key.is_empty()) {
+        *key = REDACTED_SECRET.to_string();
+        redacted.push("sources.brave_api_key".to_string());
+    }
+    for engine in &mut sources.search_apis.engines {
+        if let Some(key) = engine.api_key.as_mut().filter(|key| !key.is_empty()) {
+            *key = REDACTED_SECRET.to_string();
+            redacted.push(format!("sources.engines.{}.api_key", engine.name));
+        }
+    }
+    redacted
+}
+
+/// Mantém as chaves locais quando o bundle veio sem elas; retorna o que ficou faltando
+pub fn restore_source_secrets(imported: &mut SourcesConfig, local: &SourcesConfig) -> Vec<String> {
+    let mut missing = Vec::new();
+    if imported.search_apis.brave_api_key.as_deref() == Some(REDACTED_SECRET) {
+        imported.search_apis.brave_api_key = local
+            .search_apis
+            .brave_api_key
+            .clone()
+            .filter(|key| key != REDACTED_SECRET);
+        if imported.search_apis.brave_api_key.is_none() {
+            missing.push("sources.brave_api_key".to_string());
+        }
+    }
+    for engine in &mut imported.search_apis.engines {
+        if engine.api_key.as_deref() != Some(REDACTED_SECRET) {
+            continue;
+        }
+        engine.api_key = local
+            .search_apis
+            .engine(&engine.name)
+            .and_then(|e| e.api_key.clone())
+            .filter(|key| key != REDACTED_SECRET);
+        if engine.api_key.is_none() {
+            missing.push(format!("sources.engines.{}.api_key", engine.name));
+        }
+    }
+    missing
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn server(env: &[(&str, &str)]) -> McpServerConfig {
+        McpServerConfig {
+            command: "npx".to_string(),
+            args: vec!["server".to_string()],
+            env: Some(env.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect()),
+        }
+    }
+
+    #[test]
+    fn test_strip_and_merge_secrets() {
+        let mut exported = HashMap::from([
+            ("github".to_string(), server(&[("GITHUB_TOKEN", "ghp_x"), ("LOG_LEVEL", "info")])),
+            ("db".to_string(), server(&[("DB_PASSWORD", "hunter2")])),
+        ]);
+        assert_eq!(strip_secrets(&mut exported), vec!["db.DB_PASSWORD", "github.GITHUB_TOKEN"]);
+        assert_eq!(exported["github"].env.as_ref().unwrap()["LOG_LEVEL"], "info");
+
+        let mut local = HashMap::from([("github".to_string(), server(&[("GITHUB_TOKEN", "ghp_local")]))]);
+        let missing = merge_mcp_servers(&mut local, exported);
+        assert_eq!(missing, vec!["db.DB_PASSWORD"]);
+        assert_eq!(local["github"].env.as_ref().unwrap()["GITHUB_TOKEN"], "ghp_local");
+        assert!(!local["db"].env.as_ref().unwrap().contains_key("DB_PASSWORD"));
+    }
+}
@@ -0,0 +1,247 @@
+//! Transcrições do YouTube como fonte de conteúdo
+//!
+//! A página de um vídeo quase não tem texto útil; para links do YouTube o scraper busca a
+//! legenda (API timedtext, pela lista `captionTracks` da página) e devolve a transcrição
+//! em Markdown com as marcações de tempo linkando para o trecho do vídeo.
+
+use crate::web_scraper::ScrapedContent;
+use regex::Regex;
+use reqwest::header::{ACCEPT_LANGUAGE, COOKIE, USER_AGENT};
+use serde::Deserialize;
+use std::time::Duration;
+
+const FETCH_TIMEOUT: Duration = Duration::from_secs(15);
+/// Trechos de legenda agrupados em parágrafos de até este intervalo
+const PARAGRAPH_SECS: f64 = 30.0;
+const BROWSER_USER_AGENT: &str =
+    "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/124.0 Safari/537.36";
+
+/// Faixa de legenda listada na página do vídeo
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+struct CaptionTrack {
+    base_url: String,
+    #[serde(default)]
+    language_code: String,
+    /// "asr" para legendas geradas automaticamente
+    #[serde(default)]
+    kind: Option<String>,
+}
+
+/// Trecho da legenda (início em segundos)
+#[derive(Debug, Clone, PartialEq)]
+struct Cue {
+    start: f64,
+    text: String,
+}
+
+/// ID do vídeo para links `watch?v=`, `youtu.be/`, `shorts/`, `embed/` e `live/`
+pub fn video_id(url: &str) -> Option<String> {
+    let parsed = url::Url::parse(url).ok()?;
+    let host = parsed.host_str()?.trim_start_matches("www.").trim_start_matches("m.");
+    let candidate = match host {
+        "youtu.be" => parsed.path_segments()?.next()?.to_string(),
+        "youtube.com" | "music.youtube.com" | "youtube-nocookie.com" => {
+            let mut segments = parsed.path_segments()?;
+            match segments.next()? {
+                "watch" => parsed.query_pairs().find(|(k, _)| k == "v")?.1.to_string(),
+                "shorts" | "embed" | "live" | "v" => segments.next()?.to_string(),
+                _ => return None,
+            }
+        }
+        _ => return None,
+    };
+    let valid = candidate.len() == 11
+        && candidate.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+    valid.then_some(candidate)
+}
+
+/// Lista `"captionTracks":[...]` do JSON embutido na página
+fn caption_tracks(html: &str) -> Vec<CaptionTrack> {
+    let Some(start) = html.find("\"captionTracks\":").map(|i| i + "\"captionTracks\":".len()) else {
+        return Vec::new();
+    };
+    // Fecha o array respeitando strings (as URLs têm escapes)
+    let bytes = html[start..].as_bytes();
+    let (mut depth, mut in_string, mut escaped) = (0usize, false, false);
+    let mut end = None;
+    for (i, byte) in bytes.iter().enumerate() {
+        if in_string {
+            match byte {
+                _ if escaped => escaped = false,
+                b'\\' => escaped = true,
+                b'"' => in_string = false,
+                _ => {}
+            }
+            continue;
+        }
+        match byte {
+            b'"' => in_string = true,
+            b'[' | b'{' => depth += 1,
+            b']' | b'}' => {
+                depth = depth.saturating_sub(1);
+                if depth == 0 {
+                    end = Some(i + 1);
+                    break;
+                }
+            }
+            _ => {}
+        }
+    }
+    end.and_then(|end| serde_json::from_str(&html[start..start + end]).ok())
+        .unwrap_or_default()
+}
+
+/// Legenda manual primeiro; entre as automáticas, a primeira listada (idioma original)
+fn pick_track(tracks: &[CaptionTrack]) -> Option<&CaptionTrack> {
+    tracks
+        .iter()
+        .find(|t| t.kind.as_deref() != Some("asr"))
+        .or_else(|| tracks.first())
+}
+
+/// Trechos do XML da timedtext (formato clássico `<text start>` ou srv3 `<p t>` em ms)
+fn parse_cues(xml: &str) -> Vec<Cue> {
+    let classic = Regex::new(r#"(?s)<text start="([\d.]+)"[^>]*>(.*?)</text>"#).expect("valid cue regex");
+    let srv3 = Regex::new(r#"(?s)<p t="(\d+)"[^>]*>(.*?)</p>"#).expect("valid cue regex");
+
+    let classic_cues = classic
+        .captures_iter(xml)
+        .filter_map(|caps| Some((caps[1].parse::<f64>().ok()?, caps[2].to_string())));
+    let srv3_cues = srv3
+        .captures_iter(xml)
+        .filter_map(|caps| Some((caps[1].parse::<f64>().ok()? / 1000.0, caps[2].to_string())));
+
+    let mut cues: Vec<Cue> = classic_cues
+        .chain(srv3_cues)
+        .map(|(start, raw)| Cue {
+            start,
+            // Entidades vêm escapadas duas vezes (`&amp;#39;`)
+            text: crate::web_scraper::strip_html_tags(&crate::web_scraper::html_escape_decode(&raw))
+                .split_whitespace()
+                .collect::<Vec<_>>()
+                .join(" "),
+        })
+        .filter(|cue| !cue.text.is_empty())
+        .collect();
+    cues.sort_by(|a, b| a.start.partial_cmp(&b.start).unwrap_or(std::cmp::Ordering::Equal));
+    cues
+}
+
+fn timestamp(secs: f64) -> String {
+    let total = secs as u64;
+    let (h, m, s) = (total / 3600, total % 3600 / 60, total % 60);
+    if h > 0 { format!("{}:{:02}:{:02}", h, m, s) } else { format!("{:02}:{:02}", m, s) }
+}
+
+/// Markdown com um parágrafo a cada `PARAGRAPH_SECS`, cada um com o link do seu tempo
+fn transcript_markdown(video_id: &str, title: &str, cues: &[Cue]) -> String {
+    let mut markdown = format!("# {}\n\nTranscrição do vídeo https://www.youtube.com/watch?v={}\n", title, video_id);
+    let mut paragraph_start: Option<f64> = None;
+    for cue in cues {
+        if paragraph_start.map_or(true, |start| cue.start - start >= PARAGRAPH_SECS) {
+            paragraph_start = Some(cue.start);
+            markdown.push_str(&format!(
+                "\n\n[{}](https://www.youtube.com/watch?v={}&t={}s) ",
+                timestamp(cue.start),
+                video_id,
+                cue.start as u64
+            ));
+        } else {
+            markdown.push(' ');
+        }
+        markdown.push_str(&cue.text);
+    }
+    markdown.push('\n');
+    markdown
+}
+
+fn video_title(html: &str) -> Option<String> {
+    let document = scraper::Html::parse_document(html);
+    let selector = scraper::Selector::parse("meta[property='og:title']").ok()?;
+    let title = document.select(&selector).next()?.value().attr("content")?.trim();
+    (!title.is_empty()).then(|| title.to_string())
+}
+
+async fn fetch_text(client: &reqwest::Client, url: &str) -> Result<String, String> {
+    let response = client
+        .get(url)
+        .header(USER_AGENT, BROWSER_USER_AGENT)
+        .header(ACCEPT_LANGUAGE, "pt-BR,pt;q=0.9,en;q=0.8")
+        // Evita a página de consentimento de cookies na Europa
+        .header(COOKIE, "CONSENT=YES+1")
+        .send()
+        .await
+        .map_err(|e| format!("Failed to fetch {}: {}", url, e))?;
+    if !response.status().is_success() {
+        return Err(format!("Failed to fetch {}: HTTP {}", url, response.status()));
+    }
+    response.text().await.map_err(|e| format!("Failed to read {}: {}", url, e))
+}
+
+/// Transcrição do vídeo como `ScrapedContent` (erro se o vídeo não tiver legendas)
+pub async fn fetch_transcript(url: &str) -> Result<ScrapedContent, String> {
+    let id = video_id(url).ok_or_else(|| format!("Não é um link de vídeo do YouTube: {}", url))?;
+    let client = crate::web_scraper::client_builder(None)
+        .timeout(FETCH_TIMEOUT)
+        .build()
+        .map_err(|e| format!("Failed to create HTTP client: {}", e))?;
+
+    let watch_url = format!("https://www.youtube.com/watch?v={}", id);
+    let page = fetch_text(&client, &watch_url).await?;
+    let tracks = caption_tracks(&page);
+    let track = pick_track(&tracks).ok_or_else(|| format!("Vídeo sem legendas: {}", watch_url))?;
+
+    let xml = fetch_text(&client, &track.base_url).await?;
+    let cues = parse_cues(&xml);
+    if cues.is_empty() {
+        return Err(format!("Legenda vazia: {}", watch_url));
+    }
+
+    let title = video_title(&page).unwrap_or_else(|| format!("Vídeo do YouTube {}", id));
+    let content = cues.iter().map(|cue| cue.text.as_str()).collect::<Vec<_>>().join(" ");
+    log::info!(
+        "[YouTube] Transcrição de {} ({}, {} trechos{})",
+        id,
+        track.language_code,
+        cues.len(),
+        if track.kind.as_deref() == Some("asr") { ", automática" } else { "" }
+    );
+
+    Ok(ScrapedContent {
+        markdown: transcript_markdown(&id, &title, &cues),
+        title,
+        url: watch_url,
+        content,
+        original_language: (!track.language_code.is_empty()).then(|| track.language_code.clone()),
+        published_at: crate::web_scraper::extract_published_date(&page),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_video_id_and_transcript() {
+        assert_eq!(video_id("https://www.youtube.com/watch?v=dQw4w9WgXcQ&t=42").as_deref(), Some("dQw4w9WgXcQ"));
+        assert_eq!(video_id("https://youtu.be/dQw4w9WgXcQ?si=x").as_deref(), Some("dQw4w9WgXcQ"));
+        assert_eq!(video_id("https://m.youtube.com/shorts/dQw4w9WgXcQ").as_deref(), Some("dQw4w9WgXcQ"));
+        assert_eq!(video_id("https://www.youtube.com/@canal"), None);
+        assert_eq!(video_id("https://example.org/watch?v=dQw4w9WgXcQ"), None);
+
+        let page = r#"var x = {"captions":{"playerCaptionsTracklistRenderer":{"captionTracks":[{"baseUrl":"https://www.youtube.com/api/timedtext?v=1&lang=en","languageCode":"en","kind":"asr"},{"baseUrl":"https://www.youtube.com/api/timedtext?v=1&lang=pt","languageCode":"pt"}],"audioTracks":[]}}};"#;
+        let tracks = caption_tracks(page);
+        assert_eq!(tracks.len(), 2);
+        assert_eq!(pick_track(&tracks).unwrap().language_code, "pt");
+        assert!(tracks[0].base_url.ends_with("v=1&lang=en"));
+
+        let xml = r#"<?xml version="1.0"?><transcript><text start="0.5" dur="2">Olá &amp;amp; bem-vindos</text><text start="12" dur="3">it&amp;#39;s   ok</text><text start="75.2" dur="1">fim</text></transcript>"#;
+        let cues = parse_cues(xml);
+        assert_eq!(cues[0].text, "Olá & bem-vindos");
+        assert_eq!(cues[1].text, "it's ok");
+        let markdown = transcript_markdown("abc", "Título", &cues);
+        assert!(markdown.contains("[00:00](https://www.youtube.com/watch?v=abc&t=0s) Olá & bem-vindos it's ok"));
+        assert!(markdown.contains("\n\n[01:15](https://www.youtube.com/watch?v=abc&t=75s) fim"));
+    }
+}
@@ -7,6 +7,8 @@ export interface SearchCategory {
   name: string;
   baseSites: string[];
   enabled: boolean;
+  /** Peso na divisão do limite de fontes (padrão 1) */
+  weight?: number;
 }
 
 export interface SettingsState {